@@ -0,0 +1,19 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use forensic_webhistory::carver;
+
+mod gen;
+
+fn bench_raw_scan(c: &mut Criterion) {
+    let data = gen::carve_buffer(100 * 1024 * 1024);
+
+    let mut group = c.benchmark_group("carver");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("raw_url_scan_100mb", |b| {
+        b.iter(|| carver::carve_raw_buffer(&data, "bench", &carver::CarveConfig::default()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_raw_scan);
+criterion_main!(benches);
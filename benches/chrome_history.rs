@@ -0,0 +1,20 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use forensic_webhistory::browsers::{self, BrowserType};
+
+mod gen;
+
+fn bench_chrome_history(c: &mut Criterion) {
+    let dir = tempfile::TempDir::new().unwrap();
+    let db = dir.path().join("History");
+    gen::chrome_history(&db, 20_000, 200_000);
+
+    let mut group = c.benchmark_group("chrome_history");
+    group.sample_size(10);
+    group.bench_function("extract_200k_visits", |b| {
+        b.iter(|| browsers::chrome::extract(&db, "alice", Some(BrowserType::Chrome)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_chrome_history);
+criterion_main!(benches);
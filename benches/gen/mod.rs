@@ -0,0 +1,136 @@
+//! Fixture generators shared by the benches. Output is deterministic (fixed-seed LCG)
+//! so runs are comparable.
+
+// Each bench target includes this module but uses only part of it
+#![allow(dead_code)]
+
+use chrono::{Duration, TimeZone, Utc};
+use forensic_webhistory::browsers::HistoryEntry;
+use std::path::Path;
+
+/// Minimal linear congruential generator; good enough to vary sizes and offsets.
+pub struct Lcg(u64);
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+}
+
+fn url(i: usize) -> String {
+    format!(
+        "https://site{}.example.com/path/{}?q={}",
+        i % 997,
+        i,
+        i * 31
+    )
+}
+
+/// Build a Chrome `History` database at `path` with `urls` URLs and `visits` visits,
+/// a tenth of them chained to the previous visit through `from_visit`.
+pub fn chrome_history(path: &Path, urls: usize, visits: usize) {
+    let mut conn = rusqlite::Connection::open(path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                            visit_count INTEGER, typed_count INTEGER, last_visit_time INTEGER);
+         CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                              from_visit INTEGER, transition INTEGER, visit_duration INTEGER);",
+    )
+    .unwrap();
+    let mut rng = Lcg::new(1);
+    let tx = conn.transaction().unwrap();
+    {
+        let mut insert_url = tx
+            .prepare("INSERT INTO urls VALUES (?1, ?2, ?3, 1, 0, 13245010621000000)")
+            .unwrap();
+        for i in 1..=urls {
+            insert_url
+                .execute(rusqlite::params![i as i64, url(i), format!("Page {i}")])
+                .unwrap();
+        }
+        let mut insert_visit = tx
+            .prepare("INSERT INTO visits VALUES (?1, ?2, ?3, ?4, ?5, ?6)")
+            .unwrap();
+        for i in 1..=visits {
+            let from = if i > 1 && rng.next().is_multiple_of(10) {
+                i - 1
+            } else {
+                0
+            };
+            let transition = if from == 0 { 1 } else { 0 };
+            insert_visit
+                .execute(rusqlite::params![
+                    i as i64,
+                    (rng.next() as usize % urls + 1) as i64,
+                    13245010621000000i64 + i as i64 * 1_000_000,
+                    from as i64,
+                    transition,
+                    (rng.next() % 60_000_000) as i64,
+                ])
+                .unwrap();
+        }
+    }
+    tx.commit().unwrap();
+}
+
+/// `len` bytes of noise with a URL (some followed by a title) every few KiB, roughly
+/// like the unallocated space of a history database.
+pub fn carve_buffer(len: usize) -> Vec<u8> {
+    let mut rng = Lcg::new(2);
+    let mut data = Vec::with_capacity(len + 256);
+    let mut i = 0;
+    while data.len() < len {
+        let noise = 1024 + rng.next() as usize % 4096;
+        data.extend((0..noise).map(|_| rng.next() as u8));
+        data.extend_from_slice(url(i).as_bytes());
+        if i.is_multiple_of(2) {
+            data.extend_from_slice(format!("Example page title {i}").as_bytes());
+        }
+        data.push(0);
+        i += 1;
+    }
+    data.truncate(len);
+    data
+}
+
+/// `n` history entries with varied URLs, titles and timestamps.
+pub fn history_entries(n: usize) -> Vec<HistoryEntry> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap();
+    (0..n)
+        .map(|i| {
+            let url = url(i);
+            HistoryEntry {
+                url_length: url.len(),
+                url,
+                title: format!("Page {i}"),
+                visit_time: start + Duration::seconds(i as i64),
+                visit_count: 1 + (i % 7) as u32,
+                visited_from: String::new(),
+                app_id: String::new(),
+                journey: String::new(),
+                canonical_url: String::new(),
+                visit_type: "Link".to_string(),
+                visit_duration: "00:00:05".to_string(),
+                view_time_ms: None,
+                key_presses: None,
+                hidden: false,
+                visited_link_confirmed: false,
+                web_browser: "Chrome".to_string(),
+                user_profile: "alice".to_string(),
+                browser_profile: "Default".to_string(),
+                account: String::new(),
+                typed_count: 0,
+                history_file: "History".to_string(),
+                record_id: i as i64,
+            }
+        })
+        .collect()
+}
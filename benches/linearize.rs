@@ -0,0 +1,18 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use forensic_webhistory::browsers::linearize_entry;
+
+mod gen;
+
+fn bench_linearize(c: &mut Criterion) {
+    let entries = gen::history_entries(100_000);
+
+    let mut group = c.benchmark_group("linearize");
+    group.throughput(Throughput::Elements(entries.len() as u64));
+    group.bench_function("history_100k", |b| {
+        b.iter(|| entries.iter().map(linearize_entry).collect::<Vec<_>>())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_linearize);
+criterion_main!(benches);
@@ -0,0 +1,296 @@
+//! Per-host rollup of a history export (`webx aggregate`).
+//!
+//! Works from an already-written history CSV, so a large case can be summarized by host
+//! or registrable domain without reopening the browser databases.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::canonical::url_host;
+use crate::merge::TimeParser;
+use crate::output;
+
+/// What history rows are grouped by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// The URL's host name (`mail.example.co.uk`)
+    Host,
+    /// The registrable domain, eTLD+1 from the Public Suffix List (`example.co.uk`)
+    Etld1,
+}
+
+impl GroupBy {
+    fn header(self) -> &'static str {
+        match self {
+            GroupBy::Host => "Host",
+            GroupBy::Etld1 => "Domain",
+        }
+    }
+
+    fn key(self, host: String) -> String {
+        match self {
+            GroupBy::Host => host,
+            GroupBy::Etld1 => registrable_domain(host),
+        }
+    }
+}
+
+/// Registrable domain of a lowercased host: eTLD+1 from the Public Suffix List
+/// (`mail.example.co.uk` -> `example.co.uk`). IP addresses, `localhost` and unlisted
+/// suffixes stay as the host itself.
+pub fn registrable_domain(host: String) -> String {
+    psl::domain_str(&host).map(str::to_string).unwrap_or(host)
+}
+
+/// Columns written by `webx aggregate`, after the Host/Domain column.
+pub const AGGREGATE_HEADERS: &[&str] = &[
+    "Visits",
+    "Distinct URLs",
+    "First Seen",
+    "Last Seen",
+    "Typed Count",
+    "Most Common Title",
+];
+
+/// Counts reported after an aggregation.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AggregateStats {
+    pub rows_read: usize,
+    /// Rows whose URL has no host (`about:`, `file:`, bare terms)
+    pub rows_without_host: usize,
+    pub groups: usize,
+}
+
+#[derive(Default)]
+struct Group {
+    visits: usize,
+    /// Typed count per URL; the export repeats the URL-level count on every visit row
+    urls: HashMap<String, u32>,
+    first: Option<(DateTime<Utc>, String)>,
+    last: Option<(DateTime<Utc>, String)>,
+    titles: HashMap<String, usize>,
+}
+
+impl Group {
+    fn add(&mut self, url: &str, time: Option<(DateTime<Utc>, &str)>, title: &str, typed: u32) {
+        self.visits += 1;
+        let typed_max = self.urls.entry(url.to_string()).or_default();
+        *typed_max = (*typed_max).max(typed);
+        if let Some((t, raw)) = time {
+            if self.first.as_ref().is_none_or(|(f, _)| t < *f) {
+                self.first = Some((t, raw.to_string()));
+            }
+            if self.last.as_ref().is_none_or(|(l, _)| t > *l) {
+                self.last = Some((t, raw.to_string()));
+            }
+        }
+        if !title.is_empty() {
+            *self.titles.entry(title.to_string()).or_default() += 1;
+        }
+    }
+
+    /// Most frequent title; ties go to the alphabetically first so output is stable.
+    fn top_title(&self) -> &str {
+        self.titles
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+            .map_or("", |(t, _)| t.as_str())
+    }
+}
+
+/// Summarize the history CSV at `input` into one row per host (or registrable domain)
+/// at `output_path`: total visits, distinct URLs, first/last visit, typed count summed
+/// over the distinct URLs, and the most common title. Rows are ordered by visits,
+/// most first.
+///
+/// Columns are located by their history header names, so exports written with
+/// `--columns` work as long as they kept `URL` and `Visit Time`. Times are parsed with
+/// `date_fmt` (the format the export was written with) and written back unchanged.
+pub fn aggregate_history(
+    input: &Path,
+    by: GroupBy,
+    output_path: &Path,
+    date_fmt: &str,
+) -> Result<AggregateStats> {
+    let mut reader = csv::Reader::from_path(input)
+        .with_context(|| format!("Failed to open CSV: {}", input.display()))?;
+    let header = reader
+        .headers()
+        .with_context(|| format!("Failed to read CSV header: {}", input.display()))?
+        .clone();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let (url_idx, time_idx) = match (column("URL"), column("Visit Time")) {
+        (Some(u), Some(t)) => (u, t),
+        _ => anyhow::bail!(
+            "{} is not a history export (needs \"URL\" and \"Visit Time\" columns)",
+            input.display()
+        ),
+    };
+    let title_idx = column("Title");
+    let typed_idx = column("Typed Count");
+
+    let mut times = TimeParser::new(date_fmt, None);
+    let mut stats = AggregateStats::default();
+    let mut groups: HashMap<String, Group> = HashMap::new();
+    for record in reader.records() {
+        let record =
+            record.with_context(|| format!("Failed to read CSV row: {}", input.display()))?;
+        stats.rows_read += 1;
+        let url = &record[url_idx];
+        let host = match url_host(url) {
+            Some(h) => h,
+            None => {
+                stats.rows_without_host += 1;
+                continue;
+            }
+        };
+        let key = by.key(host);
+        let raw_time = &record[time_idx];
+        let time = times.parse(raw_time).map(|t| (t, raw_time));
+        let title = title_idx.map_or("", |i| &record[i]);
+        let typed = typed_idx.and_then(|i| record[i].parse().ok()).unwrap_or(0);
+        groups.entry(key).or_default().add(url, time, title, typed);
+    }
+
+    let mut rows: Vec<(&String, &Group)> = groups.iter().collect();
+    rows.sort_by(|a, b| b.1.visits.cmp(&a.1.visits).then_with(|| a.0.cmp(b.0)));
+
+    output::ensure_parent(output_path)?;
+    let mut wtr = csv::Writer::from_path(output_path)
+        .with_context(|| format!("Failed to create output file: {}", output_path.display()))?;
+    wtr.write_record(std::iter::once(by.header()).chain(AGGREGATE_HEADERS.iter().copied()))?;
+    for (key, g) in &rows {
+        let typed: u32 = g.urls.values().sum();
+        wtr.write_record([
+            key.as_str(),
+            &g.visits.to_string(),
+            &g.urls.len().to_string(),
+            g.first.as_ref().map_or("", |(_, raw)| raw.as_str()),
+            g.last.as_ref().map_or("", |(_, raw)| raw.as_str()),
+            &typed.to_string(),
+            g.top_title(),
+        ])?;
+    }
+    wtr.flush()?;
+    stats.groups = rows.len();
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::browsers::ArtifactType;
+
+    const FMT: &str = "%Y-%m-%d %H:%M";
+
+    fn write_export(path: &Path, rows: &[(&str, &str, &str, &str)]) {
+        let (headers, _) = output::artifact_columns(ArtifactType::History);
+        let col = |name: &str| headers.iter().position(|h| *h == name).unwrap();
+        let mut wtr = csv::Writer::from_path(path).unwrap();
+        wtr.write_record(headers).unwrap();
+        for (time, url, title, typed) in rows {
+            let mut record = vec![""; headers.len()];
+            record[col("Visit Time")] = time;
+            record[col("URL")] = url;
+            record[col("Title")] = title;
+            record[col("Typed Count")] = typed;
+            wtr.write_record(&record).unwrap();
+        }
+        wtr.flush().unwrap();
+    }
+
+    fn read_rows(path: &Path) -> Vec<Vec<String>> {
+        let mut rdr = csv::Reader::from_path(path).unwrap();
+        rdr.records()
+            .map(|r| r.unwrap().iter().map(str::to_string).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_aggregate_by_host_and_domain() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("history.csv");
+        write_export(
+            &input,
+            &[
+                (
+                    "2024-01-15 09:00",
+                    "https://mail.example.co.uk/inbox",
+                    "Inbox",
+                    "2",
+                ),
+                (
+                    "2024-01-15 08:00",
+                    "https://mail.example.co.uk/inbox",
+                    "Inbox",
+                    "2",
+                ),
+                (
+                    "2024-01-16 10:30",
+                    "https://MAIL.example.co.uk/sent",
+                    "Sent",
+                    "0",
+                ),
+                (
+                    "2024-01-14 07:00",
+                    "https://www.example.co.uk/",
+                    "Home",
+                    "1",
+                ),
+                ("2024-01-15 12:00", "https://other.test:8080/x", "", "0"),
+                ("2024-01-15 12:00", "about:blank", "", "0"),
+            ],
+        );
+
+        let by_host = dir.path().join("hosts.csv");
+        let stats = aggregate_history(&input, GroupBy::Host, &by_host, FMT).unwrap();
+        assert_eq!(
+            stats,
+            AggregateStats {
+                rows_read: 6,
+                rows_without_host: 1,
+                groups: 3,
+            }
+        );
+        let rows = read_rows(&by_host);
+        assert_eq!(
+            rows[0],
+            [
+                "mail.example.co.uk",
+                "3",
+                "2",
+                "2024-01-15 08:00",
+                "2024-01-16 10:30",
+                "2",
+                "Inbox",
+            ]
+        );
+        assert_eq!(rows[1][0], "other.test");
+        assert_eq!(rows[2][0], "www.example.co.uk");
+
+        let by_domain = dir.path().join("domains.csv");
+        aggregate_history(&input, GroupBy::Etld1, &by_domain, FMT).unwrap();
+        let mut rdr = csv::Reader::from_path(&by_domain).unwrap();
+        assert_eq!(rdr.headers().unwrap().get(0), Some("Domain"));
+        let rows = read_rows(&by_domain);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[0][..6],
+            [
+                "example.co.uk",
+                "4",
+                "3",
+                "2024-01-14 07:00",
+                "2024-01-16 10:30",
+                "3",
+            ]
+        );
+
+        // Another artifact's export is rejected
+        let cookies = dir.path().join("cookies.csv");
+        std::fs::write(&cookies, "Creation Time,Host\n1,2\n").unwrap();
+        assert!(aggregate_history(&cookies, GroupBy::Host, &by_host, FMT).is_err());
+    }
+}
@@ -0,0 +1,318 @@
+//! Redaction of user-identifying fields for shareable exports (`--anonymize`).
+//!
+//! Values are replaced with salted SHA-256 hashes. The same salt is used for the whole
+//! run, so a value always maps to the same hash and joins across artifacts still work,
+//! while URLs and timestamps are left untouched. The user directory in source file and
+//! download paths gets the same hash as the user profile, so paths keep their shape
+//! without naming the user.
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+use crate::browsers::{
+    AutofillEntry, AutofillProfileEntry, BookmarkEntry, BrowserSettingsEntry, CookieEntry,
+    CreditCardEntry, DailyVisitEntry, DownloadEntry, ExtensionEntry, FaviconEntry, HistoryEntry,
+    KeywordSearchEntry, LoginEntry, PredictorEntry, ShortcutEntry, TopSiteEntry,
+};
+
+/// Directories whose child is a user's home directory.
+const HOME_DIRS: [&str; 3] = ["Users", "home", "Documents and Settings"];
+
+/// Salted hasher shared by every artifact in a run.
+pub struct Anonymizer {
+    salt: Vec<u8>,
+}
+
+impl Anonymizer {
+    /// Use `salt` if given (reproducible across runs), otherwise a random per-run salt.
+    pub fn new(salt: Option<&str>) -> Result<Self> {
+        let salt = match salt {
+            Some(s) => s.as_bytes().to_vec(),
+            None => {
+                let mut buf = vec![0u8; 32];
+                getrandom::fill(&mut buf).context("Failed to generate anonymization salt")?;
+                buf
+            }
+        };
+        Ok(Self { salt })
+    }
+
+    /// Hex SHA-256 of salt + value. Empty values stay empty so blank cells remain blank.
+    pub fn hash(&self, value: &str) -> String {
+        if value.is_empty() {
+            return String::new();
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(&self.salt);
+        hasher.update(value.as_bytes());
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    fn hash_in_place(&self, value: &mut String) {
+        *value = self.hash(value);
+    }
+
+    /// Replace the user directory in `path` with its hash: the segment after `Users`,
+    /// `home` or `Documents and Settings`, and any segment equal to `user`. Separators
+    /// and every other segment are kept.
+    pub fn hash_user_in_path(&self, path: &mut String, user: &str) {
+        if path.is_empty() {
+            return;
+        }
+        let mut out = String::with_capacity(path.len());
+        let mut after_home = false;
+        for piece in path.split_inclusive(['/', '\\']) {
+            let segment = piece.trim_end_matches(['/', '\\']);
+            if !segment.is_empty() && (after_home || segment == user) {
+                out.push_str(&self.hash(segment));
+            } else {
+                out.push_str(segment);
+            }
+            out.push_str(&piece[segment.len()..]);
+            after_home = HOME_DIRS.iter().any(|h| segment.eq_ignore_ascii_case(h));
+        }
+        *path = out;
+    }
+
+    /// Hash the user directory in each of `paths`, then `user_profile` itself.
+    fn hash_user(&self, user_profile: &mut String, paths: &mut [&mut String]) {
+        for path in paths.iter_mut() {
+            self.hash_user_in_path(path, user_profile);
+        }
+        self.hash_in_place(user_profile);
+    }
+
+    /// Anonymize every entry in `entries`.
+    pub fn apply<T: Anonymize>(&self, entries: &mut [T]) {
+        for e in entries {
+            e.anonymize(self);
+        }
+    }
+}
+
+/// An entry with user-identifying fields to hash under `--anonymize`.
+pub trait Anonymize {
+    fn anonymize(&mut self, anon: &Anonymizer);
+}
+
+impl Anonymize for HistoryEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.history_file]);
+        anon.hash_in_place(&mut self.account);
+    }
+}
+
+impl Anonymize for DownloadEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(
+            &mut self.user_profile,
+            &mut [
+                &mut self.target_path,
+                &mut self.current_path,
+                &mut self.source_file,
+            ],
+        );
+    }
+}
+
+impl Anonymize for KeywordSearchEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+    }
+}
+
+impl Anonymize for BookmarkEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+    }
+}
+
+impl Anonymize for ExtensionEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+    }
+}
+
+impl Anonymize for BrowserSettingsEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+        if self.setting == "Synced Account" {
+            anon.hash_in_place(&mut self.value);
+            self.details.clear();
+        }
+    }
+}
+
+impl Anonymize for DailyVisitEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+    }
+}
+
+impl Anonymize for FaviconEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+    }
+}
+
+impl Anonymize for TopSiteEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+    }
+}
+
+impl Anonymize for PredictorEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+    }
+}
+
+impl Anonymize for ShortcutEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+    }
+}
+
+impl Anonymize for CookieEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+        anon.hash_in_place(&mut self.value);
+    }
+}
+
+impl Anonymize for AutofillEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+        anon.hash_in_place(&mut self.value);
+    }
+}
+
+impl Anonymize for AutofillProfileEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+        anon.hash_in_place(&mut self.full_name);
+        anon.hash_in_place(&mut self.street_address);
+        anon.hash_in_place(&mut self.emails);
+        anon.hash_in_place(&mut self.phones);
+    }
+}
+
+impl Anonymize for CreditCardEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+        anon.hash_in_place(&mut self.name_on_card);
+    }
+}
+
+impl Anonymize for LoginEntry {
+    fn anonymize(&mut self, anon: &Anonymizer) {
+        anon.hash_user(&mut self.user_profile, &mut [&mut self.source_file]);
+        anon.hash_in_place(&mut self.username_value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn cookie(user: &str, value: &str) -> CookieEntry {
+        CookieEntry {
+            host: ".example.com".to_string(),
+            name: "sid".to_string(),
+            path: "/".to_string(),
+            value: value.to_string(),
+            creation_time: Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap(),
+            is_secure: true,
+            is_httponly: true,
+            is_persistent: true,
+            web_browser: "Chrome".to_string(),
+            user_profile: user.to_string(),
+            record_id: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_consistent_within_run() {
+        let anon = Anonymizer::new(None).unwrap();
+        let mut cookies = vec![
+            cookie("alice", "token-1"),
+            cookie("alice", "token-2"),
+            cookie("bob", "token-1"),
+        ];
+        anon.apply(&mut cookies);
+
+        assert_eq!(cookies[0].user_profile, cookies[1].user_profile);
+        assert_ne!(cookies[0].user_profile, cookies[2].user_profile);
+        assert_eq!(cookies[0].value, cookies[2].value);
+        assert_ne!(cookies[0].value, cookies[1].value);
+        assert_ne!(cookies[0].user_profile, "alice");
+        assert_eq!(cookies[0].user_profile.len(), 64);
+        // Non-PII fields untouched
+        assert_eq!(cookies[0].host, ".example.com");
+        assert_eq!(cookies[0].name, "sid");
+    }
+
+    #[test]
+    fn test_salt() {
+        let a = Anonymizer::new(Some("case-42")).unwrap();
+        let b = Anonymizer::new(Some("case-42")).unwrap();
+        let c = Anonymizer::new(Some("other")).unwrap();
+        assert_eq!(a.hash("alice"), b.hash("alice"));
+        assert_ne!(a.hash("alice"), c.hash("alice"));
+        assert_eq!(a.hash(""), "");
+
+        let random = Anonymizer::new(None).unwrap();
+        assert_ne!(
+            random.hash("alice"),
+            Anonymizer::new(None).unwrap().hash("alice")
+        );
+    }
+
+    #[test]
+    fn test_username_absent_from_paths() {
+        let anon = Anonymizer::new(Some("case-42")).unwrap();
+        let mut history = vec![HistoryEntry {
+            url: "https://example.com/".to_string(),
+            user_profile: "alice".to_string(),
+            history_file: "/triage/C/Users/alice/AppData/Local/Google/Chrome/User Data/\
+                           Default/History"
+                .to_string(),
+            ..Default::default()
+        }];
+        let mut downloads = vec![DownloadEntry {
+            user_profile: "alice".to_string(),
+            target_path: r"C:\Users\alice\Downloads\report.pdf".to_string(),
+            current_path: "file:///home/alice/Downloads/report.pdf".to_string(),
+            source_file: "/evidence/alice/places.sqlite".to_string(),
+            ..Default::default()
+        }];
+        anon.apply(&mut history);
+        anon.apply(&mut downloads);
+
+        let serialized = format!(
+            "{}{}",
+            serde_json::to_string(&history).unwrap(),
+            serde_json::to_string(&downloads).unwrap()
+        );
+        assert!(!serialized.contains("alice"), "{serialized}");
+
+        // The directory hashes the same as the profile, and the rest of the path stays
+        let hash = anon.hash("alice");
+        assert_eq!(history[0].user_profile, hash);
+        assert_eq!(
+            downloads[0].target_path,
+            format!(r"C:\Users\{hash}\Downloads\report.pdf")
+        );
+        assert_eq!(
+            downloads[0].current_path,
+            format!("file:///home/{hash}/Downloads/report.pdf")
+        );
+        assert!(history[0].history_file.ends_with("/Default/History"));
+    }
+}
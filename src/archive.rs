@@ -0,0 +1,211 @@
+//! Triage bundles passed to `scan --dir` as an archive (`.tar`, `.tar.gz` / `.tgz`).
+//!
+//! The archive is unpacked to a temp tree that keeps each member's internal path, so the
+//! normal path-based scan (`Users/<name>/AppData/...`) and extractors work unchanged.
+//! Each format is a [`Decoder`] that hands its regular files to the shared unpacker,
+//! which owns path sanitizing and the size guard; a new format only needs a decoder.
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use log::warn;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+/// Default cap on the bytes written when unpacking one archive. A member that would go
+/// over it aborts the scan instead of filling the disk (e.g. a decompression bomb).
+pub const DEFAULT_MAX_UNPACKED_BYTES: u64 = 64 * 1024 * 1024 * 1024;
+
+/// Reads one archive format, passing each regular file to `sink` with its internal path.
+pub trait Decoder {
+    fn for_each_file(
+        &self,
+        file: File,
+        sink: &mut dyn FnMut(&Path, &mut dyn Read) -> Result<()>,
+    ) -> Result<()>;
+}
+
+/// POSIX tar, optionally gzip-compressed.
+struct Tar {
+    gzip: bool,
+}
+
+impl Decoder for Tar {
+    fn for_each_file(
+        &self,
+        file: File,
+        sink: &mut dyn FnMut(&Path, &mut dyn Read) -> Result<()>,
+    ) -> Result<()> {
+        let reader: Box<dyn Read> = if self.gzip {
+            Box::new(GzDecoder::new(BufReader::new(file)))
+        } else {
+            Box::new(BufReader::new(file))
+        };
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries().context("Failed to read tar archive")? {
+            let mut entry = entry.context("Corrupt tar entry")?;
+            // Links and devices are skipped: browser databases are regular files
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let path = entry.path().context("Invalid tar entry path")?.into_owned();
+            sink(&path, &mut entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// The decoder for `path`, chosen by extension, or `None` if it is not an archive.
+fn decoder_for(path: &Path) -> Option<Box<dyn Decoder>> {
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(Box::new(Tar { gzip: true }))
+    } else if name.ends_with(".tar") {
+        Some(Box::new(Tar { gzip: false }))
+    } else {
+        None
+    }
+}
+
+/// Whether `path` is an archive file `scan` can unpack.
+pub fn is_archive(path: &Path) -> bool {
+    path.is_file() && decoder_for(path).is_some()
+}
+
+/// `internal` made relative and free of `..`, or `None` if nothing is left of it.
+fn sanitize(internal: &Path) -> Option<PathBuf> {
+    let mut clean = PathBuf::new();
+    for c in internal.components() {
+        match c {
+            Component::Normal(part) => clean.push(part),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+            Component::ParentDir => return None,
+        }
+    }
+    (!clean.as_os_str().is_empty()).then_some(clean)
+}
+
+/// Unpack `archive` under `dest`, keeping internal paths. Members with `..` in their path
+/// are skipped. Fails once more than `max_bytes` would be written. Returns the number of
+/// files unpacked.
+pub fn stage(archive: &Path, dest: &Path, max_bytes: u64) -> Result<usize> {
+    let decoder = match decoder_for(archive) {
+        Some(d) => d,
+        None => anyhow::bail!("Not a supported archive: {}", archive.display()),
+    };
+    let file = File::open(crate::browsers::long_path(archive))
+        .with_context(|| format!("Failed to open archive: {}", archive.display()))?;
+
+    let mut written = 0u64;
+    let mut count = 0usize;
+    decoder.for_each_file(file, &mut |internal, contents| {
+        let rel = match sanitize(internal) {
+            Some(rel) => rel,
+            None => {
+                warn!(
+                    "Skipping archive member outside the tree: {}",
+                    internal.display()
+                );
+                return Ok(());
+            }
+        };
+        let out = dest.join(&rel);
+        if let Some(parent) = out.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let mut f =
+            File::create(&out).with_context(|| format!("Failed to create {}", out.display()))?;
+        // Read one byte past the remaining budget to tell "fits exactly" from "too big"
+        let budget = max_bytes - written;
+        let copied = std::io::copy(&mut contents.take(budget + 1), &mut f)
+            .with_context(|| format!("Failed to unpack {}", rel.display()))?;
+        if copied > budget {
+            anyhow::bail!(
+                "{} unpacks to more than {} bytes; stopped at {}",
+                archive.display(),
+                max_bytes,
+                rel.display()
+            );
+        }
+        written += copied;
+        count += 1;
+        Ok(())
+    })?;
+    Ok(count)
+}
+
+/// The `archive!member` name of a file unpacked by [`stage`] to `staged` under `dest`,
+/// so output points into the evidence rather than at the temp tree. `None` when `staged`
+/// is not under `dest`.
+pub fn member_name(archive: &Path, dest: &Path, staged: &str) -> Option<String> {
+    let staged = Path::new(staged);
+    let rel = match staged.strip_prefix(dest) {
+        Ok(rel) => rel,
+        // The scan may have resolved symlinks in the temp dir (e.g. macOS /private/var)
+        Err(_) => staged.strip_prefix(dest.canonicalize().ok()?).ok()?,
+    };
+    let member: Vec<_> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    Some(format!("{}!{}", archive.display(), member.join("/")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_tar(path: &Path, members: &[(&str, &[u8])]) {
+        let mut builder = tar::Builder::new(File::create(path).unwrap());
+        for (name, data) in members {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            // set_path refuses `..`, so write the raw name as a hostile archive would
+            let raw = &mut header.as_old_mut().name;
+            raw[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_cksum();
+            builder.append(&header, *data).unwrap();
+        }
+        builder.finish().unwrap();
+    }
+
+    #[test]
+    fn test_stage_skips_traversal_and_enforces_limit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let tar_path = dir.path().join("bundle.tar");
+        write_tar(
+            &tar_path,
+            &[
+                ("./C/Users/alice/places.sqlite", b"0123456789"),
+                ("../../escape.txt", b"x"),
+            ],
+        );
+
+        let dest = dir.path().join("out");
+        assert_eq!(stage(&tar_path, &dest, 10).unwrap(), 1);
+        assert_eq!(
+            std::fs::read(dest.join("C/Users/alice/places.sqlite")).unwrap(),
+            b"0123456789"
+        );
+        assert!(!dir.path().join("escape.txt").exists());
+
+        let err = stage(&tar_path, &dir.path().join("small"), 9).unwrap_err();
+        assert!(err.to_string().contains("more than 9 bytes"), "{err}");
+
+        assert!(!is_archive(&dest));
+        assert!(is_archive(&tar_path));
+    }
+
+    #[test]
+    fn test_member_name_points_into_archive() {
+        let archive = Path::new("/evidence/bundle.tar.gz");
+        let dest = Path::new("/tmp/unpacked");
+        assert_eq!(
+            member_name(archive, dest, "/tmp/unpacked/C/Users/alice/History").as_deref(),
+            Some("/evidence/bundle.tar.gz!C/Users/alice/History")
+        );
+        assert_eq!(member_name(archive, dest, "/elsewhere/History"), None);
+    }
+}
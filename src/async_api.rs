@@ -0,0 +1,82 @@
+//! Non-blocking wrappers for embedding WebX in async services (`async` feature).
+//!
+//! The extractors are synchronous: SQLite, ESE and file reads all block. These functions
+//! only move that work onto tokio's blocking thread pool via `spawn_blocking`, so an
+//! axum/warp handler can await them without stalling the reactor. They must be called
+//! from within a tokio runtime.
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use crate::browsers::HistoryEntry;
+use crate::extract::{self, ArtifactSet, ExtractOptions};
+use crate::scanner;
+
+/// Async form of [`extract::extract_all`]: scan `dir` and extract every artifact `opts`
+/// selects.
+pub async fn extract_all_async(
+    dir: impl Into<PathBuf>,
+    opts: ExtractOptions,
+) -> Result<ArtifactSet> {
+    let dir = dir.into();
+    tokio::task::spawn_blocking(move || extract::extract_all(&dir, &opts))
+        .await
+        .context("Extraction task failed to complete")?
+}
+
+/// Async form of [`scanner::extract_history`]: extract history from a single database
+/// with the registry extractor for its file name.
+pub async fn extract_history_async(path: impl Into<PathBuf>) -> Result<Vec<HistoryEntry>> {
+    let path = path.into();
+    tokio::task::spawn_blocking(move || scanner::extract_history(&path))
+        .await
+        .context("History extraction task failed to complete")?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_history_async() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("typed_history.xml");
+        std::fs::write(
+            &path,
+            r#"<typed_history><typed_history_item content="example.com" type="text" last_typed="2013-03-22T16:36:58Z"/></typed_history>"#,
+        )
+        .unwrap();
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let entries = rt.block_on(extract_history_async(&path)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].url, "example.com");
+
+        let missing = rt.block_on(extract_history_async(dir.path().join("History")));
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_extract_all_async() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir.path().join("Users/bob/AppData/Roaming/Opera/Opera");
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(
+            profile.join("typed_history.xml"),
+            r#"<typed_history><typed_history_item content="example.com" type="text" last_typed="2013-03-22T16:36:58Z"/></typed_history>"#,
+        )
+        .unwrap();
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let set = rt
+            .block_on(extract_all_async(dir.path(), ExtractOptions::default()))
+            .unwrap();
+        assert_eq!(set.history.len(), 1);
+        assert_eq!(set.history[0].user_profile, "bob");
+        assert!(set.errors.is_empty());
+    }
+}
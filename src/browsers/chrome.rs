@@ -1,9 +1,13 @@
 use anyhow::{Context, Result};
-use rusqlite::Connection;
+use log::warn;
 use std::path::Path;
 use tempfile::TempDir;
 
-use super::{chrome_time_to_datetime, detect_chromium_browser, BrowserType, HistoryEntry};
+use super::{
+    chrome_prefs, chrome_time_to_datetime, chrome_visited_links, collect_rows,
+    detect_chromium_browser, fill_journeys, fill_visited_from, long_path, make_temp_dir,
+    open_in_place, open_temp_copy, BrowserType, HistoryEntry, PREFETCH_VISIT_TYPE,
+};
 
 /// Core transition type (lower 8 bits of the transition field).
 fn transition_name(transition: i32) -> &'static str {
@@ -23,17 +27,91 @@ fn transition_name(transition: i32) -> &'static str {
     }
 }
 
+/// Redirect-chain boundary qualifiers. Every real navigation starts and ends a chain.
+const CHAIN_START: i32 = 0x1000_0000;
+const CHAIN_END: i32 = 0x2000_0000;
+/// Redirect qualifiers. Hops inside a chain carry one of these but no boundary bit.
+const CLIENT_REDIRECT: i32 = 0x4000_0000;
+const SERVER_REDIRECT: i32 = 0x8000_0000u32 as i32;
+
+/// Qualifier bits above the core type, in the order they are listed. The chain
+/// boundaries come last and are only named for hops inside a multi-visit chain.
+const TRANSITION_QUALIFIERS: [(u32, &str); 9] = [
+    (0x0080_0000, "Blocked"),
+    (0x0100_0000, "Forward/Back"),
+    (0x0200_0000, "From Address Bar"),
+    (0x0400_0000, "Home Page"),
+    (0x0800_0000, "From API"),
+    (CLIENT_REDIRECT as u32, "Client Redirect"),
+    (SERVER_REDIRECT as u32, "Server Redirect"),
+    (CHAIN_START as u32, "Chain Start"),
+    (CHAIN_END as u32, "Chain End"),
+];
+
+/// Core transition name followed by its qualifiers, e.g. `Link (Server Redirect, Chain End)`.
+///
+/// A visit that both starts and ends its chain is an ordinary single-hop navigation,
+/// so that pair is left out rather than repeated on nearly every row.
+fn transition_label(transition: i32) -> String {
+    let core = transition_name(transition);
+    let mut bits = transition as u32;
+    let chain = (CHAIN_START | CHAIN_END) as u32;
+    if bits & chain == chain {
+        bits &= !chain;
+    }
+    let qualifiers: Vec<&str> = TRANSITION_QUALIFIERS
+        .iter()
+        .filter(|(bit, _)| bits & bit != 0)
+        .map(|&(_, name)| name)
+        .collect();
+    if qualifiers.is_empty() {
+        core.to_string()
+    } else {
+        format!("{} ({})", core, qualifiers.join(", "))
+    }
+}
+
+/// Prerendered/prefetched pages leave visits with a zero duration and no chain
+/// boundaries, i.e. no user transition. Redirect hops in the middle of a chain look the
+/// same but carry a redirect qualifier. A missing duration (old schema) is not enough.
+fn is_prefetch(transition: i32, visit_duration: Option<i64>) -> bool {
+    visit_duration == Some(0)
+        && transition & (CHAIN_START | CHAIN_END | CLIENT_REDIRECT | SERVER_REDIRECT) == 0
+}
+
+/// `visits.visit_duration` (microseconds the tab was focused) as `N.NNN s` under a
+/// minute, else `[Hh ]Mm Ss`. Zero or missing (old schema) is left empty.
+fn format_visit_duration(micros: Option<i64>) -> String {
+    let micros = match micros {
+        Some(m) if m > 0 => m,
+        _ => return String::new(),
+    };
+    if micros < 60_000_000 {
+        return format!("{:.3} s", micros as f64 / 1_000_000.0);
+    }
+    let secs = micros / 1_000_000;
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if h > 0 {
+        format!("{h}h {m}m {s}s")
+    } else {
+        format!("{m}m {s}s")
+    }
+}
+
 /// Copy a Chrome-style database to a temp directory (Chrome locks its DB).
 /// Returns (TempDir, PathBuf to copied DB).
 pub fn copy_db_to_temp(db_path: &Path, filename: &str) -> Result<(TempDir, std::path::PathBuf)> {
-    let tmp_dir = TempDir::new().context("Failed to create temp directory")?;
+    let tmp_dir = make_temp_dir()?;
     let tmp_db = tmp_dir.path().join(filename);
-    std::fs::copy(db_path, &tmp_db)
+    let src = long_path(db_path);
+    std::fs::copy(&src, &tmp_db)
         .with_context(|| format!("Failed to copy database: {}", db_path.display()))?;
 
     // Copy WAL/SHM/journal if present
     for ext in &["-wal", "-shm", "-journal"] {
-        let aux = db_path.with_extension(&ext[1..]);
+        let mut aux = src.clone().into_os_string();
+        aux.push(ext);
+        let aux = std::path::PathBuf::from(aux);
         if aux.exists() {
             let _ = std::fs::copy(&aux, tmp_dir.path().join(format!("{filename}{ext}")));
         }
@@ -42,7 +120,29 @@ pub fn copy_db_to_temp(db_path: &Path, filename: &str) -> Result<(TempDir, std::
     Ok((tmp_dir, tmp_db))
 }
 
+/// Open a Chrome-style database for reading: in place when [`open_in_place`] allows it,
+/// otherwise through a temp copy made by [`copy_db_to_temp`]. The `TempDir`, if any,
+/// must outlive the connection.
+pub fn open_db(db_path: &Path, filename: &str) -> Result<(rusqlite::Connection, Option<TempDir>)> {
+    if let Some(conn) = open_in_place(db_path) {
+        return Ok((conn, None));
+    }
+    let (tmp_dir, tmp_db) = copy_db_to_temp(db_path, filename)?;
+    let conn = open_temp_copy(&tmp_db)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+    Ok((conn, Some(tmp_dir)))
+}
+
 /// Extract browsing history from a Chrome/Chromium `History` SQLite file.
+///
+/// Rows are tagged with the profile's signed-in account, read from the `Preferences`
+/// file next to the database, when there is one. Typed and start-page visits get a
+/// `journey` of the pages reached from them via `from_visit`, and every visit gets the
+/// URL its `from_visit` points at as Visited From. Visits opened from
+/// another app take Visited From from `external_referrer_url` and App ID from `app_id`
+/// on Chrome versions that record them. URLs with `urls.hidden` set (redirect hops and
+/// other pages kept out of the history UI) are returned with `hidden`. URLs found in the
+/// profile's `Visited Links` table are returned with `visited_link_confirmed`.
 pub fn extract(
     db_path: &Path,
     username: &str,
@@ -51,18 +151,28 @@ pub fn extract(
     let db_str = db_path.to_string_lossy().to_string();
     let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&db_str));
 
-    let (_tmp_dir, tmp_db) = copy_db_to_temp(db_path, "History")?;
+    let (conn, _tmp_dir) = open_db(db_path, "History")?;
 
-    let conn = Connection::open(&tmp_db)
-        .with_context(|| format!("Failed to open database: {}", db_str))?;
-
-    let mut stmt = conn.prepare(
+    // Columns added (or dropped) by Chrome versions are read only when the schema has them
+    let optional_column = |table: &str, alias: &str, col: &str| {
+        if super::has_column(&conn, table, col) {
+            format!("{alias}.{col}")
+        } else {
+            "NULL".to_string()
+        }
+    };
+    let query = format!(
         "SELECT u.url, u.title, v.visit_time, u.visit_count, \
-                v.from_visit, v.transition, u.typed_count, u.id \
+                v.from_visit, v.transition, u.typed_count, u.id, {}, v.id, {}, {}, {} \
          FROM urls u \
          JOIN visits v ON u.id = v.url \
          ORDER BY v.visit_time ASC",
-    )?;
+        optional_column("visits", "v", "visit_duration"),
+        optional_column("visits", "v", "external_referrer_url"),
+        optional_column("visits", "v", "app_id"),
+        optional_column("urls", "u", "hidden"),
+    );
+    let mut stmt = conn.prepare(&query)?;
 
     let rows = stmt.query_map([], |row| {
         Ok((
@@ -74,13 +184,35 @@ pub fn extract(
             row.get::<_, i32>(5)?,
             row.get::<_, i32>(6)?,
             row.get::<_, i64>(7)?,
+            row.get::<_, Option<i64>>(8)?,
+            row.get::<_, i64>(9)?,
+            row.get::<_, Option<String>>(10)?,
+            row.get::<_, Option<String>>(11)?,
+            row.get::<_, Option<i64>>(12)?,
         ))
     })?;
 
+    let account = chrome_prefs::profile_synced_account(&db_path.with_file_name("Preferences"))
+        .unwrap_or_default();
+
     let mut entries = Vec::new();
-    for row in rows {
-        let (url, title, visit_time_raw, visit_count, _from_visit, transition, typed_count, id) =
-            row?;
+    let mut visits = Vec::new();
+    for row in collect_rows(rows, &db_str) {
+        let (
+            url,
+            title,
+            visit_time_raw,
+            visit_count,
+            from_visit,
+            transition,
+            typed_count,
+            id,
+            visit_duration,
+            visit_id,
+            external_referrer,
+            app_id,
+            hidden,
+        ) = row;
 
         if url.is_empty() {
             continue;
@@ -97,24 +229,61 @@ pub fn extract(
             title: title.unwrap_or_default(),
             visit_time,
             visit_count: visit_count as u32,
-            visited_from: String::new(),
-            visit_type: transition_name(transition).to_string(),
-            visit_duration: String::new(),
+            // Filled from from_visit below; links opened from another app (mail client,
+            // chat) have none but carry the referrer that app passed along
+            visited_from: if from_visit == 0 {
+                external_referrer.unwrap_or_default()
+            } else {
+                String::new()
+            },
+            app_id: app_id.unwrap_or_default(),
+            journey: String::new(),
+            canonical_url: String::new(),
+            visit_type: if is_prefetch(transition, visit_duration) {
+                PREFETCH_VISIT_TYPE.to_string()
+            } else {
+                transition_label(transition)
+            },
+            visit_duration: format_visit_duration(visit_duration),
+            view_time_ms: None,
+            key_presses: None,
+            hidden: hidden.unwrap_or(0) != 0,
+            visited_link_confirmed: false,
             web_browser: browser.display_name().to_string(),
             user_profile: username.to_string(),
             browser_profile: String::new(),
+            account: account.clone(),
             typed_count: typed_count as u32,
             history_file: db_str.clone(),
             record_id: id,
         });
+        visits.push((visit_id, from_visit));
     }
 
+    fill_visited_from(&mut entries, &visits);
+    fill_journeys(&mut entries, &visits);
+
+    let visited_links = db_path.with_file_name("Visited Links");
+    if let Err(e) = chrome_visited_links::tag_entries(&mut entries, &visited_links) {
+        warn!("{}: {:#}", visited_links.display(), e);
+    }
     Ok(entries)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_format_visit_duration() {
+        assert_eq!(format_visit_duration(None), "");
+        assert_eq!(format_visit_duration(Some(0)), "");
+        assert_eq!(format_visit_duration(Some(250_500)), "0.251 s");
+        assert_eq!(format_visit_duration(Some(59_999_000)), "59.999 s");
+        assert_eq!(format_visit_duration(Some(185_400_000)), "3m 5s");
+        assert_eq!(format_visit_duration(Some(3_723_000_000)), "1h 2m 3s");
+    }
 
     #[test]
     fn test_chrome_time_conversion() {
@@ -132,6 +301,30 @@ mod tests {
         assert_eq!(transition_name(99), "Other");
     }
 
+    #[test]
+    fn test_transition_labels() {
+        assert_eq!(transition_label(CHAIN_START | CHAIN_END), "Link");
+        assert_eq!(transition_label(0x0800_0001), "Typed (From API)");
+        assert_eq!(
+            transition_label(0x0800_0001 | CHAIN_START),
+            "Typed (From API, Chain Start)"
+        );
+        assert_eq!(transition_label(0x4000_0000), "Link (Client Redirect)");
+        assert_eq!(
+            transition_label(0x4000_0000 | CHAIN_END),
+            "Link (Client Redirect, Chain End)"
+        );
+        // SERVER_REDIRECT is the sign bit of the stored integer
+        assert_eq!(
+            transition_label(0x8000_0000u32 as i32 | CHAIN_END),
+            "Link (Server Redirect, Chain End)"
+        );
+        assert_eq!(
+            transition_label(0x0200_0001 | CHAIN_START | CHAIN_END),
+            "Typed (From Address Bar)"
+        );
+    }
+
     #[test]
     fn test_detect_browser() {
         assert_eq!(
@@ -153,4 +346,268 @@ mod tests {
             BrowserType::EdgeChromium
         );
     }
+
+    #[test]
+    fn test_malformed_row_is_skipped() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History");
+        let conn = Connection::open(&db).unwrap();
+        // visit_time on visit 2 is text, which fails to decode as i64
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                visit_count INTEGER, typed_count INTEGER);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time,
+                                  from_visit INTEGER, transition INTEGER);
+             INSERT INTO urls VALUES (1, 'https://a.example/', 'A', 1, 0);
+             INSERT INTO urls VALUES (2, 'https://b.example/', 'B', 1, 0);
+             INSERT INTO urls VALUES (3, 'https://c.example/', 'C', 1, 0);
+             INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 0);
+             INSERT INTO visits VALUES (2, 2, 'corrupt', 0, 0);
+             INSERT INTO visits VALUES (3, 3, 13245010623000000, 0, 0);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "user", Some(BrowserType::Chrome)).unwrap();
+        let urls: Vec<_> = entries.iter().map(|e| e.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://a.example/", "https://c.example/"]);
+        // No visit_duration column: nothing can be called a prefetch
+        assert!(entries.iter().all(|e| e.visit_type == "Link"));
+    }
+
+    #[test]
+    fn test_prefetch_visits_are_labeled() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History");
+        let conn = Connection::open(&db).unwrap();
+        // 805306368 = LINK | CHAIN_START | CHAIN_END; 0 = bare LINK with no chain
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                visit_count INTEGER, typed_count INTEGER);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                                  from_visit INTEGER, transition INTEGER,
+                                  visit_duration INTEGER DEFAULT 0 NOT NULL);
+             INSERT INTO urls VALUES (1, 'https://news.example/', 'News', 1, 0);
+             INSERT INTO urls VALUES (2, 'https://news.example/article', 'Article', 1, 0);
+             INSERT INTO urls VALUES (3, 'https://news.example/quick', 'Quick', 1, 0);
+             INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 805306368, 12000000);
+             INSERT INTO visits VALUES (2, 2, 13245010622000000, 1, 0, 0);
+             INSERT INTO visits VALUES (3, 3, 13245010623000000, 0, 805306368, 0);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "user", Some(BrowserType::Chrome)).unwrap();
+        let types: Vec<_> = entries.iter().map(|e| e.visit_type.as_str()).collect();
+        // A real navigation closed before any duration was recorded stays a Link
+        assert_eq!(types, vec!["Link", "Prefetch", "Link"]);
+        assert!(is_prefetch(0, Some(0)));
+        assert!(!is_prefetch(0, None));
+        assert!(!is_prefetch(CHAIN_START | CHAIN_END, Some(0)));
+    }
+
+    #[test]
+    fn test_mid_chain_redirect_is_not_prefetch() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History");
+        let conn = Connection::open(&db).unwrap();
+        // a -> (302) b -> (302) c, all with zero duration: 268435456 = LINK | CHAIN_START,
+        // -2147483648 = LINK | SERVER_REDIRECT (the middle hop, no chain boundary),
+        // -1610612736 = LINK | SERVER_REDIRECT | CHAIN_END
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                visit_count INTEGER, typed_count INTEGER);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                                  from_visit INTEGER, transition INTEGER,
+                                  visit_duration INTEGER DEFAULT 0 NOT NULL);
+             INSERT INTO urls VALUES (1, 'https://a.example/', 'A', 1, 0);
+             INSERT INTO urls VALUES (2, 'https://b.example/', 'B', 1, 0);
+             INSERT INTO urls VALUES (3, 'https://c.example/', 'C', 1, 0);
+             INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 268435456, 0);
+             INSERT INTO visits VALUES (2, 2, 13245010621000001, 1, -2147483648, 0);
+             INSERT INTO visits VALUES (3, 3, 13245010621000002, 2, -1610612736, 0);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "user", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|e| e.visit_type != "Prefetch"));
+        assert!(!is_prefetch(SERVER_REDIRECT, Some(0)));
+        assert!(!is_prefetch(CLIENT_REDIRECT, Some(0)));
+    }
+
+    #[test]
+    fn test_visited_from_resolves_from_visit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History");
+        let conn = Connection::open(&db).unwrap();
+        // Visit 3 points at a visit that was expired from the database
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                visit_count INTEGER, typed_count INTEGER);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                                  from_visit INTEGER, transition INTEGER);
+             INSERT INTO urls VALUES (1, 'https://search.example/?q=shoes', 'Search', 1, 1);
+             INSERT INTO urls VALUES (2, 'https://shop.example/shoes', 'Shoes', 1, 0);
+             INSERT INTO urls VALUES (3, 'https://shop.example/cart', 'Cart', 1, 0);
+             INSERT INTO visits VALUES (10, 1, 13245010621000000, 0, 1);
+             INSERT INTO visits VALUES (11, 2, 13245010622000000, 10, 0);
+             INSERT INTO visits VALUES (12, 3, 13245010623000000, 7, 0);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "user", Some(BrowserType::Chrome)).unwrap();
+        let from: Vec<_> = entries.iter().map(|e| e.visited_from.as_str()).collect();
+        assert_eq!(from, vec!["", "https://search.example/?q=shoes", ""]);
+    }
+
+    #[test]
+    fn test_external_referrer_and_app_id() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History");
+        let conn = Connection::open(&db).unwrap();
+        // Visit 1 opened from a mail app; visit 2 followed a link from it in the browser,
+        // so its internal from_visit wins over the stale external referrer
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                visit_count INTEGER, typed_count INTEGER);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                                  from_visit INTEGER, transition INTEGER,
+                                  visit_duration INTEGER DEFAULT 0 NOT NULL,
+                                  external_referrer_url TEXT, app_id TEXT);
+             INSERT INTO urls VALUES (1, 'https://invoice.example/pay', 'Pay', 1, 0);
+             INSERT INTO urls VALUES (2, 'https://invoice.example/confirm', 'Done', 1, 0);
+             INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 805306368, 5000000,
+                                        'android-app://com.google.android.gm/', 'com.google.android.gm');
+             INSERT INTO visits VALUES (2, 2, 13245010622000000, 1, 805306368, 5000000,
+                                        'https://stale.example/', NULL);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "user", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(
+            entries[0].visited_from,
+            "android-app://com.google.android.gm/"
+        );
+        assert_eq!(entries[0].app_id, "com.google.android.gm");
+        assert_eq!(entries[1].visited_from, "https://invoice.example/pay");
+        assert_eq!(entries[1].app_id, "");
+    }
+
+    #[test]
+    fn test_visited_links_confirm_urls() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History");
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                visit_count INTEGER, typed_count INTEGER);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                                  from_visit INTEGER, transition INTEGER);
+             INSERT INTO urls VALUES (1, 'https://seen.example/', 'Seen', 1, 0);
+             INSERT INTO urls VALUES (2, 'https://cleared.example/', 'Cleared', 1, 0);
+             INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 0);
+             INSERT INTO visits VALUES (2, 2, 13245010622000000, 0, 0);",
+        )
+        .unwrap();
+        drop(conn);
+
+        // No Visited Links file: nothing confirmed
+        let entries = extract(&db, "user", Some(BrowserType::Chrome)).unwrap();
+        assert!(entries.iter().all(|e| !e.visited_link_confirmed));
+
+        let file = chrome_visited_links::visited_links_file([1; 8], 8, &["https://seen.example/"]);
+        std::fs::write(dir.path().join("Visited Links"), file).unwrap();
+
+        let entries = extract(&db, "user", Some(BrowserType::Chrome)).unwrap();
+        let confirmed: Vec<_> = entries
+            .iter()
+            .map(|e| (e.url.as_str(), e.visited_link_confirmed))
+            .collect();
+        assert_eq!(
+            confirmed,
+            vec![
+                ("https://seen.example/", true),
+                ("https://cleared.example/", false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hidden_urls_flagged_and_removable() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History");
+        let conn = Connection::open(&db).unwrap();
+        // A typed short link that redirected: the intermediate hop is hidden
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                visit_count INTEGER, typed_count INTEGER,
+                                hidden INTEGER DEFAULT 0 NOT NULL);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                                  from_visit INTEGER, transition INTEGER);
+             INSERT INTO urls VALUES (1, 'https://sho.rt/x', '', 1, 1, 1);
+             INSERT INTO urls VALUES (2, 'https://landing.example/', 'Landing', 1, 0, 0);
+             INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 1);
+             INSERT INTO visits VALUES (2, 2, 13245010622000000, 1, 5);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let mut entries = extract(&db, "user", Some(BrowserType::Chrome)).unwrap();
+        let hidden: Vec<_> = entries.iter().map(|e| (e.url.as_str(), e.hidden)).collect();
+        assert_eq!(
+            hidden,
+            vec![
+                ("https://sho.rt/x", true),
+                ("https://landing.example/", false)
+            ]
+        );
+
+        assert_eq!(crate::browsers::remove_hidden(&mut entries), 1);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].url, "https://landing.example/");
+    }
+
+    #[test]
+    fn test_typed_visit_journey() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History");
+        let conn = Connection::open(&db).unwrap();
+        // Typed search -> result -> reload -> download page; visit 5 is a second tab
+        // opened from the search later on. Visits 6/7 point at each other (corrupt).
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                visit_count INTEGER, typed_count INTEGER);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                                  from_visit INTEGER, transition INTEGER);
+             INSERT INTO urls VALUES (1, 'https://search.example/?q=tool', 'Search', 1, 1);
+             INSERT INTO urls VALUES (2, 'https://tool.example/', 'Tool', 2, 0);
+             INSERT INTO urls VALUES (3, 'https://tool.example/download', 'Get', 1, 0);
+             INSERT INTO urls VALUES (4, 'https://other.example/', 'Other', 1, 0);
+             INSERT INTO urls VALUES (5, 'https://loop.example/', 'Loop', 2, 1);
+             INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 1);
+             INSERT INTO visits VALUES (2, 2, 13245010622000000, 1, 0);
+             INSERT INTO visits VALUES (3, 2, 13245010623000000, 2, 8);
+             INSERT INTO visits VALUES (4, 3, 13245010624000000, 3, 0);
+             INSERT INTO visits VALUES (5, 4, 13245010625000000, 1, 0);
+             INSERT INTO visits VALUES (6, 5, 13245010626000000, 7, 1);
+             INSERT INTO visits VALUES (7, 5, 13245010627000000, 6, 0);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "user", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(
+            entries[0].journey,
+            "https://search.example/?q=tool -> https://tool.example/ -> https://tool.example/download"
+        );
+        // Only typed/start-page visits carry a journey
+        assert!(entries[1..5].iter().all(|e| e.journey.is_empty()));
+        // The cycle terminates, and a same-URL hop adds nothing
+        assert_eq!(entries[5].visit_type, "Typed");
+        assert!(entries[5].journey.is_empty());
+    }
 }
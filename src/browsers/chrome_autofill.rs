@@ -1,9 +1,10 @@
-use anyhow::{Context, Result};
-use rusqlite::Connection;
+use anyhow::Result;
 use std::path::Path;
 
-use super::chrome::copy_db_to_temp;
-use super::{detect_chromium_browser, unix_seconds_to_datetime, AutofillEntry, BrowserType};
+use super::chrome::open_db;
+use super::{
+    collect_rows, detect_chromium_browser, unix_seconds_to_datetime, AutofillEntry, BrowserType,
+};
 
 /// Extract autofill entries from a Chrome/Chromium `Web Data` SQLite file.
 ///
@@ -16,10 +17,7 @@ pub fn extract(
     let db_str = db_path.to_string_lossy().to_string();
     let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&db_str));
 
-    let (_tmp_dir, tmp_db) = copy_db_to_temp(db_path, "WebData")?;
-
-    let conn = Connection::open(&tmp_db)
-        .with_context(|| format!("Failed to open database: {}", db_str))?;
+    let (conn, _tmp_dir) = open_db(db_path, "WebData")?;
 
     let table_exists: bool = conn
         .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='autofill'")?
@@ -46,8 +44,8 @@ pub fn extract(
     })?;
 
     let mut entries = Vec::new();
-    for row in rows {
-        let (rowid, name, value, date_created, date_last_used, count) = row?;
+    for row in collect_rows(rows, &db_str) {
+        let (rowid, name, value, date_created, date_last_used, count) = row;
 
         if name.is_empty() && value.is_empty() {
             continue;
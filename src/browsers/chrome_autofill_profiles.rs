@@ -0,0 +1,380 @@
+use anyhow::Result;
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::chrome::open_db;
+use super::{
+    collect_rows, detect_chromium_browser, unix_seconds_to_datetime, AutofillProfileEntry,
+    BrowserType, CreditCardEntry,
+};
+
+/// Extract saved addresses from a Chrome/Chromium `Web Data` SQLite file.
+///
+/// Each row of `autofill_profiles` is one address; the names, emails and phone numbers
+/// kept against its `guid` in `autofill_profile_names`, `autofill_profile_emails` and
+/// `autofill_profile_phones` are joined onto it. Times are Unix epoch seconds, as in
+/// the `autofill` table. A missing `autofill_profiles` table gives no entries.
+pub fn extract(
+    db_path: &Path,
+    username: &str,
+    browser_override: Option<BrowserType>,
+) -> Result<Vec<AutofillProfileEntry>> {
+    let db_str = db_path.to_string_lossy().to_string();
+    let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&db_str));
+
+    let (conn, _tmp_dir) = open_db(db_path, "WebData")?;
+    if !table_exists(&conn, "autofill_profiles")? {
+        return Ok(Vec::new());
+    }
+
+    let names = values_by_guid(&conn, "autofill_profile_names", "full_name", &db_str)?;
+    let emails = values_by_guid(&conn, "autofill_profile_emails", "email", &db_str)?;
+    let phones = values_by_guid(&conn, "autofill_profile_phones", "number", &db_str)?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT guid, {}, {}, {}, {}, {}, {}, use_count, use_date, date_modified \
+         FROM autofill_profiles \
+         ORDER BY use_date DESC",
+        optional_column(&conn, "autofill_profiles", "company_name"),
+        optional_column(&conn, "autofill_profiles", "street_address"),
+        optional_column(&conn, "autofill_profiles", "city"),
+        optional_column(&conn, "autofill_profiles", "state"),
+        optional_column(&conn, "autofill_profiles", "zipcode"),
+        optional_column(&conn, "autofill_profiles", "country_code"),
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, Option<String>>(4)?,
+            row.get::<_, Option<String>>(5)?,
+            row.get::<_, Option<String>>(6)?,
+            row.get::<_, Option<i64>>(7)?,
+            row.get::<_, Option<i64>>(8)?,
+            row.get::<_, Option<i64>>(9)?,
+        ))
+    })?;
+
+    let joined = |by_guid: &HashMap<String, Vec<String>>, guid: &str| {
+        by_guid.get(guid).map(|v| v.join("; ")).unwrap_or_default()
+    };
+    let mut entries = Vec::new();
+    for row in collect_rows(rows, &db_str) {
+        let (guid, company, street, city, state, zipcode, country, use_count, use_date, modified) =
+            row;
+
+        entries.push(AutofillProfileEntry {
+            full_name: joined(&names, &guid),
+            company_name: company.unwrap_or_default(),
+            street_address: street.unwrap_or_default(),
+            city: city.unwrap_or_default(),
+            state: state.unwrap_or_default(),
+            zipcode: zipcode.unwrap_or_default(),
+            country_code: country.unwrap_or_default(),
+            emails: joined(&emails, &guid),
+            phones: joined(&phones, &guid),
+            use_count: use_count.unwrap_or(0).max(0) as u32,
+            last_used: use_date.and_then(unix_seconds_to_datetime),
+            date_modified: modified.and_then(unix_seconds_to_datetime),
+            guid,
+            web_browser: browser.display_name().to_string(),
+            user_profile: username.to_string(),
+            browser_profile: String::new(),
+            source_file: db_str.clone(),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Extract saved payment card metadata from a Chrome/Chromium `Web Data` SQLite file.
+///
+/// IMPORTANT: Only extracts metadata (name on card, expiry, network, usage).
+/// Card numbers and CVVs are NEVER extracted. Cards saved on the device
+/// (`credit_cards`) keep their number encrypted, so no digits are reported for them;
+/// cards synced from Google Pay (`masked_credit_cards`) carry only their last four
+/// digits, which are reported, with usage from `server_card_metadata`.
+pub fn extract_credit_cards(
+    db_path: &Path,
+    username: &str,
+    browser_override: Option<BrowserType>,
+) -> Result<Vec<CreditCardEntry>> {
+    let db_str = db_path.to_string_lossy().to_string();
+    let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&db_str));
+
+    let (conn, _tmp_dir) = open_db(db_path, "WebData")?;
+
+    // (guid, name, network, last four, month, year, nickname, use count, use date,
+    // date modified, storage)
+    let mut cards = Vec::new();
+    if table_exists(&conn, "credit_cards")? {
+        // card_number_encrypted is never selected
+        let mut stmt = conn.prepare(&format!(
+            "SELECT guid, name_on_card, '', '', expiration_month, expiration_year, {}, \
+                    use_count, use_date, date_modified, 'Local' \
+             FROM credit_cards",
+            optional_column(&conn, "credit_cards", "nickname"),
+        ))?;
+        let rows = stmt.query_map([], card_row)?;
+        cards.extend(collect_rows(rows, &db_str));
+    }
+    if table_exists(&conn, "masked_credit_cards")? {
+        // Newer Chrome names the card network `network`; older versions call it `type`
+        let network = if super::has_column(&conn, "masked_credit_cards", "network") {
+            "network".to_string()
+        } else {
+            optional_column(&conn, "masked_credit_cards", "type")
+        };
+        let (use_count, use_date, join) = if table_exists(&conn, "server_card_metadata")? {
+            (
+                "s.use_count",
+                "s.use_date",
+                "LEFT JOIN server_card_metadata s ON s.id = m.id",
+            )
+        } else {
+            ("NULL", "NULL", "")
+        };
+        let mut stmt = conn.prepare(&format!(
+            "SELECT m.id, m.name_on_card, {network}, m.last_four, m.exp_month, m.exp_year, \
+                    {}, {use_count}, {use_date}, NULL, 'Server (masked)' \
+             FROM masked_credit_cards m {join}",
+            optional_column(&conn, "masked_credit_cards", "nickname"),
+        ))?;
+        let rows = stmt.query_map([], card_row)?;
+        cards.extend(collect_rows(rows, &db_str));
+    }
+
+    let mut entries: Vec<CreditCardEntry> = cards
+        .into_iter()
+        .map(|row| {
+            let (
+                guid,
+                name,
+                network,
+                last_four,
+                month,
+                year,
+                nickname,
+                use_count,
+                use_date,
+                modified,
+                storage,
+            ) = row;
+            CreditCardEntry {
+                name_on_card: name.unwrap_or_default(),
+                network: network.unwrap_or_default(),
+                last_four: last_four.unwrap_or_default(),
+                expiration_month: month.filter(|m| (1..=12).contains(m)).map(|m| m as u32),
+                expiration_year: year.filter(|y| *y > 0).map(|y| y as u32),
+                nickname: nickname.unwrap_or_default(),
+                use_count: use_count.unwrap_or(0).max(0) as u32,
+                last_used: use_date.and_then(unix_seconds_to_datetime),
+                date_modified: modified.and_then(unix_seconds_to_datetime),
+                storage,
+                guid,
+                web_browser: browser.display_name().to_string(),
+                user_profile: username.to_string(),
+                browser_profile: String::new(),
+                source_file: db_str.clone(),
+            }
+        })
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.last_used));
+
+    Ok(entries)
+}
+
+type CardRow = (
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<i64>,
+    Option<i64>,
+    Option<String>,
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+    String,
+);
+
+fn card_row(row: &rusqlite::Row) -> rusqlite::Result<CardRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+        row.get(6)?,
+        row.get(7)?,
+        row.get(8)?,
+        row.get(9)?,
+        row.get(10)?,
+    ))
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    Ok(conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name=?1")?
+        .exists([table])?)
+}
+
+/// `column`, or `NULL` on Chrome versions whose `table` doesn't have it.
+fn optional_column(conn: &Connection, table: &str, column: &str) -> String {
+    if super::has_column(conn, table, column) {
+        column.to_string()
+    } else {
+        "NULL".to_string()
+    }
+}
+
+/// Non-empty `column` values of `table`, grouped by `guid` in row order. Empty when the
+/// table (which older Chrome versions lack) or the column is missing.
+fn values_by_guid(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    db_str: &str,
+) -> Result<HashMap<String, Vec<String>>> {
+    let mut by_guid: HashMap<String, Vec<String>> = HashMap::new();
+    if !table_exists(conn, table)? || !super::has_column(conn, table, column) {
+        return Ok(by_guid);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT guid, {column} FROM {table} ORDER BY rowid"
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+    })?;
+    for (guid, value) in collect_rows(rows, db_str) {
+        match value {
+            Some(v) if !v.is_empty() => by_guid.entry(guid).or_default().push(v),
+            _ => {}
+        }
+    }
+    Ok(by_guid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn web_data(dir: &Path) -> std::path::PathBuf {
+        let db = dir.join("Web Data");
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE autofill (name VARCHAR, value VARCHAR, value_lower VARCHAR,
+                 date_created INTEGER, date_last_used INTEGER, count INTEGER);
+             CREATE TABLE autofill_profiles (guid VARCHAR PRIMARY KEY, company_name VARCHAR,
+                 street_address VARCHAR, dependent_locality VARCHAR, city VARCHAR,
+                 state VARCHAR, zipcode VARCHAR, sorting_code VARCHAR, country_code VARCHAR,
+                 date_modified INTEGER NOT NULL DEFAULT 0, origin VARCHAR DEFAULT '',
+                 language_code VARCHAR, use_count INTEGER NOT NULL DEFAULT 0,
+                 use_date INTEGER NOT NULL DEFAULT 0);
+             CREATE TABLE autofill_profile_names (guid VARCHAR, first_name VARCHAR,
+                 middle_name VARCHAR, last_name VARCHAR, full_name VARCHAR);
+             CREATE TABLE autofill_profile_emails (guid VARCHAR, email VARCHAR);
+             CREATE TABLE autofill_profile_phones (guid VARCHAR, number VARCHAR);
+             CREATE TABLE credit_cards (guid VARCHAR PRIMARY KEY, name_on_card VARCHAR,
+                 expiration_month INTEGER, expiration_year INTEGER,
+                 card_number_encrypted BLOB, date_modified INTEGER NOT NULL DEFAULT 0,
+                 origin VARCHAR DEFAULT '', use_count INTEGER NOT NULL DEFAULT 0,
+                 use_date INTEGER NOT NULL DEFAULT 0, billing_address_id VARCHAR,
+                 nickname VARCHAR);
+             CREATE TABLE masked_credit_cards (id VARCHAR, name_on_card VARCHAR,
+                 network VARCHAR, last_four VARCHAR, exp_month INTEGER DEFAULT 0,
+                 exp_year INTEGER DEFAULT 0, bank_name VARCHAR, nickname VARCHAR);
+             CREATE TABLE server_card_metadata (id VARCHAR NOT NULL, use_count INTEGER,
+                 use_date INTEGER, billing_address_id VARCHAR);
+
+             INSERT INTO autofill_profiles (guid, company_name, street_address, city, state,
+                 zipcode, country_code, date_modified, use_count, use_date)
+                 VALUES ('p1', 'Acme', '1 Main St\nApt 2', 'Springfield', 'IL', '62701',
+                         'US', 1700000000, 4, 1705305600);
+             INSERT INTO autofill_profile_names (guid, full_name) VALUES ('p1', 'Jane Doe');
+             INSERT INTO autofill_profile_emails VALUES ('p1', 'jane@example.com');
+             INSERT INTO autofill_profile_emails VALUES ('p1', 'jd@work.example');
+             INSERT INTO autofill_profile_phones VALUES ('p1', '+1 555 0100');
+
+             INSERT INTO credit_cards (guid, name_on_card, expiration_month, expiration_year,
+                 card_number_encrypted, date_modified, use_count, use_date, nickname)
+                 VALUES ('c1', 'Jane Doe', 7, 2027, x'763130414243', 1700000000, 2,
+                         1705219200, 'Travel');
+             INSERT INTO masked_credit_cards (id, name_on_card, network, last_four,
+                 exp_month, exp_year)
+                 VALUES ('s1', 'J DOE', 'visa', '4242', 12, 2026);
+             INSERT INTO server_card_metadata VALUES ('s1', 9, 1705305600, '');",
+        )
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_extract_address_with_names_emails_phones() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = web_data(dir.path());
+
+        let entries = extract(&db, "alice", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries.len(), 1);
+        let p = &entries[0];
+        assert_eq!(p.full_name, "Jane Doe");
+        assert_eq!(p.company_name, "Acme");
+        assert_eq!(p.street_address, "1 Main St\nApt 2");
+        assert_eq!((p.city.as_str(), p.state.as_str()), ("Springfield", "IL"));
+        assert_eq!(p.zipcode, "62701");
+        assert_eq!(p.emails, "jane@example.com; jd@work.example");
+        assert_eq!(p.phones, "+1 555 0100");
+        assert_eq!(p.use_count, 4);
+        assert_eq!(
+            p.last_used.unwrap().format("%Y-%m-%d").to_string(),
+            "2024-01-15"
+        );
+        assert_eq!(p.user_profile, "alice");
+    }
+
+    #[test]
+    fn test_extract_card_metadata_without_numbers() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = web_data(dir.path());
+
+        let cards = extract_credit_cards(&db, "alice", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(cards.len(), 2);
+        // Most recently used first
+        assert_eq!(cards[0].storage, "Server (masked)");
+        assert_eq!(cards[0].network, "visa");
+        assert_eq!(cards[0].last_four, "4242");
+        assert_eq!(cards[0].use_count, 9);
+        assert_eq!(
+            (cards[0].expiration_month, cards[0].expiration_year),
+            (Some(12), Some(2026))
+        );
+        assert_eq!(cards[1].storage, "Local");
+        assert_eq!(cards[1].name_on_card, "Jane Doe");
+        assert_eq!(cards[1].nickname, "Travel");
+        assert_eq!(cards[1].last_four, "");
+        assert_eq!(cards[1].expiration_month, Some(7));
+
+        // The encrypted number must not leak into any field
+        let out = dir.path().join("cards.csv");
+        crate::output::write_credit_cards_csv(&cards, &out, "%Y-%m-%d", None).unwrap();
+        let csv = std::fs::read_to_string(out).unwrap();
+        assert!(!csv.contains("v10ABC"));
+        assert!(!csv.to_lowercase().contains("cvv"));
+    }
+
+    #[test]
+    fn test_missing_tables_are_empty() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("Web Data");
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch("CREATE TABLE autofill (name VARCHAR, value VARCHAR);")
+            .unwrap();
+        drop(conn);
+        assert!(extract(&db, "", None).unwrap().is_empty());
+        assert!(extract_credit_cards(&db, "", None).unwrap().is_empty());
+    }
+}
@@ -2,7 +2,9 @@ use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::path::Path;
 
-use super::{chrome_time_to_datetime, detect_chromium_browser, BookmarkEntry, BrowserType};
+use super::{
+    chrome_time_to_datetime, detect_chromium_browser, stable_record_id, BookmarkEntry, BrowserType,
+};
 
 #[derive(Deserialize)]
 struct BookmarksRoot {
@@ -24,10 +26,19 @@ struct BookmarkNode {
     #[serde(default)]
     date_last_used: String,
     #[serde(default)]
+    date_modified: String,
+    #[serde(default)]
+    meta_info: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    sync_transaction_version: String,
+    #[serde(default)]
     id: String,
+    #[serde(default)]
+    guid: String,
 }
 
-/// Extract bookmarks from a Chrome/Chromium `Bookmarks` JSON file.
+/// Extract bookmarks from a Chrome/Chromium `Bookmarks` JSON file, or its `Bookmarks.bak`
+/// copy of the previous state (same format).
 pub fn extract(
     file_path: &Path,
     username: &str,
@@ -36,7 +47,7 @@ pub fn extract(
     let file_str = file_path.to_string_lossy().to_string();
     let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&file_str));
 
-    let data = std::fs::read_to_string(file_path)
+    let data = std::fs::read_to_string(super::long_path(file_path))
         .with_context(|| format!("Failed to read Bookmarks file: {}", file_str))?;
 
     let root: BookmarksRoot = serde_json::from_str(&data)
@@ -76,15 +87,30 @@ fn walk_bookmarks(
     if node.node_type == "url" && !node.url.is_empty() {
         let date_added = parse_chrome_time_string(&node.date_added);
         let date_last_used = parse_chrome_time_string(&node.date_last_used);
+        let date_modified = parse_chrome_time_string(&node.date_modified);
+        let last_visited_desktop = node
+            .meta_info
+            .get("last_visited_desktop")
+            .and_then(|s| parse_chrome_time_string(s));
 
-        let record_id = node.id.parse::<i64>().unwrap_or(0);
+        // Ids are numeric in Chrome's own files; hand-edited or third-party ones may
+        // carry only a GUID, or neither
+        let record_id = match node.id.parse::<i64>() {
+            Ok(id) => id,
+            Err(_) if !node.guid.is_empty() => stable_record_id(&[&node.guid]),
+            Err(_) => stable_record_id(&[&node.url, &node.date_added, folder_path, &node.name]),
+        };
 
         entries.push(BookmarkEntry {
             url: node.url.clone(),
             title: node.name.clone(),
             date_added,
             date_last_used,
+            date_modified,
+            last_visited_desktop,
+            sync_transaction_version: node.sync_transaction_version.clone(),
             folder_path: folder_path.to_string(),
+            status: String::new(),
             web_browser: browser.display_name().to_string(),
             user_profile: username.to_string(),
             browser_profile: String::new(),
@@ -107,6 +133,24 @@ fn walk_bookmarks(
     }
 }
 
+/// `status` of backup bookmarks that are no longer in the live file.
+pub const RECENTLY_REMOVED: &str = "Recently Removed";
+
+/// Reduce `backup` (from `Bookmarks.bak`) to the bookmarks missing from `live`, marking
+/// them [`RECENTLY_REMOVED`]. A bookmark is matched by URL and date added, so renaming
+/// or moving it between saves doesn't count as a removal. Returns the number kept.
+pub fn diff_backup(backup: &mut Vec<BookmarkEntry>, live: &[BookmarkEntry]) -> usize {
+    let keys: std::collections::HashSet<_> = live
+        .iter()
+        .map(|e| (e.url.as_str(), e.date_added))
+        .collect();
+    backup.retain(|e| !keys.contains(&(e.url.as_str(), e.date_added)));
+    for e in backup.iter_mut() {
+        e.status = RECENTLY_REMOVED.to_string();
+    }
+    backup.len()
+}
+
 /// Parse a Chrome timestamp stored as a string (microseconds since 1601-01-01).
 fn parse_chrome_time_string(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
     if s.is_empty() || s == "0" {
@@ -114,3 +158,113 @@ fn parse_chrome_time_string(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
     }
     s.parse::<i64>().ok().and_then(chrome_time_to_datetime)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r#"{
+        "checksum": "0",
+        "roots": {
+            "bookmark_bar": {
+                "children": [
+                    {
+                        "date_added": "13245010621000000",
+                        "date_last_used": "0",
+                        "date_modified": "13246010621000000",
+                        "id": "5",
+                        "meta_info": { "last_visited_desktop": "13247010621000000" },
+                        "name": "Synced",
+                        "sync_transaction_version": "42",
+                        "type": "url",
+                        "url": "https://example.com/synced"
+                    },
+                    {
+                        "date_added": "13245010622000000",
+                        "id": "6",
+                        "name": "Local",
+                        "type": "url",
+                        "url": "https://example.com/local"
+                    }
+                ],
+                "date_added": "13245010620000000",
+                "date_modified": "13246010621000000",
+                "id": "1",
+                "name": "Bookmarks bar",
+                "type": "folder"
+            }
+        },
+        "version": 1
+    }"#;
+
+    #[test]
+    fn test_date_modified_and_sync_meta() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("Bookmarks");
+        std::fs::write(&path, FIXTURE).unwrap();
+
+        let entries = extract(&path, "user", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let synced = &entries[0];
+        assert_eq!(synced.url, "https://example.com/synced");
+        assert_eq!(
+            synced.date_modified.unwrap().format("%Y-%m-%d").to_string(),
+            "2020-10-01"
+        );
+        assert!(synced.last_visited_desktop.is_some());
+        assert_eq!(synced.sync_transaction_version, "42");
+        assert!(synced.date_last_used.is_none());
+
+        let local = &entries[1];
+        assert!(local.date_modified.is_none());
+        assert!(local.last_visited_desktop.is_none());
+        assert!(local.sync_transaction_version.is_empty());
+    }
+
+    #[test]
+    fn test_record_id_without_numeric_id() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("Bookmarks");
+        let json = FIXTURE
+            .replace(
+                r#""id": "5","#,
+                r#""guid": "0bc5d13f-2cba-5d74-951f-3f233fe6c908","#,
+            )
+            .replace(r#""id": "6","#, "");
+        std::fs::write(&path, json).unwrap();
+
+        let first = extract(&path, "user", Some(BrowserType::Chrome)).unwrap();
+        let second = extract(&path, "user", Some(BrowserType::Chrome)).unwrap();
+        let ids: Vec<i64> = first.iter().map(|e| e.record_id).collect();
+        assert_eq!(ids, second.iter().map(|e| e.record_id).collect::<Vec<_>>());
+        assert!(ids.iter().all(|&id| id > 0));
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(
+            ids[0],
+            stable_record_id(&["0bc5d13f-2cba-5d74-951f-3f233fe6c908"])
+        );
+    }
+
+    #[test]
+    fn test_backup_diff_flags_removed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let bak = dir.path().join("Bookmarks.bak");
+        std::fs::write(&bak, FIXTURE).unwrap();
+        // Live state: "Synced" was deleted, "Local" renamed, and a new one added
+        let live = dir.path().join("Bookmarks");
+        let live_json = FIXTURE
+            .replace("https://example.com/synced", "https://example.com/new")
+            .replace(r#""name": "Local""#, r#""name": "Local (renamed)""#);
+        std::fs::write(&live, live_json).unwrap();
+
+        let live = extract(&live, "user", Some(BrowserType::Chrome)).unwrap();
+        let mut backup = extract(&bak, "user", Some(BrowserType::Chrome)).unwrap();
+        assert!(backup[0].source_file.ends_with("Bookmarks.bak"));
+
+        assert_eq!(diff_backup(&mut backup, &live), 1);
+        assert_eq!(backup[0].url, "https://example.com/synced");
+        assert_eq!(backup[0].status, RECENTLY_REMOVED);
+        assert!(live.iter().all(|e| e.status.is_empty()));
+    }
+}
@@ -1,9 +1,10 @@
-use anyhow::{Context, Result};
-use rusqlite::Connection;
+use anyhow::Result;
 use std::path::Path;
 
-use super::chrome::copy_db_to_temp;
-use super::{chrome_time_to_datetime, detect_chromium_browser, BrowserType, CookieEntry};
+use super::chrome::open_db;
+use super::{
+    chrome_time_to_datetime, collect_rows, detect_chromium_browser, BrowserType, CookieEntry,
+};
 
 fn samesite_name(val: i32) -> &'static str {
     match val {
@@ -24,10 +25,7 @@ pub fn extract(
     let db_str = db_path.to_string_lossy().to_string();
     let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&db_str));
 
-    let (_tmp_dir, tmp_db) = copy_db_to_temp(db_path, "Cookies")?;
-
-    let conn = Connection::open(&tmp_db)
-        .with_context(|| format!("Failed to open database: {}", db_str))?;
+    let (conn, _tmp_dir) = open_db(db_path, "Cookies")?;
 
     let table_exists: bool = conn
         .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='cookies'")?
@@ -37,9 +35,7 @@ pub fn extract(
     }
 
     // Check which columns exist — Chrome schema has changed over versions
-    let has_is_persistent: bool = conn
-        .prepare("SELECT is_persistent FROM cookies LIMIT 0")
-        .is_ok();
+    let has_is_persistent = super::has_column(&conn, "cookies", "is_persistent");
 
     let query = if has_is_persistent {
         "SELECT rowid, host_key, name, path, value, \
@@ -75,7 +71,7 @@ pub fn extract(
     })?;
 
     let mut entries = Vec::new();
-    for row in rows {
+    for row in collect_rows(rows, &db_str) {
         let (
             rowid,
             host_key,
@@ -89,7 +85,7 @@ pub fn extract(
             is_httponly,
             is_persistent,
             samesite,
-        ) = row?;
+        ) = row;
 
         let creation_time = match chrome_time_to_datetime(creation_utc) {
             Some(dt) => dt,
@@ -115,6 +111,8 @@ pub fn extract(
             is_httponly: is_httponly != 0,
             is_persistent: is_persistent != 0,
             same_site: samesite_name(samesite.unwrap_or(-1)).to_string(),
+            visited_host: false,
+            first_party: false,
             web_browser: browser.display_name().to_string(),
             user_profile: username.to_string(),
             browser_profile: String::new(),
@@ -1,9 +1,12 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use rusqlite::Connection;
+use std::collections::HashMap;
 use std::path::Path;
 
-use super::chrome::copy_db_to_temp;
-use super::{chrome_time_to_datetime, detect_chromium_browser, BrowserType, DownloadEntry};
+use super::chrome::open_db;
+use super::{
+    chrome_time_to_datetime, collect_rows, detect_chromium_browser, BrowserType, DownloadEntry,
+};
 
 fn download_state_name(state: i32) -> &'static str {
     match state {
@@ -15,8 +18,9 @@ fn download_state_name(state: i32) -> &'static str {
     }
 }
 
-fn danger_type_name(danger: i32) -> &'static str {
-    match danger {
+/// Chrome's `DownloadDangerType`. Values added after this list are kept as their number.
+fn danger_type_name(danger: i32) -> String {
+    let name = match danger {
         0 => "Not Dangerous",
         1 => "Dangerous File",
         2 => "Dangerous URL",
@@ -27,8 +31,62 @@ fn danger_type_name(danger: i32) -> &'static str {
         7 => "Dangerous Host",
         8 => "Potentially Unwanted",
         9 => "Allowlisted By Policy",
-        _ => "Unknown",
-    }
+        10 => "Async Scanning",
+        11 => "Blocked Password Protected",
+        12 => "Blocked Too Large",
+        13 => "Sensitive Content Warning",
+        14 => "Sensitive Content Block",
+        15 => "Deep Scanned Safe",
+        16 => "Deep Scanned Opened Dangerous",
+        17 => "Prompt For Scanning",
+        18 => "Blocked Unsupported Filetype",
+        19 => "Dangerous Account Compromise",
+        20 => "Deep Scanned Failed",
+        21 => "Prompt For Local Password Scanning",
+        22 => "Async Local Password Scanning",
+        23 => "Blocked Scan Failed",
+        _ => return danger.to_string(),
+    };
+    name.to_string()
+}
+
+/// Chrome's `DownloadInterruptReason`: why a download stopped, including blocks by
+/// Safe Browsing, antivirus or policy. Empty for none; unknown values as their number.
+fn interrupt_reason_name(reason: i32) -> String {
+    let name = match reason {
+        0 => "",
+        1 => "File Failed",
+        2 => "File Access Denied",
+        3 => "File No Space",
+        5 => "File Name Too Long",
+        6 => "File Too Large",
+        7 => "Virus Infected",
+        10 => "File Transient Error",
+        11 => "File Blocked",
+        12 => "Security Check Failed",
+        13 => "File Too Short",
+        14 => "File Hash Mismatch",
+        15 => "File Same As Source",
+        20 => "Network Failed",
+        21 => "Network Timeout",
+        22 => "Network Disconnected",
+        23 => "Network Server Down",
+        24 => "Network Invalid Request",
+        30 => "Server Failed",
+        31 => "Server No Range",
+        33 => "Server Bad Content",
+        34 => "Server Unauthorized",
+        35 => "Server Certificate Problem",
+        36 => "Server Forbidden",
+        37 => "Server Unreachable",
+        38 => "Server Content Length Mismatch",
+        39 => "Server Cross-Origin Redirect",
+        40 => "User Canceled",
+        41 => "User Shutdown",
+        50 => "Crash",
+        _ => return reason.to_string(),
+    };
+    name.to_string()
 }
 
 /// Extract downloads from a Chrome/Chromium `History` SQLite file.
@@ -40,10 +98,7 @@ pub fn extract(
     let db_str = db_path.to_string_lossy().to_string();
     let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&db_str));
 
-    let (_tmp_dir, tmp_db) = copy_db_to_temp(db_path, "History")?;
-
-    let conn = Connection::open(&tmp_db)
-        .with_context(|| format!("Failed to open database: {}", db_str))?;
+    let (conn, _tmp_dir) = open_db(db_path, "History")?;
 
     // Check if downloads table exists
     let table_exists: bool = conn
@@ -60,26 +115,44 @@ pub fn extract(
         )?
         .exists([])?;
 
-    let query = if chains_exist {
-        "SELECT d.id, d.current_path, d.target_path, \
-                d.start_time, d.end_time, d.received_bytes, d.total_bytes, \
-                d.state, d.danger_type, d.opened, \
-                d.referrer, d.tab_url, d.mime_type, d.original_mime_type, \
-                duc.url AS chain_url \
-         FROM downloads d \
-         LEFT JOIN downloads_url_chains duc ON d.id = duc.id AND duc.chain_index = 0 \
-         ORDER BY d.start_time ASC"
+    // guid and by_ext_id/by_ext_name were added in later Chrome versions
+    let optional_col = |col: &str| {
+        if super::has_column(&conn, "downloads", col) {
+            format!("d.{col}")
+        } else {
+            "NULL".to_string()
+        }
+    };
+    let extra_cols = format!(
+        "{}, {}, {}, {}",
+        optional_col("guid"),
+        optional_col("by_ext_id"),
+        optional_col("by_ext_name"),
+        optional_col("interrupt_reason")
+    );
+
+    // Title of the originating page from history: the tab's URL, else the referrer
+    let urls_exist: bool = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='urls'")?
+        .exists([])?;
+    let page_title = if urls_exist {
+        "(SELECT u.title FROM urls u \
+          WHERE u.url = COALESCE(NULLIF(d.tab_url, ''), d.referrer) LIMIT 1)"
     } else {
+        "NULL"
+    };
+
+    let query = format!(
         "SELECT d.id, d.current_path, d.target_path, \
                 d.start_time, d.end_time, d.received_bytes, d.total_bytes, \
                 d.state, d.danger_type, d.opened, \
                 d.referrer, d.tab_url, d.mime_type, d.original_mime_type, \
-                NULL AS chain_url \
+                {extra_cols}, {page_title} \
          FROM downloads d \
          ORDER BY d.start_time ASC"
-    };
+    );
 
-    let mut stmt = conn.prepare(query)?;
+    let mut stmt = conn.prepare(&query)?;
 
     let rows = stmt.query_map([], |row| {
         Ok((
@@ -98,11 +171,21 @@ pub fn extract(
             row.get::<_, Option<String>>(12)?,
             row.get::<_, Option<String>>(13)?,
             row.get::<_, Option<String>>(14)?,
+            row.get::<_, Option<String>>(15)?,
+            row.get::<_, Option<String>>(16)?,
+            row.get::<_, Option<i32>>(17)?,
+            row.get::<_, Option<String>>(18)?,
         ))
     })?;
 
+    let chains = if chains_exist {
+        read_url_chains(&conn, &db_str)?
+    } else {
+        HashMap::new()
+    };
+
     let mut entries = Vec::new();
-    for row in rows {
+    for row in collect_rows(rows, &db_str) {
         let (
             id,
             current_path,
@@ -118,8 +201,12 @@ pub fn extract(
             tab_url,
             mime_type,
             original_mime_type,
-            chain_url,
-        ) = row?;
+            guid,
+            by_ext_id,
+            by_ext_name,
+            interrupt_reason,
+            page_title,
+        ) = row;
 
         let start_time = match chrome_time_to_datetime(start_time_raw) {
             Some(dt) => dt,
@@ -127,8 +214,11 @@ pub fn extract(
         };
         let end_time = end_time_raw.and_then(|t| if t == 0 { None } else { Some(t) }).and_then(chrome_time_to_datetime);
 
-        // Use chain_url (actual download URL) if available, fall back to tab_url
-        let url = chain_url
+        // The last hop of the chain is where the file actually came from; without a
+        // chain, fall back to tab_url
+        let chain = chains.get(&id);
+        let url = chain
+            .and_then(|c| c.last().cloned())
             .or_else(|| tab_url.clone())
             .unwrap_or_default();
         if url.is_empty() {
@@ -137,6 +227,7 @@ pub fn extract(
 
         entries.push(DownloadEntry {
             url,
+            url_chain: chain.map(|c| c.join(" -> ")).unwrap_or_default(),
             target_path: target_path.unwrap_or_default(),
             current_path: current_path.unwrap_or_default(),
             start_time,
@@ -144,11 +235,19 @@ pub fn extract(
             received_bytes,
             total_bytes,
             state: download_state_name(state).to_string(),
-            danger_type: danger_type_name(danger_type).to_string(),
+            danger_type: danger_type_name(danger_type),
+            interrupt_reason: interrupt_reason
+                .map(interrupt_reason_name)
+                .unwrap_or_default(),
             mime_type: mime_type.or(original_mime_type).unwrap_or_default(),
             referrer: referrer.unwrap_or_default(),
             tab_url: tab_url.unwrap_or_default(),
+            page_title: page_title.unwrap_or_default(),
+            inferred_referrer: String::new(),
             opened: opened != 0,
+            guid: guid.unwrap_or_default(),
+            by_ext_id: by_ext_id.unwrap_or_default(),
+            by_ext_name: by_ext_name.unwrap_or_default(),
             web_browser: browser.display_name().to_string(),
             user_profile: username.to_string(),
             browser_profile: String::new(),
@@ -159,3 +258,176 @@ pub fn extract(
 
     Ok(entries)
 }
+
+/// Every download's `downloads_url_chains` URLs, by download id, in `chain_index` order.
+fn read_url_chains(conn: &Connection, db_str: &str) -> Result<HashMap<i64, Vec<String>>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, url FROM downloads_url_chains \
+         WHERE url IS NOT NULL AND url != '' \
+         ORDER BY id, chain_index ASC",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
+    let mut chains: HashMap<i64, Vec<String>> = HashMap::new();
+    for (id, url) in collect_rows(rows, db_str) {
+        chains.entry(id).or_default().push(url);
+    }
+    Ok(chains)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOWNLOADS_SCHEMA: &str = "id INTEGER PRIMARY KEY, current_path LONGVARCHAR,
+        target_path LONGVARCHAR, start_time INTEGER, end_time INTEGER,
+        received_bytes INTEGER, total_bytes INTEGER, state INTEGER, danger_type INTEGER,
+        opened INTEGER, referrer VARCHAR, tab_url VARCHAR, mime_type VARCHAR,
+        original_mime_type VARCHAR";
+
+    fn history_db(dir: &Path, extra_cols: &str, insert: &str) -> std::path::PathBuf {
+        let db = dir.join("History");
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch(&format!(
+            "CREATE TABLE downloads ({DOWNLOADS_SCHEMA}{extra_cols});
+             CREATE TABLE downloads_url_chains (id INTEGER, chain_index INTEGER, url LONGVARCHAR);
+             INSERT INTO downloads_url_chains VALUES (1, 0, 'https://cdn.example.com/tool.exe');
+             {insert}"
+        ))
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_extension_initiated_download() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = history_db(
+            dir.path(),
+            ", guid VARCHAR, by_ext_id VARCHAR, by_ext_name VARCHAR",
+            "INSERT INTO downloads VALUES (1, 'C:\\Users\\a\\Downloads\\tool.exe',
+                'C:\\Users\\a\\Downloads\\tool.exe', 13245010621000000, 13245010622000000,
+                100, 100, 1, 0, 0, '', '', 'application/octet-stream', '',
+                '6f1c8e2a-0b7d-4a55-9a43-6f0d3e5b2c11', 'abcdefghijklmnopabcdefghijklmnop',
+                'Free VPN');",
+        );
+
+        let entries = extract(&db, "a", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries.len(), 1);
+        let dl = &entries[0];
+        assert_eq!(dl.url, "https://cdn.example.com/tool.exe");
+        assert_eq!(dl.guid, "6f1c8e2a-0b7d-4a55-9a43-6f0d3e5b2c11");
+        assert_eq!(dl.by_ext_id, "abcdefghijklmnopabcdefghijklmnop");
+        assert_eq!(dl.by_ext_name, "Free VPN");
+        assert!(
+            crate::browsers::linearize_download(dl).contains("Initiated by extension: Free VPN")
+        );
+    }
+
+    #[test]
+    fn test_danger_type_and_interrupt_reason_names() {
+        assert_eq!(danger_type_name(0), "Not Dangerous");
+        assert_eq!(danger_type_name(9), "Allowlisted By Policy");
+        assert_eq!(danger_type_name(11), "Blocked Password Protected");
+        assert_eq!(danger_type_name(16), "Deep Scanned Opened Dangerous");
+        assert_eq!(danger_type_name(23), "Blocked Scan Failed");
+        // Values from newer Chrome builds stay informative
+        assert_eq!(danger_type_name(42), "42");
+
+        assert_eq!(interrupt_reason_name(0), "");
+        assert_eq!(interrupt_reason_name(11), "File Blocked");
+        assert_eq!(interrupt_reason_name(99), "99");
+
+        // A download blocked as malware: verdict and reason read from the database
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = history_db(
+            dir.path(),
+            ", interrupt_reason INTEGER",
+            "INSERT INTO downloads VALUES (1, '', 'C:\\Users\\a\\Downloads\\tool.exe',
+                13245010621000000, 13245010622000000, 0, 100, 3, 3, 0, '', '',
+                'application/octet-stream', '', 12);",
+        );
+        let entries = extract(&db, "a", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries[0].state, "Interrupted");
+        assert_eq!(entries[0].danger_type, "Dangerous Content");
+        assert_eq!(entries[0].interrupt_reason, "Security Check Failed");
+    }
+
+    #[test]
+    fn test_redirect_chain_kept_in_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = history_db(
+            dir.path(),
+            "",
+            "INSERT INTO downloads_url_chains VALUES (2, 2, 'https://cdn.example.net/setup.exe');
+             INSERT INTO downloads_url_chains VALUES (2, 0, 'https://bit.example/x1');
+             INSERT INTO downloads_url_chains VALUES (2, 1, 'https://ads.example/click?id=9');
+             INSERT INTO downloads VALUES (1, '/tmp/tool', '/tmp/tool', 13245010621000000, 0,
+                100, 100, 1, 0, 0, '', '', '', '');
+             INSERT INTO downloads VALUES (2, '/tmp/setup', '/tmp/setup', 13245010622000000, 0,
+                100, 100, 1, 0, 0, '', '', '', '');
+             INSERT INTO downloads VALUES (3, '/tmp/page', '/tmp/page', 13245010623000000, 0,
+                100, 100, 1, 0, 0, '', 'https://tab.example/', '', '');",
+        );
+
+        let entries = extract(&db, "a", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].url, "https://cdn.example.com/tool.exe");
+        assert_eq!(entries[0].url_chain, "https://cdn.example.com/tool.exe");
+        assert_eq!(entries[1].url, "https://cdn.example.net/setup.exe");
+        assert_eq!(
+            entries[1].url_chain,
+            "https://bit.example/x1 -> https://ads.example/click?id=9 \
+             -> https://cdn.example.net/setup.exe"
+        );
+        // No chain rows: the tab URL stands in and there is no chain
+        assert_eq!(entries[2].url, "https://tab.example/");
+        assert!(entries[2].url_chain.is_empty());
+    }
+
+    #[test]
+    fn test_old_schema_without_guid_columns() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = history_db(
+            dir.path(),
+            "",
+            "INSERT INTO downloads VALUES (1, '/tmp/tool', '/tmp/tool', 13245010621000000, 0,
+                100, 100, 1, 0, 0, '', '', '', '');",
+        );
+
+        let entries = extract(&db, "a", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].guid.is_empty());
+        assert!(entries[0].by_ext_id.is_empty());
+        assert!(entries[0].interrupt_reason.is_empty());
+        // No urls table to take a title from
+        assert!(entries[0].page_title.is_empty());
+    }
+
+    #[test]
+    fn test_page_title_from_history() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = history_db(
+            dir.path(),
+            "",
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR);
+             INSERT INTO urls VALUES (1, 'https://tab.example/', 'Tool - Download Page');
+             INSERT INTO urls VALUES (2, 'https://ref.example/', 'Referring Article');
+             INSERT INTO downloads_url_chains VALUES (2, 0, 'https://cdn.example.com/b.zip');
+             INSERT INTO downloads VALUES (1, '/tmp/tool', '/tmp/tool', 13245010621000000, 0,
+                100, 100, 1, 0, 0, 'https://ref.example/', 'https://tab.example/', '', '');
+             INSERT INTO downloads VALUES (2, '/tmp/b', '/tmp/b', 13245010622000000, 0,
+                100, 100, 1, 0, 0, 'https://ref.example/', '', '', '');
+             INSERT INTO downloads VALUES (3, '/tmp/c', '/tmp/c', 13245010623000000, 0,
+                100, 100, 1, 0, 0, '', 'https://unvisited.example/', '', '');",
+        );
+
+        let entries = extract(&db, "a", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries[0].page_title, "Tool - Download Page");
+        assert!(crate::browsers::linearize_download(&entries[0])
+            .contains("| Page: \"Tool - Download Page\""));
+        // No tab URL: the referrer's title
+        assert_eq!(entries[1].page_title, "Referring Article");
+        assert!(entries[2].page_title.is_empty());
+    }
+}
@@ -1,7 +1,87 @@
 use anyhow::{Context, Result};
+use log::debug;
+use std::collections::HashSet;
 use std::path::Path;
 
-use super::{chrome_time_to_datetime, detect_chromium_browser, BrowserType, ExtensionEntry};
+use super::chrome::open_db;
+use super::{
+    chrome_time_to_datetime, collect_rows, detect_chromium_browser, BrowserType, ExtensionEntry,
+};
+
+/// Per-extension LevelDB directories under the profile, one `<id>` subdirectory each.
+const SETTINGS_DIRS: &[&str] = &[
+    "Local Extension Settings",
+    "Sync Extension Settings",
+    "Managed Extension Settings",
+];
+
+/// Chrome extension ids are 32 characters in `a`-`p`.
+fn is_extension_id(s: &str) -> bool {
+    s.len() == 32 && s.bytes().all(|b| (b'a'..=b'p').contains(&b))
+}
+
+/// Ids of extensions with runtime data on disk in `profile_dir`: `chrome.storage`
+/// directories, IndexedDB origins, `Extension Cookies` hosts and keys in the
+/// `Extension State` LevelDB. Only presence is checked; values are not parsed.
+fn stored_state_ids(profile_dir: &Path) -> HashSet<String> {
+    let mut ids = HashSet::new();
+    let mut add_names = |dir: &Path, id_of: &dyn Fn(&str) -> Option<String>| {
+        if let Ok(rd) = std::fs::read_dir(super::long_path(dir)) {
+            for entry in rd.flatten() {
+                if let Some(id) = id_of(&entry.file_name().to_string_lossy()) {
+                    ids.insert(id);
+                }
+            }
+        }
+    };
+    for dir in SETTINGS_DIRS {
+        add_names(&profile_dir.join(dir), &|name| {
+            is_extension_id(name).then(|| name.to_string())
+        });
+    }
+    // IndexedDB/chrome-extension_<id>_0.indexeddb.leveldb (and .blob)
+    add_names(&profile_dir.join("IndexedDB"), &|name| {
+        let id = name.strip_prefix("chrome-extension_")?.split('_').next()?;
+        is_extension_id(id).then(|| id.to_string())
+    });
+
+    // Extension Cookies uses the regular cookies schema, keyed by extension id
+    let cookies = profile_dir.join("Extension Cookies");
+    if super::long_path(&cookies).is_file() {
+        match extension_cookie_hosts(&cookies) {
+            Ok(hosts) => ids.extend(hosts.into_iter().filter(|h| is_extension_id(h))),
+            Err(e) => debug!("{}: {:#}", cookies.display(), e),
+        }
+    }
+
+    // Extension State keys are "<id>.<pref>"; ids are found by a raw scan of the
+    // LevelDB files, so ones only present in compressed table blocks may be missed
+    if let Ok(rd) = std::fs::read_dir(super::long_path(&profile_dir.join("Extension State"))) {
+        for entry in rd.flatten() {
+            let data = match std::fs::read(entry.path()) {
+                Ok(d) => d,
+                Err(_) => continue,
+            };
+            for window in data.windows(33) {
+                if window[32] == b'.' {
+                    if let Ok(id) = std::str::from_utf8(&window[..32]) {
+                        if is_extension_id(id) {
+                            ids.insert(id.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    ids
+}
+
+fn extension_cookie_hosts(db_path: &Path) -> Result<Vec<String>> {
+    let (conn, _tmp_dir) = open_db(db_path, "Extension Cookies")?;
+    let mut stmt = conn.prepare("SELECT DISTINCT host_key FROM cookies")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    Ok(collect_rows(rows, &db_path.to_string_lossy()))
+}
 
 /// Extract extension metadata from a Chrome/Chromium `Preferences` JSON file.
 pub fn extract(
@@ -12,7 +92,7 @@ pub fn extract(
     let file_str = file_path.to_string_lossy().to_string();
     let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&file_str));
 
-    let data = std::fs::read_to_string(file_path)
+    let data = std::fs::read_to_string(super::long_path(file_path))
         .with_context(|| format!("Failed to read Preferences file: {}", file_str))?;
 
     let root: serde_json::Value = serde_json::from_str(&data)
@@ -27,6 +107,8 @@ pub fn extract(
         None => return Ok(Vec::new()),
     };
 
+    let stored_state = file_path.parent().map(stored_state_ids).unwrap_or_default();
+
     let mut entries = Vec::new();
     for (ext_id, ext_data) in settings {
         // Skip component extensions (built-in Chrome features)
@@ -97,6 +179,7 @@ pub fn extract(
             version: version.to_string(),
             description: description.to_string(),
             enabled: state == 1,
+            has_stored_state: stored_state.contains(ext_id),
             install_time,
             update_url: update_url.to_string(),
             permissions,
@@ -109,3 +192,51 @@ pub fn extract(
 
     Ok(entries)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WITH_STATE: &str = "aapocclcgogkmnckokdopfmhonfmgoek";
+    const STORAGE_ONLY: &str = "ghbmnnjooekpmoecnnnilnnbdlolhkhi";
+    const NO_STATE: &str = "nmmhkkegccagdldgiimedpiccmgmieda";
+
+    #[test]
+    fn test_stored_state_matches_extension_ids() {
+        let profile = tempfile::TempDir::new().unwrap();
+        let prefs = serde_json::json!({
+            "extensions": { "settings": {
+                WITH_STATE: { "state": 1, "manifest": { "name": "Slides", "version": "1.0" } },
+                STORAGE_ONLY: { "state": 1, "manifest": { "name": "Docs", "version": "1.0" } },
+                NO_STATE: { "state": 0, "manifest": { "name": "Payments", "version": "1.0" } },
+            }}
+        });
+        std::fs::write(profile.path().join("Preferences"), prefs.to_string()).unwrap();
+        std::fs::create_dir_all(
+            profile
+                .path()
+                .join("Local Extension Settings")
+                .join(STORAGE_ONLY),
+        )
+        .unwrap();
+        let state = profile.path().join("Extension State");
+        std::fs::create_dir_all(&state).unwrap();
+        std::fs::write(
+            state.join("000003.log"),
+            format!("\x01\x00{WITH_STATE}.last_seen\x00"),
+        )
+        .unwrap();
+
+        let entries = extract(&profile.path().join("Preferences"), "alice", None).unwrap();
+        let stored = |id: &str| {
+            entries
+                .iter()
+                .find(|e| e.extension_id == id)
+                .unwrap()
+                .has_stored_state
+        };
+        assert!(stored(WITH_STATE));
+        assert!(stored(STORAGE_ONLY));
+        assert!(!stored(NO_STATE));
+    }
+}
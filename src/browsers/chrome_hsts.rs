@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::info;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+use super::{detect_chromium_browser, stable_record_id, BrowserType, HistoryEntry};
+
+/// Extract HSTS host records from a Chrome/Chromium `TransportSecurity` JSON file.
+///
+/// Two layouts exist: current files (`"version": 2`) hold an `sts` array of objects with
+/// a `host` field, older ones are an object keyed by host. Chrome normally stores each
+/// host as a base64 SHA-256 digest, which can't be reversed, so hashed entries are only
+/// counted. Plaintext host names are emitted as history rows with
+/// `visit_type = "HSTS Record"`, timed by when the HSTS header was observed.
+pub fn extract(
+    file_path: &Path,
+    username: &str,
+    browser_override: Option<BrowserType>,
+) -> Result<Vec<HistoryEntry>> {
+    let file_str = file_path.to_string_lossy().to_string();
+    let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&file_str));
+
+    let data = std::fs::read_to_string(super::long_path(file_path))
+        .with_context(|| format!("Failed to read TransportSecurity file: {}", file_str))?;
+
+    let root: Value = serde_json::from_str(&data)
+        .with_context(|| format!("Failed to parse TransportSecurity JSON: {}", file_str))?;
+
+    let records: Vec<(&str, &Map<String, Value>)> = match root.get("sts").and_then(|v| v.as_array())
+    {
+        Some(sts) => sts
+            .iter()
+            .filter_map(|e| {
+                let e = e.as_object()?;
+                Some((e.get("host")?.as_str()?, e))
+            })
+            .collect(),
+        None => root
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(host, e)| Some((host.as_str(), e.as_object()?)))
+            .collect(),
+    };
+
+    let mut entries = Vec::new();
+    let mut hashed = 0usize;
+    for (host, record) in records {
+        if is_hashed_host(host) {
+            hashed += 1;
+            continue;
+        }
+        let visit_time = match record
+            .get("sts_observed")
+            .and_then(|v| v.as_f64())
+            .and_then(seconds_to_datetime)
+        {
+            Some(dt) => dt,
+            None => continue,
+        };
+
+        let mut details = Vec::new();
+        if let Some(expiry) = record
+            .get("expiry")
+            .and_then(|v| v.as_f64())
+            .and_then(seconds_to_datetime)
+        {
+            details.push(format!(
+                "HSTS expires {}",
+                expiry.format("%Y-%m-%d %H:%M:%S")
+            ));
+        }
+        let subdomains = ["sts_include_subdomains", "include_subdomains"]
+            .iter()
+            .any(|k| record.get(*k).and_then(|v| v.as_bool()) == Some(true));
+        if subdomains {
+            details.push("includes subdomains".to_string());
+        }
+
+        let url = format!("https://{host}/");
+        entries.push(HistoryEntry {
+            url_length: url.len(),
+            url,
+            title: details.join(", "),
+            visit_time,
+            visit_count: 0,
+            visited_from: String::new(),
+            app_id: String::new(),
+            journey: String::new(),
+            canonical_url: String::new(),
+            visit_type: "HSTS Record".to_string(),
+            visit_duration: String::new(),
+            view_time_ms: None,
+            key_presses: None,
+            hidden: false,
+            visited_link_confirmed: false,
+            web_browser: browser.display_name().to_string(),
+            user_profile: username.to_string(),
+            browser_profile: String::new(),
+            account: String::new(),
+            typed_count: 0,
+            history_file: file_str.clone(),
+            record_id: stable_record_id(&[host]),
+        });
+    }
+
+    if hashed > 0 {
+        info!(
+            "{}: {} hashed HSTS host(s) not recoverable (SHA-256), {} plaintext",
+            file_str,
+            hashed,
+            entries.len()
+        );
+    }
+
+    entries.sort_by_key(|e| e.visit_time);
+    Ok(entries)
+}
+
+/// Chrome's hashed hosts are base64-encoded SHA-256 digests: 44 chars ending in `=`.
+fn is_hashed_host(host: &str) -> bool {
+    host.len() == 44
+        && host.ends_with('=')
+        && host
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'='))
+}
+
+/// HSTS timestamps are Unix seconds stored as doubles.
+fn seconds_to_datetime(seconds: f64) -> Option<DateTime<Utc>> {
+    if seconds <= 0.0 {
+        return None;
+    }
+    DateTime::from_timestamp_millis((seconds * 1000.0) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASHED: &str = "7SxHBxyDtsDbgBu9qgRCC0SiR5QzDuD0bJcB0e2K5Qk=";
+
+    fn write(dir: &tempfile::TempDir, json: &str) -> std::path::PathBuf {
+        let path = dir.path().join("TransportSecurity");
+        std::fs::write(&path, json).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_v2_sts_array() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let json = format!(
+            r#"{{"version": 2, "expect_ct": [], "sts": [
+                {{"host": "{HASHED}", "mode": "force-https", "sts_include_subdomains": true,
+                  "sts_observed": 1705305600.5, "expiry": 1736841600.0}},
+                {{"host": "login.example.com", "mode": "force-https",
+                  "sts_include_subdomains": true,
+                  "sts_observed": 1705305600.0, "expiry": 1736841600.0}}
+            ]}}"#
+        );
+        let path = write(&dir, &json);
+
+        let entries = extract(&path, "alice", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries.len(), 1);
+        let e = &entries[0];
+        assert_eq!(e.url, "https://login.example.com/");
+        assert_eq!(e.visit_type, "HSTS Record");
+        assert_eq!(
+            e.visit_time.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "2024-01-15 08:00:00"
+        );
+        assert!(e.title.contains("HSTS expires 2025-01-14"));
+        assert!(e.title.contains("includes subdomains"));
+    }
+
+    #[test]
+    fn test_legacy_keyed_object() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let json = format!(
+            r#"{{
+                "{HASHED}": {{"mode": "force-https", "sts_observed": 1705305600.0}},
+                "www.example.org": {{"mode": "force-https", "sts_observed": 1705219200.0,
+                                     "sts_include_subdomains": false}}
+            }}"#
+        );
+        let path = write(&dir, &json);
+
+        let entries = extract(&path, "alice", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].url, "https://www.example.org/");
+        assert!(!entries[0].title.contains("subdomains"));
+    }
+}
@@ -1,9 +1,10 @@
-use anyhow::{Context, Result};
-use rusqlite::Connection;
+use anyhow::Result;
 use std::path::Path;
 
-use super::chrome::copy_db_to_temp;
-use super::{chrome_time_to_datetime, detect_chromium_browser, BrowserType, KeywordSearchEntry};
+use super::chrome::open_db;
+use super::{
+    chrome_time_to_datetime, collect_rows, detect_chromium_browser, BrowserType, KeywordSearchEntry,
+};
 
 /// Extract keyword search terms from a Chrome/Chromium `History` SQLite file.
 pub fn extract(
@@ -14,10 +15,7 @@ pub fn extract(
     let db_str = db_path.to_string_lossy().to_string();
     let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&db_str));
 
-    let (_tmp_dir, tmp_db) = copy_db_to_temp(db_path, "History")?;
-
-    let conn = Connection::open(&tmp_db)
-        .with_context(|| format!("Failed to open database: {}", db_str))?;
+    let (conn, _tmp_dir) = open_db(db_path, "History")?;
 
     // Check if keyword_search_terms table exists
     let table_exists: bool = conn
@@ -50,8 +48,8 @@ pub fn extract(
     })?;
 
     let mut entries = Vec::new();
-    for row in rows {
-        let (keyword_id, url_id, term, normalized_term, url, title, last_visit_time) = row?;
+    for row in collect_rows(rows, &db_str) {
+        let (keyword_id, url_id, term, normalized_term, url, title, last_visit_time) = row;
 
         if term.is_empty() {
             continue;
@@ -1,9 +1,11 @@
-use anyhow::{Context, Result};
-use rusqlite::Connection;
+use anyhow::Result;
+use rusqlite::types::Value;
 use std::path::Path;
 
-use super::chrome::copy_db_to_temp;
-use super::{chrome_time_to_datetime, detect_chromium_browser, BrowserType, LoginEntry};
+use super::chrome::open_db;
+use super::{
+    chrome_time_to_datetime, collect_rows, detect_chromium_browser, BrowserType, LoginEntry,
+};
 
 /// Extract login metadata from a Chrome/Chromium `Login Data` SQLite file.
 ///
@@ -17,10 +19,7 @@ pub fn extract(
     let db_str = db_path.to_string_lossy().to_string();
     let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&db_str));
 
-    let (_tmp_dir, tmp_db) = copy_db_to_temp(db_path, "LoginData")?;
-
-    let conn = Connection::open(&tmp_db)
-        .with_context(|| format!("Failed to open database: {}", db_str))?;
+    let (conn, _tmp_dir) = open_db(db_path, "LoginData")?;
 
     let table_exists: bool = conn
         .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='logins'")?
@@ -29,22 +28,27 @@ pub fn extract(
         return Ok(Vec::new());
     }
 
-    // Check which columns exist (older Chromium versions lack date_password_modified)
-    let has_date_pw_modified: bool = conn
-        .prepare("SELECT date_password_modified FROM logins LIMIT 0")
-        .is_ok();
+    // Check which columns exist (older Chromium versions lack date_password_modified
+    // and federation_url; newer ones renamed blacklisted_by_user to blocklisted_by_user)
+    let column_or_null = |cols: &[&'static str]| {
+        cols.iter()
+            .copied()
+            .find(|c| super::has_column(&conn, "logins", c))
+            .unwrap_or("NULL")
+    };
 
-    let query = if has_date_pw_modified {
+    let query = format!(
         "SELECT rowid, origin_url, action_url, username_value, \
-                date_created, date_last_used, date_password_modified, times_used \
-         FROM logins ORDER BY date_created ASC"
-    } else {
-        "SELECT rowid, origin_url, action_url, username_value, \
-                date_created, date_last_used, NULL, times_used \
-         FROM logins ORDER BY date_created ASC"
-    };
+                date_created, date_last_used, {}, times_used, \
+                {}, {}, {} \
+         FROM logins ORDER BY date_created ASC",
+        column_or_null(&["date_password_modified"]),
+        column_or_null(&["signon_realm"]),
+        column_or_null(&["federation_url"]),
+        column_or_null(&["blacklisted_by_user", "blocklisted_by_user"]),
+    );
 
-    let mut stmt = conn.prepare(query)?;
+    let mut stmt = conn.prepare(&query)?;
 
     let rows = stmt.query_map([], |row| {
         Ok((
@@ -56,11 +60,14 @@ pub fn extract(
             row.get::<_, Option<i64>>(5)?,
             row.get::<_, Option<i64>>(6)?,
             row.get::<_, i32>(7)?,
+            text_or_blob(row.get::<_, Value>(8)?),
+            text_or_blob(row.get::<_, Value>(9)?),
+            row.get::<_, Option<i64>>(10)?.unwrap_or(0) != 0,
         ))
     })?;
 
     let mut entries = Vec::new();
-    for row in rows {
+    for row in collect_rows(rows, &db_str) {
         let (
             rowid,
             origin_url,
@@ -70,7 +77,10 @@ pub fn extract(
             date_last_used,
             date_password_modified,
             times_used,
-        ) = row?;
+            signon_realm,
+            federation_url,
+            blacklisted_by_user,
+        ) = row;
 
         if origin_url.is_empty() {
             continue;
@@ -84,6 +94,9 @@ pub fn extract(
             date_last_used: date_last_used.and_then(chrome_time_to_datetime),
             date_password_modified: date_password_modified.and_then(chrome_time_to_datetime),
             times_used: times_used as u32,
+            signon_realm,
+            federation_url,
+            blacklisted_by_user,
             web_browser: browser.display_name().to_string(),
             user_profile: username.to_string(),
             browser_profile: String::new(),
@@ -94,3 +107,57 @@ pub fn extract(
 
     Ok(entries)
 }
+
+/// `signon_realm` and `federation_url` are declared VARCHAR but some Chromium builds
+/// write them as BLOBs, so accept either.
+fn text_or_blob(value: Value) -> String {
+    match value {
+        Value::Text(s) => s,
+        Value::Blob(b) => String::from_utf8_lossy(&b).into_owned(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_federated_and_blacklisted_logins() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db_path = dir.path().join("Login Data");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE logins (origin_url VARCHAR NOT NULL, action_url VARCHAR,
+                username_value VARCHAR, password_value BLOB, signon_realm VARCHAR NOT NULL,
+                date_created INTEGER NOT NULL, blacklisted_by_user INTEGER NOT NULL,
+                times_used INTEGER, federation_url VARCHAR, date_last_used INTEGER,
+                date_password_modified INTEGER);
+             INSERT INTO logins VALUES ('https://app.example.com/login', '', 'alice@gmail.com',
+                X'', 'federation://app.example.com/accounts.google.com', 13350000000000000, 0,
+                3, 'https://accounts.google.com', 13350000000000000, 0);
+             INSERT INTO logins VALUES ('https://bank.example.com/', '', '', X'',
+                CAST('https://bank.example.com/' AS BLOB), 13350000100000000, 1, 0, NULL, 0, 0);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db_path, "alice", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let sso = &entries[0];
+        assert_eq!(sso.federation_url, "https://accounts.google.com");
+        assert_eq!(
+            sso.signon_realm,
+            "federation://app.example.com/accounts.google.com"
+        );
+        assert!(!sso.blacklisted_by_user);
+        assert!(crate::browsers::linearize_login(sso).contains("Federated Login"));
+
+        let never = &entries[1];
+        assert!(never.blacklisted_by_user);
+        assert_eq!(never.signon_realm, "https://bank.example.com/");
+        assert!(never.federation_url.is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::chrome::open_db;
+use super::{collect_rows, detect_chromium_browser, BrowserType, PredictorEntry};
+
+/// Extract omnibox predictions from a Chrome/Chromium `Network Action Predictor` file.
+///
+/// Each row pairs text typed into the address bar with a URL Chrome predicted for it,
+/// and counts how often the user did (hits) or didn't (misses) go on to open that URL.
+/// The rows outlive the history they were learned from, though they carry no time.
+/// A missing file or `network_action_predictor` table gives no entries.
+pub fn extract(
+    db_path: &Path,
+    username: &str,
+    browser_override: Option<BrowserType>,
+) -> Result<Vec<PredictorEntry>> {
+    if !super::long_path(db_path).is_file() {
+        return Ok(Vec::new());
+    }
+    let db_str = db_path.to_string_lossy().to_string();
+    let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&db_str));
+
+    let (conn, _tmp_dir) = open_db(db_path, "Network Action Predictor")?;
+
+    let table_exists: bool = conn
+        .prepare(
+            "SELECT name FROM sqlite_master WHERE type='table' \
+             AND name='network_action_predictor'",
+        )?
+        .exists([])?;
+    if !table_exists {
+        return Ok(Vec::new());
+    }
+
+    // Not every Chrome version keeps a consecutive-miss count
+    let consecutive = if super::has_column(
+        &conn,
+        "network_action_predictor",
+        "number_of_consecutive_misses",
+    ) {
+        "number_of_consecutive_misses"
+    } else {
+        "NULL"
+    };
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT user_text, url, number_of_hits, number_of_misses, {} \
+         FROM network_action_predictor \
+         ORDER BY number_of_hits DESC, user_text ASC",
+        consecutive
+    ))?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, Option<String>>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, Option<i64>>(2)?,
+            row.get::<_, Option<i64>>(3)?,
+            row.get::<_, Option<i64>>(4)?,
+        ))
+    })?;
+
+    let count = |n: Option<i64>| n.unwrap_or(0).max(0) as u32;
+    let mut entries = Vec::new();
+    for row in collect_rows(rows, &db_str) {
+        let (user_text, url, hits, misses, consecutive_misses) = row;
+
+        entries.push(PredictorEntry {
+            user_text: user_text.unwrap_or_default(),
+            url: url.unwrap_or_default(),
+            number_of_hits: count(hits),
+            number_of_misses: count(misses),
+            number_of_consecutive_misses: consecutive_misses.map(|n| n.max(0) as u32),
+            web_browser: browser.display_name().to_string(),
+            user_profile: username.to_string(),
+            browser_profile: String::new(),
+            source_file: db_str.clone(),
+        });
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_predictor() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("Network Action Predictor");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE network_action_predictor (id TEXT PRIMARY KEY, user_text TEXT,
+                 url TEXT, number_of_hits INTEGER, number_of_misses INTEGER);
+             INSERT INTO network_action_predictor
+                 VALUES ('a', 'gma', 'https://mail.google.com/', 7, 1);
+             INSERT INTO network_action_predictor
+                 VALUES ('b', 'wik', 'https://en.wikipedia.org/', 2, 3);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "alice", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].user_text, "gma");
+        assert_eq!(entries[0].url, "https://mail.google.com/");
+        assert_eq!(entries[0].number_of_hits, 7);
+        assert_eq!(entries[0].number_of_misses, 1);
+        assert_eq!(entries[0].number_of_consecutive_misses, None);
+        assert_eq!(entries[1].user_text, "wik");
+        assert_eq!(entries[1].web_browser, "Chrome");
+        assert_eq!(entries[1].user_profile, "alice");
+    }
+
+    #[test]
+    fn test_consecutive_misses_read_when_present() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("Network Action Predictor");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE network_action_predictor (id TEXT PRIMARY KEY, user_text TEXT,
+                 url TEXT, number_of_hits INTEGER, number_of_misses INTEGER,
+                 number_of_consecutive_misses INTEGER);
+             INSERT INTO network_action_predictor
+                 VALUES ('a', 'news', 'https://news.example/', 1, 4, 2);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "", None).unwrap();
+        assert_eq!(entries[0].number_of_consecutive_misses, Some(2));
+    }
+
+    #[test]
+    fn test_missing_file_or_table_is_empty() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("Network Action Predictor");
+        assert!(extract(&db, "alice", None).unwrap().is_empty());
+
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        conn.execute_batch("CREATE TABLE resource_prefetch_predictor_url (id INTEGER);")
+            .unwrap();
+        drop(conn);
+        assert!(extract(&db, "alice", None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,363 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::{chrome_time_to_datetime, detect_chromium_browser, BrowserSettingsEntry, BrowserType};
+
+/// Extract startup pages, homepage, default search engine and the synced account from a
+/// Chrome/Chromium `Preferences` JSON file.
+///
+/// These are common targets for browser hijackers, so a custom (non-prepopulated)
+/// search provider is called out in the details column.
+pub fn extract(
+    file_path: &Path,
+    username: &str,
+    browser_override: Option<BrowserType>,
+) -> Result<Vec<BrowserSettingsEntry>> {
+    let file_str = file_path.to_string_lossy().to_string();
+    let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&file_str));
+
+    let data = std::fs::read_to_string(super::long_path(file_path))
+        .with_context(|| format!("Failed to read Preferences file: {}", file_str))?;
+
+    let root: Value = serde_json::from_str(&data)
+        .with_context(|| format!("Failed to parse Preferences JSON: {}", file_str))?;
+
+    let mut entries = Vec::new();
+    let mut push = |setting: &str, value: String, details: String, last_modified| {
+        entries.push(BrowserSettingsEntry {
+            setting: setting.to_string(),
+            value,
+            details,
+            last_modified,
+            web_browser: browser.display_name().to_string(),
+            user_profile: username.to_string(),
+            browser_profile: String::new(),
+            source_file: file_str.clone(),
+        });
+    };
+
+    // ---- Startup ----
+    let session = root.get("session");
+    if let Some(mode) = session
+        .and_then(|s| s.get("restore_on_startup"))
+        .and_then(|v| v.as_i64())
+    {
+        let behavior = match mode {
+            0 => "Open Home Page",
+            1 => "Continue Where You Left Off",
+            4 => "Open Specific Pages",
+            5 => "Open New Tab Page",
+            _ => "Unknown",
+        };
+        push(
+            "Startup Behavior",
+            behavior.to_string(),
+            format!("restore_on_startup = {mode}"),
+            None,
+        );
+    }
+    let startup_urls = session
+        .and_then(|s| s.get("startup_urls"))
+        .and_then(|v| v.as_array());
+    for (i, url) in startup_urls.into_iter().flatten().enumerate() {
+        if let Some(url) = url.as_str() {
+            push(
+                "Startup URL",
+                url.to_string(),
+                format!("Startup page {}", i + 1),
+                None,
+            );
+        }
+    }
+
+    // ---- Homepage ----
+    if let Some(homepage) = root.get("homepage").and_then(|v| v.as_str()) {
+        let mut details = Vec::new();
+        if let Some(ntp) = root.get("homepage_is_newtabpage").and_then(|v| v.as_bool()) {
+            details.push(format!("Is New Tab Page: {ntp}"));
+        }
+        if let Some(show) = root
+            .get("browser")
+            .and_then(|b| b.get("show_home_button"))
+            .and_then(|v| v.as_bool())
+        {
+            details.push(format!("Home Button Shown: {show}"));
+        }
+        push("Homepage", homepage.to_string(), details.join("; "), None);
+    }
+
+    // ---- Default search provider ----
+    if let Some(data) = root
+        .get("default_search_provider_data")
+        .and_then(|d| d.get("template_url_data"))
+    {
+        let str_field = |key: &str| data.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+        let url = str_field("url");
+        if !url.is_empty() {
+            let prepopulate_id = data
+                .get("prepopulate_id")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0);
+            let mut details = vec![
+                format!("Name: {}", str_field("short_name")),
+                format!("Keyword: {}", str_field("keyword")),
+            ];
+            if prepopulate_id == 0 {
+                details.push("Custom (not a built-in engine)".to_string());
+            }
+            let last_modified = str_field("last_modified")
+                .parse::<i64>()
+                .ok()
+                .and_then(chrome_time_to_datetime);
+            push(
+                "Default Search Provider",
+                url.to_string(),
+                details.join("; "),
+                last_modified,
+            );
+        }
+    } else if let Some(legacy) = root.get("default_search_provider") {
+        // Older Chrome versions store the provider directly under `default_search_provider`
+        let str_field = |key: &str| legacy.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+        let url = str_field("search_url");
+        if !url.is_empty() {
+            push(
+                "Default Search Provider",
+                url.to_string(),
+                format!(
+                    "Name: {}; Keyword: {}",
+                    str_field("name"),
+                    str_field("keyword")
+                ),
+                None,
+            );
+        }
+    }
+
+    // ---- Signed-in / synced account ----
+    let accounts = root.get("account_info").and_then(|v| v.as_array());
+    let mut has_account_info = false;
+    for info in accounts.into_iter().flatten() {
+        let str_field = |key: &str| info.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+        let email = str_field("email");
+        if email.is_empty() {
+            continue;
+        }
+        has_account_info = true;
+        let mut details = Vec::new();
+        for (label, key) in [("Name", "full_name"), ("GAIA ID", "gaia"), ("Domain", "hd")] {
+            let value = str_field(key);
+            if !value.is_empty() && value != "NO_HOSTED_DOMAIN" {
+                details.push(format!("{label}: {value}"));
+            }
+        }
+        push(
+            "Synced Account",
+            email.to_string(),
+            details.join("; "),
+            None,
+        );
+    }
+    if !has_account_info {
+        if let Some(email) = last_username(&root) {
+            push(
+                "Synced Account",
+                email.to_string(),
+                "From google.services.last_username".to_string(),
+                None,
+            );
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Email of the account the profile is signed in with: the first `account_info` entry
+/// (Chrome and Edge), falling back to `google.services.last_username`.
+pub fn synced_account(root: &Value) -> Option<String> {
+    root.get("account_info")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|info| info.get("email").and_then(|v| v.as_str()))
+        .find(|email| !email.is_empty())
+        .or_else(|| last_username(root))
+        .map(str::to_string)
+}
+
+/// Read [`synced_account`] from a `Preferences` file. Missing or unreadable files give `None`.
+pub fn read_synced_account(prefs_path: &Path) -> Option<String> {
+    let data = std::fs::read_to_string(super::long_path(prefs_path)).ok()?;
+    let root: Value = serde_json::from_str(&data).ok()?;
+    synced_account(&root)
+}
+
+/// Accounts already read, by `Preferences` path. Live and archived history, and every
+/// History opened for referrers or cookies, share their profile's file.
+static ACCOUNTS: Mutex<BTreeMap<PathBuf, Option<String>>> = Mutex::new(BTreeMap::new());
+
+/// [`read_synced_account`], reading each profile's `Preferences` only once per run.
+pub fn profile_synced_account(prefs_path: &Path) -> Option<String> {
+    let cached = ACCOUNTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(prefs_path)
+        .cloned();
+    if let Some(account) = cached {
+        return account;
+    }
+    // Read outside the lock so other profiles aren't held up; a race reads twice at worst
+    let account = read_synced_account(prefs_path);
+    ACCOUNTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(prefs_path.to_path_buf(), account.clone());
+    account
+}
+
+fn last_username(root: &Value) -> Option<&str> {
+    let services = root.get("google").and_then(|g| g.get("services"))?;
+    ["last_username", "last_signed_in_username"]
+        .iter()
+        .filter_map(|k| services.get(*k).and_then(|v| v.as_str()))
+        .find(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r#"{
+        "browser": { "show_home_button": true },
+        "default_search_provider_data": {
+            "template_url_data": {
+                "keyword": "search.example.net",
+                "last_modified": "13245010621000000",
+                "prepopulate_id": 0,
+                "short_name": "Example Search",
+                "url": "https://search.example.net/q?s={searchTerms}&src=hp"
+            }
+        },
+        "extensions": { "settings": {} },
+        "homepage": "https://portal.example.net/",
+        "homepage_is_newtabpage": false,
+        "session": {
+            "restore_on_startup": 4,
+            "startup_urls": ["https://portal.example.net/", "https://news.example.org/"]
+        }
+    }"#;
+
+    #[test]
+    fn test_startup_urls_and_custom_search_provider() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("Preferences");
+        std::fs::write(&path, FIXTURE).unwrap();
+
+        let entries = extract(&path, "user", Some(BrowserType::Chrome)).unwrap();
+        let find = |setting: &str| {
+            entries
+                .iter()
+                .filter(|e| e.setting == setting)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(find("Startup Behavior")[0].value, "Open Specific Pages");
+
+        let startup: Vec<_> = find("Startup URL")
+            .iter()
+            .map(|e| e.value.as_str())
+            .collect();
+        assert_eq!(
+            startup,
+            vec!["https://portal.example.net/", "https://news.example.org/"]
+        );
+
+        let homepage = find("Homepage");
+        assert_eq!(homepage[0].value, "https://portal.example.net/");
+        assert!(homepage[0].details.contains("Is New Tab Page: false"));
+
+        let search = find("Default Search Provider");
+        assert_eq!(search.len(), 1);
+        assert_eq!(
+            search[0].value,
+            "https://search.example.net/q?s={searchTerms}&src=hp"
+        );
+        assert!(search[0].details.contains("Name: Example Search"));
+        assert!(search[0].details.contains("Custom"));
+        assert!(search[0].last_modified.is_some());
+
+        assert!(find("Synced Account").is_empty());
+        assert_eq!(read_synced_account(&path), None);
+    }
+
+    #[test]
+    fn test_synced_account() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("Preferences");
+        std::fs::write(
+            &path,
+            r#"{
+                "account_info": [{
+                    "account_id": "114477010212345678901",
+                    "email": "jdoe@example.com",
+                    "full_name": "Jane Doe",
+                    "gaia": "114477010212345678901",
+                    "hd": "example.com"
+                }],
+                "google": { "services": { "last_username": "old@example.com" } }
+            }"#,
+        )
+        .unwrap();
+
+        let entries = extract(&path, "user", Some(BrowserType::Chrome)).unwrap();
+        let accounts: Vec<_> = entries
+            .iter()
+            .filter(|e| e.setting == "Synced Account")
+            .collect();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].value, "jdoe@example.com");
+        assert!(accounts[0].details.contains("Name: Jane Doe"));
+        assert!(accounts[0].details.contains("Domain: example.com"));
+        assert_eq!(
+            read_synced_account(&path).as_deref(),
+            Some("jdoe@example.com")
+        );
+
+        // Older profiles only have the last signed-in username
+        std::fs::write(
+            &path,
+            r#"{"google": {"services": {"last_username": "old@example.com"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            read_synced_account(&path).as_deref(),
+            Some("old@example.com")
+        );
+    }
+
+    #[test]
+    fn test_profile_synced_account_reads_file_once() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("Preferences");
+        std::fs::write(
+            &path,
+            r#"{"account_info": [{"email": "jdoe@example.com"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            profile_synced_account(&path).as_deref(),
+            Some("jdoe@example.com")
+        );
+
+        // Later History files of the same profile reuse the first read
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            profile_synced_account(&path).as_deref(),
+            Some("jdoe@example.com")
+        );
+        assert_eq!(read_synced_account(&path), None);
+    }
+}
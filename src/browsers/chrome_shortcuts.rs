@@ -0,0 +1,113 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::chrome::open_db;
+use super::{
+    chrome_time_to_datetime, collect_rows, detect_chromium_browser, BrowserType, ShortcutEntry,
+};
+
+/// Extract omnibox shortcuts from a Chrome/Chromium `Shortcuts` SQLite file.
+///
+/// Each row pairs the text typed into the address bar with the suggestion the user
+/// picked for it, so it covers typed navigation that never produced a keyword search.
+pub fn extract(
+    db_path: &Path,
+    username: &str,
+    browser_override: Option<BrowserType>,
+) -> Result<Vec<ShortcutEntry>> {
+    let db_str = db_path.to_string_lossy().to_string();
+    let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&db_str));
+
+    let (conn, _tmp_dir) = open_db(db_path, "Shortcuts")?;
+
+    let table_exists: bool = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='omni_box_shortcuts'")?
+        .exists([])?;
+    if !table_exists {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT text, url, contents, number_of_hits, last_access_time \
+         FROM omni_box_shortcuts \
+         ORDER BY last_access_time ASC",
+    )?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, Option<String>>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<i64>>(3)?,
+            row.get::<_, Option<i64>>(4)?,
+        ))
+    })?;
+
+    let mut entries = Vec::new();
+    for row in collect_rows(rows, &db_str) {
+        let (text, url, contents, hits, last_access_time) = row;
+
+        entries.push(ShortcutEntry {
+            text: text.unwrap_or_default(),
+            url: url.unwrap_or_default(),
+            contents: contents.unwrap_or_default(),
+            number_of_hits: hits.unwrap_or(0).max(0) as u32,
+            last_access_time: last_access_time.and_then(chrome_time_to_datetime),
+            web_browser: browser.display_name().to_string(),
+            user_profile: username.to_string(),
+            browser_profile: String::new(),
+            source_file: db_str.clone(),
+        });
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_shortcuts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("Shortcuts");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        // 13245010621000000 = 2020-09-19 17:37:01 UTC
+        conn.execute_batch(
+            "CREATE TABLE omni_box_shortcuts (id VARCHAR PRIMARY KEY, text VARCHAR,
+                 fill_into_edit VARCHAR, url VARCHAR, contents VARCHAR,
+                 contents_class VARCHAR, description VARCHAR, description_class VARCHAR,
+                 transition INTEGER, type INTEGER, keyword VARCHAR,
+                 last_access_time INTEGER, number_of_hits INTEGER);
+             INSERT INTO omni_box_shortcuts (id, text, url, contents, last_access_time,
+                 number_of_hits)
+                 VALUES ('b', 'mail', 'https://mail.example.com/', 'mail.example.com',
+                     13245010621000000, 7);
+             INSERT INTO omni_box_shortcuts (id, text, url, contents, last_access_time,
+                 number_of_hits)
+                 VALUES ('a', 'ban', 'https://bank.example/login', 'bank.example/login',
+                     13245000000000000, 2);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "alice", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].text, "ban");
+
+        let mail = &entries[1];
+        assert_eq!(mail.text, "mail");
+        assert_eq!(mail.url, "https://mail.example.com/");
+        assert_eq!(mail.contents, "mail.example.com");
+        assert_eq!(mail.number_of_hits, 7);
+        assert_eq!(
+            mail.last_access_time
+                .unwrap()
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            "2020-09-19 17:37:01"
+        );
+        assert_eq!(mail.web_browser, "Chrome");
+        assert_eq!(mail.user_profile, "alice");
+    }
+}
@@ -0,0 +1,220 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+use log::debug;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::chrome::open_db;
+use super::{
+    chrome_time_to_datetime, collect_rows, detect_chromium_browser, BrowserType, TopSiteEntry,
+};
+
+/// Extract the new-tab "most visited" tiles from a Chrome/Chromium `Top Sites` file.
+///
+/// `top_sites` keeps only the URL, title and rank. When the profile's `History` still
+/// has `segments`/`segment_usage` rows for a site, each day counted there becomes its
+/// own row, so the ranking can be dated even after the visits themselves were pruned.
+/// Older profiles without a `top_sites` table give no entries.
+pub fn extract(
+    db_path: &Path,
+    username: &str,
+    browser_override: Option<BrowserType>,
+) -> Result<Vec<TopSiteEntry>> {
+    let db_str = db_path.to_string_lossy().to_string();
+    let browser = browser_override.unwrap_or_else(|| detect_chromium_browser(&db_str));
+
+    let (conn, _tmp_dir) = open_db(db_path, "Top Sites")?;
+
+    let table_exists: bool = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='top_sites'")?
+        .exists([])?;
+    if !table_exists {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare("SELECT url, title, url_rank FROM top_sites ORDER BY url_rank")?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, Option<i64>>(2)?,
+        ))
+    })?;
+
+    let history = db_path.with_file_name("History");
+    let usage = if super::long_path(&history).is_file() {
+        segment_usage(&history).unwrap_or_else(|e| {
+            debug!("No segment usage from {}: {}", history.display(), e);
+            HashMap::new()
+        })
+    } else {
+        HashMap::new()
+    };
+
+    let mut entries = Vec::new();
+    for row in collect_rows(rows, &db_str) {
+        let (url, title, rank) = row;
+
+        let entry = TopSiteEntry {
+            url_rank: rank.unwrap_or(-1),
+            title: title.unwrap_or_default(),
+            day: None,
+            visit_count: None,
+            web_browser: browser.display_name().to_string(),
+            user_profile: username.to_string(),
+            browser_profile: String::new(),
+            source_file: db_str.clone(),
+            url,
+        };
+        match usage.get(&entry.url) {
+            Some(days) => {
+                for &(day, count) in days {
+                    entries.push(TopSiteEntry {
+                        day: Some(day),
+                        visit_count: Some(count),
+                        ..entry.clone()
+                    });
+                }
+            }
+            None => entries.push(entry),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Per-day visit counts by URL from the `segment_usage` table of a `History` file,
+/// oldest day first. Missing tables give an empty map.
+fn segment_usage(history: &Path) -> Result<HashMap<String, Vec<(NaiveDate, u32)>>> {
+    let (conn, _tmp_dir) = open_db(history, "History")?;
+
+    let tables: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type='table' \
+         AND name IN ('segments', 'segment_usage', 'urls')",
+        [],
+        |row| row.get(0),
+    )?;
+    if tables < 3 {
+        return Ok(HashMap::new());
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT u.url, su.time_slot, su.visit_count \
+         FROM segment_usage su \
+         JOIN segments s ON s.id = su.segment_id \
+         JOIN urls u ON u.id = s.url_id \
+         ORDER BY su.time_slot ASC",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, Option<i64>>(2)?,
+        ))
+    })?;
+
+    let mut usage: HashMap<String, Vec<(NaiveDate, u32)>> = HashMap::new();
+    for row in collect_rows(rows, &history.to_string_lossy()) {
+        let (url, time_slot, count) = row;
+        // Slots are local midnight; the nearest UTC midnight is that calendar day
+        let day = match chrome_time_to_datetime(time_slot) {
+            Some(dt) => (dt + Duration::hours(12)).date_naive(),
+            None => continue,
+        };
+        usage
+            .entry(url)
+            .or_default()
+            .push((day, count.unwrap_or(0).max(0) as u32));
+    }
+    Ok(usage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_top_sites(dir: &Path) -> std::path::PathBuf {
+        let db = dir.join("Top Sites");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE meta (key LONGVARCHAR NOT NULL UNIQUE PRIMARY KEY, value LONGVARCHAR);
+             CREATE TABLE top_sites (url LONGVARCHAR NOT NULL PRIMARY KEY,
+                 url_rank INTEGER NOT NULL, title LONGVARCHAR NOT NULL);
+             INSERT INTO top_sites VALUES ('https://news.example/', 1, 'News');
+             INSERT INTO top_sites VALUES ('https://mail.example/', 0, 'Mail');",
+        )
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_extract_top_sites() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = write_top_sites(dir.path());
+
+        let entries = extract(&db, "alice", Some(BrowserType::Chrome)).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].url, "https://mail.example/");
+        assert_eq!(entries[0].title, "Mail");
+        assert_eq!(entries[0].url_rank, 0);
+        assert_eq!(entries[0].day, None);
+        assert_eq!(entries[1].url_rank, 1);
+        assert_eq!(entries[1].web_browser, "Chrome");
+        assert_eq!(entries[1].user_profile, "alice");
+    }
+
+    #[test]
+    fn test_segment_usage_adds_daily_counts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = write_top_sites(dir.path());
+        let conn = rusqlite::Connection::open(dir.path().join("History")).unwrap();
+        // 13244947200000000 = 2020-09-19 00:00:00 UTC; the second slot is a local
+        // midnight east of UTC (2020-09-19 22:00 UTC = 2020-09-20 00:00 UTC+2)
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR);
+             CREATE TABLE segments (id INTEGER PRIMARY KEY, name VARCHAR, url_id INTEGER);
+             CREATE TABLE segment_usage (id INTEGER PRIMARY KEY, segment_id INTEGER,
+                 time_slot INTEGER, visit_count INTEGER DEFAULT 0 NOT NULL);
+             INSERT INTO urls VALUES (7, 'https://mail.example/', 'Mail');
+             INSERT INTO segments VALUES (1, 'http://mail.example/', 7);
+             INSERT INTO segment_usage VALUES (1, 1, 13244947200000000, 4);
+             INSERT INTO segment_usage VALUES (2, 1, 13245026400000000, 9);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "alice", Some(BrowserType::Chrome)).unwrap();
+        let mail: Vec<_> = entries
+            .iter()
+            .filter(|e| e.url == "https://mail.example/")
+            .map(|e| (e.day.unwrap().to_string(), e.visit_count.unwrap()))
+            .collect();
+        assert_eq!(
+            mail,
+            vec![("2020-09-19".to_string(), 4), ("2020-09-20".to_string(), 9)]
+        );
+        // No segment data: one row without a day
+        let news: Vec<_> = entries
+            .iter()
+            .filter(|e| e.url == "https://news.example/")
+            .collect();
+        assert_eq!(news.len(), 1);
+        assert_eq!(news[0].visit_count, None);
+    }
+
+    #[test]
+    fn test_missing_top_sites_table_is_empty() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("Top Sites");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE thumbnails (url LONGVARCHAR PRIMARY KEY, url_rank INTEGER,
+                 title LONGVARCHAR, thumbnail BLOB);",
+        )
+        .unwrap();
+        drop(conn);
+
+        assert!(extract(&db, "alice", None).unwrap().is_empty());
+    }
+}
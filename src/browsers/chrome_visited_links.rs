@@ -0,0 +1,251 @@
+use anyhow::{Context, Result};
+use log::debug;
+use std::path::Path;
+
+use super::HistoryEntry;
+
+/// File signature at the start of a `Visited Links` file ("VLnk" as a little-endian u32).
+const SIGNATURE: &[u8] = b"VLnk";
+
+/// The only table layout read: 8-byte salted MD5 fingerprints. Older versions hashed
+/// differently and are skipped.
+const SUPPORTED_VERSION: u32 = 3;
+
+/// Signature, version, table length, used count (u32 LE each), then the 8-byte salt.
+const HEADER_LEN: usize = 24;
+
+/// Chrome's `Visited Links` table: a hash table of fingerprints of every URL the profile
+/// has visited, used to draw links as visited. Fingerprints can't be turned back into
+/// URLs, but a known URL can be looked up.
+#[derive(Debug, Clone)]
+pub struct VisitedLinks {
+    salt: [u8; 8],
+    /// Fingerprint slots; `0` is an empty slot
+    table: Vec<u64>,
+}
+
+impl VisitedLinks {
+    /// Read a `Visited Links` file. Gives `None` when the file is missing, too short,
+    /// or isn't a version this reader knows, so callers simply don't tag anything.
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        let path = &super::long_path(path);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let data = std::fs::read(path)
+            .with_context(|| format!("Failed to read Visited Links: {}", path.display()))?;
+        Ok(Self::parse(&data, &path.to_string_lossy()))
+    }
+
+    fn parse(data: &[u8], source: &str) -> Option<Self> {
+        let header = data.get(..HEADER_LEN)?;
+        if &header[..4] != SIGNATURE {
+            debug!("{}: not a Visited Links file", source);
+            return None;
+        }
+        let u32_at =
+            |i: usize| u32::from_le_bytes([header[i], header[i + 1], header[i + 2], header[i + 3]]);
+        let version = u32_at(4);
+        if version != SUPPORTED_VERSION {
+            debug!("{}: unsupported Visited Links version {}", source, version);
+            return None;
+        }
+        let length = u32_at(8) as usize;
+        let mut salt = [0u8; 8];
+        salt.copy_from_slice(&header[16..24]);
+
+        // A truncated file keeps the slots it has; lookups that probe past them miss
+        let table: Vec<u64> = data[HEADER_LEN..]
+            .chunks_exact(8)
+            .take(length)
+            .map(|c| u64::from_le_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]))
+            .collect();
+        if table.is_empty() {
+            return None;
+        }
+        Some(Self { salt, table })
+    }
+
+    /// Fingerprint of `url`: the first 8 bytes of MD5(salt || url), little-endian.
+    fn fingerprint(&self, url: &str) -> u64 {
+        let mut input = self.salt.to_vec();
+        input.extend_from_slice(url.as_bytes());
+        let digest = md5(&input);
+        u64::from_le_bytes([
+            digest[0], digest[1], digest[2], digest[3], digest[4], digest[5], digest[6], digest[7],
+        ])
+    }
+
+    /// Whether `url` (in Chrome's canonical form, as stored in `urls.url`) is in the
+    /// table. Slots are probed linearly from `fingerprint % length` until an empty one.
+    pub fn contains(&self, url: &str) -> bool {
+        let fingerprint = self.fingerprint(url);
+        if fingerprint == 0 {
+            return false;
+        }
+        let len = self.table.len();
+        let start = (fingerprint % len as u64) as usize;
+        for i in 0..len {
+            match self.table[(start + i) % len] {
+                0 => return false,
+                f if f == fingerprint => return true,
+                _ => {}
+            }
+        }
+        false
+    }
+}
+
+/// Set `visited_link_confirmed` on each entry whose URL is in the `Visited Links` file
+/// at `path`. Returns how many entries were confirmed; a missing or unrecognized file
+/// confirms none.
+pub fn tag_entries(entries: &mut [HistoryEntry], path: &Path) -> Result<usize> {
+    let links = match VisitedLinks::read(path)? {
+        Some(l) => l,
+        None => return Ok(0),
+    };
+    let mut confirmed = 0;
+    for entry in entries.iter_mut() {
+        entry.visited_link_confirmed = links.contains(&entry.url);
+        if entry.visited_link_confirmed {
+            confirmed += 1;
+        }
+    }
+    Ok(confirmed)
+}
+
+/// MD5 (RFC 1321) of `data`. Only used for Visited Links fingerprints, which is why it
+/// lives here rather than pulling in a crate.
+fn md5(data: &[u8]) -> [u8; 16] {
+    const S: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5,
+        9, 14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10,
+        15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+    // K[i] = floor(abs(sin(i + 1)) * 2^32)
+    let k: Vec<u32> = (0..64)
+        .map(|i| ((i as f64 + 1.0).sin().abs() * 4_294_967_296.0) as u32)
+        .collect();
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    let mut state: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+    for block in message.chunks_exact(64) {
+        let m: Vec<u32> = block
+            .chunks_exact(4)
+            .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+            .collect();
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let rotated = a
+                .wrapping_add(f)
+                .wrapping_add(k[i])
+                .wrapping_add(m[g])
+                .rotate_left(S[i]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(rotated);
+        }
+        state[0] = state[0].wrapping_add(a);
+        state[1] = state[1].wrapping_add(b);
+        state[2] = state[2].wrapping_add(c);
+        state[3] = state[3].wrapping_add(d);
+    }
+
+    let mut digest = [0u8; 16];
+    for (i, word) in state.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+/// A version-3 file holding `urls`, laid out the way Chrome writes it.
+#[cfg(test)]
+pub(crate) fn visited_links_file(salt: [u8; 8], length: usize, urls: &[&str]) -> Vec<u8> {
+    let mut links = VisitedLinks {
+        salt,
+        table: vec![0; length],
+    };
+    for url in urls {
+        let fingerprint = links.fingerprint(url);
+        let mut slot = (fingerprint % length as u64) as usize;
+        while links.table[slot] != 0 {
+            slot = (slot + 1) % length;
+        }
+        links.table[slot] = fingerprint;
+    }
+    let mut data = SIGNATURE.to_vec();
+    data.extend(SUPPORTED_VERSION.to_le_bytes());
+    data.extend((length as u32).to_le_bytes());
+    data.extend((urls.len() as u32).to_le_bytes());
+    data.extend(salt);
+    for f in &links.table {
+        data.extend(f.to_le_bytes());
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_md5_known_digests() {
+        assert_eq!(hex(&md5(b"")), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(
+            hex(&md5(b"The quick brown fox jumps over the lazy dog")),
+            "9e107d9d372bb6826bd81d3542a419d6"
+        );
+        // Padding spills into a second block
+        assert_eq!(hex(&md5(&[b'a'; 56])), "3b0c8ac703f828b04c6c197006d17218");
+    }
+
+    #[test]
+    fn test_contains_with_collisions() {
+        // A small table so the probe chains wrap around
+        let urls = [
+            "https://example.com/",
+            "https://example.org/a",
+            "https://rust-lang.org/",
+        ];
+        let data = visited_links_file(*b"saltsalt", 4, &urls);
+        let links = VisitedLinks::parse(&data, "test").unwrap();
+        for url in urls {
+            assert!(links.contains(url), "{url}");
+        }
+        assert!(!links.contains("https://example.net/"));
+    }
+
+    #[test]
+    fn test_missing_or_unknown_file_is_none() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("Visited Links");
+        assert!(VisitedLinks::read(&path).unwrap().is_none());
+
+        let mut data = visited_links_file([7; 8], 16, &["https://seen.example/"]);
+        std::fs::write(&path, &data).unwrap();
+        assert!(VisitedLinks::read(&path).unwrap().is_some());
+
+        data[4] = 2;
+        std::fs::write(&path, &data).unwrap();
+        assert!(VisitedLinks::read(&path).unwrap().is_none());
+        std::fs::write(&path, b"not a visited links file at all").unwrap();
+        assert!(VisitedLinks::read(&path).unwrap().is_none());
+    }
+}
@@ -1,11 +1,14 @@
 use anyhow::{Context, Result};
-use rusqlite::Connection;
+use std::collections::HashMap;
 use std::path::Path;
 use tempfile::TempDir;
 
-use super::{prtime_to_datetime, HistoryEntry};
+use super::{
+    collect_rows, fill_journeys, fill_visited_from, long_path, make_temp_dir, open_in_place,
+    open_temp_copy, prtime_to_datetime, HistoryEntry,
+};
 
-fn visit_type_name(visit_type: i32) -> &'static str {
+pub(crate) fn visit_type_name(visit_type: i32) -> &'static str {
     match visit_type {
         1 => "Link",
         2 => "Typed",
@@ -22,15 +25,16 @@ fn visit_type_name(visit_type: i32) -> &'static str {
 
 /// Copy a Firefox database to a temp directory (Firefox locks its DB).
 pub fn copy_db_to_temp(db_path: &Path, filename: &str) -> Result<(TempDir, std::path::PathBuf)> {
-    let tmp_dir = TempDir::new().context("Failed to create temp directory")?;
+    let tmp_dir = make_temp_dir()?;
     let tmp_db = tmp_dir.path().join(filename);
-    std::fs::copy(db_path, &tmp_db)
+    let src = long_path(db_path);
+    std::fs::copy(&src, &tmp_db)
         .with_context(|| format!("Failed to copy database: {}", db_path.display()))?;
 
     // Copy WAL/SHM if present
     for ext in &["-wal", "-shm"] {
         let aux_name = format!("{filename}{ext}");
-        let aux = db_path.parent().unwrap_or(Path::new(".")).join(&aux_name);
+        let aux = src.parent().unwrap_or(Path::new(".")).join(&aux_name);
         if aux.exists() {
             let _ = std::fs::copy(&aux, tmp_dir.path().join(&aux_name));
         }
@@ -39,18 +43,32 @@ pub fn copy_db_to_temp(db_path: &Path, filename: &str) -> Result<(TempDir, std::
     Ok((tmp_dir, tmp_db))
 }
 
+/// Open a Firefox database for reading: in place when [`open_in_place`] allows it,
+/// otherwise through a temp copy made by [`copy_db_to_temp`]. The `TempDir`, if any,
+/// must outlive the connection.
+pub fn open_db(db_path: &Path, filename: &str) -> Result<(rusqlite::Connection, Option<TempDir>)> {
+    if let Some(conn) = open_in_place(db_path) {
+        return Ok((conn, None));
+    }
+    let (tmp_dir, tmp_db) = copy_db_to_temp(db_path, filename)?;
+    let conn = open_temp_copy(&tmp_db)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+    Ok((conn, Some(tmp_dir)))
+}
+
 /// Extract browsing history from a Firefox `places.sqlite` file.
+///
+/// Visited From is the URL of the visit `from_visit` points at, when it still exists.
+/// On Firefox versions with `moz_places_metadata` (86+), visits also carry the page's
+/// foreground view time and key presses (see [`attach_metadata`]).
 pub fn extract(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
     let db_str = db_path.to_string_lossy().to_string();
 
-    let (_tmp_dir, tmp_db) = copy_db_to_temp(db_path, "places.sqlite")?;
-
-    let conn = Connection::open(&tmp_db)
-        .with_context(|| format!("Failed to open database: {}", db_str))?;
+    let (conn, _tmp_dir) = open_db(db_path, "places.sqlite")?;
 
     let mut stmt = conn.prepare(
         "SELECT p.url, p.title, v.visit_date, p.visit_count, \
-                v.from_visit, v.visit_type, p.id \
+                v.from_visit, v.visit_type, p.id, v.id \
          FROM moz_places p \
          JOIN moz_historyvisits v ON p.id = v.place_id \
          ORDER BY v.visit_date ASC",
@@ -65,12 +83,14 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
             row.get::<_, i64>(4)?,
             row.get::<_, i32>(5)?,
             row.get::<_, i64>(6)?,
+            row.get::<_, i64>(7)?,
         ))
     })?;
 
     let mut entries = Vec::new();
-    for row in rows {
-        let (url, title, visit_date, visit_count, _from_visit, visit_type, id) = row?;
+    let mut visits = Vec::new();
+    for row in collect_rows(rows, &db_str) {
+        let (url, title, visit_date, visit_count, from_visit, visit_type, id, visit_id) = row;
 
         if url.is_empty() {
             continue;
@@ -88,20 +108,82 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
             visit_time,
             visit_count: visit_count as u32,
             visited_from: String::new(),
+            app_id: String::new(),
+            journey: String::new(),
+            canonical_url: String::new(),
             visit_type: visit_type_name(visit_type).to_string(),
             visit_duration: String::new(),
+            view_time_ms: None,
+            key_presses: None,
+            hidden: false,
+            visited_link_confirmed: false,
             web_browser: "Firefox".to_string(),
             user_profile: username.to_string(),
             browser_profile: String::new(),
+            account: String::new(),
             typed_count: 0,
             history_file: db_str.clone(),
             record_id: id,
         });
+        visits.push((visit_id, from_visit));
     }
 
+    fill_visited_from(&mut entries, &visits);
+    fill_journeys(&mut entries, &visits);
+    attach_metadata(&conn, &mut entries, &db_str);
     Ok(entries)
 }
 
+/// Add `moz_places_metadata` engagement to the visits in `entries`.
+///
+/// Firefox writes one metadata row per page load, keyed by place rather than visit, so
+/// each row is credited to the visit of the same place closest to its `created_at`;
+/// rows landing on the same visit are summed. Nothing happens on older schemas.
+fn attach_metadata(conn: &rusqlite::Connection, entries: &mut [HistoryEntry], db_str: &str) {
+    let mut stmt = match conn.prepare(
+        "SELECT place_id, created_at, total_view_time, key_presses FROM moz_places_metadata",
+    ) {
+        Ok(stmt) => stmt,
+        Err(_) => return,
+    };
+    let rows = match stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, Option<i64>>(2)?,
+            row.get::<_, Option<i64>>(3)?,
+        ))
+    }) {
+        Ok(rows) => rows,
+        Err(_) => return,
+    };
+
+    // `record_id` is the moz_places id for Firefox rows
+    let mut by_place: HashMap<i64, Vec<usize>> = HashMap::new();
+    for (i, e) in entries.iter().enumerate() {
+        by_place.entry(e.record_id).or_default().push(i);
+    }
+
+    for (place_id, created_at, view_time, key_presses) in collect_rows(rows, db_str) {
+        let candidates = match by_place.get(&place_id) {
+            Some(c) => c,
+            None => continue,
+        };
+        // created_at is in milliseconds since the epoch
+        let nearest = candidates
+            .iter()
+            .copied()
+            .min_by_key(|&i| (entries[i].visit_time.timestamp_millis() - created_at).abs());
+        if let Some(i) = nearest {
+            let e = &mut entries[i];
+            let view_time = view_time.unwrap_or(0).max(0) as u64;
+            let key_presses = key_presses.unwrap_or(0).clamp(0, u32::MAX as i64) as u32;
+            e.view_time_ms = Some(e.view_time_ms.unwrap_or(0) + view_time);
+            e.key_presses = Some(e.key_presses.unwrap_or(0).saturating_add(key_presses));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -114,6 +196,80 @@ mod tests {
         assert_eq!(dt.format("%Y-%m-%d").to_string(), "2020-09-19");
     }
 
+    #[test]
+    fn test_places_metadata_engagement() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("places.sqlite");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        // Two visits to the same article a day apart, each with its own metadata row;
+        // the second page has no metadata at all
+        conn.execute_batch(
+            "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                      visit_count INTEGER);
+             CREATE TABLE moz_historyvisits (id INTEGER PRIMARY KEY, from_visit INTEGER,
+                                             place_id INTEGER, visit_date INTEGER,
+                                             visit_type INTEGER);
+             CREATE TABLE moz_places_metadata (id INTEGER PRIMARY KEY, place_id INTEGER,
+                                               referrer_place_id INTEGER, created_at INTEGER,
+                                               updated_at INTEGER, total_view_time INTEGER,
+                                               typing_time INTEGER, key_presses INTEGER,
+                                               scrolling_time INTEGER, scrolling_distance INTEGER,
+                                               document_type INTEGER);
+             INSERT INTO moz_places VALUES (1, 'https://docs.example/a', 'A', 2);
+             INSERT INTO moz_places VALUES (2, 'https://ads.example/', 'Ad', 1);
+             INSERT INTO moz_historyvisits VALUES (1, 0, 1, 1600480000000000, 1);
+             INSERT INTO moz_historyvisits VALUES (2, 0, 2, 1600480001000000, 1);
+             INSERT INTO moz_historyvisits VALUES (3, 0, 1, 1600566400000000, 1);
+             INSERT INTO moz_places_metadata VALUES (1, 1, NULL, 1600480000120, 1600480095000,
+                                                     94000, 12000, 57, 3000, 800, 0);
+             INSERT INTO moz_places_metadata VALUES (2, 1, NULL, 1600566400050, 1600566402000,
+                                                     1500, 0, 0, 0, 0, 0);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "user").unwrap();
+        let engagement: Vec<_> = entries
+            .iter()
+            .map(|e| (e.url.as_str(), e.view_time_ms, e.key_presses))
+            .collect();
+        assert_eq!(
+            engagement,
+            vec![
+                ("https://docs.example/a", Some(94000), Some(57)),
+                ("https://ads.example/", None, None),
+                ("https://docs.example/a", Some(1500), Some(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visited_from_resolves_from_visit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("places.sqlite");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        // Visit 3 came from a visit that has since been removed
+        conn.execute_batch(
+            "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                      visit_count INTEGER);
+             CREATE TABLE moz_historyvisits (id INTEGER PRIMARY KEY, from_visit INTEGER,
+                                             place_id INTEGER, visit_date INTEGER,
+                                             visit_type INTEGER);
+             INSERT INTO moz_places VALUES (1, 'https://news.example/', 'News', 1);
+             INSERT INTO moz_places VALUES (2, 'https://news.example/story', 'Story', 1);
+             INSERT INTO moz_places VALUES (3, 'https://video.example/clip', 'Clip', 1);
+             INSERT INTO moz_historyvisits VALUES (1, 0, 1, 1600480000000000, 2);
+             INSERT INTO moz_historyvisits VALUES (2, 1, 2, 1600480001000000, 1);
+             INSERT INTO moz_historyvisits VALUES (3, 99, 3, 1600480002000000, 1);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "user").unwrap();
+        let from: Vec<_> = entries.iter().map(|e| e.visited_from.as_str()).collect();
+        assert_eq!(from, vec!["", "https://news.example/", ""]);
+    }
+
     #[test]
     fn test_visit_type_names() {
         assert_eq!(visit_type_name(1), "Link");
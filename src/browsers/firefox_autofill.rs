@@ -1,18 +1,14 @@
-use anyhow::{Context, Result};
-use rusqlite::Connection;
+use anyhow::Result;
 use std::path::Path;
 
-use super::firefox::copy_db_to_temp;
-use super::{prtime_to_datetime, AutofillEntry};
+use super::firefox::open_db;
+use super::{collect_rows, prtime_to_datetime, AutofillEntry};
 
 /// Extract form history from a Firefox `formhistory.sqlite` file.
 pub fn extract(db_path: &Path, username: &str) -> Result<Vec<AutofillEntry>> {
     let db_str = db_path.to_string_lossy().to_string();
 
-    let (_tmp_dir, tmp_db) = copy_db_to_temp(db_path, "formhistory.sqlite")?;
-
-    let conn = Connection::open(&tmp_db)
-        .with_context(|| format!("Failed to open database: {}", db_str))?;
+    let (conn, _tmp_dir) = open_db(db_path, "formhistory.sqlite")?;
 
     let table_exists: bool = conn
         .prepare(
@@ -41,8 +37,8 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<AutofillEntry>> {
     })?;
 
     let mut entries = Vec::new();
-    for row in rows {
-        let (id, fieldname, value, times_used, first_used, last_used) = row?;
+    for row in collect_rows(rows, &db_str) {
+        let (id, fieldname, value, times_used, first_used, last_used) = row;
 
         if fieldname.is_empty() && value.is_empty() {
             continue;
@@ -1,19 +1,15 @@
-use anyhow::{Context, Result};
-use rusqlite::Connection;
+use anyhow::Result;
 use std::collections::HashMap;
 use std::path::Path;
 
-use super::firefox::copy_db_to_temp;
-use super::{prtime_to_datetime, BookmarkEntry};
+use super::firefox::open_db;
+use super::{collect_rows, prtime_to_datetime, BookmarkEntry};
 
 /// Extract bookmarks from a Firefox `places.sqlite` file.
 pub fn extract(db_path: &Path, username: &str) -> Result<Vec<BookmarkEntry>> {
     let db_str = db_path.to_string_lossy().to_string();
 
-    let (_tmp_dir, tmp_db) = copy_db_to_temp(db_path, "places.sqlite")?;
-
-    let conn = Connection::open(&tmp_db)
-        .with_context(|| format!("Failed to open database: {}", db_str))?;
+    let (conn, _tmp_dir) = open_db(db_path, "places.sqlite")?;
 
     // Check if moz_bookmarks table exists
     let table_exists: bool = conn
@@ -36,8 +32,8 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<BookmarkEntry>> {
     })?;
 
     let mut folders: HashMap<i64, (String, i64)> = HashMap::new();
-    for row in folder_rows {
-        let (id, title, parent) = row?;
+    for row in collect_rows(folder_rows, &db_str) {
+        let (id, title, parent) = row;
         folders.insert(id, (title.unwrap_or_default(), parent));
     }
 
@@ -64,21 +60,27 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<BookmarkEntry>> {
     })?;
 
     let mut entries = Vec::new();
-    for row in rows {
-        let (id, title, date_added, last_modified, parent_id, url) = row?;
+    for row in collect_rows(rows, &db_str) {
+        let (id, title, date_added, last_modified, parent_id, url) = row;
 
         if url.is_empty() {
             continue;
         }
 
         let folder_path = build_folder_path(parent_id, &folders);
+        let last_modified = last_modified.and_then(prtime_to_datetime);
 
         entries.push(BookmarkEntry {
             url,
             title: title.unwrap_or_default(),
             date_added: date_added.and_then(prtime_to_datetime),
-            date_last_used: last_modified.and_then(prtime_to_datetime),
+            // Firefox keeps no last-used time; this column has always shown lastModified
+            date_last_used: last_modified,
+            date_modified: last_modified,
+            last_visited_desktop: None,
+            sync_transaction_version: String::new(),
             folder_path,
+            status: String::new(),
             web_browser: "Firefox".to_string(),
             user_profile: username.to_string(),
             browser_profile: String::new(),
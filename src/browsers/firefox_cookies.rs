@@ -1,9 +1,8 @@
-use anyhow::{Context, Result};
-use rusqlite::Connection;
+use anyhow::Result;
 use std::path::Path;
 
-use super::firefox::copy_db_to_temp;
-use super::{prtime_to_datetime, unix_seconds_to_datetime, CookieEntry};
+use super::firefox::open_db;
+use super::{collect_rows, prtime_to_datetime, unix_seconds_to_datetime, CookieEntry};
 
 fn samesite_name(val: i32) -> &'static str {
     match val {
@@ -18,10 +17,7 @@ fn samesite_name(val: i32) -> &'static str {
 pub fn extract(db_path: &Path, username: &str) -> Result<Vec<CookieEntry>> {
     let db_str = db_path.to_string_lossy().to_string();
 
-    let (_tmp_dir, tmp_db) = copy_db_to_temp(db_path, "cookies.sqlite")?;
-
-    let conn = Connection::open(&tmp_db)
-        .with_context(|| format!("Failed to open database: {}", db_str))?;
+    let (conn, _tmp_dir) = open_db(db_path, "cookies.sqlite")?;
 
     let table_exists: bool = conn
         .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='moz_cookies'")?
@@ -31,9 +27,7 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<CookieEntry>> {
     }
 
     // Check for sameSite column (added in newer Firefox)
-    let has_samesite: bool = conn
-        .prepare("SELECT sameSite FROM moz_cookies LIMIT 0")
-        .is_ok();
+    let has_samesite = super::has_column(&conn, "moz_cookies", "sameSite");
 
     let query = if has_samesite {
         "SELECT id, host, name, path, value, \
@@ -68,7 +62,7 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<CookieEntry>> {
     })?;
 
     let mut entries = Vec::new();
-    for row in rows {
+    for row in collect_rows(rows, &db_str) {
         let (
             id,
             host,
@@ -81,7 +75,7 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<CookieEntry>> {
             is_secure,
             is_httponly,
             samesite,
-        ) = row?;
+        ) = row;
 
         let creation_time = match prtime_to_datetime(creation_time_raw) {
             Some(dt) => dt,
@@ -113,6 +107,8 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<CookieEntry>> {
             } else {
                 String::new()
             },
+            visited_host: false,
+            first_party: false,
             web_browser: "Firefox".to_string(),
             user_profile: username.to_string(),
             browser_profile: String::new(),
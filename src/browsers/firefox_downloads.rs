@@ -1,9 +1,9 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use rusqlite::Connection;
 use std::path::Path;
 
-use super::firefox::copy_db_to_temp;
-use super::{prtime_to_datetime, DownloadEntry};
+use super::firefox::open_db;
+use super::{collect_rows, prtime_to_datetime, DownloadEntry};
 
 /// Extract downloads from a Firefox `places.sqlite` file.
 ///
@@ -13,10 +13,7 @@ use super::{prtime_to_datetime, DownloadEntry};
 pub fn extract(db_path: &Path, username: &str) -> Result<Vec<DownloadEntry>> {
     let db_str = db_path.to_string_lossy().to_string();
 
-    let (_tmp_dir, tmp_db) = copy_db_to_temp(db_path, "places.sqlite")?;
-
-    let conn = Connection::open(&tmp_db)
-        .with_context(|| format!("Failed to open database: {}", db_str))?;
+    let (conn, _tmp_dir) = open_db(db_path, "places.sqlite")?;
 
     // Try modern approach first (moz_annos)
     let entries = extract_from_annos(&conn, username, &db_str);
@@ -60,8 +57,8 @@ fn extract_from_annos(
     })?;
 
     let mut destinations = std::collections::HashMap::new();
-    for row in dest_rows {
-        let (place_id, content, date_added) = row?;
+    for row in collect_rows(dest_rows, db_str) {
+        let (place_id, content, date_added) = row;
         destinations.insert(place_id, (content.unwrap_or_default(), date_added));
     }
 
@@ -85,8 +82,8 @@ fn extract_from_annos(
     })?;
 
     let mut metadata = std::collections::HashMap::new();
-    for row in meta_rows {
-        let (place_id, content) = row?;
+    for row in collect_rows(meta_rows, db_str) {
+        let (place_id, content) = row;
         if let Some(json_str) = content {
             if let Ok(val) = serde_json::from_str::<serde_json::Value>(&json_str) {
                 metadata.insert(place_id, val);
@@ -110,7 +107,7 @@ fn extract_from_annos(
             ))
         });
 
-        let (url, _title) = match place {
+        let (url, title) = match place {
             Ok(p) => p,
             Err(_) => continue,
         };
@@ -124,7 +121,7 @@ fn extract_from_annos(
             .unwrap_or(dest_uri)
             .to_string();
 
-        let start_time = date_added.and_then(|d| prtime_to_datetime(d));
+        let start_time = date_added.and_then(prtime_to_datetime);
         let start_time = match start_time {
             Some(dt) => dt,
             None => continue,
@@ -161,6 +158,7 @@ fn extract_from_annos(
 
         entries.push(DownloadEntry {
             url,
+            url_chain: String::new(),
             target_path,
             current_path: String::new(),
             start_time,
@@ -169,10 +167,16 @@ fn extract_from_annos(
             total_bytes,
             state: state.to_string(),
             danger_type: String::new(),
+            interrupt_reason: String::new(),
             mime_type: String::new(),
             referrer: String::new(),
             tab_url: String::new(),
+            page_title: title.unwrap_or_default(),
+            inferred_referrer: String::new(),
             opened: false,
+            guid: String::new(),
+            by_ext_id: String::new(),
+            by_ext_name: String::new(),
             web_browser: "Firefox".to_string(),
             user_profile: username.to_string(),
             browser_profile: String::new(),
@@ -220,9 +224,9 @@ fn extract_from_legacy(
     })?;
 
     let mut entries = Vec::new();
-    for row in rows {
+    for row in collect_rows(rows, db_str) {
         let (id, _name, source, target, start_time_raw, end_time_raw, curr_bytes, max_bytes, state) =
-            row?;
+            row;
 
         let url = source.unwrap_or_default();
         if url.is_empty() {
@@ -248,6 +252,7 @@ fn extract_from_legacy(
 
         entries.push(DownloadEntry {
             url,
+            url_chain: String::new(),
             target_path,
             current_path: String::new(),
             start_time,
@@ -256,10 +261,16 @@ fn extract_from_legacy(
             total_bytes: max_bytes,
             state: state_name.to_string(),
             danger_type: String::new(),
+            interrupt_reason: String::new(),
             mime_type: String::new(),
             referrer: String::new(),
             tab_url: String::new(),
+            page_title: String::new(),
+            inferred_referrer: String::new(),
             opened: false,
+            guid: String::new(),
+            by_ext_id: String::new(),
+            by_ext_name: String::new(),
             web_browser: "Firefox".to_string(),
             user_profile: username.to_string(),
             browser_profile: String::new(),
@@ -270,3 +281,37 @@ fn extract_from_legacy(
 
     Ok(entries)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annos_download_keeps_page_title() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("places.sqlite");
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR);
+             CREATE TABLE moz_anno_attributes (id INTEGER PRIMARY KEY, name VARCHAR(32));
+             CREATE TABLE moz_annos (id INTEGER PRIMARY KEY, place_id INTEGER,
+                 anno_attribute_id INTEGER, content LONGVARCHAR, dateAdded INTEGER);
+             INSERT INTO moz_places VALUES (1, 'https://dl.example/setup.exe', 'Setup Downloads');
+             INSERT INTO moz_places VALUES (2, 'https://dl.example/notes.txt', NULL);
+             INSERT INTO moz_anno_attributes VALUES (1, 'downloads/destinationFileURI');
+             INSERT INTO moz_anno_attributes VALUES (2, 'downloads/metaData');
+             INSERT INTO moz_annos VALUES (1, 1, 1, 'file:///home/a/setup.exe', 1700000000000000);
+             INSERT INTO moz_annos VALUES (2, 1, 2, '{\"state\":1,\"fileSize\":10}', 1700000000000000);
+             INSERT INTO moz_annos VALUES (3, 2, 1, 'file:///home/a/notes.txt', 1700000001000000);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "a").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].url, "https://dl.example/setup.exe");
+        assert_eq!(entries[0].page_title, "Setup Downloads");
+        assert_eq!(entries[0].state, "Complete");
+        assert!(entries[1].page_title.is_empty());
+    }
+}
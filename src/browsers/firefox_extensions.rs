@@ -7,7 +7,7 @@ use super::{unix_millis_to_datetime, ExtensionEntry};
 pub fn extract(file_path: &Path, username: &str) -> Result<Vec<ExtensionEntry>> {
     let file_str = file_path.to_string_lossy().to_string();
 
-    let data = std::fs::read_to_string(file_path)
+    let data = std::fs::read_to_string(super::long_path(file_path))
         .with_context(|| format!("Failed to read extensions.json: {}", file_str))?;
 
     let root: serde_json::Value = serde_json::from_str(&data)
@@ -82,6 +82,7 @@ pub fn extract(file_path: &Path, username: &str) -> Result<Vec<ExtensionEntry>>
             version: version.to_string(),
             description: description.to_string(),
             enabled: active,
+            has_stored_state: false,
             install_time: install_date,
             update_url: update_url.to_string(),
             permissions,
@@ -0,0 +1,107 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::firefox::open_db;
+use super::{collect_rows, unix_millis_to_datetime, FaviconEntry};
+
+/// Extract page/icon pairs from a Firefox `favicons.sqlite` file.
+///
+/// Pages in `moz_pages_w_icons` are joined through `moz_icons_to_pages` to the icons
+/// shown for them. An icon's `expire_ms` is set when it is fetched, so it dates a visit
+/// to the page even after the visit itself has been trimmed from `places.sqlite`.
+pub fn extract(db_path: &Path, username: &str) -> Result<Vec<FaviconEntry>> {
+    let db_str = db_path.to_string_lossy().to_string();
+
+    let (conn, _tmp_dir) = open_db(db_path, "favicons.sqlite")?;
+
+    let table_exists: bool = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='moz_pages_w_icons'")?
+        .exists([])?;
+    if !table_exists {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT p.page_url, i.icon_url, i.width, i.expire_ms \
+         FROM moz_pages_w_icons p \
+         JOIN moz_icons_to_pages ip ON ip.page_id = p.id \
+         JOIN moz_icons i ON i.id = ip.icon_id \
+         ORDER BY i.expire_ms ASC",
+    )?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<i64>>(2)?,
+            row.get::<_, Option<i64>>(3)?,
+        ))
+    })?;
+
+    let mut entries = Vec::new();
+    for row in collect_rows(rows, &db_str) {
+        let (page_url, icon_url, width, expire_ms) = row;
+
+        entries.push(FaviconEntry {
+            page_url,
+            icon_url,
+            width: width.unwrap_or(0).max(0) as u32,
+            expire_time: expire_ms.and_then(unix_millis_to_datetime),
+            web_browser: "Firefox".to_string(),
+            user_profile: username.to_string(),
+            browser_profile: String::new(),
+            source_file: db_str.clone(),
+        });
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_favicons() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("favicons.sqlite");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        // 1705320000000 = 2024-01-15 12:00:00 UTC
+        conn.execute_batch(
+            "CREATE TABLE moz_icons (id INTEGER PRIMARY KEY, icon_url TEXT NOT NULL,
+                 fixed_icon_url_hash INTEGER NOT NULL, width INTEGER NOT NULL DEFAULT 0,
+                 root INTEGER NOT NULL DEFAULT 0, color INTEGER,
+                 expire_ms INTEGER NOT NULL DEFAULT 0, data BLOB);
+             CREATE TABLE moz_pages_w_icons (id INTEGER PRIMARY KEY, page_url TEXT NOT NULL,
+                 page_url_hash INTEGER NOT NULL);
+             CREATE TABLE moz_icons_to_pages (page_id INTEGER NOT NULL,
+                 icon_id INTEGER NOT NULL, PRIMARY KEY (page_id, icon_id));
+             INSERT INTO moz_icons VALUES
+                 (1, 'https://bank.example/favicon.ico', 0, 16, 1, NULL, 1705320000000, NULL),
+                 (2, 'https://bank.example/icon-32.png', 0, 32, 0, NULL, 1705300000000, NULL),
+                 (3, 'https://orphan.example/favicon.ico', 0, 16, 1, NULL, 1705310000000, NULL);
+             INSERT INTO moz_pages_w_icons VALUES (10, 'https://bank.example/login', 0);
+             INSERT INTO moz_icons_to_pages VALUES (10, 1), (10, 2);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "alice").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].icon_url, "https://bank.example/icon-32.png");
+        assert_eq!(entries[0].width, 32);
+
+        let ico = &entries[1];
+        assert_eq!(ico.page_url, "https://bank.example/login");
+        assert_eq!(ico.icon_url, "https://bank.example/favicon.ico");
+        assert_eq!(
+            ico.expire_time
+                .unwrap()
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            "2024-01-15 12:00:00"
+        );
+        assert_eq!(ico.web_browser, "Firefox");
+        assert_eq!(ico.user_profile, "alice");
+    }
+}
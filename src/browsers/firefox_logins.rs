@@ -1,16 +1,20 @@
 use anyhow::{Context, Result};
 use std::path::Path;
 
-use super::{unix_millis_to_datetime, LoginEntry};
+use super::{stable_record_id, unix_millis_to_datetime, LoginEntry};
 
 /// Extract login metadata from a Firefox `logins.json` file.
 ///
 /// IMPORTANT: Only extracts metadata (URLs, usernames, timestamps, usage counts).
 /// Encrypted password data is NEVER extracted.
+///
+/// Older profiles name the fields `hostname`/`formSubmitURL`, newer ones
+/// `origin`/`formActionOrigin`, and either may hold a full URL. Both are reduced to
+/// `scheme://host[:port]` so rows from different Firefox versions line up.
 pub fn extract(file_path: &Path, username: &str) -> Result<Vec<LoginEntry>> {
     let file_str = file_path.to_string_lossy().to_string();
 
-    let data = std::fs::read_to_string(file_path)
+    let data = std::fs::read_to_string(super::long_path(file_path))
         .with_context(|| format!("Failed to read logins.json: {}", file_str))?;
 
     let root: serde_json::Value = serde_json::from_str(&data)
@@ -22,7 +26,7 @@ pub fn extract(file_path: &Path, username: &str) -> Result<Vec<LoginEntry>> {
     };
 
     let mut entries = Vec::new();
-    for (idx, login) in logins.iter().enumerate() {
+    for login in logins {
         let hostname = login
             .get("hostname")
             .or_else(|| login.get("origin"))
@@ -59,26 +63,168 @@ pub fn extract(file_path: &Path, username: &str) -> Result<Vec<LoginEntry>> {
             .and_then(|v| v.as_i64())
             .and_then(unix_millis_to_datetime);
 
+        let http_realm = login
+            .get("httpRealm")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+
         let times_used = login
             .get("timesUsed")
             .and_then(|v| v.as_i64())
             .unwrap_or(0) as u32;
 
         entries.push(LoginEntry {
-            origin_url: hostname.to_string(),
-            action_url: form_submit_url.to_string(),
+            origin_url: normalize_origin(hostname),
+            action_url: normalize_origin(form_submit_url),
             username_value: username_field.to_string(),
             date_created: time_created,
             date_last_used: time_last_used,
             date_password_modified: time_password_changed,
             times_used,
+            signon_realm: http_realm.to_string(),
+            federation_url: String::new(),
+            blacklisted_by_user: false,
             web_browser: "Firefox".to_string(),
             user_profile: username.to_string(),
             browser_profile: String::new(),
             source_file: file_str.clone(),
-            record_id: idx as i64,
+            record_id: login_record_id(login),
         });
     }
 
     Ok(entries)
 }
+
+/// The login's numeric `id`, or else a hash of its `guid` (or of the whole record), so
+/// the same login keeps its `record_id` across runs and edits to other logins.
+fn login_record_id(login: &serde_json::Value) -> i64 {
+    if let Some(id) = login.get("id").and_then(|v| v.as_i64()) {
+        return id;
+    }
+    match login.get("guid").and_then(|v| v.as_str()) {
+        Some(guid) if !guid.is_empty() => stable_record_id(&[guid]),
+        _ => stable_record_id(&[&login.to_string()]),
+    }
+}
+
+/// Reduce a login origin or form action URL to `scheme://host[:port]`: lowercased, with
+/// no credentials, path, query or default port. Values that aren't URLs (empty, or
+/// `javascript:` for script-submitted forms) are returned as-is.
+fn normalize_origin(value: &str) -> String {
+    let value = value.trim();
+    let (scheme, rest) = match value.split_once("://") {
+        Some(parts) => parts,
+        None => return value.to_string(),
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    let scheme = scheme.to_ascii_lowercase();
+    let host = host.to_ascii_lowercase();
+    let default_port = match scheme.as_str() {
+        "http" => ":80",
+        "https" => ":443",
+        _ => "",
+    };
+    let host = match host.strip_suffix(default_port) {
+        Some(h) if !default_port.is_empty() => h,
+        _ => &host,
+    };
+    format!("{scheme}://{host}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &tempfile::TempDir, json: &str) -> std::path::PathBuf {
+        let path = dir.path().join("logins.json");
+        std::fs::write(&path, json).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_old_and_new_schema_agree() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let old = write(
+            &dir,
+            r#"{"nextId": 3, "logins": [
+                {"id": 1, "hostname": "https://Mail.Example.com:443/",
+                 "formSubmitURL": "https://mail.example.com/login.php?next=inbox",
+                 "usernameField": "user", "timeCreated": 1705305600000, "timesUsed": 4},
+                {"id": 2, "hostname": "http://intranet.example.com:8080", "httpRealm": "Staff",
+                 "formSubmitURL": null, "timeCreated": 1705305600000}
+            ]}"#,
+        );
+        let old = extract(&old, "alice").unwrap();
+
+        let new = write(
+            &dir,
+            r#"{"nextId": 3, "logins": [
+                {"id": 1, "origin": "https://mail.example.com",
+                 "formActionOrigin": "https://mail.example.com",
+                 "usernameField": "user", "timeCreated": 1705305600000, "timesUsed": 4},
+                {"id": 2, "origin": "http://intranet.example.com:8080", "httpRealm": "Staff",
+                 "timeCreated": 1705305600000}
+            ]}"#,
+        );
+        let new = extract(&new, "alice").unwrap();
+
+        for entries in [&old, &new] {
+            assert_eq!(entries.len(), 2);
+            assert_eq!(entries[0].origin_url, "https://mail.example.com");
+            assert_eq!(entries[0].action_url, "https://mail.example.com");
+            assert_eq!(entries[1].origin_url, "http://intranet.example.com:8080");
+            assert_eq!(entries[1].action_url, "");
+            assert_eq!(entries[1].signon_realm, "Staff");
+        }
+    }
+
+    #[test]
+    fn test_record_id_stable_without_id() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            r#"{"logins": [
+                {"guid": "{9e8b1a5c-3f0e-4d7b-a1c2-5d6e7f809a1b}", "origin": "https://a.example"},
+                {"guid": "{1f2e3d4c-5b6a-4978-8a9b-0c1d2e3f4a5b}", "origin": "https://b.example"},
+                {"origin": "https://c.example", "timeCreated": 1705305600000},
+                {"id": 7, "origin": "https://d.example"}
+            ]}"#,
+        );
+        let first = extract(&path, "alice").unwrap();
+        let second = extract(&path, "alice").unwrap();
+        let ids: Vec<i64> = first.iter().map(|e| e.record_id).collect();
+        assert_eq!(ids, second.iter().map(|e| e.record_id).collect::<Vec<_>>());
+        assert_eq!(ids[3], 7);
+        let unique: std::collections::HashSet<_> = ids.iter().collect();
+        assert_eq!(unique.len(), 4);
+        assert!(ids.iter().all(|&id| id > 0));
+
+        // A login keeps its id when others are removed or reordered
+        let path = write(
+            &dir,
+            r#"{"logins": [
+                {"guid": "{1f2e3d4c-5b6a-4978-8a9b-0c1d2e3f4a5b}", "origin": "https://b.example"}
+            ]}"#,
+        );
+        assert_eq!(extract(&path, "alice").unwrap()[0].record_id, ids[1]);
+    }
+
+    #[test]
+    fn test_normalize_origin() {
+        assert_eq!(
+            normalize_origin("HTTP://user:pw@Example.com:80/a?b#c"),
+            "http://example.com"
+        );
+        assert_eq!(
+            normalize_origin("https://[::1]:8443/"),
+            "https://[::1]:8443"
+        );
+        assert_eq!(
+            normalize_origin("https://example.com:4430"),
+            "https://example.com:4430"
+        );
+        assert_eq!(normalize_origin("javascript:"), "javascript:");
+        assert_eq!(normalize_origin(""), "");
+    }
+}
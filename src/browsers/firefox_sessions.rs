@@ -0,0 +1,286 @@
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+
+use super::{stable_record_id, unix_millis_to_datetime, BrowserType, HistoryEntry};
+
+/// `visit_type` of a page in the navigation stack of a tab open in a session file.
+pub const OPEN_TAB_VISIT: &str = "Open Tab";
+
+/// Session files in a Firefox profile: `sessionstore.jsonlz4` (written at shutdown) and,
+/// in `sessionstore-backups/`, the live `recovery.jsonlz4`, its `recovery.baklz4` and the
+/// previous session's `previous.jsonlz4`. Firefox before 56 wrote the same JSON
+/// uncompressed under the `.js` / `.bak` names.
+pub const SESSION_FILES: &[&str] = &[
+    "sessionstore.jsonlz4",
+    "recovery.jsonlz4",
+    "recovery.baklz4",
+    "previous.jsonlz4",
+    "sessionstore.js",
+    "recovery.js",
+    "recovery.bak",
+    "previous.js",
+];
+
+/// Whether `file_name` is one of the [`SESSION_FILES`].
+pub fn is_session_file(file_name: &str) -> bool {
+    SESSION_FILES.contains(&file_name)
+}
+
+/// Header of a mozLz4 file: this magic, then the decompressed size (u32 LE), then one
+/// LZ4 block.
+const MOZLZ4_MAGIC: &[u8] = b"mozLz40\0";
+
+/// Extract the tabs open in a Firefox session restore file (see [`SESSION_FILES`]).
+///
+/// Every page in the back/forward stack of each tab in `windows[].tabs[]` becomes a
+/// history row with `visit_type = "Open Tab"`, timed at the tab's `lastAccessed` (or the
+/// session's `lastUpdate` when the tab has none). Visited From is the page before it in
+/// the same tab. These tabs were open when the file was written, so they can show pages
+/// that never reached, or were since removed from, `places.sqlite`. Closed tabs and
+/// windows are not reported.
+pub fn extract(file_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
+    let file_str = file_path.to_string_lossy().to_string();
+
+    let data = std::fs::read(super::long_path(file_path))
+        .with_context(|| format!("Failed to read session file: {}", file_str))?;
+    let json = match data.strip_prefix(MOZLZ4_MAGIC) {
+        Some(compressed) => decompress_mozlz4(compressed)
+            .with_context(|| format!("Failed to decompress session file: {}", file_str))?,
+        None => data,
+    };
+    // Very old sessionstore.js files wrap the object in parentheses
+    let text = String::from_utf8_lossy(&json);
+    let text = text.trim();
+    let text = match text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        Some(inner) => inner,
+        None => text,
+    };
+    let root: serde_json::Value = serde_json::from_str(text)
+        .with_context(|| format!("Failed to parse session file: {}", file_str))?;
+
+    let last_update = root.pointer("/session/lastUpdate").and_then(|v| v.as_i64());
+    let windows = match root.get("windows").and_then(|w| w.as_array()) {
+        Some(w) => w,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut entries = Vec::new();
+    for (w, window) in windows.iter().enumerate() {
+        let tabs = match window.get("tabs").and_then(|t| t.as_array()) {
+            Some(t) => t,
+            None => continue,
+        };
+        for (t, tab) in tabs.iter().enumerate() {
+            let accessed = tab
+                .get("lastAccessed")
+                .and_then(|v| v.as_i64())
+                .or(last_update)
+                .and_then(unix_millis_to_datetime);
+            let visit_time = match accessed {
+                Some(dt) => dt,
+                None => continue,
+            };
+            let stack = match tab.get("entries").and_then(|e| e.as_array()) {
+                Some(e) => e,
+                None => continue,
+            };
+
+            let mut previous = String::new();
+            for (i, page) in stack.iter().enumerate() {
+                let url = page.get("url").and_then(|v| v.as_str()).unwrap_or_default();
+                if url.is_empty() {
+                    continue;
+                }
+                let title = page
+                    .get("title")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default();
+                let position = format!("{w}/{t}/{i}");
+                let record_id = stable_record_id(&[url, &visit_time.to_rfc3339(), &position]);
+                entries.push(HistoryEntry {
+                    url: url.to_string(),
+                    title: title.to_string(),
+                    visit_time,
+                    visit_count: 1,
+                    visited_from: std::mem::replace(&mut previous, url.to_string()),
+                    app_id: String::new(),
+                    journey: String::new(),
+                    canonical_url: String::new(),
+                    visit_type: OPEN_TAB_VISIT.to_string(),
+                    visit_duration: String::new(),
+                    view_time_ms: None,
+                    key_presses: None,
+                    hidden: false,
+                    visited_link_confirmed: false,
+                    web_browser: BrowserType::Firefox.display_name().to_string(),
+                    user_profile: username.to_string(),
+                    browser_profile: String::new(),
+                    account: String::new(),
+                    url_length: url.len(),
+                    typed_count: 0,
+                    history_file: file_str.clone(),
+                    record_id,
+                });
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Decompress the body of a mozLz4 file (after [`MOZLZ4_MAGIC`]).
+fn decompress_mozlz4(data: &[u8]) -> Result<Vec<u8>> {
+    let size = match data.get(..4) {
+        Some(b) => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize,
+        None => bail!("truncated mozLz4 header"),
+    };
+    let out = lz4_block_decompress(&data[4..], size)?;
+    if out.len() != size {
+        bail!(
+            "mozLz4 block gave {} bytes, header says {}",
+            out.len(),
+            size
+        );
+    }
+    Ok(out)
+}
+
+/// Decode one raw LZ4 block of at most `max_size` bytes. Each sequence is a token
+/// (literal length, match length), the literals, and a 2-byte offset back into the
+/// output to copy the match from; the last sequence has literals only.
+fn lz4_block_decompress(src: &[u8], max_size: usize) -> Result<Vec<u8>> {
+    // Lengths of 15 continue in the following bytes, each added until one isn't 255
+    fn extended(src: &[u8], i: &mut usize, mut len: usize) -> Result<usize> {
+        loop {
+            let b = *src
+                .get(*i)
+                .context("LZ4 length runs past the end of the block")?;
+            *i += 1;
+            len += b as usize;
+            if b != 255 {
+                return Ok(len);
+            }
+        }
+    }
+
+    // A hostile header shouldn't make us reserve gigabytes up front
+    let mut out = Vec::with_capacity(max_size.min(src.len().saturating_mul(4)));
+    let mut i = 0;
+    while i < src.len() {
+        let token = src[i];
+        i += 1;
+
+        let mut literals = (token >> 4) as usize;
+        if literals == 15 {
+            literals = extended(src, &mut i, literals)?;
+        }
+        let run = src
+            .get(i..i + literals)
+            .context("LZ4 literals run past the end of the block")?;
+        if out.len() + literals > max_size {
+            bail!("LZ4 block decodes past its declared {} bytes", max_size);
+        }
+        out.extend_from_slice(run);
+        i += literals;
+        if i == src.len() {
+            break;
+        }
+
+        let offset = match src.get(i..i + 2) {
+            Some(b) => u16::from_le_bytes([b[0], b[1]]) as usize,
+            None => bail!("truncated LZ4 match offset"),
+        };
+        i += 2;
+        if offset == 0 || offset > out.len() {
+            bail!(
+                "LZ4 match offset {} outside the {} bytes decoded",
+                offset,
+                out.len()
+            );
+        }
+        let mut length = (token & 0x0F) as usize;
+        if length == 15 {
+            length = extended(src, &mut i, length)?;
+        }
+        length += 4;
+        if out.len() + length > max_size {
+            bail!("LZ4 block decodes past its declared {} bytes", max_size);
+        }
+        // The match may overlap the bytes it produces, so copy one at a time
+        let start = out.len() - offset;
+        for k in 0..length {
+            out.push(out[start + k]);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = r#"{"version":["sessionrestore",1],
+        "windows":[{"tabs":[
+            {"entries":[{"url":"https://example.com/","title":"Example"},
+                        {"url":"https://example.com/search?q=rust","title":"Results"}],
+             "index":2,"lastAccessed":1700000000000},
+            {"entries":[{"url":"about:newtab"}]}]}],
+        "session":{"lastUpdate":1700000100000}}"#;
+
+    /// Minimal mozLz4 encoder: the whole input as one literal run.
+    fn mozlz4(json: &[u8]) -> Vec<u8> {
+        let mut out = MOZLZ4_MAGIC.to_vec();
+        out.extend((json.len() as u32).to_le_bytes());
+        out.push(0xF0);
+        let mut rest = json.len() - 15;
+        while rest >= 255 {
+            out.push(255);
+            rest -= 255;
+        }
+        out.push(rest as u8);
+        out.extend_from_slice(json);
+        out
+    }
+
+    #[test]
+    fn test_lz4_block_with_overlapping_match() {
+        // "abc", then a 9-byte match at offset 3 -> "abcabcabcabc", then literal "!"
+        let block = [0x35, b'a', b'b', b'c', 3, 0, 0x10, b'!'];
+        let out = lz4_block_decompress(&block, 13).unwrap();
+        assert_eq!(out, b"abcabcabcabc!");
+        assert!(lz4_block_decompress(&block, 8).is_err());
+        assert!(lz4_block_decompress(&[0x10, b'a', 5, 0], 16).is_err());
+    }
+
+    #[test]
+    fn test_extract_recovery_jsonlz4() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("recovery.jsonlz4");
+        std::fs::write(&path, mozlz4(SESSION.as_bytes())).unwrap();
+
+        let entries = extract(&path, "alice").unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|e| e.visit_type == OPEN_TAB_VISIT));
+        assert_eq!(entries[1].url, "https://example.com/search?q=rust");
+        assert_eq!(entries[1].title, "Results");
+        assert_eq!(entries[1].visited_from, "https://example.com/");
+        assert_eq!(entries[0].visit_time.timestamp_millis(), 1_700_000_000_000);
+        // No lastAccessed: the session's last update
+        assert_eq!(entries[2].url, "about:newtab");
+        assert_eq!(entries[2].visit_time.timestamp_millis(), 1_700_000_100_000);
+        assert_eq!(entries[2].visited_from, "");
+        assert_eq!(entries[0].user_profile, "alice");
+        assert_ne!(entries[0].record_id, entries[1].record_id);
+    }
+
+    #[test]
+    fn test_extract_uncompressed_sessionstore_js() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("sessionstore.js");
+        std::fs::write(&path, format!("({})", SESSION)).unwrap();
+        assert_eq!(extract(&path, "").unwrap().len(), 3);
+
+        std::fs::write(&path, b"mozLz40\0\xff\xff\xff\x7f\x10").unwrap();
+        assert!(extract(&path, "").is_err());
+    }
+}
@@ -1,22 +1,44 @@
 pub mod chrome;
 pub mod chrome_autofill;
+pub mod chrome_autofill_profiles;
 pub mod chrome_bookmarks;
 pub mod chrome_cookies;
 pub mod chrome_downloads;
 pub mod chrome_extensions;
+pub mod chrome_hsts;
 pub mod chrome_keywords;
 pub mod chrome_logins;
+pub mod chrome_predictor;
+pub mod chrome_prefs;
+pub mod chrome_shortcuts;
+pub mod chrome_topsites;
+pub mod chrome_visited_links;
 pub mod firefox;
 pub mod firefox_autofill;
 pub mod firefox_bookmarks;
 pub mod firefox_cookies;
 pub mod firefox_downloads;
 pub mod firefox_extensions;
+pub mod firefox_favicons;
 pub mod firefox_logins;
+pub mod firefox_sessions;
+pub mod opera_notes;
+pub mod opera_typed;
 pub mod safari;
+pub mod safari_cookies;
+pub mod safari_downloads;
+pub mod signature;
 pub mod webcache;
 
 use chrono::{DateTime, Duration, NaiveDate, Utc};
+use log::{debug, info, warn};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Mutex;
+use tempfile::TempDir;
 
 // ---------------------------------------------------------------------------
 // Shared timestamp conversion functions
@@ -75,6 +97,57 @@ pub fn unix_millis_to_datetime(millis: i64) -> Option<DateTime<Utc>> {
     Some(DateTime::from_naive_utc_and_offset(dt, Utc))
 }
 
+/// `record_id` for a row from a JSON/XML source with no numeric id: the first 8 bytes
+/// of SHA-256 over its identity `parts` (e.g. a GUID), as a non-negative i64. The same
+/// identity gets the same id on every run.
+pub fn stable_record_id(parts: &[&str]) -> i64 {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    let digest = hasher.finalize();
+    i64::from_be_bytes(digest[..8].try_into().unwrap()) & i64::MAX
+}
+
+/// Collect rows from a `query_map`, skipping rows that fail to decode instead of
+/// aborting the whole extraction. A SQLite-level failure (e.g. a corrupt page) ends the
+/// read but keeps the rows collected so far. Skipped rows are counted and logged per file.
+pub fn collect_rows<T>(rows: impl Iterator<Item = rusqlite::Result<T>>, source: &str) -> Vec<T> {
+    let mut out = Vec::new();
+    let mut skipped = 0usize;
+    for row in rows {
+        match row {
+            Ok(r) => out.push(r),
+            Err(rusqlite::Error::SqliteFailure(e, msg)) => {
+                // Stepping again after a failure would restart the query
+                warn!(
+                    "{}: read stopped after {} row(s): {}",
+                    source,
+                    out.len(),
+                    msg.unwrap_or_else(|| e.to_string())
+                );
+                break;
+            }
+            Err(e) => {
+                debug!("{}: skipping unreadable row: {}", source, e);
+                skipped += 1;
+            }
+        }
+    }
+    if skipped > 0 {
+        warn!("{}: skipped {} unreadable row(s)", source, skipped);
+    }
+    out
+}
+
+/// Whether `table` has `column`. Browser versions add and drop columns, so optional
+/// ones are selected only when the schema has them.
+pub fn has_column(conn: &rusqlite::Connection, table: &str, column: &str) -> bool {
+    conn.prepare(&format!("SELECT {column} FROM {table} LIMIT 0"))
+        .is_ok()
+}
+
 /// Truncate a string to max length, appending "..." if truncated.
 /// Uses char boundaries to avoid panicking on multi-byte characters (e.g. emojis).
 pub fn truncate_str(s: &str, max: usize) -> String {
@@ -86,6 +159,194 @@ pub fn truncate_str(s: &str, max: usize) -> String {
     }
 }
 
+/// Legacy Windows `MAX_PATH` limit. Longer paths fail in most file APIs unless they
+/// carry the `\\?\` extended-length prefix.
+const MAX_PATH: usize = 260;
+
+/// Rewrite an absolute Windows path in `\\?\` extended-length form.
+/// UNC paths become `\\?\UNC\server\share\...`; already-prefixed paths are returned as-is.
+pub fn to_extended_length(path: &str) -> String {
+    if path.starts_with(r"\\?\") {
+        return path.to_string();
+    }
+    // The prefix disables path normalization, so separators must already be backslashes
+    let path = path.replace('/', "\\");
+    match path.strip_prefix(r"\\") {
+        Some(unc) => format!(r"\\?\UNC\{unc}"),
+        None => format!(r"\\?\{path}"),
+    }
+}
+
+/// Undo [`to_extended_length`] so reported paths look like the ones the examiner typed.
+pub fn strip_extended_length(path: &str) -> String {
+    if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{unc}")
+    } else {
+        path.strip_prefix(r"\\?\").unwrap_or(path).to_string()
+    }
+}
+
+/// Path to hand to file operations. On Windows, paths at or over `MAX_PATH` are made
+/// absolute and given the extended-length prefix so deep profiles aren't silently skipped.
+/// Elsewhere (or for short paths) the path is returned unchanged.
+pub fn long_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) || path.as_os_str().len() < MAX_PATH {
+        return path.to_path_buf();
+    }
+    let abs = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    PathBuf::from(to_extended_length(&abs.to_string_lossy()))
+}
+
+// ---------------------------------------------------------------------------
+// SQLite connections
+// ---------------------------------------------------------------------------
+
+/// Default `--sqlite-mmap`: bytes of each database to memory-map.
+pub const DEFAULT_SQLITE_MMAP: i64 = 256 * 1024 * 1024;
+/// Default `--sqlite-cache`: page cache per connection, in KiB.
+pub const DEFAULT_SQLITE_CACHE_KIB: i64 = 64 * 1024;
+
+static SQLITE_MMAP: AtomicI64 = AtomicI64::new(DEFAULT_SQLITE_MMAP);
+static SQLITE_CACHE_KIB: AtomicI64 = AtomicI64::new(DEFAULT_SQLITE_CACHE_KIB);
+
+/// Set the mmap and page cache sizes used by [`open_temp_copy`] for the rest of the run.
+pub fn set_sqlite_tuning(mmap_bytes: i64, cache_kib: i64) {
+    SQLITE_MMAP.store(mmap_bytes, Ordering::Relaxed);
+    SQLITE_CACHE_KIB.store(cache_kib, Ordering::Relaxed);
+}
+
+// ---------------------------------------------------------------------------
+// Temp copies
+// ---------------------------------------------------------------------------
+
+static TEMP_ROOT: Mutex<Option<PathBuf>> = Mutex::new(None);
+static PRESERVE_TEMP: AtomicBool = AtomicBool::new(false);
+
+/// Set where database copies are made (`--temp-dir`, default: the system temp dir) and
+/// whether they are kept after the run (`--preserve-temp`).
+pub fn set_temp_options(root: Option<PathBuf>, preserve: bool) {
+    *TEMP_ROOT.lock().unwrap_or_else(|e| e.into_inner()) = root;
+    PRESERVE_TEMP.store(preserve, Ordering::Relaxed);
+}
+
+/// Create a directory for copies of a browser database. With `--preserve-temp` it is
+/// not deleted on drop and its path is logged, so a failed extraction can be inspected.
+pub fn make_temp_dir() -> anyhow::Result<TempDir> {
+    use anyhow::Context;
+
+    let root = TEMP_ROOT.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let mut dir = match &root {
+        Some(root) => TempDir::with_prefix_in("webx-", root)
+            .with_context(|| format!("Failed to create temp directory in {}", root.display()))?,
+        None => TempDir::with_prefix("webx-").context("Failed to create temp directory")?,
+    };
+    if PRESERVE_TEMP.load(Ordering::Relaxed) {
+        dir.disable_cleanup(true);
+        info!("--preserve-temp: keeping {}", dir.path().display());
+    }
+    Ok(dir)
+}
+
+/// Open the temp copy of a browser database for reading.
+///
+/// The copy is first opened read-write once so a copied `-wal` is checkpointed into the
+/// main file (and a hot `-journal` rolled back) and the copy leaves WAL mode. It is then
+/// reopened read-only without mutexes, with `query_only` set and mmap/cache sized for
+/// full-table scans. Never use this on the original evidence file.
+pub fn open_temp_copy(tmp_db: &Path) -> Result<rusqlite::Connection, rusqlite::Error> {
+    use rusqlite::{Connection, OpenFlags};
+
+    // A corrupt file fails here too; the read-only open below reports it
+    if let Err(e) = Connection::open(tmp_db)
+        .and_then(|c| c.query_row("PRAGMA journal_mode=DELETE", [], |_| Ok(())))
+    {
+        debug!("{}: journal replay skipped: {}", tmp_db.display(), e);
+    }
+
+    let conn = Connection::open_with_flags(
+        tmp_db,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    tune_connection(&conn)?;
+    Ok(conn)
+}
+
+/// Set `query_only` and the `--sqlite-mmap`/`--sqlite-cache` sizes on a reading connection.
+fn tune_connection(conn: &rusqlite::Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(&format!(
+        "PRAGMA query_only = ON; PRAGMA mmap_size = {}; PRAGMA cache_size = -{};",
+        SQLITE_MMAP.load(Ordering::Relaxed),
+        SQLITE_CACHE_KIB.load(Ordering::Relaxed)
+    ))
+}
+
+static NO_COPY: AtomicBool = AtomicBool::new(false);
+
+/// Read databases in place instead of through temp copies where [`open_in_place`]
+/// allows it (`--no-copy`), for evidence that no browser can be writing to.
+pub fn set_no_copy(no_copy: bool) {
+    NO_COPY.store(no_copy, Ordering::Relaxed);
+}
+
+/// Open the original database for reading without copying it, when that is safe:
+/// `--no-copy` was given or the file itself is read-only (as on a mounted image), and
+/// no non-empty `-wal` or `-journal` sits beside it, since an immutable open ignores
+/// both. `None` means the caller should copy, which is also the answer when the direct
+/// open or its first read fails (a file locked by a running browser, say).
+pub fn open_in_place(db_path: &Path) -> Option<rusqlite::Connection> {
+    let read_only = std::fs::metadata(long_path(db_path)).is_ok_and(|m| m.permissions().readonly());
+    if !(NO_COPY.load(Ordering::Relaxed) || read_only)
+        || has_companion(db_path, "-wal")
+        || has_companion(db_path, "-journal")
+    {
+        return None;
+    }
+    let conn = open_immutable(db_path)?;
+    let checked = conn
+        .query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(()))
+        .and_then(|_| tune_connection(&conn));
+    match checked {
+        Ok(()) => Some(conn),
+        Err(e) => {
+            let path = db_path.display();
+            debug!("{}: direct open failed, copying instead: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Open the original file without writing anything next to it: `immutable=1` skips
+/// locking, journal and WAL handling. Unix only; elsewhere the caller uses a temp copy.
+pub(crate) fn open_immutable(path: &Path) -> Option<rusqlite::Connection> {
+    use rusqlite::{Connection, OpenFlags};
+
+    if cfg!(windows) {
+        return None;
+    }
+    let abs = std::path::absolute(path).ok()?;
+    let mut uri = String::from("file:");
+    for b in abs.to_string_lossy().bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'.' | b'_' | b'~' => {
+                uri.push(b as char)
+            }
+            _ => uri.push_str(&format!("%{b:02X}")),
+        }
+    }
+    uri.push_str("?immutable=1");
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY
+        | OpenFlags::SQLITE_OPEN_URI
+        | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    Connection::open_with_flags(uri, flags).ok()
+}
+
+/// Whether `path` has a non-empty companion file, e.g. `History-wal` for `"-wal"`.
+pub(crate) fn has_companion(path: &Path, ext: &str) -> bool {
+    let mut aux = path.as_os_str().to_owned();
+    aux.push(ext);
+    std::fs::metadata(long_path(Path::new(&aux))).is_ok_and(|m| m.len() > 0)
+}
+
 // ---------------------------------------------------------------------------
 // Browser type and artifact type enums
 // ---------------------------------------------------------------------------
@@ -96,10 +357,14 @@ pub enum BrowserType {
     EdgeChromium,
     Brave,
     Opera,
+    OperaGX,
     Vivaldi,
+    Yandex,
     Chromium,
     Arc,
     Firefox,
+    /// Firefox-based; same profile files, but history is off by default
+    TorBrowser,
     Safari,
     InternetExplorer,
 }
@@ -111,10 +376,13 @@ impl BrowserType {
             Self::EdgeChromium => "Edge Chromium",
             Self::Brave => "Brave",
             Self::Opera => "Opera",
+            Self::OperaGX => "Opera GX",
             Self::Vivaldi => "Vivaldi",
+            Self::Yandex => "Yandex Browser",
             Self::Chromium => "Chromium",
             Self::Arc => "Arc",
             Self::Firefox => "Firefox",
+            Self::TorBrowser => "Tor Browser",
             Self::Safari => "Safari",
             Self::InternetExplorer => "Internet Explorer 10/11 / Edge",
         }
@@ -127,11 +395,47 @@ impl BrowserType {
                 | Self::EdgeChromium
                 | Self::Brave
                 | Self::Opera
+                | Self::OperaGX
                 | Self::Vivaldi
+                | Self::Yandex
                 | Self::Chromium
                 | Self::Arc
         )
     }
+
+    /// Firefox and the browsers built on it, read with the Firefox extractors.
+    pub fn is_firefox(&self) -> bool {
+        matches!(self, Self::Firefox | Self::TorBrowser)
+    }
+}
+
+/// Parses the `--browser` names the CLI accepts, case-insensitively: `chrome`,
+/// `chromium`, `edge`, `brave`, `opera`, `operagx`, `vivaldi`, `yandex`, `arc`, `firefox`,
+/// `tor`, `safari`, and `ie` / `edge-legacy` / `webcache` for the ESE WebCache.
+impl std::str::FromStr for BrowserType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "chrome" => Ok(Self::Chrome),
+            "chromium" => Ok(Self::Chromium),
+            "edge" => Ok(Self::EdgeChromium),
+            "brave" => Ok(Self::Brave),
+            "opera" => Ok(Self::Opera),
+            "operagx" | "opera-gx" | "opera_gx" => Ok(Self::OperaGX),
+            "vivaldi" => Ok(Self::Vivaldi),
+            "yandex" => Ok(Self::Yandex),
+            "arc" => Ok(Self::Arc),
+            "firefox" => Ok(Self::Firefox),
+            "tor" | "torbrowser" | "tor-browser" => Ok(Self::TorBrowser),
+            "safari" => Ok(Self::Safari),
+            "ie" | "edge-legacy" | "webcache" => Ok(Self::InternetExplorer),
+            _ => anyhow::bail!(
+                "Unknown browser '{}'. Valid: chrome, firefox, tor, safari, ie, edge, brave, opera, operagx, vivaldi, yandex, arc",
+                s
+            ),
+        }
+    }
 }
 
 /// Type of browser artifact being extracted.
@@ -145,9 +449,39 @@ pub enum ArtifactType {
     Bookmarks,
     LoginData,
     Extensions,
+    BrowserSettings,
+    DailyVisits,
+    HstsRecords,
+    Shortcuts,
+    Favicons,
+    TopSites,
+    Predictor,
+    AutofillProfiles,
+    CreditCards,
 }
 
 impl ArtifactType {
+    /// Every artifact type, in output order.
+    pub const ALL: [ArtifactType; 17] = [
+        Self::History,
+        Self::Downloads,
+        Self::KeywordSearches,
+        Self::Cookies,
+        Self::Autofill,
+        Self::Bookmarks,
+        Self::LoginData,
+        Self::Extensions,
+        Self::BrowserSettings,
+        Self::DailyVisits,
+        Self::HstsRecords,
+        Self::Shortcuts,
+        Self::Favicons,
+        Self::TopSites,
+        Self::Predictor,
+        Self::AutofillProfiles,
+        Self::CreditCards,
+    ];
+
     pub fn display_name(&self) -> &'static str {
         match self {
             Self::History => "History",
@@ -158,6 +492,15 @@ impl ArtifactType {
             Self::Bookmarks => "Bookmarks",
             Self::LoginData => "Login Data",
             Self::Extensions => "Extensions",
+            Self::BrowserSettings => "Browser Settings",
+            Self::DailyVisits => "Daily Visits",
+            Self::HstsRecords => "HSTS Records",
+            Self::Shortcuts => "Omnibox Shortcuts",
+            Self::Favicons => "Favicons",
+            Self::TopSites => "Top Sites",
+            Self::Predictor => "Network Action Predictor",
+            Self::AutofillProfiles => "Autofill Profiles",
+            Self::CreditCards => "Credit Cards",
         }
     }
 
@@ -171,6 +514,46 @@ impl ArtifactType {
             Self::Bookmarks => "bookmarks",
             Self::LoginData => "login_data",
             Self::Extensions => "extensions",
+            Self::BrowserSettings => "settings",
+            Self::DailyVisits => "daily_visits",
+            Self::HstsRecords => "hsts",
+            Self::Shortcuts => "shortcuts",
+            Self::Favicons => "favicons",
+            Self::TopSites => "topsites",
+            Self::Predictor => "predictor",
+            Self::AutofillProfiles => "autofill_profiles",
+            Self::CreditCards => "credit_cards",
+        }
+    }
+}
+
+/// Parses the `--artifacts` names the CLI accepts, case-insensitively, including the
+/// aliases `searches`, `forms`, `passwords` / `login_data`, `addons`, `preferences`,
+/// `daily_visits`, `transport_security`, `omnibox`, `icons`, `top_sites`,
+/// `network_action_predictor`, `addresses` and `cards`.
+impl std::str::FromStr for ArtifactType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "history" => Ok(Self::History),
+            "downloads" => Ok(Self::Downloads),
+            "keywords" | "searches" => Ok(Self::KeywordSearches),
+            "cookies" => Ok(Self::Cookies),
+            "autofill" | "forms" => Ok(Self::Autofill),
+            "bookmarks" => Ok(Self::Bookmarks),
+            "logins" | "passwords" | "login_data" => Ok(Self::LoginData),
+            "extensions" | "addons" => Ok(Self::Extensions),
+            "settings" | "preferences" => Ok(Self::BrowserSettings),
+            "daily" | "daily_visits" => Ok(Self::DailyVisits),
+            "hsts" | "transport_security" => Ok(Self::HstsRecords),
+            "shortcuts" | "omnibox" => Ok(Self::Shortcuts),
+            "favicons" | "icons" => Ok(Self::Favicons),
+            "topsites" | "top_sites" => Ok(Self::TopSites),
+            "predictor" | "network_action_predictor" => Ok(Self::Predictor),
+            "autofill_profiles" | "addresses" => Ok(Self::AutofillProfiles),
+            "credit_cards" | "cards" => Ok(Self::CreditCards),
+            _ => anyhow::bail!("Unknown artifact type: {}", s),
         }
     }
 }
@@ -180,18 +563,36 @@ impl ArtifactType {
 // ---------------------------------------------------------------------------
 
 /// A single browser history entry, matching NirSoft BrowsingHistoryView CSV format.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct HistoryEntry {
     pub url: String,
     pub title: String,
     pub visit_time: DateTime<Utc>,
     pub visit_count: u32,
     pub visited_from: String,
+    /// Android/ChromeOS app that launched the visit (Chrome `visits.app_id`), if any
+    pub app_id: String,
+    /// Forward navigation path from a typed/start-page visit, e.g. `A -> B -> C`
+    pub journey: String,
+    /// URL with host lowercased and fragment/tracking parameters removed
+    /// (`--canonical-dedup` only)
+    pub canonical_url: String,
     pub visit_type: String,
     pub visit_duration: String,
+    /// Firefox: time the page was in the foreground, from `moz_places_metadata`
+    pub view_time_ms: Option<u64>,
+    /// Firefox: keys pressed on the page, from `moz_places_metadata`
+    pub key_presses: Option<u32>,
+    /// Chrome: `urls.hidden` is set (not shown in the history UI, e.g. redirect hops)
+    pub hidden: bool,
+    /// Chrome: the URL's fingerprint is in the profile's `Visited Links` table, i.e.
+    /// links to it were drawn as visited
+    pub visited_link_confirmed: bool,
     pub web_browser: String,
     pub user_profile: String,
     pub browser_profile: String,
+    /// Google/Microsoft account the profile was signed in and syncing as, if known
+    pub account: String,
     pub url_length: usize,
     pub typed_count: u32,
     pub history_file: String,
@@ -208,10 +609,173 @@ pub struct BrowserArtifact {
     pub username: String,
 }
 
+impl BrowserArtifact {
+    /// Chrome's legacy `Archived History` file: same `urls`/`visits` schema as `History`
+    /// but without downloads or keyword searches.
+    pub fn is_archived_history(&self) -> bool {
+        std::path::Path::new(&self.db_path)
+            .file_name()
+            .is_some_and(|n| n == "Archived History")
+    }
+
+    /// A Firefox session restore file (`recovery.jsonlz4` and the like): open tabs only,
+    /// no downloads or bookmarks behind it.
+    pub fn is_session_restore(&self) -> bool {
+        std::path::Path::new(&self.db_path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(firefox_sessions::is_session_file)
+    }
+
+    /// Chromium's `Bookmarks.bak`: the bookmarks as of the previous save.
+    pub fn is_bookmarks_backup(&self) -> bool {
+        std::path::Path::new(&self.db_path)
+            .file_name()
+            .is_some_and(|n| n == "Bookmarks.bak")
+    }
+
+    /// Opera's `typed_history.xml`: address-bar input only, no SQLite tables behind it.
+    pub fn is_typed_history(&self) -> bool {
+        std::path::Path::new(&self.db_path)
+            .file_name()
+            .is_some_and(|n| n == "typed_history.xml")
+    }
+
+    /// Opera's `notes.adr`: notes saved in the browser, read as bookmarks.
+    pub fn is_opera_notes(&self) -> bool {
+        std::path::Path::new(&self.db_path)
+            .file_name()
+            .is_some_and(|n| n == "notes.adr")
+    }
+
+    /// Chromium's Guest or System profile, labeled by the scanner: its activity is not
+    /// attributed to the OS user whose directory holds it.
+    pub fn is_unattributed_profile(&self) -> bool {
+        self.browser.is_chromium()
+            && self.username.is_empty()
+            && matches!(self.profile_name.as_str(), "Guest" | "System")
+    }
+}
+
+/// Drop entries whose (URL, visit time) already appears in `seen`. Returns the number removed.
+pub fn remove_seen_history(entries: &mut Vec<HistoryEntry>, seen: &[HistoryEntry]) -> usize {
+    let keys: std::collections::HashSet<_> = seen
+        .iter()
+        .map(|e| (e.url.as_str(), e.visit_time))
+        .collect();
+    let before = entries.len();
+    entries.retain(|e| !keys.contains(&(e.url.as_str(), e.visit_time)));
+    before - entries.len()
+}
+
+/// Collapse rows sharing (URL, visit time, record ID), keeping the first. The same
+/// visit can be read twice when a `-wal` copied next to the database replays rows
+/// the main file already holds, and carved records overlap the live ones.
+pub fn dedup_history(entries: Vec<HistoryEntry>) -> Vec<HistoryEntry> {
+    let mut seen = std::collections::HashSet::new();
+    entries
+        .into_iter()
+        .filter(|e| seen.insert((e.url.clone(), e.visit_time, e.record_id)))
+        .collect()
+}
+
+/// `visit_type` given to Chrome visits that look like prerender/prefetch page loads
+/// rather than pages the user actually viewed.
+pub const PREFETCH_VISIT_TYPE: &str = "Prefetch";
+
+/// Drop prefetch/prerender visits (`--exclude-prefetch`). Returns the number removed.
+pub fn remove_prefetch(entries: &mut Vec<HistoryEntry>) -> usize {
+    let before = entries.len();
+    entries.retain(|e| e.visit_type != PREFETCH_VISIT_TYPE);
+    before - entries.len()
+}
+
+/// Drop visits to URLs the browser hides from its history UI (`--exclude-hidden`).
+/// Returns the number removed.
+pub fn remove_hidden(entries: &mut Vec<HistoryEntry>) -> usize {
+    let before = entries.len();
+    entries.retain(|e| !e.hidden);
+    before - entries.len()
+}
+
+/// Longest `journey` recorded, in pages (including the typed page itself).
+pub const MAX_JOURNEY_PAGES: usize = 10;
+
+/// Fill `visited_from` with the URL of the visit each entry's `from_visit` points at.
+///
+/// `visits[i]` is the `(visit_id, from_visit)` pair for `entries[i]`. A `from_visit` of 0
+/// (no referrer) or one pointing at a visit that is no longer in the database leaves
+/// `visited_from` as it is, so a referrer filled from elsewhere is kept.
+pub fn fill_visited_from(entries: &mut [HistoryEntry], visits: &[(i64, i64)]) {
+    let urls: HashMap<i64, String> = visits
+        .iter()
+        .zip(entries.iter())
+        .map(|(&(visit_id, _), e)| (visit_id, e.url.clone()))
+        .collect();
+    for (e, &(_, from_visit)) in entries.iter_mut().zip(visits) {
+        if from_visit == 0 {
+            continue;
+        }
+        if let Some(url) = urls.get(&from_visit) {
+            e.visited_from = url.clone();
+        }
+    }
+}
+
+/// Fill `journey` on typed and start-page visits with where they led: child visits
+/// (whose `from_visit` points back) are followed forward and joined as `A -> B -> C`.
+///
+/// `visits[i]` is the `(visit_id, from_visit)` pair for `entries[i]`, and entries must be
+/// in visit-time order. When a visit has several children (e.g. links opened in new
+/// tabs), the earliest one is followed. Reloads of the same URL are collapsed, a visit
+/// ID seen twice ends the walk, and the path is capped at [`MAX_JOURNEY_PAGES`].
+/// Visits that led nowhere keep an empty journey.
+pub fn fill_journeys(entries: &mut [HistoryEntry], visits: &[(i64, i64)]) {
+    let mut first_child: HashMap<i64, usize> = HashMap::new();
+    for (i, &(visit_id, from_visit)) in visits.iter().enumerate() {
+        if from_visit != 0 && from_visit != visit_id {
+            first_child.entry(from_visit).or_insert(i);
+        }
+    }
+
+    for start in 0..entries.len() {
+        if !matches!(
+            core_visit_type(&entries[start].visit_type),
+            "Typed" | "Start Page"
+        ) {
+            continue;
+        }
+        let mut pages = vec![entries[start].url.as_str()];
+        let mut seen = HashSet::from([visits[start].0]);
+        let mut current = visits[start].0;
+        while let Some(&next) = first_child.get(&current) {
+            let visit_id = visits[next].0;
+            if !seen.insert(visit_id) {
+                break;
+            }
+            let url = entries[next].url.as_str();
+            if pages.last() != Some(&url) {
+                if pages.len() == MAX_JOURNEY_PAGES {
+                    break;
+                }
+                pages.push(url);
+            }
+            current = visit_id;
+        }
+        if pages.len() > 1 {
+            let journey = pages.join(" -> ");
+            entries[start].journey = journey;
+        }
+    }
+}
+
 /// A browser download entry.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct DownloadEntry {
+    /// Final URL the file was fetched from (last hop of the redirect chain)
     pub url: String,
+    /// Chrome: every hop of `downloads_url_chains`, first request first, joined by ` -> `
+    pub url_chain: String,
     pub target_path: String,
     pub current_path: String,
     pub start_time: DateTime<Utc>,
@@ -220,10 +784,23 @@ pub struct DownloadEntry {
     pub total_bytes: i64,
     pub state: String,
     pub danger_type: String,
+    /// Why Chrome interrupted the download (e.g. "File Blocked", "Virus Infected").
+    pub interrupt_reason: String,
     pub mime_type: String,
     pub referrer: String,
     pub tab_url: String,
+    /// Title of the page the download came from, as recorded in history (Firefox: the
+    /// download's `moz_places` row; Chrome: the tab URL's, else the referrer's, `urls`
+    /// row). Empty when history has no title for it.
+    pub page_title: String,
+    /// Originating page inferred from history when `referrer` is empty (see `correlate`).
+    pub inferred_referrer: String,
     pub opened: bool,
+    /// Chrome's stable download GUID.
+    pub guid: String,
+    /// Extension that initiated the download (Chrome `by_ext_id` / `by_ext_name`).
+    pub by_ext_id: String,
+    pub by_ext_name: String,
     pub web_browser: String,
     pub user_profile: String,
     pub browser_profile: String,
@@ -232,7 +809,7 @@ pub struct DownloadEntry {
 }
 
 /// A keyword/omnibox search term.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct KeywordSearchEntry {
     pub search_term: String,
     pub normalized_term: String,
@@ -247,8 +824,79 @@ pub struct KeywordSearchEntry {
     pub url_id: i64,
 }
 
+/// Text typed into the Chromium omnibox and the suggestion picked for it
+/// (`Shortcuts` database, `omni_box_shortcuts` table).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ShortcutEntry {
+    /// What the user typed
+    pub text: String,
+    /// Where the chosen suggestion led
+    pub url: String,
+    /// The suggestion as shown in the dropdown
+    pub contents: String,
+    /// Times this suggestion was chosen for the typed text
+    pub number_of_hits: u32,
+    pub last_access_time: Option<DateTime<Utc>>,
+    pub web_browser: String,
+    pub user_profile: String,
+    pub browser_profile: String,
+    pub source_file: String,
+}
+
+/// An icon Firefox fetched for a page (`favicons.sqlite`).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FaviconEntry {
+    pub page_url: String,
+    pub icon_url: String,
+    /// Icon width in pixels (0 for SVG or unknown)
+    pub width: u32,
+    /// When the cached icon expires; set at fetch time, so it dates a visit to the page
+    pub expire_time: Option<DateTime<Utc>>,
+    pub web_browser: String,
+    pub user_profile: String,
+    pub browser_profile: String,
+    pub source_file: String,
+}
+
+/// A Chromium new-tab "most visited" tile (`Top Sites` database, `top_sites` table),
+/// with one day's visit count from the profile's `History` segment usage when present.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TopSiteEntry {
+    pub url: String,
+    pub title: String,
+    /// Position among the tiles, 0 first
+    pub url_rank: i64,
+    /// Day `visit_count` was counted for (`segment_usage.time_slot`)
+    pub day: Option<NaiveDate>,
+    pub visit_count: Option<u32>,
+    pub web_browser: String,
+    pub user_profile: String,
+    pub browser_profile: String,
+    pub source_file: String,
+}
+
+/// Text typed into the Chromium omnibox and a URL Chrome predicted for it
+/// (`Network Action Predictor` database, `network_action_predictor` table).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PredictorEntry {
+    /// What the user typed
+    pub user_text: String,
+    /// The URL predicted for that text
+    pub url: String,
+    /// Times the user went on to open `url` after typing the text
+    pub number_of_hits: u32,
+    /// Times the user typed the text and went elsewhere
+    pub number_of_misses: u32,
+    /// Misses since the last hit; `None` when the table has no such column
+    pub number_of_consecutive_misses: Option<u32>,
+    pub web_browser: String,
+    pub user_profile: String,
+    pub browser_profile: String,
+    pub source_file: String,
+}
+
 /// A browser cookie entry.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct CookieEntry {
     pub host: String,
     pub name: String,
@@ -261,6 +909,10 @@ pub struct CookieEntry {
     pub is_httponly: bool,
     pub is_persistent: bool,
     pub same_site: String,
+    /// A visited page's host is this cookie's host or a subdomain of it.
+    pub visited_host: bool,
+    /// The cookie's registrable domain (eTLD+1) is that of a visited page.
+    pub first_party: bool,
     pub web_browser: String,
     pub user_profile: String,
     pub browser_profile: String,
@@ -269,7 +921,7 @@ pub struct CookieEntry {
 }
 
 /// An autofill/form history entry.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct AutofillEntry {
     pub field_name: String,
     pub value: String,
@@ -284,13 +936,20 @@ pub struct AutofillEntry {
 }
 
 /// A browser bookmark entry.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct BookmarkEntry {
     pub url: String,
     pub title: String,
     pub date_added: Option<DateTime<Utc>>,
     pub date_last_used: Option<DateTime<Utc>>,
+    pub date_modified: Option<DateTime<Utc>>,
+    /// Chrome sync metadata (`meta_info.last_visited_desktop`); only present on synced bookmarks.
+    pub last_visited_desktop: Option<DateTime<Utc>>,
+    pub sync_transaction_version: String,
     pub folder_path: String,
+    /// `Recently Removed` for `Bookmarks.bak` entries missing from the live `Bookmarks`
+    /// (set by `--dedup`), otherwise empty
+    pub status: String,
     pub web_browser: String,
     pub user_profile: String,
     pub browser_profile: String,
@@ -298,8 +957,58 @@ pub struct BookmarkEntry {
     pub record_id: i64,
 }
 
+/// A saved address from Chromium autofill (`Web Data` → `autofill_profiles`, with the
+/// names, emails and phone numbers stored against it).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AutofillProfileEntry {
+    /// Full names on the profile, `; `-separated when there are several
+    pub full_name: String,
+    pub company_name: String,
+    /// Street lines, newline-separated as Chrome stores them
+    pub street_address: String,
+    pub city: String,
+    pub state: String,
+    pub zipcode: String,
+    pub country_code: String,
+    /// `; `-separated
+    pub emails: String,
+    /// `; `-separated
+    pub phones: String,
+    pub use_count: u32,
+    pub last_used: Option<DateTime<Utc>>,
+    pub date_modified: Option<DateTime<Utc>>,
+    pub guid: String,
+    pub web_browser: String,
+    pub user_profile: String,
+    pub browser_profile: String,
+    pub source_file: String,
+}
+
+/// Payment card metadata from Chromium autofill (NO card numbers or CVVs extracted).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CreditCardEntry {
+    pub name_on_card: String,
+    /// Card network (`visa`, `mastercard`, ...) for synced cards; empty for local cards
+    pub network: String,
+    /// Last four digits, only where Chrome keeps them in the clear (synced cards)
+    pub last_four: String,
+    pub expiration_month: Option<u32>,
+    pub expiration_year: Option<u32>,
+    pub nickname: String,
+    pub use_count: u32,
+    pub last_used: Option<DateTime<Utc>>,
+    pub date_modified: Option<DateTime<Utc>>,
+    /// `Local` (`credit_cards`) or `Server (masked)` (`masked_credit_cards`)
+    pub storage: String,
+    pub guid: String,
+    pub web_browser: String,
+    pub user_profile: String,
+    pub browser_profile: String,
+    pub source_file: String,
+}
+
 /// Login/credential metadata (NO passwords extracted).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct LoginEntry {
     pub origin_url: String,
     pub action_url: String,
@@ -308,6 +1017,12 @@ pub struct LoginEntry {
     pub date_last_used: Option<DateTime<Utc>>,
     pub date_password_modified: Option<DateTime<Utc>>,
     pub times_used: u32,
+    /// Chrome `signon_realm` (scheme + host, or the HTTP auth realm); Firefox `httpRealm`
+    pub signon_realm: String,
+    /// Identity provider for federated (SSO) logins, e.g. `https://accounts.google.com`
+    pub federation_url: String,
+    /// The user chose "Never" when offered to save a password for this site
+    pub blacklisted_by_user: bool,
     pub web_browser: String,
     pub user_profile: String,
     pub browser_profile: String,
@@ -316,13 +1031,15 @@ pub struct LoginEntry {
 }
 
 /// A browser extension entry.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ExtensionEntry {
     pub extension_id: String,
     pub name: String,
     pub version: String,
     pub description: String,
     pub enabled: bool,
+    /// The profile holds runtime data for this extension (storage, IndexedDB, cookies).
+    pub has_stored_state: bool,
     pub install_time: Option<DateTime<Utc>>,
     pub update_url: String,
     pub permissions: String,
@@ -332,15 +1049,49 @@ pub struct ExtensionEntry {
     pub source_file: String,
 }
 
+/// A browser configuration value (startup pages, homepage, default search engine).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BrowserSettingsEntry {
+    pub setting: String,
+    pub value: String,
+    pub details: String,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub web_browser: String,
+    pub user_profile: String,
+    pub browser_profile: String,
+    pub source_file: String,
+}
+
+/// Visits to one URL on one calendar day (Safari `history_items.daily_visit_counts`).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DailyVisitEntry {
+    pub url: String,
+    pub title: String,
+    pub day: NaiveDate,
+    pub visit_count: u32,
+    pub web_browser: String,
+    pub user_profile: String,
+    pub browser_profile: String,
+    pub source_file: String,
+}
+
 // ---------------------------------------------------------------------------
 // Activity detection and natural language linearizers
 // ---------------------------------------------------------------------------
 
+/// Visit type without any parenthesised qualifiers, e.g. `Typed (From Address Bar)` -> `Typed`.
+fn core_visit_type(visit_type: &str) -> &str {
+    match visit_type.find(" (") {
+        Some(i) => &visit_type[..i],
+        None => visit_type,
+    }
+}
+
 /// Detect the type of web activity from URL, visit type, and title.
 fn detect_activity<'a>(url: &str, visit_type: &str, title: &str) -> &'a str {
     let url_lower = url.to_lowercase();
     let title_lower = title.to_lowercase();
-    let vtype_lower = visit_type.to_lowercase();
+    let vtype_lower = core_visit_type(visit_type).to_lowercase();
 
     // Download detection
     if vtype_lower == "download" || vtype_lower.contains("download") {
@@ -395,6 +1146,9 @@ pub fn linearize_entry(entry: &HistoryEntry) -> String {
     if !entry.browser_profile.is_empty() {
         parts.push(format!("| Profile: {}", entry.browser_profile));
     }
+    if !entry.account.is_empty() {
+        parts.push(format!("| Account: {}", entry.account));
+    }
 
     parts.join(" ")
 }
@@ -417,10 +1171,22 @@ pub fn linearize_download(entry: &DownloadEntry) -> String {
         parts.push(format!("- \"{}\"", filename));
     }
     parts.push(format!("from ({})", truncate_str(&entry.url, 200)));
+    if !entry.page_title.is_empty() {
+        parts.push(format!(
+            "| Page: \"{}\"",
+            truncate_str(&entry.page_title, 150)
+        ));
+    }
     if !entry.mime_type.is_empty() {
         parts.push(format!("| MIME: {}", entry.mime_type));
     }
     parts.push(format!("| State: {}", entry.state));
+    if !entry.by_ext_id.is_empty() {
+        parts.push(format!(
+            "| Initiated by extension: {} ({})",
+            entry.by_ext_name, entry.by_ext_id
+        ));
+    }
     if entry.total_bytes > 0 {
         parts.push(format!("| Size: {} bytes", entry.total_bytes));
     }
@@ -447,6 +1213,77 @@ pub fn linearize_keyword_search(entry: &KeywordSearchEntry) -> String {
     parts.join(" ")
 }
 
+pub fn linearize_shortcut(entry: &ShortcutEntry) -> String {
+    let mut parts = Vec::new();
+    if let Some(dt) = entry.last_access_time {
+        parts.push(format!("[{}]", dt.format("%Y-%m-%d %H:%M:%S")));
+    } else {
+        parts.push("[Unknown Time]".to_string());
+    }
+    parts.push("Omnibox Shortcut".to_string());
+    parts.push(format!("in {}", entry.web_browser));
+    parts.push(format!("- Typed: \"{}\"", entry.text));
+    parts.push(format!("-> {}", truncate_str(&entry.url, 200)));
+    parts.push(format!("({} hits)", entry.number_of_hits));
+    if !entry.user_profile.is_empty() {
+        parts.push(format!("| User: {}", entry.user_profile));
+    }
+    parts.join(" ")
+}
+
+pub fn linearize_favicon(entry: &FaviconEntry) -> String {
+    let mut parts = Vec::new();
+    if let Some(dt) = entry.expire_time {
+        parts.push(format!("[Icon expires {}]", dt.format("%Y-%m-%d %H:%M:%S")));
+    } else {
+        parts.push("[Unknown Time]".to_string());
+    }
+    parts.push("Favicon".to_string());
+    parts.push(format!("in {}", entry.web_browser));
+    parts.push(format!("for {}", truncate_str(&entry.page_url, 200)));
+    parts.push(format!("({})", truncate_str(&entry.icon_url, 200)));
+    if !entry.user_profile.is_empty() {
+        parts.push(format!("| User: {}", entry.user_profile));
+    }
+    parts.join(" ")
+}
+
+pub fn linearize_topsite(entry: &TopSiteEntry) -> String {
+    let mut parts = Vec::new();
+    match (entry.day, entry.visit_count) {
+        (Some(day), Some(count)) => parts.push(format!(
+            "[{}] Top Site visited {} time(s)",
+            day.format("%Y-%m-%d"),
+            count
+        )),
+        _ => parts.push("[Unknown Time] Top Site".to_string()),
+    }
+    parts.push(format!("#{} in {}:", entry.url_rank + 1, entry.web_browser));
+    if !entry.title.is_empty() {
+        parts.push(format!("\"{}\"", truncate_str(&entry.title, 100)));
+    }
+    parts.push(format!("({})", truncate_str(&entry.url, 200)));
+    if !entry.user_profile.is_empty() {
+        parts.push(format!("| User: {}", entry.user_profile));
+    }
+    parts.join(" ")
+}
+
+pub fn linearize_predictor(entry: &PredictorEntry) -> String {
+    let mut parts = vec!["[Unknown Time] Omnibox Prediction".to_string()];
+    parts.push(format!("in {}", entry.web_browser));
+    parts.push(format!("- Typed: \"{}\"", entry.user_text));
+    parts.push(format!("-> {}", truncate_str(&entry.url, 200)));
+    parts.push(format!(
+        "({} hits, {} misses)",
+        entry.number_of_hits, entry.number_of_misses
+    ));
+    if !entry.user_profile.is_empty() {
+        parts.push(format!("| User: {}", entry.user_profile));
+    }
+    parts.join(" ")
+}
+
 pub fn linearize_cookie(entry: &CookieEntry) -> String {
     let mut parts = Vec::new();
     parts.push(format!(
@@ -490,6 +1327,76 @@ pub fn linearize_autofill(entry: &AutofillEntry) -> String {
     parts.join(" ")
 }
 
+pub fn linearize_autofill_profile(entry: &AutofillProfileEntry) -> String {
+    let mut parts = Vec::new();
+    match entry.last_used.or(entry.date_modified) {
+        Some(dt) => parts.push(format!("[{}]", dt.format("%Y-%m-%d %H:%M:%S"))),
+        None => parts.push("[Unknown Time]".to_string()),
+    }
+    parts.push("Saved Address".to_string());
+    parts.push(format!("in {}", entry.web_browser));
+    if !entry.full_name.is_empty() {
+        parts.push(format!("- Name: \"{}\"", entry.full_name));
+    }
+    let place: Vec<&str> = [
+        entry.street_address.as_str(),
+        &entry.city,
+        &entry.state,
+        &entry.zipcode,
+        &entry.country_code,
+    ]
+    .into_iter()
+    .filter(|p| !p.is_empty())
+    .collect();
+    if !place.is_empty() {
+        parts.push(format!(
+            "- Address: \"{}\"",
+            place.join(", ").replace('\n', ", ")
+        ));
+    }
+    if !entry.emails.is_empty() {
+        parts.push(format!("- Email: {}", entry.emails));
+    }
+    if !entry.phones.is_empty() {
+        parts.push(format!("- Phone: {}", entry.phones));
+    }
+    parts.push(format!("| Used {} times", entry.use_count));
+    if !entry.user_profile.is_empty() {
+        parts.push(format!("| User: {}", entry.user_profile));
+    }
+    parts.join(" ")
+}
+
+pub fn linearize_credit_card(entry: &CreditCardEntry) -> String {
+    let mut parts = Vec::new();
+    match entry.last_used.or(entry.date_modified) {
+        Some(dt) => parts.push(format!("[{}]", dt.format("%Y-%m-%d %H:%M:%S"))),
+        None => parts.push("[Unknown Time]".to_string()),
+    }
+    parts.push("Saved Payment Card".to_string());
+    parts.push(format!("in {}", entry.web_browser));
+    if !entry.network.is_empty() {
+        parts.push(format!("- {}", entry.network));
+    }
+    if !entry.last_four.is_empty() {
+        parts.push(format!("ending {}", entry.last_four));
+    }
+    if !entry.name_on_card.is_empty() {
+        parts.push(format!("- Name: \"{}\"", entry.name_on_card));
+    }
+    if let (Some(m), Some(y)) = (entry.expiration_month, entry.expiration_year) {
+        parts.push(format!("- Expires {:02}/{}", m, y));
+    }
+    parts.push(format!(
+        "| {} card, used {} times",
+        entry.storage, entry.use_count
+    ));
+    if !entry.user_profile.is_empty() {
+        parts.push(format!("| User: {}", entry.user_profile));
+    }
+    parts.join(" ")
+}
+
 pub fn linearize_bookmark(entry: &BookmarkEntry) -> String {
     let mut parts = Vec::new();
     if let Some(dt) = entry.date_added {
@@ -506,6 +1413,9 @@ pub fn linearize_bookmark(entry: &BookmarkEntry) -> String {
     if !entry.folder_path.is_empty() {
         parts.push(format!("| Folder: {}", entry.folder_path));
     }
+    if !entry.status.is_empty() {
+        parts.push(format!("| Status: {}", entry.status));
+    }
     if !entry.user_profile.is_empty() {
         parts.push(format!("| User: {}", entry.user_profile));
     }
@@ -519,10 +1429,21 @@ pub fn linearize_login(entry: &LoginEntry) -> String {
     } else {
         parts.push("[Unknown Time]".to_string());
     }
-    parts.push("Login Credential Stored".to_string());
+    if entry.blacklisted_by_user {
+        parts.push("Password Saving Declined".to_string());
+    } else if !entry.federation_url.is_empty() {
+        parts.push("Federated Login Stored".to_string());
+    } else {
+        parts.push("Login Credential Stored".to_string());
+    }
     parts.push(format!("in {}", entry.web_browser));
-    parts.push(format!("- Username: \"{}\"", entry.username_value));
+    if !entry.blacklisted_by_user {
+        parts.push(format!("- Username: \"{}\"", entry.username_value));
+    }
     parts.push(format!("on {}", entry.origin_url));
+    if !entry.federation_url.is_empty() {
+        parts.push(format!("via {}", entry.federation_url));
+    }
     parts.push(format!("| Used {} times", entry.times_used));
     if !entry.user_profile.is_empty() {
         parts.push(format!("| User: {}", entry.user_profile));
@@ -542,6 +1463,48 @@ pub fn linearize_extension(entry: &ExtensionEntry) -> String {
     parts.push(format!("- \"{}\" v{}", entry.name, entry.version));
     parts.push(format!("({})", entry.extension_id));
     parts.push(format!("| Enabled: {}", entry.enabled));
+    if entry.has_stored_state {
+        parts.push("| Has stored state".to_string());
+    }
+    if !entry.user_profile.is_empty() {
+        parts.push(format!("| User: {}", entry.user_profile));
+    }
+    parts.join(" ")
+}
+
+pub fn linearize_setting(entry: &BrowserSettingsEntry) -> String {
+    let mut parts = Vec::new();
+    if let Some(dt) = entry.last_modified {
+        parts.push(format!("[{}]", dt.format("%Y-%m-%d %H:%M:%S")));
+    } else {
+        parts.push("[Unknown Time]".to_string());
+    }
+    parts.push("Browser Setting".to_string());
+    parts.push(format!("in {}", entry.web_browser));
+    parts.push(format!(
+        "- {}: {}",
+        entry.setting,
+        truncate_str(&entry.value, 200)
+    ));
+    if !entry.details.is_empty() {
+        parts.push(format!("({})", entry.details));
+    }
+    if !entry.user_profile.is_empty() {
+        parts.push(format!("| User: {}", entry.user_profile));
+    }
+    parts.join(" ")
+}
+
+pub fn linearize_daily_visit(entry: &DailyVisitEntry) -> String {
+    let mut parts = vec![format!("[{}]", entry.day.format("%Y-%m-%d"))];
+    parts.push(format!(
+        "Visited {} time(s) in {}:",
+        entry.visit_count, entry.web_browser
+    ));
+    if !entry.title.is_empty() {
+        parts.push(format!("\"{}\"", truncate_str(&entry.title, 100)));
+    }
+    parts.push(format!("({})", truncate_str(&entry.url, 200)));
     if !entry.user_profile.is_empty() {
         parts.push(format!("| User: {}", entry.user_profile));
     }
@@ -553,10 +1516,14 @@ pub fn detect_chromium_browser(path: &str) -> BrowserType {
     let lower = path.to_lowercase();
     if lower.contains("brave") {
         BrowserType::Brave
+    } else if lower.contains("opera gx") || lower.contains("opera_gx") {
+        BrowserType::OperaGX
     } else if lower.contains("opera") {
         BrowserType::Opera
     } else if lower.contains("vivaldi") {
         BrowserType::Vivaldi
+    } else if lower.contains("yandex") {
+        BrowserType::Yandex
     } else if lower.contains("edge") || lower.contains("msedge") {
         BrowserType::EdgeChromium
     } else if lower.contains("/arc/") {
@@ -567,3 +1534,258 @@ pub fn detect_chromium_browser(path: &str) -> BrowserType {
         BrowserType::Chrome
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_column() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE downloads (id INTEGER, guid TEXT);")
+            .unwrap();
+        assert!(has_column(&conn, "downloads", "guid"));
+        assert!(!has_column(&conn, "downloads", "by_ext_id"));
+        assert!(!has_column(&conn, "logins", "guid"));
+    }
+
+    #[test]
+    fn test_truncate_str_multibyte() {
+        // Each character is 3 bytes, so 7 lands inside the third one
+        let title = "日本語のタイトル";
+        assert_eq!(truncate_str(title, 7), "日本...");
+        assert_eq!(truncate_str(title, 9), "日本語...");
+        assert_eq!(truncate_str(title, 100), title);
+        assert_eq!(truncate_str("👍👍", 5), "👍...");
+    }
+
+    #[test]
+    fn test_browser_type_from_str() {
+        let cases = [
+            ("chrome", BrowserType::Chrome),
+            ("Chromium", BrowserType::Chromium),
+            ("edge", BrowserType::EdgeChromium),
+            ("brave", BrowserType::Brave),
+            ("opera", BrowserType::Opera),
+            ("OperaGX", BrowserType::OperaGX),
+            ("opera-gx", BrowserType::OperaGX),
+            ("vivaldi", BrowserType::Vivaldi),
+            ("Yandex", BrowserType::Yandex),
+            ("arc", BrowserType::Arc),
+            ("FIREFOX", BrowserType::Firefox),
+            ("tor", BrowserType::TorBrowser),
+            ("TorBrowser", BrowserType::TorBrowser),
+            ("safari", BrowserType::Safari),
+            ("ie", BrowserType::InternetExplorer),
+            ("edge-legacy", BrowserType::InternetExplorer),
+            ("webcache", BrowserType::InternetExplorer),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(name.parse::<BrowserType>().unwrap(), expected, "{name}");
+        }
+        let err = "netscape".parse::<BrowserType>().unwrap_err();
+        assert!(
+            err.to_string().contains("Unknown browser 'netscape'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_artifact_type_from_str() {
+        let cases = [
+            ("history", ArtifactType::History),
+            ("downloads", ArtifactType::Downloads),
+            ("keywords", ArtifactType::KeywordSearches),
+            ("searches", ArtifactType::KeywordSearches),
+            ("cookies", ArtifactType::Cookies),
+            ("autofill", ArtifactType::Autofill),
+            ("forms", ArtifactType::Autofill),
+            ("bookmarks", ArtifactType::Bookmarks),
+            ("logins", ArtifactType::LoginData),
+            ("passwords", ArtifactType::LoginData),
+            ("login_data", ArtifactType::LoginData),
+            ("extensions", ArtifactType::Extensions),
+            ("addons", ArtifactType::Extensions),
+            ("settings", ArtifactType::BrowserSettings),
+            ("preferences", ArtifactType::BrowserSettings),
+            ("daily", ArtifactType::DailyVisits),
+            ("daily_visits", ArtifactType::DailyVisits),
+            ("HSTS", ArtifactType::HstsRecords),
+            ("transport_security", ArtifactType::HstsRecords),
+            ("shortcuts", ArtifactType::Shortcuts),
+            ("omnibox", ArtifactType::Shortcuts),
+            ("favicons", ArtifactType::Favicons),
+            ("icons", ArtifactType::Favicons),
+            ("topsites", ArtifactType::TopSites),
+            ("Top_Sites", ArtifactType::TopSites),
+            ("predictor", ArtifactType::Predictor),
+            ("network_action_predictor", ArtifactType::Predictor),
+            ("addresses", ArtifactType::AutofillProfiles),
+            ("Credit_Cards", ArtifactType::CreditCards),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(name.parse::<ArtifactType>().unwrap(), expected, "{name}");
+        }
+        let err = "thumbnails".parse::<ArtifactType>().unwrap_err();
+        assert_eq!(err.to_string(), "Unknown artifact type: thumbnails");
+    }
+
+    #[test]
+    fn test_extended_length_paths() {
+        let deep = format!(r"C:\Users\alice\{}History", "nested\\".repeat(40));
+        assert!(deep.len() > MAX_PATH);
+        let prefixed = to_extended_length(&deep);
+        assert_eq!(prefixed, format!(r"\\?\{deep}"));
+        assert_eq!(to_extended_length(&prefixed), prefixed);
+        assert_eq!(strip_extended_length(&prefixed), deep);
+
+        assert_eq!(
+            to_extended_length("C:/Users/alice/History"),
+            r"\\?\C:\Users\alice\History"
+        );
+        let unc = to_extended_length(r"\\server\share\Users\alice");
+        assert_eq!(unc, r"\\?\UNC\server\share\Users\alice");
+        assert_eq!(strip_extended_length(&unc), r"\\server\share\Users\alice");
+    }
+
+    #[test]
+    fn test_journey_is_capped() {
+        let mut entries: Vec<_> = (0..15)
+            .map(|i| HistoryEntry {
+                url: format!("https://example.com/{i}"),
+                visit_time: DateTime::from_timestamp(1_705_305_600 + i, 0).unwrap(),
+                visit_count: 1,
+                visit_type: if i == 0 { "Typed" } else { "Link" }.to_string(),
+                web_browser: "Chrome".to_string(),
+                record_id: i,
+                ..Default::default()
+            })
+            .collect();
+        let visits: Vec<_> = (1..=15).map(|id| (id, id - 1)).collect();
+        fill_journeys(&mut entries, &visits);
+        assert_eq!(entries[0].journey.split(" -> ").count(), MAX_JOURNEY_PAGES);
+        assert!(entries[0].journey.ends_with("https://example.com/9"));
+    }
+
+    #[test]
+    fn test_detect_opera_gx() {
+        assert_eq!(
+            detect_chromium_browser(
+                r"C:\Users\bob\AppData\Roaming\Opera Software\Opera GX Stable\History"
+            ),
+            BrowserType::OperaGX
+        );
+        assert_eq!(
+            detect_chromium_browser("/home/bob/.config/opera_gx/History"),
+            BrowserType::OperaGX
+        );
+        assert_eq!(
+            detect_chromium_browser(
+                r"C:\Users\bob\AppData\Roaming\Opera Software\Opera Stable\History"
+            ),
+            BrowserType::Opera
+        );
+    }
+
+    #[test]
+    fn test_detect_yandex() {
+        let windows = r"C:\Users\bob\AppData\Local\Yandex\YandexBrowser\User Data\Default\History";
+        assert_eq!(detect_chromium_browser(windows), BrowserType::Yandex);
+        assert_eq!(
+            detect_chromium_browser(
+                "/Users/bob/Library/Application Support/Yandex/YandexBrowser/Default/History"
+            ),
+            BrowserType::Yandex
+        );
+        assert!(BrowserType::Yandex.is_chromium());
+        assert_eq!(BrowserType::Yandex.display_name(), "Yandex Browser");
+    }
+
+    #[test]
+    fn test_dedup_history_keeps_first_occurrence() {
+        let entry = |url: &str, secs: i64, record_id: i64, title: &str| HistoryEntry {
+            url: url.to_string(),
+            title: title.to_string(),
+            visit_time: DateTime::from_timestamp(1_705_305_600 + secs, 0).unwrap(),
+            visit_count: 1,
+            visit_type: "Link".to_string(),
+            web_browser: "Chrome".to_string(),
+            record_id,
+            ..Default::default()
+        };
+        let entries = vec![
+            entry("https://a.example/", 0, 1, "first"),
+            entry("https://a.example/", 0, 1, "replayed"),
+            // Same URL and time under another visit ID is a separate visit
+            entry("https://a.example/", 0, 2, "other visit"),
+            entry("https://b.example/", 5, 3, "b"),
+        ];
+
+        let kept = dedup_history(entries);
+        let titles: Vec<_> = kept.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, ["first", "other visit", "b"]);
+    }
+
+    #[test]
+    fn test_open_temp_copy_replays_wal() {
+        let live = tempfile::TempDir::new().unwrap();
+        let db = live.path().join("History");
+        let writer = rusqlite::Connection::open(&db).unwrap();
+        writer
+            .execute_batch(
+                "PRAGMA journal_mode = WAL; PRAGMA wal_autocheckpoint = 0;
+                 CREATE TABLE urls (id INTEGER PRIMARY KEY, url TEXT);
+                 INSERT INTO urls VALUES (1, 'https://example.com/');",
+            )
+            .unwrap();
+        // The writer is still open, so the row exists only in History-wal
+        assert!(live.path().join("History-wal").exists());
+
+        let (_tmp, tmp_db) = chrome::copy_db_to_temp(&db, "History").unwrap();
+        let conn = open_temp_copy(&tmp_db).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM urls", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+        assert!(conn.execute("DELETE FROM urls", []).is_err());
+        drop(writer);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_in_place_only_for_static_databases() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url TEXT);
+             INSERT INTO urls VALUES (1, 'https://example.com/');",
+        )
+        .unwrap();
+        drop(conn);
+        // A writable file is copied unless --no-copy is given
+        assert!(open_in_place(&db).is_none());
+
+        let mut perms = std::fs::metadata(&db).unwrap().permissions();
+        perms.set_readonly(true);
+        std::fs::set_permissions(&db, perms).unwrap();
+        let conn = open_in_place(&db).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM urls", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+        assert!(conn.execute("DELETE FROM urls", []).is_err());
+        drop(conn);
+        assert!(!dir.path().join("History-journal").exists());
+
+        // Changes in a -wal would be ignored, so that still means a copy
+        std::fs::write(dir.path().join("History-wal"), b"frames").unwrap();
+        assert!(open_in_place(&db).is_none());
+    }
+
+    #[test]
+    fn test_long_path_short_paths_unchanged() {
+        let p = Path::new("Users/alice/AppData/Local/Google/Chrome/User Data/Default/History");
+        assert_eq!(long_path(p), p);
+    }
+}
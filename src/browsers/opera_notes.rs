@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::path::Path;
+
+use super::{stable_record_id, BookmarkEntry, BrowserType};
+
+/// Extract Opera (Presto) notes from `notes.adr`.
+///
+/// The file is Opera's hotlist text format: `#NOTE` and `#FOLDER` blocks of tab-indented
+/// `KEY=value` lines, with a line holding only `-` closing the innermost folder. Each
+/// note becomes a bookmark row: the note text as the title (Opera stores line breaks as
+/// two `\x02` characters), the page it was taken on as the URL (often empty), `CREATED`
+/// as the date added and the enclosing folders, e.g. `Trash`, as the folder path.
+pub fn extract(file_path: &Path, username: &str) -> Result<Vec<BookmarkEntry>> {
+    let file_str = file_path.to_string_lossy().to_string();
+
+    let data = std::fs::read(super::long_path(file_path))
+        .with_context(|| format!("Failed to read Opera notes: {}", file_str))?;
+    let data = String::from_utf8_lossy(&data);
+
+    let mut entries = Vec::new();
+    let mut folders: Vec<String> = Vec::new();
+    // The block being read: its kind and its fields so far
+    let mut block: Option<(&str, Vec<(&str, &str)>)> = None;
+    for line in data.lines().chain(std::iter::once("")) {
+        let line = line.trim_end_matches('\r');
+        if let Some(field) = line.strip_prefix('\t') {
+            if let (Some((_, fields)), Some((key, value))) = (&mut block, field.split_once('=')) {
+                fields.push((key.trim(), value));
+            }
+            continue;
+        }
+
+        // Any other line ends the current block
+        if let Some((kind, fields)) = block.take() {
+            let get = |key: &str| {
+                fields
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                    .map_or("", |(_, v)| *v)
+            };
+            match kind {
+                // A note with neither text nor a page has nothing to show
+                "#NOTE" if !get("NAME").is_empty() || !get("URL").is_empty() => {
+                    let text = get("NAME").replace("\x02\x02", "\n");
+                    let record_id = match get("ID").parse::<i64>() {
+                        Ok(id) => id,
+                        Err(_) => stable_record_id(&[get("UNIQUEID"), &text, get("CREATED")]),
+                    };
+                    entries.push(BookmarkEntry {
+                        url: get("URL").to_string(),
+                        title: text,
+                        date_added: parse_created(get("CREATED")),
+                        date_last_used: None,
+                        date_modified: None,
+                        last_visited_desktop: None,
+                        sync_transaction_version: String::new(),
+                        folder_path: folders.join(" > "),
+                        status: String::new(),
+                        web_browser: BrowserType::Opera.display_name().to_string(),
+                        user_profile: username.to_string(),
+                        browser_profile: String::new(),
+                        source_file: file_str.clone(),
+                        record_id,
+                    });
+                }
+                "#FOLDER" => folders.push(get("NAME").to_string()),
+                _ => {}
+            }
+        }
+
+        match line.trim() {
+            "-" => {
+                folders.pop();
+            }
+            kind if kind.starts_with('#') => block = Some((kind, Vec::new())),
+            _ => {}
+        }
+    }
+
+    entries.sort_by_key(|e| e.date_added);
+    Ok(entries)
+}
+
+/// `CREATED` is seconds since the Unix epoch.
+fn parse_created(s: &str) -> Option<DateTime<Utc>> {
+    s.trim()
+        .parse::<i64>()
+        .ok()
+        .filter(|&secs| secs > 0)
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notes_adr() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("notes.adr");
+        std::fs::write(
+            &path,
+            "Opera Hotlist version 2.0\r\n\
+             Options: encoding = utf8, version=3\r\n\
+             \r\n\
+             #NOTE\r\n\
+             \tID=2\r\n\
+             \tNAME=wire 4000 to acct 1234\x02\x02before friday\r\n\
+             \tURL=http://bank.example.com/transfer\r\n\
+             \tCREATED=1363970218\r\n\
+             \r\n\
+             #FOLDER\r\n\
+             \tID=3\r\n\
+             \tNAME=Trash\r\n\
+             \tTRASH FOLDER=YES\r\n\
+             \r\n\
+             #NOTE\r\n\
+             \tID=4\r\n\
+             \tNAME=deleted note\r\n\
+             \tCREATED=1363970300\r\n\
+             \r\n\
+             -\r\n\
+             \r\n\
+             #NOTE\r\n\
+             \tID=5\r\n\
+             \tNAME=after the folder\r\n",
+        )
+        .unwrap();
+
+        let entries = extract(&path, "alice").unwrap();
+        assert_eq!(entries.len(), 3);
+        // No CREATED sorts first
+        assert_eq!(entries[0].title, "after the folder");
+        assert_eq!(entries[0].folder_path, "");
+        assert_eq!(entries[1].title, "wire 4000 to acct 1234\nbefore friday");
+        assert_eq!(entries[1].url, "http://bank.example.com/transfer");
+        assert_eq!(entries[1].record_id, 2);
+        assert_eq!(
+            entries[1].date_added.unwrap().to_rfc3339(),
+            "2013-03-22T16:36:58+00:00"
+        );
+        assert_eq!(entries[2].title, "deleted note");
+        assert_eq!(entries[2].folder_path, "Trash");
+        assert!(entries.iter().all(|e| e.web_browser == "Opera"));
+    }
+}
@@ -0,0 +1,223 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::warn;
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use std::path::Path;
+
+use super::{stable_record_id, BrowserType, HistoryEntry, KeywordSearchEntry};
+
+/// One `typed_history_item`: what was typed, Opera's `type` for it and when.
+struct TypedItem {
+    content: String,
+    kind: String,
+    last_typed: DateTime<Utc>,
+}
+
+/// Read every `typed_history_item` of `typed_history.xml`. Items without a parseable
+/// `last_typed` are skipped since the row has no time to anchor it, and an item whose
+/// attributes can't be unescaped is logged and skipped rather than failing the file.
+fn read_items(file_path: &Path) -> Result<Vec<TypedItem>> {
+    let file_str = file_path.to_string_lossy();
+
+    let data = std::fs::read_to_string(super::long_path(file_path))
+        .with_context(|| format!("Failed to read typed history: {}", file_str))?;
+
+    let mut reader = Reader::from_str(&data);
+    let mut items = Vec::new();
+    loop {
+        let event = reader.read_event().with_context(|| {
+            format!(
+                "Failed to parse typed history XML at byte {}: {}",
+                reader.buffer_position(),
+                file_str
+            )
+        })?;
+        let item = match event {
+            Event::Empty(e) | Event::Start(e) if e.name().as_ref() == b"typed_history_item" => e,
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        let mut content = String::new();
+        let mut kind = String::new();
+        let mut last_typed = None;
+        let mut bad_value = None;
+        for attr in item.attributes().flatten() {
+            let value = match attr.unescape_value() {
+                Ok(v) => v.into_owned(),
+                Err(e) => {
+                    bad_value = Some(e);
+                    break;
+                }
+            };
+            match attr.key.as_ref() {
+                b"content" => content = value,
+                b"type" => kind = value,
+                b"last_typed" => last_typed = parse_last_typed(&value),
+                _ => {}
+            }
+        }
+        if let Some(e) = bad_value {
+            warn!(
+                "{}: skipping typed_history_item before byte {}: {}",
+                file_str,
+                reader.buffer_position(),
+                e
+            );
+            continue;
+        }
+
+        let last_typed = match last_typed {
+            Some(dt) => dt,
+            None => continue,
+        };
+        if content.is_empty() {
+            continue;
+        }
+        items.push(TypedItem {
+            content,
+            kind,
+            last_typed,
+        });
+    }
+
+    items.sort_by_key(|i| i.last_typed);
+    Ok(items)
+}
+
+/// Extract address-bar input from Opera's `typed_history.xml`.
+///
+/// Each `<typed_history_item content="..." type="text" last_typed="2013-03-22T16:36:58Z"/>`
+/// becomes a history row with `visit_type = "Typed"`. The Opera `type` (`text`,
+/// `selected`) is kept in the title column. `search` items are search terms rather than
+/// addresses and come from [`extract_searches`] instead.
+pub fn extract(file_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
+    let file_str = file_path.to_string_lossy().to_string();
+    let entries = read_items(file_path)?
+        .into_iter()
+        .filter(|item| item.kind != "search")
+        .map(|item| HistoryEntry {
+            url_length: item.content.len(),
+            record_id: stable_record_id(&[&item.content, &item.last_typed.to_rfc3339()]),
+            url: item.content,
+            title: if item.kind.is_empty() {
+                String::new()
+            } else {
+                format!("Typed input ({})", item.kind)
+            },
+            visit_time: item.last_typed,
+            visit_count: 1,
+            visited_from: String::new(),
+            app_id: String::new(),
+            journey: String::new(),
+            canonical_url: String::new(),
+            visit_type: "Typed".to_string(),
+            visit_duration: String::new(),
+            view_time_ms: None,
+            key_presses: None,
+            hidden: false,
+            visited_link_confirmed: false,
+            web_browser: BrowserType::Opera.display_name().to_string(),
+            user_profile: username.to_string(),
+            browser_profile: String::new(),
+            account: String::new(),
+            typed_count: 1,
+            history_file: file_str.clone(),
+        })
+        .collect();
+    Ok(entries)
+}
+
+/// Extract the search terms typed into Opera's address bar: the `type="search"` items
+/// of `typed_history.xml`. Opera doesn't record which engine ran them, so the URL is
+/// empty.
+pub fn extract_searches(file_path: &Path, username: &str) -> Result<Vec<KeywordSearchEntry>> {
+    let file_str = file_path.to_string_lossy().to_string();
+    let entries = read_items(file_path)?
+        .into_iter()
+        .filter(|item| item.kind == "search")
+        .map(|item| KeywordSearchEntry {
+            normalized_term: item.content.to_lowercase(),
+            url_id: stable_record_id(&[&item.content, &item.last_typed.to_rfc3339()]),
+            search_term: item.content,
+            url: String::new(),
+            title: "Typed input (search)".to_string(),
+            visit_time: Some(item.last_typed),
+            web_browser: BrowserType::Opera.display_name().to_string(),
+            user_profile: username.to_string(),
+            browser_profile: String::new(),
+            source_file: file_str.clone(),
+            keyword_id: 0,
+        })
+        .collect();
+    Ok(entries)
+}
+
+/// `last_typed` is an ISO 8601 UTC timestamp, e.g. `2013-03-22T16:36:58Z`.
+fn parse_last_typed(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_typed_history_xml() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("typed_history.xml");
+        std::fs::write(
+            &path,
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<typed_history>
+    <typed_history_item content="http://intranet.example.com/?a=1&amp;b=2" type="text" last_typed="2013-03-22T16:36:58Z"/>
+    <typed_history_item content="How to wipe a drive" type="search" last_typed="2013-03-21T09:00:00Z"/>
+    <typed_history_item content="no-time.example.com" type="text"/>
+</typed_history>
+"#,
+        )
+        .unwrap();
+
+        let entries = extract(&path, "alice").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].url, "http://intranet.example.com/?a=1&b=2");
+        assert_eq!(entries[0].title, "Typed input (text)");
+        assert_eq!(
+            entries[0]
+                .visit_time
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            "2013-03-22 16:36:58"
+        );
+        assert_eq!(entries[0].visit_type, "Typed");
+        assert_eq!(entries[0].web_browser, "Opera");
+
+        let searches = extract_searches(&path, "alice").unwrap();
+        assert_eq!(searches.len(), 1);
+        assert_eq!(searches[0].search_term, "How to wipe a drive");
+        assert_eq!(searches[0].normalized_term, "how to wipe a drive");
+        assert_eq!(searches[0].web_browser, "Opera");
+    }
+
+    #[test]
+    fn test_bad_escape_skips_only_that_item() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("typed_history.xml");
+        std::fs::write(
+            &path,
+            r#"<typed_history>
+    <typed_history_item content="bad&bogus;.example.com" type="text" last_typed="2013-03-22T16:00:00Z"/>
+    <typed_history_item content="good.example.com" type="text" last_typed="2013-03-22T17:00:00Z"/>
+</typed_history>
+"#,
+        )
+        .unwrap();
+
+        let entries = extract(&path, "alice").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].url, "good.example.com");
+    }
+}
@@ -3,62 +3,91 @@ use rusqlite::Connection;
 use std::path::Path;
 use tempfile::TempDir;
 
-use super::{safari_time_to_datetime, HistoryEntry};
+use chrono::{Duration, NaiveDate};
 
-/// Extract browsing history from Safari's History.db SQLite file.
-///
-/// Opens the database read-only directly. Falls back to copying to a temp dir
-/// if the direct open fails (e.g., locked by a running browser).
-pub fn extract(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
-    let db_str = db_path.to_string_lossy().to_string();
+use super::{
+    collect_rows, long_path, make_temp_dir, open_temp_copy, safari_time_to_datetime,
+    DailyVisitEntry, HistoryEntry,
+};
+
+/// Open Safari's History.db read-only, falling back to a temp copy if the direct open
+/// fails (e.g., locked by a running browser). The `TempDir` must outlive the connection.
+fn open_history_db(db_path: &Path) -> Result<(Connection, Option<TempDir>)> {
+    let db_str = db_path.to_string_lossy();
+    let src = long_path(db_path);
 
     // Try opening read-only directly first (avoids needing copy permissions)
-    let (conn, _tmp_dir) = match Connection::open_with_flags(
-        db_path,
+    match Connection::open_with_flags(
+        &src,
         rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
     ) {
-        Ok(c) => (c, None),
+        Ok(c) => Ok((c, None)),
         Err(_) => {
             // Fallback: copy to temp (handles locked DBs on live systems)
-            let tmp_dir = TempDir::new().context("Failed to create temp directory")?;
+            let tmp_dir = make_temp_dir()?;
             let tmp_db = tmp_dir.path().join("History.db");
-            std::fs::copy(db_path, &tmp_db)
+            std::fs::copy(&src, &tmp_db)
                 .with_context(|| format!("Failed to copy Safari database: {}", db_str))?;
             for ext in &["-wal", "-shm"] {
                 let aux_name = format!("History.db{ext}");
-                let aux = db_path.parent().unwrap_or(Path::new(".")).join(&aux_name);
+                let aux = src.parent().unwrap_or(Path::new(".")).join(&aux_name);
                 if aux.exists() {
                     let _ = std::fs::copy(&aux, tmp_dir.path().join(&aux_name));
                 }
             }
-            let c = Connection::open(&tmp_db)
+            let c = open_temp_copy(&tmp_db)
                 .with_context(|| format!("Failed to open Safari database: {}", db_str))?;
-            (c, Some(tmp_dir))
+            Ok((c, Some(tmp_dir)))
         }
-    };
+    }
+}
+
+/// `visit_type` of a visit whose `history_items` row was deleted or has lost its URL.
+pub const ORPHAN_VISIT: &str = "Orphan Visit (deleted item)";
+
+/// Extract browsing history from Safari's History.db SQLite file.
+///
+/// Opens the database read-only directly. Falls back to copying to a temp dir
+/// if the direct open fails (e.g., locked by a running browser).
+pub fn extract(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
+    extract_visits(db_path, username, false)
+}
+
+/// Like [`extract`], but also reports orphan visits: `history_visits` rows whose
+/// `history_item` was deleted or whose URL is NULL after partial clearing. These keep
+/// their title and time, with an empty URL and `visit_type` [`ORPHAN_VISIT`].
+pub fn extract_with_orphans(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
+    extract_visits(db_path, username, true)
+}
+
+fn extract_visits(db_path: &Path, username: &str, orphans: bool) -> Result<Vec<HistoryEntry>> {
+    let db_str = db_path.to_string_lossy().to_string();
+    let (conn, _tmp_dir) = open_history_db(db_path)?;
 
     let mut stmt = conn.prepare(
         "SELECT hi.url, hv.title, hv.visit_time, hi.visit_count, hv.id \
-         FROM history_items hi \
-         JOIN history_visits hv ON hi.id = hv.history_item \
+         FROM history_visits hv \
+         LEFT JOIN history_items hi ON hi.id = hv.history_item \
          ORDER BY hv.visit_time ASC",
     )?;
 
     let rows = stmt.query_map([], |row| {
         Ok((
-            row.get::<_, String>(0)?,
+            row.get::<_, Option<String>>(0)?,
             row.get::<_, Option<String>>(1)?,
             row.get::<_, f64>(2)?,
-            row.get::<_, i32>(3)?,
+            row.get::<_, Option<i32>>(3)?,
             row.get::<_, i64>(4)?,
         ))
     })?;
 
     let mut entries = Vec::new();
-    for row in rows {
-        let (url, title, visit_time_raw, visit_count, id) = row?;
+    for row in collect_rows(rows, &db_str) {
+        let (url, title, visit_time_raw, visit_count, id) = row;
 
-        if url.is_empty() {
+        let url = url.unwrap_or_default();
+        let orphan = url.is_empty();
+        if orphan && !orphans {
             continue;
         }
 
@@ -72,13 +101,25 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
             url,
             title: title.unwrap_or_default(),
             visit_time,
-            visit_count: visit_count as u32,
+            visit_count: visit_count.unwrap_or(0) as u32,
             visited_from: String::new(),
-            visit_type: String::new(),
+            app_id: String::new(),
+            journey: String::new(),
+            canonical_url: String::new(),
+            visit_type: if orphan {
+                ORPHAN_VISIT.to_string()
+            } else {
+                String::new()
+            },
             visit_duration: String::new(),
+            view_time_ms: None,
+            key_presses: None,
+            hidden: false,
+            visited_link_confirmed: false,
             web_browser: "Safari".to_string(),
             user_profile: username.to_string(),
             browser_profile: String::new(),
+            account: String::new(),
             typed_count: 0,
             history_file: db_str.clone(),
             record_id: id,
@@ -88,6 +129,81 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
     Ok(entries)
 }
 
+/// Decode a `daily_visit_counts` blob into (day, visits) pairs.
+///
+/// The blob is a packed array of little-endian `i32` pairs: a day offset counted back
+/// from `base_day` (0 = `base_day` itself) followed by the visit count for that day.
+/// A trailing partial pair and non-positive counts are ignored.
+pub fn decode_daily_visit_counts(blob: &[u8], base_day: NaiveDate) -> Vec<(NaiveDate, u32)> {
+    blob.chunks_exact(8)
+        .filter_map(|pair| {
+            let offset = i32::from_le_bytes(pair[0..4].try_into().unwrap());
+            let count = i32::from_le_bytes(pair[4..8].try_into().unwrap());
+            let day = base_day.checked_sub_signed(Duration::days(offset as i64))?;
+            (count > 0).then_some((day, count as u32))
+        })
+        .collect()
+}
+
+/// Extract the per-day visit breakdown from `history_items.daily_visit_counts`.
+///
+/// Offsets in the blob are relative to the day of the item's most recent visit. Items
+/// without a blob (or databases predating the column) contribute nothing.
+pub fn extract_daily_visits(db_path: &Path, username: &str) -> Result<Vec<DailyVisitEntry>> {
+    let db_str = db_path.to_string_lossy().to_string();
+    let (conn, _tmp_dir) = open_history_db(db_path)?;
+
+    if !super::has_column(&conn, "history_items", "daily_visit_counts") {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT hi.url, hi.daily_visit_counts, MAX(hv.visit_time), \
+                (SELECT title FROM history_visits WHERE history_item = hi.id \
+                 ORDER BY visit_time DESC LIMIT 1) \
+         FROM history_items hi \
+         JOIN history_visits hv ON hi.id = hv.history_item \
+         WHERE length(hi.daily_visit_counts) > 0 \
+         GROUP BY hi.id",
+    )?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Vec<u8>>(1)?,
+            row.get::<_, f64>(2)?,
+            row.get::<_, Option<String>>(3)?,
+        ))
+    })?;
+
+    let mut entries = Vec::new();
+    for row in collect_rows(rows, &db_str) {
+        let (url, blob, last_visit_raw, title) = row;
+
+        let base_day = match safari_time_to_datetime(last_visit_raw) {
+            Some(dt) => dt.date_naive(),
+            None => continue,
+        };
+
+        let title = title.unwrap_or_default();
+        for (day, visit_count) in decode_daily_visit_counts(&blob, base_day) {
+            entries.push(DailyVisitEntry {
+                url: url.clone(),
+                title: title.clone(),
+                day,
+                visit_count,
+                web_browser: "Safari".to_string(),
+                user_profile: username.to_string(),
+                browser_profile: String::new(),
+                source_file: db_str.clone(),
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| a.day.cmp(&b.day).then_with(|| a.url.cmp(&b.url)));
+    Ok(entries)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,4 +220,111 @@ mod tests {
     fn test_safari_time_zero() {
         assert!(safari_time_to_datetime(0.0).is_none());
     }
+
+    #[test]
+    fn test_orphan_visits() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History.db");
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE history_items (id INTEGER PRIMARY KEY, url TEXT, visit_count INTEGER);
+             CREATE TABLE history_visits (id INTEGER PRIMARY KEY, history_item INTEGER,
+                                          visit_time REAL, title TEXT);
+             INSERT INTO history_items VALUES (1, 'https://example.com/', 1);
+             INSERT INTO history_items VALUES (2, NULL, 3);
+             INSERT INTO history_visits VALUES (10, 1, 727012800.0, 'Example');
+             INSERT INTO history_visits VALUES (11, 2, 727012900.0, 'Cleared URL');
+             INSERT INTO history_visits VALUES (12, 99, 727013000.0, 'Deleted item');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract(&db, "alice").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].url, "https://example.com/");
+        assert_eq!(entries[0].visit_type, "");
+
+        let entries = extract_with_orphans(&db, "alice").unwrap();
+        assert_eq!(entries.len(), 3);
+        let orphans: Vec<_> = entries
+            .iter()
+            .filter(|e| e.visit_type == ORPHAN_VISIT)
+            .collect();
+        assert_eq!(orphans.len(), 2);
+        assert_eq!(orphans[0].title, "Cleared URL");
+        assert_eq!(orphans[0].visit_count, 3);
+        assert_eq!(orphans[1].title, "Deleted item");
+        assert_eq!(orphans[1].record_id, 12);
+        assert!(orphans.iter().all(|e| e.url.is_empty()));
+        assert_eq!(
+            orphans[1]
+                .visit_time
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            "2024-01-15 12:03:20"
+        );
+    }
+
+    /// Little-endian (day offset, count) pairs.
+    fn blob(pairs: &[(i32, i32)]) -> Vec<u8> {
+        pairs
+            .iter()
+            .flat_map(|(d, c)| d.to_le_bytes().into_iter().chain(c.to_le_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_daily_visit_counts() {
+        let base = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let mut data = blob(&[(0, 3), (1, 0), (2, 5), (30, 1)]);
+        data.extend_from_slice(&[0xff, 0xff]); // trailing partial pair
+        let days = decode_daily_visit_counts(&data, base);
+        assert_eq!(
+            days,
+            vec![
+                (base, 3),
+                (NaiveDate::from_ymd_opt(2024, 1, 13).unwrap(), 5),
+                (NaiveDate::from_ymd_opt(2023, 12, 16).unwrap(), 1),
+            ]
+        );
+        assert!(decode_daily_visit_counts(&[], base).is_empty());
+    }
+
+    #[test]
+    fn test_extract_daily_visits() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History.db");
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE history_items (id INTEGER PRIMARY KEY, url TEXT, visit_count INTEGER,
+                                         daily_visit_counts BLOB);
+             CREATE TABLE history_visits (id INTEGER PRIMARY KEY, history_item INTEGER,
+                                          visit_time REAL, title TEXT);
+             INSERT INTO history_items VALUES (2, 'https://example.org/', 1, NULL);
+             INSERT INTO history_visits VALUES (1, 1, 726984000.0, 'Old');
+             INSERT INTO history_visits VALUES (2, 1, 727012800.0, 'Example');
+             INSERT INTO history_visits VALUES (3, 2, 727012800.0, 'No blob');",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO history_items VALUES (1, 'https://example.com/', 4, ?1)",
+            [blob(&[(0, 1), (1, 3)])],
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries = extract_daily_visits(&db, "alice").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].day,
+            NaiveDate::from_ymd_opt(2024, 1, 14).unwrap()
+        );
+        assert_eq!(entries[0].visit_count, 3);
+        assert_eq!(
+            entries[1].day,
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()
+        );
+        assert_eq!(entries[1].title, "Example");
+        assert!(entries.iter().all(|e| e.url == "https://example.com/"));
+    }
 }
@@ -0,0 +1,208 @@
+use anyhow::{bail, Context, Result};
+use log::warn;
+use std::path::Path;
+
+use super::{safari_time_to_datetime, CookieEntry};
+
+/// `flags` bit for a cookie only sent over HTTPS.
+const FLAG_SECURE: u32 = 0x1;
+/// `flags` bit for a cookie hidden from scripts.
+const FLAG_HTTPONLY: u32 = 0x4;
+/// Bytes before a cookie's strings: size, version, flags, port flag, four string
+/// offsets, the end-of-header marker, then the expiry and creation doubles.
+const COOKIE_HEADER_LEN: usize = 56;
+
+/// Extract cookies from Safari's `Cookies.binarycookies`.
+///
+/// The file is the magic `cook`, a big-endian page count and page sizes, then the pages.
+/// Each page (little-endian from here on) lists the offsets of its cookie records, and
+/// each record carries flags, offsets of its NUL-terminated domain/name/path/value
+/// strings and Core Data expiry and creation times. A malformed page or record is
+/// skipped with a warning rather than failing the file, since the rest is still evidence.
+pub fn extract(file_path: &Path, username: &str) -> Result<Vec<CookieEntry>> {
+    let file_str = file_path.to_string_lossy().to_string();
+    let data = std::fs::read(super::long_path(file_path))
+        .with_context(|| format!("Failed to read cookie file: {}", file_str))?;
+    parse(&data, username, &file_str)
+}
+
+fn parse(data: &[u8], username: &str, file_str: &str) -> Result<Vec<CookieEntry>> {
+    if data.get(..4) != Some(b"cook") {
+        bail!("Not a Safari binarycookies file: {}", file_str);
+    }
+    let page_count = be_u32(data, 4).context("Truncated binarycookies header")? as usize;
+    let mut page_start = 8 + 4 * page_count;
+
+    let mut entries = Vec::new();
+    for page in 0..page_count {
+        let page_len = match be_u32(data, 8 + 4 * page) {
+            Some(len) => len as usize,
+            None => bail!("Truncated binarycookies page table: {}", file_str),
+        };
+        let page_data = match data.get(page_start..page_start + page_len) {
+            Some(d) => d,
+            None => {
+                warn!("{}: page {} runs past the end of the file", file_str, page);
+                break;
+            }
+        };
+        page_start += page_len;
+
+        let cookie_count = le_u32(page_data, 4).unwrap_or(0) as usize;
+        for i in 0..cookie_count {
+            let offset = match le_u32(page_data, 8 + 4 * i) {
+                Some(o) => o as usize,
+                None => break,
+            };
+            let mut entry = match parse_cookie(page_data, offset) {
+                Some(entry) => entry,
+                None => {
+                    warn!(
+                        "{}: skipping malformed cookie {} on page {}",
+                        file_str, i, page
+                    );
+                    continue;
+                }
+            };
+            entry.web_browser = "Safari".to_string();
+            entry.user_profile = username.to_string();
+            entry.source_file = file_str.to_string();
+            entry.record_id = entries.len() as i64 + 1;
+            entries.push(entry);
+        }
+    }
+
+    entries.sort_by_key(|e| e.creation_time);
+    Ok(entries)
+}
+
+/// One cookie record starting at `offset` in its page, or `None` if it doesn't fit.
+fn parse_cookie(page: &[u8], offset: usize) -> Option<CookieEntry> {
+    let size = le_u32(page, offset)? as usize;
+    if size < COOKIE_HEADER_LEN {
+        return None;
+    }
+    let record = page.get(offset..offset + size)?;
+    let flags = le_u32(record, 8)?;
+    let string_at = |field: usize| -> Option<String> {
+        let start = le_u32(record, field)? as usize;
+        let rest = record.get(start..)?;
+        let end = rest.iter().position(|&b| b == 0)?;
+        Some(String::from_utf8_lossy(&rest[..end]).into_owned())
+    };
+    let expiry = f64::from_le_bytes(record.get(40..48)?.try_into().ok()?);
+    let creation = f64::from_le_bytes(record.get(48..56)?.try_into().ok()?);
+
+    Some(CookieEntry {
+        host: string_at(16)?,
+        name: string_at(20)?,
+        path: string_at(24)?,
+        value: string_at(28)?,
+        creation_time: safari_time_to_datetime(creation)?,
+        expiry_time: safari_time_to_datetime(expiry),
+        last_access_time: None,
+        is_secure: flags & FLAG_SECURE != 0,
+        is_httponly: flags & FLAG_HTTPONLY != 0,
+        is_persistent: true, // session cookies are never written to binarycookies
+        same_site: String::new(),
+        visited_host: false,
+        first_party: false,
+        web_browser: String::new(),
+        user_profile: String::new(),
+        browser_profile: String::new(),
+        source_file: String::new(),
+        record_id: 0,
+    })
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cookie_record(flags: u32, strings: [&str; 4], expiry: f64, creation: f64) -> Vec<u8> {
+        let mut tail = Vec::new();
+        let mut offsets = [0u32; 4];
+        for (offset, s) in offsets.iter_mut().zip(strings) {
+            *offset = (COOKIE_HEADER_LEN + tail.len()) as u32;
+            tail.extend_from_slice(s.as_bytes());
+            tail.push(0);
+        }
+        let mut rec = Vec::new();
+        rec.extend_from_slice(&((COOKIE_HEADER_LEN + tail.len()) as u32).to_le_bytes());
+        rec.extend_from_slice(&0u32.to_le_bytes());
+        rec.extend_from_slice(&flags.to_le_bytes());
+        rec.extend_from_slice(&0u32.to_le_bytes());
+        for offset in offsets {
+            rec.extend_from_slice(&offset.to_le_bytes());
+        }
+        rec.extend_from_slice(&[0; 8]);
+        rec.extend_from_slice(&expiry.to_le_bytes());
+        rec.extend_from_slice(&creation.to_le_bytes());
+        rec.extend_from_slice(&tail);
+        rec
+    }
+
+    #[test]
+    fn test_parse_minimal_page() {
+        let records = [
+            cookie_record(
+                FLAG_SECURE | FLAG_HTTPONLY,
+                [".shop.example", "session", "/", "abc"],
+                760_000_000.0,
+                727_012_800.0,
+            ),
+            cookie_record(0, ["ads.example", "uid", "/t", "xyz"], 0.0, 727_000_000.0),
+        ];
+        let header_len = 8 + 4 * records.len() + 4;
+        let mut page = vec![0x00, 0x00, 0x01, 0x00];
+        page.extend_from_slice(&(records.len() as u32).to_le_bytes());
+        let mut offset = header_len;
+        for rec in &records {
+            page.extend_from_slice(&(offset as u32).to_le_bytes());
+            offset += rec.len();
+        }
+        page.extend_from_slice(&[0; 4]);
+        for rec in &records {
+            page.extend_from_slice(rec);
+        }
+
+        let mut file = b"cook".to_vec();
+        file.extend_from_slice(&1u32.to_be_bytes());
+        file.extend_from_slice(&(page.len() as u32).to_be_bytes());
+        file.extend_from_slice(&page);
+
+        let entries = parse(&file, "alice", "Cookies.binarycookies").unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let ads = &entries[0];
+        assert_eq!(
+            (ads.host.as_str(), ads.name.as_str()),
+            ("ads.example", "uid")
+        );
+        assert_eq!((ads.path.as_str(), ads.value.as_str()), ("/t", "xyz"));
+        assert!(!ads.is_secure && !ads.is_httponly);
+        assert!(ads.expiry_time.is_none());
+
+        let session = &entries[1];
+        assert_eq!(session.host, ".shop.example");
+        assert_eq!(session.value, "abc");
+        assert!(session.is_secure && session.is_httponly);
+        assert_eq!(
+            session.creation_time.format("%Y-%m-%d").to_string(),
+            "2024-01-15"
+        );
+        assert!(session.expiry_time.unwrap() > session.creation_time);
+        assert_eq!(session.web_browser, "Safari");
+        assert_eq!(session.user_profile, "alice");
+
+        assert!(parse(b"notcookies", "alice", "x").is_err());
+    }
+}
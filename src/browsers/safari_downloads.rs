@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use plist::{Dictionary, Value};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{safari_time_to_datetime, stable_record_id, DownloadEntry};
+
+/// Seconds between the Unix epoch and the Core Data epoch (2001-01-01).
+const CORE_DATA_EPOCH_OFFSET: f64 = 978_307_200.0;
+
+/// Extract downloads from Safari's `~/Library/Safari/Downloads.plist`.
+///
+/// The file is a binary or XML plist whose `DownloadHistory` array holds one dictionary
+/// per download. Safari drops finished downloads from the list according to its
+/// "Remove download list items" setting, so this is rarely the full history. Entries
+/// without a parseable `DownloadEntryDateAddedKey` are skipped since the row has no time
+/// to anchor it.
+pub fn extract(plist_path: &Path, username: &str) -> Result<Vec<DownloadEntry>> {
+    let plist_str = plist_path.to_string_lossy().to_string();
+
+    let root = Value::from_file(super::long_path(plist_path))
+        .with_context(|| format!("Failed to parse downloads plist: {}", plist_str))?;
+    let history = root
+        .as_dictionary()
+        .and_then(|d| d.get("DownloadHistory"))
+        .and_then(Value::as_array)
+        .with_context(|| format!("No DownloadHistory array in {}", plist_str))?;
+
+    let mut entries = Vec::new();
+    for item in history {
+        let item = match item.as_dictionary() {
+            Some(d) => d,
+            None => continue,
+        };
+        let start_time = match item.get("DownloadEntryDateAddedKey").and_then(to_datetime) {
+            Some(dt) => dt,
+            None => continue,
+        };
+
+        let url = string(item, "DownloadEntryURL");
+        let target_path = string(item, "DownloadEntryPath");
+        let received_bytes = integer(item, "DownloadEntryProgressBytesSoFar");
+        let total_bytes = integer(item, "DownloadEntryProgressTotalToLoad");
+        let end_time = item
+            .get("DownloadEntryDateFinishedKey")
+            .and_then(to_datetime);
+        let state = if end_time.is_some() || (total_bytes > 0 && received_bytes >= total_bytes) {
+            "Complete"
+        } else {
+            "Incomplete"
+        };
+
+        entries.push(DownloadEntry {
+            record_id: stable_record_id(&[&url, &start_time.to_rfc3339()]),
+            url,
+            url_chain: String::new(),
+            target_path,
+            current_path: String::new(),
+            start_time,
+            end_time,
+            received_bytes,
+            total_bytes,
+            state: state.to_string(),
+            danger_type: String::new(),
+            interrupt_reason: String::new(),
+            mime_type: String::new(),
+            referrer: String::new(),
+            tab_url: String::new(),
+            page_title: String::new(),
+            inferred_referrer: String::new(),
+            opened: false,
+            guid: string(item, "DownloadEntryIdentifier"),
+            by_ext_id: String::new(),
+            by_ext_name: String::new(),
+            web_browser: "Safari".to_string(),
+            user_profile: username.to_string(),
+            browser_profile: String::new(),
+            source_file: plist_str.clone(),
+        });
+    }
+
+    entries.sort_by_key(|e| e.start_time);
+    Ok(entries)
+}
+
+fn string(item: &Dictionary, key: &str) -> String {
+    item.get(key)
+        .and_then(Value::as_string)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn integer(item: &Dictionary, key: &str) -> i64 {
+    match item.get(key) {
+        Some(Value::Integer(i)) => i.as_signed().unwrap_or(0),
+        Some(Value::Real(r)) => *r as i64,
+        _ => 0,
+    }
+}
+
+/// A plist `<date>`, or a Core Data timestamp stored as a number, as a UTC time.
+fn to_datetime(value: &Value) -> Option<DateTime<Utc>> {
+    let seconds = match value {
+        Value::Date(date) => {
+            let unix = match SystemTime::from(*date).duration_since(UNIX_EPOCH) {
+                Ok(d) => d.as_secs_f64(),
+                Err(e) => -e.duration().as_secs_f64(),
+            };
+            unix - CORE_DATA_EPOCH_OFFSET
+        }
+        Value::Real(r) => *r,
+        Value::Integer(i) => i.as_signed()? as f64,
+        _ => return None,
+    };
+    safari_time_to_datetime(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn download(url: &str, added: Value, so_far: i64, total: i64) -> Value {
+        let mut d = Dictionary::new();
+        d.insert("DownloadEntryURL".into(), url.into());
+        d.insert(
+            "DownloadEntryPath".into(),
+            "/Users/alice/Downloads/tool.dmg".into(),
+        );
+        d.insert("DownloadEntryDateAddedKey".into(), added);
+        d.insert("DownloadEntryProgressBytesSoFar".into(), so_far.into());
+        d.insert("DownloadEntryProgressTotalToLoad".into(), total.into());
+        d.insert("DownloadEntryIdentifier".into(), "6A2B-01".into());
+        Value::Dictionary(d)
+    }
+
+    fn downloads_plist() -> Value {
+        // 2024-01-15 12:00:00 UTC
+        let added = UNIX_EPOCH + Duration::from_secs(1_705_320_000);
+        let mut root = Dictionary::new();
+        root.insert(
+            "DownloadHistory".into(),
+            Value::Array(vec![
+                download(
+                    "https://dl.example/tool.dmg",
+                    Value::Date(added.into()),
+                    2048,
+                    4096,
+                ),
+                download(
+                    "https://dl.example/early.zip",
+                    Value::Real(726_969_600.0),
+                    10,
+                    10,
+                ),
+                download("https://dl.example/no-date.zip", Value::Boolean(true), 1, 1),
+            ]),
+        );
+        Value::Dictionary(root)
+    }
+
+    #[test]
+    fn test_parse_binary_and_xml_downloads_plist() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let binary = dir.path().join("Downloads.plist");
+        downloads_plist().to_file_binary(&binary).unwrap();
+        let xml = dir.path().join("Downloads.xml.plist");
+        downloads_plist().to_file_xml(&xml).unwrap();
+
+        for path in [&binary, &xml] {
+            let entries = extract(path, "alice").unwrap();
+            assert_eq!(entries.len(), 2);
+            assert_eq!(entries[0].url, "https://dl.example/early.zip");
+            assert_eq!(entries[0].state, "Complete");
+            assert_eq!(
+                entries[0].start_time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                "2024-01-15 00:00:00"
+            );
+
+            let dmg = &entries[1];
+            assert_eq!(dmg.url, "https://dl.example/tool.dmg");
+            assert_eq!(dmg.target_path, "/Users/alice/Downloads/tool.dmg");
+            assert_eq!(
+                dmg.start_time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                "2024-01-15 12:00:00"
+            );
+            assert_eq!((dmg.received_bytes, dmg.total_bytes), (2048, 4096));
+            assert_eq!(dmg.state, "Incomplete");
+            assert_eq!(dmg.guid, "6A2B-01");
+            assert_eq!(dmg.web_browser, "Safari");
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Schema checks that a SQLite file is the browser database its name suggests.
+//!
+//! Scanning goes by file name and path, so an unrelated SQLite file called `History` or
+//! `cookies.sqlite` (an app cache, a test fixture) would otherwise be "extracted" into
+//! empty or misleading output. Each database name has signature tables that must
+//! prepare, and a foreign `PRAGMA application_id` rules the file out. Encrypted
+//! databases (SQLite SEE, SQLCipher) are recognized separately by [`encryption_reason`].
+
+use log::debug;
+use rusqlite::Connection;
+use std::io::Read;
+use std::path::Path;
+
+use super::chrome::copy_db_to_temp;
+use super::{has_companion, open_immutable, open_temp_copy};
+
+/// Queries that only prepare against the expected schema of each database file name.
+/// Only long-standing tables and columns are used so old browser versions still pass.
+pub fn probes(file_name: &str) -> Option<&'static [&'static str]> {
+    let probes: &'static [&'static str] = match file_name {
+        "History" | "Archived History" => &[
+            "SELECT url, title, visit_count, last_visit_time FROM urls LIMIT 0",
+            "SELECT url, visit_time FROM visits LIMIT 0",
+        ],
+        "Cookies" => &["SELECT host_key, name, creation_utc, expires_utc FROM cookies LIMIT 0"],
+        "Web Data" => &["SELECT name, value FROM autofill LIMIT 0"],
+        "Login Data" => &["SELECT origin_url, username_value FROM logins LIMIT 0"],
+        "Shortcuts" => &["SELECT text, url, last_access_time FROM omni_box_shortcuts LIMIT 0"],
+        "Top Sites" => &["SELECT url, url_rank FROM top_sites LIMIT 0"],
+        "Network Action Predictor" => {
+            &["SELECT user_text, url, number_of_hits FROM network_action_predictor LIMIT 0"]
+        }
+        "places.sqlite" => &[
+            "SELECT url, title FROM moz_places LIMIT 0",
+            "SELECT place_id, visit_date FROM moz_historyvisits LIMIT 0",
+        ],
+        "cookies.sqlite" => &["SELECT host, name, value FROM moz_cookies LIMIT 0"],
+        "favicons.sqlite" => &[
+            "SELECT page_url FROM moz_pages_w_icons LIMIT 0",
+            "SELECT icon_url, expire_ms FROM moz_icons LIMIT 0",
+        ],
+        "formhistory.sqlite" => &["SELECT fieldname, value FROM moz_formhistory LIMIT 0"],
+        "History.db" => &[
+            "SELECT url FROM history_items LIMIT 0",
+            "SELECT history_item, visit_time FROM history_visits LIMIT 0",
+        ],
+        _ => return None,
+    };
+    Some(probes)
+}
+
+/// The 100-byte database header at the start of every SQLite file.
+fn read_header(path: &Path) -> Option<[u8; 100]> {
+    let mut header = [0u8; 100];
+    std::fs::File::open(super::long_path(path))
+        .and_then(|mut f| f.read_exact(&mut header))
+        .ok()?;
+    Some(header)
+}
+
+/// Whether the file starts with the SQLite 3 header.
+pub fn is_sqlite(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    std::fs::File::open(super::long_path(path))
+        .and_then(|mut f| f.read_exact(&mut header))
+        .is_ok_and(|_| &header == b"SQLite format 3\0")
+}
+
+/// Whether the header fields SQLite validates before reading any page hold legal values:
+/// a power-of-two page size from 512 to 65536 (stored as 1), file format versions 1
+/// (rollback journal) or 2 (WAL), and the fixed payload fractions 64/32/32. SQLite
+/// answers "file is not a database" when they don't.
+fn header_is_valid(header: &[u8; 100]) -> bool {
+    let page_size = u16::from_be_bytes([header[16], header[17]]);
+    let page_size_ok = page_size == 1 || (page_size >= 512 && page_size.is_power_of_two());
+    let versions_ok = (1..=2).contains(&header[18]) && (1..=2).contains(&header[19]);
+    page_size_ok && versions_ok && header[21..24] == [64, 32, 32]
+}
+
+/// Smallest SQLite page size; a database file is always a whole number of pages.
+const MIN_PAGE_SIZE: u64 = 512;
+
+/// Why `path`, named like a browser SQLite database, looks encrypted (SQLite SEE,
+/// SQLCipher) or is not SQLite at all, or `None` if it reads as SQLite.
+///
+/// That is a file of whole pages without the `SQLite format 3` header (encryption covers
+/// the header too), or one with the header but header fields SQLite rejects as "not a
+/// database". Only the header is read, so the file is never opened or copied here.
+/// Empty and truncated files give `None` and are left to the extractor.
+pub fn encryption_reason(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    probes(file_name)?;
+    let len = std::fs::metadata(super::long_path(path)).ok()?.len();
+    if len == 0 || len % MIN_PAGE_SIZE != 0 {
+        return None;
+    }
+    if !is_sqlite(path) {
+        return Some(format!(
+            "no SQLite header in {len} bytes of whole {MIN_PAGE_SIZE}-byte pages"
+        ));
+    }
+    if !header_is_valid(&read_header(path)?) {
+        return Some("SQLite header but unreadable pages (invalid header fields)".to_string());
+    }
+    None
+}
+
+/// Reject another program's database by its `application_id` (header offset 68), which
+/// browsers leave at 0.
+fn check_application_id(application_id: u32, file_name: &str) -> Result<(), String> {
+    if application_id != 0 {
+        return Err(format!(
+            "application_id 0x{application_id:08X} belongs to another application, \
+             not a browser {file_name} database"
+        ));
+    }
+    Ok(())
+}
+
+fn check_schema(conn: &Connection, file_name: &str, probes: &[&str]) -> Result<(), String> {
+    let application_id: u32 = conn
+        .query_row("PRAGMA application_id", [], |r| r.get::<_, i64>(0))
+        .map_err(|e| format!("unreadable schema ({e})"))? as u32;
+    check_application_id(application_id, file_name)?;
+    if probes.iter().all(|sql| conn.prepare(sql).is_ok()) {
+        return Ok(());
+    }
+    let user_version: i64 = conn
+        .query_row("PRAGMA user_version", [], |r| r.get(0))
+        .unwrap_or_default();
+    let mut tables: Vec<String> = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |r| r.get(0))?
+                .collect::<Result<Vec<String>, _>>()
+        })
+        .unwrap_or_default();
+    tables.truncate(8);
+    Err(format!(
+        "SQLite file without the browser {file_name} schema (user_version {user_version}, \
+         tables: {})",
+        if tables.is_empty() {
+            "none".to_string()
+        } else {
+            tables.join(", ")
+        }
+    ))
+}
+
+/// Confirm `path` has the schema expected for its file name.
+///
+/// `None` when there is nothing to check: an unknown file name, a file that isn't
+/// SQLite (corrupt evidence is left for the extractor to report), or one that can't be
+/// opened. `Some(Err(reason))` means a readable SQLite database of some other kind.
+///
+/// A foreign `application_id` is read from the header without opening the file. The
+/// tables are probed in place where [`open_immutable`] can open it, and otherwise on a
+/// temp copy, the only copy made before extraction.
+pub fn verify(path: &Path) -> Option<Result<(), String>> {
+    let file_name = path.file_name()?.to_str()?;
+    let probes = probes(file_name)?;
+    if !is_sqlite(path) {
+        return None;
+    }
+    let header = read_header(path)?;
+    if !header_is_valid(&header) {
+        return None;
+    }
+    let application_id = u32::from_be_bytes([header[68], header[69], header[70], header[71]]);
+    if let Err(reason) = check_application_id(application_id, file_name) {
+        return Some(Err(reason));
+    }
+
+    if let Some(conn) = open_immutable(path) {
+        match check_schema(&conn, file_name, probes) {
+            Ok(()) => return Some(Ok(())),
+            // Tables created since the last checkpoint are only in the WAL, which
+            // immutable mode ignores; decide from a replayed temp copy instead
+            Err(reason) if !has_companion(path, "-wal") => return Some(Err(reason)),
+            Err(reason) => debug!("{}: {}; rechecking with WAL", path.display(), reason),
+        }
+    }
+
+    let (_tmp_dir, tmp_db) = copy_db_to_temp(path, file_name).ok()?;
+    let conn = open_temp_copy(&tmp_db).ok()?;
+    Some(check_schema(&conn, file_name, probes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_history_schema() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let history = dir.path().join("History");
+        let conn = Connection::open(&history).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                visit_count INTEGER, typed_count INTEGER, last_visit_time INTEGER);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER);",
+        )
+        .unwrap();
+        drop(conn);
+        assert_eq!(verify(&history), Some(Ok(())));
+
+        // Not SQLite at all, or not a known database name: nothing to decide
+        std::fs::write(dir.path().join("Cookies"), b"garbage").unwrap();
+        assert_eq!(verify(&dir.path().join("Cookies")), None);
+        assert_eq!(verify(&dir.path().join("notes.db")), None);
+    }
+
+    #[test]
+    fn test_encryption_reason() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let history = dir.path().join("History");
+        let conn = Connection::open(&history).unwrap();
+        conn.execute_batch("CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR);")
+            .unwrap();
+        drop(conn);
+        assert_eq!(encryption_reason(&history), None);
+
+        // SQLCipher-style: whole pages of ciphertext, header included
+        let cookies = dir.path().join("Cookies");
+        let noise: Vec<u8> = (0..4096u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect();
+        std::fs::write(&cookies, &noise).unwrap();
+        let reason = encryption_reason(&cookies).unwrap();
+        assert!(reason.contains("no SQLite header"), "{reason}");
+
+        // Plaintext header over pages SQLite can't interpret
+        let mut header_only = b"SQLite format 3\0".to_vec();
+        header_only.extend_from_slice(&noise[16..]);
+        std::fs::write(&cookies, &header_only).unwrap();
+        let reason = encryption_reason(&cookies).unwrap();
+        assert!(reason.contains("unreadable pages"), "{reason}");
+
+        // Truncated garbage and unknown file names are not called encrypted
+        std::fs::write(&cookies, b"garbage").unwrap();
+        assert_eq!(encryption_reason(&cookies), None);
+        std::fs::write(dir.path().join("notes.db"), &noise).unwrap();
+        assert_eq!(encryption_reason(&dir.path().join("notes.db")), None);
+    }
+
+    #[test]
+    fn test_verify_rejects_other_sqlite() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let places = dir.path().join("places.sqlite");
+        let conn = Connection::open(&places).unwrap();
+        conn.execute_batch("CREATE TABLE cache (key TEXT, data BLOB); PRAGMA user_version = 3;")
+            .unwrap();
+        drop(conn);
+        let reason = verify(&places).unwrap().unwrap_err();
+        assert!(reason.contains("user_version 3"), "{reason}");
+        assert!(reason.contains("tables: cache"), "{reason}");
+
+        let foreign = dir.path().join("History.db");
+        let conn = Connection::open(&foreign).unwrap();
+        conn.execute_batch(
+            "PRAGMA application_id = 1196444487;
+             CREATE TABLE history_items (id INTEGER PRIMARY KEY, url TEXT);
+             CREATE TABLE history_visits (id INTEGER, history_item INTEGER, visit_time REAL);",
+        )
+        .unwrap();
+        drop(conn);
+        let reason = verify(&foreign).unwrap().unwrap_err();
+        assert!(reason.contains("0x47504B47"), "{reason}");
+    }
+}
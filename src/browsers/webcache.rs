@@ -1,43 +1,152 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, NaiveDateTime, Utc};
-use libesedb::EseDb;
+use libesedb::{EseDb, Record, Value};
 use std::collections::HashSet;
 use std::path::Path;
 
 use super::{BrowserType, HistoryEntry};
 
-/// Parse a datetime string produced by libesedb Value::to_string().
-/// The library formats FILETIME values as human-readable strings.
+/// String layouts accepted when a timestamp is not a raw FILETIME. `%.f` also matches
+/// no fraction at all. US month-first dates are tried before day-first ones, so only
+/// dates that cannot be month-first (day > 12) are read as day-first.
+const ESE_DATETIME_FORMATS: &[&str] = &[
+    "%b %d, %Y %H:%M:%S%.f",
+    "%d %b %Y %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y/%m/%d %H:%M:%S%.f",
+    "%m/%d/%Y %I:%M:%S%.f %p",
+    "%m/%d/%Y %H:%M:%S%.f",
+    "%d/%m/%Y %H:%M:%S%.f",
+    "%d.%m.%Y %H:%M:%S%.f",
+    "%d-%m-%Y %H:%M:%S%.f",
+];
+
+/// Parse an ESE timestamp as rendered by [`value_text`].
+///
+/// libesedb renders FILETIME and 64-bit integer columns (WebCache's `AccessedTime` and
+/// `ModifiedTime`) as the raw integer, which is locale-independent and tried first.
+/// Formatted strings, from other builds or exports, are a fallback.
 fn parse_ese_datetime(s: &str) -> Option<DateTime<Utc>> {
     let s = s.trim();
     if s.is_empty() || s == "0" || s == "Not set" {
         return None;
     }
 
-    // libesedb formats as: "Mon DD, YYYY HH:MM:SS.NNN" or similar
-    // Try common patterns
-    for fmt in &[
-        "%b %d, %Y %H:%M:%S",
-        "%Y-%m-%dT%H:%M:%S",
-        "%Y-%m-%d %H:%M:%S",
-        "%m/%d/%Y %I:%M:%S %p",
-    ] {
-        if let Ok(ndt) = NaiveDateTime::parse_from_str(s, fmt) {
-            return Some(DateTime::from_naive_utc_and_offset(ndt, Utc));
+    // FILETIME integer (100ns intervals since 1601-01-01)
+    if let Ok(ft) = s.parse::<u64>() {
+        if ft == 0 {
+            return None;
         }
+        let microseconds = ft / 10;
+        let epoch = chrono::NaiveDate::from_ymd_opt(1601, 1, 1)?.and_hms_opt(0, 0, 0)?;
+        let dt = epoch.checked_add_signed(chrono::Duration::microseconds(microseconds as i64))?;
+        return Some(DateTime::from_naive_utc_and_offset(dt, Utc));
     }
 
-    // Try parsing as FILETIME integer (100ns intervals since 1601-01-01)
-    if let Ok(ft) = s.parse::<u64>() {
-        if ft > 0 {
-            let microseconds = ft / 10;
-            let epoch = chrono::NaiveDate::from_ymd_opt(1601, 1, 1)?.and_hms_opt(0, 0, 0)?;
-            let dt = epoch + chrono::Duration::microseconds(microseconds as i64);
-            return Some(DateTime::from_naive_utc_and_offset(dt, Utc));
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    ESE_DATETIME_FORMATS.iter().find_map(|fmt| {
+        NaiveDateTime::parse_from_str(s, fmt)
+            .ok()
+            .map(|ndt| DateTime::from_naive_utc_and_offset(ndt, Utc))
+    })
+}
+
+/// Decode the raw bytes of an ESE long-value text column.
+///
+/// WebCache stores `Url` as UTF-16LE large text; when the value spills into a
+/// long-value it comes back as raw bytes, so detect UTF-16 by its NUL high bytes
+/// and fall back to UTF-8 otherwise.
+fn decode_long_text(bytes: &[u8]) -> String {
+    let looks_utf16 = bytes.len() >= 2
+        && bytes.len().is_multiple_of(2)
+        && bytes.iter().skip(1).step_by(2).filter(|&&b| b == 0).count() * 2 >= bytes.len() / 2;
+    let text = if looks_utf16 {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    };
+    text.trim_end_matches('\0').to_string()
+}
+
+/// Read one record column as text.
+///
+/// `Value::to_string()` renders long-values and multi-values as the placeholders
+/// "LongValue" / "MultiValue", which silently drops very long IE URLs. Those are
+/// read through libesedb's long-value / multi-value APIs instead.
+fn value_text(rec: &Record, entry: i32) -> String {
+    match rec.value(entry) {
+        Ok(Value::Long) => {
+            let lv = match rec.long(entry) {
+                Ok(lv) => lv,
+                Err(_) => return String::new(),
+            };
+            if matches!(lv.variant(), Value::Text(_) | Value::LargeText(_)) {
+                if let Ok(s) = lv.utf8() {
+                    if !s.is_empty() {
+                        return s.trim_end_matches('\0').to_string();
+                    }
+                }
+            }
+            lv.vec().map(|b| decode_long_text(&b)).unwrap_or_default()
+        }
+        Ok(Value::Multi) => rec
+            .multi(entry)
+            .and_then(|mv| {
+                mv.iter_values().map(|it| {
+                    it.filter_map(|v| v.ok())
+                        .map(|v| v.to_string())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .map(|vals| vals.join(";"))
+            .unwrap_or_default(),
+        Ok(val) => val.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+/// Read every column of a record as text (see [`value_text`]).
+fn record_texts(rec: &Record) -> Vec<String> {
+    (0..rec.count_values().unwrap_or(0))
+        .map(|i| value_text(rec, i))
+        .collect()
+}
+
+/// Whether `s` starts like a cached URL: a `scheme:` prefix or a `:Host:` marker.
+fn looks_like_url(s: &str) -> bool {
+    if s.starts_with(":Host:") || s.starts_with(":host:") {
+        return true;
+    }
+    match s.split_once(':') {
+        Some((scheme, _)) => {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
         }
+        None => false,
     }
+}
 
-    None
+/// Split "Username@url" into its parts.
+///
+/// Usernames may be domain-qualified (`CORP\jdoe`), non-ASCII, or UPNs that contain
+/// `@` themselves, and the URL may carry userinfo (`http://a@b.com/`). The split is at
+/// the first `@` whose remainder looks like a URL, falling back to the first `@`.
+fn split_user(rest: &str) -> Option<(&str, &str)> {
+    let at_pos = rest
+        .match_indices('@')
+        .map(|(i, _)| i)
+        .find(|&i| looks_like_url(rest[i + 1..].trim_start()))
+        .or_else(|| rest.find('@'))?;
+    Some((rest[..at_pos].trim(), rest[at_pos + 1..].trim()))
 }
 
 /// Parse URL from ESE value string — handles multiple IE URL formats:
@@ -54,13 +163,11 @@ fn parse_url(text: &str) -> (Option<String>, Option<String>) {
     // IE History container: "Visited: Username@url"
     if let Some(rest) = text.strip_prefix("Visited:") {
         let rest = rest.trim();
-        if let Some(at_pos) = rest.find('@') {
-            let user = rest[..at_pos].trim().to_string();
-            let url = rest[at_pos + 1..].trim().to_string();
+        if let Some((user, url)) = split_user(rest) {
             if url.starts_with(":Host:") || url.starts_with(":host:") {
                 return (None, None);
             }
-            return (Some(url), Some(user));
+            return (Some(url.to_string()), Some(user.to_string()));
         }
         return (Some(rest.to_string()), None);
     }
@@ -70,16 +177,14 @@ fn parse_url(text: &str) -> (Option<String>, Option<String>) {
         // Find the second colon (end of date range)
         if let Some(second_colon) = after_first_colon.find(':') {
             let rest = after_first_colon[second_colon + 1..].trim(); // skip "daterange: "
-            if let Some(at_pos) = rest.find('@') {
-                let user = rest[..at_pos].trim().to_string();
-                let url = rest[at_pos + 1..].trim().to_string();
+            if let Some((user, url)) = split_user(rest) {
                 if url.starts_with(":Host:") || url.starts_with(":host:") {
                     return (None, None);
                 }
                 if url.is_empty() {
                     return (None, None);
                 }
-                return (Some(url), Some(user));
+                return (Some(url.to_string()), Some(user.to_string()));
             }
         }
         // Unrecognized colon-prefixed entry
@@ -98,8 +203,8 @@ fn parse_url(text: &str) -> (Option<String>, Option<String>) {
 pub fn extract(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
     let db_str = db_path.to_string_lossy().to_string();
 
-    let db =
-        EseDb::open(db_path).with_context(|| format!("Failed to open ESE database: {}", db_str))?;
+    let db = EseDb::open(super::long_path(db_path))
+        .with_context(|| format!("Failed to open ESE database: {}", db_str))?;
 
     // Find history container IDs from the Containers table
     let containers = db
@@ -113,12 +218,7 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
             Err(_) => continue,
         };
 
-        let vals: Vec<String> = rec
-            .iter_values()
-            .ok()
-            .into_iter()
-            .flat_map(|iter| iter.map(|v| v.map(|val| val.to_string()).unwrap_or_default()))
-            .collect();
+        let vals = record_texts(&rec);
 
         // Column 0 = ContainerId, Column 8 = Name
         if vals.len() > 8 {
@@ -167,16 +267,7 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
                 Err(_) => continue,
             };
 
-            let vals: Vec<String> = rec
-                .iter_values()
-                .ok()
-                .into_iter()
-                .flat_map(|iter| {
-                    iter.map(|v: std::io::Result<libesedb::Value>| {
-                        v.map(|val| val.to_string()).unwrap_or_default()
-                    })
-                })
-                .collect();
+            let vals = record_texts(&rec);
 
             // Get URL
             let url_raw = url_idx
@@ -227,11 +318,19 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
                 visit_time,
                 visit_count: access_count,
                 visited_from: String::new(),
+                app_id: String::new(),
+                journey: String::new(),
+                canonical_url: String::new(),
                 visit_type: String::new(),
                 visit_duration: String::new(),
+                view_time_ms: None,
+                key_presses: None,
+                hidden: false,
+                visited_link_confirmed: false,
                 web_browser: BrowserType::InternetExplorer.display_name().to_string(),
                 user_profile: effective_user,
                 browser_profile: String::new(),
+                account: String::new(),
                 typed_count: 0,
                 history_file: db_str.clone(),
                 record_id: entry_id,
@@ -254,3 +353,79 @@ pub fn extract(db_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
 
     Ok(entries)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_parse_ese_datetime() {
+        let expected = "2024-01-19 08:30:00.250";
+        let fmt = |s: &str| {
+            parse_ese_datetime(s)
+                .map(|dt| dt.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+                .unwrap_or_default()
+        };
+        // Raw FILETIME, as libesedb renders DateTime and I64 columns
+        assert_eq!(fmt("133501266002500000"), expected);
+        assert_eq!(fmt(" 133501266002500000 "), expected);
+
+        // Formatted strings, including day-first and fractional seconds
+        assert_eq!(fmt("Jan 19, 2024 08:30:00.250"), expected);
+        assert_eq!(fmt("19.01.2024 08:30:00.25"), expected);
+        assert_eq!(fmt("19/01/2024 08:30:00.250"), expected);
+        assert_eq!(fmt("2024-01-19T08:30:00.250Z"), expected);
+        assert_eq!(fmt("01/19/2024 08:30:00.250 AM"), expected);
+
+        assert_eq!(parse_ese_datetime("0"), None);
+        assert_eq!(parse_ese_datetime("Not set"), None);
+        assert_eq!(parse_ese_datetime("19 janvier 2024"), None);
+    }
+
+    #[test]
+    fn test_long_value_url_fully_recovered() {
+        let long_url = format!("https://example.com/search?q={}", "a".repeat(4096));
+        let raw = format!("Visited: jdoe@{}\0", long_url);
+
+        let text = decode_long_text(&utf16le(&raw));
+        let (url, user) = parse_url(&text);
+        assert_eq!(url.as_deref(), Some(long_url.as_str()));
+        assert_eq!(user.as_deref(), Some("jdoe"));
+
+        let text = decode_long_text(raw.as_bytes());
+        assert_eq!(parse_url(&text).0.map(|u| u.len()), Some(long_url.len()));
+    }
+
+    #[test]
+    fn test_domain_qualified_username() {
+        let (url, user) = parse_url("Visited: CORP\\jdoe@https://intranet.corp.local/");
+        assert_eq!(url.as_deref(), Some("https://intranet.corp.local/"));
+        assert_eq!(user.as_deref(), Some("CORP\\jdoe"));
+
+        // Non-ASCII name that survived the UTF-16 decode
+        let raw = utf16le(":2024011520240116: Jürgen@https://example.de/\0");
+        let (url, user) = parse_url(&decode_long_text(&raw));
+        assert_eq!(url.as_deref(), Some("https://example.de/"));
+        assert_eq!(user.as_deref(), Some("Jürgen"));
+
+        // UPN-style username containing its own '@'
+        let (url, user) = parse_url("Visited: jdoe@corp.example@https://example.com/");
+        assert_eq!(url.as_deref(), Some("https://example.com/"));
+        assert_eq!(user.as_deref(), Some("jdoe@corp.example"));
+    }
+
+    #[test]
+    fn test_url_with_userinfo() {
+        let (url, user) = parse_url("Visited: user@http://a@b.com/");
+        assert_eq!(url.as_deref(), Some("http://a@b.com/"));
+        assert_eq!(user.as_deref(), Some("user"));
+
+        let (url, user) = parse_url(":2024011520240116: user@ftp://anon:pw@files.example/x");
+        assert_eq!(url.as_deref(), Some("ftp://anon:pw@files.example/x"));
+        assert_eq!(user.as_deref(), Some("user"));
+    }
+}
@@ -0,0 +1,235 @@
+//! URL canonicalization for page-level dedup (`--canonical-dedup`).
+//!
+//! The same page is often recorded under several URLs that differ only in campaign
+//! tags (`utm_source=...`, `fbclid=...`) or `#fragment`. Canonical URLs drop those and
+//! lowercase the scheme and host, so such visits collapse to one row per page.
+
+use std::collections::HashMap;
+
+use crate::browsers::HistoryEntry;
+
+/// Query parameters stripped by default. A trailing `*` matches any suffix.
+pub const DEFAULT_TRACKING_PARAMS: &[&str] = &[
+    "utm_*", "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "igshid",
+    "mc_cid", "mc_eid", "_ga", "_gl",
+];
+
+/// Lowercased host of `url`, without credentials or port. `None` for URLs without a
+/// `scheme://host` part (`about:blank`, `file:///...`, bare search terms).
+pub fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://")?.1;
+    let authority = &rest[..rest.find(['/', '?', '#']).unwrap_or(rest.len())];
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let host = if host_port.starts_with('[') {
+        // IPv6 literal: the port follows the closing bracket
+        &host_port[..host_port.find(']').map_or(host_port.len(), |i| i + 1)]
+    } else {
+        host_port.split(':').next().unwrap_or(host_port)
+    };
+    let host = host.trim_end_matches('.');
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+/// Rewrites URLs to their canonical form.
+#[derive(Debug, Clone)]
+pub struct Canonicalizer {
+    /// Lowercased parameter names or `prefix*` patterns
+    params: Vec<String>,
+}
+
+impl Default for Canonicalizer {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRACKING_PARAMS)
+    }
+}
+
+impl Canonicalizer {
+    /// Strip the given tracking parameters (names matched case-insensitively; a trailing
+    /// `*` matches any suffix).
+    pub fn new<S: AsRef<str>>(params: &[S]) -> Self {
+        Self {
+            params: params
+                .iter()
+                .map(|p| p.as_ref().trim().to_lowercase())
+                .filter(|p| !p.is_empty())
+                .collect(),
+        }
+    }
+
+    fn is_tracking(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.params.iter().any(|p| match p.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == *p,
+        })
+    }
+
+    /// Lowercase the scheme and host, drop the fragment and remove tracking query
+    /// parameters (the remaining ones keep their order). Values without `://` are
+    /// returned with only the fragment removed.
+    pub fn canonicalize(&self, url: &str) -> String {
+        let url = url.split_once('#').map_or(url, |(u, _)| u);
+        let (scheme, rest) = match url.split_once("://") {
+            Some(parts) => parts,
+            None => return url.to_string(),
+        };
+        let (authority, path_query) = rest.split_at(rest.find(['/', '?']).unwrap_or(rest.len()));
+        // Credentials are case-sensitive; only the host part is lowercased
+        let authority = match authority.rsplit_once('@') {
+            Some((userinfo, host)) => format!("{userinfo}@{}", host.to_lowercase()),
+            None => authority.to_lowercase(),
+        };
+        let (path, query) = path_query.split_once('?').unwrap_or((path_query, ""));
+        let kept: Vec<&str> = query
+            .split('&')
+            .filter(|pair| {
+                let name = pair.split_once('=').map_or(*pair, |(n, _)| n);
+                !pair.is_empty() && !self.is_tracking(name)
+            })
+            .collect();
+
+        let mut out = format!("{}://{}{}", scheme.to_lowercase(), authority, path);
+        if !kept.is_empty() {
+            out.push('?');
+            out.push_str(&kept.join("&"));
+        }
+        out
+    }
+
+    /// Fill `canonical_url` on every entry, then keep only the earliest visit of each
+    /// canonical URL per browser and profile, across all of `files`. Returns the number
+    /// of rows removed.
+    pub fn dedup(&self, files: &mut [&mut Vec<HistoryEntry>]) -> usize {
+        for entries in files.iter_mut() {
+            for e in entries.iter_mut() {
+                e.canonical_url = self.canonicalize(&e.url);
+            }
+        }
+
+        // (file, row) of the earliest visit per page; the first one wins on ties
+        let mut keep: HashMap<(&str, &str, &str, &str), (usize, usize)> = HashMap::new();
+        for (f, entries) in files.iter().enumerate() {
+            for (i, e) in entries.iter().enumerate() {
+                let key = (
+                    e.canonical_url.as_str(),
+                    e.web_browser.as_str(),
+                    e.user_profile.as_str(),
+                    e.browser_profile.as_str(),
+                );
+                keep.entry(key)
+                    .and_modify(|best| {
+                        if e.visit_time < files[best.0][best.1].visit_time {
+                            *best = (f, i);
+                        }
+                    })
+                    .or_insert((f, i));
+            }
+        }
+        let mut retained = vec![Vec::new(); files.len()];
+        for (f, i) in keep.into_values() {
+            retained[f].push(i);
+        }
+
+        let mut removed = 0;
+        for (entries, mut rows) in files.iter_mut().zip(retained) {
+            rows.sort_unstable();
+            let before = entries.len();
+            let mut rows = rows.into_iter().peekable();
+            let mut i = 0;
+            entries.retain(|_| {
+                let kept = rows.next_if_eq(&i).is_some();
+                i += 1;
+                kept
+            });
+            removed += before - entries.len();
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn visit(url: &str, minute: u32) -> HistoryEntry {
+        HistoryEntry {
+            url: url.to_string(),
+            visit_time: Utc.with_ymd_and_hms(2024, 1, 15, 8, minute, 0).unwrap(),
+            visit_count: 1,
+            web_browser: "Chrome".to_string(),
+            user_profile: "alice".to_string(),
+            url_length: url.len(),
+            record_id: minute as i64,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let c = Canonicalizer::default();
+        assert_eq!(
+            c.canonicalize(
+                "HTTPS://News.Example.com/story?id=7&utm_source=tw&UTM_Medium=social#top"
+            ),
+            "https://news.example.com/story?id=7"
+        );
+        assert_eq!(
+            c.canonicalize("https://example.com/?fbclid=abc&gclid=def"),
+            "https://example.com/"
+        );
+        assert_eq!(
+            c.canonicalize("https://User:Pw@Example.com?q=Rust"),
+            "https://User:Pw@example.com?q=Rust"
+        );
+        assert_eq!(c.canonicalize("about:blank#x"), "about:blank");
+
+        let custom = Canonicalizer::new(&["ref"]);
+        assert_eq!(
+            custom.canonicalize("https://example.com/a?ref=hn&utm_source=x"),
+            "https://example.com/a?utm_source=x"
+        );
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("https://User:pw@WWW.Example.com:8443/a?b#c").as_deref(),
+            Some("www.example.com")
+        );
+        assert_eq!(url_host("http://[::1]:8080/").as_deref(), Some("[::1]"));
+        assert_eq!(
+            url_host("https://example.com.").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(url_host("file:///C:/x.html"), None);
+        assert_eq!(url_host("about:blank"), None);
+    }
+
+    #[test]
+    fn test_utm_variants_collapse() {
+        let mut live = vec![
+            visit("https://example.com/a?utm_source=newsletter", 5),
+            visit("https://example.com/b", 6),
+        ];
+        let mut archived = vec![
+            visit(
+                "https://Example.com/a?utm_source=twitter&utm_campaign=x#comments",
+                1,
+            ),
+            visit("https://example.com/a?page=2", 2),
+        ];
+        let removed = Canonicalizer::default().dedup(&mut [&mut live, &mut archived]);
+        assert_eq!(removed, 1);
+
+        // The earliest visit of the page is kept, with its original URL
+        assert_eq!(
+            archived[0].url,
+            "https://Example.com/a?utm_source=twitter&utm_campaign=x#comments"
+        );
+        assert_eq!(archived[0].canonical_url, "https://example.com/a");
+        assert_eq!(archived[1].canonical_url, "https://example.com/a?page=2");
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].url, "https://example.com/b");
+    }
+}
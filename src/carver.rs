@@ -2,8 +2,9 @@
 //!
 //! Recovers deleted browsing history entries by:
 //! 1. Scanning SQLite freelist pages for residual URL data
-//! 2. Parsing WAL (Write-Ahead Log) files for uncommitted/deleted entries
-//! 3. Raw byte scanning for URL patterns in unallocated space
+//! 2. Walking freeblocks and unallocated gaps inside live table b-tree leaf pages
+//! 3. Parsing WAL (Write-Ahead Log) files for uncommitted/deleted entries
+//! 4. Raw byte scanning for URL patterns in unallocated space
 //!
 //! Browser databases frequently contain deleted records because SQLite reuses
 //! freed pages lazily — the data persists until overwritten.
@@ -11,9 +12,12 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Duration, NaiveDate, Utc};
 use log::{debug, info, warn};
-use std::collections::HashSet;
-use std::fs;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::browsers::{long_path, HistoryEntry};
 
 /// A recovered (carved) browsing history entry.
 #[derive(Debug, Clone)]
@@ -24,6 +28,8 @@ pub struct CarvedEntry {
     pub browser_hint: String,
     pub source: CarveSource,
     pub source_file: String,
+    /// For entries recovered from a WAL, the frame they were found in
+    pub wal_frame: Option<WalFrame>,
 }
 
 /// Where the carved data was recovered from.
@@ -31,6 +37,8 @@ pub struct CarvedEntry {
 pub enum CarveSource {
     /// SQLite freelist page
     FreelistPage,
+    /// Freeblock or unallocated gap inside a live table b-tree leaf page
+    FreeblockCell,
     /// WAL (Write-Ahead Log) file
     WalFile,
     /// Raw byte scan of unallocated space
@@ -41,24 +49,145 @@ impl std::fmt::Display for CarveSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             CarveSource::FreelistPage => write!(f, "Freelist Page"),
+            CarveSource::FreeblockCell => write!(f, "Freeblock"),
             CarveSource::WalFile => write!(f, "WAL File"),
             CarveSource::RawScan => write!(f, "Raw Scan"),
         }
     }
 }
 
+/// One frame of a WAL file: its position and the fields of its 24-byte header that
+/// say which page it holds and whether SQLite would still use it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalFrame {
+    /// 1-based position of the frame in the WAL
+    pub index: u32,
+    /// Database page the frame is a copy of
+    pub page: u32,
+    /// Database size in pages after the commit, for a commit frame; 0 otherwise
+    pub commit_size: u32,
+    pub status: WalFrameStatus,
+}
+
+impl std::fmt::Display for WalFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "WAL frame {}, page {} ({})",
+            self.index, self.page, self.status
+        )
+    }
+}
+
+/// Whether a WAL frame is the copy of its page SQLite would read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalFrameStatus {
+    /// Committed, and the newest committed copy of its page
+    Current,
+    /// Committed, but a later committed frame holds a newer copy of the same page, so
+    /// rows here may have since been changed or deleted
+    Superseded,
+    /// Valid but after the last commit frame: a transaction that never committed
+    Uncommitted,
+    /// Salt or checksum doesn't match the WAL header: left over from before the WAL was
+    /// last reset and partly overwritten
+    Stale,
+}
+
+impl std::fmt::Display for WalFrameStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalFrameStatus::Current => write!(f, "current"),
+            WalFrameStatus::Superseded => write!(f, "superseded"),
+            WalFrameStatus::Uncommitted => write!(f, "uncommitted"),
+            WalFrameStatus::Stale => write!(f, "stale"),
+        }
+    }
+}
+
+/// Candidate counts per recovery source, plus the unique total after URL dedup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CarveStats {
+    pub freelist: usize,
+    pub freeblock: usize,
+    pub wal: usize,
+    pub raw: usize,
+    pub unique: usize,
+}
+
+impl std::ops::AddAssign for CarveStats {
+    fn add_assign(&mut self, other: Self) {
+        self.freelist += other.freelist;
+        self.freeblock += other.freeblock;
+        self.wal += other.wal;
+        self.raw += other.raw;
+        self.unique += other.unique;
+    }
+}
+
+impl std::fmt::Display for CarveStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "freelist: {}, freeblock: {}, WAL: {}, raw: {}, unique: {}",
+            self.freelist, self.freeblock, self.wal, self.raw, self.unique
+        )
+    }
+}
+
+/// Default `--min-url-len`.
+pub const DEFAULT_MIN_URL_LEN: usize = 12;
+
+/// Default `--min-domain-len`.
+pub const DEFAULT_MIN_DOMAIN_LEN: usize = 4;
+
+/// Default `--title-alpha-ratio`.
+pub const DEFAULT_TITLE_ALPHA_RATIO: f64 = 0.5;
+
+/// Thresholds deciding which carved strings are kept as URLs and titles. Loosen them to
+/// recover borderline fragments, tighten them to cut noise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CarveConfig {
+    /// Shortest URL kept, scheme included
+    pub min_url_len: usize,
+    /// Shortest host kept for http(s)/ftp URLs (which also need a dot in the host)
+    pub min_domain_len: usize,
+    /// Smallest share of letters and whitespace in a string taken as a page title
+    pub title_alpha_ratio: f64,
+}
+
+impl Default for CarveConfig {
+    fn default() -> Self {
+        Self {
+            min_url_len: DEFAULT_MIN_URL_LEN,
+            min_domain_len: DEFAULT_MIN_DOMAIN_LEN,
+            title_alpha_ratio: DEFAULT_TITLE_ALPHA_RATIO,
+        }
+    }
+}
+
 /// Main entry point: carve deleted entries from a browser database file.
-pub fn carve(db_path: &Path) -> Result<Vec<CarvedEntry>> {
+pub fn carve(db_path: &Path, config: &CarveConfig) -> Result<Vec<CarvedEntry>> {
+    carve_with_stats(db_path, config).map(|(entries, _)| entries)
+}
+
+/// Like [`carve`], but also returns per-source candidate counts.
+pub fn carve_with_stats(
+    db_path: &Path,
+    config: &CarveConfig,
+) -> Result<(Vec<CarvedEntry>, CarveStats)> {
     let db_str = db_path.to_string_lossy().to_string();
     info!("Carving deleted entries from: {}", db_str);
 
     let mut entries = Vec::new();
     let mut seen_urls = HashSet::new();
+    let mut stats = CarveStats::default();
 
     // 1. Scan freelist pages in the main database
-    match carve_freelist_pages(db_path) {
+    match carve_freelist_pages(db_path, config) {
         Ok(carved) => {
             info!("  Freelist carving: {} candidate entries", carved.len());
+            stats.freelist = carved.len();
             for e in carved {
                 if seen_urls.insert(e.url.clone()) {
                     entries.push(e);
@@ -68,7 +197,21 @@ pub fn carve(db_path: &Path) -> Result<Vec<CarvedEntry>> {
         Err(e) => warn!("  Freelist carving failed: {}", e),
     }
 
-    // 2. Parse WAL file if present
+    // 2. Walk freeblocks and unallocated gaps in live leaf table pages
+    match carve_freeblocks(db_path, config) {
+        Ok(carved) => {
+            info!("  Freeblock carving: {} candidate entries", carved.len());
+            stats.freeblock = carved.len();
+            for e in carved {
+                if seen_urls.insert(e.url.clone()) {
+                    entries.push(e);
+                }
+            }
+        }
+        Err(e) => warn!("  Freeblock carving failed: {}", e),
+    }
+
+    // 3. Parse WAL file if present
     let wal_path = db_path.with_extension(
         db_path
             .extension()
@@ -85,14 +228,15 @@ pub fn carve(db_path: &Path) -> Result<Vec<CarvedEntry>> {
     ];
 
     for wal in &wal_candidates {
-        if wal.exists() {
-            match carve_wal_file(wal, &db_str) {
+        if long_path(wal).exists() {
+            match carve_wal_file(wal, &db_str, config) {
                 Ok(carved) => {
                     info!(
                         "  WAL carving ({}): {} candidate entries",
                         wal.display(),
                         carved.len()
                     );
+                    stats.wal = carved.len();
                     for e in carved {
                         if seen_urls.insert(e.url.clone()) {
                             entries.push(e);
@@ -105,10 +249,11 @@ pub fn carve(db_path: &Path) -> Result<Vec<CarvedEntry>> {
         }
     }
 
-    // 3. Raw byte scan of the entire database file
-    match carve_raw_urls(db_path) {
+    // 4. Raw byte scan of the entire database file
+    match carve_raw_urls(db_path, config) {
         Ok(carved) => {
             info!("  Raw URL scan: {} candidate entries", carved.len());
+            stats.raw = carved.len();
             for e in carved {
                 if seen_urls.insert(e.url.clone()) {
                     entries.push(e);
@@ -123,7 +268,8 @@ pub fn carve(db_path: &Path) -> Result<Vec<CarvedEntry>> {
         entries.len(),
         db_str
     );
-    Ok(entries)
+    stats.unique = entries.len();
+    Ok((entries, stats))
 }
 
 /// Read the SQLite header to get page size and freelist info.
@@ -134,7 +280,9 @@ struct SqliteHeader {
     total_pages: u32,
 }
 
-fn read_sqlite_header(data: &[u8]) -> Result<SqliteHeader> {
+/// Parse the 100-byte header at the start of `data`; `file_len` sizes databases whose
+/// header leaves the page count at zero.
+fn read_sqlite_header(data: &[u8], file_len: u64) -> Result<SqliteHeader> {
     if data.len() < 100 {
         anyhow::bail!("File too small for SQLite header");
     }
@@ -156,7 +304,7 @@ fn read_sqlite_header(data: &[u8]) -> Result<SqliteHeader> {
 
     let db_size_pages = u32::from_be_bytes([data[28], data[29], data[30], data[31]]);
     let total_pages = if db_size_pages == 0 {
-        (data.len() as u32) / page_size
+        (file_len / page_size as u64) as u32
     } else {
         db_size_pages
     };
@@ -169,10 +317,26 @@ fn read_sqlite_header(data: &[u8]) -> Result<SqliteHeader> {
     })
 }
 
+/// Open a database file and read its header. The file is left positioned after it.
+fn open_sqlite(db_path: &Path) -> Result<(File, SqliteHeader)> {
+    let mut file = File::open(long_path(db_path)).context("Failed to read database file")?;
+    let file_len = file.metadata()?.len();
+    let mut head = Vec::with_capacity(100);
+    (&mut file).take(100).read_to_end(&mut head)?;
+    let header = read_sqlite_header(&head, file_len)?;
+    Ok((file, header))
+}
+
+/// Read page `page_number` (1-based) into `page`. False when the file ends first.
+fn read_page(file: &mut File, page_number: u32, page: &mut [u8]) -> Result<bool> {
+    let offset = (page_number as u64 - 1) * page.len() as u64;
+    file.seek(SeekFrom::Start(offset))?;
+    read_full(file, page)
+}
+
 /// Carve URL-like strings from SQLite freelist pages.
-fn carve_freelist_pages(db_path: &Path) -> Result<Vec<CarvedEntry>> {
-    let data = fs::read(db_path).context("Failed to read database file")?;
-    let header = read_sqlite_header(&data)?;
+fn carve_freelist_pages(db_path: &Path, config: &CarveConfig) -> Result<Vec<CarvedEntry>> {
+    let (mut file, header) = open_sqlite(db_path)?;
     let db_str = db_path.to_string_lossy().to_string();
 
     debug!(
@@ -191,6 +355,8 @@ fn carve_freelist_pages(db_path: &Path) -> Result<Vec<CarvedEntry>> {
     let mut entries = Vec::new();
     let mut visited_pages = HashSet::new();
     let mut trunk_page = header.freelist_trunk_page;
+    let mut page = vec![0u8; header.page_size as usize];
+    let mut leaf_data = vec![0u8; header.page_size as usize];
 
     // Walk the freelist trunk chain
     while trunk_page > 0 && trunk_page <= header.total_pages {
@@ -198,13 +364,10 @@ fn carve_freelist_pages(db_path: &Path) -> Result<Vec<CarvedEntry>> {
             break; // Cycle detection
         }
 
-        let offset = ((trunk_page - 1) as usize) * (header.page_size as usize);
-        if offset + (header.page_size as usize) > data.len() {
+        if !read_page(&mut file, trunk_page, &mut page)? {
             break;
         }
 
-        let page = &data[offset..offset + header.page_size as usize];
-
         // First 4 bytes: next trunk page pointer
         let next_trunk = u32::from_be_bytes([page[0], page[1], page[2], page[3]]);
         // Next 4 bytes: count of leaf page pointers on this trunk page
@@ -212,13 +375,14 @@ fn carve_freelist_pages(db_path: &Path) -> Result<Vec<CarvedEntry>> {
 
         // Scan the trunk page itself for URL data
         entries.extend(extract_urls_from_page(
-            page,
+            &page,
             &db_str,
             CarveSource::FreelistPage,
+            config,
         ));
 
         // Scan each leaf page
-        for i in 0..leaf_count.min(((header.page_size - 8) / 4) as u32) {
+        for i in 0..leaf_count.min((header.page_size - 8) / 4) {
             let ptr_offset = 8 + (i as usize) * 4;
             if ptr_offset + 4 > page.len() {
                 break;
@@ -230,16 +394,16 @@ fn carve_freelist_pages(db_path: &Path) -> Result<Vec<CarvedEntry>> {
                 page[ptr_offset + 3],
             ]);
 
-            if leaf_page > 0 && leaf_page <= header.total_pages {
-                let leaf_offset = ((leaf_page - 1) as usize) * (header.page_size as usize);
-                if leaf_offset + (header.page_size as usize) <= data.len() {
-                    let leaf_data = &data[leaf_offset..leaf_offset + header.page_size as usize];
-                    entries.extend(extract_urls_from_page(
-                        leaf_data,
-                        &db_str,
-                        CarveSource::FreelistPage,
-                    ));
-                }
+            if leaf_page > 0
+                && leaf_page <= header.total_pages
+                && read_page(&mut file, leaf_page, &mut leaf_data)?
+            {
+                entries.extend(extract_urls_from_page(
+                    &leaf_data,
+                    &db_str,
+                    CarveSource::FreelistPage,
+                    config,
+                ));
             }
         }
 
@@ -249,54 +413,320 @@ fn carve_freelist_pages(db_path: &Path) -> Result<Vec<CarvedEntry>> {
     Ok(entries)
 }
 
-/// Carve URL data from a WAL (Write-Ahead Log) file.
-fn carve_wal_file(wal_path: &Path, source_db: &str) -> Result<Vec<CarvedEntry>> {
-    let data = fs::read(wal_path).context("Failed to read WAL file")?;
+/// Carve URL-like strings from the free space of live leaf table b-tree pages.
+///
+/// Deleting a row rarely frees its page: SQLite just unlinks the cell and adds its bytes
+/// to the page's freeblock chain, where the record survives until the space is reused or
+/// the database is vacuumed. For every 0x0D page this walks that chain and also scans the
+/// unallocated gap between the cell pointer array and the cell content area.
+fn carve_freeblocks(db_path: &Path, config: &CarveConfig) -> Result<Vec<CarvedEntry>> {
+    let (mut file, header) = open_sqlite(db_path)?;
+    let db_str = db_path.to_string_lossy().to_string();
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = BufReader::new(file);
+    let mut page = vec![0u8; header.page_size as usize];
+
+    let mut entries = Vec::new();
+    let mut page_number = 0;
+    // A partial page at the end of the file is ignored
+    while read_full(&mut reader, &mut page)? {
+        page_number += 1;
+        for region in leaf_free_regions(&page, page_number) {
+            entries.extend(extract_urls_from_page(
+                &page[region],
+                &db_str,
+                CarveSource::FreeblockCell,
+                config,
+            ));
+        }
+    }
+    Ok(entries)
+}
+
+/// Fill `buf` from `reader`. False when the input ends first.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Byte ranges of a leaf table page that hold no live cell: each freeblock (minus its
+/// 4-byte next/size header) and the gap before the cell content area. Empty for any
+/// other page type.
+fn leaf_free_regions(page: &[u8], page_number: u32) -> Vec<std::ops::Range<usize>> {
+    // Page 1 starts with the 100-byte database header
+    let hdr = if page_number == 1 { 100 } else { 0 };
+    let mut regions = Vec::new();
+    if page.len() < hdr + 8 || page[hdr] != 0x0d {
+        return regions;
+    }
+    let be16 = |at: usize| u16::from_be_bytes([page[at], page[at + 1]]) as usize;
+
+    let cell_count = be16(hdr + 3);
+    let content_start = match be16(hdr + 5) {
+        0 => 65536,
+        n => n,
+    };
+    let gap_start = hdr + 8 + cell_count * 2;
+    let gap_end = content_start.min(page.len());
+    if gap_start < gap_end {
+        regions.push(gap_start..gap_end);
+    }
+
+    // Freeblocks are chained in ascending offset order, which also rules out cycles
+    let mut freeblock = be16(hdr + 1);
+    let mut last = 0;
+    while freeblock > last && freeblock + 4 <= page.len() {
+        let size = be16(freeblock + 2);
+        let end = (freeblock + size).min(page.len());
+        if freeblock + 4 < end {
+            regions.push(freeblock + 4..end);
+        }
+        last = freeblock;
+        freeblock = be16(freeblock);
+    }
+    regions
+}
 
-    if data.len() < 32 {
+/// Call `on_frame` with the 1-based index, page number and page bytes of each complete
+/// frame of a WAL file, reading one frame at a time. A partial frame at the end is
+/// ignored.
+///
+/// Returns the header of every frame read. A frame is valid when its salt matches the
+/// WAL header and the running checksum holds up to and including it, as SQLite checks
+/// on recovery; valid frames up to the last commit frame are committed, and of those
+/// only the last copy of each page is current.
+fn for_each_wal_frame(
+    wal_path: &Path,
+    mut on_frame: impl FnMut(u32, u32, &[u8]),
+) -> Result<Vec<WalFrame>> {
+    let file = File::open(long_path(wal_path)).context("Failed to read WAL file")?;
+    let mut reader = BufReader::new(file);
+
+    let mut header = [0u8; 32];
+    if !read_full(&mut reader, &mut header)? {
         anyhow::bail!("WAL file too small");
     }
 
     // WAL header: magic number check
-    let magic = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+    let magic = be32(&header, 0);
     if magic != 0x377f0682 && magic != 0x377f0683 {
         anyhow::bail!("Invalid WAL magic number: 0x{:08x}", magic);
     }
+    // The low bit of the magic says which byte order the checksums were computed in
+    let big_endian = magic & 1 == 1;
 
-    let page_size = u32::from_be_bytes([data[8], data[9], data[10], data[11]]) as usize;
+    let page_size = be32(&header, 8) as usize;
     if page_size == 0 || page_size > 65536 {
         anyhow::bail!("Invalid WAL page size: {}", page_size);
     }
+    let salt = &header[16..24];
+
+    // Each frame = 24-byte header + page_size bytes. The header holds the page number,
+    // the commit size, the salt copied from the WAL header and the running checksum
+    let mut frames = Vec::new();
+    let mut checksum = (be32(&header, 24), be32(&header, 28));
+    let mut chain_intact = true;
+    let mut frame = vec![0u8; 24 + page_size];
+    while read_full(&mut reader, &mut frame)? {
+        let index = frames.len() as u32 + 1;
+        let page = be32(&frame, 0);
+        if chain_intact {
+            checksum = wal_checksum(&frame[..8], checksum, big_endian);
+            checksum = wal_checksum(&frame[24..], checksum, big_endian);
+            chain_intact =
+                &frame[8..16] == salt && checksum == (be32(&frame, 16), be32(&frame, 20));
+        }
+        frames.push(WalFrame {
+            index,
+            page,
+            commit_size: be32(&frame, 4),
+            status: if chain_intact {
+                WalFrameStatus::Current
+            } else {
+                WalFrameStatus::Stale
+            },
+        });
+        on_frame(index, page, &frame[24..]);
+    }
 
-    let mut entries = Vec::new();
-    let frame_header_size = 24;
-    let wal_header_size = 32;
+    resolve_wal_frame_status(&mut frames);
+    Ok(frames)
+}
 
-    // Walk WAL frames: each frame = 24-byte header + page_size bytes
-    let mut offset = wal_header_size;
-    while offset + frame_header_size + page_size <= data.len() {
-        let page_data = &data[offset + frame_header_size..offset + frame_header_size + page_size];
-        entries.extend(extract_urls_from_page(
-            page_data,
-            source_db,
-            CarveSource::WalFile,
-        ));
-        offset += frame_header_size + page_size;
+/// Mark valid frames after the last commit as uncommitted, and committed frames with a
+/// later committed copy of the same page as superseded.
+fn resolve_wal_frame_status(frames: &mut [WalFrame]) {
+    let last_commit = frames
+        .iter()
+        .filter(|f| f.status != WalFrameStatus::Stale && f.commit_size != 0)
+        .map(|f| f.index)
+        .max()
+        .unwrap_or(0);
+    let mut newest: HashMap<u32, u32> = HashMap::new();
+    for frame in frames.iter_mut() {
+        if frame.status == WalFrameStatus::Stale {
+            continue;
+        }
+        if frame.index > last_commit {
+            frame.status = WalFrameStatus::Uncommitted;
+        } else {
+            newest.insert(frame.page, frame.index);
+        }
+    }
+    for frame in frames.iter_mut() {
+        if frame.status == WalFrameStatus::Current && newest[&frame.page] != frame.index {
+            frame.status = WalFrameStatus::Superseded;
+        }
+    }
+}
+
+/// SQLite's WAL checksum of `data` (a multiple of 8 bytes), continuing from `(s0, s1)`.
+fn wal_checksum(data: &[u8], (mut s0, mut s1): (u32, u32), big_endian: bool) -> (u32, u32) {
+    let word = |b: &[u8]| {
+        let b = [b[0], b[1], b[2], b[3]];
+        if big_endian {
+            u32::from_be_bytes(b)
+        } else {
+            u32::from_le_bytes(b)
+        }
+    };
+    for pair in data.chunks_exact(8) {
+        s0 = s0.wrapping_add(word(&pair[..4])).wrapping_add(s1);
+        s1 = s1.wrapping_add(word(&pair[4..])).wrapping_add(s0);
+    }
+    (s0, s1)
+}
+
+fn be32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+/// Carve URL data from a WAL (Write-Ahead Log) file.
+fn carve_wal_file(
+    wal_path: &Path,
+    source_db: &str,
+    config: &CarveConfig,
+) -> Result<Vec<CarvedEntry>> {
+    // Safari's visit times are f64 Core Data values the URL scan can't place, so its
+    // WAL records are decoded first and take precedence in the URL dedup
+    let mut entries = if Path::new(source_db)
+        .file_name()
+        .is_some_and(|n| n == "History.db")
+    {
+        carve_safari_wal_records(wal_path, source_db)?
+    } else {
+        Vec::new()
+    };
+
+    let mut found = Vec::new();
+    let frames = for_each_wal_frame(wal_path, |index, _, page| {
+        for entry in extract_urls_from_page(page, source_db, CarveSource::WalFile, config) {
+            found.push((index, entry));
+        }
+    })?;
+    for (index, mut entry) in found {
+        entry.wal_frame = Some(frames[index as usize - 1]);
+        entries.push(entry);
     }
 
     Ok(entries)
 }
 
+/// Bytes read per step of the raw scan.
+const RAW_WINDOW: usize = 4 * 1024 * 1024;
+
+/// Bytes of context kept on each side of a window edge. A URL (with the title searched
+/// for before it) that straddles the edge is seen whole as long as it is shorter.
+const RAW_OVERLAP: usize = 16 * 1024;
+
 /// Scan the raw database file for URL patterns in potentially unallocated space.
-fn carve_raw_urls(db_path: &Path) -> Result<Vec<CarvedEntry>> {
-    let data = fs::read(db_path).context("Failed to read database file")?;
+fn carve_raw_urls(db_path: &Path, config: &CarveConfig) -> Result<Vec<CarvedEntry>> {
+    let file = File::open(long_path(db_path)).context("Failed to read database file")?;
     let db_str = db_path.to_string_lossy().to_string();
-    Ok(extract_urls_from_page(&data, &db_str, CarveSource::RawScan))
+    carve_raw_reader(file, &db_str, config)
+}
+
+/// Raw URL scan over an in-memory buffer (the last carving pass), attributing hits to
+/// `source_file`.
+pub fn carve_raw_buffer(data: &[u8], source_file: &str, config: &CarveConfig) -> Vec<CarvedEntry> {
+    extract_urls_from_page(data, source_file, CarveSource::RawScan, config)
+}
+
+/// Raw URL scan that reads `reader` in fixed-size windows, so memory use doesn't grow
+/// with the input. Gives the same hits as [`carve_raw_buffer`] over the whole input.
+pub fn carve_raw_reader(
+    reader: impl Read,
+    source_file: &str,
+    config: &CarveConfig,
+) -> Result<Vec<CarvedEntry>> {
+    carve_raw_windows(reader, source_file, RAW_WINDOW, config)
+}
+
+fn carve_raw_windows(
+    mut reader: impl Read,
+    source_file: &str,
+    window: usize,
+    config: &CarveConfig,
+) -> Result<Vec<CarvedEntry>> {
+    debug_assert!(window > RAW_OVERLAP);
+    let mut buf: Vec<u8> = Vec::with_capacity(window + 2 * RAW_OVERLAP);
+    let mut entries = Vec::new();
+    // Hits starting before this offset in `buf` were reported from the previous window
+    let mut owned_from = 0;
+    loop {
+        let read = (&mut reader).take(window as u64).read_to_end(&mut buf)?;
+        let at_eof = read < window;
+        // Hits in the last RAW_OVERLAP bytes are left to the next window, which sees
+        // how they end
+        let owned_to = if at_eof {
+            buf.len()
+        } else {
+            buf.len() - RAW_OVERLAP
+        };
+        for (start, entry) in scan_urls(&buf, source_file, CarveSource::RawScan, config) {
+            if (owned_from..owned_to).contains(&start) {
+                entries.push(entry);
+            }
+        }
+        if at_eof {
+            return Ok(entries);
+        }
+        // Keep the unreported tail plus RAW_OVERLAP bytes before it for titles
+        let keep_from = owned_to.saturating_sub(RAW_OVERLAP);
+        buf.drain(..keep_from);
+        owned_from = owned_to - keep_from;
+    }
 }
 
 /// Extract URL strings from a page/buffer of bytes.
 /// Looks for common URL prefixes and extracts the full string.
-fn extract_urls_from_page(data: &[u8], source_file: &str, source: CarveSource) -> Vec<CarvedEntry> {
+fn extract_urls_from_page(
+    data: &[u8],
+    source_file: &str,
+    source: CarveSource,
+    config: &CarveConfig,
+) -> Vec<CarvedEntry> {
+    scan_urls(data, source_file, source, config)
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .collect()
+}
+
+/// [`extract_urls_from_page`], with the offset each URL starts at.
+fn scan_urls(
+    data: &[u8],
+    source_file: &str,
+    source: CarveSource,
+    config: &CarveConfig,
+) -> Vec<(usize, CarvedEntry)> {
     let mut entries = Vec::new();
     let prefixes: &[&[u8]] = &[b"https://", b"http://", b"ftp://", b"file:///"];
 
@@ -363,10 +793,10 @@ fn extract_urls_from_page(data: &[u8], source_file: &str, source: CarveSource) -
         if let Ok(url) = std::str::from_utf8(url_bytes) {
             let url = url.to_string();
 
-            // Filter: must be at least 12 chars and look like a real URL
-            if url.len() >= 12 && is_plausible_url(&url) {
+            // Filter: must be long enough and look like a real URL
+            if url.len() >= config.min_url_len && is_plausible_url(&url, config) {
                 // Try to find a title nearby — but only non-URL text
-                let title = find_nearby_title(data, start, end);
+                let title = find_nearby_title(data, start, end, config);
 
                 // Try to find a timestamp nearby (only for structured sources, not raw scan)
                 let visit_time = if source != CarveSource::RawScan {
@@ -375,14 +805,18 @@ fn extract_urls_from_page(data: &[u8], source_file: &str, source: CarveSource) -
                     None
                 };
 
-                entries.push(CarvedEntry {
-                    url,
-                    title: title.unwrap_or_default(),
-                    visit_time,
-                    browser_hint: guess_browser_from_url(source_file),
-                    source: source.clone(),
-                    source_file: source_file.to_string(),
-                });
+                entries.push((
+                    start,
+                    CarvedEntry {
+                        url,
+                        title: title.unwrap_or_default(),
+                        visit_time,
+                        browser_hint: guess_browser_from_url(source_file),
+                        source: source.clone(),
+                        source_file: source_file.to_string(),
+                        wal_frame: None,
+                    },
+                ));
             }
         }
 
@@ -393,7 +827,7 @@ fn extract_urls_from_page(data: &[u8], source_file: &str, source: CarveSource) -
 }
 
 /// Check if a URL looks plausible (not just a fragment or garbage).
-fn is_plausible_url(url: &str) -> bool {
+fn is_plausible_url(url: &str, config: &CarveConfig) -> bool {
     // Must have a domain-like component after the scheme
     if let Some(rest) = url
         .strip_prefix("http://")
@@ -403,7 +837,7 @@ fn is_plausible_url(url: &str) -> bool {
         // Must have at least one dot in the domain
         let domain_end = rest.find('/').unwrap_or(rest.len());
         let domain = &rest[..domain_end];
-        domain.contains('.') && domain.len() >= 4
+        domain.contains('.') && domain.len() >= config.min_domain_len
     } else if url.starts_with("file:///") {
         url.len() > 10
     } else {
@@ -413,7 +847,14 @@ fn is_plausible_url(url: &str) -> bool {
 
 /// Try to find a page title near a URL in the binary data.
 /// Only returns text that looks like a real title (not another URL or path).
-fn find_nearby_title(data: &[u8], url_start: usize, _url_end: usize) -> Option<String> {
+/// Both UTF-8 and UTF-16LE (Chrome and IE on Windows) titles are considered; the longer
+/// plausible candidate wins.
+fn find_nearby_title(
+    data: &[u8],
+    url_start: usize,
+    _url_end: usize,
+    config: &CarveConfig,
+) -> Option<String> {
     // Look backwards from URL start for a text string
     let search_range = 200.min(url_start);
     if search_range < 5 {
@@ -422,7 +863,16 @@ fn find_nearby_title(data: &[u8], url_start: usize, _url_end: usize) -> Option<S
 
     let region = &data[url_start - search_range..url_start];
 
-    // Find the last printable UTF-8 string in the region
+    let utf8 = find_utf8_title(region, config.title_alpha_ratio);
+    let utf16 = find_utf16_title(region, config.title_alpha_ratio);
+    [utf8, utf16]
+        .into_iter()
+        .flatten()
+        .max_by_key(|s| s.chars().count())
+}
+
+/// The last printable UTF-8 string in `region` that passes [`is_plausible_title`].
+fn find_utf8_title(region: &[u8], alpha_ratio: f64) -> Option<String> {
     let mut i = region.len();
 
     while i > 0 {
@@ -439,7 +889,7 @@ fn find_nearby_title(data: &[u8], url_start: usize, _url_end: usize) -> Option<S
         let text = &region[i..text_end];
         if let Ok(s) = std::str::from_utf8(text) {
             let s = s.trim();
-            if is_plausible_title(s) {
+            if is_plausible_title(s, alpha_ratio) {
                 return Some(s.to_string());
             }
         }
@@ -452,8 +902,52 @@ fn find_nearby_title(data: &[u8], url_start: usize, _url_end: usize) -> Option<S
     None
 }
 
-/// Check if text looks like a plausible page title rather than a URL fragment.
-fn is_plausible_title(s: &str) -> bool {
+/// The last run of printable UTF-16LE code units in `region` that decodes to a
+/// plausible title. Both byte alignments are tried since the string can start anywhere.
+fn find_utf16_title(region: &[u8], alpha_ratio: f64) -> Option<String> {
+    let printable = |unit: u16| match unit {
+        0..=0x1f | 0x7f => false,
+        0x20..=0xff => true,
+        _ => char::from_u32(unit as u32).is_some_and(char::is_alphabetic),
+    };
+
+    let mut best: Option<String> = None;
+    for parity in 0..2 {
+        let units: Vec<u16> = region[parity..]
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+
+        let mut end = units.len();
+        while end > 0 {
+            if !printable(units[end - 1]) {
+                end -= 1;
+                continue;
+            }
+            let mut start = end - 1;
+            while start > 0 && printable(units[start - 1]) {
+                start -= 1;
+            }
+
+            let decoded: String = char::decode_utf16(units[start..end].iter().copied())
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect();
+            let decoded = decoded.trim();
+            if is_plausible_title(decoded, alpha_ratio) {
+                if best.as_ref().is_none_or(|b| decoded.len() > b.len()) {
+                    best = Some(decoded.to_string());
+                }
+                break;
+            }
+            end = start;
+        }
+    }
+    best
+}
+
+/// Check if text looks like a plausible page title rather than a URL fragment. At least
+/// `alpha_ratio` of it must be letters or whitespace.
+fn is_plausible_title(s: &str, alpha_ratio: f64) -> bool {
     if s.len() < 4 || s.len() > 500 {
         return false;
     }
@@ -476,7 +970,7 @@ fn is_plausible_title(s: &str) -> bool {
         .filter(|c| c.is_alphabetic() || c.is_whitespace())
         .count();
     let ratio = alpha_count as f64 / s.len() as f64;
-    if ratio < 0.5 {
+    if ratio < alpha_ratio {
         return false;
     }
     // Must contain at least one space or be a recognizable word
@@ -583,6 +1077,8 @@ fn guess_browser_from_url(path: &str) -> String {
         "Opera".to_string()
     } else if lower.contains("vivaldi") {
         "Vivaldi".to_string()
+    } else if lower.contains("yandex") {
+        "Yandex Browser".to_string()
     } else if lower.contains("arc") {
         "Arc".to_string()
     } else {
@@ -618,11 +1114,414 @@ fn linearize_carved(entry: &CarvedEntry) -> String {
     parts.push(format!("({})", crate::browsers::truncate_str(&entry.url, 200)));
 
     // Recovery source
-    parts.push(format!("| Carved from {}", entry.source));
+    match entry.wal_frame {
+        Some(frame) => parts.push(format!("| Carved from {}", frame)),
+        None => parts.push(format!("| Carved from {}", entry.source)),
+    }
 
     parts.join(" ")
 }
 
+// ============================================================================
+// Structured WAL recovery (Firefox places.sqlite without its main database, Safari
+// History.db visits)
+// ============================================================================
+
+/// A decoded SQLite record value.
+#[derive(Debug, Clone, PartialEq)]
+enum CellValue {
+    Null,
+    Int(i64),
+    Real(f64),
+    Text(String),
+    Blob,
+}
+
+impl CellValue {
+    fn as_int(&self) -> Option<i64> {
+        match self {
+            CellValue::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    fn as_text(&self) -> Option<&str> {
+        match self {
+            CellValue::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// Read a SQLite varint at `pos`. Returns (value, bytes consumed).
+fn read_varint(data: &[u8], pos: usize) -> Option<(u64, usize)> {
+    let mut value: u64 = 0;
+    for i in 0..9 {
+        let byte = *data.get(pos + i)?;
+        if i == 8 {
+            return Some(((value << 8) | byte as u64, 9));
+        }
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Decode a record payload (header of serial types followed by the column values).
+fn parse_record(payload: &[u8]) -> Option<Vec<CellValue>> {
+    let (header_len, n) = read_varint(payload, 0)?;
+    let header_len = header_len as usize;
+    if header_len > payload.len() {
+        return None;
+    }
+    let mut types = Vec::new();
+    let mut pos = n;
+    while pos < header_len {
+        let (t, n) = read_varint(payload, pos)?;
+        types.push(t);
+        pos += n;
+    }
+
+    let mut values = Vec::with_capacity(types.len());
+    let mut pos = header_len;
+    for t in types {
+        let int_len = match t {
+            1..=4 => t as usize,
+            5 => 6,
+            6 | 7 => 8,
+            _ => 0,
+        };
+        let value = match t {
+            0 => CellValue::Null,
+            1..=6 => {
+                let bytes = payload.get(pos..pos + int_len)?;
+                // Sign-extend the big-endian two's complement value
+                let mut v: i64 = if bytes[0] & 0x80 != 0 { -1 } else { 0 };
+                for b in bytes {
+                    v = (v << 8) | *b as i64;
+                }
+                pos += int_len;
+                CellValue::Int(v)
+            }
+            7 => {
+                let bytes = payload.get(pos..pos + 8)?;
+                pos += 8;
+                CellValue::Real(f64::from_be_bytes(bytes.try_into().ok()?))
+            }
+            8 => CellValue::Int(0),
+            9 => CellValue::Int(1),
+            10 | 11 => return None,
+            n => {
+                let len = ((n - 12) / 2) as usize;
+                let bytes = payload.get(pos..pos + len)?;
+                pos += len;
+                if n % 2 == 1 {
+                    CellValue::Text(String::from_utf8_lossy(bytes).into_owned())
+                } else {
+                    CellValue::Blob
+                }
+            }
+        };
+        values.push(value);
+    }
+    Some(values)
+}
+
+/// Decode every cell on a table b-tree leaf page into (rowid, values).
+/// Cells that spill onto overflow pages are skipped, since those pages may be missing.
+fn parse_table_leaf_page(page: &[u8], page_number: u32) -> Vec<(i64, Vec<CellValue>)> {
+    // Page 1 starts with the 100-byte database header
+    let hdr = if page_number == 1 { 100 } else { 0 };
+    let mut rows = Vec::new();
+    if page.len() < hdr + 8 || page[hdr] != 0x0d {
+        return rows;
+    }
+    let cell_count = u16::from_be_bytes([page[hdr + 3], page[hdr + 4]]) as usize;
+    let max_local = page.len().saturating_sub(35);
+
+    for i in 0..cell_count {
+        let ptr = hdr + 8 + i * 2;
+        let cell = match page.get(ptr..ptr + 2) {
+            Some(b) => u16::from_be_bytes([b[0], b[1]]) as usize,
+            None => break,
+        };
+        let (payload_len, n1) = match read_varint(page, cell) {
+            Some(v) => v,
+            None => continue,
+        };
+        let (rowid, n2) = match read_varint(page, cell + n1) {
+            Some(v) => v,
+            None => continue,
+        };
+        let payload_len = payload_len as usize;
+        if payload_len > max_local {
+            continue;
+        }
+        let start = cell + n1 + n2;
+        if let Some(values) = page.get(start..start + payload_len).and_then(parse_record) {
+            rows.push((rowid as i64, values));
+        }
+    }
+    rows
+}
+
+/// PRTime values between 2000 and 2100, to tell timestamps apart from other integers.
+fn is_plausible_prtime(v: i64) -> bool {
+    (946_684_800_000_000..4_102_444_800_000_000).contains(&v)
+}
+
+/// A `moz_places` row: (url, title, visit_count, last_visit_date).
+/// Column layout: id, url, title, rev_host, visit_count, hidden, typed, [favicon_id,]
+/// frecency, last_visit_date, ... (favicon_id was dropped in Firefox 55).
+fn as_place(values: &[CellValue]) -> Option<(String, String, u32, Option<i64>)> {
+    if values.len() < 9 || values[0] != CellValue::Null {
+        return None;
+    }
+    let url = values[1].as_text()?;
+    if !url.contains(':') || !matches!(values[3], CellValue::Text(_) | CellValue::Null) {
+        return None;
+    }
+    let visit_count = values[4].as_int()?;
+    let last_visit = values[8..values.len().min(10)]
+        .iter()
+        .filter_map(CellValue::as_int)
+        .find(|v| is_plausible_prtime(*v));
+    let title = values[2].as_text().unwrap_or_default().to_string();
+    Some((
+        url.to_string(),
+        title,
+        visit_count.max(0) as u32,
+        last_visit,
+    ))
+}
+
+/// A `moz_historyvisits` row: (place_id, visit_date, visit_type).
+/// Column layout: id, from_visit, place_id, visit_date, visit_type, session | source, ...
+fn as_visit(values: &[CellValue]) -> Option<(i64, i64, i32)> {
+    if !(5..=8).contains(&values.len()) || values[0] != CellValue::Null {
+        return None;
+    }
+    if values
+        .iter()
+        .any(|v| matches!(v, CellValue::Text(_) | CellValue::Blob))
+    {
+        return None;
+    }
+    values[1].as_int()?;
+    let place_id = values[2].as_int().filter(|id| *id > 0)?;
+    let visit_date = values[3].as_int().filter(|d| is_plausible_prtime(*d))?;
+    let visit_type = values[4].as_int().filter(|t| (1..=10).contains(t))?;
+    Some((place_id, visit_date, visit_type as i32))
+}
+
+/// Safari Core Data times between 2003 and 2100 (seconds since 2001-01-01).
+fn is_plausible_safari_time(v: f64) -> bool {
+    (63_072_000.0..3_124_137_600.0).contains(&v)
+}
+
+/// A Safari `history_items` row: the url.
+/// Column layout: id, url, domain_expansion, visit_count, daily_visit_counts, ...
+fn as_safari_item(values: &[CellValue]) -> Option<String> {
+    if values.len() < 5 || values[0] != CellValue::Null {
+        return None;
+    }
+    let url = values[1].as_text().filter(|u| u.contains(':'))?;
+    if !matches!(values[2], CellValue::Text(_) | CellValue::Null)
+        || values[3].as_int().is_none()
+        || values[4] != CellValue::Blob
+    {
+        return None;
+    }
+    Some(url.to_string())
+}
+
+/// A Safari `history_visits` row: (history_item, visit_time, title).
+/// Column layout: id, history_item, visit_time, title, load_successful, ...
+/// SQLite stores whole-second REAL values as integers on disk, so both are accepted.
+fn as_safari_visit(values: &[CellValue]) -> Option<(i64, f64, String)> {
+    if values.len() < 5 || values[0] != CellValue::Null {
+        return None;
+    }
+    let item = values[1].as_int().filter(|id| *id > 0)?;
+    let visit_time = match values[2] {
+        CellValue::Real(f) => f,
+        CellValue::Int(i) => i as f64,
+        _ => return None,
+    };
+    if !is_plausible_safari_time(visit_time) {
+        return None;
+    }
+    let title = match &values[3] {
+        CellValue::Text(t) => t.clone(),
+        CellValue::Null => String::new(),
+        _ => return None,
+    };
+    values[4].as_int().filter(|v| matches!(v, 0 | 1))?;
+    Some((item, visit_time, title))
+}
+
+/// Decode Safari `history_items` and `history_visits` records from the frames of a
+/// `History.db-wal`, joining each visit to its item for the URL.
+///
+/// Rows deleted from the live database often survive in earlier frames; later frames
+/// supersede earlier copies of the same row. Visits whose item isn't in the WAL are
+/// skipped, as their URL is unknown.
+fn carve_safari_wal_records(wal_path: &Path, source_db: &str) -> Result<Vec<CarvedEntry>> {
+    let mut items = HashMap::new();
+    let mut visits = HashMap::new();
+    let frames = for_each_wal_frame(wal_path, |index, page_number, page| {
+        for (rowid, values) in parse_table_leaf_page(page, page_number) {
+            if let Some(url) = as_safari_item(&values) {
+                items.insert(rowid, url);
+            } else if let Some(visit) = as_safari_visit(&values) {
+                visits.insert(rowid, (index, visit));
+            }
+        }
+    })?;
+    debug!(
+        "{}: {} history_items and {} history_visits records in WAL",
+        source_db,
+        items.len(),
+        visits.len()
+    );
+
+    let mut entries: Vec<CarvedEntry> = visits
+        .into_values()
+        .filter_map(|(index, (item, visit_time, title))| {
+            Some(CarvedEntry {
+                url: items.get(&item)?.clone(),
+                title,
+                visit_time: safari_time_to_datetime(visit_time),
+                browser_hint: "Safari".to_string(),
+                source: CarveSource::WalFile,
+                source_file: source_db.to_string(),
+                wal_frame: Some(frames[index as usize - 1]),
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| a.visit_time.cmp(&b.visit_time).then(a.url.cmp(&b.url)));
+    Ok(entries)
+}
+
+/// If `path` is a Firefox `places.sqlite` whose main database is missing or empty but
+/// whose `-wal` survives (or is that lone WAL), return the WAL path.
+pub fn firefox_wal_only(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let is_empty_or_missing = |p: &Path| fs::metadata(long_path(p)).map_or(true, |m| m.len() == 0);
+    let (main, wal) = match name.strip_suffix("-wal") {
+        Some(main_name) => (path.with_file_name(main_name), path.to_path_buf()),
+        None => (
+            path.to_path_buf(),
+            path.with_file_name(format!("{name}-wal")),
+        ),
+    };
+    let is_places = main.file_name().is_some_and(|n| n == "places.sqlite");
+    (is_places && long_path(&wal).is_file() && is_empty_or_missing(&main)).then_some(wal)
+}
+
+/// Rebuild Firefox history from a `places.sqlite-wal` alone by decoding the
+/// `moz_places` and `moz_historyvisits` records in its frames.
+///
+/// Later frames supersede earlier copies of the same row. Visits are joined to their
+/// place for URL and title; places with no surviving visit rows fall back to their
+/// `last_visit_date` with an empty visit type.
+pub fn recover_firefox_wal(wal_path: &Path, username: &str) -> Result<Vec<HistoryEntry>> {
+    let wal_str = wal_path.to_string_lossy().to_string();
+
+    let mut places = HashMap::new();
+    let mut visits = HashMap::new();
+    for_each_wal_frame(wal_path, |_, page_number, page| {
+        for (rowid, values) in parse_table_leaf_page(page, page_number) {
+            if let Some(place) = as_place(&values) {
+                places.insert(rowid, place);
+            } else if let Some(visit) = as_visit(&values) {
+                visits.insert(rowid, visit);
+            }
+        }
+    })?;
+    debug!(
+        "{}: {} moz_places and {} moz_historyvisits records in WAL",
+        wal_str,
+        places.len(),
+        visits.len()
+    );
+
+    let entry = |place_id: i64, visit_date: i64, visit_type: &str| {
+        let (url, title, visit_count, _) = &places[&place_id];
+        Some(HistoryEntry {
+            url_length: url.len(),
+            url: url.clone(),
+            title: title.clone(),
+            visit_time: prtime_to_datetime(visit_date)?,
+            visit_count: *visit_count,
+            visited_from: String::new(),
+            app_id: String::new(),
+            journey: String::new(),
+            canonical_url: String::new(),
+            visit_type: visit_type.to_string(),
+            visit_duration: String::new(),
+            view_time_ms: None,
+            key_presses: None,
+            hidden: false,
+            visited_link_confirmed: false,
+            web_browser: "Firefox".to_string(),
+            user_profile: username.to_string(),
+            browser_profile: String::new(),
+            account: String::new(),
+            typed_count: 0,
+            history_file: wal_str.clone(),
+            record_id: place_id,
+        })
+    };
+
+    let mut entries = Vec::new();
+    let mut visited_places = HashSet::new();
+    for (place_id, visit_date, visit_type) in visits.values() {
+        if places.contains_key(place_id) {
+            visited_places.insert(*place_id);
+            let vtype = crate::browsers::firefox::visit_type_name(*visit_type);
+            entries.extend(entry(*place_id, *visit_date, vtype));
+        }
+    }
+    for (place_id, (_, _, _, last_visit)) in &places {
+        if let Some(last_visit) = last_visit.filter(|_| !visited_places.contains(place_id)) {
+            entries.extend(entry(*place_id, last_visit, ""));
+        }
+    }
+
+    entries.sort_by(|a, b| a.visit_time.cmp(&b.visit_time).then(a.url.cmp(&b.url)));
+    Ok(entries)
+}
+
+/// Carve a Firefox WAL whose main database is gone, via [`recover_firefox_wal`].
+pub fn carve_firefox_wal_only(wal_path: &Path) -> Result<(Vec<CarvedEntry>, CarveStats)> {
+    info!(
+        "Main database missing or empty; rebuilding history from {}",
+        wal_path.display()
+    );
+    let entries: Vec<CarvedEntry> = recover_firefox_wal(wal_path, "")?
+        .into_iter()
+        .map(|e| CarvedEntry {
+            url: e.url,
+            title: e.title,
+            visit_time: Some(e.visit_time),
+            browser_hint: e.web_browser,
+            source: CarveSource::WalFile,
+            source_file: e.history_file,
+            wal_frame: None,
+        })
+        .collect();
+    info!("  WAL record recovery: {} visits", entries.len());
+    let stats = CarveStats {
+        wal: entries.len(),
+        unique: entries.len(),
+        ..CarveStats::default()
+    };
+    Ok((entries, stats))
+}
+
 /// Write carved entries to CSV.
 pub fn write_carved_csv(entries: &[CarvedEntry], output_path: &Path, date_fmt: &str) -> Result<usize> {
     if entries.is_empty() {
@@ -643,6 +1542,7 @@ pub fn write_carved_csv(entries: &[CarvedEntry], output_path: &Path, date_fmt: &
         "Title",
         "Browser Hint",
         "Recovery Source",
+        "WAL Frame",
         "Source File",
         "NaturalLanguage",
     ])?;
@@ -652,12 +1552,13 @@ pub fn write_carved_csv(entries: &[CarvedEntry], output_path: &Path, date_fmt: &
         wtr.write_record([
             &entry
                 .visit_time
-                .map(|dt| dt.format(date_fmt).to_string())
+                .map(|dt| crate::output::format_timestamp(&dt, date_fmt))
                 .unwrap_or_default(),
             &entry.url,
             &entry.title,
             &entry.browser_hint,
             &entry.source.to_string(),
+            &entry.wal_frame.map(|f| f.to_string()).unwrap_or_default(),
             &entry.source_file,
             &nl,
         ])?;
@@ -673,11 +1574,56 @@ mod tests {
 
     #[test]
     fn test_is_plausible_url() {
-        assert!(is_plausible_url("https://www.google.com/search?q=test"));
-        assert!(is_plausible_url("http://example.com/path"));
-        assert!(is_plausible_url("file:///Users/test/doc.pdf"));
-        assert!(!is_plausible_url("https://x")); // too short, no dot
-        assert!(!is_plausible_url("http://ab")); // too short
+        let plausible = |url| is_plausible_url(url, &CarveConfig::default());
+        assert!(plausible("https://www.google.com/search?q=test"));
+        assert!(plausible("http://example.com/path"));
+        assert!(plausible("file:///Users/test/doc.pdf"));
+        assert!(!plausible("https://x")); // too short, no dot
+        assert!(!plausible("http://ab")); // too short
+    }
+
+    #[test]
+    fn test_domain_threshold_decides_short_links() {
+        let mut data = vec![0u8; 64];
+        data[20..33].copy_from_slice(b"http://t.co/x");
+        let carved = |config: &CarveConfig| {
+            extract_urls_from_page(&data, "History", CarveSource::RawScan, config)
+        };
+
+        // A 4-character host sits right on the default threshold
+        assert_eq!(carved(&CarveConfig::default())[0].url, "http://t.co/x");
+        let strict = CarveConfig {
+            min_domain_len: 5,
+            ..CarveConfig::default()
+        };
+        assert!(carved(&strict).is_empty());
+        let loose = CarveConfig {
+            min_domain_len: 3,
+            ..strict
+        };
+        assert_eq!(carved(&loose)[0].url, "http://t.co/x");
+
+        // Under 12 characters the URL length threshold has to come down too
+        let looser = CarveConfig {
+            min_url_len: 8,
+            ..loose
+        };
+        let mut data = vec![0u8; 64];
+        data[20..31].copy_from_slice(b"http://a.b/");
+        assert!(extract_urls_from_page(&data, "History", CarveSource::RawScan, &loose).is_empty());
+        assert_eq!(
+            extract_urls_from_page(&data, "History", CarveSource::RawScan, &looser)[0].url,
+            "http://a.b/"
+        );
+    }
+
+    #[test]
+    fn test_title_alpha_ratio() {
+        let ratio = DEFAULT_TITLE_ALPHA_RATIO;
+        assert!(is_plausible_title("Example Domain", ratio));
+        assert!(!is_plausible_title("v2.1 #42", ratio));
+        assert!(is_plausible_title("v2.1 #42", 0.2));
+        assert!(!is_plausible_title("Example Domain 2024", 0.9));
     }
 
     #[test]
@@ -694,7 +1640,7 @@ mod tests {
         // page_size = 4096
         fake_header[16] = 0x10;
         fake_header[17] = 0x00;
-        let hdr = read_sqlite_header(&fake_header).unwrap();
+        let hdr = read_sqlite_header(&fake_header, 4096).unwrap();
         assert_eq!(hdr.page_size, 4096);
     }
 
@@ -704,8 +1650,379 @@ mod tests {
         let url = b"https://www.example.com/test/page";
         data[50..50 + url.len()].copy_from_slice(url);
 
-        let entries = extract_urls_from_page(&data, "test.db", CarveSource::RawScan);
+        let config = CarveConfig::default();
+        let entries = extract_urls_from_page(&data, "test.db", CarveSource::RawScan, &config);
         assert_eq!(entries.len(), 1);
         assert_eq!(entries[0].url, "https://www.example.com/test/page");
     }
+
+    /// Zero-filled input of `len` bytes with `urls` placed at their offsets, generated
+    /// as it's read so the whole input never exists in memory.
+    struct SyntheticReader {
+        pos: usize,
+        len: usize,
+        urls: Vec<(usize, String)>,
+    }
+
+    impl Read for SyntheticReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(self.len - self.pos);
+            buf[..n].fill(0);
+            for (at, url) in &self.urls {
+                let (start, end) = (*at, at + url.len());
+                if start < self.pos + n && end > self.pos {
+                    let from = start.max(self.pos);
+                    let to = end.min(self.pos + n);
+                    buf[from - self.pos..to - self.pos]
+                        .copy_from_slice(&url.as_bytes()[from - start..to - start]);
+                }
+            }
+            self.pos += n;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_raw_scan_streams_across_window_edges() {
+        let window = 64 * 1024;
+        let len = 10 * 1024 * 1024;
+        // One URL every 100 KB, plus one across each of the first window edges and
+        // one ending right at the end of the input
+        let mut offsets: Vec<usize> = (1..100).map(|i| i * 100_000).collect();
+        offsets.extend((1..20).map(|i| i * window - 10));
+        offsets.push(len - 40);
+        offsets.sort_unstable();
+        let urls: Vec<(usize, String)> = offsets
+            .iter()
+            .map(|&at| (at, format!("https://site{at}.example.com/p")))
+            .filter(|(at, url)| at + url.len() <= len)
+            .collect();
+
+        let reader = SyntheticReader {
+            pos: 0,
+            len,
+            urls: urls.clone(),
+        };
+        let carved = carve_raw_windows(reader, "History", window, &CarveConfig::default()).unwrap();
+        let found: Vec<&str> = carved.iter().map(|e| e.url.as_str()).collect();
+        let expected: Vec<&str> = urls.iter().map(|(_, url)| url.as_str()).collect();
+        assert_eq!(found, expected);
+
+        // Same hits as scanning the input in one piece
+        let mut whole = Vec::new();
+        let mut reader = SyntheticReader { pos: 0, len, urls };
+        reader.read_to_end(&mut whole).unwrap();
+        let config = CarveConfig::default();
+        let buffered = carve_raw_buffer(&whole, "History", &config);
+        assert_eq!(buffered.len(), carved.len());
+    }
+
+    #[test]
+    fn test_utf16le_title_before_url() {
+        let mut data = vec![0u8; 256];
+        let title: Vec<u8> = "Example Page Title"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        data[40..40 + title.len()].copy_from_slice(&title);
+        let url = b"https://www.example.com/page";
+        let url_at = 40 + title.len() + 6;
+        data[url_at..url_at + url.len()].copy_from_slice(url);
+
+        let config = CarveConfig::default();
+        let entries = extract_urls_from_page(&data, "History", CarveSource::FreelistPage, &config);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].url, "https://www.example.com/page");
+        assert_eq!(entries[0].title, "Example Page Title");
+    }
+
+    #[test]
+    fn test_stats_match_full_carve() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "PRAGMA secure_delete = OFF;
+             CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR);",
+        )
+        .unwrap();
+        for i in 0..200 {
+            conn.execute(
+                "INSERT INTO urls (url, title) VALUES (?1, ?2)",
+                [
+                    format!("https://deleted{i}.example.com/page/{}", "x".repeat(60)),
+                    format!("Deleted page {i}"),
+                ],
+            )
+            .unwrap();
+        }
+        conn.execute("DELETE FROM urls WHERE id > 20", []).unwrap();
+        drop(conn);
+
+        let config = CarveConfig::default();
+        let (entries, stats) = carve_with_stats(&db, &config).unwrap();
+        assert_eq!(stats.unique, entries.len());
+        assert_eq!(stats.unique, carve(&db, &config).unwrap().len());
+        assert!(stats.freelist > 0);
+        assert!(stats.unique <= stats.freelist + stats.wal + stats.raw);
+    }
+
+    #[test]
+    fn test_deleted_row_recovered_from_live_page_freeblock() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("History");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "PRAGMA secure_delete = OFF;
+             CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR);",
+        )
+        .unwrap();
+        for i in 0..10 {
+            conn.execute(
+                "INSERT INTO urls (url, title) VALUES (?1, ?2)",
+                [format!("https://row{i}.example.com/page"), format!("Row {i}")],
+            )
+            .unwrap();
+        }
+        // One row leaves the page allocated, so nothing reaches the freelist
+        conn.execute("DELETE FROM urls WHERE id = 5", []).unwrap();
+        drop(conn);
+
+        let config = CarveConfig::default();
+        assert!(carve_freelist_pages(&db, &config).unwrap().is_empty());
+        let carved = carve_freeblocks(&db, &config).unwrap();
+        // The title column follows the URL with no delimiter, so only the prefix is exact
+        assert_eq!(carved.len(), 1);
+        assert!(carved[0].url.starts_with("https://row4.example.com/page"));
+        assert_eq!(carved[0].source, CarveSource::FreeblockCell);
+
+        let (entries, stats) = carve_with_stats(&db, &CarveConfig::default()).unwrap();
+        assert_eq!(stats.freeblock, 1);
+        let row4 = entries.iter().find(|e| e.url.contains("row4")).unwrap();
+        assert_eq!(row4.source, CarveSource::FreeblockCell);
+    }
+
+    #[test]
+    fn test_recover_deleted_safari_visit_from_wal() {
+        let live = tempfile::TempDir::new().unwrap();
+        let db = live.path().join("History.db");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        // 726998400.25 = 2024-01-15 08:00:00.25 UTC; 726912000 (whole seconds, stored
+        // as an integer) = 2024-01-14 08:00:00 UTC
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA wal_autocheckpoint = 0;
+             CREATE TABLE history_items (id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 url TEXT NOT NULL UNIQUE, domain_expansion TEXT NULL,
+                 visit_count INTEGER NOT NULL, daily_visit_counts BLOB NOT NULL,
+                 weekly_visit_counts BLOB NULL, autocomplete_triggers BLOB NULL,
+                 should_recompute_derived_visit_counts INTEGER NOT NULL,
+                 visit_count_score INTEGER NOT NULL);
+             CREATE TABLE history_visits (id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 history_item INTEGER NOT NULL, visit_time REAL NOT NULL, title TEXT NULL,
+                 load_successful BOOLEAN NOT NULL DEFAULT 1,
+                 http_non_get BOOLEAN NOT NULL DEFAULT 0,
+                 synthesized BOOLEAN NOT NULL DEFAULT 0);
+             INSERT INTO history_items VALUES (1, 'https://secret.example/plans', 'secret', 1,
+                 x'01', NULL, NULL, 0, 20);
+             INSERT INTO history_items VALUES (2, 'https://kept.example/', 'kept', 1,
+                 x'01', NULL, NULL, 0, 20);
+             INSERT INTO history_visits (history_item, visit_time, title)
+                 VALUES (1, 726998400.25, 'Plans');
+             INSERT INTO history_visits (history_item, visit_time, title)
+                 VALUES (2, 726912000, 'Kept');
+             DELETE FROM history_visits WHERE history_item = 1;
+             DELETE FROM history_items WHERE id = 1;",
+        )
+        .unwrap();
+        let live_urls: Vec<String> = conn
+            .prepare("SELECT url FROM history_items")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(live_urls, ["https://kept.example/"]);
+
+        // Copy while the connection is open so the WAL isn't checkpointed away
+        let evidence = tempfile::TempDir::new().unwrap();
+        let main = evidence.path().join("History.db");
+        fs::copy(&db, &main).unwrap();
+        fs::copy(
+            live.path().join("History.db-wal"),
+            evidence.path().join("History.db-wal"),
+        )
+        .unwrap();
+        drop(conn);
+
+        let (entries, stats) = carve_with_stats(&main, &CarveConfig::default()).unwrap();
+        assert!(stats.wal >= 2);
+        let deleted = entries
+            .iter()
+            .find(|e| e.url == "https://secret.example/plans")
+            .unwrap();
+        assert_eq!(deleted.source, CarveSource::WalFile);
+        // The page was rewritten by the DELETE, so the frame holding the row is an older copy
+        assert_eq!(
+            deleted.wal_frame.unwrap().status,
+            WalFrameStatus::Superseded
+        );
+        assert_eq!(deleted.browser_hint, "Safari");
+        assert_eq!(deleted.title, "Plans");
+        assert_eq!(
+            deleted
+                .visit_time
+                .unwrap()
+                .format("%Y-%m-%d %H:%M:%S%.3f")
+                .to_string(),
+            "2024-01-15 08:00:00.250"
+        );
+        let kept = entries
+            .iter()
+            .find(|e| e.url == "https://kept.example/")
+            .unwrap();
+        assert_eq!(
+            kept.visit_time.unwrap().format("%Y-%m-%d %H:%M").to_string(),
+            "2024-01-14 08:00"
+        );
+    }
+
+    #[test]
+    fn test_wal_frame_headers_and_stale_frames() {
+        let live = tempfile::TempDir::new().unwrap();
+        let conn = rusqlite::Connection::open(live.path().join("History")).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA wal_autocheckpoint = 0;
+             CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR);
+             INSERT INTO urls (url) VALUES ('https://first.example/');
+             INSERT INTO urls (url) VALUES ('https://second.example/');",
+        )
+        .unwrap();
+        let evidence = tempfile::TempDir::new().unwrap();
+        let wal = evidence.path().join("History-wal");
+        fs::copy(live.path().join("History-wal"), &wal).unwrap();
+        drop(conn);
+
+        let frames = for_each_wal_frame(&wal, |_, _, _| {}).unwrap();
+        assert!(frames.len() >= 3);
+        assert!(frames
+            .iter()
+            .enumerate()
+            .all(|(i, f)| f.index as usize == i + 1));
+        let last = frames.last().unwrap();
+        assert_ne!(last.commit_size, 0);
+        assert_eq!(last.status, WalFrameStatus::Current);
+        // Every insert rewrote the urls leaf page; only its last copy is current
+        let copies: Vec<_> = frames.iter().filter(|f| f.page == last.page).collect();
+        assert!(copies.len() >= 2);
+        assert!(copies[..copies.len() - 1]
+            .iter()
+            .all(|f| f.status == WalFrameStatus::Superseded));
+        assert_eq!(
+            last.to_string(),
+            format!("WAL frame {}, page {} (current)", last.index, last.page)
+        );
+
+        // A salt from another WAL generation breaks the chain from that frame on
+        let mut data = fs::read(&wal).unwrap();
+        let page_size = u32::from_be_bytes([data[8], data[9], data[10], data[11]]) as usize;
+        data[32 + (24 + page_size) + 8] ^= 0xff;
+        fs::write(&wal, &data).unwrap();
+        let frames = for_each_wal_frame(&wal, |_, _, _| {}).unwrap();
+        assert_ne!(frames[0].status, WalFrameStatus::Stale);
+        assert!(frames[1..]
+            .iter()
+            .all(|f| f.status == WalFrameStatus::Stale));
+    }
+
+    #[test]
+    fn test_frames_after_last_commit_are_uncommitted() {
+        let frame = |index, page, commit_size| WalFrame {
+            index,
+            page,
+            commit_size,
+            status: WalFrameStatus::Current,
+        };
+        let mut frames = [frame(1, 2, 0), frame(2, 3, 4), frame(3, 2, 0)];
+        resolve_wal_frame_status(&mut frames);
+        let status: Vec<_> = frames.iter().map(|f| f.status).collect();
+        assert_eq!(
+            status,
+            [
+                WalFrameStatus::Current,
+                WalFrameStatus::Current,
+                WalFrameStatus::Uncommitted
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recover_firefox_history_from_lone_wal() {
+        let live = tempfile::TempDir::new().unwrap();
+        let conn = rusqlite::Connection::open(live.path().join("places.sqlite")).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA wal_autocheckpoint = 0;
+             CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                 rev_host LONGVARCHAR, visit_count INTEGER DEFAULT 0, hidden INTEGER DEFAULT 0,
+                 typed INTEGER DEFAULT 0, frecency INTEGER DEFAULT -1, last_visit_date INTEGER,
+                 guid TEXT, foreign_count INTEGER DEFAULT 0, url_hash INTEGER DEFAULT 0);
+             CREATE TABLE moz_historyvisits (id INTEGER PRIMARY KEY, from_visit INTEGER,
+                 place_id INTEGER, visit_date INTEGER, visit_type INTEGER, session INTEGER);
+             INSERT INTO moz_places (url, title, rev_host, visit_count, last_visit_date, guid)
+                 VALUES ('https://www.mozilla.org/', 'Mozilla', 'gro.allizom.www.', 2,
+                         1705305600000000, 'abcdefghijkl');
+             INSERT INTO moz_places (url, title, rev_host, visit_count, last_visit_date, guid)
+                 VALUES ('https://example.com/orphan', NULL, 'moc.elpmaxe.', 1,
+                         1705219200000000, 'mnopqrstuvwx');
+             INSERT INTO moz_historyvisits VALUES (1, 0, 1, 1705305000000000, 1, 0);
+             INSERT INTO moz_historyvisits VALUES (2, 1, 1, 1705305600000000, 2, 0);",
+        )
+        .unwrap();
+
+        // Evidence copy: the WAL survived, the main database was truncated
+        let evidence = tempfile::TempDir::new().unwrap();
+        let main = evidence.path().join("places.sqlite");
+        fs::copy(
+            live.path().join("places.sqlite-wal"),
+            evidence.path().join("places.sqlite-wal"),
+        )
+        .unwrap();
+        fs::write(&main, b"").unwrap();
+        drop(conn);
+
+        let wal = firefox_wal_only(&main).unwrap();
+        let entries = recover_firefox_wal(&wal, "alice").unwrap();
+        let summary: Vec<_> = entries
+            .iter()
+            .map(|e| (e.url.as_str(), e.visit_type.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("https://example.com/orphan", ""),
+                ("https://www.mozilla.org/", "Link"),
+                ("https://www.mozilla.org/", "Typed"),
+            ]
+        );
+        assert_eq!(entries[1].title, "Mozilla");
+        assert_eq!(entries[1].visit_count, 2);
+        assert_eq!(
+            entries[2]
+                .visit_time
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            "2024-01-15 08:00:00"
+        );
+
+        let (carved, stats) = carve_firefox_wal_only(&wal).unwrap();
+        assert_eq!(carved.len(), 3);
+        assert_eq!(stats.wal, 3);
+        assert!(carved.iter().all(|e| e.source == CarveSource::WalFile));
+
+        // A healthy main database is left to the regular carver
+        fs::write(&main, b"SQLite format 3\0").unwrap();
+        assert!(firefox_wal_only(&main).is_none());
+    }
 }
@@ -0,0 +1,140 @@
+//! `webx.toml` configuration file support.
+//!
+//! Examiners tend to run the same flags on every case, so default options can be
+//! stored in a TOML file instead of being retyped. The file is looked up in the
+//! current working directory (`./webx.toml`) unless `--config <file>` is given.
+//!
+//! Precedence: explicit CLI flags > config file > built-in defaults.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// File name searched for in the current working directory.
+pub const CONFIG_FILE_NAME: &str = "webx.toml";
+
+/// Default options loaded from `webx.toml`. Every key is optional.
+///
+/// ```toml
+/// date_format = "iso"
+/// artifacts = ["history", "downloads", "cookies"]
+/// user = "suspect"
+/// format = ["csv", "jsonl"]
+/// tz = "America/New_York"
+/// max_value_len = 4096
+/// carve_min_url_len = 16
+/// ```
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Date format for output (same values as `--date-format`).
+    pub date_format: Option<String>,
+    /// Artifact types to extract during `scan` (same names as `--artifacts`).
+    pub artifacts: Option<Vec<String>>,
+    /// Username override for `scan` and `extract`.
+    pub user: Option<String>,
+    /// Query parameters stripped by `--canonical-dedup` (same syntax as `--tracking-params`).
+    pub tracking_params: Option<Vec<String>>,
+    /// Output file formats for `scan` (same values as `--format`).
+    pub format: Option<Vec<String>>,
+    /// IANA time zone timestamps are written in (same values as `--tz`).
+    pub tz: Option<String>,
+    /// Free-text field limit in bytes (same as `--max-value-len`; 0 = no limit).
+    pub max_value_len: Option<usize>,
+    /// Shortest carved URL kept (`carve --min-url-len`).
+    pub carve_min_url_len: Option<usize>,
+    /// Shortest carved host kept (`carve --min-domain-len`).
+    pub carve_min_domain_len: Option<usize>,
+    /// Smallest letter share of a carved title (`carve --title-alpha-ratio`).
+    pub carve_title_alpha_ratio: Option<f64>,
+}
+
+impl Config {
+    /// Parse a config from TOML text.
+    pub fn from_toml(text: &str) -> Result<Self> {
+        toml::from_str(text).context("Failed to parse config file")
+    }
+
+    /// Read and parse a config file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        Self::from_toml(&text).with_context(|| format!("Invalid config file: {}", path.display()))
+    }
+}
+
+/// Load the config from an explicit path, or from `./webx.toml` if present.
+///
+/// An explicit path that doesn't exist is an error; a missing `./webx.toml` is
+/// not (built-in defaults are used). Returns the path the config was read from.
+pub fn load(explicit: Option<&Path>) -> Result<(Config, Option<PathBuf>)> {
+    if let Some(path) = explicit {
+        return Ok((Config::from_file(path)?, Some(path.to_path_buf())));
+    }
+    let default_path = PathBuf::from(CONFIG_FILE_NAME);
+    if default_path.is_file() {
+        let config = Config::from_file(&default_path)?;
+        return Ok((config, Some(default_path)));
+    }
+    Ok((Config::default(), None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::from_toml(
+            "date_format = \"iso\"\nartifacts = [\"history\", \"cookies\"]\nuser = \"jdoe\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.date_format.as_deref(), Some("iso"));
+        assert_eq!(
+            config.artifacts,
+            Some(vec!["history".to_string(), "cookies".to_string()])
+        );
+        assert_eq!(config.user.as_deref(), Some("jdoe"));
+
+        let config = Config::from_toml("tracking_params = [\"utm_*\", \"ref\"]\n").unwrap();
+        assert_eq!(
+            config.tracking_params,
+            Some(vec!["utm_*".to_string(), "ref".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_parse_output_and_carving_keys() {
+        let config = Config::from_toml(
+            "format = [\"csv\", \"jsonl\"]\ntz = \"Europe/Berlin\"\nmax_value_len = 0\n\
+             carve_min_url_len = 20\ncarve_min_domain_len = 6\ncarve_title_alpha_ratio = 0.7\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.format,
+            Some(vec!["csv".to_string(), "jsonl".to_string()])
+        );
+        assert_eq!(config.tz.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(config.max_value_len, Some(0));
+        assert_eq!(config.carve_min_url_len, Some(20));
+        assert_eq!(config.carve_min_domain_len, Some(6));
+        assert_eq!(config.carve_title_alpha_ratio, Some(0.7));
+    }
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = Config::from_toml("").unwrap();
+        assert!(config.date_format.is_none());
+        assert!(config.artifacts.is_none());
+    }
+
+    #[test]
+    fn test_unknown_key_rejected() {
+        assert!(Config::from_toml("date_fromat = \"iso\"").is_err());
+    }
+
+    #[test]
+    fn test_explicit_missing_file_is_error() {
+        assert!(load(Some(Path::new("/nonexistent/webx.toml"))).is_err());
+    }
+}
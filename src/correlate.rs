@@ -0,0 +1,245 @@
+//! Cross-artifact correlation passes.
+//!
+//! These run after extraction and derive extra columns by combining artifact types
+//! that come from the same browser profile (e.g. downloads + history).
+
+use chrono::Duration;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::browsers::{CookieEntry, DownloadEntry, HistoryEntry};
+use crate::canonical::url_host;
+
+/// Default look-back window for [`infer_download_referrers`].
+pub const DEFAULT_REFERRER_WINDOW_SECS: i64 = 120;
+
+/// True if two source files live in the same profile directory.
+fn same_profile_dir(a: &str, b: &str) -> bool {
+    Path::new(a).parent() == Path::new(b).parent()
+}
+
+/// Fill `inferred_referrer` on downloads whose `referrer` is empty.
+///
+/// For each such download, the nearest history visit from the same browser, user and
+/// profile directory that happened within `window` before the download's `start_time`
+/// is taken as the originating page. Visits to the download URL itself are ignored.
+/// Returns the number of downloads that received an inferred referrer.
+pub fn infer_download_referrers(
+    downloads: &mut [DownloadEntry],
+    history: &[HistoryEntry],
+    window: Duration,
+) -> usize {
+    let mut filled = 0;
+    for dl in downloads.iter_mut() {
+        if !dl.referrer.is_empty() {
+            continue;
+        }
+        let earliest = dl.start_time - window;
+        let nearest = history
+            .iter()
+            .filter(|h| {
+                h.visit_time <= dl.start_time
+                    && h.visit_time >= earliest
+                    && h.url != dl.url
+                    && h.web_browser == dl.web_browser
+                    && h.user_profile == dl.user_profile
+                    && same_profile_dir(&h.history_file, &dl.source_file)
+            })
+            .max_by_key(|h| h.visit_time);
+        if let Some(h) = nearest {
+            dl.inferred_referrer = h.url.clone();
+            filled += 1;
+        }
+    }
+    filled
+}
+
+/// The profile directory holding `file`. Chromium 96+ keeps `Cookies` in a `Network`
+/// subfolder of the profile rather than next to `History`.
+pub fn profile_dir(file: &str) -> &Path {
+    let parent = Path::new(file).parent().unwrap_or(Path::new(""));
+    match parent.file_name() {
+        Some(name) if name == "Network" => parent.parent().unwrap_or(parent),
+        _ => parent,
+    }
+}
+
+/// Hosts visited in one profile: every visited host with its parent domains, and the
+/// registrable domains (eTLD+1) of those hosts.
+#[derive(Default)]
+struct VisitedHosts {
+    domain_matched: HashSet<String>,
+    registrable: HashSet<String>,
+}
+
+/// Set `visited_host` and `first_party` on each cookie from the history of the same
+/// browser, user and profile directory.
+///
+/// A cookie is a visited host when a visited page's host is its host or a subdomain of
+/// it, i.e. the cookie would have been sent with that page. It is first party when its
+/// registrable domain (eTLD+1) is that of a visited page; cookies that are neither were
+/// only ever set by content embedded in other sites. Returns the number of first-party
+/// cookies.
+pub fn mark_cookie_hosts(cookies: &mut [CookieEntry], history: &[HistoryEntry]) -> usize {
+    let mut profiles: HashMap<(&str, &str, &Path), VisitedHosts> = HashMap::new();
+    for h in history {
+        let host = match url_host(&h.url) {
+            Some(host) => host,
+            None => continue,
+        };
+        let visited = profiles
+            .entry((
+                &h.web_browser,
+                &h.user_profile,
+                profile_dir(&h.history_file),
+            ))
+            .or_default();
+        if let Some(domain) = psl::domain_str(&host) {
+            visited.registrable.insert(domain.to_string());
+        }
+        let mut suffix = host.as_str();
+        loop {
+            visited.domain_matched.insert(suffix.to_string());
+            match suffix.split_once('.') {
+                Some((_, parent)) => suffix = parent,
+                None => break,
+            }
+        }
+    }
+
+    let mut first_party = 0;
+    for c in cookies.iter_mut() {
+        let key = (
+            c.web_browser.as_str(),
+            c.user_profile.as_str(),
+            profile_dir(&c.source_file),
+        );
+        let visited = match profiles.get(&key) {
+            Some(v) => v,
+            None => continue,
+        };
+        let host = c.host.trim_start_matches('.').to_lowercase();
+        let domain = psl::domain_str(&host).unwrap_or(&host);
+        c.visited_host = visited.domain_matched.contains(&host);
+        c.first_party = visited.registrable.contains(domain);
+        if c.first_party {
+            first_party += 1;
+        }
+    }
+    first_party
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn visit(url: &str, secs: i64) -> HistoryEntry {
+        HistoryEntry {
+            url: url.to_string(),
+            visit_time: Utc.timestamp_opt(secs, 0).unwrap(),
+            visit_count: 1,
+            visit_type: "Link".to_string(),
+            web_browser: "Chrome".to_string(),
+            user_profile: "jdoe".to_string(),
+            url_length: url.len(),
+            history_file: "/Users/jdoe/Chrome/Default/History".to_string(),
+            record_id: 1,
+            ..Default::default()
+        }
+    }
+
+    fn download(url: &str, referrer: &str, secs: i64) -> DownloadEntry {
+        DownloadEntry {
+            url: url.to_string(),
+            start_time: Utc.timestamp_opt(secs, 0).unwrap(),
+            state: "Complete".to_string(),
+            referrer: referrer.to_string(),
+            web_browser: "Chrome".to_string(),
+            user_profile: "jdoe".to_string(),
+            source_file: "/Users/jdoe/Chrome/Default/History".to_string(),
+            record_id: 1,
+            ..Default::default()
+        }
+    }
+
+    fn cookie(host: &str, source_file: &str) -> CookieEntry {
+        CookieEntry {
+            host: host.to_string(),
+            name: "id".to_string(),
+            path: "/".to_string(),
+            creation_time: Utc.timestamp_opt(1_000, 0).unwrap(),
+            is_secure: true,
+            is_persistent: true,
+            web_browser: "Chrome".to_string(),
+            user_profile: "jdoe".to_string(),
+            source_file: source_file.to_string(),
+            record_id: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_marks_visited_and_first_party_cookie_hosts() {
+        let history = vec![
+            visit("https://mail.example.co.uk/inbox", 1_000),
+            visit("https://news.site.com:8443/story", 1_100),
+        ];
+        let network = "/Users/jdoe/Chrome/Default/Network/Cookies";
+        let mut cookies = vec![
+            cookie(".example.co.uk", network),
+            cookie("accounts.example.co.uk", network),
+            cookie("site.com", network),
+            cookie(".tracker.example", network),
+            // Same host, but from another profile's cookie jar
+            cookie(".example.co.uk", "/Users/jdoe/Chrome/Profile 1/Cookies"),
+        ];
+        assert_eq!(mark_cookie_hosts(&mut cookies, &history), 3);
+        let flags: Vec<_> = cookies
+            .iter()
+            .map(|c| (c.visited_host, c.first_party))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![
+                (true, true),
+                (false, true),
+                (true, true),
+                (false, false),
+                (false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infers_nearest_preceding_visit() {
+        let history = vec![
+            visit("https://old.example.com/", 1_000),
+            visit("https://files.example.com/page", 1_050),
+            visit("https://files.example.com/setup.exe", 1_090),
+            visit("https://later.example.com/", 1_200),
+        ];
+        let mut downloads = vec![
+            download("https://files.example.com/setup.exe", "", 1_100),
+            download("https://x.example.com/a.zip", "https://known/", 1_100),
+        ];
+        let filled = infer_download_referrers(&mut downloads, &history, Duration::seconds(120));
+        assert_eq!(filled, 1);
+        assert_eq!(
+            downloads[0].inferred_referrer,
+            "https://files.example.com/page"
+        );
+        assert!(downloads[1].inferred_referrer.is_empty());
+    }
+
+    #[test]
+    fn test_respects_window() {
+        let history = vec![visit("https://files.example.com/page", 1_000)];
+        let mut downloads = vec![download("https://files.example.com/a.zip", "", 1_100)];
+        assert_eq!(
+            infer_download_referrers(&mut downloads, &history, Duration::seconds(30)),
+            0
+        );
+        assert!(downloads[0].inferred_referrer.is_empty());
+    }
+}
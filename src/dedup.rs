@@ -0,0 +1,276 @@
+//! Scan-wide duplicate removal (`--global-dedup`).
+//!
+//! The same record often turns up in several files of one scan: a visit in both
+//! `History` and `Archived History`, a profile copied twice into a triage folder, or a
+//! shadow copy next to the live volume. A seen-set per artifact type drops every record
+//! already written from an earlier file, while repeats within one file are kept.
+
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+
+use crate::browsers::{
+    ArtifactType, AutofillEntry, AutofillProfileEntry, BookmarkEntry, BrowserSettingsEntry,
+    CookieEntry, CreditCardEntry, DailyVisitEntry, DownloadEntry, ExtensionEntry, FaviconEntry,
+    HistoryEntry, KeywordSearchEntry, LoginEntry, PredictorEntry, ShortcutEntry, TopSiteEntry,
+};
+
+/// (URL or nearest equivalent, primary timestamp, web browser, user profile)
+pub type Key = (String, Option<DateTime<Utc>>, String, String);
+
+/// An entry that can be recognized when it reappears in another file.
+pub trait DedupKey {
+    fn dedup_key(&self) -> Key;
+}
+
+impl DedupKey for HistoryEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            self.url.clone(),
+            Some(self.visit_time),
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for DownloadEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            format!("{}\n{}", self.url, self.target_path),
+            Some(self.start_time),
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for KeywordSearchEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            format!("{}\n{}", self.url, self.search_term),
+            self.visit_time,
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for CookieEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            format!("{}\n{}\n{}", self.host, self.path, self.name),
+            Some(self.creation_time),
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for AutofillEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            format!("{}\n{}", self.field_name, self.value),
+            self.first_used,
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for AutofillProfileEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            self.guid.clone(),
+            self.date_modified,
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for CreditCardEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            self.guid.clone(),
+            self.date_modified,
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for BookmarkEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            format!("{}\n{}", self.url, self.folder_path),
+            self.date_added,
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for LoginEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            format!("{}\n{}", self.origin_url, self.username_value),
+            self.date_created,
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for ExtensionEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            self.extension_id.clone(),
+            self.install_time,
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for BrowserSettingsEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            format!("{}\n{}", self.setting, self.value),
+            self.last_modified,
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for DailyVisitEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            self.url.clone(),
+            self.day.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc()),
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for ShortcutEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            format!("{}\n{}", self.url, self.text),
+            self.last_access_time,
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for FaviconEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            format!("{}\n{}", self.page_url, self.icon_url),
+            self.expire_time,
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for TopSiteEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            self.url.clone(),
+            self.day
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|dt| dt.and_utc()),
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+impl DedupKey for PredictorEntry {
+    fn dedup_key(&self) -> Key {
+        (
+            format!("{}\n{}", self.user_text, self.url),
+            None,
+            self.web_browser.clone(),
+            self.user_profile.clone(),
+        )
+    }
+}
+
+/// Records seen so far in the scan, per artifact type.
+#[derive(Debug, Default)]
+pub struct GlobalDedup {
+    seen: HashMap<ArtifactType, HashSet<Key>>,
+}
+
+impl GlobalDedup {
+    /// Drop the entries of one file that an earlier file already produced, then record
+    /// this file's entries as seen. Returns the number removed.
+    pub fn retain_unseen<T: DedupKey>(
+        &mut self,
+        artifact: ArtifactType,
+        entries: &mut Vec<T>,
+    ) -> usize {
+        let seen = self.seen.entry(artifact).or_default();
+        let before = entries.len();
+        let mut file_keys = Vec::with_capacity(before);
+        entries.retain(|e| {
+            let key = e.dedup_key();
+            if seen.contains(&key) {
+                return false;
+            }
+            file_keys.push(key);
+            true
+        });
+        seen.extend(file_keys);
+        before - entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn visit(url: &str, minute: u32, user: &str) -> HistoryEntry {
+        HistoryEntry {
+            url: url.to_string(),
+            visit_time: Utc.with_ymd_and_hms(2024, 1, 15, 8, minute, 0).unwrap(),
+            visit_count: 1,
+            web_browser: "Chrome".to_string(),
+            user_profile: user.to_string(),
+            url_length: url.len(),
+            record_id: minute as i64,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cross_file_duplicates_removed() {
+        let mut dedup = GlobalDedup::default();
+        // A repeat within one file is kept
+        let mut live = vec![
+            visit("https://a.example/", 1, "alice"),
+            visit("https://a.example/", 1, "alice"),
+            visit("https://b.example/", 2, "alice"),
+        ];
+        assert_eq!(dedup.retain_unseen(ArtifactType::History, &mut live), 0);
+        assert_eq!(live.len(), 3);
+
+        let mut archived = vec![
+            visit("https://a.example/", 1, "alice"),
+            visit("https://a.example/", 1, "bob"),
+            visit("https://b.example/", 3, "alice"),
+        ];
+        assert_eq!(dedup.retain_unseen(ArtifactType::History, &mut archived), 1);
+        assert_eq!(archived.len(), 2);
+        assert_eq!(archived[0].user_profile, "bob");
+
+        // HSTS records share the entry type but have their own seen-set
+        let mut hsts = vec![visit("https://a.example/", 1, "alice")];
+        assert_eq!(dedup.retain_unseen(ArtifactType::HstsRecords, &mut hsts), 0);
+    }
+}
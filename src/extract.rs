@@ -0,0 +1,810 @@
+//! Library entry points that scan a triage directory and run every extractor, so
+//! embedders get typed entries without going through the CLI.
+//!
+//! [`extract_artifact`] runs the [`registry`] extractor for one scanned artifact (it is
+//! what `webx scan` runs for each file); [`extract_all`] runs the scanner and collects
+//! every artifact into an [`ArtifactSet`].
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, info, warn};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::anonymize::Anonymizer;
+use crate::browsers::{
+    signature, ArtifactType, AutofillEntry, AutofillProfileEntry, BookmarkEntry, BrowserArtifact,
+    BrowserSettingsEntry, BrowserType, CookieEntry, CreditCardEntry, DailyVisitEntry,
+    DownloadEntry, ExtensionEntry, FaviconEntry, HistoryEntry, KeywordSearchEntry, LoginEntry,
+    PredictorEntry, ShortcutEntry, TopSiteEntry,
+};
+use crate::correlate;
+use crate::dedup::GlobalDedup;
+use crate::filter::{self, DateRange};
+use crate::redact::Redactor;
+use crate::registry;
+use crate::scanner;
+use crate::truncate;
+
+/// What [`extract_all`] extracts, and per-artifact extractor switches.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractOptions {
+    /// Artifact types to extract; empty for all of them
+    pub artifacts: HashSet<ArtifactType>,
+    /// Attribute every entry to this user instead of the one in the path
+    pub user: Option<String>,
+    /// Only keep entries whose activity time falls in this range
+    pub date_range: Option<DateRange>,
+    /// Safari: also report visits whose history item was deleted
+    pub include_orphans: bool,
+    /// Infer missing download referrers from history within this window
+    pub referrer_window: Option<Duration>,
+}
+
+impl ExtractOptions {
+    fn wants(&self, artifact_type: ArtifactType) -> bool {
+        self.artifacts.is_empty() || self.artifacts.contains(&artifact_type)
+    }
+}
+
+/// Every entry extracted from a triage directory, by artifact type.
+#[derive(Debug, Clone, Default)]
+pub struct ArtifactSet {
+    /// Visits, including HSTS records
+    pub history: Vec<HistoryEntry>,
+    pub downloads: Vec<DownloadEntry>,
+    pub keyword_searches: Vec<KeywordSearchEntry>,
+    pub cookies: Vec<CookieEntry>,
+    pub autofill: Vec<AutofillEntry>,
+    pub bookmarks: Vec<BookmarkEntry>,
+    pub logins: Vec<LoginEntry>,
+    pub extensions: Vec<ExtensionEntry>,
+    pub browser_settings: Vec<BrowserSettingsEntry>,
+    pub daily_visits: Vec<DailyVisitEntry>,
+    pub shortcuts: Vec<ShortcutEntry>,
+    pub top_sites: Vec<TopSiteEntry>,
+    pub predictor: Vec<PredictorEntry>,
+    pub autofill_profiles: Vec<AutofillProfileEntry>,
+    pub credit_cards: Vec<CreditCardEntry>,
+    pub favicons: Vec<FaviconEntry>,
+    /// Files that could not be extracted, with the reason
+    pub errors: Vec<(String, String)>,
+}
+
+impl ArtifactSet {
+    /// Add one file's entries to the set.
+    pub fn push(&mut self, extracted: Extracted) {
+        match extracted {
+            Extracted::History(e) => self.history.extend(e),
+            Extracted::Downloads(e) => self.downloads.extend(e),
+            Extracted::KeywordSearches(e) => self.keyword_searches.extend(e),
+            Extracted::Cookies(e) => self.cookies.extend(e),
+            Extracted::Autofill(e) => self.autofill.extend(e),
+            Extracted::Bookmarks(e) => self.bookmarks.extend(e),
+            Extracted::LoginData(e) => self.logins.extend(e),
+            Extracted::Extensions(e) => self.extensions.extend(e),
+            Extracted::BrowserSettings(e) => self.browser_settings.extend(e),
+            Extracted::DailyVisits(e) => self.daily_visits.extend(e),
+            Extracted::Shortcuts(e) => self.shortcuts.extend(e),
+            Extracted::TopSites(e) => self.top_sites.extend(e),
+            Extracted::Predictor(e) => self.predictor.extend(e),
+            Extracted::AutofillProfiles(e) => self.autofill_profiles.extend(e),
+            Extracted::CreditCards(e) => self.credit_cards.extend(e),
+            Extracted::Favicons(e) => self.favicons.extend(e),
+        }
+    }
+}
+
+/// Scan `dir` and run the extractor for every artifact found that `opts` selects.
+///
+/// A file that fails to extract is recorded in [`ArtifactSet::errors`] rather than
+/// failing the whole run; SQLite files of another kind than their name suggests are
+/// skipped. With a date range, entries without any timestamp are dropped.
+pub fn extract_all(dir: &Path, opts: &ExtractOptions) -> Result<ArtifactSet> {
+    if !dir.exists() {
+        bail!("Directory not found: {}", dir.display());
+    }
+    let mut set = ArtifactSet::default();
+    for artifact in scanner::scan(dir) {
+        if !opts.wants(artifact.artifact_type) {
+            continue;
+        }
+        let db_path = Path::new(&artifact.db_path);
+        if let Some(reason) = signature::encryption_reason(db_path) {
+            set.errors.push((artifact.db_path.clone(), reason));
+            continue;
+        }
+        if let Some(Err(reason)) = signature::verify(db_path) {
+            debug!("Skipping {}: {}", artifact.db_path, reason);
+            continue;
+        }
+
+        // Guest/System profile activity stays unattributed, even with a user override
+        let username = if artifact.is_unattributed_profile() {
+            ""
+        } else {
+            opts.user.as_deref().unwrap_or(&artifact.username)
+        };
+        match extract_artifact(&artifact, db_path, username, opts) {
+            Some(Ok(mut entries)) => {
+                if artifact.is_unattributed_profile() {
+                    entries.set_browser_profile(&artifact.profile_name);
+                }
+                for cutoff in opts.date_range.iter().flat_map(|r| r.cutoffs()) {
+                    entries.retain_within(cutoff);
+                }
+                set.push(entries);
+            }
+            Some(Err(e)) => set
+                .errors
+                .push((artifact.db_path.clone(), format!("{e:#}"))),
+            None => {}
+        }
+    }
+    Ok(set)
+}
+
+/// Entries extracted from one artifact file. HSTS records are history rows.
+#[derive(Debug, Clone)]
+pub enum Extracted {
+    History(Vec<HistoryEntry>),
+    Downloads(Vec<DownloadEntry>),
+    KeywordSearches(Vec<KeywordSearchEntry>),
+    Cookies(Vec<CookieEntry>),
+    Autofill(Vec<AutofillEntry>),
+    Bookmarks(Vec<BookmarkEntry>),
+    LoginData(Vec<LoginEntry>),
+    Extensions(Vec<ExtensionEntry>),
+    BrowserSettings(Vec<BrowserSettingsEntry>),
+    DailyVisits(Vec<DailyVisitEntry>),
+    Shortcuts(Vec<ShortcutEntry>),
+    TopSites(Vec<TopSiteEntry>),
+    Predictor(Vec<PredictorEntry>),
+    AutofillProfiles(Vec<AutofillProfileEntry>),
+    CreditCards(Vec<CreditCardEntry>),
+    Favicons(Vec<FaviconEntry>),
+}
+
+impl From<Vec<HistoryEntry>> for Extracted {
+    fn from(entries: Vec<HistoryEntry>) -> Self {
+        Self::History(entries)
+    }
+}
+
+impl From<Vec<DownloadEntry>> for Extracted {
+    fn from(entries: Vec<DownloadEntry>) -> Self {
+        Self::Downloads(entries)
+    }
+}
+
+impl From<Vec<KeywordSearchEntry>> for Extracted {
+    fn from(entries: Vec<KeywordSearchEntry>) -> Self {
+        Self::KeywordSearches(entries)
+    }
+}
+
+impl From<Vec<CookieEntry>> for Extracted {
+    fn from(entries: Vec<CookieEntry>) -> Self {
+        Self::Cookies(entries)
+    }
+}
+
+impl From<Vec<AutofillEntry>> for Extracted {
+    fn from(entries: Vec<AutofillEntry>) -> Self {
+        Self::Autofill(entries)
+    }
+}
+
+impl From<Vec<BookmarkEntry>> for Extracted {
+    fn from(entries: Vec<BookmarkEntry>) -> Self {
+        Self::Bookmarks(entries)
+    }
+}
+
+impl From<Vec<LoginEntry>> for Extracted {
+    fn from(entries: Vec<LoginEntry>) -> Self {
+        Self::LoginData(entries)
+    }
+}
+
+impl From<Vec<ExtensionEntry>> for Extracted {
+    fn from(entries: Vec<ExtensionEntry>) -> Self {
+        Self::Extensions(entries)
+    }
+}
+
+impl From<Vec<BrowserSettingsEntry>> for Extracted {
+    fn from(entries: Vec<BrowserSettingsEntry>) -> Self {
+        Self::BrowserSettings(entries)
+    }
+}
+
+impl From<Vec<DailyVisitEntry>> for Extracted {
+    fn from(entries: Vec<DailyVisitEntry>) -> Self {
+        Self::DailyVisits(entries)
+    }
+}
+
+impl From<Vec<ShortcutEntry>> for Extracted {
+    fn from(entries: Vec<ShortcutEntry>) -> Self {
+        Self::Shortcuts(entries)
+    }
+}
+
+impl From<Vec<TopSiteEntry>> for Extracted {
+    fn from(entries: Vec<TopSiteEntry>) -> Self {
+        Self::TopSites(entries)
+    }
+}
+
+impl From<Vec<PredictorEntry>> for Extracted {
+    fn from(entries: Vec<PredictorEntry>) -> Self {
+        Self::Predictor(entries)
+    }
+}
+
+impl From<Vec<AutofillProfileEntry>> for Extracted {
+    fn from(entries: Vec<AutofillProfileEntry>) -> Self {
+        Self::AutofillProfiles(entries)
+    }
+}
+
+impl From<Vec<CreditCardEntry>> for Extracted {
+    fn from(entries: Vec<CreditCardEntry>) -> Self {
+        Self::CreditCards(entries)
+    }
+}
+
+impl From<Vec<FaviconEntry>> for Extracted {
+    fn from(entries: Vec<FaviconEntry>) -> Self {
+        Self::Favicons(entries)
+    }
+}
+
+impl Extracted {
+    /// Newest activity timestamp (see [`filter::Timestamped`]).
+    pub fn latest(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::History(e) => filter::latest(e),
+            Self::Downloads(e) => filter::latest(e),
+            Self::KeywordSearches(e) => filter::latest(e),
+            Self::Cookies(e) => filter::latest(e),
+            Self::Autofill(e) => filter::latest(e),
+            Self::Bookmarks(e) => filter::latest(e),
+            Self::LoginData(e) => filter::latest(e),
+            Self::Extensions(e) => filter::latest(e),
+            Self::BrowserSettings(e) => filter::latest(e),
+            Self::DailyVisits(e) => filter::latest(e),
+            Self::Shortcuts(e) => filter::latest(e),
+            Self::TopSites(e) => filter::latest(e),
+            Self::Predictor(e) => filter::latest(e),
+            Self::AutofillProfiles(e) => filter::latest(e),
+            Self::CreditCards(e) => filter::latest(e),
+            Self::Favicons(e) => filter::latest(e),
+        }
+    }
+
+    /// Keep only entries passing `cutoff`; returns the number removed.
+    pub fn retain_within(&mut self, cutoff: filter::Cutoff) -> usize {
+        match self {
+            Self::History(e) => filter::retain_within(e, cutoff),
+            Self::Downloads(e) => filter::retain_within(e, cutoff),
+            Self::KeywordSearches(e) => filter::retain_within(e, cutoff),
+            Self::Cookies(e) => filter::retain_within(e, cutoff),
+            Self::Autofill(e) => filter::retain_within(e, cutoff),
+            Self::Bookmarks(e) => filter::retain_within(e, cutoff),
+            Self::LoginData(e) => filter::retain_within(e, cutoff),
+            Self::Extensions(e) => filter::retain_within(e, cutoff),
+            Self::BrowserSettings(e) => filter::retain_within(e, cutoff),
+            Self::DailyVisits(e) => filter::retain_within(e, cutoff),
+            Self::Shortcuts(e) => filter::retain_within(e, cutoff),
+            Self::TopSites(e) => filter::retain_within(e, cutoff),
+            Self::Predictor(e) => filter::retain_within(e, cutoff),
+            Self::AutofillProfiles(e) => filter::retain_within(e, cutoff),
+            Self::CreditCards(e) => filter::retain_within(e, cutoff),
+            Self::Favicons(e) => filter::retain_within(e, cutoff),
+        }
+    }
+
+    /// Drop entries `seen` already holds from another file; returns the number removed.
+    pub fn retain_unseen(&mut self, artifact: ArtifactType, seen: &mut GlobalDedup) -> usize {
+        match self {
+            Self::History(e) => seen.retain_unseen(artifact, e),
+            Self::Downloads(e) => seen.retain_unseen(artifact, e),
+            Self::KeywordSearches(e) => seen.retain_unseen(artifact, e),
+            Self::Cookies(e) => seen.retain_unseen(artifact, e),
+            Self::Autofill(e) => seen.retain_unseen(artifact, e),
+            Self::Bookmarks(e) => seen.retain_unseen(artifact, e),
+            Self::LoginData(e) => seen.retain_unseen(artifact, e),
+            Self::Extensions(e) => seen.retain_unseen(artifact, e),
+            Self::BrowserSettings(e) => seen.retain_unseen(artifact, e),
+            Self::DailyVisits(e) => seen.retain_unseen(artifact, e),
+            Self::Shortcuts(e) => seen.retain_unseen(artifact, e),
+            Self::TopSites(e) => seen.retain_unseen(artifact, e),
+            Self::Predictor(e) => seen.retain_unseen(artifact, e),
+            Self::AutofillProfiles(e) => seen.retain_unseen(artifact, e),
+            Self::CreditCards(e) => seen.retain_unseen(artifact, e),
+            Self::Favicons(e) => seen.retain_unseen(artifact, e),
+        }
+    }
+
+    /// Truncate oversized free-text fields; returns the number truncated.
+    pub fn cap_values(&mut self, max: usize) -> usize {
+        match self {
+            Self::History(e) => truncate::cap_all(e, max),
+            Self::Downloads(e) => truncate::cap_all(e, max),
+            Self::KeywordSearches(e) => truncate::cap_all(e, max),
+            Self::Cookies(e) => truncate::cap_all(e, max),
+            Self::Autofill(e) => truncate::cap_all(e, max),
+            Self::Bookmarks(e) => truncate::cap_all(e, max),
+            Self::Extensions(e) => truncate::cap_all(e, max),
+            Self::BrowserSettings(e) => truncate::cap_all(e, max),
+            Self::DailyVisits(e) => truncate::cap_all(e, max),
+            Self::Shortcuts(e) => truncate::cap_all(e, max),
+            Self::TopSites(e) => truncate::cap_all(e, max),
+            Self::Predictor(e) => truncate::cap_all(e, max),
+            Self::AutofillProfiles(e) => truncate::cap_all(e, max),
+            // Only URLs, paths, names and numbers
+            Self::LoginData(_) | Self::Favicons(_) | Self::CreditCards(_) => 0,
+        }
+    }
+
+    /// Redact matching values; returns the number of rows changed.
+    pub fn redact(&mut self, redactor: &Redactor) -> usize {
+        match self {
+            Self::History(e) => redactor.apply(e),
+            Self::Downloads(e) => redactor.apply(e),
+            Self::Cookies(e) => redactor.apply(e),
+            Self::Bookmarks(e) => redactor.apply(e),
+            Self::Shortcuts(e) => redactor.apply(e),
+            Self::TopSites(e) => redactor.apply(e),
+            Self::Predictor(e) => redactor.apply(e),
+            Self::Favicons(e) => redactor.apply(e),
+            Self::KeywordSearches(e) => redactor.apply(e),
+            Self::LoginData(e) => redactor.apply(e),
+            Self::BrowserSettings(e) => redactor.apply(e),
+            Self::DailyVisits(e) => redactor.apply(e),
+            Self::Autofill(_)
+            | Self::Extensions(_)
+            | Self::AutofillProfiles(_)
+            | Self::CreditCards(_) => 0,
+        }
+    }
+
+    /// Hash user-identifying fields.
+    pub fn anonymize(&mut self, anon: &Anonymizer) {
+        match self {
+            Self::History(e) => anon.apply(e),
+            Self::Downloads(e) => anon.apply(e),
+            Self::KeywordSearches(e) => anon.apply(e),
+            Self::Cookies(e) => anon.apply(e),
+            Self::Autofill(e) => anon.apply(e),
+            Self::Bookmarks(e) => anon.apply(e),
+            Self::LoginData(e) => anon.apply(e),
+            Self::Extensions(e) => anon.apply(e),
+            Self::BrowserSettings(e) => anon.apply(e),
+            Self::DailyVisits(e) => anon.apply(e),
+            Self::Shortcuts(e) => anon.apply(e),
+            Self::TopSites(e) => anon.apply(e),
+            Self::Predictor(e) => anon.apply(e),
+            Self::AutofillProfiles(e) => anon.apply(e),
+            Self::CreditCards(e) => anon.apply(e),
+            Self::Favicons(e) => anon.apply(e),
+        }
+    }
+
+    /// Set the Web Browser column of every entry to `browser`.
+    pub fn set_web_browser(&mut self, browser: &str) {
+        let b = &browser.to_string();
+        match self {
+            Self::History(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::Downloads(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::KeywordSearches(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::Cookies(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::Autofill(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::Bookmarks(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::LoginData(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::Extensions(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::BrowserSettings(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::DailyVisits(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::Shortcuts(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::TopSites(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::Predictor(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::AutofillProfiles(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::CreditCards(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+            Self::Favicons(e) => e.iter_mut().for_each(|e| e.web_browser.clone_from(b)),
+        }
+    }
+
+    /// Rewrite the source file column of every entry (History File for history) where
+    /// `rename` returns a new value.
+    pub fn rename_source_file(&mut self, rename: impl Fn(&str) -> Option<String>) {
+        fn apply(field: &mut String, rename: &impl Fn(&str) -> Option<String>) {
+            if let Some(renamed) = rename(field) {
+                *field = renamed;
+            }
+        }
+        let r = &rename;
+        match self {
+            Self::History(e) => e.iter_mut().for_each(|e| apply(&mut e.history_file, r)),
+            Self::Downloads(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::KeywordSearches(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::Cookies(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::Autofill(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::Bookmarks(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::LoginData(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::Extensions(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::BrowserSettings(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::DailyVisits(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::Shortcuts(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::TopSites(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::Predictor(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::AutofillProfiles(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::CreditCards(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+            Self::Favicons(e) => e.iter_mut().for_each(|e| apply(&mut e.source_file, r)),
+        }
+    }
+
+    /// Label every entry with `profile` (e.g. Guest / System profiles).
+    pub fn set_browser_profile(&mut self, profile: &str) {
+        let p = &profile.to_string();
+        match self {
+            Self::History(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::Downloads(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::KeywordSearches(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::Cookies(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::Autofill(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::Bookmarks(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::LoginData(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::Extensions(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::BrowserSettings(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::DailyVisits(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::Shortcuts(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::TopSites(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::Predictor(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::AutofillProfiles(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::CreditCards(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+            Self::Favicons(e) => e.iter_mut().for_each(|e| e.browser_profile.clone_from(p)),
+        }
+    }
+}
+
+/// Run the extractor for one scanned artifact, as found in the [`registry`]. Returns
+/// `None` when the browser has no extractor for this artifact type.
+pub fn extract_artifact(
+    artifact: &BrowserArtifact,
+    db_path: &Path,
+    username: &str,
+    opts: &ExtractOptions,
+) -> Option<Result<Extracted>> {
+    let browser = artifact.browser;
+    let firefox = browser.is_firefox();
+    let extractors = registry::registry(opts);
+    let extractor = registry::find(&extractors, artifact)?;
+    let result =
+        extractor
+            .extract_entries(db_path, username, browser)
+            .map(|entries| match entries {
+                Extracted::Downloads(mut entries) => {
+                    if let Some(window) = opts.referrer_window {
+                        infer_referrers(
+                            &extractors,
+                            &mut entries,
+                            artifact,
+                            db_path,
+                            username,
+                            window,
+                        );
+                    }
+                    Extracted::Downloads(entries)
+                }
+                Extracted::Cookies(mut entries) => {
+                    mark_cookie_hosts(&extractors, &mut entries, artifact, db_path, username);
+                    Extracted::Cookies(entries)
+                }
+                entries => entries,
+            });
+    // The Firefox extractors label rows "Firefox"; forks are named after themselves
+    let result = if firefox && browser != BrowserType::Firefox {
+        result.map(|mut entries| {
+            entries.set_web_browser(browser.display_name());
+            entries
+        })
+    } else {
+        result
+    };
+    if browser == BrowserType::TorBrowser {
+        if let Ok(Extracted::History(entries)) = &result {
+            if entries.is_empty() {
+                info!(
+                    "    Tor Browser keeps no history by default; no visits in {}",
+                    db_path.display()
+                );
+            }
+        }
+    }
+    Some(result)
+}
+
+/// Extract the history database at `history_path` that belongs with `artifact` (same
+/// browser and profile), with its extractor from the [`registry`].
+fn related_history(
+    extractors: &[Box<dyn registry::DynExtractor>],
+    artifact: &BrowserArtifact,
+    history_path: &Path,
+    username: &str,
+) -> Result<Vec<HistoryEntry>> {
+    let history = BrowserArtifact {
+        artifact_type: ArtifactType::History,
+        db_path: history_path.to_string_lossy().into_owned(),
+        ..artifact.clone()
+    };
+    let extractor = match registry::find(extractors, &history) {
+        Some(e) => e,
+        None => bail!(
+            "no history extractor for {}",
+            artifact.browser.display_name()
+        ),
+    };
+    match extractor.extract_entries(history_path, username, artifact.browser)? {
+        Extracted::History(entries) => Ok(entries),
+        _ => bail!("{} does not extract history", extractor.name()),
+    }
+}
+
+/// Correlate downloads with history from the same database to fill inferred referrers.
+fn infer_referrers(
+    extractors: &[Box<dyn registry::DynExtractor>],
+    downloads: &mut [DownloadEntry],
+    artifact: &BrowserArtifact,
+    db_path: &Path,
+    username: &str,
+    window: chrono::Duration,
+) {
+    // Downloads.plist sits next to History.db in ~/Library/Safari; the other browsers
+    // keep downloads in the history database itself
+    let safari_history;
+    let history_path = if artifact.browser == BrowserType::Safari {
+        safari_history = correlate::profile_dir(&artifact.db_path).join("History.db");
+        if !safari_history.is_file() {
+            debug!(
+                "    No History.db next to {}; referrers not inferred",
+                db_path.display()
+            );
+            return;
+        }
+        safari_history.as_path()
+    } else {
+        db_path
+    };
+    match related_history(extractors, artifact, history_path, username) {
+        Ok(history) => {
+            let filled = correlate::infer_download_referrers(downloads, &history, window);
+            if filled > 0 {
+                info!("    Inferred referrer for {} download(s)", filled);
+            }
+        }
+        Err(e) => warn!("    Referrer inference skipped: {}", e),
+    }
+}
+
+/// Flag cookies whose host was visited, from the history database of the same profile.
+fn mark_cookie_hosts(
+    extractors: &[Box<dyn registry::DynExtractor>],
+    cookies: &mut [CookieEntry],
+    artifact: &BrowserArtifact,
+    db_path: &Path,
+    username: &str,
+) {
+    let (history_name, history_path) = match artifact.browser {
+        b if b.is_chromium() => (
+            "History",
+            correlate::profile_dir(&artifact.db_path).join("History"),
+        ),
+        // Library/Cookies/Cookies.binarycookies -> Library/Safari/History.db
+        BrowserType::Safari => {
+            let library = correlate::profile_dir(&artifact.db_path)
+                .parent()
+                .unwrap_or(Path::new(""));
+            ("History.db", library.join("Safari").join("History.db"))
+        }
+        _ => (
+            "places.sqlite",
+            correlate::profile_dir(&artifact.db_path).join("places.sqlite"),
+        ),
+    };
+    if !history_path.is_file() {
+        debug!(
+            "    No {} next to {}; cookie hosts not correlated",
+            history_name,
+            db_path.display()
+        );
+        return;
+    }
+    match related_history(extractors, artifact, &history_path, username) {
+        Ok(history) => {
+            let first_party = correlate::mark_cookie_hosts(cookies, &history);
+            info!(
+                "    {} of {} cookie(s) are first-party to visited sites",
+                first_party,
+                cookies.len()
+            );
+        }
+        Err(e) => warn!("    Cookie host correlation skipped: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_history(root: &Path, user: &str) {
+        let profile = root
+            .join("Users")
+            .join(user)
+            .join("AppData/Local/Google/Chrome/User Data/Default");
+        std::fs::create_dir_all(&profile).unwrap();
+        let conn = rusqlite::Connection::open(profile.join("History")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                visit_count INTEGER, typed_count INTEGER,
+                                last_visit_time INTEGER);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                                  from_visit INTEGER, transition INTEGER);
+             CREATE TABLE downloads (id INTEGER PRIMARY KEY, current_path LONGVARCHAR,
+                                     target_path LONGVARCHAR, start_time INTEGER,
+                                     end_time INTEGER, received_bytes INTEGER,
+                                     total_bytes INTEGER, state INTEGER, danger_type INTEGER,
+                                     opened INTEGER, referrer VARCHAR, tab_url VARCHAR,
+                                     mime_type VARCHAR, original_mime_type VARCHAR);
+             INSERT INTO urls VALUES (1, 'https://a.example/', 'A', 1, 0, 13245010621000000);
+             INSERT INTO urls VALUES (2, 'https://b.example/', 'B', 1, 0, 13260000000000000);
+             INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 1);
+             INSERT INTO visits VALUES (2, 2, 13260000000000000, 0, 1);
+             INSERT INTO downloads VALUES (1, '/tmp/x', '/tmp/x', 13245010622000000, 0,
+                                           1, 1, 1, 0, 0, '', 'https://a.example/', '', '');",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_extract_all_filters_and_overrides_user() {
+        let dir = tempfile::TempDir::new().unwrap();
+        write_history(dir.path(), "alice");
+
+        let all = extract_all(dir.path(), &ExtractOptions::default()).unwrap();
+        assert_eq!(all.history.len(), 2);
+        assert_eq!(all.downloads.len(), 1);
+        assert!(all.errors.is_empty());
+        assert!(all.history.iter().all(|e| e.user_profile == "alice"));
+
+        let opts = ExtractOptions {
+            artifacts: HashSet::from([ArtifactType::History]),
+            user: Some("suspect".to_string()),
+            date_range: DateRange::new(Some(filter::parse_timestamp("2021-01-01").unwrap()), None)
+                .unwrap(),
+            ..ExtractOptions::default()
+        };
+        let set = extract_all(dir.path(), &opts).unwrap();
+        assert!(set.downloads.is_empty());
+        assert_eq!(set.history.len(), 1);
+        assert_eq!(set.history[0].url, "https://b.example/");
+        assert_eq!(set.history[0].user_profile, "suspect");
+
+        assert!(extract_all(&dir.path().join("missing"), &opts).is_err());
+    }
+
+    #[test]
+    fn test_tor_browser_uses_firefox_extractors() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir
+            .path()
+            .join("Users/alice/Desktop/Tor Browser/Browser/TorBrowser/Data/Browser")
+            .join("profile.default");
+        std::fs::create_dir_all(&profile).unwrap();
+        let conn = rusqlite::Connection::open(profile.join("favicons.sqlite")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE moz_icons (id INTEGER PRIMARY KEY, icon_url TEXT NOT NULL,
+                 width INTEGER NOT NULL DEFAULT 0, expire_ms INTEGER NOT NULL DEFAULT 0);
+             CREATE TABLE moz_pages_w_icons (id INTEGER PRIMARY KEY, page_url TEXT NOT NULL);
+             CREATE TABLE moz_icons_to_pages (page_id INTEGER NOT NULL,
+                 icon_id INTEGER NOT NULL);
+             INSERT INTO moz_icons VALUES (1, 'http://onion.example/favicon.ico', 16,
+                 1705320000000);
+             INSERT INTO moz_pages_w_icons VALUES (1, 'http://onion.example/');
+             INSERT INTO moz_icons_to_pages VALUES (1, 1);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let set = extract_all(dir.path(), &ExtractOptions::default()).unwrap();
+        assert!(set.errors.is_empty(), "{:?}", set.errors);
+        assert_eq!(set.favicons.len(), 1);
+        assert_eq!(set.favicons[0].web_browser, "Tor Browser");
+        assert_eq!(set.favicons[0].user_profile, "alice");
+    }
+
+    #[test]
+    fn test_redacted_host_absent_from_every_artifact() {
+        let url = "https://secure.chase.com/login".to_string();
+        let title = "Chase".to_string();
+        let mut all = vec![
+            Extracted::History(vec![HistoryEntry {
+                url: url.clone(),
+                title: title.clone(),
+                visited_from: url.clone(),
+                journey: url.clone(),
+                canonical_url: url.clone(),
+                ..Default::default()
+            }]),
+            Extracted::Downloads(vec![DownloadEntry {
+                url: url.clone(),
+                url_chain: url.clone(),
+                referrer: url.clone(),
+                tab_url: url.clone(),
+                inferred_referrer: url.clone(),
+                page_title: title.clone(),
+                ..Default::default()
+            }]),
+            Extracted::KeywordSearches(vec![KeywordSearchEntry {
+                url: url.clone(),
+                title: title.clone(),
+                search_term: "chase.com login".to_string(),
+                normalized_term: "chase.com login".to_string(),
+                ..Default::default()
+            }]),
+            Extracted::Cookies(vec![CookieEntry {
+                host: ".chase.com".to_string(),
+                ..Default::default()
+            }]),
+            Extracted::Bookmarks(vec![BookmarkEntry {
+                url: url.clone(),
+                title: title.clone(),
+                ..Default::default()
+            }]),
+            Extracted::LoginData(vec![LoginEntry {
+                origin_url: url.clone(),
+                action_url: url.clone(),
+                signon_realm: "https://secure.chase.com/".to_string(),
+                ..Default::default()
+            }]),
+            Extracted::BrowserSettings(vec![BrowserSettingsEntry {
+                setting: "homepage".to_string(),
+                value: url.clone(),
+                ..Default::default()
+            }]),
+            Extracted::DailyVisits(vec![DailyVisitEntry {
+                url: url.clone(),
+                title: title.clone(),
+                ..Default::default()
+            }]),
+            Extracted::Shortcuts(vec![ShortcutEntry {
+                url: url.clone(),
+                contents: url.clone(),
+                text: "chase".to_string(),
+                ..Default::default()
+            }]),
+            Extracted::TopSites(vec![TopSiteEntry {
+                url: url.clone(),
+                title: title.clone(),
+                ..Default::default()
+            }]),
+            Extracted::Predictor(vec![PredictorEntry {
+                url: url.clone(),
+                user_text: "chase.com".to_string(),
+                ..Default::default()
+            }]),
+            Extracted::Favicons(vec![FaviconEntry {
+                page_url: url.clone(),
+                icon_url: url.clone(),
+                ..Default::default()
+            }]),
+        ];
+        let redactor = Redactor::new(&[r"chase\.com"]).unwrap();
+        for extracted in &mut all {
+            assert_eq!(extracted.redact(&redactor), 1, "{extracted:?}");
+            let debug = format!("{extracted:?}");
+            assert!(!debug.contains("chase"), "{debug}");
+        }
+    }
+}
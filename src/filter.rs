@@ -0,0 +1,377 @@
+//! Time-based filtering of extracted entries.
+//!
+//! Relative windows (`--last 7d`) are anchored to the newest timestamp found in the
+//! extracted data rather than the wall clock, since triage images are historical.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+use crate::browsers::{
+    AutofillEntry, AutofillProfileEntry, BookmarkEntry, BrowserSettingsEntry, CookieEntry,
+    CreditCardEntry, DailyVisitEntry, DownloadEntry, ExtensionEntry, FaviconEntry, HistoryEntry,
+    KeywordSearchEntry, LoginEntry, PredictorEntry, ShortcutEntry, TopSiteEntry,
+};
+
+/// An entry with a single "most recent activity" timestamp used for time filtering.
+pub trait Timestamped {
+    /// Latest activity timestamp on the entry, if it has any.
+    fn activity_time(&self) -> Option<DateTime<Utc>>;
+}
+
+impl Timestamped for HistoryEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        Some(self.visit_time)
+    }
+}
+
+impl Timestamped for DownloadEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        Some(
+            self.end_time
+                .map_or(self.start_time, |t| t.max(self.start_time)),
+        )
+    }
+}
+
+impl Timestamped for KeywordSearchEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        self.visit_time
+    }
+}
+
+impl Timestamped for CookieEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        Some(
+            self.last_access_time
+                .map_or(self.creation_time, |t| t.max(self.creation_time)),
+        )
+    }
+}
+
+impl Timestamped for AutofillEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        self.last_used.max(self.first_used)
+    }
+}
+
+impl Timestamped for AutofillProfileEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        self.last_used.max(self.date_modified)
+    }
+}
+
+impl Timestamped for CreditCardEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        self.last_used.max(self.date_modified)
+    }
+}
+
+impl Timestamped for BookmarkEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        [
+            self.date_added,
+            self.date_last_used,
+            self.date_modified,
+            self.last_visited_desktop,
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+    }
+}
+
+impl Timestamped for LoginEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        [
+            self.date_created,
+            self.date_last_used,
+            self.date_password_modified,
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+    }
+}
+
+impl Timestamped for ExtensionEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        self.install_time
+    }
+}
+
+impl Timestamped for BrowserSettingsEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        self.last_modified
+    }
+}
+
+impl Timestamped for ShortcutEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        self.last_access_time
+    }
+}
+
+impl Timestamped for FaviconEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        self.expire_time
+    }
+}
+
+impl Timestamped for TopSiteEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        self.day
+            .and_then(|d| d.and_hms_milli_opt(23, 59, 59, 999))
+            .map(|dt| dt.and_utc())
+    }
+}
+
+impl Timestamped for PredictorEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+}
+
+impl Timestamped for DailyVisitEntry {
+    fn activity_time(&self) -> Option<DateTime<Utc>> {
+        // Only the day is known; treat it as its last instant so `--last` keeps the whole day
+        self.day
+            .and_hms_milli_opt(23, 59, 59, 999)
+            .map(|dt| dt.and_utc())
+    }
+}
+
+/// Parse a relative duration such as `7d`, `48h`, `30m`, `2w` or `90s`.
+pub fn parse_relative_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .with_context(|| format!("Missing unit in '{s}' (expected s, m, h, d or w)"))?;
+    let (num, unit) = s.split_at(split);
+    let n: i64 = num
+        .parse()
+        .with_context(|| format!("Invalid number in '{s}'"))?;
+    let dur = match unit.to_lowercase().as_str() {
+        "s" => Duration::try_seconds(n),
+        "m" => Duration::try_minutes(n),
+        "h" => Duration::try_hours(n),
+        "d" => Duration::try_days(n),
+        "w" => Duration::try_weeks(n),
+        other => bail!("Unknown unit '{other}' in '{s}' (expected s, m, h, d or w)"),
+    };
+    dur.with_context(|| format!("Duration out of range: '{s}'"))
+}
+
+/// Newest activity timestamp across `entries`.
+pub fn latest<T: Timestamped>(entries: &[T]) -> Option<DateTime<Utc>> {
+    entries.iter().filter_map(|e| e.activity_time()).max()
+}
+
+/// Parse an absolute timestamp given on the command line: RFC 3339
+/// (`2024-03-01T12:00:00Z`), or `YYYY-MM-DD[ HH:MM[:SS]]` / `YYYY-MM-DDTHH:MM[:SS]`
+/// taken as UTC.
+pub fn parse_timestamp(s: &str) -> Result<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    for fmt in [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(ndt) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Ok(ndt.and_utc());
+        }
+    }
+    match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        Ok(d) => Ok(d.and_time(NaiveTime::MIN).and_utc()),
+        Err(_) => bail!("Invalid timestamp '{s}' (expected e.g. 2024-03-01 12:00:00 or RFC 3339)"),
+    }
+}
+
+/// Time bound applied to every artifact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cutoff {
+    /// At or after the instant (`--last`, `--after`)
+    Since(DateTime<Utc>),
+    /// Strictly after the instant (`--resume-from`), so the last entry of a previous
+    /// export is not written twice
+    After(DateTime<Utc>),
+    /// Strictly before the instant (`--before`)
+    Before(DateTime<Utc>),
+}
+
+impl Cutoff {
+    fn admits(self, t: DateTime<Utc>) -> bool {
+        match self {
+            Cutoff::Since(cutoff) => t >= cutoff,
+            Cutoff::After(cutoff) => t > cutoff,
+            Cutoff::Before(cutoff) => t < cutoff,
+        }
+    }
+}
+
+/// Absolute incident window (`--after` / `--before`): at or after `after` and strictly
+/// before `before`, so `--after 2024-03-01 --before 2024-03-02` is exactly March 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub after: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+}
+
+impl DateRange {
+    /// The range for the given bounds, or `None` if neither is set.
+    pub fn new(
+        after: Option<DateTime<Utc>>,
+        before: Option<DateTime<Utc>>,
+    ) -> Result<Option<Self>> {
+        if let (Some(a), Some(b)) = (after, before) {
+            if a >= b {
+                bail!("--after ({a}) must be earlier than --before ({b})");
+            }
+        }
+        Ok((after.is_some() || before.is_some()).then_some(Self { after, before }))
+    }
+
+    /// The bounds as cutoffs for [`retain_within`]: `Since(after)`, then `Before(before)`.
+    pub fn cutoffs(self) -> impl Iterator<Item = Cutoff> {
+        let after = self.after.map(Cutoff::Since);
+        after.into_iter().chain(self.before.map(Cutoff::Before))
+    }
+}
+
+/// Keep only entries whose activity time passes `cutoff`.
+/// Entries without any timestamp are dropped. Returns the number removed.
+pub fn retain_within<T: Timestamped>(entries: &mut Vec<T>, cutoff: Cutoff) -> usize {
+    let before = entries.len();
+    entries.retain(|e| e.activity_time().is_some_and(|t| cutoff.admits(t)));
+    before - entries.len()
+}
+
+/// Keep only entries whose activity time is at or after `cutoff`.
+pub fn retain_since<T: Timestamped>(entries: &mut Vec<T>, cutoff: DateTime<Utc>) -> usize {
+    retain_within(entries, Cutoff::Since(cutoff))
+}
+
+/// Filter `entries` to the `window` ending at their newest timestamp.
+pub fn retain_last<T: Timestamped>(entries: &mut Vec<T>, window: Duration) -> usize {
+    match latest(entries) {
+        Some(newest) => retain_since(entries, newest - window),
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, TimeZone};
+
+    fn visit(day: u32, hour: u32) -> HistoryEntry {
+        HistoryEntry {
+            url: format!("https://example.com/{day}/{hour}"),
+            visit_time: Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap(),
+            visit_count: 1,
+            web_browser: "Chrome".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Visits at noon on March 1..=20, plus a late one on the 20th.
+    fn fixture() -> Vec<HistoryEntry> {
+        let mut v: Vec<_> = (1..=20).map(|d| visit(d, 12)).collect();
+        v.push(visit(20, 23));
+        v
+    }
+
+    #[test]
+    fn test_parse_relative_duration() {
+        assert_eq!(parse_relative_duration("7d").unwrap(), Duration::days(7));
+        assert_eq!(parse_relative_duration("48h").unwrap(), Duration::hours(48));
+        assert_eq!(parse_relative_duration("2W").unwrap(), Duration::weeks(2));
+        assert!(parse_relative_duration("7").is_err());
+        assert!(parse_relative_duration("d").is_err());
+        assert!(parse_relative_duration("7y").is_err());
+    }
+
+    #[test]
+    fn test_last_7d_anchored_to_newest_entry() {
+        let mut entries = fixture();
+        retain_last(&mut entries, parse_relative_duration("7d").unwrap());
+        // Newest is Mar 20 23:00, cutoff Mar 13 23:00 -> noon visits on 14..=20 plus the late one
+        assert_eq!(entries.len(), 8);
+        assert!(entries
+            .iter()
+            .all(|e| e.visit_time >= Utc.with_ymd_and_hms(2024, 3, 13, 23, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_last_12h() {
+        let mut entries = fixture();
+        let removed = retain_last(&mut entries, parse_relative_duration("12h").unwrap());
+        assert_eq!(removed, 19);
+        let times: Vec<_> = entries.iter().map(|e| e.visit_time).collect();
+        assert_eq!(
+            times,
+            vec![
+                Utc.with_ymd_and_hms(2024, 3, 20, 12, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 3, 20, 23, 0, 0).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let noon = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(parse_timestamp("2024-03-01 12:00:00").unwrap(), noon);
+        assert_eq!(parse_timestamp("2024-03-01T12:00").unwrap(), noon);
+        assert_eq!(parse_timestamp("2024-03-01T14:00:00+02:00").unwrap(), noon);
+        assert_eq!(
+            parse_timestamp("2024-03-01").unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()
+        );
+        assert!(parse_timestamp("03/01/2024").is_err());
+    }
+
+    #[test]
+    fn test_resume_from_is_exclusive() {
+        let mut entries = fixture();
+        let last_exported = Utc.with_ymd_and_hms(2024, 3, 18, 12, 0, 0).unwrap();
+        let removed = retain_within(&mut entries, Cutoff::After(last_exported));
+        assert_eq!(removed, 18);
+        assert!(entries.iter().all(|e| e.visit_time > last_exported));
+
+        let mut entries = fixture();
+        retain_within(&mut entries, Cutoff::Since(last_exported));
+        assert_eq!(entries.len(), 4);
+    }
+
+    #[test]
+    fn test_date_range_is_half_open() {
+        let range = DateRange::new(
+            Some(parse_timestamp("2024-03-05").unwrap()),
+            Some(parse_timestamp("2024-03-07").unwrap()),
+        )
+        .unwrap()
+        .unwrap();
+        let mut entries = fixture();
+        let removed: usize = range
+            .cutoffs()
+            .map(|c| retain_within(&mut entries, c))
+            .sum();
+        assert_eq!(removed, 19);
+        let days: Vec<_> = entries.iter().map(|e| e.visit_time.day()).collect();
+        assert_eq!(days, [5, 6]);
+
+        let mut entries = fixture();
+        let until = DateRange::new(None, Some(parse_timestamp("2024-03-03T12:00:00Z").unwrap()));
+        for cutoff in until.unwrap().unwrap().cutoffs() {
+            retain_within(&mut entries, cutoff);
+        }
+        assert_eq!(entries.len(), 2);
+
+        assert!(DateRange::new(None, None).unwrap().is_none());
+        let noon = parse_timestamp("2024-03-01 12:00").unwrap();
+        assert!(DateRange::new(Some(noon), Some(noon)).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+//! iOS backup support (`--ios-backup`).
+//!
+//! iTunes/Finder backups store every file as `<backup>/<xx>/<fileID>` (a SHA-1 name), with
+//! the original location recorded only in `Manifest.db` (`Files.fileID` -> `domain`,
+//! `relativePath`). Browser files are copied back to `<dest>/<domain>/<relativePath>` so
+//! the normal filename-based scan and extractors, including WAL handling, work unchanged.
+
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use rusqlite::{Connection, OpenFlags};
+use std::path::{Component, Path, PathBuf};
+
+use crate::browsers::collect_rows;
+use crate::scanner;
+
+/// A file listed in `Manifest.db`.
+#[derive(Debug, Clone)]
+pub struct BackupFile {
+    pub file_id: String,
+    pub domain: String,
+    pub relative_path: String,
+}
+
+impl BackupFile {
+    /// Where the file's content lives in the backup: `<xx>/<fileID>` (iOS 10+), or
+    /// `<fileID>` directly in older flat backups.
+    fn content_path(&self, backup_dir: &Path) -> Option<PathBuf> {
+        let nested = backup_dir.join(self.file_id.get(..2)?).join(&self.file_id);
+        let flat = backup_dir.join(&self.file_id);
+        [nested, flat].into_iter().find(|p| p.is_file())
+    }
+
+    /// `<domain>/<relativePath>`, or `None` if either part would escape the staging dir.
+    fn restored_path(&self) -> Option<PathBuf> {
+        let path = Path::new(&self.domain).join(&self.relative_path);
+        path.components()
+            .all(|c| matches!(c, Component::Normal(_)))
+            .then_some(path)
+    }
+}
+
+fn is_browser_file(relative_path: &str) -> bool {
+    let name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+    let base = ["-wal", "-shm", "-journal"]
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .unwrap_or(name);
+    scanner::is_artifact_file(base) || scanner::SIDECAR_FILES.contains(&base)
+}
+
+/// List the browser files recorded in `<backup_dir>/Manifest.db`.
+pub fn browser_files(backup_dir: &Path) -> Result<Vec<BackupFile>> {
+    let manifest = backup_dir.join("Manifest.db");
+    let conn = Connection::open_with_flags(&manifest, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {}", manifest.display()))?;
+
+    // flags = 1 marks regular files (2 = directory, 4 = symlink)
+    let mut stmt = conn
+        .prepare("SELECT fileID, domain, relativePath FROM Files WHERE flags = 1")
+        .with_context(|| {
+            format!(
+                "Failed to read {} (encrypted backups are not supported)",
+                manifest.display()
+            )
+        })?;
+    let rows = stmt.query_map([], |row| {
+        Ok(BackupFile {
+            file_id: row.get(0)?,
+            domain: row.get(1)?,
+            relative_path: row.get(2)?,
+        })
+    })?;
+
+    let files = collect_rows(rows, &manifest.to_string_lossy());
+    Ok(files
+        .into_iter()
+        .filter(|f| is_browser_file(&f.relative_path))
+        .collect())
+}
+
+/// Copy the backup's browser files to `dest/<domain>/<relativePath>`.
+/// Returns the number of files restored.
+pub fn stage(backup_dir: &Path, dest: &Path) -> Result<usize> {
+    let mut staged = 0;
+    for file in browser_files(backup_dir)? {
+        let restored = match file.restored_path() {
+            Some(p) => dest.join(p),
+            None => {
+                warn!(
+                    "iOS backup: skipping unsafe path {}/{}",
+                    file.domain, file.relative_path
+                );
+                continue;
+            }
+        };
+        let src = match file.content_path(backup_dir) {
+            Some(p) => p,
+            None => {
+                warn!(
+                    "iOS backup: {} ({}/{}) listed in Manifest.db but missing",
+                    file.file_id, file.domain, file.relative_path
+                );
+                continue;
+            }
+        };
+        if let Some(parent) = restored.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        std::fs::copy(&src, &restored).with_context(|| {
+            format!("Failed to copy {} to {}", src.display(), restored.display())
+        })?;
+        debug!("iOS backup: {} -> {}", file.file_id, restored.display());
+        staged += 1;
+    }
+    Ok(staged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::browsers::{ArtifactType, BrowserType};
+
+    fn add_file(backup: &Path, conn: &Connection, id: &str, domain: &str, rel: &str) {
+        conn.execute(
+            "INSERT INTO Files VALUES (?1, ?2, ?3, 1, NULL)",
+            [id, domain, rel],
+        )
+        .unwrap();
+        let dir = backup.join(&id[..2]);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(id), rel.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_stage_resolves_manifest_paths() {
+        let backup = tempfile::TempDir::new().unwrap();
+        let conn = Connection::open(backup.path().join("Manifest.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE Files (fileID TEXT PRIMARY KEY, domain TEXT, relativePath TEXT,
+                                 flags INTEGER, file BLOB);
+             INSERT INTO Files VALUES ('0000dir', 'HomeDomain', 'Library/Safari', 2, NULL);",
+        )
+        .unwrap();
+        let safari = "1a6b8c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b";
+        add_file(
+            backup.path(),
+            &conn,
+            safari,
+            "HomeDomain",
+            "Library/Safari/History.db",
+        );
+        add_file(
+            backup.path(),
+            &conn,
+            "2b7c9d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c",
+            "HomeDomain",
+            "Library/Safari/History.db-wal",
+        );
+        add_file(
+            backup.path(),
+            &conn,
+            "3c8d0e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d",
+            "HomeDomain",
+            "Library/SMS/sms.db",
+        );
+        add_file(
+            backup.path(),
+            &conn,
+            "4d9e1f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e",
+            "AppDomain-com.google.chrome.ios",
+            "../../escape/History",
+        );
+        drop(conn);
+
+        let dest = tempfile::TempDir::new().unwrap();
+        assert_eq!(stage(backup.path(), dest.path()).unwrap(), 2);
+
+        let history = dest.path().join("HomeDomain/Library/Safari/History.db");
+        assert_eq!(
+            std::fs::read_to_string(&history).unwrap(),
+            "Library/Safari/History.db"
+        );
+        assert!(dest
+            .path()
+            .join("HomeDomain/Library/Safari/History.db-wal")
+            .exists());
+        assert!(!dest.path().join("HomeDomain/Library/SMS").exists());
+
+        let artifacts = crate::scanner::scan(dest.path());
+        assert!(artifacts.iter().any(|a| a.browser == BrowserType::Safari
+            && a.artifact_type == ArtifactType::History
+            && a.db_path.ends_with("History.db")));
+    }
+
+    #[test]
+    fn test_safari_side_files_are_browser_files() {
+        for path in [
+            "Library/Cookies/Cookies.binarycookies",
+            "Library/Safari/Downloads.plist",
+            "Library/Application Support/Firefox/Profiles/x.default/favicons.sqlite",
+            "Library/Application Support/Firefox/Profiles/x.default/sessionstore-backups/recovery.jsonlz4",
+            "Library/Application Support/Google/Chrome/Default/Visited Links",
+        ] {
+            assert!(is_browser_file(path), "{path}");
+        }
+        assert!(!is_browser_file("Library/SMS/sms.db"));
+    }
+
+    #[test]
+    fn test_missing_manifest_is_an_error() {
+        let backup = tempfile::TempDir::new().unwrap();
+        let dest = tempfile::TempDir::new().unwrap();
+        assert!(stage(backup.path(), dest.path()).is_err());
+    }
+}
@@ -1,4 +1,25 @@
+pub mod aggregate;
+pub mod anonymize;
+pub mod archive;
+#[cfg(feature = "async")]
+pub mod async_api;
 pub mod browsers;
+pub mod canonical;
 pub mod carver;
+pub mod config;
+pub mod correlate;
+pub mod dedup;
+pub mod extract;
+pub mod filter;
+pub mod ios_backup;
+pub mod merge;
 pub mod output;
+pub mod progress;
+pub mod redact;
+pub mod registry;
 pub mod scanner;
+pub mod truncate;
+pub mod watch;
+pub mod xlsx;
+
+pub use extract::{extract_all, ArtifactSet, ExtractOptions};
@@ -1,21 +1,42 @@
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
 use log::{error, info, warn};
-use std::collections::HashSet;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
 
+use forensic_webhistory::aggregate;
+use forensic_webhistory::anonymize::Anonymizer;
+use forensic_webhistory::archive;
 use forensic_webhistory::browsers::{self, ArtifactType, BrowserType, HistoryEntry};
+use forensic_webhistory::canonical::{self, Canonicalizer};
 use forensic_webhistory::carver;
+use forensic_webhistory::config;
+use forensic_webhistory::correlate;
+use forensic_webhistory::dedup::GlobalDedup;
+use forensic_webhistory::extract::{self, ExtractOptions, Extracted};
+use forensic_webhistory::filter;
+use forensic_webhistory::ios_backup;
+use forensic_webhistory::merge;
 use forensic_webhistory::output;
+use forensic_webhistory::progress::{ScanProgress, TerminalProgress};
+use forensic_webhistory::redact::Redactor;
 use forensic_webhistory::scanner;
+use forensic_webhistory::truncate;
+use forensic_webhistory::watch;
 
 #[derive(Parser)]
 #[command(
     name = "webx",
     about = "WebX — Forensic Browser Artifact Analyzer",
     long_about = "Extract browsing history, downloads, cookies, autofill, bookmarks, login metadata,\n\
-                  keyword searches, and extensions from Chrome, Firefox, IE/Edge, Brave, Opera, Vivaldi, Arc, and Safari.\n\n\
+                  keyword searches, and extensions from Chrome, Firefox, IE/Edge, Brave, Opera, Vivaldi, Yandex, Arc, and Safari.\n\n\
                   Set RUST_LOG=debug for verbose logging.",
     version
 )]
@@ -33,15 +54,51 @@ struct Cli {
 
     /// Date format for CSV output. Use "iso" for "%Y-%m-%d %H:%M:%S",
     /// or provide a custom strftime format string. Default: "%m/%d/%Y %I:%M:%S %p"
-    #[arg(long, global = true, default_value = "%m/%d/%Y %I:%M:%S %p")]
-    date_format: String,
+    #[arg(long, global = true)]
+    date_format: Option<String>,
+
+    /// IANA time zone to write timestamps in (e.g. America/New_York); each time is
+    /// followed by the zone abbreviation. Default: UTC, marked with a trailing " UTC"
+    #[arg(long, global = true)]
+    tz: Option<chrono_tz::Tz>,
+
+    /// Config file with default options (default: ./webx.toml if present).
+    /// Explicit CLI flags override config values.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Bytes of each SQLite database to memory-map while reading (0 = off)
+    #[arg(long, global = true, default_value_t = browsers::DEFAULT_SQLITE_MMAP)]
+    sqlite_mmap: i64,
+
+    /// SQLite page cache per database while reading, in KiB
+    #[arg(long, global = true, default_value_t = browsers::DEFAULT_SQLITE_CACHE_KIB)]
+    sqlite_cache: i64,
+
+    /// Directory for the working copies of browser databases (default: system temp).
+    /// Point it at a larger volume when /tmp cannot hold multi-GB databases
+    #[arg(long, global = true)]
+    temp_dir: Option<PathBuf>,
+
+    /// Keep the working copies of browser databases after the run and log where they
+    /// are, to inspect a failed extraction
+    #[arg(long, global = true)]
+    preserve_temp: bool,
+
+    /// Read databases in place, read-only and immutable, instead of copying them first.
+    /// For mounted images and other evidence no browser is writing to; a database with
+    /// a non-empty -wal or -journal is still copied. Read-only files are read in place
+    /// even without this flag
+    #[arg(long, global = true)]
+    no_copy: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Scan a triage directory for all browser artifacts and extract everything
     Scan {
-        /// Path to triage directory (KAPE output, mounted image, etc.)
+        /// Path to triage directory (KAPE output, mounted image, etc.), or a .tar /
+        /// .tar.gz bundle of one, unpacked to a temp directory first
         #[arg(short, long)]
         dir: PathBuf,
 
@@ -57,10 +114,185 @@ enum Commands {
         #[arg(long = "out")]
         parquet_dir: Option<PathBuf>,
 
+        /// Also write Arrow IPC (Feather v2) files, same schema as Parquet, to this directory
+        #[arg(long = "arrow")]
+        arrow_dir: Option<PathBuf>,
+
         /// Artifact types to extract (comma-separated). Default: all.
-        /// Options: history,downloads,keywords,cookies,autofill,bookmarks,logins,extensions
+        /// Options: history,downloads,keywords,cookies,autofill,bookmarks,logins,extensions,settings,daily_visits,hsts,shortcuts,favicons,topsites,predictor,autofill_profiles,credit_cards
         #[arg(long, value_delimiter = ',')]
         artifacts: Option<Vec<String>>,
+
+        /// Fill an "Inferred Referrer" column for downloads with no referrer, using the
+        /// nearest preceding history visit from the same profile
+        #[arg(long)]
+        infer_referrers: bool,
+
+        /// Look-back window in seconds for --infer-referrers
+        #[arg(long, default_value_t = correlate::DEFAULT_REFERRER_WINDOW_SECS)]
+        referrer_window: i64,
+
+        /// Skip paths matching this glob during the walk (repeatable),
+        /// e.g. "**/System Volume Information/**"
+        #[arg(long = "exclude-path")]
+        exclude_path: Vec<String>,
+
+        /// Treat the input as an iOS (iTunes/Finder) backup: browser files are located via
+        /// Manifest.db and restored to <output>/ios_backup/<domain>/<path> before scanning
+        #[arg(long)]
+        ios_backup: bool,
+
+        /// Only keep entries from this window before the newest timestamp in the data
+        /// (e.g. 7d, 48h, 30m, 2w). Anchored to the data, not the current time.
+        #[arg(long, value_parser = filter::parse_relative_duration)]
+        last: Option<chrono::Duration>,
+
+        /// Only keep entries strictly after this timestamp (e.g. "2024-03-01 12:00:00",
+        /// UTC, or RFC 3339), to append a new acquisition to an earlier export
+        #[arg(long, value_parser = filter::parse_timestamp)]
+        resume_from: Option<DateTime<Utc>>,
+
+        /// Only keep entries at or after this time (YYYY-MM-DD, UTC, or RFC 3339).
+        /// With --after or --before, entries without a timestamp are dropped
+        #[arg(long, value_parser = filter::parse_timestamp)]
+        after: Option<DateTime<Utc>>,
+
+        /// Only keep entries strictly before this time (YYYY-MM-DD, UTC, or RFC 3339)
+        #[arg(long, value_parser = filter::parse_timestamp)]
+        before: Option<DateTime<Utc>>,
+
+        /// Compare backups with the live files of the same profile: drop `Archived
+        /// History` visits also in `History`, and `Bookmarks.bak` entries still in
+        /// `Bookmarks`. Off by default to keep raw fidelity.
+        #[arg(long)]
+        dedup: bool,
+
+        /// Collapse repeats of the same visit (URL, visit time and record ID) within a
+        /// file, e.g. rows read twice when a `-wal` copy replays them. Off by default.
+        #[arg(long)]
+        dedup_visits: bool,
+
+        /// Drop records already written from another file of the scan (same URL or
+        /// equivalent, timestamp, browser and user), across every artifact type.
+        /// Repeats within one file are kept
+        #[arg(long)]
+        global_dedup: bool,
+
+        /// Drop Chrome visits labeled "Prefetch" (prerendered/prefetched pages with no
+        /// user transition) so only actual browsing remains
+        #[arg(long)]
+        exclude_prefetch: bool,
+
+        /// Drop Chrome visits to URLs marked hidden (redirect hops and other pages the
+        /// browser keeps out of its history UI). Kept by default, flagged in "Hidden"
+        #[arg(long)]
+        exclude_hidden: bool,
+
+        /// Safari: also report visits whose history item was deleted or lost its URL,
+        /// with an empty URL and Visit Type "Orphan Visit (deleted item)"
+        #[arg(long)]
+        include_orphans: bool,
+
+        /// Collapse history visits to one row per page, keeping the earliest: URLs are
+        /// compared with a lowercase host and without fragment or tracking parameters.
+        /// Fills the "Canonical URL" column
+        #[arg(long)]
+        canonical_dedup: bool,
+
+        /// Query parameters stripped by --canonical-dedup (comma-separated; a trailing *
+        /// matches any suffix). Default: utm_*, fbclid, gclid and other click IDs
+        #[arg(long, value_delimiter = ',', requires = "canonical_dedup")]
+        tracking_params: Option<Vec<String>>,
+
+        /// Only process this browser profile (repeatable). Matches the profile directory
+        /// (e.g. "Profile 2") or its display name from Local State, case-insensitively
+        #[arg(long = "profile")]
+        profiles: Vec<String>,
+
+        /// Split each CSV into parts of at most this many rows (name_0001.csv, ...),
+        /// repeating the header in each. 0 = single file
+        #[arg(long, default_value_t = 0)]
+        split_rows: usize,
+
+        /// Output file formats, comma-separated: csv, json (one array per file) and/or
+        /// jsonl (one object per line). JSON records use the `extract --stdout jsonl` fields.
+        /// Default: csv
+        #[arg(long, value_enum, value_delimiter = ',')]
+        format: Vec<OutputFormat>,
+
+        /// Databases to extract at once. 0 (the default) uses one thread per CPU
+        #[arg(long, default_value_t = 0)]
+        threads: usize,
+
+        /// Show a status line on stderr while walking the folder and extracting
+        /// ("N/M artifacts"). Ignored when stdout isn't a terminal
+        #[arg(long)]
+        progress: bool,
+
+        /// Replace usernames, cookie values, autofill values and login usernames with
+        /// salted SHA-256 hashes (consistent within the run) for shareable output
+        #[arg(long)]
+        anonymize: bool,
+
+        /// Fixed salt for --anonymize so hashes match across runs (default: random)
+        #[arg(long, requires = "anonymize")]
+        anonymize_salt: Option<String>,
+
+        /// Replace URLs and cookie hosts matching this regex with [REDACTED] (repeatable).
+        /// Rows are kept with their timestamps and counts; applies to history, downloads,
+        /// cookies and bookmarks
+        #[arg(long = "redact-pattern")]
+        redact_patterns: Vec<String>,
+
+        /// Truncate titles, cookie/autofill values, descriptions and other free-text
+        /// fields longer than this many bytes, marking the cut. 0 = no limit. Default: 8192
+        #[arg(long)]
+        max_value_len: Option<usize>,
+
+        /// Write a day-of-week x hour-of-day matrix of history visit counts to this CSV
+        #[arg(long)]
+        heatmap: Option<PathBuf>,
+
+        /// IANA time zone for --heatmap buckets (e.g. America/New_York). Default: --tz,
+        /// or UTC
+        #[arg(long, requires = "heatmap")]
+        heatmap_tz: Option<chrono_tz::Tz>,
+
+        /// Also write a self-contained HTML report to this file: a day-by-day timeline
+        /// across all artifacts and a sortable table per artifact type
+        #[arg(long)]
+        html: Option<PathBuf>,
+
+        /// Also write every extracted entry to this one CSV, sorted by timestamp: time,
+        /// artifact type, browser, user and a natural-language description
+        #[arg(long)]
+        timeline: Option<PathBuf>,
+
+        /// Also write every extracted artifact to this Excel workbook, one worksheet per
+        /// artifact type, with times as Excel date-times and a frozen header row
+        #[arg(long)]
+        xlsx: Option<PathBuf>,
+
+        /// Also write per-domain, per-day history visit counts to this CSV: Domain
+        /// (registrable domain), Date, VisitCount, FirstVisit, LastVisit, DistinctURLs
+        #[arg(long)]
+        summary: Option<PathBuf>,
+
+        /// Fail with an error (exit code 1) if some artifacts failed to extract, after
+        /// writing the output for the others. Without it such a scan exits with code 3
+        #[arg(long)]
+        strict: bool,
+
+        /// After the scan, print a one-line JSON summary (artifact and entry counts,
+        /// duration, exit code) to stdout. Logs stay on stderr
+        #[arg(long)]
+        json_summary: bool,
+
+        /// Only write these CSV columns, in this order (comma-separated header names,
+        /// e.g. "Visit Time,URL,Web Browser"). Columns an artifact lacks are skipped;
+        /// artifacts with none of them are written in full. Parquet is unaffected
+        #[arg(long, value_delimiter = ',')]
+        columns: Option<Vec<String>>,
     },
 
     /// Carve deleted/residual browser history from database files
@@ -70,8 +302,26 @@ enum Commands {
         input: PathBuf,
 
         /// Output CSV file for recovered entries
-        #[arg(short, long)]
-        output: PathBuf,
+        #[arg(short, long, required_unless_present = "validate_only")]
+        output: Option<PathBuf>,
+
+        /// Only report recoverable counts per source (freelist, freeblock, WAL, raw, unique)
+        /// without writing a CSV
+        #[arg(long)]
+        validate_only: bool,
+
+        /// Shortest carved URL kept, scheme included. Default: 12
+        #[arg(long)]
+        min_url_len: Option<usize>,
+
+        /// Shortest host kept for carved http(s)/ftp URLs. Default: 4
+        #[arg(long)]
+        min_domain_len: Option<usize>,
+
+        /// Smallest share (0-1) of letters and spaces in text taken as a carved title.
+        /// Default: 0.5
+        #[arg(long)]
+        title_alpha_ratio: Option<f64>,
     },
 
     /// Extract from a specific browser database file
@@ -84,10 +334,20 @@ enum Commands {
         #[arg(short, long)]
         output: Option<PathBuf>,
 
-        /// Browser type: chrome, firefox, ie, safari (auto-detected if omitted)
+        /// Format used when writing to stdout (no -o): csv, or jsonl for piping into jq
+        #[arg(long, value_enum, default_value_t = StdoutFormat::Csv, conflicts_with = "output")]
+        stdout: StdoutFormat,
+
+        /// Browser type: chrome, firefox, tor, ie, safari, or webdata for a Chromium
+        /// `Web Data` file (auto-detected if omitted)
         #[arg(short, long)]
         browser: Option<String>,
 
+        /// Artifact to read from the input: history, or autofill (saved form entries) from
+        /// a Chromium `Web Data` file. Default: autofill for `Web Data`, else history
+        #[arg(long, value_delimiter = ',')]
+        artifacts: Option<Vec<String>>,
+
         /// Username to include in output
         #[arg(short, long)]
         user: Option<String>,
@@ -95,9 +355,313 @@ enum Commands {
         /// Also write Parquet output alongside CSV
         #[arg(long = "out")]
         parquet_dir: Option<PathBuf>,
+
+        /// Also write Arrow IPC (Feather v2) files, same schema as Parquet, to this directory
+        #[arg(long = "arrow")]
+        arrow_dir: Option<PathBuf>,
+
+        /// Only keep entries from this window before the newest visit (e.g. 7d, 48h)
+        #[arg(long, value_parser = filter::parse_relative_duration)]
+        last: Option<chrono::Duration>,
+
+        /// Only keep entries at or after this time (YYYY-MM-DD, UTC, or RFC 3339).
+        /// With --after or --before, entries without a timestamp are dropped
+        #[arg(long, value_parser = filter::parse_timestamp)]
+        after: Option<DateTime<Utc>>,
+
+        /// Only keep entries strictly before this time (YYYY-MM-DD, UTC, or RFC 3339)
+        #[arg(long, value_parser = filter::parse_timestamp)]
+        before: Option<DateTime<Utc>>,
+
+        /// Collapse repeats of the same visit (URL, visit time and record ID), e.g. rows
+        /// read twice when a `-wal` copy replays them. Off by default to keep raw fidelity.
+        #[arg(long)]
+        dedup_visits: bool,
+
+        /// Drop Chrome visits labeled "Prefetch" (prerendered/prefetched pages with no
+        /// user transition) so only actual browsing remains
+        #[arg(long)]
+        exclude_prefetch: bool,
+
+        /// Drop Chrome visits to URLs marked hidden (redirect hops and other pages the
+        /// browser keeps out of its history UI). Kept by default, flagged in "Hidden"
+        #[arg(long)]
+        exclude_hidden: bool,
+
+        /// Safari: also report visits whose history item was deleted or lost its URL,
+        /// with an empty URL and Visit Type "Orphan Visit (deleted item)"
+        #[arg(long)]
+        include_orphans: bool,
+
+        /// Collapse history visits to one row per page, keeping the earliest: URLs are
+        /// compared with a lowercase host and without fragment or tracking parameters.
+        /// Fills the "Canonical URL" column
+        #[arg(long)]
+        canonical_dedup: bool,
+
+        /// Query parameters stripped by --canonical-dedup (comma-separated; a trailing *
+        /// matches any suffix). Default: utm_*, fbclid, gclid and other click IDs
+        #[arg(long, value_delimiter = ',', requires = "canonical_dedup")]
+        tracking_params: Option<Vec<String>>,
+
+        /// Skip the input unless it belongs to this profile (repeatable; directory or
+        /// display name, case-insensitive)
+        #[arg(long = "profile")]
+        profiles: Vec<String>,
+
+        /// Split the output CSV into parts of at most this many rows (name_0001.csv, ...).
+        /// 0 = single file
+        #[arg(long, default_value_t = 0, requires = "output")]
+        split_rows: usize,
+
+        /// Replace usernames, cookie values, autofill values and login usernames with
+        /// salted SHA-256 hashes (consistent within the run) for shareable output
+        #[arg(long)]
+        anonymize: bool,
+
+        /// Fixed salt for --anonymize so hashes match across runs (default: random)
+        #[arg(long, requires = "anonymize")]
+        anonymize_salt: Option<String>,
+
+        /// Replace URLs and cookie hosts matching this regex with [REDACTED] (repeatable).
+        /// Rows are kept with their timestamps and counts; applies to history, downloads,
+        /// cookies and bookmarks
+        #[arg(long = "redact-pattern")]
+        redact_patterns: Vec<String>,
+
+        /// Truncate titles, cookie/autofill values, descriptions and other free-text
+        /// fields longer than this many bytes, marking the cut. 0 = no limit. Default: 8192
+        #[arg(long)]
+        max_value_len: Option<usize>,
+
+        /// Also write per-domain, per-day history visit counts to this CSV: Domain
+        /// (registrable domain), Date, VisitCount, FirstVisit, LastVisit, DistinctURLs
+        #[arg(long)]
+        summary: Option<PathBuf>,
+
+        /// Exit with an error if the input failed to extract. A single input always
+        /// does; accepted so scripts can pass the same flags to scan and extract
+        #[arg(long)]
+        strict: bool,
+
+        /// After extracting, print a one-line JSON summary to stdout. Needs -o, since
+        /// without it the entries themselves go to stdout
+        #[arg(long, requires = "output")]
+        json_summary: bool,
+
+        /// Only write these CSV columns, in this order (comma-separated header names,
+        /// e.g. "Visit Time,URL,Web Browser"). Parquet is unaffected
+        #[arg(long, value_delimiter = ',')]
+        columns: Option<Vec<String>>,
+    },
+
+    /// Follow a live history database: poll it and print visits that appear after the
+    /// first read to stdout as JSON Lines, until interrupted
+    Watch {
+        /// History database to follow (History, places.sqlite, History.db, WebCacheV01.dat)
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Time between polls (e.g. 5s, 1m)
+        #[arg(long, default_value = "5s", value_parser = filter::parse_relative_duration)]
+        interval: chrono::Duration,
+
+        /// Also print the newest N visits already in the database when starting
+        #[arg(long, default_value_t = 0)]
+        tail: usize,
+
+        /// Stop after this many polls. 0 = run until interrupted
+        #[arg(long, default_value_t = 0)]
+        max_polls: u64,
+    },
+
+    /// Combine CSV exports of one artifact type (e.g. from several machines) into a
+    /// single deduplicated CSV sorted by time
+    Merge {
+        /// CSV files, or directories searched recursively for CSVs (repeatable). CSVs in
+        /// directories with another artifact's header are skipped
+        #[arg(short, long, required = true)]
+        input: Vec<PathBuf>,
+
+        /// Artifact type of the exports (same names as --artifacts)
+        #[arg(long, default_value = "history")]
+        artifact: String,
+
+        /// Merged CSV file to write
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Summarize a history CSV export per host or registrable domain: visits, distinct
+    /// URLs, first/last seen, typed count and most common title
+    Aggregate {
+        /// History CSV written by scan, extract or merge
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Group by host name, or by registrable domain (eTLD+1, e.g. example.co.uk)
+        #[arg(long, value_enum, default_value_t = AggregateBy::Host)]
+        by: AggregateBy,
+
+        /// CSV file to write
+        #[arg(short, long)]
+        output: PathBuf,
     },
+
+    /// Print the output columns (CSV headers, Parquet types, descriptions) per artifact
+    Schema {
+        /// Artifact type to describe (same names as --artifacts). Default: all
+        #[arg(long)]
+        artifact: Option<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "json")]
+        format: SchemaFormat,
+    },
+}
+
+/// Grouping key for `aggregate`.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum AggregateBy {
+    Host,
+    Etld1,
+}
+
+/// Output file format for `scan`.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Csv,
+    Json,
+    Jsonl,
+}
+
+impl OutputFormat {
+    fn record_format(self) -> output::RecordFormat {
+        match self {
+            OutputFormat::Csv => output::RecordFormat::Csv,
+            OutputFormat::Json => output::RecordFormat::Json,
+            OutputFormat::Jsonl => output::RecordFormat::Jsonl,
+        }
+    }
+}
+
+/// `--format`, else the config file's `format`, else CSV.
+fn output_formats(
+    flags: Vec<OutputFormat>,
+    config: Option<&[String]>,
+) -> Result<Vec<output::RecordFormat>> {
+    let formats = match (flags.is_empty(), config) {
+        (false, _) => flags,
+        (true, Some(names)) => names
+            .iter()
+            .map(|name| {
+                OutputFormat::from_str(name, true)
+                    .map_err(|_| anyhow::anyhow!("Invalid format in config file: {name}"))
+            })
+            .collect::<Result<_>>()?,
+        (true, None) => vec![OutputFormat::Csv],
+    };
+    Ok(formats
+        .into_iter()
+        .map(OutputFormat::record_format)
+        .collect())
+}
+
+/// Record format for `extract` when streaming to stdout.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum StdoutFormat {
+    Csv,
+    Jsonl,
+}
+
+/// Output format for `schema`.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SchemaFormat {
+    Json,
+    Text,
+}
+
+/// How a scan or extract ended, reported as the process exit code. Usage errors and
+/// fatal errors (unreadable input, unwritable output) exit with 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Outcome {
+    /// Artifacts were extracted and rows written (0)
+    #[default]
+    Success,
+    /// No browser artifacts were found, none were left to extract, or no rows were
+    /// written (2)
+    NoArtifacts,
+    /// Some artifacts failed to extract and the others were written (3); `scan --strict`
+    /// reports it as an error instead
+    PartialFailure,
+    /// Every artifact that was attempted failed to extract (4)
+    AllFailed,
+}
+
+impl Outcome {
+    fn code(self) -> u8 {
+        match self {
+            Outcome::Success => 0,
+            Outcome::NoArtifacts => 2,
+            Outcome::PartialFailure => 3,
+            Outcome::AllFailed => 4,
+        }
+    }
+}
+
+impl From<Outcome> for ExitCode {
+    fn from(outcome: Outcome) -> Self {
+        ExitCode::from(outcome.code())
+    }
+}
+
+/// Counts for a finished scan or extract, printed to stdout by `--json-summary`.
+#[derive(Debug, Default, Serialize)]
+struct ScanStats {
+    command: &'static str,
+    input: String,
+    artifacts_found: usize,
+    artifacts_extracted: usize,
+    artifacts_failed: usize,
+    /// Databases that are encrypted or not SQLite; their artifacts count as failed
+    encrypted_databases: Vec<String>,
+    entries_written: usize,
+    /// Entries written per artifact type display name
+    entries_by_type: BTreeMap<&'static str, usize>,
+    duration_secs: f64,
+    #[serde(rename = "exit_code", serialize_with = "serialize_outcome")]
+    outcome: Outcome,
 }
 
+fn serialize_outcome<S: serde::Serializer>(outcome: &Outcome, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u8(outcome.code())
+}
+
+impl ScanStats {
+    fn new(command: &'static str, input: &Path) -> Self {
+        Self {
+            command,
+            input: input.display().to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Stamp the run time, print the summary if asked, and hand back the outcome. A run
+    /// that wrote no rows (e.g. everything was filtered out) counts as no artifacts.
+    fn finish(mut self, started: Instant, json_summary: bool) -> Result<Outcome> {
+        self.duration_secs = started.elapsed().as_secs_f64();
+        if self.outcome == Outcome::Success && self.entries_written == 0 {
+            self.outcome = Outcome::NoArtifacts;
+        }
+        if json_summary {
+            println!("{}", serde_json::to_string(&self)?);
+        }
+        Ok(self.outcome)
+    }
+}
+
+const DEFAULT_DATE_FORMAT: &str = "%m/%d/%Y %I:%M:%S %p";
+
 fn resolve_date_format(fmt: &str) -> &str {
     match fmt.to_lowercase().as_str() {
         "iso" | "iso8601" => "%Y-%m-%d %H:%M:%S",
@@ -105,78 +669,291 @@ fn resolve_date_format(fmt: &str) -> &str {
     }
 }
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
         .format_timestamp(None)
         .init();
 
-    let cli = Cli::parse();
-    let date_fmt = resolve_date_format(&cli.date_format);
+    // clap exits with 2 on usage errors, which is taken by "no artifacts found"
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return Ok(if e.use_stderr() {
+                ExitCode::FAILURE
+            } else {
+                ExitCode::SUCCESS
+            });
+        }
+    };
+    let (config, config_path) = config::load(cli.config.as_deref())?;
+    if let Some(path) = &config_path {
+        info!("Using config file: {}", path.display());
+    }
+    let date_format = cli
+        .date_format
+        .or(config.date_format.clone())
+        .unwrap_or_else(|| DEFAULT_DATE_FORMAT.to_string());
+    let date_fmt = resolve_date_format(&date_format);
+    let tz = match cli.tz {
+        Some(tz) => Some(tz),
+        None => config
+            .tz
+            .as_deref()
+            .map(|name| {
+                name.parse::<chrono_tz::Tz>()
+                    .map_err(|e| anyhow::anyhow!("Invalid tz in config file: {e}"))
+            })
+            .transpose()?,
+    };
+    output::set_output_tz(tz);
+    browsers::set_sqlite_tuning(cli.sqlite_mmap, cli.sqlite_cache);
+    if let Some(dir) = &cli.temp_dir {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create temp directory: {}", dir.display()))?;
+    }
+    browsers::set_temp_options(cli.temp_dir.clone(), cli.preserve_temp);
+    browsers::set_no_copy(cli.no_copy);
 
     if cli.interactive || cli.command.is_none() {
-        return interactive_menu(date_fmt);
+        interactive_menu(date_fmt)?;
+        return Ok(ExitCode::SUCCESS);
     }
 
-    match cli.command.unwrap() {
+    let outcome = match cli.command.unwrap() {
         Commands::Scan {
             dir,
             output,
             user,
             parquet_dir,
+            arrow_dir,
             artifacts,
-        } => cmd_scan(
-            &dir,
-            &output,
-            user.as_deref(),
-            parquet_dir.as_deref(),
-            &parse_artifact_filter(&artifacts),
-            date_fmt,
-        ),
-        Commands::Carve { input, output } => cmd_carve(&input, &output, date_fmt),
+            infer_referrers,
+            referrer_window,
+            exclude_path,
+            ios_backup,
+            last,
+            resume_from,
+            after,
+            before,
+            dedup,
+            dedup_visits,
+            global_dedup,
+            exclude_prefetch,
+            exclude_hidden,
+            include_orphans,
+            canonical_dedup,
+            tracking_params,
+            profiles,
+            split_rows,
+            format,
+            threads,
+            progress,
+            anonymize,
+            anonymize_salt,
+            redact_patterns,
+            max_value_len,
+            heatmap,
+            heatmap_tz,
+            html,
+            timeline,
+            xlsx,
+            summary,
+            strict,
+            json_summary,
+            columns,
+        } => {
+            let user = user.or(config.user);
+            let anonymizer = anonymize
+                .then(|| Anonymizer::new(anonymize_salt.as_deref()))
+                .transpose()?;
+            let redactor = (!redact_patterns.is_empty())
+                .then(|| Redactor::new(&redact_patterns))
+                .transpose()?;
+            let canonicalizer = canonical_dedup
+                .then(|| canonicalizer(tracking_params.or(config.tracking_params)));
+            let opts = ScanSettings {
+                user: user.as_deref(),
+                parquet_dir: parquet_dir.as_deref(),
+                arrow_dir: arrow_dir.as_deref(),
+                artifact_filter: parse_artifact_filter(&artifacts.or(config.artifacts)),
+                scan_options: scanner::ScanOptions::with_excludes(&exclude_path)?,
+                ios_backup,
+                referrer_window: infer_referrers
+                    .then(|| chrono::Duration::seconds(referrer_window)),
+                last,
+                resume_from,
+                date_range: filter::DateRange::new(after, before)?,
+                dedup,
+                dedup_visits,
+                global_dedup,
+                exclude_prefetch,
+                exclude_hidden,
+                include_orphans,
+                canonicalizer: canonicalizer.as_ref(),
+                profiles: &profiles,
+                split_rows,
+                threads,
+                progress,
+                formats: output_formats(format, config.format.as_deref())?,
+                anonymizer: anonymizer.as_ref(),
+                redactor: redactor.as_ref(),
+                max_value_len: max_value_len
+                    .or(config.max_value_len)
+                    .unwrap_or(truncate::DEFAULT_MAX_VALUE_LEN),
+                heatmap: heatmap
+                    .as_deref()
+                    .map(|p| (p, heatmap_tz.or(tz).unwrap_or(chrono_tz::UTC))),
+                html: html.as_deref(),
+                timeline: timeline.as_deref(),
+                xlsx: xlsx.as_deref(),
+                summary: summary.as_deref(),
+                columns: columns.as_deref(),
+                date_fmt,
+            };
+            let started = Instant::now();
+            let stats = cmd_scan(&dir, &output, &opts)?;
+            let failed = stats.artifacts_failed;
+            let outcome = stats.finish(started, json_summary)?;
+            if strict && outcome == Outcome::PartialFailure {
+                anyhow::bail!("--strict: {} artifact(s) failed to extract", failed);
+            }
+            outcome
+        }
+        Commands::Carve {
+            input,
+            output,
+            validate_only,
+            min_url_len,
+            min_domain_len,
+            title_alpha_ratio,
+        } => {
+            let output = output.filter(|_| !validate_only);
+            let config = carver::CarveConfig {
+                min_url_len: min_url_len
+                    .or(config.carve_min_url_len)
+                    .unwrap_or(carver::DEFAULT_MIN_URL_LEN),
+                min_domain_len: min_domain_len
+                    .or(config.carve_min_domain_len)
+                    .unwrap_or(carver::DEFAULT_MIN_DOMAIN_LEN),
+                title_alpha_ratio: title_alpha_ratio
+                    .or(config.carve_title_alpha_ratio)
+                    .unwrap_or(carver::DEFAULT_TITLE_ALPHA_RATIO),
+            };
+            cmd_carve(&input, output.as_deref(), &config, date_fmt)?;
+            Outcome::Success
+        }
         Commands::Extract {
             input,
             output,
+            stdout,
             browser,
+            artifacts,
             user,
             parquet_dir,
-        } => cmd_extract(
-            &input,
-            output.as_deref(),
-            browser.as_deref(),
-            user.as_deref(),
-            parquet_dir.as_deref(),
-            date_fmt,
-        ),
-    }
+            arrow_dir,
+            last,
+            after,
+            before,
+            dedup_visits,
+            exclude_prefetch,
+            exclude_hidden,
+            include_orphans,
+            canonical_dedup,
+            tracking_params,
+            profiles,
+            split_rows,
+            anonymize,
+            anonymize_salt,
+            redact_patterns,
+            max_value_len,
+            summary,
+            strict: _,
+            json_summary,
+            columns,
+        } => {
+            let user = user.or(config.user);
+            let anonymizer = anonymize
+                .then(|| Anonymizer::new(anonymize_salt.as_deref()))
+                .transpose()?;
+            let redactor = (!redact_patterns.is_empty())
+                .then(|| Redactor::new(&redact_patterns))
+                .transpose()?;
+            let canonicalizer = canonical_dedup
+                .then(|| canonicalizer(tracking_params.or(config.tracking_params)));
+            let artifact = match artifacts.as_deref() {
+                None => None,
+                Some([name]) => Some(name.parse()?),
+                Some(_) => {
+                    anyhow::bail!("extract reads one artifact per input; pass one --artifacts")
+                }
+            };
+            let opts = ExtractSettings {
+                browser: browser.as_deref(),
+                artifact,
+                user: user.as_deref(),
+                parquet_dir: parquet_dir.as_deref(),
+                arrow_dir: arrow_dir.as_deref(),
+                stdout_format: stdout,
+                last,
+                date_range: filter::DateRange::new(after, before)?,
+                dedup_visits,
+                exclude_prefetch,
+                exclude_hidden,
+                include_orphans,
+                canonicalizer: canonicalizer.as_ref(),
+                profiles: &profiles,
+                split_rows,
+                anonymizer: anonymizer.as_ref(),
+                redactor: redactor.as_ref(),
+                max_value_len: max_value_len
+                    .or(config.max_value_len)
+                    .unwrap_or(truncate::DEFAULT_MAX_VALUE_LEN),
+                summary: summary.as_deref(),
+                columns: columns.as_deref(),
+                date_fmt,
+            };
+            let started = Instant::now();
+            cmd_extract(&input, output.as_deref(), &opts)?.finish(started, json_summary)?
+        }
+        Commands::Merge {
+            input,
+            artifact,
+            output,
+        } => {
+            cmd_merge(&input, &artifact, &output, date_fmt, tz)?;
+            Outcome::Success
+        }
+        Commands::Watch {
+            input,
+            interval,
+            tail,
+            max_polls,
+        } => {
+            cmd_watch(&input, interval, tail, max_polls)?;
+            Outcome::Success
+        }
+        Commands::Aggregate { input, by, output } => {
+            cmd_aggregate(&input, by, &output, date_fmt)?;
+            Outcome::Success
+        }
+        Commands::Schema { artifact, format } => {
+            cmd_schema(artifact.as_deref(), format)?;
+            Outcome::Success
+        }
+    };
+    Ok(outcome.into())
 }
 
 fn parse_artifact_filter(artifacts: &Option<Vec<String>>) -> HashSet<ArtifactType> {
     match artifacts {
-        None => vec![
-            ArtifactType::History,
-            ArtifactType::Downloads,
-            ArtifactType::KeywordSearches,
-            ArtifactType::Cookies,
-            ArtifactType::Autofill,
-            ArtifactType::Bookmarks,
-            ArtifactType::LoginData,
-            ArtifactType::Extensions,
-        ]
-        .into_iter()
-        .collect(),
+        None => ArtifactType::ALL.into_iter().collect(),
         Some(list) => list
             .iter()
-            .filter_map(|s| match s.to_lowercase().as_str() {
-                "history" => Some(ArtifactType::History),
-                "downloads" => Some(ArtifactType::Downloads),
-                "keywords" | "searches" => Some(ArtifactType::KeywordSearches),
-                "cookies" => Some(ArtifactType::Cookies),
-                "autofill" | "forms" => Some(ArtifactType::Autofill),
-                "bookmarks" => Some(ArtifactType::Bookmarks),
-                "logins" | "passwords" | "login_data" => Some(ArtifactType::LoginData),
-                "extensions" | "addons" => Some(ArtifactType::Extensions),
-                _ => {
-                    warn!("Unknown artifact type: {}", s);
+            .filter_map(|s| match s.parse() {
+                Ok(a) => Some(a),
+                Err(e) => {
+                    warn!("{}", e);
                     None
                 }
             })
@@ -184,6 +961,115 @@ fn parse_artifact_filter(artifacts: &Option<Vec<String>>) -> HashSet<ArtifactTyp
     }
 }
 
+/// Poll `input` every `interval` and stream new visits to stdout as JSON Lines. A
+/// failed first read is fatal; later failures (the file mid-replacement, a lock) are
+/// logged and retried on the next poll.
+fn cmd_watch(input: &Path, interval: chrono::Duration, tail: usize, max_polls: u64) -> Result<()> {
+    let pause = match interval.to_std() {
+        Ok(pause) if !pause.is_zero() => pause,
+        _ => anyhow::bail!("--interval must be positive"),
+    };
+    info!(
+        "Watching {} every {}s (Ctrl-C to stop)",
+        input.display(),
+        pause.as_secs_f64()
+    );
+    let mut watcher = watch::Watcher::new(input, tail);
+    let mut polls = 0u64;
+    loop {
+        match watcher.poll() {
+            Ok(entries) => {
+                output::write_jsonl_stdout(&entries)?;
+            }
+            Err(e) if polls == 0 => return Err(e),
+            Err(e) => warn!("Poll failed, retrying: {:#}", e),
+        }
+        polls += 1;
+        if max_polls > 0 && polls >= max_polls {
+            return Ok(());
+        }
+        std::thread::sleep(pause);
+    }
+}
+
+/// Merge prior CSV exports of one artifact type. Timestamps are parsed with `date_fmt`,
+/// which should match the `--date-format` the exports were written with.
+fn cmd_merge(
+    inputs: &[PathBuf],
+    artifact: &str,
+    output: &Path,
+    date_fmt: &str,
+    tz: Option<chrono_tz::Tz>,
+) -> Result<()> {
+    let artifact: ArtifactType = artifact.parse()?;
+    let stats = merge::merge_csv(inputs, artifact, output, date_fmt, tz)?;
+    if stats.files == 0 {
+        warn!("No {} CSV exports found", artifact.display_name());
+    }
+    info!(
+        "Merged {} row(s) from {} file(s) into {} ({} duplicate(s) dropped)",
+        stats.rows_written,
+        stats.files,
+        output.display(),
+        stats.duplicates
+    );
+    Ok(())
+}
+
+fn cmd_aggregate(input: &Path, by: AggregateBy, output: &Path, date_fmt: &str) -> Result<()> {
+    let by = match by {
+        AggregateBy::Host => aggregate::GroupBy::Host,
+        AggregateBy::Etld1 => aggregate::GroupBy::Etld1,
+    };
+    let stats = aggregate::aggregate_history(input, by, output, date_fmt)?;
+    if stats.rows_without_host > 0 {
+        info!(
+            "{} row(s) without a host (about:, file:, ...) not counted",
+            stats.rows_without_host
+        );
+    }
+    info!(
+        "Aggregated {} row(s) into {} group(s) -> {}",
+        stats.rows_read,
+        stats.groups,
+        output.display()
+    );
+    Ok(())
+}
+
+/// Print the column contract for one artifact type, or all of them.
+fn cmd_schema(artifact: Option<&str>, format: SchemaFormat) -> Result<()> {
+    let artifacts = match artifact {
+        Some(name) => vec![name.parse()?],
+        None => ArtifactType::ALL.to_vec(),
+    };
+    let schemas: Vec<_> = artifacts.into_iter().map(output::artifact_schema).collect();
+
+    match format {
+        SchemaFormat::Json => {
+            let json = if schemas.len() == 1 {
+                serde_json::to_string_pretty(&schemas[0])?
+            } else {
+                serde_json::to_string_pretty(&schemas)?
+            };
+            println!("{json}");
+        }
+        SchemaFormat::Text => {
+            for schema in &schemas {
+                println!("{} ({})", schema.display_name, schema.artifact);
+                for col in &schema.csv {
+                    println!(
+                        "  {:<28} {:<8} {}",
+                        col.name, col.data_type, col.description
+                    );
+                }
+                println!();
+            }
+        }
+    }
+    Ok(())
+}
+
 fn interactive_menu(date_fmt: &str) -> Result<()> {
     println!();
     println!(
@@ -192,7 +1078,7 @@ fn interactive_menu(date_fmt: &str) -> Result<()> {
     );
     println!();
     println!("  Supported Browsers:");
-    println!("    Chrome, Edge Chromium, Brave, Opera, Vivaldi, Arc (SQLite)");
+    println!("    Chrome, Edge Chromium, Brave, Opera, Opera GX, Vivaldi, Yandex, Arc (SQLite)");
     println!("    Firefox (places.sqlite)");
     println!("    Safari (History.db — macOS)");
     println!("    Internet Explorer / Edge Legacy (WebCacheV01.dat ESE)");
@@ -221,9 +1107,12 @@ fn interactive_menu(date_fmt: &str) -> Result<()> {
 
                 let dir = PathBuf::from(dir.trim());
                 let output = PathBuf::from(output.trim());
-                let all = parse_artifact_filter(&None);
-                match cmd_scan(&dir, &output, user.as_deref(), None, &all, date_fmt) {
-                    Ok(()) => println!("\n  Done!\n"),
+                let opts = ScanSettings {
+                    user: user.as_deref(),
+                    ..ScanSettings::new(date_fmt)
+                };
+                match cmd_scan(&dir, &output, &opts) {
+                    Ok(_) => println!("\n  Done!\n"),
                     Err(e) => println!("\n  Error: {e}\n"),
                 }
             }
@@ -236,15 +1125,13 @@ fn interactive_menu(date_fmt: &str) -> Result<()> {
 
                 let file = PathBuf::from(file.trim());
                 let output_path = output.as_ref().map(PathBuf::from);
-                match cmd_extract(
-                    &file,
-                    output_path.as_deref(),
-                    browser.as_deref(),
-                    user.as_deref(),
-                    None,
-                    date_fmt,
-                ) {
-                    Ok(()) => println!("\n  Done!\n"),
+                let opts = ExtractSettings {
+                    browser: browser.as_deref(),
+                    user: user.as_deref(),
+                    ..ExtractSettings::new(date_fmt)
+                };
+                match cmd_extract(&file, output_path.as_deref(), &opts) {
+                    Ok(_) => println!("\n  Done!\n"),
                     Err(e) => println!("\n  Error: {e}\n"),
                 }
             }
@@ -255,9 +1142,11 @@ fn interactive_menu(date_fmt: &str) -> Result<()> {
                 println!("    webx scan -d <triage_dir> -o <output_dir> --artifacts history,downloads,cookies");
                 println!("    webx extract -i <db_file> -o <output.csv>");
                 println!("    webx carve -i <db_file> -o <output.csv>");
+                println!("    webx merge -i <exports_dir> --artifact history -o <merged.csv>");
+                println!("    webx schema --artifact history --format json");
                 println!();
     println!("  ARTIFACT TYPES (all extracted by default):");
-                println!("    history, downloads, keywords, cookies, autofill, bookmarks, logins, extensions");
+                println!("    history, downloads, keywords, cookies, autofill, bookmarks, logins, extensions, settings, daily_visits, hsts, shortcuts, favicons, topsites, predictor, autofill_profiles, credit_cards");
                 println!("    Use --artifacts to limit, e.g. --artifacts history,downloads");
                 println!();
             }
@@ -297,25 +1186,161 @@ fn prompt_optional(message: &str) -> Result<Option<String>> {
     }
 }
 
-fn cmd_scan(
-    dir: &Path,
-    output_dir: &Path,
-    user: Option<&str>,
-    parquet_dir: Option<&Path>,
-    artifact_filter: &HashSet<ArtifactType>,
-    date_fmt: &str,
-) -> Result<()> {
+/// Options for `cmd_scan` beyond the input and output directories.
+struct ScanSettings<'a> {
+    user: Option<&'a str>,
+    parquet_dir: Option<&'a Path>,
+    arrow_dir: Option<&'a Path>,
+    artifact_filter: HashSet<ArtifactType>,
+    scan_options: scanner::ScanOptions,
+    ios_backup: bool,
+    referrer_window: Option<chrono::Duration>,
+    last: Option<chrono::Duration>,
+    resume_from: Option<DateTime<Utc>>,
+    date_range: Option<filter::DateRange>,
+    dedup: bool,
+    dedup_visits: bool,
+    global_dedup: bool,
+    exclude_prefetch: bool,
+    exclude_hidden: bool,
+    include_orphans: bool,
+    canonicalizer: Option<&'a Canonicalizer>,
+    profiles: &'a [String],
+    split_rows: usize,
+    /// Extraction threads; 0 for one per CPU
+    threads: usize,
+    /// `--progress`: status line while walking and extracting
+    progress: bool,
+    /// Per-artifact output files, one per format; CSV unless `--format` says otherwise
+    formats: Vec<output::RecordFormat>,
+    anonymizer: Option<&'a Anonymizer>,
+    redactor: Option<&'a Redactor>,
+    max_value_len: usize,
+    heatmap: Option<(&'a Path, chrono_tz::Tz)>,
+    html: Option<&'a Path>,
+    timeline: Option<&'a Path>,
+    /// `--xlsx`: every artifact in one Excel workbook
+    xlsx: Option<&'a Path>,
+    /// `--summary`: per-domain, per-day visit counts
+    summary: Option<&'a Path>,
+    columns: Option<&'a [String]>,
+    date_fmt: &'a str,
+}
+
+impl<'a> ScanSettings<'a> {
+    /// Default settings: all artifact types, no optional passes.
+    fn new(date_fmt: &'a str) -> Self {
+        Self {
+            user: None,
+            parquet_dir: None,
+            arrow_dir: None,
+            artifact_filter: parse_artifact_filter(&None),
+            scan_options: scanner::ScanOptions::default(),
+            ios_backup: false,
+            referrer_window: None,
+            last: None,
+            resume_from: None,
+            date_range: None,
+            dedup: false,
+            dedup_visits: false,
+            global_dedup: false,
+            exclude_prefetch: false,
+            exclude_hidden: false,
+            include_orphans: false,
+            canonicalizer: None,
+            profiles: &[],
+            split_rows: 0,
+            threads: 0,
+            progress: false,
+            formats: vec![output::RecordFormat::Csv],
+            anonymizer: None,
+            redactor: None,
+            max_value_len: truncate::DEFAULT_MAX_VALUE_LEN,
+            heatmap: None,
+            html: None,
+            timeline: None,
+            xlsx: None,
+            summary: None,
+            columns: None,
+            date_fmt,
+        }
+    }
+}
+
+fn cmd_scan(dir: &Path, output_dir: &Path, opts: &ScanSettings) -> Result<ScanStats> {
     if !dir.exists() {
         anyhow::bail!("Directory not found: {}", dir.display());
     }
+    let mut stats = ScanStats::new("scan", dir);
+
+    let date_fmt = opts.date_fmt;
+
+    if let Some(columns) = opts.columns {
+        output::check_columns(opts.artifact_filter.iter().copied(), columns)?;
+        for artifact in ArtifactType::ALL {
+            if opts.artifact_filter.contains(&artifact)
+                && output::Projection::matching(artifact, columns).is_none()
+            {
+                info!(
+                    "--columns: none apply to {}; writing all of its columns",
+                    artifact.display_name()
+                );
+            }
+        }
+    }
+
+    // Archived triage bundles are unpacked to a temp tree with their internal paths;
+    // source columns name the member inside the archive instead of the temp copy
+    let unpacked;
+    let mut bundle = None;
+    let dir = if archive::is_archive(dir) {
+        unpacked = browsers::make_temp_dir()?;
+        let count = archive::stage(dir, unpacked.path(), archive::DEFAULT_MAX_UNPACKED_BYTES)?;
+        info!(
+            "Archive: unpacked {} file(s) from {} to {}",
+            count,
+            dir.display(),
+            unpacked.path().display()
+        );
+        bundle = Some((dir, unpacked.path()));
+        unpacked.path()
+    } else {
+        dir
+    };
+
+    // Hashed iOS backup files are restored under their real names first, then scanned
+    let restored;
+    let dir = if opts.ios_backup {
+        restored = output_dir.join("ios_backup");
+        let count = ios_backup::stage(dir, &restored)?;
+        info!(
+            "iOS backup: restored {} browser file(s) listed in Manifest.db to {}",
+            count,
+            restored.display()
+        );
+        restored.as_path()
+    } else {
+        dir
+    };
 
     info!("Scanning for browser artifacts in {}", dir.display());
 
-    let artifacts = scanner::scan(dir);
+    let progress = TerminalProgress::new(opts.progress);
+    let mut artifacts = scanner::scan_with_progress(dir, &opts.scan_options, &progress);
+
+    if !opts.profiles.is_empty() {
+        let skipped = scanner::retain_profiles(&mut artifacts, opts.profiles);
+        info!(
+            "--profile: skipping {} artifact(s) from other profiles",
+            skipped
+        );
+    }
 
     if artifacts.is_empty() {
+        progress.finish();
         warn!("No browser artifacts found in {}", dir.display());
-        return Ok(());
+        stats.outcome = Outcome::NoArtifacts;
+        return Ok(stats);
     }
 
     // Count by type
@@ -325,6 +1350,7 @@ fn cmd_scan(
             .entry(a.artifact_type.display_name())
             .or_insert(0usize) += 1;
     }
+    stats.artifacts_found = artifacts.len();
     info!("Found {} artifact(s):", artifacts.len());
     for (atype, count) in &type_counts {
         info!("  {} x {}", count, atype);
@@ -337,233 +1363,307 @@ fn cmd_scan(
         )
     })?;
 
-    let mut total = 0usize;
     let mut errors = 0usize;
+    let mut jobs = Vec::new();
+    // One database backs several artifacts (History: visits, downloads, searches)
+    let mut prechecked: HashMap<String, Option<Skip>> = HashMap::new();
 
     for artifact in &artifacts {
-        if !artifact_filter.contains(&artifact.artifact_type) {
+        if !opts.artifact_filter.contains(&artifact.artifact_type) {
             continue;
         }
+        let skip = *prechecked
+            .entry(artifact.db_path.clone())
+            .or_insert_with(|| {
+                let skip = precheck(Path::new(&artifact.db_path));
+                if skip == Some(Skip::Encrypted) {
+                    stats.encrypted_databases.push(artifact.db_path.clone());
+                }
+                skip
+            });
+        match skip {
+            None => {}
+            Some(Skip::OtherSchema) => continue,
+            Some(Skip::Encrypted) => {
+                errors += 1;
+                continue;
+            }
+        }
 
-        let username = user.unwrap_or(&artifact.username);
-        let db_path = PathBuf::from(&artifact.db_path);
+        // Guest/System profile activity stays unattributed, even with --user
+        let username = if artifact.is_unattributed_profile() {
+            ""
+        } else {
+            opts.user.unwrap_or(&artifact.username)
+        };
+        // Output file names carry the username too, so hash it there as well
+        let label_user = match opts.anonymizer {
+            Some(anon) => anon.hash(username),
+            None => username.to_string(),
+        };
+        // A profile keeps several session files; name their outputs after each one
+        let session_suffix = if artifact.is_session_restore() {
+            let name = Path::new(&artifact.db_path).file_name().unwrap_or_default();
+            format!("_{}", name.to_string_lossy().replace('.', "_"))
+        } else {
+            String::new()
+        };
         let label = format!(
-            "{}_{}_{}{}",
+            "{}_{}_{}{}{}",
             artifact.browser.display_name().replace([' ', '/'], "_"),
             artifact.artifact_type.file_suffix(),
-            username.replace([' ', '/', '\\'], "_"),
+            label_user.replace([' ', '/', '\\'], "_"),
             if artifact.profile_name.is_empty() {
                 String::new()
             } else {
                 format!("_{}", artifact.profile_name)
+            },
+            if artifact.is_archived_history() {
+                "_archived"
+            } else if artifact.is_bookmarks_backup() {
+                "_bak"
+            } else if artifact.is_typed_history() {
+                "_typed"
+            } else if artifact.is_opera_notes() {
+                "_notes"
+            } else if artifact.is_session_restore() {
+                session_suffix.as_str()
+            } else {
+                ""
             }
         );
+        jobs.push((artifact, username, label));
+    }
 
-        match artifact.artifact_type {
-            ArtifactType::History => {
-                let entries = match artifact.browser {
-                    BrowserType::InternetExplorer => {
-                        browsers::webcache::extract(&db_path, username)
-                    }
-                    BrowserType::Firefox => browsers::firefox::extract(&db_path, username),
-                    BrowserType::Safari => browsers::safari::extract(&db_path, username),
-                    _ => browsers::chrome::extract(&db_path, username, Some(artifact.browser)),
-                };
-                match entries {
-                    Ok(entries) => {
-                        let out_file = output_dir.join(format!("{label}.csv"));
-                        let count = output::write_csv(&entries, &out_file, date_fmt)?;
-                        info!("  {} — {} entries -> {}", label, count, out_file.display());
-                        if let Some(pq_dir) = parquet_dir {
-                            let pq_file = pq_dir.join(format!("{label}.parquet"));
-                            output::write_parquet(&entries, &pq_file)?;
-                        }
-                        total += count;
-                    }
-                    Err(e) => {
-                        error!("  {} — FAILED: {}", label, e);
-                        errors += 1;
-                    }
-                }
-            }
-            ArtifactType::Downloads => {
-                let entries = if artifact.browser.is_chromium() {
-                    browsers::chrome_downloads::extract(&db_path, username, Some(artifact.browser))
-                } else if artifact.browser == BrowserType::Firefox {
-                    browsers::firefox_downloads::extract(&db_path, username)
-                } else {
-                    continue;
-                };
-                match entries {
-                    Ok(entries) => {
-                        let out_file = output_dir.join(format!("{label}.csv"));
-                        let count = output::write_downloads_csv(&entries, &out_file, date_fmt)?;
-                        info!("  {} — {} entries -> {}", label, count, out_file.display());
-                        if let Some(pq_dir) = parquet_dir {
-                            let pq_file = pq_dir.join(format!("{label}.parquet"));
-                            output::write_downloads_parquet(&entries, &pq_file)?;
-                        }
-                        total += count;
-                    }
-                    Err(e) => {
-                        error!("  {} — FAILED: {}", label, e);
-                        errors += 1;
-                    }
-                }
-            }
-            ArtifactType::KeywordSearches => {
-                if !artifact.browser.is_chromium() {
-                    continue;
-                }
-                match browsers::chrome_keywords::extract(
-                    &db_path,
-                    username,
-                    Some(artifact.browser),
-                ) {
-                    Ok(entries) => {
-                        let out_file = output_dir.join(format!("{label}.csv"));
-                        let count = output::write_keywords_csv(&entries, &out_file, date_fmt)?;
-                        info!("  {} — {} entries -> {}", label, count, out_file.display());
-                        if let Some(pq_dir) = parquet_dir {
-                            let pq_file = pq_dir.join(format!("{label}.parquet"));
-                            output::write_keywords_parquet(&entries, &pq_file)?;
-                        }
-                        total += count;
-                    }
-                    Err(e) => {
-                        error!("  {} — FAILED: {}", label, e);
-                        errors += 1;
-                    }
-                }
-            }
-            ArtifactType::Cookies => {
-                let entries = if artifact.browser.is_chromium() {
-                    browsers::chrome_cookies::extract(&db_path, username, Some(artifact.browser))
-                } else if artifact.browser == BrowserType::Firefox {
-                    browsers::firefox_cookies::extract(&db_path, username)
-                } else {
-                    continue;
-                };
-                match entries {
-                    Ok(entries) => {
-                        let out_file = output_dir.join(format!("{label}.csv"));
-                        let count = output::write_cookies_csv(&entries, &out_file, date_fmt)?;
-                        info!("  {} — {} entries -> {}", label, count, out_file.display());
-                        if let Some(pq_dir) = parquet_dir {
-                            let pq_file = pq_dir.join(format!("{label}.parquet"));
-                            output::write_cookies_parquet(&entries, &pq_file)?;
-                        }
-                        total += count;
-                    }
-                    Err(e) => {
-                        error!("  {} — FAILED: {}", label, e);
-                        errors += 1;
-                    }
-                }
-            }
-            ArtifactType::Autofill => {
-                let entries = if artifact.browser.is_chromium() {
-                    browsers::chrome_autofill::extract(&db_path, username, Some(artifact.browser))
-                } else if artifact.browser == BrowserType::Firefox {
-                    browsers::firefox_autofill::extract(&db_path, username)
-                } else {
-                    continue;
-                };
-                match entries {
-                    Ok(entries) => {
-                        let out_file = output_dir.join(format!("{label}.csv"));
-                        let count = output::write_autofill_csv(&entries, &out_file, date_fmt)?;
-                        info!("  {} — {} entries -> {}", label, count, out_file.display());
-                        if let Some(pq_dir) = parquet_dir {
-                            let pq_file = pq_dir.join(format!("{label}.parquet"));
-                            output::write_autofill_parquet(&entries, &pq_file)?;
-                        }
-                        total += count;
-                    }
-                    Err(e) => {
-                        error!("  {} — FAILED: {}", label, e);
-                        errors += 1;
-                    }
-                }
+    let extract_opts = ExtractOptions {
+        include_orphans: opts.include_orphans,
+        referrer_window: opts.referrer_window,
+        ..ExtractOptions::default()
+    };
+    // Each extractor reads its own temp copy, so files are extracted in parallel.
+    // Results come back in scan order, keeping the output and later passes deterministic.
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(opts.threads)
+        .build()
+        .context("Failed to start extraction threads")?;
+    // Passes that look across artifacts need every row in memory; without them each
+    // artifact is filtered and written on its extraction thread, then dropped
+    let buffered = opts.dedup
+        || opts.global_dedup
+        || opts.canonicalizer.is_some()
+        || opts.last.is_some()
+        || opts.heatmap.is_some()
+        || opts.html.is_some()
+        || opts.timeline.is_some()
+        || opts.xlsx.is_some()
+        || opts.summary.is_some();
+    let total = jobs.len();
+    let done = AtomicUsize::new(0);
+    progress.artifacts_extracted(0, total);
+    let results: Vec<_> = pool.install(|| {
+        jobs.into_par_iter()
+            .map(|(artifact, username, label)| {
+                let db_path = PathBuf::from(&artifact.db_path);
+                let result = extract::extract_artifact(artifact, &db_path, username, &extract_opts)
+                    .map(|r| {
+                        r.map(|entries| {
+                            let mut entries = postprocess(artifact, &label, entries, opts);
+                            if let Some((archive_path, root)) = bundle {
+                                entries.rename_source_file(|staged| {
+                                    archive::member_name(archive_path, root, staged)
+                                });
+                            }
+                            if buffered {
+                                return Finished::Buffered(entries);
+                            }
+                            let filtered = FilterCounts::apply(&mut entries, opts);
+                            let written =
+                                write_artifact(artifact, &label, &entries, output_dir, opts);
+                            Finished::Written(written, filtered)
+                        })
+                    });
+                progress.artifacts_extracted(done.fetch_add(1, Ordering::Relaxed) + 1, total);
+                (artifact, label, result)
+            })
+            .collect()
+    });
+    progress.finish();
+
+    let mut extracted = Vec::new();
+    let mut streamed = 0usize;
+    let mut filtered = FilterCounts::default();
+    let mut total = 0usize;
+    for (artifact, label, result) in results {
+        match result {
+            Some(Ok(Finished::Buffered(entries))) => extracted.push((artifact, label, entries)),
+            Some(Ok(Finished::Written(written, counts))) => {
+                let written = written?;
+                *stats
+                    .entries_by_type
+                    .entry(artifact.artifact_type.display_name())
+                    .or_default() += written;
+                total += written;
+                filtered.add(counts);
+                streamed += 1;
             }
-            ArtifactType::Bookmarks => {
-                let entries = if artifact.browser.is_chromium() {
-                    browsers::chrome_bookmarks::extract(&db_path, username, Some(artifact.browser))
-                } else if artifact.browser == BrowserType::Firefox {
-                    browsers::firefox_bookmarks::extract(&db_path, username)
-                } else {
-                    continue;
-                };
-                match entries {
-                    Ok(entries) => {
-                        let out_file = output_dir.join(format!("{label}.csv"));
-                        let count = output::write_bookmarks_csv(&entries, &out_file, date_fmt)?;
-                        info!("  {} — {} entries -> {}", label, count, out_file.display());
-                        if let Some(pq_dir) = parquet_dir {
-                            let pq_file = pq_dir.join(format!("{label}.parquet"));
-                            output::write_bookmarks_parquet(&entries, &pq_file)?;
-                        }
-                        total += count;
-                    }
-                    Err(e) => {
-                        error!("  {} — FAILED: {}", label, e);
-                        errors += 1;
-                    }
-                }
+            Some(Err(e)) => {
+                error!("  {} — FAILED: {}", label, e);
+                errors += 1;
             }
-            ArtifactType::LoginData => {
-                let entries = if artifact.browser.is_chromium() {
-                    browsers::chrome_logins::extract(&db_path, username, Some(artifact.browser))
-                } else if artifact.browser == BrowserType::Firefox {
-                    browsers::firefox_logins::extract(&db_path, username)
-                } else {
-                    continue;
-                };
-                match entries {
-                    Ok(entries) => {
-                        let out_file = output_dir.join(format!("{label}.csv"));
-                        let count = output::write_logins_csv(&entries, &out_file, date_fmt)?;
-                        info!("  {} — {} entries -> {}", label, count, out_file.display());
-                        if let Some(pq_dir) = parquet_dir {
-                            let pq_file = pq_dir.join(format!("{label}.parquet"));
-                            output::write_logins_parquet(&entries, &pq_file)?;
-                        }
-                        total += count;
-                    }
-                    Err(e) => {
-                        error!("  {} — FAILED: {}", label, e);
-                        errors += 1;
-                    }
-                }
+            None => {}
+        }
+    }
+    if !buffered {
+        filtered.log(opts, buffered);
+    }
+
+    if opts.dedup {
+        dedup_archived_history(&mut extracted);
+        diff_bookmark_backups(&mut extracted);
+    }
+
+    if opts.global_dedup {
+        let mut seen = GlobalDedup::default();
+        let mut removed = 0;
+        for (artifact, label, entries) in extracted.iter_mut() {
+            let n = entries.retain_unseen(artifact.artifact_type, &mut seen);
+            if n > 0 {
+                info!(
+                    "  {} — {} entries already seen in another file removed",
+                    label, n
+                );
             }
-            ArtifactType::Extensions => {
-                let entries = if artifact.browser.is_chromium() {
-                    browsers::chrome_extensions::extract(&db_path, username, Some(artifact.browser))
-                } else if artifact.browser == BrowserType::Firefox {
-                    browsers::firefox_extensions::extract(&db_path, username)
-                } else {
-                    continue;
-                };
-                match entries {
-                    Ok(entries) => {
-                        let out_file = output_dir.join(format!("{label}.csv"));
-                        let count = output::write_extensions_csv(&entries, &out_file, date_fmt)?;
-                        info!("  {} — {} entries -> {}", label, count, out_file.display());
-                        if let Some(pq_dir) = parquet_dir {
-                            let pq_file = pq_dir.join(format!("{label}.parquet"));
-                            output::write_extensions_parquet(&entries, &pq_file)?;
-                        }
-                        total += count;
-                    }
-                    Err(e) => {
-                        error!("  {} — FAILED: {}", label, e);
-                        errors += 1;
-                    }
-                }
+            removed += n;
+        }
+        info!(
+            "--global-dedup: {} cross-file duplicate(s) removed",
+            removed
+        );
+    }
+
+    if let Some(canonicalizer) = opts.canonicalizer {
+        let mut histories: Vec<&mut Vec<HistoryEntry>> = extracted
+            .iter_mut()
+            .filter(|(a, _, _)| a.artifact_type == ArtifactType::History)
+            .filter_map(|(_, _, e)| match e {
+                Extracted::History(e) => Some(e),
+                _ => None,
+            })
+            .collect();
+        let removed = canonicalizer.dedup(&mut histories);
+        info!(
+            "--canonical-dedup: dropped {} visit(s) to already-seen pages",
+            removed
+        );
+    }
+
+    // Relative windows are anchored to the newest timestamp across the whole scan
+    if let Some(window) = opts.last {
+        match extracted.iter().filter_map(|(_, _, e)| e.latest()).max() {
+            Some(newest) => {
+                let cutoff = newest - window;
+                let removed: usize = extracted
+                    .iter_mut()
+                    .map(|(_, _, e)| e.retain_within(filter::Cutoff::Since(cutoff)))
+                    .sum();
+                info!(
+                    "--last: keeping entries since {} (newest {}); {} older entries dropped",
+                    cutoff.format(date_fmt),
+                    newest.format(date_fmt),
+                    removed
+                );
             }
+            None => warn!("--last: no timestamped entries found"),
+        }
+    }
+
+    if buffered {
+        for (_, _, e) in extracted.iter_mut() {
+            filtered.add(FilterCounts::time_filters(e, opts));
+        }
+        filtered.log(opts, buffered);
+    }
+
+    if let Some((path, tz)) = opts.heatmap {
+        let visits: Vec<HistoryEntry> = extracted
+            .iter()
+            .filter(|(a, _, _)| a.artifact_type == ArtifactType::History)
+            .filter_map(|(_, _, e)| match e {
+                Extracted::History(e) => Some(e.iter().cloned()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        let counted = output::write_activity_heatmap(&visits, path, &tz)?;
+        info!(
+            "Activity heatmap ({}) — {} visits -> {}",
+            tz,
+            counted,
+            path.display()
+        );
+    }
+
+    // Last, so dedup and time filters still see the real URLs
+    if let Some(redactor) = opts.redactor.filter(|_| buffered) {
+        let redacted: usize = extracted
+            .iter_mut()
+            .map(|(_, _, e)| e.redact(redactor))
+            .sum();
+        info!("--redact-pattern: {} row(s) redacted", redacted);
+    }
+
+    // Cross-artifact outputs work on the whole scan at once
+    if opts.html.is_some() || opts.timeline.is_some() || opts.xlsx.is_some() {
+        let mut set = extract::ArtifactSet::default();
+        for (_, _, entries) in &extracted {
+            set.push(entries.clone());
         }
+        if let Some(path) = opts.html {
+            let events = output::write_html_report(&set, path)?;
+            info!(
+                "HTML report — {} timeline events -> {}",
+                events,
+                path.display()
+            );
+        }
+        if let Some(path) = opts.timeline {
+            let events = output::write_timeline_csv(&set, path, date_fmt)?;
+            info!("Timeline — {} events -> {}", events, path.display());
+        }
+        if let Some(path) = opts.xlsx {
+            let rows = output::write_xlsx(&set, path)?;
+            info!("Excel workbook — {} row(s) -> {}", rows, path.display());
+        }
+    }
+
+    if let Some(path) = opts.summary {
+        let visits: Vec<HistoryEntry> = extracted
+            .iter()
+            .filter(|(a, _, _)| a.artifact_type == ArtifactType::History)
+            .filter_map(|(_, _, e)| match e {
+                Extracted::History(e) => Some(e.iter().cloned()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        let rows = output::write_domain_summary_csv(&visits, path, date_fmt)?;
+        info!(
+            "Domain summary — {} domain-day(s) -> {}",
+            rows,
+            path.display()
+        );
     }
 
+    for (artifact, label, entries) in &extracted {
+        let written = write_artifact(artifact, label, entries, output_dir, opts)?;
+        *stats
+            .entries_by_type
+            .entry(artifact.artifact_type.display_name())
+            .or_default() += written;
+        total += written;
+    }
+    let succeeded = extracted.len() + streamed;
+
     info!("");
     info!(
         "Complete: {} total entries extracted from {} artifact(s) ({} errors)",
@@ -571,110 +1671,766 @@ fn cmd_scan(
         artifacts.len(),
         errors
     );
-    Ok(())
+    stats.artifacts_extracted = succeeded;
+    stats.artifacts_failed = errors;
+    stats.entries_written = total;
+    stats.outcome = if errors == 0 {
+        if succeeded == 0 {
+            Outcome::NoArtifacts
+        } else {
+            Outcome::Success
+        }
+    } else if succeeded == 0 {
+        error!("All {} artifact(s) failed to extract", errors);
+        Outcome::AllFailed
+    } else {
+        warn!("{} artifact(s) failed to extract", errors);
+        Outcome::PartialFailure
+    };
+    Ok(stats)
 }
 
-fn cmd_extract(
-    input: &Path,
-    output: Option<&Path>,
-    browser: Option<&str>,
-    user: Option<&str>,
-    parquet_dir: Option<&Path>,
-    date_fmt: &str,
-) -> Result<()> {
-    if !input.exists() {
-        anyhow::bail!("File not found: {}", input.display());
+/// Per-file passes that run right after extraction, on the extraction thread.
+fn postprocess(
+    artifact: &browsers::BrowserArtifact,
+    label: &str,
+    mut entries: Extracted,
+    opts: &ScanSettings,
+) -> Extracted {
+    if opts.dedup_visits {
+        if let Extracted::History(e) = &mut entries {
+            let before = e.len();
+            *e = browsers::dedup_history(std::mem::take(e));
+            let removed = before - e.len();
+            if removed > 0 {
+                info!("  {} — {} duplicate visit(s) removed", label, removed);
+            }
+        }
+    }
+    if opts.exclude_prefetch {
+        if let Extracted::History(e) = &mut entries {
+            let removed = browsers::remove_prefetch(e);
+            if removed > 0 {
+                info!("  {} — {} prefetch visit(s) excluded", label, removed);
+            }
+        }
+    }
+    if opts.exclude_hidden {
+        if let Extracted::History(e) = &mut entries {
+            let removed = browsers::remove_hidden(e);
+            if removed > 0 {
+                info!("  {} — {} hidden visit(s) excluded", label, removed);
+            }
+        }
+    }
+    let capped = entries.cap_values(opts.max_value_len);
+    if capped > 0 {
+        info!("  {} — {} oversized field(s) truncated", label, capped);
+    }
+    if artifact.is_unattributed_profile() {
+        entries.set_browser_profile(&artifact.profile_name);
     }
+    if let Some(anon) = opts.anonymizer {
+        entries.anonymize(anon);
+    }
+    entries
+}
 
-    let username = user.unwrap_or("");
-    let file_name = input.file_name().and_then(|n| n.to_str()).unwrap_or("");
+/// What became of one artifact's entries on its extraction thread.
+enum Finished {
+    /// Kept for the passes across artifacts, written once they have run
+    Buffered(Extracted),
+    /// Filtered and written right away: rows written (a write error fails the scan,
+    /// as it does for buffered output) and rows filtered
+    Written(Result<usize>, FilterCounts),
+}
 
-    info!("Extracting from: {}", input.display());
+/// Rows dropped by the per-entry time filters, and rows redacted.
+#[derive(Debug, Default, Clone, Copy)]
+struct FilterCounts {
+    resume_from: usize,
+    date_range: usize,
+    redacted: usize,
+}
 
-    let entries: Vec<HistoryEntry> = match browser.map(|b| b.to_lowercase()).as_deref() {
-        Some("chrome") | Some("chromium") | Some("edge") | Some("brave") | Some("opera")
-        | Some("vivaldi") | Some("arc") => {
-            let bt = match browser.unwrap().to_lowercase().as_str() {
-                "edge" => BrowserType::EdgeChromium,
-                "brave" => BrowserType::Brave,
-                "opera" => BrowserType::Opera,
-                "vivaldi" => BrowserType::Vivaldi,
-                "chromium" => BrowserType::Chromium,
-                "arc" => BrowserType::Arc,
-                _ => BrowserType::Chrome,
-            };
-            info!("Browser: {} (specified)", bt.display_name());
-            browsers::chrome::extract(input, username, Some(bt))?
+impl FilterCounts {
+    /// `--resume-from` and `--after`/`--before` on one artifact's entries.
+    fn time_filters(entries: &mut Extracted, opts: &ScanSettings) -> Self {
+        FilterCounts {
+            resume_from: opts.resume_from.map_or(0, |cutoff| {
+                entries.retain_within(filter::Cutoff::After(cutoff))
+            }),
+            date_range: opts.date_range.map_or(0, |range| {
+                range.cutoffs().map(|c| entries.retain_within(c)).sum()
+            }),
+            redacted: 0,
         }
-        Some("firefox") => {
-            info!("Browser: Firefox (specified)");
-            browsers::firefox::extract(input, username)?
+    }
+
+    /// The time filters, then `--redact-pattern`, on an artifact that is not buffered.
+    fn apply(entries: &mut Extracted, opts: &ScanSettings) -> Self {
+        let mut counts = Self::time_filters(entries, opts);
+        counts.redacted = opts.redactor.map_or(0, |r| entries.redact(r));
+        counts
+    }
+
+    fn add(&mut self, other: Self) {
+        self.resume_from += other.resume_from;
+        self.date_range += other.date_range;
+        self.redacted += other.redacted;
+    }
+
+    /// `buffered` scans redact later, after the cross-file passes, and log it there.
+    fn log(&self, opts: &ScanSettings, buffered: bool) {
+        if let Some(cutoff) = opts.resume_from {
+            info!(
+                "--resume-from: keeping entries after {}; {} entries dropped",
+                cutoff.format(opts.date_fmt),
+                self.resume_from
+            );
+        }
+        if opts.date_range.is_some() {
+            info!(
+                "--after/--before: {} entries outside the range dropped",
+                self.date_range
+            );
+        }
+        if opts.redactor.is_some() && !buffered {
+            info!("--redact-pattern: {} row(s) redacted", self.redacted);
+        }
+    }
+}
+
+/// Write one artifact's entries to its output files; returns the number written.
+fn write_artifact(
+    artifact: &browsers::BrowserArtifact,
+    label: &str,
+    entries: &Extracted,
+    output_dir: &Path,
+    opts: &ScanSettings,
+) -> Result<usize> {
+    let projection = opts
+        .columns
+        .and_then(|c| output::Projection::matching(artifact.artifact_type, c));
+    let dest = OutputDest {
+        label,
+        output_dir,
+        parquet_dir: opts.parquet_dir,
+        arrow_dir: opts.arrow_dir,
+        split_rows: opts.split_rows,
+        formats: &opts.formats,
+        projection: projection.as_ref(),
+        date_fmt: opts.date_fmt,
+    };
+    write_extracted(entries, &dest)
+}
+
+/// Why a database is left out before any extractor runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Skip {
+    /// Encrypted (SQLite SEE, SQLCipher) or not SQLite at all
+    Encrypted,
+    /// A readable SQLite database without the expected browser schema
+    OtherSchema,
+}
+
+/// Check `db_path` before extraction, logging why it is skipped.
+fn precheck(db_path: &Path) -> Option<Skip> {
+    if let Some(reason) = browsers::signature::encryption_reason(db_path) {
+        error!(
+            "Skipping {}: encrypted or non-SQLite database, cannot extract ({})",
+            db_path.display(),
+            reason
+        );
+        return Some(Skip::Encrypted);
+    }
+    schema_mismatch(db_path).then_some(Skip::OtherSchema)
+}
+
+/// Warn and return true if `db_path` is a SQLite database of some other kind than its
+/// file name suggests (see `browsers::signature`).
+fn schema_mismatch(db_path: &Path) -> bool {
+    match browsers::signature::verify(db_path) {
+        Some(Err(reason)) => {
+            warn!("Skipping {}: {}", db_path.display(), reason);
+            true
         }
-        Some("safari") => {
-            info!("Browser: Safari (specified)");
-            browsers::safari::extract(input, username)?
+        _ => false,
+    }
+}
+
+/// `--canonical-dedup` canonicalizer: `--tracking-params`, then the config file's
+/// `tracking_params`, then the built-in list.
+fn canonicalizer(tracking_params: Option<Vec<String>>) -> Canonicalizer {
+    match tracking_params {
+        Some(params) => Canonicalizer::new(&params),
+        None => Canonicalizer::new(canonical::DEFAULT_TRACKING_PARAMS),
+    }
+}
+
+/// Drop `Archived History` visits that are also present in the live `History` of the
+/// same profile directory.
+fn dedup_archived_history(extracted: &mut [(&browsers::BrowserArtifact, String, Extracted)]) {
+    let profile_dir =
+        |a: &browsers::BrowserArtifact| Path::new(&a.db_path).parent().map(Path::to_path_buf);
+    let mut live: HashMap<_, Vec<HistoryEntry>> = HashMap::new();
+    for (artifact, _, entries) in extracted.iter() {
+        if let Extracted::History(e) = entries {
+            if artifact.browser.is_chromium()
+                && artifact.artifact_type == ArtifactType::History
+                && !artifact.is_archived_history()
+                && !artifact.is_typed_history()
+            {
+                live.entry((artifact.browser, profile_dir(artifact)))
+                    .or_default()
+                    .extend(e.iter().cloned());
+            }
         }
-        Some("ie") | Some("edge-legacy") | Some("webcache") => {
-            info!("Browser: IE/Edge Legacy (specified)");
-            browsers::webcache::extract(input, username)?
+    }
+    for (artifact, label, entries) in extracted.iter_mut() {
+        if let Extracted::History(e) = entries {
+            if !artifact.is_archived_history() {
+                continue;
+            }
+            if let Some(seen) = live.get(&(artifact.browser, profile_dir(artifact))) {
+                let removed = browsers::remove_seen_history(e, seen);
+                if removed > 0 {
+                    info!(
+                        "  {} — {} entries already in live History removed",
+                        label, removed
+                    );
+                }
+            }
         }
-        None => match file_name {
-            "History" => {
-                info!("Browser: Chrome/Chromium (auto-detected from filename)");
-                browsers::chrome::extract(input, username, None)?
+    }
+}
+
+/// Keep only the `Bookmarks.bak` entries that are missing from the live `Bookmarks` of
+/// the same profile directory, flagged as recently removed.
+fn diff_bookmark_backups(extracted: &mut [(&browsers::BrowserArtifact, String, Extracted)]) {
+    let profile_dir =
+        |a: &browsers::BrowserArtifact| Path::new(&a.db_path).parent().map(Path::to_path_buf);
+    let mut live: HashMap<_, Vec<browsers::BookmarkEntry>> = HashMap::new();
+    for (artifact, _, entries) in extracted.iter() {
+        if let Extracted::Bookmarks(e) = entries {
+            if artifact.browser.is_chromium()
+                && !artifact.is_bookmarks_backup()
+                && !artifact.is_opera_notes()
+            {
+                live.entry(profile_dir(artifact))
+                    .or_default()
+                    .extend(e.iter().cloned());
             }
-            "places.sqlite" => {
-                info!("Browser: Firefox (auto-detected from filename)");
-                browsers::firefox::extract(input, username)?
+        }
+    }
+    for (artifact, label, entries) in extracted.iter_mut() {
+        if let Extracted::Bookmarks(e) = entries {
+            if !artifact.is_bookmarks_backup() {
+                continue;
             }
-            "History.db" => {
-                info!("Browser: Safari (auto-detected from filename)");
-                browsers::safari::extract(input, username)?
+            // Without the live file there is nothing to compare against
+            if let Some(current) = live.get(&profile_dir(artifact)) {
+                let removed = browsers::chrome_bookmarks::diff_backup(e, current);
+                info!("  {} — {} bookmark(s) recently removed", label, removed);
             }
-            "WebCacheV01.dat" => {
-                info!("Browser: IE/Edge Legacy (auto-detected from filename)");
-                browsers::webcache::extract(input, username)?
+        }
+    }
+}
+
+/// Write the CSV (and Parquet/Arrow, if requested) for one artifact file.
+fn write_extracted(entries: &Extracted, dest: &OutputDest) -> Result<usize> {
+    match entries {
+        Extracted::History(e) => dest.write(e, output::write_csv, output::write_parquet),
+        Extracted::Downloads(e) => dest.write(
+            e,
+            output::write_downloads_csv,
+            output::write_downloads_parquet,
+        ),
+        Extracted::KeywordSearches(e) => dest.write(
+            e,
+            output::write_keywords_csv,
+            output::write_keywords_parquet,
+        ),
+        Extracted::Cookies(e) => {
+            dest.write(e, output::write_cookies_csv, output::write_cookies_parquet)
+        }
+        Extracted::Autofill(e) => dest.write(
+            e,
+            output::write_autofill_csv,
+            output::write_autofill_parquet,
+        ),
+        Extracted::Bookmarks(e) => dest.write(
+            e,
+            output::write_bookmarks_csv,
+            output::write_bookmarks_parquet,
+        ),
+        Extracted::LoginData(e) => {
+            dest.write(e, output::write_logins_csv, output::write_logins_parquet)
+        }
+        Extracted::Extensions(e) => dest.write(
+            e,
+            output::write_extensions_csv,
+            output::write_extensions_parquet,
+        ),
+        Extracted::BrowserSettings(e) => dest.write(
+            e,
+            output::write_settings_csv,
+            output::write_settings_parquet,
+        ),
+        Extracted::DailyVisits(e) => dest.write(
+            e,
+            output::write_daily_visits_csv,
+            output::write_daily_visits_parquet,
+        ),
+        Extracted::Shortcuts(e) => dest.write(
+            e,
+            output::write_shortcuts_csv,
+            output::write_shortcuts_parquet,
+        ),
+        Extracted::Favicons(e) => dest.write(
+            e,
+            output::write_favicons_csv,
+            output::write_favicons_parquet,
+        ),
+        Extracted::TopSites(e) => dest.write(
+            e,
+            output::write_topsites_csv,
+            output::write_topsites_parquet,
+        ),
+        Extracted::Predictor(e) => dest.write(
+            e,
+            output::write_predictor_csv,
+            output::write_predictor_parquet,
+        ),
+        Extracted::AutofillProfiles(e) => dest.write(
+            e,
+            output::write_autofill_profiles_csv,
+            output::write_autofill_profiles_parquet,
+        ),
+        Extracted::CreditCards(e) => dest.write(
+            e,
+            output::write_credit_cards_csv,
+            output::write_credit_cards_parquet,
+        ),
+    }
+}
+
+/// Where one artifact file's output goes.
+struct OutputDest<'a> {
+    label: &'a str,
+    output_dir: &'a Path,
+    parquet_dir: Option<&'a Path>,
+    arrow_dir: Option<&'a Path>,
+    split_rows: usize,
+    formats: &'a [output::RecordFormat],
+    projection: Option<&'a output::Projection>,
+    date_fmt: &'a str,
+}
+
+impl OutputDest<'_> {
+    fn write<T: output::ArrowRecords>(
+        &self,
+        entries: &[T],
+        write_csv: fn(&[T], &Path, &str, Option<&output::Projection>) -> Result<usize>,
+        write_parquet: fn(&[T], &Path) -> Result<usize>,
+    ) -> Result<usize> {
+        let label = self.label;
+        let mut count = 0;
+        // The CSV writers switch to JSON / JSON Lines by the file extension
+        for format in self.formats {
+            let out_file = self
+                .output_dir
+                .join(format!("{label}.{}", format.extension()));
+            let parts;
+            (count, parts) = output::write_split(entries, &out_file, self.split_rows, |e, p| {
+                write_csv(e, p, self.date_fmt, self.projection)
+            })?;
+            if parts > 1 {
+                info!(
+                    "  {} — {} entries -> {} ({} parts)",
+                    label,
+                    count,
+                    out_file.display(),
+                    parts
+                );
+            } else {
+                info!("  {} — {} entries -> {}", label, count, out_file.display());
             }
-            _ => anyhow::bail!(
-                "Cannot auto-detect browser from filename '{}'. Use --browser to specify.",
-                file_name
-            ),
-        },
-        Some(other) => anyhow::bail!(
-            "Unknown browser '{}'. Valid: chrome, firefox, safari, ie, edge, brave, opera, vivaldi, arc",
-            other
+        }
+        if let Some(pq_dir) = self.parquet_dir {
+            let pq_file = pq_dir.join(format!("{label}.parquet"));
+            write_parquet(entries, &pq_file)?;
+        }
+        if let Some(arrow_dir) = self.arrow_dir {
+            output::write_arrow_ipc(entries, &arrow_dir.join(format!("{label}.arrow")))?;
+        }
+        Ok(count)
+    }
+}
+
+/// Options for `cmd_extract` beyond the input and output paths.
+struct ExtractSettings<'a> {
+    browser: Option<&'a str>,
+    /// History unless set, or the input is a `Web Data` file
+    artifact: Option<ArtifactType>,
+    user: Option<&'a str>,
+    parquet_dir: Option<&'a Path>,
+    arrow_dir: Option<&'a Path>,
+    stdout_format: StdoutFormat,
+    last: Option<chrono::Duration>,
+    date_range: Option<filter::DateRange>,
+    dedup_visits: bool,
+    exclude_prefetch: bool,
+    exclude_hidden: bool,
+    include_orphans: bool,
+    canonicalizer: Option<&'a Canonicalizer>,
+    profiles: &'a [String],
+    split_rows: usize,
+    anonymizer: Option<&'a Anonymizer>,
+    redactor: Option<&'a Redactor>,
+    max_value_len: usize,
+    /// `--summary`: per-domain, per-day visit counts
+    summary: Option<&'a Path>,
+    columns: Option<&'a [String]>,
+    date_fmt: &'a str,
+}
+
+impl<'a> ExtractSettings<'a> {
+    /// Default settings: auto-detect browser, CSV to stdout, no filtering.
+    fn new(date_fmt: &'a str) -> Self {
+        Self {
+            browser: None,
+            artifact: None,
+            user: None,
+            parquet_dir: None,
+            arrow_dir: None,
+            stdout_format: StdoutFormat::Csv,
+            last: None,
+            date_range: None,
+            dedup_visits: false,
+            exclude_prefetch: false,
+            exclude_hidden: false,
+            include_orphans: false,
+            canonicalizer: None,
+            profiles: &[],
+            split_rows: 0,
+            anonymizer: None,
+            redactor: None,
+            max_value_len: truncate::DEFAULT_MAX_VALUE_LEN,
+            summary: None,
+            columns: None,
+            date_fmt,
+        }
+    }
+}
+
+fn cmd_extract(input: &Path, output: Option<&Path>, opts: &ExtractSettings) -> Result<ScanStats> {
+    if !input.exists() {
+        anyhow::bail!("File not found: {}", input.display());
+    }
+    let mut stats = ScanStats::new("extract", input);
+
+    let browser = opts.browser;
+    let date_fmt = opts.date_fmt;
+    let parquet_dir = opts.parquet_dir;
+    let username = opts.user.unwrap_or("");
+    let file_name = input.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let web_data = file_name == "Web Data"
+        || matches!(
+            browser.map(|b| b.to_lowercase()).as_deref(),
+            Some("webdata" | "web data")
+        );
+    let artifact = match opts.artifact {
+        Some(a) => a,
+        None if web_data => ArtifactType::Autofill,
+        None => ArtifactType::History,
+    };
+    if artifact != ArtifactType::History && artifact != ArtifactType::Autofill {
+        anyhow::bail!(
+            "extract supports --artifacts history or autofill, not {}",
+            artifact.display_name()
+        );
+    }
+    if artifact == ArtifactType::Autofill
+        && output.is_none()
+        && opts.stdout_format == StdoutFormat::Csv
+    {
+        anyhow::bail!("Autofill CSV needs -o (or use --stdout jsonl)");
+    }
+    let projection = opts
+        .columns
+        .map(|c| output::Projection::new(artifact, c))
+        .transpose()?;
+
+    if !opts.profiles.is_empty() && !scanner::profile_matches(input, opts.profiles) {
+        warn!(
+            "Skipping {}: not in selected profile(s) {}",
+            input.display(),
+            opts.profiles.join(", ")
+        );
+        stats.outcome = Outcome::NoArtifacts;
+        return Ok(stats);
+    }
+
+    if let Some(reason) = browsers::signature::encryption_reason(input) {
+        anyhow::bail!(
+            "{}: encrypted or non-SQLite database, cannot extract ({})",
+            input.display(),
+            reason
+        );
+    }
+    if schema_mismatch(input) {
+        stats.outcome = Outcome::NoArtifacts;
+        return Ok(stats);
+    }
+
+    stats.artifacts_found = 1;
+    info!("Extracting from: {}", input.display());
+
+    if artifact == ArtifactType::Autofill {
+        return extract_autofill(input, output, opts, projection.as_ref(), stats);
+    }
+
+    let browser_type = browser.map(str::parse::<BrowserType>).transpose()?;
+    let found = match scanner::file_artifact(input, ArtifactType::History, browser_type) {
+        Some(found) => found,
+        None => anyhow::bail!(
+            "Cannot auto-detect browser from filename '{}'. Use --browser to specify.",
+            file_name
         ),
     };
+    info!(
+        "Browser: {} ({})",
+        found.browser.display_name(),
+        if browser_type.is_some() {
+            "specified"
+        } else {
+            "auto-detected from filename"
+        }
+    );
+    let extract_opts = extract::ExtractOptions {
+        include_orphans: opts.include_orphans,
+        ..Default::default()
+    };
+    let mut entries: Vec<HistoryEntry> =
+        match extract::extract_artifact(&found, input, username, &extract_opts) {
+            Some(Ok(Extracted::History(entries))) => entries,
+            Some(Err(e)) => return Err(e),
+            _ => anyhow::bail!(
+                "{} has no history extractor for {}",
+                found.browser.display_name(),
+                input.display()
+            ),
+        };
 
     info!("Extracted {} history entries", entries.len());
 
-    let _count = if let Some(out_path) = output {
-        let c = output::write_csv(&entries, out_path, date_fmt)?;
-        info!("Wrote {} entries to {}", c, out_path.display());
+    if opts.dedup_visits {
+        let before = entries.len();
+        entries = browsers::dedup_history(entries);
+        let removed = before - entries.len();
+        info!("--dedup-visits: dropped {} duplicate visit(s)", removed);
+    }
+
+    if opts.exclude_prefetch {
+        let removed = browsers::remove_prefetch(&mut entries);
+        info!("--exclude-prefetch: dropped {} prefetch visit(s)", removed);
+    }
+
+    if opts.exclude_hidden {
+        let removed = browsers::remove_hidden(&mut entries);
+        info!("--exclude-hidden: dropped {} hidden visit(s)", removed);
+    }
+
+    if let Some(canonicalizer) = opts.canonicalizer {
+        let removed = canonicalizer.dedup(&mut [&mut entries]);
+        info!(
+            "--canonical-dedup: dropped {} visit(s) to already-seen pages",
+            removed
+        );
+    }
+
+    let capped = truncate::cap_all(&mut entries, opts.max_value_len);
+    if capped > 0 {
+        info!("Truncated {} oversized title(s)", capped);
+    }
+
+    if let Some(anon) = opts.anonymizer {
+        anon.apply(&mut entries);
+    }
+
+    if let Some(window) = opts.last {
+        let removed = filter::retain_last(&mut entries, window);
+        info!("--last: dropped {} older entries", removed);
+    }
+    if let Some(range) = opts.date_range {
+        let removed: usize = range
+            .cutoffs()
+            .map(|c| filter::retain_within(&mut entries, c))
+            .sum();
+        info!(
+            "--after/--before: dropped {} entries outside the range",
+            removed
+        );
+    }
+
+    if let Some(redactor) = opts.redactor {
+        let redacted = redactor.apply(&mut entries);
+        info!("--redact-pattern: {} row(s) redacted", redacted);
+    }
+
+    if let Some(path) = opts.summary {
+        let rows = output::write_domain_summary_csv(&entries, path, date_fmt)?;
+        info!(
+            "Domain summary — {} domain-day(s) -> {}",
+            rows,
+            path.display()
+        );
+    }
+
+    let count = if let Some(out_path) = output {
+        let (c, parts) = output::write_split(&entries, out_path, opts.split_rows, |e, p| {
+            output::write_csv(e, p, date_fmt, projection.as_ref())
+        })?;
+        if parts > 1 {
+            info!(
+                "Wrote {} entries to {} parts of {}",
+                c,
+                parts,
+                out_path.display()
+            );
+        } else {
+            info!("Wrote {} entries to {}", c, out_path.display());
+        }
         c
+    } else if opts.stdout_format == StdoutFormat::Jsonl {
+        output::write_jsonl_stdout(&entries)?
     } else {
-        output::write_csv_stdout(&entries, date_fmt)?
+        output::write_csv_stdout(&entries, date_fmt, projection.as_ref())?
     };
 
+    let stem = input
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("webhistory");
     if let Some(pq_dir) = parquet_dir {
-        let stem = input
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("webhistory");
         let pq_file = pq_dir.join(format!("{stem}.parquet"));
         output::write_parquet(&entries, &pq_file)?;
         info!("Parquet: {}", pq_file.display());
     }
+    if let Some(arrow_dir) = opts.arrow_dir {
+        let arrow_file = arrow_dir.join(format!("{stem}.arrow"));
+        output::write_arrow_ipc(&entries, &arrow_file)?;
+        info!("Arrow: {}", arrow_file.display());
+    }
 
-    Ok(())
+    stats.artifacts_extracted = 1;
+    stats.entries_written = count;
+    stats
+        .entries_by_type
+        .insert(ArtifactType::History.display_name(), count);
+    Ok(stats)
 }
 
-fn cmd_carve(input: &Path, output: &Path, date_fmt: &str) -> Result<()> {
-    if !input.exists() {
+/// `extract` for a Chromium `Web Data` file: its saved autofill form entries.
+fn extract_autofill(
+    input: &Path,
+    output: Option<&Path>,
+    opts: &ExtractSettings,
+    projection: Option<&output::Projection>,
+    mut stats: ScanStats,
+) -> Result<ScanStats> {
+    info!("Browser: Chromium Web Data (autofill)");
+    let mut entries = browsers::chrome_autofill::extract(input, opts.user.unwrap_or(""), None)?;
+    info!("Extracted {} autofill entries", entries.len());
+
+    let capped = truncate::cap_all(&mut entries, opts.max_value_len);
+    if capped > 0 {
+        info!("Truncated {} oversized value(s)", capped);
+    }
+    if let Some(anon) = opts.anonymizer {
+        anon.apply(&mut entries);
+    }
+    if let Some(window) = opts.last {
+        let removed = filter::retain_last(&mut entries, window);
+        info!("--last: dropped {} older entries", removed);
+    }
+    if let Some(range) = opts.date_range {
+        let removed: usize = range
+            .cutoffs()
+            .map(|c| filter::retain_within(&mut entries, c))
+            .sum();
+        info!(
+            "--after/--before: dropped {} entries outside the range",
+            removed
+        );
+    }
+
+    let count = match output {
+        Some(out_path) => {
+            let (c, parts) = output::write_split(&entries, out_path, opts.split_rows, |e, p| {
+                output::write_autofill_csv(e, p, opts.date_fmt, projection)
+            })?;
+            if parts > 1 {
+                info!(
+                    "Wrote {} entries to {} parts of {}",
+                    c,
+                    parts,
+                    out_path.display()
+                );
+            } else {
+                info!("Wrote {} entries to {}", c, out_path.display());
+            }
+            c
+        }
+        None => output::write_jsonl_stdout(&entries)?,
+    };
+
+    let stem = input
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("webdata")
+        .replace(' ', "_");
+    if let Some(pq_dir) = opts.parquet_dir {
+        let pq_file = pq_dir.join(format!("{stem}.parquet"));
+        output::write_autofill_parquet(&entries, &pq_file)?;
+        info!("Parquet: {}", pq_file.display());
+    }
+    if let Some(arrow_dir) = opts.arrow_dir {
+        let arrow_file = arrow_dir.join(format!("{stem}.arrow"));
+        output::write_arrow_ipc(&entries, &arrow_file)?;
+        info!("Arrow: {}", arrow_file.display());
+    }
+
+    stats.artifacts_extracted = 1;
+    stats.entries_written = count;
+    stats
+        .entries_by_type
+        .insert(ArtifactType::Autofill.display_name(), count);
+    Ok(stats)
+}
+
+/// Carve deleted entries and write them to `output`. With no output (`--validate-only`),
+/// only the per-source counts are reported.
+fn cmd_carve(
+    input: &Path,
+    output: Option<&Path>,
+    config: &carver::CarveConfig,
+    date_fmt: &str,
+) -> Result<()> {
+    // A deleted places.sqlite is fine as long as its WAL survived
+    if !input.exists() && carver::firefox_wal_only(input).is_none() {
         anyhow::bail!("Path not found: {}", input.display());
     }
 
+    // Firefox WALs without their main database get record-level recovery instead
+    let carve_file = |path: &Path| match carver::firefox_wal_only(path) {
+        Some(wal) => carver::carve_firefox_wal_only(&wal),
+        None => carver::carve_with_stats(path, config),
+    };
+
     let mut all_entries = Vec::new();
+    let mut totals = carver::CarveStats::default();
 
     if input.is_dir() {
         info!("Scanning for browser databases in {}", input.display());
@@ -694,11 +2450,17 @@ fn cmd_carve(input: &Path, output: &Path, date_fmt: &str) -> Result<()> {
                 .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or("");
-            if db_names.contains(&name) {
+            let orphan_wal = name == "places.sqlite-wal"
+                && !entry.path().with_file_name("places.sqlite").exists();
+            if db_names.contains(&name) || orphan_wal {
                 info!("  Carving: {}", entry.path().display());
-                match carver::carve(entry.path()) {
-                    Ok(entries) => {
+                match carve_file(entry.path()) {
+                    Ok((entries, stats)) => {
                         info!("    Recovered {} entries", entries.len());
+                        if output.is_none() {
+                            println!("{}: {}", entry.path().display(), stats);
+                        }
+                        totals += stats;
                         all_entries.extend(entries);
                     }
                     Err(e) => {
@@ -709,9 +2471,19 @@ fn cmd_carve(input: &Path, output: &Path, date_fmt: &str) -> Result<()> {
         }
     } else {
         info!("Carving deleted entries from: {}", input.display());
-        all_entries = carver::carve(input)?;
+        let (entries, stats) = carve_file(input)?;
+        totals = stats;
+        all_entries = entries;
     }
 
+    let output = match output {
+        Some(output) => output,
+        None => {
+            println!("Total: {}", totals);
+            return Ok(());
+        }
+    };
+
     info!(
         "Total recovered: {} unique deleted entries",
         all_entries.len()
@@ -0,0 +1,561 @@
+//! Combining prior CSV exports (`webx merge`).
+//!
+//! Per-machine or per-run exports are read back by header name into the current column
+//! layout, deduplicated on the artifact's identity columns and re-sorted by time (in
+//! UTC, whatever `--tz` each export was written with) into a single CSV.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use log::{debug, warn};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::browsers::{ArtifactType, BrowserType};
+use crate::output;
+
+/// Counts reported after a merge.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MergeStats {
+    pub files: usize,
+    pub rows_read: usize,
+    pub duplicates: usize,
+    pub rows_written: usize,
+}
+
+/// Columns that identify the same record in different exports, together with
+/// `Web Browser` and `User Profile`. The first is the time column rows are sorted by.
+fn key_columns(artifact: ArtifactType) -> &'static [&'static str] {
+    match artifact {
+        ArtifactType::History | ArtifactType::HstsRecords => &["Visit Time", "URL"],
+        ArtifactType::Downloads => &["Start Time", "URL", "Target Path"],
+        ArtifactType::KeywordSearches => &["Visit Time", "Search Term", "URL"],
+        ArtifactType::Cookies => &["Creation Time", "Host", "Name", "Path"],
+        ArtifactType::Autofill => &["First Used", "Field Name", "Value"],
+        ArtifactType::Bookmarks => &["Date Added", "URL", "Folder Path"],
+        ArtifactType::LoginData => &["Date Created", "Origin URL", "Username"],
+        ArtifactType::Extensions => &["Install Time", "Extension ID"],
+        ArtifactType::BrowserSettings => &["Last Modified", "Setting", "Value"],
+        ArtifactType::DailyVisits => &["Day", "URL"],
+        ArtifactType::Shortcuts => &["Last Access Time", "Typed Text", "URL"],
+        ArtifactType::TopSites => &["Rank", "URL", "Day"],
+        ArtifactType::Predictor => &["Typed Text", "URL"],
+        ArtifactType::AutofillProfiles => &["Date Modified", "GUID"],
+        ArtifactType::CreditCards => &["Date Modified", "GUID"],
+        ArtifactType::Favicons => &["Expire Time", "Page URL", "Icon URL"],
+    }
+}
+
+/// Reads exported timestamps back as UTC, resolving the zone each one was written in.
+///
+/// Exports end their times with ` UTC` or the `--tz` zone's abbreviation (`EDT`, `CET`,
+/// or a numeric `+03` where the zone has none). An abbreviation is looked up among the
+/// IANA zones; where it names different offsets in different zones (`CST`, `IST`), the
+/// `prefer` zone decides, and without one the time is left unparsed.
+pub(crate) struct TimeParser<'a> {
+    date_fmt: &'a str,
+    prefer: Option<Tz>,
+    offsets: HashMap<String, Option<FixedOffset>>,
+}
+
+impl<'a> TimeParser<'a> {
+    pub(crate) fn new(date_fmt: &'a str, prefer: Option<Tz>) -> Self {
+        Self {
+            date_fmt,
+            prefer,
+            offsets: HashMap::new(),
+        }
+    }
+
+    /// Parse `value`, or a bare `YYYY-MM-DD` day (taken as UTC midnight). A time with no
+    /// zone suffix is read as UTC.
+    pub(crate) fn parse(&mut self, value: &str) -> Option<DateTime<Utc>> {
+        let parse = |v: &str| NaiveDateTime::parse_from_str(v, self.date_fmt).ok();
+        if let Some(naive) = parse(value) {
+            return Some(naive.and_utc());
+        }
+        if let Some((time, zone)) = value.rsplit_once(' ') {
+            if let Some(naive) = parse(time) {
+                let offset = self.offset(zone, naive)?;
+                return offset
+                    .from_local_datetime(&naive)
+                    .single()
+                    .map(|dt| dt.with_timezone(&Utc));
+            }
+        }
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)
+            .map(|naive| naive.and_utc())
+    }
+
+    /// UTC offset of the zone suffix `zone`, as of the local time `naive`.
+    fn offset(&mut self, zone: &str, naive: NaiveDateTime) -> Option<FixedOffset> {
+        if zone == "UTC" || zone == "GMT" || zone == "Z" {
+            return FixedOffset::east_opt(0);
+        }
+        if let Some(offset) = numeric_offset(zone) {
+            return Some(offset);
+        }
+        if let Some(offset) = self.offsets.get(zone) {
+            return *offset;
+        }
+        let in_zone = |tz: Tz| {
+            let local = tz.from_local_datetime(&naive).earliest()?;
+            (local.format("%Z").to_string() == zone).then(|| local.offset().fix())
+        };
+        let offset = match self.prefer.and_then(in_zone) {
+            Some(offset) => Some(offset),
+            None => {
+                let found: HashSet<FixedOffset> = chrono_tz::TZ_VARIANTS
+                    .iter()
+                    .filter_map(|&tz| in_zone(tz))
+                    .collect();
+                if found.len() > 1 {
+                    warn!(
+                        "merge: zone \"{}\" is ambiguous; pass --tz to say which one the exports used",
+                        zone
+                    );
+                }
+                match found.len() {
+                    1 => found.into_iter().next(),
+                    _ => None,
+                }
+            }
+        };
+        self.offsets.insert(zone.to_string(), offset);
+        offset
+    }
+}
+
+/// `+03`, `-0330` or `+05:30`.
+fn numeric_offset(zone: &str) -> Option<FixedOffset> {
+    let sign = match zone.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let digits: String = zone[1..].chars().filter(|c| *c != ':').collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// The artifact a scan output file is for, from its label: `<Browser>_<artifact>_...`
+/// as `webx scan` names files, or `<artifact>.csv` / `<artifact>_....csv`. The longest
+/// artifact name wins, so `autofill_profiles` isn't read as `autofill`.
+fn labeled_artifact(path: &Path) -> Option<ArtifactType> {
+    let stem = path.file_stem()?.to_str()?;
+    let matches = |rest: &str, artifact: &ArtifactType| {
+        let suffix = artifact.file_suffix();
+        rest == suffix
+            || rest
+                .strip_prefix(suffix)
+                .is_some_and(|r| r.starts_with('_'))
+    };
+    let browsers = [
+        BrowserType::Chrome,
+        BrowserType::EdgeChromium,
+        BrowserType::Brave,
+        BrowserType::Opera,
+        BrowserType::OperaGX,
+        BrowserType::Vivaldi,
+        BrowserType::Yandex,
+        BrowserType::Chromium,
+        BrowserType::Arc,
+        BrowserType::Firefox,
+        BrowserType::TorBrowser,
+        BrowserType::Safari,
+        BrowserType::InternetExplorer,
+    ];
+    let rests = browsers
+        .iter()
+        .filter_map(|b| {
+            let prefix = b.display_name().replace([' ', '/'], "_");
+            stem.strip_prefix(&prefix)?.strip_prefix('_')
+        })
+        .chain(std::iter::once(stem));
+    rests
+        .flat_map(|rest| ArtifactType::ALL.iter().filter(move |a| matches(rest, a)))
+        .max_by_key(|a| a.file_suffix().len())
+        .copied()
+}
+
+/// Expand `inputs` into CSV files. Directories are walked recursively in name order;
+/// files are taken as given.
+fn csv_files(inputs: &[PathBuf]) -> Result<Vec<(PathBuf, bool)>> {
+    let mut files = Vec::new();
+    for input in inputs {
+        if input.is_dir() {
+            let mut found: Vec<_> = walkdir::WalkDir::new(input)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .map(|e| e.into_path())
+                .filter(|p| {
+                    p.extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+                })
+                .map(|p| (p, false))
+                .collect();
+            files.append(&mut found);
+        } else if input.is_file() {
+            files.push((input.clone(), true));
+        } else {
+            anyhow::bail!("Input not found: {}", input.display());
+        }
+    }
+    Ok(files)
+}
+
+/// Merge the `artifact` CSVs found in `inputs` into `output_path`.
+///
+/// CSVs found in directories are taken by their file name label (`Chrome_history_...`;
+/// an `_hsts_` file is never history); unlabeled ones are taken if they have the
+/// artifact's key columns. Files passed explicitly must have the key columns. Columns
+/// are matched by header name, so exports from earlier versions or written with
+/// `--columns` merge too: columns the current export doesn't have are dropped, and ones
+/// an input lacks are left empty. The first occurrence of each record wins. Rows are
+/// sorted by the artifact's time column, parsed with `date_fmt` (the format the exports
+/// were written with) and their zone suffix; rows without a parseable time go last, in
+/// input order. `tz` settles ambiguous zone abbreviations.
+pub fn merge_csv(
+    inputs: &[PathBuf],
+    artifact: ArtifactType,
+    output_path: &Path,
+    date_fmt: &str,
+    tz: Option<Tz>,
+) -> Result<MergeStats> {
+    let (headers, _) = output::artifact_columns(artifact);
+    let keys: Vec<&str> = key_columns(artifact)
+        .iter()
+        .chain(&["Web Browser", "User Profile"])
+        .copied()
+        .collect();
+    let output_canon = output_path.canonicalize().ok();
+    let mut times = TimeParser::new(date_fmt, tz);
+
+    let mut stats = MergeStats::default();
+    let mut seen = HashSet::new();
+    let mut rows: Vec<(Vec<String>, Option<DateTime<Utc>>)> = Vec::new();
+    let mut unparsed = 0;
+    for (path, explicit) in csv_files(inputs)? {
+        if output_canon.is_some() && path.canonicalize().ok() == output_canon {
+            continue;
+        }
+        if !explicit {
+            match labeled_artifact(&path) {
+                Some(a) if a != artifact => {
+                    debug!("merge: skipping {} ({})", path.display(), a.display_name());
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        let mut reader = csv::Reader::from_path(&path)
+            .with_context(|| format!("Failed to open CSV: {}", path.display()))?;
+        let header = reader
+            .headers()
+            .with_context(|| format!("Failed to read CSV header: {}", path.display()))?
+            .clone();
+        let column = |name: &str| header.iter().position(|h| h.trim() == name);
+        // Where each output column comes from in this file
+        let sources: Vec<Option<usize>> = headers.iter().map(|h| column(h)).collect();
+        let key_idx: Vec<usize> = match keys.iter().map(|k| column(k)).collect::<Option<_>>() {
+            Some(idx) => idx,
+            None => {
+                if explicit {
+                    anyhow::bail!(
+                        "{} does not have the {} key columns ({})",
+                        path.display(),
+                        artifact.display_name(),
+                        keys.join(", ")
+                    );
+                }
+                debug!(
+                    "merge: skipping {} (no {} key columns)",
+                    path.display(),
+                    artifact.display_name()
+                );
+                continue;
+            }
+        };
+        stats.files += 1;
+
+        for record in reader.records() {
+            let record =
+                record.with_context(|| format!("Failed to read CSV row: {}", path.display()))?;
+            stats.rows_read += 1;
+            let time_text = &record[key_idx[0]];
+            let time = times.parse(time_text);
+            if time.is_none() && !time_text.is_empty() {
+                unparsed += 1;
+            }
+            // The same moment written in different zones is the same record
+            let mut key: Vec<String> = key_idx.iter().map(|&i| record[i].to_string()).collect();
+            if let Some(t) = time {
+                key[0] = t.to_rfc3339();
+            }
+            if seen.insert(key) {
+                let row = sources
+                    .iter()
+                    .map(|s| s.and_then(|i| record.get(i)).unwrap_or("").to_string())
+                    .collect();
+                rows.push((row, time));
+            } else {
+                stats.duplicates += 1;
+            }
+        }
+    }
+
+    if unparsed > 0 {
+        warn!(
+            "merge: {} \"{}\" value(s) didn't match the date format \"{}\"; those rows are placed last",
+            unparsed, keys[0], date_fmt
+        );
+    }
+
+    rows.sort_by_key(|(_, t)| (t.is_none(), *t));
+
+    output::ensure_parent(output_path)?;
+    let mut wtr = csv::Writer::from_path(output_path)
+        .with_context(|| format!("Failed to create output file: {}", output_path.display()))?;
+    wtr.write_record(headers)?;
+    for (row, _) in &rows {
+        wtr.write_record(row)?;
+    }
+    wtr.flush()?;
+    stats.rows_written = rows.len();
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FMT: &str = "%Y-%m-%d %H:%M:%S";
+
+    fn history_column(name: &str) -> usize {
+        let (headers, _) = output::artifact_columns(ArtifactType::History);
+        headers.iter().position(|h| *h == name).unwrap()
+    }
+
+    fn history_csv(path: &Path, rows: &[(&str, &str, &str)]) {
+        let (headers, _) = output::artifact_columns(ArtifactType::History);
+        let mut wtr = csv::Writer::from_path(path).unwrap();
+        wtr.write_record(headers).unwrap();
+        for (time, url, user) in rows {
+            let mut record = vec![""; headers.len()];
+            record[history_column("Visit Time")] = time;
+            record[history_column("URL")] = url;
+            record[history_column("Web Browser")] = "Chrome";
+            record[history_column("User Profile")] = user;
+            wtr.write_record(&record).unwrap();
+        }
+        wtr.flush().unwrap();
+    }
+
+    #[test]
+    fn test_merge_dedups_and_sorts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let exports = dir.path().join("exports");
+        std::fs::create_dir_all(exports.join("pc2")).unwrap();
+        history_csv(
+            &exports.join("pc1_history.csv"),
+            &[
+                ("2024-01-15 09:00:00", "https://b.example/", "alice"),
+                ("2024-01-15 08:00:00", "https://a.example/", "alice"),
+            ],
+        );
+        history_csv(
+            &exports.join("pc2/history.csv"),
+            &[
+                ("2024-01-15 08:00:00", "https://a.example/", "alice"),
+                ("2024-01-15 08:00:00", "https://a.example/", "bob"),
+                ("not a date", "https://c.example/", "bob"),
+                ("2024-01-14 23:00:00", "https://d.example/", "bob"),
+            ],
+        );
+        // Another artifact's export in the same folder is ignored
+        std::fs::write(exports.join("cookies.csv"), "Creation Time,Host\n1,2\n").unwrap();
+
+        let out = dir.path().join("merged.csv");
+        let stats = merge_csv(
+            std::slice::from_ref(&exports),
+            ArtifactType::History,
+            &out,
+            FMT,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            stats,
+            MergeStats {
+                files: 2,
+                rows_read: 6,
+                duplicates: 1,
+                rows_written: 5,
+            }
+        );
+
+        let mut reader = csv::Reader::from_path(&out).unwrap();
+        let rows: Vec<(String, String)> = reader
+            .records()
+            .map(|r| {
+                let r = r.unwrap();
+                (
+                    r[history_column("URL")].to_string(),
+                    r[history_column("User Profile")].to_string(),
+                )
+            })
+            .collect();
+        let expected = [
+            ("https://d.example/", "bob"),
+            ("https://a.example/", "alice"),
+            ("https://a.example/", "bob"),
+            ("https://b.example/", "alice"),
+            ("https://c.example/", "bob"),
+        ];
+        assert_eq!(rows, expected.map(|(u, p)| (u.to_string(), p.to_string())));
+
+        // An explicitly named file with the wrong header is an error
+        let cookies = exports.join("cookies.csv");
+        assert!(merge_csv(&[cookies], ArtifactType::History, &out, FMT, None).is_err());
+    }
+
+    #[test]
+    fn test_merge_picks_files_by_label() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let scan = dir.path().join("scan");
+        std::fs::create_dir_all(&scan).unwrap();
+        history_csv(
+            &scan.join("Chrome_history_alice_Default.csv"),
+            &[("2024-01-15 08:00:00", "https://a.example/", "alice")],
+        );
+        // HSTS exports share the history header but are not history
+        history_csv(
+            &scan.join("Chrome_hsts_alice_Default.csv"),
+            &[("2024-01-15 09:00:00", "https://hsts.example/", "alice")],
+        );
+
+        let out = dir.path().join("merged.csv");
+        let stats = merge_csv(&[scan], ArtifactType::History, &out, FMT, None).unwrap();
+        assert_eq!((stats.files, stats.rows_written), (1, 1));
+
+        let label = |name: &str| labeled_artifact(Path::new(name));
+        assert_eq!(
+            label("Chrome_hsts_alice_Default.csv"),
+            Some(ArtifactType::HstsRecords)
+        );
+        assert_eq!(
+            label("Edge_Chromium_autofill_profiles_bob.csv"),
+            Some(ArtifactType::AutofillProfiles)
+        );
+        assert_eq!(
+            label("Firefox_history_bob_archived.csv"),
+            Some(ArtifactType::History)
+        );
+        assert_eq!(label("downloads.csv"), Some(ArtifactType::Downloads));
+        assert_eq!(label("pc1_history.csv"), None);
+    }
+
+    #[test]
+    fn test_merge_maps_columns_by_name() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // A --columns export, in its own order and without most columns
+        let projected = dir.path().join("projected.csv");
+        std::fs::write(
+            &projected,
+            "URL,User Profile,Visit Time,Web Browser,Title\n\
+             https://a.example/,alice,2024-01-15 08:00:00,Chrome,A\n",
+        )
+        .unwrap();
+        let full = dir.path().join("full.csv");
+        history_csv(
+            &full,
+            &[
+                ("2024-01-15 08:00:00", "https://a.example/", "alice"),
+                ("2024-01-15 07:00:00", "https://b.example/", "alice"),
+            ],
+        );
+
+        let out = dir.path().join("merged.csv");
+        let stats = merge_csv(&[projected, full], ArtifactType::History, &out, FMT, None).unwrap();
+        assert_eq!((stats.duplicates, stats.rows_written), (1, 2));
+
+        let mut reader = csv::Reader::from_path(&out).unwrap();
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(&rows[0][history_column("URL")], "https://b.example/");
+        assert_eq!(&rows[1][history_column("URL")], "https://a.example/");
+        assert_eq!(&rows[1][history_column("Title")], "A");
+        assert_eq!(&rows[1][history_column("Visit Count")], "");
+    }
+
+    #[test]
+    fn test_merge_normalizes_zones() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let utc = dir.path().join("utc.csv");
+        let eastern = dir.path().join("eastern.csv");
+        history_csv(
+            &utc,
+            &[
+                ("2024-07-15 12:00:00 UTC", "https://a.example/", "alice"),
+                ("2024-07-15 13:30:00 UTC", "https://c.example/", "alice"),
+            ],
+        );
+        // The same visit written with --tz America/New_York, and one between the two
+        history_csv(
+            &eastern,
+            &[
+                ("2024-07-15 08:00:00 EDT", "https://a.example/", "alice"),
+                ("2024-07-15 09:00:00 EDT", "https://b.example/", "alice"),
+            ],
+        );
+
+        let out = dir.path().join("merged.csv");
+        let stats = merge_csv(&[utc, eastern], ArtifactType::History, &out, FMT, None).unwrap();
+        assert_eq!((stats.duplicates, stats.rows_written), (1, 3));
+
+        let mut reader = csv::Reader::from_path(&out).unwrap();
+        let urls: Vec<String> = reader
+            .records()
+            .map(|r| r.unwrap()[history_column("URL")].to_string())
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                "https://a.example/",
+                "https://b.example/",
+                "https://c.example/"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_time_parser_reads_zone_suffix() {
+        let fmt = "%m/%d/%Y %I:%M:%S %p";
+        let mut times = TimeParser::new(fmt, None);
+        let utc = times.parse("01/15/2024 08:00:00 AM").unwrap();
+        assert_eq!(utc.to_rfc3339(), "2024-01-15T08:00:00+00:00");
+        assert_eq!(times.parse("01/15/2024 08:00:00 AM UTC"), Some(utc));
+        assert_eq!(times.parse("01/15/2024 03:00:00 AM EST"), Some(utc));
+        assert_eq!(times.parse("01/15/2024 11:00:00 AM +03"), Some(utc));
+        assert_eq!(times.parse("01/15/2024 01:30:00 PM +05:30"), Some(utc));
+        assert_eq!(times.parse("01/15/2024"), None);
+
+        // IST is India, Ireland and Israel; only --tz can say which
+        assert_eq!(times.parse("01/15/2024 01:30:00 PM IST"), None);
+        let mut india = TimeParser::new(fmt, Some(chrono_tz::Asia::Kolkata));
+        assert_eq!(india.parse("01/15/2024 01:30:00 PM IST"), Some(utc));
+    }
+}
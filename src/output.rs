@@ -1,26 +1,40 @@
 use anyhow::{Context, Result};
 use std::fs::File;
-use std::path::Path;
-use std::sync::Arc;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-use arrow::array::{BooleanBuilder, Int64Builder, StringBuilder, UInt32Builder};
+use arrow::array::{BooleanBuilder, Int64Builder, StringBuilder, UInt32Builder, UInt64Builder};
 use arrow::datatypes::{DataType, Field, Schema};
+use arrow::ipc::writer::FileWriter;
 use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 use parquet::arrow::ArrowWriter;
 use parquet::basic::Compression;
 use parquet::file::properties::WriterProperties;
+use serde::Serialize;
 
 use crate::browsers::{
-    linearize_autofill, linearize_bookmark, linearize_cookie, linearize_download, linearize_entry,
-    linearize_extension, linearize_keyword_search, linearize_login, AutofillEntry, BookmarkEntry,
-    CookieEntry, DownloadEntry, ExtensionEntry, HistoryEntry, KeywordSearchEntry, LoginEntry,
+    linearize_autofill, linearize_autofill_profile, linearize_bookmark, linearize_cookie,
+    linearize_credit_card, linearize_daily_visit, linearize_download, linearize_entry,
+    linearize_extension, linearize_keyword_search, linearize_favicon, linearize_login,
+    linearize_predictor, linearize_setting, linearize_shortcut, linearize_topsite,
+    ArtifactType,
+    AutofillEntry, AutofillProfileEntry, BookmarkEntry, BrowserSettingsEntry, CookieEntry,
+    CreditCardEntry, DailyVisitEntry, DownloadEntry, ExtensionEntry, FaviconEntry, HistoryEntry,
+    KeywordSearchEntry, LoginEntry, PredictorEntry, ShortcutEntry, TopSiteEntry,
 };
+use crate::dedup::DedupKey;
+use crate::extract::ArtifactSet;
+use crate::filter::Timestamped;
+use crate::xlsx::{Cell, Workbook};
 
 // ============================================================================
 // Shared helpers
 // ============================================================================
 
-fn ensure_parent(path: &Path) -> Result<()> {
+pub(crate) fn ensure_parent(path: &Path) -> Result<()> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)
             .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
@@ -28,15 +42,79 @@ fn ensure_parent(path: &Path) -> Result<()> {
     Ok(())
 }
 
-fn fmt_dt(dt: &chrono::DateTime<chrono::Utc>, fmt: &str) -> String {
-    dt.format(fmt).to_string()
+static OUTPUT_TZ: Mutex<Option<Tz>> = Mutex::new(None);
+
+/// Render output timestamps in `tz` (`--tz`) for the rest of the run; `None` keeps UTC.
+pub fn set_output_tz(tz: Option<Tz>) {
+    *OUTPUT_TZ.lock().unwrap_or_else(|e| e.into_inner()) = tz;
+}
+
+/// Format `dt` with `fmt` in the `--tz` zone followed by the zone abbreviation, e.g.
+/// `03/10/2024 09:30:00 PM EDT`, or in UTC with a trailing ` UTC` when no zone was set.
+/// Every CSV, JSON and Parquet writer (and the carver) formats times through this.
+pub fn format_timestamp(dt: &DateTime<Utc>, fmt: &str) -> String {
+    let tz = *OUTPUT_TZ.lock().unwrap_or_else(|e| e.into_inner());
+    format_in_zone(dt, fmt, tz)
+}
+
+fn format_in_zone(dt: &DateTime<Utc>, fmt: &str, tz: Option<Tz>) -> String {
+    match tz {
+        Some(tz) => {
+            let local = dt.with_timezone(&tz);
+            format!("{} {}", local.format(fmt), local.format("%Z"))
+        }
+        None => format!("{} UTC", dt.format(fmt)),
+    }
+}
+
+/// Fixed layout of Parquet/Arrow time columns, which `--date-format` does not change.
+const PARQUET_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+fn fmt_dt(dt: &DateTime<Utc>, fmt: &str) -> String {
+    format_timestamp(dt, fmt)
 }
 
-fn fmt_opt_dt(dt: &Option<chrono::DateTime<chrono::Utc>>, fmt: &str) -> String {
-    dt.map(|d| d.format(fmt).to_string())
+fn fmt_opt_dt(dt: &Option<DateTime<Utc>>, fmt: &str) -> String {
+    dt.map(|d| format_timestamp(&d, fmt))
         .unwrap_or_default()
 }
 
+fn opt_num<N: ToString>(n: Option<N>) -> String {
+    n.map(|n| n.to_string()).unwrap_or_default()
+}
+
+/// Entry types with an Arrow schema, shared by the Parquet and Arrow IPC writers.
+pub trait ArrowRecords: Sized {
+    /// All of `entries` as one record batch.
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch>;
+}
+
+fn write_parquet_records<T: ArrowRecords>(entries: &[T], output_path: &Path) -> Result<usize> {
+    if entries.is_empty() {
+        return Ok(0);
+    }
+    let batch = T::record_batch(entries)?;
+    write_parquet_batch(&batch, batch.schema(), output_path)?;
+    Ok(entries.len())
+}
+
+/// Write `entries` as an Arrow IPC file (Feather v2) with the same schema as the
+/// Parquet output. Returns the number of rows written; nothing is written when empty.
+pub fn write_arrow_ipc<T: ArrowRecords>(entries: &[T], output_path: &Path) -> Result<usize> {
+    if entries.is_empty() {
+        return Ok(0);
+    }
+    let batch = T::record_batch(entries)?;
+    ensure_parent(output_path)?;
+    let file = File::create(output_path)
+        .with_context(|| format!("Failed to create Arrow file: {}", output_path.display()))?;
+    let mut writer =
+        FileWriter::try_new(file, &batch.schema()).context("Failed to create Arrow writer")?;
+    writer.write(&batch).context("Failed to write Arrow batch")?;
+    writer.finish().context("Failed to close Arrow writer")?;
+    Ok(entries.len())
+}
+
 fn write_parquet_batch(
     batch: &RecordBatch,
     schema: Arc<Schema>,
@@ -57,6 +135,289 @@ fn write_parquet_batch(
     Ok(())
 }
 
+/// Path of part `n` (1-based) of a split output: `history.csv` -> `history_0001.csv`.
+pub fn part_path(path: &Path, n: usize) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output");
+    let name = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{stem}_{n:04}.{ext}"),
+        None => format!("{stem}_{n:04}"),
+    };
+    path.with_file_name(name)
+}
+
+/// Write `entries` with one of the per-artifact writers, split into files of at most
+/// `split_rows` data rows (see [`part_path`]), each with its own header row.
+/// `split_rows == 0` writes a single file at `output_path`.
+/// Returns (rows written, files written).
+pub fn write_split<T>(
+    entries: &[T],
+    output_path: &Path,
+    split_rows: usize,
+    write: impl Fn(&[T], &Path) -> Result<usize>,
+) -> Result<(usize, usize)> {
+    if split_rows == 0 {
+        let count = write(entries, output_path)?;
+        return Ok((count, usize::from(count > 0)));
+    }
+    let mut total = 0;
+    let mut parts = 0;
+    for (i, chunk) in entries.chunks(split_rows).enumerate() {
+        total += write(chunk, &part_path(output_path, i + 1))?;
+        parts += 1;
+    }
+    Ok((total, parts))
+}
+
+/// Lowercase alphanumerics only, so "Visit Time", "visit_time" and "VisitTime" match.
+fn squash_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// CSV columns selected with `--columns`, in output order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Projection {
+    headers: &'static [&'static str],
+    indices: Vec<usize>,
+}
+
+impl Projection {
+    /// Select `names` from `artifact`'s CSV headers, in the given order. Names match
+    /// ignoring case, spaces and underscores; an unknown name is an error.
+    pub fn new(artifact: ArtifactType, names: &[String]) -> Result<Self> {
+        let (headers, _) = artifact_columns(artifact);
+        let mut indices = Vec::with_capacity(names.len());
+        for name in names {
+            match headers
+                .iter()
+                .position(|h| squash_name(h) == squash_name(name))
+            {
+                Some(i) => indices.push(i),
+                None => anyhow::bail!(
+                    "Unknown {} column '{}'. Valid columns: {}",
+                    artifact.display_name(),
+                    name,
+                    headers.join(", ")
+                ),
+            }
+        }
+        Ok(Self { headers, indices })
+    }
+
+    /// Select those of `names` that `artifact` has, skipping the rest. `None` if it has
+    /// none of them.
+    pub fn matching(artifact: ArtifactType, names: &[String]) -> Option<Self> {
+        let known: Vec<String> = names
+            .iter()
+            .filter(|n| Self::new(artifact, std::slice::from_ref(n)).is_ok())
+            .cloned()
+            .collect();
+        if known.is_empty() {
+            return None;
+        }
+        Self::new(artifact, &known).ok()
+    }
+
+    /// Header row written for this selection.
+    pub fn headers(&self) -> Vec<&'static str> {
+        self.indices.iter().map(|&i| self.headers[i]).collect()
+    }
+}
+
+/// Check that each of `names` is a column of at least one of `artifacts`.
+pub fn check_columns(
+    artifacts: impl IntoIterator<Item = ArtifactType>,
+    names: &[String],
+) -> Result<()> {
+    let artifacts: Vec<ArtifactType> = artifacts.into_iter().collect();
+    for name in names {
+        if !artifacts
+            .iter()
+            .any(|&a| Projection::new(a, std::slice::from_ref(name)).is_ok())
+        {
+            anyhow::bail!(
+                "Unknown column '{}' (see `webx schema` for each artifact's columns)",
+                name
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Record layout of a per-artifact output file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    Csv,
+    /// One JSON array of record objects
+    Json,
+    /// One record object per line (bulk-indexing friendly)
+    Jsonl,
+}
+
+impl RecordFormat {
+    /// The format a writer uses for `path`: JSON for `.json`, JSON Lines for `.jsonl`,
+    /// CSV for anything else.
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            Some(ext) if ext.eq_ignore_ascii_case("jsonl") => Self::Jsonl,
+            _ => Self::Csv,
+        }
+    }
+
+    /// File extension, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Jsonl => "jsonl",
+        }
+    }
+}
+
+enum SinkOut<W: Write> {
+    Csv(Box<csv::Writer<W>>),
+    Json {
+        writer: std::io::BufWriter<W>,
+        /// Projected header names, the keys of each object
+        keys: Vec<&'static str>,
+        lines: bool,
+        records: usize,
+    },
+}
+
+/// Record writer that writes only the projected columns, if any, as CSV rows or as
+/// JSON / JSON Lines objects keyed by the CSV headers. Values are the CSV cells, so
+/// times follow `--date-format` and `--tz` and NaturalLanguage is included.
+struct RecordSink<W: Write> {
+    out: SinkOut<W>,
+    columns: Option<Vec<usize>>,
+    natural_language: bool,
+}
+
+impl RecordSink<File> {
+    /// Create `path` and start writing in the format its extension selects.
+    fn create(
+        path: &Path,
+        headers: &'static [&'static str],
+        projection: Option<&Projection>,
+    ) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create output file: {}", path.display()))?;
+        Self::new(file, RecordFormat::for_path(path), headers, projection)
+    }
+}
+
+impl<W: Write> RecordSink<W> {
+    /// Start the output; for CSV, write the (projected) header row.
+    fn new(
+        writer: W,
+        format: RecordFormat,
+        headers: &'static [&'static str],
+        projection: Option<&Projection>,
+    ) -> Result<Self> {
+        if let Some(p) = projection {
+            anyhow::ensure!(
+                p.headers == headers,
+                "Column selection is for another artifact"
+            );
+        }
+        let columns = projection.map(|p| p.indices.clone());
+        let keys = match &columns {
+            Some(columns) => columns.iter().map(|&i| headers[i]).collect(),
+            None => headers.to_vec(),
+        };
+        let out = match format {
+            RecordFormat::Csv => {
+                let mut wtr = csv::Writer::from_writer(writer);
+                wtr.write_record(&keys)?;
+                SinkOut::Csv(Box::new(wtr))
+            }
+            RecordFormat::Json | RecordFormat::Jsonl => SinkOut::Json {
+                writer: std::io::BufWriter::new(writer),
+                keys,
+                lines: format == RecordFormat::Jsonl,
+                records: 0,
+            },
+        };
+        Ok(Self {
+            out,
+            columns,
+            natural_language: projection
+                .is_none_or(|p| p.indices.iter().any(|&i| headers[i] == "NaturalLanguage")),
+        })
+    }
+
+    fn write_record<T: AsRef<[u8]>>(&mut self, record: impl IntoIterator<Item = T>) -> Result<()> {
+        let fields: Vec<T> = record.into_iter().collect();
+        let projected: Vec<&[u8]> = match &self.columns {
+            None => fields.iter().map(|f| f.as_ref()).collect(),
+            Some(columns) => columns.iter().map(|&i| fields[i].as_ref()).collect(),
+        };
+        match &mut self.out {
+            SinkOut::Csv(wtr) => wtr.write_record(projected)?,
+            SinkOut::Json {
+                writer,
+                keys,
+                lines,
+                records,
+            } => {
+                let separator: &[u8] = match (*lines, *records) {
+                    (true, _) => b"",
+                    (false, 0) => b"[\n",
+                    (false, _) => b",\n",
+                };
+                writer.write_all(separator)?;
+                writer.write_all(b"{")?;
+                for (i, (key, value)) in keys.iter().zip(projected).enumerate() {
+                    if i > 0 {
+                        writer.write_all(b",")?;
+                    }
+                    serde_json::to_writer(&mut *writer, key)?;
+                    writer.write_all(b":")?;
+                    serde_json::to_writer(&mut *writer, &String::from_utf8_lossy(value))?;
+                }
+                writer.write_all(if *lines { b"}\n" } else { b"}" })?;
+                *records += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// The NaturalLanguage cell: `linearize()`, skipped when that column isn't written.
+    fn natural_language(&self, linearize: impl FnOnce() -> String) -> String {
+        if self.natural_language {
+            linearize()
+        } else {
+            String::new()
+        }
+    }
+
+    /// Close the JSON array, if any, and flush.
+    fn finish(&mut self) -> Result<()> {
+        match &mut self.out {
+            SinkOut::Csv(wtr) => wtr.flush()?,
+            SinkOut::Json {
+                writer,
+                lines,
+                records,
+                ..
+            } => {
+                if !*lines {
+                    writer.write_all(if *records == 0 { b"[]\n" } else { b"\n]\n" })?;
+                }
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
 // ============================================================================
 // History
 // ============================================================================
@@ -77,50 +438,53 @@ const HISTORY_HEADERS: &[&str] = &[
     "History File",
     "Record ID",
     "NaturalLanguage",
+    "Account",
+    "Journey",
+    "Canonical URL",
+    "App ID",
+    "View Time (ms)",
+    "Key Presses",
+    "Hidden",
+    "Visited Link Confirmed",
 ];
 
-pub fn write_csv(entries: &[HistoryEntry], output_path: &Path, date_fmt: &str) -> Result<usize> {
+/// Write history to `output_path` as CSV, or as JSON / JSON Lines objects keyed by the
+/// same headers when it ends in `.json` / `.jsonl` (see [`RecordFormat`]). The other
+/// `write_*_csv` writers choose their format the same way.
+pub fn write_csv(
+    entries: &[HistoryEntry],
+    output_path: &Path,
+    date_fmt: &str,
+    columns: Option<&Projection>,
+) -> Result<usize> {
     if entries.is_empty() {
         return Ok(0);
     }
     ensure_parent(output_path)?;
-    let file = File::create(output_path)
-        .with_context(|| format!("Failed to create output file: {}", output_path.display()))?;
-    let mut wtr = csv::Writer::from_writer(file);
-    wtr.write_record(HISTORY_HEADERS)?;
-    for entry in entries {
-        let nl = linearize_entry(entry);
-        wtr.write_record([
-            &fmt_dt(&entry.visit_time, date_fmt),
-            &entry.url,
-            &entry.title,
-            &entry.visit_count.to_string(),
-            &entry.visited_from,
-            &entry.visit_type,
-            &entry.visit_duration,
-            &entry.web_browser,
-            &entry.user_profile,
-            &entry.browser_profile,
-            &entry.url_length.to_string(),
-            &entry.typed_count.to_string(),
-            &entry.history_file,
-            &entry.record_id.to_string(),
-            &nl,
-        ])?;
-    }
-    wtr.flush()?;
-    Ok(entries.len())
+    let wtr = RecordSink::create(output_path, HISTORY_HEADERS, columns)?;
+    write_history_records(entries, wtr, date_fmt)
 }
 
-pub fn write_csv_stdout(entries: &[HistoryEntry], date_fmt: &str) -> Result<usize> {
+pub fn write_csv_stdout(
+    entries: &[HistoryEntry],
+    date_fmt: &str,
+    columns: Option<&Projection>,
+) -> Result<usize> {
     if entries.is_empty() {
         return Ok(0);
     }
     let stdout = std::io::stdout();
-    let mut wtr = csv::Writer::from_writer(stdout.lock());
-    wtr.write_record(HISTORY_HEADERS)?;
+    let wtr = RecordSink::new(stdout.lock(), RecordFormat::Csv, HISTORY_HEADERS, columns)?;
+    write_history_records(entries, wtr, date_fmt)
+}
+
+fn write_history_records<W: Write>(
+    entries: &[HistoryEntry],
+    mut wtr: RecordSink<W>,
+    date_fmt: &str,
+) -> Result<usize> {
     for entry in entries {
-        let nl = linearize_entry(entry);
+        let nl = wtr.natural_language(|| linearize_entry(entry));
         wtr.write_record([
             &fmt_dt(&entry.visit_time, date_fmt),
             &entry.url,
@@ -137,17 +501,38 @@ pub fn write_csv_stdout(entries: &[HistoryEntry], date_fmt: &str) -> Result<usiz
             &entry.history_file,
             &entry.record_id.to_string(),
             &nl,
+            &entry.account,
+            &entry.journey,
+            &entry.canonical_url,
+            &entry.app_id,
+            &opt_num(entry.view_time_ms),
+            &opt_num(entry.key_presses),
+            &entry.hidden.to_string(),
+            &entry.visited_link_confirmed.to_string(),
         ])?;
     }
-    wtr.flush()?;
+    wtr.finish()?;
     Ok(entries.len())
 }
 
-pub fn write_parquet(entries: &[HistoryEntry], output_path: &Path) -> Result<usize> {
-    if entries.is_empty() {
-        return Ok(0);
+/// Write entries as JSON Lines (one object per line), flushing after every record so
+/// output streams through a pipe (e.g. into `jq`) as it is produced.
+pub fn write_jsonl<T: Serialize, W: Write>(entries: &[T], mut writer: W) -> Result<usize> {
+    for entry in entries {
+        serde_json::to_writer(&mut writer, entry).context("Failed to serialize JSONL record")?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
     }
-    let schema = Arc::new(Schema::new(vec![
+    Ok(entries.len())
+}
+
+pub fn write_jsonl_stdout<T: Serialize>(entries: &[T]) -> Result<usize> {
+    let stdout = std::io::stdout();
+    write_jsonl(entries, stdout.lock())
+}
+
+fn history_parquet_schema() -> Schema {
+    Schema::new(vec![
         Field::new("VisitTime", DataType::Utf8, true),
         Field::new("URL", DataType::Utf8, true),
         Field::new("Title", DataType::Utf8, true),
@@ -163,49 +548,84 @@ pub fn write_parquet(entries: &[HistoryEntry], output_path: &Path) -> Result<usi
         Field::new("HistoryFile", DataType::Utf8, true),
         Field::new("RecordID", DataType::Int64, false),
         Field::new("NaturalLanguage", DataType::Utf8, true),
-    ]));
-    let mut b0 = StringBuilder::new();
-    let mut b1 = StringBuilder::new();
-    let mut b2 = StringBuilder::new();
-    let mut b3 = UInt32Builder::new();
-    let mut b4 = StringBuilder::new();
-    let mut b5 = StringBuilder::new();
-    let mut b6 = StringBuilder::new();
-    let mut b7 = StringBuilder::new();
-    let mut b8 = StringBuilder::new();
-    let mut b9 = StringBuilder::new();
-    let mut b10 = UInt32Builder::new();
-    let mut b11 = UInt32Builder::new();
-    let mut b12 = StringBuilder::new();
-    let mut b13 = Int64Builder::new();
-    let mut b14 = StringBuilder::new();
-    for entry in entries {
-        let nl = linearize_entry(entry);
-        b0.append_value(entry.visit_time.format("%Y-%m-%d %H:%M:%S%.3f").to_string());
-        b1.append_value(&entry.url);
-        b2.append_value(&entry.title);
-        b3.append_value(entry.visit_count);
-        b4.append_value(&entry.visited_from);
-        b5.append_value(&entry.visit_type);
-        b6.append_value(&entry.visit_duration);
-        b7.append_value(&entry.web_browser);
-        b8.append_value(&entry.user_profile);
-        b9.append_value(&entry.browser_profile);
-        b10.append_value(entry.url_length as u32);
-        b11.append_value(entry.typed_count);
-        b12.append_value(&entry.history_file);
-        b13.append_value(entry.record_id);
-        b14.append_value(&nl);
-    }
-    let batch = RecordBatch::try_new(schema.clone(), vec![
-        Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
-        Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
-        Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
-        Arc::new(b9.finish()), Arc::new(b10.finish()), Arc::new(b11.finish()),
-        Arc::new(b12.finish()), Arc::new(b13.finish()), Arc::new(b14.finish()),
-    ])?;
-    write_parquet_batch(&batch, schema, output_path)?;
-    Ok(entries.len())
+        Field::new("Account", DataType::Utf8, true),
+        Field::new("Journey", DataType::Utf8, true),
+        Field::new("CanonicalURL", DataType::Utf8, true),
+        Field::new("AppID", DataType::Utf8, true),
+        Field::new("ViewTimeMs", DataType::UInt64, true),
+        Field::new("KeyPresses", DataType::UInt32, true),
+        Field::new("Hidden", DataType::Boolean, false),
+        Field::new("VisitedLinkConfirmed", DataType::Boolean, false),
+    ])
+}
+
+impl ArrowRecords for HistoryEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(history_parquet_schema());
+        let mut b0 = StringBuilder::new();
+        let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new();
+        let mut b3 = UInt32Builder::new();
+        let mut b4 = StringBuilder::new();
+        let mut b5 = StringBuilder::new();
+        let mut b6 = StringBuilder::new();
+        let mut b7 = StringBuilder::new();
+        let mut b8 = StringBuilder::new();
+        let mut b9 = StringBuilder::new();
+        let mut b10 = UInt32Builder::new();
+        let mut b11 = UInt32Builder::new();
+        let mut b12 = StringBuilder::new();
+        let mut b13 = Int64Builder::new();
+        let mut b14 = StringBuilder::new();
+        let mut b15 = StringBuilder::new();
+        let mut b16 = StringBuilder::new();
+        let mut b17 = StringBuilder::new();
+        let mut b18 = StringBuilder::new();
+        let mut b19 = UInt64Builder::new();
+        let mut b20 = UInt32Builder::new();
+        let mut b21 = BooleanBuilder::new();
+        let mut b22 = BooleanBuilder::new();
+        for entry in entries {
+            let nl = linearize_entry(entry);
+            b0.append_value(fmt_dt(&entry.visit_time, PARQUET_TIME_FORMAT));
+            b1.append_value(&entry.url);
+            b2.append_value(&entry.title);
+            b3.append_value(entry.visit_count);
+            b4.append_value(&entry.visited_from);
+            b5.append_value(&entry.visit_type);
+            b6.append_value(&entry.visit_duration);
+            b7.append_value(&entry.web_browser);
+            b8.append_value(&entry.user_profile);
+            b9.append_value(&entry.browser_profile);
+            b10.append_value(entry.url_length as u32);
+            b11.append_value(entry.typed_count);
+            b12.append_value(&entry.history_file);
+            b13.append_value(entry.record_id);
+            b14.append_value(&nl);
+            b15.append_value(&entry.account);
+            b16.append_value(&entry.journey);
+            b17.append_value(&entry.canonical_url);
+            b18.append_value(&entry.app_id);
+            b19.append_option(entry.view_time_ms);
+            b20.append_option(entry.key_presses);
+            b21.append_value(entry.hidden);
+            b22.append_value(entry.visited_link_confirmed);
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+            Arc::new(b9.finish()), Arc::new(b10.finish()), Arc::new(b11.finish()),
+            Arc::new(b12.finish()), Arc::new(b13.finish()), Arc::new(b14.finish()),
+            Arc::new(b15.finish()), Arc::new(b16.finish()), Arc::new(b17.finish()),
+            Arc::new(b18.finish()), Arc::new(b19.finish()), Arc::new(b20.finish()),
+            Arc::new(b21.finish()), Arc::new(b22.finish()),
+        ])?)
+    }
+}
+
+pub fn write_parquet(entries: &[HistoryEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
 }
 
 // ============================================================================
@@ -217,16 +637,16 @@ const DOWNLOAD_HEADERS: &[&str] = &[
     "Received Bytes", "Total Bytes", "State", "Danger Type", "MIME Type",
     "Referrer", "Tab URL", "Opened", "Web Browser", "User Profile",
     "Browser Profile", "Source File", "Record ID", "NaturalLanguage",
+    "Inferred Referrer", "GUID", "Initiating Extension ID", "Initiating Extension Name",
+    "Interrupt Reason", "URL Chain", "Page Title",
 ];
 
-pub fn write_downloads_csv(entries: &[DownloadEntry], output_path: &Path, date_fmt: &str) -> Result<usize> {
+pub fn write_downloads_csv(entries: &[DownloadEntry], output_path: &Path, date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
     ensure_parent(output_path)?;
-    let file = File::create(output_path)?;
-    let mut wtr = csv::Writer::from_writer(file);
-    wtr.write_record(DOWNLOAD_HEADERS)?;
+    let mut wtr = RecordSink::create(output_path, DOWNLOAD_HEADERS, columns)?;
     for e in entries {
-        let nl = linearize_download(e);
+        let nl = wtr.natural_language(|| linearize_download(e));
         wtr.write_record([
             &fmt_dt(&e.start_time, date_fmt),
             &fmt_opt_dt(&e.end_time, date_fmt),
@@ -235,15 +655,16 @@ pub fn write_downloads_csv(entries: &[DownloadEntry], output_path: &Path, date_f
             &e.state, &e.danger_type, &e.mime_type, &e.referrer, &e.tab_url,
             &e.opened.to_string(), &e.web_browser, &e.user_profile,
             &e.browser_profile, &e.source_file, &e.record_id.to_string(), &nl,
+            &e.inferred_referrer, &e.guid, &e.by_ext_id, &e.by_ext_name,
+            &e.interrupt_reason, &e.url_chain, &e.page_title,
         ])?;
     }
-    wtr.flush()?;
+    wtr.finish()?;
     Ok(entries.len())
 }
 
-pub fn write_downloads_parquet(entries: &[DownloadEntry], output_path: &Path) -> Result<usize> {
-    if entries.is_empty() { return Ok(0); }
-    let schema = Arc::new(Schema::new(vec![
+fn downloads_parquet_schema() -> Schema {
+    Schema::new(vec![
         Field::new("StartTime", DataType::Utf8, true),
         Field::new("URL", DataType::Utf8, true),
         Field::new("TargetPath", DataType::Utf8, true),
@@ -255,29 +676,49 @@ pub fn write_downloads_parquet(entries: &[DownloadEntry], output_path: &Path) ->
         Field::new("UserProfile", DataType::Utf8, true),
         Field::new("RecordID", DataType::Int64, false),
         Field::new("NaturalLanguage", DataType::Utf8, true),
-    ]));
-    let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
-    let mut b2 = StringBuilder::new(); let mut b3 = Int64Builder::new();
-    let mut b4 = StringBuilder::new(); let mut b5 = StringBuilder::new();
-    let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
-    let mut b8 = StringBuilder::new(); let mut b9 = Int64Builder::new();
-    let mut b10 = StringBuilder::new();
-    for e in entries {
-        b0.append_value(e.start_time.format("%Y-%m-%d %H:%M:%S%.3f").to_string());
-        b1.append_value(&e.url); b2.append_value(&e.target_path);
-        b3.append_value(e.total_bytes); b4.append_value(&e.state);
-        b5.append_value(&e.danger_type); b6.append_value(&e.mime_type);
-        b7.append_value(&e.web_browser); b8.append_value(&e.user_profile);
-        b9.append_value(e.record_id); b10.append_value(linearize_download(e));
-    }
-    let batch = RecordBatch::try_new(schema.clone(), vec![
-        Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
-        Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
-        Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
-        Arc::new(b9.finish()), Arc::new(b10.finish()),
-    ])?;
-    write_parquet_batch(&batch, schema, output_path)?;
-    Ok(entries.len())
+        Field::new("GUID", DataType::Utf8, true),
+        Field::new("InitiatingExtensionID", DataType::Utf8, true),
+        Field::new("InterruptReason", DataType::Utf8, true),
+        Field::new("URLChain", DataType::Utf8, true),
+        Field::new("PageTitle", DataType::Utf8, true),
+    ])
+}
+
+impl ArrowRecords for DownloadEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(downloads_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = Int64Builder::new();
+        let mut b4 = StringBuilder::new(); let mut b5 = StringBuilder::new();
+        let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
+        let mut b8 = StringBuilder::new(); let mut b9 = Int64Builder::new();
+        let mut b10 = StringBuilder::new(); let mut b11 = StringBuilder::new();
+        let mut b12 = StringBuilder::new(); let mut b13 = StringBuilder::new();
+        let mut b14 = StringBuilder::new(); let mut b15 = StringBuilder::new();
+        for e in entries {
+            b0.append_value(fmt_dt(&e.start_time, PARQUET_TIME_FORMAT));
+            b1.append_value(&e.url); b2.append_value(&e.target_path);
+            b3.append_value(e.total_bytes); b4.append_value(&e.state);
+            b5.append_value(&e.danger_type); b6.append_value(&e.mime_type);
+            b7.append_value(&e.web_browser); b8.append_value(&e.user_profile);
+            b9.append_value(e.record_id); b10.append_value(linearize_download(e));
+            b11.append_value(&e.guid); b12.append_value(&e.by_ext_id);
+            b13.append_value(&e.interrupt_reason); b14.append_value(&e.url_chain);
+            b15.append_value(&e.page_title);
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+            Arc::new(b9.finish()), Arc::new(b10.finish()), Arc::new(b11.finish()),
+            Arc::new(b12.finish()), Arc::new(b13.finish()), Arc::new(b14.finish()),
+            Arc::new(b15.finish()),
+        ])?)
+    }
+}
+
+pub fn write_downloads_parquet(entries: &[DownloadEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
 }
 
 // ============================================================================
@@ -290,14 +731,12 @@ const KEYWORD_HEADERS: &[&str] = &[
     "Keyword ID", "URL ID", "NaturalLanguage",
 ];
 
-pub fn write_keywords_csv(entries: &[KeywordSearchEntry], output_path: &Path, date_fmt: &str) -> Result<usize> {
+pub fn write_keywords_csv(entries: &[KeywordSearchEntry], output_path: &Path, date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
     ensure_parent(output_path)?;
-    let file = File::create(output_path)?;
-    let mut wtr = csv::Writer::from_writer(file);
-    wtr.write_record(KEYWORD_HEADERS)?;
+    let mut wtr = RecordSink::create(output_path, KEYWORD_HEADERS, columns)?;
     for e in entries {
-        let nl = linearize_keyword_search(e);
+        let nl = wtr.natural_language(|| linearize_keyword_search(e));
         wtr.write_record([
             &fmt_opt_dt(&e.visit_time, date_fmt),
             &e.search_term, &e.normalized_term, &e.url, &e.title,
@@ -306,7 +745,7 @@ pub fn write_keywords_csv(entries: &[KeywordSearchEntry], output_path: &Path, da
             &e.url_id.to_string(), &nl,
         ])?;
     }
-    wtr.flush()?;
+    wtr.finish()?;
     Ok(entries.len())
 }
 
@@ -320,16 +759,15 @@ const COOKIE_HEADERS: &[&str] = &[
     "Secure", "HttpOnly", "Persistent", "SameSite",
     "Web Browser", "User Profile", "Browser Profile", "Source File",
     "Record ID", "NaturalLanguage",
+    "Visited Host", "First Party",
 ];
 
-pub fn write_cookies_csv(entries: &[CookieEntry], output_path: &Path, date_fmt: &str) -> Result<usize> {
+pub fn write_cookies_csv(entries: &[CookieEntry], output_path: &Path, date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
     ensure_parent(output_path)?;
-    let file = File::create(output_path)?;
-    let mut wtr = csv::Writer::from_writer(file);
-    wtr.write_record(COOKIE_HEADERS)?;
+    let mut wtr = RecordSink::create(output_path, COOKIE_HEADERS, columns)?;
     for e in entries {
-        let nl = linearize_cookie(e);
+        let nl = wtr.natural_language(|| linearize_cookie(e));
         wtr.write_record([
             &fmt_dt(&e.creation_time, date_fmt),
             &fmt_opt_dt(&e.expiry_time, date_fmt), &fmt_opt_dt(&e.last_access_time, date_fmt),
@@ -338,9 +776,10 @@ pub fn write_cookies_csv(entries: &[CookieEntry], output_path: &Path, date_fmt:
             &e.is_persistent.to_string(), &e.same_site,
             &e.web_browser, &e.user_profile, &e.browser_profile,
             &e.source_file, &e.record_id.to_string(), &nl,
+            &e.visited_host.to_string(), &e.first_party.to_string(),
         ])?;
     }
-    wtr.flush()?;
+    wtr.finish()?;
     Ok(entries.len())
 }
 
@@ -354,14 +793,12 @@ const AUTOFILL_HEADERS: &[&str] = &[
     "Record ID", "NaturalLanguage",
 ];
 
-pub fn write_autofill_csv(entries: &[AutofillEntry], output_path: &Path, date_fmt: &str) -> Result<usize> {
+pub fn write_autofill_csv(entries: &[AutofillEntry], output_path: &Path, date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
     ensure_parent(output_path)?;
-    let file = File::create(output_path)?;
-    let mut wtr = csv::Writer::from_writer(file);
-    wtr.write_record(AUTOFILL_HEADERS)?;
+    let mut wtr = RecordSink::create(output_path, AUTOFILL_HEADERS, columns)?;
     for e in entries {
-        let nl = linearize_autofill(e);
+        let nl = wtr.natural_language(|| linearize_autofill(e));
         wtr.write_record([
             &fmt_opt_dt(&e.first_used, date_fmt), &fmt_opt_dt(&e.last_used, date_fmt),
             &e.field_name, &e.value, &e.times_used.to_string(),
@@ -369,7 +806,7 @@ pub fn write_autofill_csv(entries: &[AutofillEntry], output_path: &Path, date_fm
             &e.source_file, &e.record_id.to_string(), &nl,
         ])?;
     }
-    wtr.flush()?;
+    wtr.finish()?;
     Ok(entries.len())
 }
 
@@ -381,25 +818,27 @@ const BOOKMARK_HEADERS: &[&str] = &[
     "Date Added", "Date Last Used", "URL", "Title", "Folder Path",
     "Web Browser", "User Profile", "Browser Profile", "Source File",
     "Record ID", "NaturalLanguage",
+    "Date Modified", "Last Visited (Desktop)", "Sync Transaction Version", "Status",
 ];
 
-pub fn write_bookmarks_csv(entries: &[BookmarkEntry], output_path: &Path, date_fmt: &str) -> Result<usize> {
+pub fn write_bookmarks_csv(entries: &[BookmarkEntry], output_path: &Path, date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
     ensure_parent(output_path)?;
-    let file = File::create(output_path)?;
-    let mut wtr = csv::Writer::from_writer(file);
-    wtr.write_record(BOOKMARK_HEADERS)?;
+    let mut wtr = RecordSink::create(output_path, BOOKMARK_HEADERS, columns)?;
     for e in entries {
-        let nl = linearize_bookmark(e);
+        let nl = wtr.natural_language(|| linearize_bookmark(e));
         wtr.write_record([
             &fmt_opt_dt(&e.date_added, date_fmt),
             &fmt_opt_dt(&e.date_last_used, date_fmt),
             &e.url, &e.title, &e.folder_path,
             &e.web_browser, &e.user_profile, &e.browser_profile,
             &e.source_file, &e.record_id.to_string(), &nl,
+            &fmt_opt_dt(&e.date_modified, date_fmt),
+            &fmt_opt_dt(&e.last_visited_desktop, date_fmt),
+            &e.sync_transaction_version, &e.status,
         ])?;
     }
-    wtr.flush()?;
+    wtr.finish()?;
     Ok(entries.len())
 }
 
@@ -412,16 +851,15 @@ const LOGIN_HEADERS: &[&str] = &[
     "Origin URL", "Action URL", "Username",
     "Times Used", "Web Browser", "User Profile",
     "Browser Profile", "Source File", "Record ID", "NaturalLanguage",
+    "Signon Realm", "Federation URL", "Blacklisted By User",
 ];
 
-pub fn write_logins_csv(entries: &[LoginEntry], output_path: &Path, date_fmt: &str) -> Result<usize> {
+pub fn write_logins_csv(entries: &[LoginEntry], output_path: &Path, date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
     ensure_parent(output_path)?;
-    let file = File::create(output_path)?;
-    let mut wtr = csv::Writer::from_writer(file);
-    wtr.write_record(LOGIN_HEADERS)?;
+    let mut wtr = RecordSink::create(output_path, LOGIN_HEADERS, columns)?;
     for e in entries {
-        let nl = linearize_login(e);
+        let nl = wtr.natural_language(|| linearize_login(e));
         wtr.write_record([
             &fmt_opt_dt(&e.date_created, date_fmt), &fmt_opt_dt(&e.date_last_used, date_fmt),
             &fmt_opt_dt(&e.date_password_modified, date_fmt),
@@ -429,9 +867,10 @@ pub fn write_logins_csv(entries: &[LoginEntry], output_path: &Path, date_fmt: &s
             &e.times_used.to_string(),
             &e.web_browser, &e.user_profile, &e.browser_profile,
             &e.source_file, &e.record_id.to_string(), &nl,
+            &e.signon_realm, &e.federation_url, &e.blacklisted_by_user.to_string(),
         ])?;
     }
-    wtr.flush()?;
+    wtr.finish()?;
     Ok(entries.len())
 }
 
@@ -443,260 +882,2205 @@ const EXTENSION_HEADERS: &[&str] = &[
     "Install Time", "Extension ID", "Name", "Version", "Description", "Enabled",
     "Update URL", "Permissions", "Web Browser",
     "User Profile", "Browser Profile", "Source File", "NaturalLanguage",
+    "Has Stored State",
 ];
 
-pub fn write_extensions_csv(entries: &[ExtensionEntry], output_path: &Path, date_fmt: &str) -> Result<usize> {
+pub fn write_extensions_csv(entries: &[ExtensionEntry], output_path: &Path, date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
     ensure_parent(output_path)?;
-    let file = File::create(output_path)?;
-    let mut wtr = csv::Writer::from_writer(file);
-    wtr.write_record(EXTENSION_HEADERS)?;
+    let mut wtr = RecordSink::create(output_path, EXTENSION_HEADERS, columns)?;
     for e in entries {
-        let nl = linearize_extension(e);
+        let nl = wtr.natural_language(|| linearize_extension(e));
         wtr.write_record([
             &fmt_opt_dt(&e.install_time, date_fmt),
             &e.extension_id, &e.name, &e.version, &e.description,
             &e.enabled.to_string(),
             &e.update_url, &e.permissions, &e.web_browser,
             &e.user_profile, &e.browser_profile, &e.source_file, &nl,
+            &e.has_stored_state.to_string(),
         ])?;
     }
-    wtr.flush()?;
+    wtr.finish()?;
     Ok(entries.len())
 }
 
 // ============================================================================
-// Parquet writers for remaining artifact types
+// Browser Settings
 // ============================================================================
 
-pub fn write_keywords_parquet(entries: &[KeywordSearchEntry], output_path: &Path) -> Result<usize> {
+const SETTINGS_HEADERS: &[&str] = &[
+    "Setting", "Value", "Details", "Last Modified", "Web Browser",
+    "User Profile", "Browser Profile", "Source File", "NaturalLanguage",
+];
+
+pub fn write_settings_csv(entries: &[BrowserSettingsEntry], output_path: &Path, date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
-    let schema = Arc::new(Schema::new(vec![
-        Field::new("VisitTime", DataType::Utf8, true),
-        Field::new("SearchTerm", DataType::Utf8, true),
-        Field::new("NormalizedTerm", DataType::Utf8, true),
-        Field::new("URL", DataType::Utf8, true),
-        Field::new("Title", DataType::Utf8, true),
-        Field::new("WebBrowser", DataType::Utf8, true),
-        Field::new("UserProfile", DataType::Utf8, true),
-        Field::new("BrowserProfile", DataType::Utf8, true),
-        Field::new("KeywordID", DataType::Int64, false),
-        Field::new("URLID", DataType::Int64, false),
-        Field::new("NaturalLanguage", DataType::Utf8, true),
-    ]));
-    let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
-    let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
-    let mut b4 = StringBuilder::new(); let mut b5 = StringBuilder::new();
-    let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
-    let mut b8 = Int64Builder::new(); let mut b9 = Int64Builder::new();
-    let mut b10 = StringBuilder::new();
+    ensure_parent(output_path)?;
+    let mut wtr = RecordSink::create(output_path, SETTINGS_HEADERS, columns)?;
     for e in entries {
-        b0.append_value(e.visit_time.map(|d| d.format("%Y-%m-%d %H:%M:%S%.3f").to_string()).unwrap_or_default());
-        b1.append_value(&e.search_term); b2.append_value(&e.normalized_term);
-        b3.append_value(&e.url); b4.append_value(&e.title);
-        b5.append_value(&e.web_browser); b6.append_value(&e.user_profile);
-        b7.append_value(&e.browser_profile);
-        b8.append_value(e.keyword_id); b9.append_value(e.url_id);
-        b10.append_value(linearize_keyword_search(e));
-    }
-    let batch = RecordBatch::try_new(schema.clone(), vec![
-        Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
-        Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
-        Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
-        Arc::new(b9.finish()), Arc::new(b10.finish()),
-    ])?;
-    write_parquet_batch(&batch, schema, output_path)?;
+        let nl = wtr.natural_language(|| linearize_setting(e));
+        wtr.write_record([
+            &e.setting, &e.value, &e.details,
+            &fmt_opt_dt(&e.last_modified, date_fmt),
+            &e.web_browser,
+            &e.user_profile, &e.browser_profile, &e.source_file, &nl,
+        ])?;
+    }
+    wtr.finish()?;
     Ok(entries.len())
 }
 
-pub fn write_cookies_parquet(entries: &[CookieEntry], output_path: &Path) -> Result<usize> {
+// ============================================================================
+// Daily Visits
+// ============================================================================
+
+const DAILY_VISIT_HEADERS: &[&str] = &[
+    "Day", "URL", "Title", "Visit Count", "Web Browser",
+    "User Profile", "Browser Profile", "Source File", "NaturalLanguage",
+];
+
+pub fn write_daily_visits_csv(entries: &[DailyVisitEntry], output_path: &Path, _date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
-    let schema = Arc::new(Schema::new(vec![
-        Field::new("CreationTime", DataType::Utf8, true),
-        Field::new("ExpiryTime", DataType::Utf8, true),
-        Field::new("LastAccessTime", DataType::Utf8, true),
-        Field::new("Host", DataType::Utf8, true),
-        Field::new("Name", DataType::Utf8, true),
-        Field::new("Path", DataType::Utf8, true),
-        Field::new("Secure", DataType::Boolean, false),
-        Field::new("HttpOnly", DataType::Boolean, false),
-        Field::new("SameSite", DataType::Utf8, true),
-        Field::new("WebBrowser", DataType::Utf8, true),
-        Field::new("UserProfile", DataType::Utf8, true),
-        Field::new("RecordID", DataType::Int64, false),
-        Field::new("NaturalLanguage", DataType::Utf8, true),
-    ]));
-    let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
-    let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
-    let mut b4 = StringBuilder::new(); let mut b5 = StringBuilder::new();
-    let mut b6 = BooleanBuilder::new(); let mut b7 = BooleanBuilder::new();
-    let mut b8 = StringBuilder::new(); let mut b9 = StringBuilder::new();
-    let mut b10 = StringBuilder::new(); let mut b11 = Int64Builder::new();
-    let mut b12 = StringBuilder::new();
+    ensure_parent(output_path)?;
+    let mut wtr = RecordSink::create(output_path, DAILY_VISIT_HEADERS, columns)?;
+    // Days carry no time of day, so they're always written as YYYY-MM-DD
     for e in entries {
-        b0.append_value(e.creation_time.format("%Y-%m-%d %H:%M:%S%.3f").to_string());
-        b1.append_value(e.expiry_time.map(|d| d.format("%Y-%m-%d %H:%M:%S%.3f").to_string()).unwrap_or_default());
-        b2.append_value(e.last_access_time.map(|d| d.format("%Y-%m-%d %H:%M:%S%.3f").to_string()).unwrap_or_default());
-        b3.append_value(&e.host); b4.append_value(&e.name);
-        b5.append_value(&e.path);
-        b6.append_value(e.is_secure); b7.append_value(e.is_httponly);
-        b8.append_value(&e.same_site); b9.append_value(&e.web_browser);
-        b10.append_value(&e.user_profile); b11.append_value(e.record_id);
-        b12.append_value(linearize_cookie(e));
-    }
-    let batch = RecordBatch::try_new(schema.clone(), vec![
-        Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
-        Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
-        Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
-        Arc::new(b9.finish()), Arc::new(b10.finish()), Arc::new(b11.finish()),
-        Arc::new(b12.finish()),
-    ])?;
-    write_parquet_batch(&batch, schema, output_path)?;
+        let nl = wtr.natural_language(|| linearize_daily_visit(e));
+        wtr.write_record([
+            &e.day.format("%Y-%m-%d").to_string(), &e.url, &e.title,
+            &e.visit_count.to_string(), &e.web_browser,
+            &e.user_profile, &e.browser_profile, &e.source_file, &nl,
+        ])?;
+    }
+    wtr.finish()?;
     Ok(entries.len())
 }
 
-pub fn write_autofill_parquet(entries: &[AutofillEntry], output_path: &Path) -> Result<usize> {
+// ============================================================================
+// Omnibox Shortcuts
+// ============================================================================
+
+const SHORTCUT_HEADERS: &[&str] = &[
+    "Last Access Time", "Typed Text", "URL", "Contents", "Hits",
+    "Web Browser", "User Profile", "Browser Profile", "Source File", "NaturalLanguage",
+];
+
+pub fn write_shortcuts_csv(entries: &[ShortcutEntry], output_path: &Path, date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
-    let schema = Arc::new(Schema::new(vec![
-        Field::new("FirstUsed", DataType::Utf8, true),
-        Field::new("LastUsed", DataType::Utf8, true),
-        Field::new("FieldName", DataType::Utf8, true),
-        Field::new("Value", DataType::Utf8, true),
-        Field::new("TimesUsed", DataType::UInt32, false),
-        Field::new("WebBrowser", DataType::Utf8, true),
-        Field::new("UserProfile", DataType::Utf8, true),
-        Field::new("RecordID", DataType::Int64, false),
-        Field::new("NaturalLanguage", DataType::Utf8, true),
-    ]));
-    let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
-    let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
-    let mut b4 = UInt32Builder::new(); let mut b5 = StringBuilder::new();
-    let mut b6 = StringBuilder::new(); let mut b7 = Int64Builder::new();
-    let mut b8 = StringBuilder::new();
+    ensure_parent(output_path)?;
+    let mut wtr = RecordSink::create(output_path, SHORTCUT_HEADERS, columns)?;
     for e in entries {
-        b0.append_value(e.first_used.map(|d| d.format("%Y-%m-%d %H:%M:%S%.3f").to_string()).unwrap_or_default());
-        b1.append_value(e.last_used.map(|d| d.format("%Y-%m-%d %H:%M:%S%.3f").to_string()).unwrap_or_default());
-        b2.append_value(&e.field_name); b3.append_value(&e.value);
-        b4.append_value(e.times_used);
-        b5.append_value(&e.web_browser); b6.append_value(&e.user_profile);
-        b7.append_value(e.record_id); b8.append_value(linearize_autofill(e));
-    }
-    let batch = RecordBatch::try_new(schema.clone(), vec![
-        Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
-        Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
-        Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
-    ])?;
-    write_parquet_batch(&batch, schema, output_path)?;
+        let nl = wtr.natural_language(|| linearize_shortcut(e));
+        wtr.write_record([
+            &fmt_opt_dt(&e.last_access_time, date_fmt),
+            &e.text, &e.url, &e.contents, &e.number_of_hits.to_string(),
+            &e.web_browser, &e.user_profile, &e.browser_profile, &e.source_file, &nl,
+        ])?;
+    }
+    wtr.finish()?;
     Ok(entries.len())
 }
 
-pub fn write_bookmarks_parquet(entries: &[BookmarkEntry], output_path: &Path) -> Result<usize> {
+// ============================================================================
+// Favicons
+// ============================================================================
+
+const FAVICON_HEADERS: &[&str] = &[
+    "Expire Time", "Page URL", "Icon URL", "Width",
+    "Web Browser", "User Profile", "Browser Profile", "Source File", "NaturalLanguage",
+];
+
+pub fn write_favicons_csv(entries: &[FaviconEntry], output_path: &Path, date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
-    let schema = Arc::new(Schema::new(vec![
-        Field::new("DateAdded", DataType::Utf8, true),
-        Field::new("DateLastUsed", DataType::Utf8, true),
-        Field::new("URL", DataType::Utf8, true),
-        Field::new("Title", DataType::Utf8, true),
-        Field::new("FolderPath", DataType::Utf8, true),
-        Field::new("WebBrowser", DataType::Utf8, true),
-        Field::new("UserProfile", DataType::Utf8, true),
-        Field::new("RecordID", DataType::Int64, false),
-        Field::new("NaturalLanguage", DataType::Utf8, true),
-    ]));
-    let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
-    let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
-    let mut b4 = StringBuilder::new(); let mut b5 = StringBuilder::new();
-    let mut b6 = StringBuilder::new(); let mut b7 = Int64Builder::new();
-    let mut b8 = StringBuilder::new();
+    ensure_parent(output_path)?;
+    let mut wtr = RecordSink::create(output_path, FAVICON_HEADERS, columns)?;
     for e in entries {
-        b0.append_value(e.date_added.map(|d| d.format("%Y-%m-%d %H:%M:%S%.3f").to_string()).unwrap_or_default());
-        b1.append_value(e.date_last_used.map(|d| d.format("%Y-%m-%d %H:%M:%S%.3f").to_string()).unwrap_or_default());
-        b2.append_value(&e.url); b3.append_value(&e.title);
-        b4.append_value(&e.folder_path); b5.append_value(&e.web_browser);
-        b6.append_value(&e.user_profile); b7.append_value(e.record_id);
-        b8.append_value(linearize_bookmark(e));
-    }
-    let batch = RecordBatch::try_new(schema.clone(), vec![
-        Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
-        Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
-        Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
-    ])?;
-    write_parquet_batch(&batch, schema, output_path)?;
+        let nl = wtr.natural_language(|| linearize_favicon(e));
+        wtr.write_record([
+            &fmt_opt_dt(&e.expire_time, date_fmt),
+            &e.page_url, &e.icon_url, &e.width.to_string(),
+            &e.web_browser, &e.user_profile, &e.browser_profile, &e.source_file, &nl,
+        ])?;
+    }
+    wtr.finish()?;
     Ok(entries.len())
 }
 
-pub fn write_logins_parquet(entries: &[LoginEntry], output_path: &Path) -> Result<usize> {
+// ============================================================================
+// Top Sites
+// ============================================================================
+
+const TOPSITE_HEADERS: &[&str] = &[
+    "Rank", "URL", "Title", "Day", "Visit Count",
+    "Web Browser", "User Profile", "Browser Profile", "Source File", "NaturalLanguage",
+];
+
+pub fn write_topsites_csv(entries: &[TopSiteEntry], output_path: &Path, _date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
-    let schema = Arc::new(Schema::new(vec![
-        Field::new("DateCreated", DataType::Utf8, true),
-        Field::new("DateLastUsed", DataType::Utf8, true),
-        Field::new("OriginURL", DataType::Utf8, true),
-        Field::new("ActionURL", DataType::Utf8, true),
-        Field::new("Username", DataType::Utf8, true),
-        Field::new("TimesUsed", DataType::UInt32, false),
-        Field::new("WebBrowser", DataType::Utf8, true),
-        Field::new("UserProfile", DataType::Utf8, true),
-        Field::new("RecordID", DataType::Int64, false),
-        Field::new("NaturalLanguage", DataType::Utf8, true),
-    ]));
-    let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
-    let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
-    let mut b4 = StringBuilder::new(); let mut b5 = UInt32Builder::new();
-    let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
-    let mut b8 = Int64Builder::new(); let mut b9 = StringBuilder::new();
+    ensure_parent(output_path)?;
+    let mut wtr = RecordSink::create(output_path, TOPSITE_HEADERS, columns)?;
     for e in entries {
-        b0.append_value(e.date_created.map(|d| d.format("%Y-%m-%d %H:%M:%S%.3f").to_string()).unwrap_or_default());
-        b1.append_value(e.date_last_used.map(|d| d.format("%Y-%m-%d %H:%M:%S%.3f").to_string()).unwrap_or_default());
-        b2.append_value(&e.origin_url); b3.append_value(&e.action_url);
-        b4.append_value(&e.username_value);
-        b5.append_value(e.times_used); b6.append_value(&e.web_browser);
-        b7.append_value(&e.user_profile); b8.append_value(e.record_id);
-        b9.append_value(linearize_login(e));
-    }
-    let batch = RecordBatch::try_new(schema.clone(), vec![
-        Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
-        Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
-        Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
-        Arc::new(b9.finish()),
-    ])?;
-    write_parquet_batch(&batch, schema, output_path)?;
+        let nl = wtr.natural_language(|| linearize_topsite(e));
+        let day = e.day.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default();
+        wtr.write_record([
+            &e.url_rank.to_string(), &e.url, &e.title, &day, &opt_num(e.visit_count),
+            &e.web_browser, &e.user_profile, &e.browser_profile, &e.source_file, &nl,
+        ])?;
+    }
+    wtr.finish()?;
     Ok(entries.len())
 }
 
-pub fn write_extensions_parquet(entries: &[ExtensionEntry], output_path: &Path) -> Result<usize> {
+// ============================================================================
+// Network Action Predictor
+// ============================================================================
+
+const PREDICTOR_HEADERS: &[&str] = &[
+    "Typed Text", "URL", "Hits", "Misses", "Consecutive Misses",
+    "Web Browser", "User Profile", "Browser Profile", "Source File", "NaturalLanguage",
+];
+
+pub fn write_predictor_csv(entries: &[PredictorEntry], output_path: &Path, _date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
     if entries.is_empty() { return Ok(0); }
-    let schema = Arc::new(Schema::new(vec![
-        Field::new("InstallTime", DataType::Utf8, true),
-        Field::new("ExtensionID", DataType::Utf8, true),
-        Field::new("Name", DataType::Utf8, true),
-        Field::new("Version", DataType::Utf8, true),
+    ensure_parent(output_path)?;
+    let mut wtr = RecordSink::create(output_path, PREDICTOR_HEADERS, columns)?;
+    // The table keeps no timestamps, so there's no date column to format
+    for e in entries {
+        let nl = wtr.natural_language(|| linearize_predictor(e));
+        wtr.write_record([
+            &e.user_text, &e.url, &e.number_of_hits.to_string(), &e.number_of_misses.to_string(),
+            &opt_num(e.number_of_consecutive_misses),
+            &e.web_browser, &e.user_profile, &e.browser_profile, &e.source_file, &nl,
+        ])?;
+    }
+    wtr.finish()?;
+    Ok(entries.len())
+}
+
+// ============================================================================
+// Autofill profiles (addresses) and credit cards
+// ============================================================================
+
+const AUTOFILL_PROFILE_HEADERS: &[&str] = &[
+    "Last Used", "Date Modified", "Full Name", "Company", "Street Address", "City", "Region",
+    "Zip Code", "Country", "Emails", "Phones", "Times Used", "GUID",
+    "Web Browser", "User Profile", "Browser Profile", "Source File", "NaturalLanguage",
+];
+
+pub fn write_autofill_profiles_csv(entries: &[AutofillProfileEntry], output_path: &Path, date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
+    if entries.is_empty() { return Ok(0); }
+    ensure_parent(output_path)?;
+    let mut wtr = RecordSink::create(output_path, AUTOFILL_PROFILE_HEADERS, columns)?;
+    for e in entries {
+        let nl = wtr.natural_language(|| linearize_autofill_profile(e));
+        wtr.write_record([
+            &fmt_opt_dt(&e.last_used, date_fmt), &fmt_opt_dt(&e.date_modified, date_fmt),
+            &e.full_name, &e.company_name, &e.street_address, &e.city, &e.state,
+            &e.zipcode, &e.country_code, &e.emails, &e.phones, &e.use_count.to_string(), &e.guid,
+            &e.web_browser, &e.user_profile, &e.browser_profile, &e.source_file, &nl,
+        ])?;
+    }
+    wtr.finish()?;
+    Ok(entries.len())
+}
+
+const CREDIT_CARD_HEADERS: &[&str] = &[
+    "Last Used", "Date Modified", "Name on Card", "Network", "Last Four",
+    "Expiration Month", "Expiration Year", "Nickname", "Times Used", "Storage", "GUID",
+    "Web Browser", "User Profile", "Browser Profile", "Source File", "NaturalLanguage",
+];
+
+/// Card metadata only: card numbers and CVVs are never extracted, so there's no column
+/// for them.
+pub fn write_credit_cards_csv(entries: &[CreditCardEntry], output_path: &Path, date_fmt: &str, columns: Option<&Projection>) -> Result<usize> {
+    if entries.is_empty() { return Ok(0); }
+    ensure_parent(output_path)?;
+    let mut wtr = RecordSink::create(output_path, CREDIT_CARD_HEADERS, columns)?;
+    for e in entries {
+        let nl = wtr.natural_language(|| linearize_credit_card(e));
+        wtr.write_record([
+            &fmt_opt_dt(&e.last_used, date_fmt), &fmt_opt_dt(&e.date_modified, date_fmt),
+            &e.name_on_card, &e.network, &e.last_four,
+            &opt_num(e.expiration_month), &opt_num(e.expiration_year), &e.nickname,
+            &e.use_count.to_string(), &e.storage, &e.guid,
+            &e.web_browser, &e.user_profile, &e.browser_profile, &e.source_file, &nl,
+        ])?;
+    }
+    wtr.finish()?;
+    Ok(entries.len())
+}
+
+// ============================================================================
+// Parquet writers for remaining artifact types
+// ============================================================================
+
+fn keywords_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("VisitTime", DataType::Utf8, true),
+        Field::new("SearchTerm", DataType::Utf8, true),
+        Field::new("NormalizedTerm", DataType::Utf8, true),
+        Field::new("URL", DataType::Utf8, true),
+        Field::new("Title", DataType::Utf8, true),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("BrowserProfile", DataType::Utf8, true),
+        Field::new("KeywordID", DataType::Int64, false),
+        Field::new("URLID", DataType::Int64, false),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+    ])
+}
+
+impl ArrowRecords for KeywordSearchEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(keywords_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
+        let mut b4 = StringBuilder::new(); let mut b5 = StringBuilder::new();
+        let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
+        let mut b8 = Int64Builder::new(); let mut b9 = Int64Builder::new();
+        let mut b10 = StringBuilder::new();
+        for e in entries {
+            b0.append_value(fmt_opt_dt(&e.visit_time, PARQUET_TIME_FORMAT));
+            b1.append_value(&e.search_term); b2.append_value(&e.normalized_term);
+            b3.append_value(&e.url); b4.append_value(&e.title);
+            b5.append_value(&e.web_browser); b6.append_value(&e.user_profile);
+            b7.append_value(&e.browser_profile);
+            b8.append_value(e.keyword_id); b9.append_value(e.url_id);
+            b10.append_value(linearize_keyword_search(e));
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+            Arc::new(b9.finish()), Arc::new(b10.finish()),
+        ])?)
+    }
+}
+
+pub fn write_keywords_parquet(entries: &[KeywordSearchEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn cookies_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("CreationTime", DataType::Utf8, true),
+        Field::new("ExpiryTime", DataType::Utf8, true),
+        Field::new("LastAccessTime", DataType::Utf8, true),
+        Field::new("Host", DataType::Utf8, true),
+        Field::new("Name", DataType::Utf8, true),
+        Field::new("Path", DataType::Utf8, true),
+        Field::new("Secure", DataType::Boolean, false),
+        Field::new("HttpOnly", DataType::Boolean, false),
+        Field::new("SameSite", DataType::Utf8, true),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("RecordID", DataType::Int64, false),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+        Field::new("VisitedHost", DataType::Boolean, false),
+        Field::new("FirstParty", DataType::Boolean, false),
+    ])
+}
+
+impl ArrowRecords for CookieEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(cookies_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
+        let mut b4 = StringBuilder::new(); let mut b5 = StringBuilder::new();
+        let mut b6 = BooleanBuilder::new(); let mut b7 = BooleanBuilder::new();
+        let mut b8 = StringBuilder::new(); let mut b9 = StringBuilder::new();
+        let mut b10 = StringBuilder::new(); let mut b11 = Int64Builder::new();
+        let mut b12 = StringBuilder::new();
+        let mut b13 = BooleanBuilder::new(); let mut b14 = BooleanBuilder::new();
+        for e in entries {
+            b0.append_value(fmt_dt(&e.creation_time, PARQUET_TIME_FORMAT));
+            b1.append_value(fmt_opt_dt(&e.expiry_time, PARQUET_TIME_FORMAT));
+            b2.append_value(fmt_opt_dt(&e.last_access_time, PARQUET_TIME_FORMAT));
+            b3.append_value(&e.host); b4.append_value(&e.name);
+            b5.append_value(&e.path);
+            b6.append_value(e.is_secure); b7.append_value(e.is_httponly);
+            b8.append_value(&e.same_site); b9.append_value(&e.web_browser);
+            b10.append_value(&e.user_profile); b11.append_value(e.record_id);
+            b12.append_value(linearize_cookie(e));
+            b13.append_value(e.visited_host); b14.append_value(e.first_party);
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+            Arc::new(b9.finish()), Arc::new(b10.finish()), Arc::new(b11.finish()),
+            Arc::new(b12.finish()), Arc::new(b13.finish()), Arc::new(b14.finish()),
+        ])?)
+    }
+}
+
+pub fn write_cookies_parquet(entries: &[CookieEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn autofill_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("FirstUsed", DataType::Utf8, true),
+        Field::new("LastUsed", DataType::Utf8, true),
+        Field::new("FieldName", DataType::Utf8, true),
+        Field::new("Value", DataType::Utf8, true),
+        Field::new("TimesUsed", DataType::UInt32, false),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("RecordID", DataType::Int64, false),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+    ])
+}
+
+impl ArrowRecords for AutofillEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(autofill_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
+        let mut b4 = UInt32Builder::new(); let mut b5 = StringBuilder::new();
+        let mut b6 = StringBuilder::new(); let mut b7 = Int64Builder::new();
+        let mut b8 = StringBuilder::new();
+        for e in entries {
+            b0.append_value(fmt_opt_dt(&e.first_used, PARQUET_TIME_FORMAT));
+            b1.append_value(fmt_opt_dt(&e.last_used, PARQUET_TIME_FORMAT));
+            b2.append_value(&e.field_name); b3.append_value(&e.value);
+            b4.append_value(e.times_used);
+            b5.append_value(&e.web_browser); b6.append_value(&e.user_profile);
+            b7.append_value(e.record_id); b8.append_value(linearize_autofill(e));
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+        ])?)
+    }
+}
+
+pub fn write_autofill_parquet(entries: &[AutofillEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn bookmarks_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("DateAdded", DataType::Utf8, true),
+        Field::new("DateLastUsed", DataType::Utf8, true),
+        Field::new("URL", DataType::Utf8, true),
+        Field::new("Title", DataType::Utf8, true),
+        Field::new("FolderPath", DataType::Utf8, true),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("RecordID", DataType::Int64, false),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+        Field::new("DateModified", DataType::Utf8, true),
+        Field::new("LastVisitedDesktop", DataType::Utf8, true),
+        Field::new("SyncTransactionVersion", DataType::Utf8, true),
+        Field::new("Status", DataType::Utf8, true),
+    ])
+}
+
+impl ArrowRecords for BookmarkEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(bookmarks_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
+        let mut b4 = StringBuilder::new(); let mut b5 = StringBuilder::new();
+        let mut b6 = StringBuilder::new(); let mut b7 = Int64Builder::new();
+        let mut b8 = StringBuilder::new(); let mut b9 = StringBuilder::new();
+        let mut b10 = StringBuilder::new(); let mut b11 = StringBuilder::new();
+        let mut b12 = StringBuilder::new();
+        for e in entries {
+            b0.append_value(fmt_opt_dt(&e.date_added, PARQUET_TIME_FORMAT));
+            b1.append_value(fmt_opt_dt(&e.date_last_used, PARQUET_TIME_FORMAT));
+            b2.append_value(&e.url); b3.append_value(&e.title);
+            b4.append_value(&e.folder_path); b5.append_value(&e.web_browser);
+            b6.append_value(&e.user_profile); b7.append_value(e.record_id);
+            b8.append_value(linearize_bookmark(e));
+            b9.append_value(fmt_opt_dt(&e.date_modified, PARQUET_TIME_FORMAT));
+            b10.append_value(fmt_opt_dt(&e.last_visited_desktop, PARQUET_TIME_FORMAT));
+            b11.append_value(&e.sync_transaction_version); b12.append_value(&e.status);
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+            Arc::new(b9.finish()), Arc::new(b10.finish()), Arc::new(b11.finish()),
+            Arc::new(b12.finish()),
+        ])?)
+    }
+}
+
+pub fn write_bookmarks_parquet(entries: &[BookmarkEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn logins_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("DateCreated", DataType::Utf8, true),
+        Field::new("DateLastUsed", DataType::Utf8, true),
+        Field::new("OriginURL", DataType::Utf8, true),
+        Field::new("ActionURL", DataType::Utf8, true),
+        Field::new("Username", DataType::Utf8, true),
+        Field::new("TimesUsed", DataType::UInt32, false),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("RecordID", DataType::Int64, false),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+        Field::new("SignonRealm", DataType::Utf8, true),
+        Field::new("FederationURL", DataType::Utf8, true),
+        Field::new("BlacklistedByUser", DataType::Boolean, false),
+    ])
+}
+
+impl ArrowRecords for LoginEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(logins_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
+        let mut b4 = StringBuilder::new(); let mut b5 = UInt32Builder::new();
+        let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
+        let mut b8 = Int64Builder::new(); let mut b9 = StringBuilder::new();
+        let mut b10 = StringBuilder::new(); let mut b11 = StringBuilder::new();
+        let mut b12 = BooleanBuilder::new();
+        for e in entries {
+            b0.append_value(fmt_opt_dt(&e.date_created, PARQUET_TIME_FORMAT));
+            b1.append_value(fmt_opt_dt(&e.date_last_used, PARQUET_TIME_FORMAT));
+            b2.append_value(&e.origin_url); b3.append_value(&e.action_url);
+            b4.append_value(&e.username_value);
+            b5.append_value(e.times_used); b6.append_value(&e.web_browser);
+            b7.append_value(&e.user_profile); b8.append_value(e.record_id);
+            b9.append_value(linearize_login(e));
+            b10.append_value(&e.signon_realm); b11.append_value(&e.federation_url);
+            b12.append_value(e.blacklisted_by_user);
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+            Arc::new(b9.finish()), Arc::new(b10.finish()), Arc::new(b11.finish()),
+            Arc::new(b12.finish()),
+        ])?)
+    }
+}
+
+pub fn write_logins_parquet(entries: &[LoginEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn extensions_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("InstallTime", DataType::Utf8, true),
+        Field::new("ExtensionID", DataType::Utf8, true),
+        Field::new("Name", DataType::Utf8, true),
+        Field::new("Version", DataType::Utf8, true),
         Field::new("Description", DataType::Utf8, true),
         Field::new("Enabled", DataType::Boolean, false),
         Field::new("Permissions", DataType::Utf8, true),
         Field::new("WebBrowser", DataType::Utf8, true),
         Field::new("UserProfile", DataType::Utf8, true),
         Field::new("NaturalLanguage", DataType::Utf8, true),
-    ]));
-    let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
-    let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
-    let mut b4 = StringBuilder::new(); let mut b5 = BooleanBuilder::new();
-    let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
-    let mut b8 = StringBuilder::new(); let mut b9 = StringBuilder::new();
+        Field::new("HasStoredState", DataType::Boolean, false),
+    ])
+}
+
+impl ArrowRecords for ExtensionEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(extensions_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
+        let mut b4 = StringBuilder::new(); let mut b5 = BooleanBuilder::new();
+        let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
+        let mut b8 = StringBuilder::new(); let mut b9 = StringBuilder::new();
+        let mut b10 = BooleanBuilder::new();
+        for e in entries {
+            b0.append_value(fmt_opt_dt(&e.install_time, PARQUET_TIME_FORMAT));
+            b1.append_value(&e.extension_id); b2.append_value(&e.name);
+            b3.append_value(&e.version); b4.append_value(&e.description);
+            b5.append_value(e.enabled);
+            b6.append_value(&e.permissions); b7.append_value(&e.web_browser);
+            b8.append_value(&e.user_profile); b9.append_value(linearize_extension(e));
+            b10.append_value(e.has_stored_state);
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+            Arc::new(b9.finish()), Arc::new(b10.finish()),
+        ])?)
+    }
+}
+
+pub fn write_extensions_parquet(entries: &[ExtensionEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn settings_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("Setting", DataType::Utf8, true),
+        Field::new("Value", DataType::Utf8, true),
+        Field::new("Details", DataType::Utf8, true),
+        Field::new("LastModified", DataType::Utf8, true),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+    ])
+}
+
+impl ArrowRecords for BrowserSettingsEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(settings_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
+        let mut b4 = StringBuilder::new(); let mut b5 = StringBuilder::new();
+        let mut b6 = StringBuilder::new();
+        for e in entries {
+            b0.append_value(&e.setting); b1.append_value(&e.value);
+            b2.append_value(&e.details);
+            b3.append_value(fmt_opt_dt(&e.last_modified, PARQUET_TIME_FORMAT));
+            b4.append_value(&e.web_browser); b5.append_value(&e.user_profile);
+            b6.append_value(linearize_setting(e));
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()),
+        ])?)
+    }
+}
+
+pub fn write_settings_parquet(entries: &[BrowserSettingsEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn daily_visits_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("Day", DataType::Utf8, true),
+        Field::new("URL", DataType::Utf8, true),
+        Field::new("Title", DataType::Utf8, true),
+        Field::new("VisitCount", DataType::UInt32, true),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+    ])
+}
+
+impl ArrowRecords for DailyVisitEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(daily_visits_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = UInt32Builder::new();
+        let mut b4 = StringBuilder::new(); let mut b5 = StringBuilder::new();
+        let mut b6 = StringBuilder::new();
+        for e in entries {
+            b0.append_value(e.day.format("%Y-%m-%d").to_string());
+            b1.append_value(&e.url); b2.append_value(&e.title);
+            b3.append_value(e.visit_count);
+            b4.append_value(&e.web_browser); b5.append_value(&e.user_profile);
+            b6.append_value(linearize_daily_visit(e));
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()),
+        ])?)
+    }
+}
+
+pub fn write_daily_visits_parquet(entries: &[DailyVisitEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn shortcuts_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("LastAccessTime", DataType::Utf8, true),
+        Field::new("TypedText", DataType::Utf8, true),
+        Field::new("URL", DataType::Utf8, true),
+        Field::new("Contents", DataType::Utf8, true),
+        Field::new("Hits", DataType::UInt32, false),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("BrowserProfile", DataType::Utf8, true),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+    ])
+}
+
+impl ArrowRecords for ShortcutEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(shortcuts_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
+        let mut b4 = UInt32Builder::new(); let mut b5 = StringBuilder::new();
+        let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
+        let mut b8 = StringBuilder::new();
+        for e in entries {
+            b0.append_value(fmt_opt_dt(&e.last_access_time, PARQUET_TIME_FORMAT));
+            b1.append_value(&e.text); b2.append_value(&e.url);
+            b3.append_value(&e.contents); b4.append_value(e.number_of_hits);
+            b5.append_value(&e.web_browser); b6.append_value(&e.user_profile);
+            b7.append_value(&e.browser_profile);
+            b8.append_value(linearize_shortcut(e));
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+        ])?)
+    }
+}
+
+pub fn write_shortcuts_parquet(entries: &[ShortcutEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn favicons_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("ExpireTime", DataType::Utf8, true),
+        Field::new("PageURL", DataType::Utf8, true),
+        Field::new("IconURL", DataType::Utf8, true),
+        Field::new("Width", DataType::UInt32, false),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("BrowserProfile", DataType::Utf8, true),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+    ])
+}
+
+impl ArrowRecords for FaviconEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(favicons_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = UInt32Builder::new();
+        let mut b4 = StringBuilder::new(); let mut b5 = StringBuilder::new();
+        let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
+        for e in entries {
+            b0.append_value(fmt_opt_dt(&e.expire_time, PARQUET_TIME_FORMAT));
+            b1.append_value(&e.page_url); b2.append_value(&e.icon_url);
+            b3.append_value(e.width);
+            b4.append_value(&e.web_browser); b5.append_value(&e.user_profile);
+            b6.append_value(&e.browser_profile);
+            b7.append_value(linearize_favicon(e));
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()),
+        ])?)
+    }
+}
+
+pub fn write_favicons_parquet(entries: &[FaviconEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn topsites_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("Rank", DataType::Int64, false),
+        Field::new("URL", DataType::Utf8, true),
+        Field::new("Title", DataType::Utf8, true),
+        Field::new("Day", DataType::Utf8, true),
+        Field::new("VisitCount", DataType::UInt32, true),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("BrowserProfile", DataType::Utf8, true),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+    ])
+}
+
+impl ArrowRecords for TopSiteEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(topsites_parquet_schema());
+        let mut b0 = Int64Builder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
+        let mut b4 = UInt32Builder::new(); let mut b5 = StringBuilder::new();
+        let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
+        let mut b8 = StringBuilder::new();
+        for e in entries {
+            b0.append_value(e.url_rank);
+            b1.append_value(&e.url); b2.append_value(&e.title);
+            b3.append_option(e.day.map(|d| d.format("%Y-%m-%d").to_string()));
+            b4.append_option(e.visit_count);
+            b5.append_value(&e.web_browser); b6.append_value(&e.user_profile);
+            b7.append_value(&e.browser_profile);
+            b8.append_value(linearize_topsite(e));
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+        ])?)
+    }
+}
+
+pub fn write_topsites_parquet(entries: &[TopSiteEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn predictor_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("TypedText", DataType::Utf8, true),
+        Field::new("URL", DataType::Utf8, true),
+        Field::new("Hits", DataType::UInt32, false),
+        Field::new("Misses", DataType::UInt32, false),
+        Field::new("ConsecutiveMisses", DataType::UInt32, true),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("BrowserProfile", DataType::Utf8, true),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+    ])
+}
+
+impl ArrowRecords for PredictorEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(predictor_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = UInt32Builder::new(); let mut b3 = UInt32Builder::new();
+        let mut b4 = UInt32Builder::new(); let mut b5 = StringBuilder::new();
+        let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
+        let mut b8 = StringBuilder::new();
+        for e in entries {
+            b0.append_value(&e.user_text); b1.append_value(&e.url);
+            b2.append_value(e.number_of_hits); b3.append_value(e.number_of_misses);
+            b4.append_option(e.number_of_consecutive_misses);
+            b5.append_value(&e.web_browser); b6.append_value(&e.user_profile);
+            b7.append_value(&e.browser_profile);
+            b8.append_value(linearize_predictor(e));
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+        ])?)
+    }
+}
+
+pub fn write_predictor_parquet(entries: &[PredictorEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn autofill_profiles_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("LastUsed", DataType::Utf8, true),
+        Field::new("DateModified", DataType::Utf8, true),
+        Field::new("FullName", DataType::Utf8, true),
+        Field::new("Company", DataType::Utf8, true),
+        Field::new("StreetAddress", DataType::Utf8, true),
+        Field::new("City", DataType::Utf8, true),
+        Field::new("Region", DataType::Utf8, true),
+        Field::new("ZipCode", DataType::Utf8, true),
+        Field::new("Country", DataType::Utf8, true),
+        Field::new("Emails", DataType::Utf8, true),
+        Field::new("Phones", DataType::Utf8, true),
+        Field::new("TimesUsed", DataType::UInt32, false),
+        Field::new("GUID", DataType::Utf8, true),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("BrowserProfile", DataType::Utf8, true),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+    ])
+}
+
+impl ArrowRecords for AutofillProfileEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(autofill_profiles_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
+        let mut b4 = StringBuilder::new(); let mut b5 = StringBuilder::new();
+        let mut b6 = StringBuilder::new(); let mut b7 = StringBuilder::new();
+        let mut b8 = StringBuilder::new(); let mut b9 = StringBuilder::new();
+        let mut b10 = StringBuilder::new(); let mut b11 = UInt32Builder::new();
+        let mut b12 = StringBuilder::new(); let mut b13 = StringBuilder::new();
+        let mut b14 = StringBuilder::new(); let mut b15 = StringBuilder::new();
+        let mut b16 = StringBuilder::new();
+        for e in entries {
+            b0.append_value(fmt_opt_dt(&e.last_used, PARQUET_TIME_FORMAT));
+            b1.append_value(fmt_opt_dt(&e.date_modified, PARQUET_TIME_FORMAT));
+            b2.append_value(&e.full_name); b3.append_value(&e.company_name);
+            b4.append_value(&e.street_address); b5.append_value(&e.city);
+            b6.append_value(&e.state); b7.append_value(&e.zipcode);
+            b8.append_value(&e.country_code); b9.append_value(&e.emails);
+            b10.append_value(&e.phones); b11.append_value(e.use_count);
+            b12.append_value(&e.guid); b13.append_value(&e.web_browser);
+            b14.append_value(&e.user_profile); b15.append_value(&e.browser_profile);
+            b16.append_value(linearize_autofill_profile(e));
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+            Arc::new(b9.finish()), Arc::new(b10.finish()), Arc::new(b11.finish()),
+            Arc::new(b12.finish()), Arc::new(b13.finish()), Arc::new(b14.finish()),
+            Arc::new(b15.finish()), Arc::new(b16.finish()),
+        ])?)
+    }
+}
+
+pub fn write_autofill_profiles_parquet(entries: &[AutofillProfileEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+fn credit_cards_parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("LastUsed", DataType::Utf8, true),
+        Field::new("DateModified", DataType::Utf8, true),
+        Field::new("NameOnCard", DataType::Utf8, true),
+        Field::new("Network", DataType::Utf8, true),
+        Field::new("LastFour", DataType::Utf8, true),
+        Field::new("ExpirationMonth", DataType::UInt32, true),
+        Field::new("ExpirationYear", DataType::UInt32, true),
+        Field::new("Nickname", DataType::Utf8, true),
+        Field::new("TimesUsed", DataType::UInt32, false),
+        Field::new("Storage", DataType::Utf8, true),
+        Field::new("GUID", DataType::Utf8, true),
+        Field::new("WebBrowser", DataType::Utf8, true),
+        Field::new("UserProfile", DataType::Utf8, true),
+        Field::new("BrowserProfile", DataType::Utf8, true),
+        Field::new("NaturalLanguage", DataType::Utf8, true),
+    ])
+}
+
+impl ArrowRecords for CreditCardEntry {
+    fn record_batch(entries: &[Self]) -> Result<RecordBatch> {
+        let schema = Arc::new(credit_cards_parquet_schema());
+        let mut b0 = StringBuilder::new(); let mut b1 = StringBuilder::new();
+        let mut b2 = StringBuilder::new(); let mut b3 = StringBuilder::new();
+        let mut b4 = StringBuilder::new(); let mut b5 = UInt32Builder::new();
+        let mut b6 = UInt32Builder::new(); let mut b7 = StringBuilder::new();
+        let mut b8 = UInt32Builder::new(); let mut b9 = StringBuilder::new();
+        let mut b10 = StringBuilder::new(); let mut b11 = StringBuilder::new();
+        let mut b12 = StringBuilder::new(); let mut b13 = StringBuilder::new();
+        let mut b14 = StringBuilder::new();
+        for e in entries {
+            b0.append_value(fmt_opt_dt(&e.last_used, PARQUET_TIME_FORMAT));
+            b1.append_value(fmt_opt_dt(&e.date_modified, PARQUET_TIME_FORMAT));
+            b2.append_value(&e.name_on_card); b3.append_value(&e.network);
+            b4.append_value(&e.last_four); b5.append_option(e.expiration_month);
+            b6.append_option(e.expiration_year); b7.append_value(&e.nickname);
+            b8.append_value(e.use_count); b9.append_value(&e.storage);
+            b10.append_value(&e.guid); b11.append_value(&e.web_browser);
+            b12.append_value(&e.user_profile); b13.append_value(&e.browser_profile);
+            b14.append_value(linearize_credit_card(e));
+        }
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
+            Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
+            Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
+            Arc::new(b9.finish()), Arc::new(b10.finish()), Arc::new(b11.finish()),
+            Arc::new(b12.finish()), Arc::new(b13.finish()), Arc::new(b14.finish()),
+        ])?)
+    }
+}
+
+pub fn write_credit_cards_parquet(entries: &[CreditCardEntry], output_path: &Path) -> Result<usize> {
+    write_parquet_records(entries, output_path)
+}
+
+// ============================================================================
+// Activity heatmap
+// ============================================================================
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday",
+];
+
+/// Write a 7x24 matrix of visit counts (rows Monday..Sunday, columns hour 00..23) with
+/// each visit bucketed by its local day-of-week and hour in `tz`.
+/// Returns the number of visits counted.
+pub fn write_activity_heatmap<Tz: chrono::TimeZone>(
+    entries: &[HistoryEntry],
+    output_path: &Path,
+    tz: &Tz,
+) -> Result<usize> {
+    use chrono::{Datelike, Timelike};
+
+    let mut grid = [[0u64; 24]; 7];
     for e in entries {
-        b0.append_value(e.install_time.map(|d| d.format("%Y-%m-%d %H:%M:%S%.3f").to_string()).unwrap_or_default());
-        b1.append_value(&e.extension_id); b2.append_value(&e.name);
-        b3.append_value(&e.version); b4.append_value(&e.description);
-        b5.append_value(e.enabled);
-        b6.append_value(&e.permissions); b7.append_value(&e.web_browser);
-        b8.append_value(&e.user_profile); b9.append_value(linearize_extension(e));
-    }
-    let batch = RecordBatch::try_new(schema.clone(), vec![
-        Arc::new(b0.finish()), Arc::new(b1.finish()), Arc::new(b2.finish()),
-        Arc::new(b3.finish()), Arc::new(b4.finish()), Arc::new(b5.finish()),
-        Arc::new(b6.finish()), Arc::new(b7.finish()), Arc::new(b8.finish()),
-        Arc::new(b9.finish()),
-    ])?;
-    write_parquet_batch(&batch, schema, output_path)?;
+        let local = e.visit_time.with_timezone(tz);
+        grid[local.weekday().num_days_from_monday() as usize][local.hour() as usize] += 1;
+    }
+
+    ensure_parent(output_path)?;
+    let file = File::create(output_path)
+        .with_context(|| format!("Failed to create heatmap file: {}", output_path.display()))?;
+    let mut wtr = csv::Writer::from_writer(file);
+    let mut header = vec!["Day".to_string()];
+    header.extend((0..24).map(|h| format!("{h:02}")));
+    wtr.write_record(&header)?;
+    for (day, counts) in WEEKDAYS.iter().zip(grid.iter()) {
+        let mut row = vec![day.to_string()];
+        row.extend(counts.iter().map(|c| c.to_string()));
+        wtr.write_record(&row)?;
+    }
+    wtr.flush()?;
     Ok(entries.len())
 }
 
+// ============================================================================
+// Per-domain daily summary (`--summary`)
+// ============================================================================
+
+const DOMAIN_SUMMARY_HEADERS: &[&str] = &[
+    "Domain", "Date", "VisitCount", "FirstVisit", "LastVisit", "DistinctURLs",
+];
+
+/// Write one row per registrable domain (see [`crate::aggregate::registrable_domain`])
+/// and day: the visits that day, the first and last of them, and how many distinct URLs
+/// they went to. Days are taken in the `--tz` output zone. Rows are ordered by domain,
+/// then date; visits to URLs without a host (`about:`, `file:`) are left out.
+/// Returns the number of rows written.
+pub fn write_domain_summary_csv(entries: &[HistoryEntry], output_path: &Path, date_fmt: &str) -> Result<usize> {
+    use std::collections::{BTreeMap, HashSet};
+
+    struct Day<'a> {
+        visits: usize,
+        first: DateTime<Utc>,
+        last: DateTime<Utc>,
+        urls: HashSet<&'a str>,
+    }
+
+    let tz = *OUTPUT_TZ.lock().unwrap_or_else(|e| e.into_inner());
+    let mut days: BTreeMap<(String, NaiveDate), Day> = BTreeMap::new();
+    for e in entries {
+        let host = match crate::canonical::url_host(&e.url) {
+            Some(h) => h,
+            None => continue,
+        };
+        let date = match tz {
+            Some(tz) => e.visit_time.with_timezone(&tz).date_naive(),
+            None => e.visit_time.date_naive(),
+        };
+        let key = (crate::aggregate::registrable_domain(host), date);
+        let day = days.entry(key).or_insert_with(|| Day {
+            visits: 0, first: e.visit_time, last: e.visit_time, urls: HashSet::new(),
+        });
+        day.visits += 1;
+        day.first = day.first.min(e.visit_time);
+        day.last = day.last.max(e.visit_time);
+        day.urls.insert(&e.url);
+    }
+
+    ensure_parent(output_path)?;
+    let mut wtr = RecordSink::create(output_path, DOMAIN_SUMMARY_HEADERS, None)?;
+    for ((domain, date), day) in &days {
+        wtr.write_record([
+            domain, &date.format("%Y-%m-%d").to_string(), &day.visits.to_string(),
+            &fmt_dt(&day.first, date_fmt), &fmt_dt(&day.last, date_fmt), &day.urls.len().to_string(),
+        ])?;
+    }
+    wtr.finish()?;
+    Ok(days.len())
+}
+
+// ============================================================================
+// Super-timeline (`--timeline`)
+// ============================================================================
+
+/// One row of the merged timeline across all artifact types.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEvent {
+    /// Most recent activity time (see [`Timestamped`]), if the entry has any
+    pub time: Option<DateTime<Utc>>,
+    /// Artifact display name; history rows that are HSTS records say so
+    pub artifact: &'static str,
+    pub browser: String,
+    pub user: String,
+    /// The entry's NaturalLanguage (`linearize_*`) text
+    pub description: String,
+}
+
+fn push_events<T: Timestamped + DedupKey>(
+    events: &mut Vec<TimelineEvent>,
+    artifact: ArtifactType,
+    entries: &[T],
+    linearize: fn(&T) -> String,
+) {
+    for e in entries {
+        let (_, _, browser, user) = e.dedup_key();
+        events.push(TimelineEvent {
+            time: e.activity_time(),
+            artifact: artifact.display_name(),
+            browser,
+            user,
+            description: linearize(e),
+        });
+    }
+}
+
+/// Every entry of `artifacts` as a timeline event, sorted by time. Entries without a
+/// timestamp come last; ties keep extraction order.
+pub fn timeline_events(artifacts: &ArtifactSet) -> Vec<TimelineEvent> {
+    let mut events = Vec::new();
+    push_events(
+        &mut events,
+        ArtifactType::History,
+        &artifacts.history,
+        linearize_entry,
+    );
+    // HSTS records are kept with the history rows, marked by their Visit Type
+    for (event, entry) in events.iter_mut().zip(&artifacts.history) {
+        if entry.visit_type == "HSTS Record" {
+            event.artifact = ArtifactType::HstsRecords.display_name();
+        }
+    }
+    push_events(
+        &mut events,
+        ArtifactType::Downloads,
+        &artifacts.downloads,
+        linearize_download,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::KeywordSearches,
+        &artifacts.keyword_searches,
+        linearize_keyword_search,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::Cookies,
+        &artifacts.cookies,
+        linearize_cookie,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::Autofill,
+        &artifacts.autofill,
+        linearize_autofill,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::Bookmarks,
+        &artifacts.bookmarks,
+        linearize_bookmark,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::LoginData,
+        &artifacts.logins,
+        linearize_login,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::Extensions,
+        &artifacts.extensions,
+        linearize_extension,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::BrowserSettings,
+        &artifacts.browser_settings,
+        linearize_setting,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::DailyVisits,
+        &artifacts.daily_visits,
+        linearize_daily_visit,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::Shortcuts,
+        &artifacts.shortcuts,
+        linearize_shortcut,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::Favicons,
+        &artifacts.favicons,
+        linearize_favicon,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::TopSites,
+        &artifacts.top_sites,
+        linearize_topsite,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::Predictor,
+        &artifacts.predictor,
+        linearize_predictor,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::AutofillProfiles,
+        &artifacts.autofill_profiles,
+        linearize_autofill_profile,
+    );
+    push_events(
+        &mut events,
+        ArtifactType::CreditCards,
+        &artifacts.credit_cards,
+        linearize_credit_card,
+    );
+    events.sort_by_key(|e| (e.time.is_none(), e.time));
+    events
+}
+
+const TIMELINE_HEADERS: &[&str] = &[
+    "Timestamp",
+    "Artifact",
+    "Web Browser",
+    "User Profile",
+    "Description",
+];
+
+/// Write every entry of `artifacts` to one CSV sorted by timestamp, Plaso-style: one
+/// line per event with its artifact type, browser, user and natural-language
+/// description. Entries without a timestamp are written last with a blank time.
+/// Returns the number of rows written.
+pub fn write_timeline_csv(
+    artifacts: &ArtifactSet,
+    output_path: &Path,
+    date_fmt: &str,
+) -> Result<usize> {
+    let events = timeline_events(artifacts);
+    ensure_parent(output_path)?;
+    let mut wtr = RecordSink::create(output_path, TIMELINE_HEADERS, None)?;
+    for e in &events {
+        wtr.write_record([
+            &fmt_opt_dt(&e.time, date_fmt),
+            e.artifact,
+            &e.browser,
+            &e.user,
+            &e.description,
+        ])?;
+    }
+    wtr.finish()?;
+    Ok(events.len())
+}
+
+// ============================================================================
+// HTML report (`--html`)
+// ============================================================================
+
+/// Clickable column headers sort the table they belong to; numeric columns sort as
+/// numbers, everything else as text. A second click reverses the order.
+const HTML_SORT_SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable th").forEach(function (th) {
+  th.addEventListener("click", function () {
+    var table = th.closest("table"), body = table.tBodies[0];
+    var col = th.cellIndex, asc = th.dataset.order !== "asc";
+    var rows = Array.prototype.slice.call(body.rows);
+    rows.sort(function (a, b) {
+      var x = a.cells[col].textContent, y = b.cells[col].textContent;
+      var nx = parseFloat(x), ny = parseFloat(y);
+      var cmp = (!isNaN(nx) && !isNaN(ny) && isFinite(x) && isFinite(y))
+        ? nx - ny : x.localeCompare(y);
+      return asc ? cmp : -cmp;
+    });
+    rows.forEach(function (r) { body.appendChild(r); });
+    table.querySelectorAll("th").forEach(function (h) { delete h.dataset.order; });
+    th.dataset.order = asc ? "asc" : "desc";
+  });
+});
+"#;
+
+const HTML_STYLE: &str = "
+body { font-family: sans-serif; margin: 1em 2em; }
+table { border-collapse: collapse; margin: 0.5em 0 1.5em; font-size: 0.85em; }
+th, td { border: 1px solid #ccc; padding: 2px 6px; text-align: left; vertical-align: top; }
+th { background: #eee; cursor: pointer; position: sticky; top: 0; }
+th[data-order=asc]::after { content: ' \\25B2'; }
+th[data-order=desc]::after { content: ' \\25BC'; }
+td { max-width: 40em; overflow-wrap: anywhere; }
+summary { cursor: pointer; font-weight: bold; margin: 0.3em 0; }
+";
+
+/// Date format of the per-artifact tables; `format_timestamp` adds the zone.
+const HTML_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+fn html_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn write_html_table<W: Write>(
+    out: &mut W,
+    headers: &[&str],
+    rows: impl IntoIterator<Item = Vec<String>>,
+) -> Result<()> {
+    write!(out, "<table class=\"sortable\"><thead><tr>")?;
+    for h in headers {
+        write!(out, "<th>{}</th>", html_escape(h))?;
+    }
+    write!(out, "</tr></thead><tbody>")?;
+    for row in rows {
+        write!(out, "<tr>")?;
+        for cell in row {
+            write!(out, "<td>{}</td>", html_escape(&cell))?;
+        }
+        writeln!(out, "</tr>")?;
+    }
+    writeln!(out, "</tbody></table>")?;
+    Ok(())
+}
+
+/// Rows of one artifact's table, formatted by its CSV writer (times with `time_fmt`) so
+/// cells read exactly as in the CSV output. The NaturalLanguage column is left to the
+/// timeline.
+fn artifact_table_rows(
+    artifacts: &ArtifactSet,
+    artifact: ArtifactType,
+    dir: &Path,
+    time_fmt: &str,
+) -> Result<(Vec<&'static str>, Vec<Vec<String>>)> {
+    let (headers, _) = artifact_columns(artifact);
+    let names: Vec<String> = headers
+        .iter()
+        .filter(|h| **h != "NaturalLanguage")
+        .map(|h| h.to_string())
+        .collect();
+    let projection = Projection::new(artifact, &names)?;
+    let path = dir.join(format!("{}.csv", artifact.file_suffix()));
+    let columns = Some(&projection);
+    let written = match artifact {
+        ArtifactType::History | ArtifactType::HstsRecords => {
+            write_csv(&artifacts.history, &path, time_fmt, columns)?
+        }
+        ArtifactType::Downloads => {
+            write_downloads_csv(&artifacts.downloads, &path, time_fmt, columns)?
+        }
+        ArtifactType::KeywordSearches => {
+            write_keywords_csv(&artifacts.keyword_searches, &path, time_fmt, columns)?
+        }
+        ArtifactType::Cookies => write_cookies_csv(&artifacts.cookies, &path, time_fmt, columns)?,
+        ArtifactType::Autofill => {
+            write_autofill_csv(&artifacts.autofill, &path, time_fmt, columns)?
+        }
+        ArtifactType::Bookmarks => {
+            write_bookmarks_csv(&artifacts.bookmarks, &path, time_fmt, columns)?
+        }
+        ArtifactType::LoginData => write_logins_csv(&artifacts.logins, &path, time_fmt, columns)?,
+        ArtifactType::Extensions => {
+            write_extensions_csv(&artifacts.extensions, &path, time_fmt, columns)?
+        }
+        ArtifactType::BrowserSettings => {
+            write_settings_csv(&artifacts.browser_settings, &path, time_fmt, columns)?
+        }
+        ArtifactType::DailyVisits => {
+            write_daily_visits_csv(&artifacts.daily_visits, &path, time_fmt, columns)?
+        }
+        ArtifactType::Shortcuts => {
+            write_shortcuts_csv(&artifacts.shortcuts, &path, time_fmt, columns)?
+        }
+        ArtifactType::Favicons => {
+            write_favicons_csv(&artifacts.favicons, &path, time_fmt, columns)?
+        }
+        ArtifactType::TopSites => {
+            write_topsites_csv(&artifacts.top_sites, &path, time_fmt, columns)?
+        }
+        ArtifactType::Predictor => {
+            write_predictor_csv(&artifacts.predictor, &path, time_fmt, columns)?
+        }
+        ArtifactType::AutofillProfiles => {
+            write_autofill_profiles_csv(&artifacts.autofill_profiles, &path, time_fmt, columns)?
+        }
+        ArtifactType::CreditCards => {
+            write_credit_cards_csv(&artifacts.credit_cards, &path, time_fmt, columns)?
+        }
+    };
+    if written == 0 {
+        return Ok((projection.headers(), Vec::new()));
+    }
+    let mut rdr = csv::Reader::from_path(&path)
+        .with_context(|| format!("Failed to read back {}", path.display()))?;
+    let rows = rdr
+        .records()
+        .map(|r| r.map(|r| r.iter().map(str::to_string).collect()))
+        .collect::<csv::Result<_>>()?;
+    Ok((projection.headers(), rows))
+}
+
+/// Write `artifacts` to `output_path` as one self-contained HTML page, with no external
+/// scripts or stylesheets.
+///
+/// The page opens with a timeline of every timestamped entry across all artifact types
+/// (its activity time and natural-language description), grouped by day in collapsible
+/// sections, followed by a sortable table per artifact type with its CSV columns.
+/// Times are in the `--tz` zone. Returns the number of timeline events.
+pub fn write_html_report(artifacts: &ArtifactSet, output_path: &Path) -> Result<usize> {
+    // Entries without a timestamp only appear in their artifact's table
+    let mut events = timeline_events(artifacts);
+    events.retain(|e| e.time.is_some());
+
+    ensure_parent(output_path)?;
+    let file = File::create(output_path)
+        .with_context(|| format!("Failed to create HTML report: {}", output_path.display()))?;
+    let mut out = std::io::BufWriter::new(file);
+
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html><head><meta charset=\"utf-8\">")?;
+    writeln!(out, "<title>WebX browser activity report</title>")?;
+    writeln!(out, "<style>{}</style></head><body>", HTML_STYLE)?;
+    writeln!(out, "<h1>Browser activity report</h1>")?;
+
+    writeln!(out, "<h2>Timeline ({} events)</h2>", events.len())?;
+    let mut days: Vec<(String, Vec<&TimelineEvent>)> = Vec::new();
+    for event in &events {
+        let day = fmt_opt_dt(&event.time, "%Y-%m-%d (%a)");
+        match days.last_mut() {
+            Some((d, day_events)) if *d == day => day_events.push(event),
+            _ => days.push((day, vec![event])),
+        }
+    }
+    for (day, day_events) in &days {
+        writeln!(
+            out,
+            "<details><summary>{} &mdash; {} event(s)</summary>",
+            html_escape(day),
+            day_events.len()
+        )?;
+        let rows = day_events.iter().map(|event| {
+            vec![
+                fmt_opt_dt(&event.time, "%H:%M:%S"),
+                event.artifact.to_string(),
+                event.description.clone(),
+            ]
+        });
+        write_html_table(&mut out, &["Time", "Artifact", "Description"], rows)?;
+        writeln!(out, "</details>")?;
+    }
+
+    let tmp =
+        tempfile::TempDir::with_prefix("webx-html-").context("Failed to create temp directory")?;
+    for artifact in ArtifactType::ALL {
+        // HSTS records are already in the history table
+        if artifact == ArtifactType::HstsRecords {
+            continue;
+        }
+        let (headers, rows) =
+            artifact_table_rows(artifacts, artifact, tmp.path(), HTML_TIME_FORMAT)?;
+        if rows.is_empty() {
+            continue;
+        }
+        writeln!(
+            out,
+            "<h2>{} ({} row(s))</h2>",
+            html_escape(artifact.display_name()),
+            rows.len()
+        )?;
+        write_html_table(&mut out, &headers, rows)?;
+    }
+
+    if !artifacts.errors.is_empty() {
+        writeln!(out, "<h2>Extraction errors</h2>")?;
+        let rows = artifacts
+            .errors
+            .iter()
+            .map(|(file, reason)| vec![file.clone(), reason.clone()]);
+        write_html_table(&mut out, &["File", "Error"], rows)?;
+    }
+
+    writeln!(out, "<script>{}</script>", HTML_SORT_SCRIPT)?;
+    writeln!(out, "</body></html>")?;
+    out.flush()?;
+    Ok(events.len())
+}
+
+// ============================================================================
+// Excel workbook (--xlsx)
+// ============================================================================
+
+/// Date format of the tables behind the workbook. Time cells are parsed back into Excel
+/// date-times, so it keeps every digit the CSV could show.
+const XLSX_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.6f";
+
+/// How a column's cells are typed in the workbook.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum XlsxColumn {
+    Text,
+    Number,
+    DateTime,
+    Date,
+}
+
+/// Typing of the column headed `header`. Anything not listed stays text, so values, IDs
+/// and versions keep their leading zeros.
+fn xlsx_column(header: &str) -> XlsxColumn {
+    match header {
+        "Visit Time"
+        | "Start Time"
+        | "End Time"
+        | "Creation Time"
+        | "Expiry Time"
+        | "Last Access Time"
+        | "First Used"
+        | "Last Used"
+        | "Date Added"
+        | "Date Last Used"
+        | "Date Modified"
+        | "Last Visited (Desktop)"
+        | "Date Created"
+        | "Date Password Modified"
+        | "Install Time"
+        | "Last Modified"
+        | "Expire Time" => XlsxColumn::DateTime,
+        "Day" => XlsxColumn::Date,
+        "Visit Count" | "View Time (ms)" | "Key Presses" | "URL Length" | "Typed Count"
+        | "Record ID" | "Received Bytes" | "Total Bytes" | "Keyword ID" | "URL ID"
+        | "Times Used" | "Hits" | "Misses" | "Consecutive Misses" | "Width" | "Rank"
+        | "Expiration Month" | "Expiration Year" => {
+            XlsxColumn::Number
+        }
+        _ => XlsxColumn::Text,
+    }
+}
+
+/// `value`, as written by a CSV writer, as a cell of a `kind` column. Values that don't
+/// parse as the column's type are kept as text.
+fn xlsx_cell(kind: XlsxColumn, value: String) -> Cell {
+    if value.is_empty() {
+        return Cell::Empty;
+    }
+    match kind {
+        XlsxColumn::Text => Cell::Text(value),
+        XlsxColumn::Number => match value.parse::<i64>() {
+            // Excel keeps 15 significant digits, so longer numbers (hashed record IDs)
+            // would be rounded
+            Ok(n) if n.unsigned_abs() < 1_000_000_000_000_000 => Cell::Number(n as f64),
+            _ => Cell::Text(value),
+        },
+        XlsxColumn::DateTime => {
+            // The zone `format_timestamp` appends goes in the header instead
+            let local = value.rsplit_once(' ').map_or(value.as_str(), |(dt, _)| dt);
+            match chrono::NaiveDateTime::parse_from_str(local, "%Y-%m-%d %H:%M:%S%.f") {
+                Ok(dt) => Cell::DateTime(dt),
+                Err(_) => Cell::Text(value),
+            }
+        }
+        XlsxColumn::Date => match NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+            Ok(d) => Cell::Date(d),
+            Err(_) => Cell::Text(value),
+        },
+    }
+}
+
+/// Write `artifacts` to `output_path` as one Excel workbook with a worksheet per
+/// artifact type, in its CSV columns (less NaturalLanguage).
+///
+/// Times are real Excel date-times in the `--tz` zone, which is named in their header
+/// (e.g. "Visit Time (UTC)"), so they sort and filter as dates. Counts, sizes and IDs
+/// are numbers; everything else is text, so nothing is reinterpreted the way Excel does
+/// when it opens a CSV. The header row is bold and frozen. Nothing is written when
+/// `artifacts` has no entries. Returns the number of rows.
+pub fn write_xlsx(artifacts: &ArtifactSet, output_path: &Path) -> Result<usize> {
+    let zone = match *OUTPUT_TZ.lock().unwrap_or_else(|e| e.into_inner()) {
+        Some(tz) => tz.name().to_string(),
+        None => "UTC".to_string(),
+    };
+    let tmp =
+        tempfile::TempDir::with_prefix("webx-xlsx-").context("Failed to create temp directory")?;
+    let mut workbook = Workbook::new();
+    let mut rows_written = 0;
+    for artifact in ArtifactType::ALL {
+        // HSTS records are already in the history sheet
+        if artifact == ArtifactType::HstsRecords {
+            continue;
+        }
+        let (headers, rows) =
+            artifact_table_rows(artifacts, artifact, tmp.path(), XLSX_TIME_FORMAT)?;
+        if rows.is_empty() {
+            continue;
+        }
+        let kinds: Vec<XlsxColumn> = headers.iter().map(|h| xlsx_column(h)).collect();
+        let labels: Vec<String> = headers
+            .iter()
+            .zip(&kinds)
+            .map(|(h, kind)| match kind {
+                XlsxColumn::DateTime => format!("{} ({})", h, zone),
+                _ => h.to_string(),
+            })
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        let cells = rows.into_iter().map(|row| {
+            row.into_iter()
+                .zip(&kinds)
+                .map(|(value, kind)| xlsx_cell(*kind, value))
+                .collect()
+        });
+        rows_written += workbook.add_sheet(artifact.display_name(), &labels, cells);
+    }
+    if workbook.is_empty() {
+        return Ok(0);
+    }
+    ensure_parent(output_path)?;
+    workbook.save(output_path)?;
+    Ok(rows_written)
+}
+
+// ============================================================================
+// Column schema (`webx schema`)
+// ============================================================================
+
+/// CSV headers and Parquet schema written for `artifact`, taken from the same
+/// definitions the writers use. HSTS records are written in the history layout.
+pub fn artifact_columns(artifact: ArtifactType) -> (&'static [&'static str], Schema) {
+    match artifact {
+        ArtifactType::History | ArtifactType::HstsRecords => {
+            (HISTORY_HEADERS, history_parquet_schema())
+        }
+        ArtifactType::Downloads => (DOWNLOAD_HEADERS, downloads_parquet_schema()),
+        ArtifactType::KeywordSearches => (KEYWORD_HEADERS, keywords_parquet_schema()),
+        ArtifactType::Cookies => (COOKIE_HEADERS, cookies_parquet_schema()),
+        ArtifactType::Autofill => (AUTOFILL_HEADERS, autofill_parquet_schema()),
+        ArtifactType::Bookmarks => (BOOKMARK_HEADERS, bookmarks_parquet_schema()),
+        ArtifactType::LoginData => (LOGIN_HEADERS, logins_parquet_schema()),
+        ArtifactType::Extensions => (EXTENSION_HEADERS, extensions_parquet_schema()),
+        ArtifactType::BrowserSettings => (SETTINGS_HEADERS, settings_parquet_schema()),
+        ArtifactType::DailyVisits => (DAILY_VISIT_HEADERS, daily_visits_parquet_schema()),
+        ArtifactType::Shortcuts => (SHORTCUT_HEADERS, shortcuts_parquet_schema()),
+        ArtifactType::Favicons => (FAVICON_HEADERS, favicons_parquet_schema()),
+        ArtifactType::TopSites => (TOPSITE_HEADERS, topsites_parquet_schema()),
+        ArtifactType::Predictor => (PREDICTOR_HEADERS, predictor_parquet_schema()),
+        ArtifactType::AutofillProfiles => {
+            (AUTOFILL_PROFILE_HEADERS, autofill_profiles_parquet_schema())
+        }
+        ArtifactType::CreditCards => (CREDIT_CARD_HEADERS, credit_cards_parquet_schema()),
+    }
+}
+
+/// Column contract of one artifact's output, as printed by `webx schema`.
+#[derive(Debug, Serialize)]
+pub struct ArtifactSchema {
+    pub artifact: &'static str,
+    pub display_name: &'static str,
+    pub csv: Vec<CsvColumn>,
+    pub parquet: Vec<ParquetColumn>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CsvColumn {
+    pub name: &'static str,
+    /// Type of the matching Parquet field, or `Utf8` for CSV-only columns
+    #[serde(rename = "type")]
+    pub data_type: String,
+    pub in_parquet: bool,
+    pub description: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParquetColumn {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub data_type: String,
+    pub nullable: bool,
+}
+
+/// Describe the CSV and Parquet columns of `artifact`. CSV cells are all text; a CSV
+/// column's type is that of the Parquet field with the same name (ignoring spaces and
+/// punctuation).
+pub fn artifact_schema(artifact: ArtifactType) -> ArtifactSchema {
+    let (headers, schema) = artifact_columns(artifact);
+    let csv = headers
+        .iter()
+        .map(|name| {
+            let field = schema
+                .fields()
+                .iter()
+                .find(|f| squash_name(f.name()) == squash_name(name));
+            CsvColumn {
+                name,
+                data_type: field.map_or("Utf8".to_string(), |f| f.data_type().to_string()),
+                in_parquet: field.is_some(),
+                description: column_description(name),
+            }
+        })
+        .collect();
+    let parquet = schema
+        .fields()
+        .iter()
+        .map(|f| ParquetColumn {
+            name: f.name().clone(),
+            data_type: f.data_type().to_string(),
+            nullable: f.is_nullable(),
+        })
+        .collect();
+    ArtifactSchema {
+        artifact: artifact.file_suffix(),
+        display_name: artifact.display_name(),
+        csv,
+        parquet,
+    }
+}
+
+/// Short description of a CSV column, by header name.
+fn column_description(header: &str) -> &'static str {
+    match header {
+        "Visit Time" => {
+            "When the page was visited (keyword searches: when the search URL was visited)"
+        }
+        "Start Time" | "End Time" => "When the download started / finished",
+        "Creation Time" | "Expiry Time" => "Cookie creation and expiry times",
+        "Last Access Time" => "When the cookie was last sent, or the shortcut last chosen",
+        "First Used" | "Last Used" => "First and most recent time the value was submitted",
+        "Date Added" | "Date Modified" | "Date Last Used" => "Bookmark/login timestamps",
+        "Last Visited (Desktop)" => "Last visit to the bookmarked URL from a desktop browser",
+        "Date Created" | "Date Password Modified" => {
+            "When the login was saved / its password last changed"
+        }
+        "Install Time" => "When the extension was installed",
+        "Last Modified" => "When the setting was last changed, if recorded",
+        "Day" => "Calendar day (YYYY-MM-DD) the visits were counted for",
+        "URL" => "Full URL",
+        "Title" => "Page title",
+        "Visit Count" => "Number of visits",
+        "Visited From" => "Referring URL, or the referrer passed by the app that opened it",
+        "App ID" => "App that opened the visit (Chrome visits.app_id)",
+        "Journey" => "Pages reached from a typed/start-page visit, as A -> B -> C",
+        "Canonical URL" => {
+            "URL without fragment or tracking parameters, lowercase host (--canonical-dedup)"
+        }
+        "Visit Type" => {
+            "Transition type (Link, Typed, Reload, ...) with Chrome qualifiers in parentheses, or record kind (e.g. HSTS Record)"
+        }
+        "Visit Duration" => "Chrome: time the page was focused",
+        "View Time (ms)" => "Firefox: milliseconds the page was in the foreground",
+        "Key Presses" => "Firefox: keys pressed while on the page",
+        "Hidden" => "Chrome: URL is hidden from the history UI (e.g. a redirect hop)",
+        "Visited Link Confirmed" => "Chrome: URL's fingerprint is in the Visited Links table",
+        "Web Browser" => "Browser name",
+        "User Profile" => "OS username, from the path or --user",
+        "Browser Profile" => "Browser profile directory name",
+        "Account" => "Signed-in/synced account email for the profile",
+        "URL Length" => "Length of the URL in bytes",
+        "Typed Count" => "Times the URL was typed into the address bar",
+        "History File" | "Source File" => "Path of the source database or file",
+        "Record ID" => {
+            "Row ID in the source, or a stable hash of the record's identity for JSON/XML sources"
+        }
+        "NaturalLanguage" => "One-line plain-English summary of the row",
+        "Target Path" | "Current Path" => "Where the download was saved / currently is",
+        "Received Bytes" | "Total Bytes" => "Bytes downloaded / expected size",
+        "State" => "Download state (Complete, Cancelled, Interrupted, ...)",
+        "Danger Type" => "Safe Browsing verdict for the download",
+        "Interrupt Reason" => "Why Chrome interrupted the download (block, scan, network)",
+        "MIME Type" => "Content type of the download",
+        "Referrer" => "Page that linked to the download",
+        "Tab URL" => "URL of the tab that started the download",
+        "Page Title" => "History title of the page the download came from",
+        "URL Chain" => "Chrome: every redirect hop to the download, first request first",
+        "Inferred Referrer" => "Nearest preceding history visit (--infer-referrers)",
+        "Opened" => "Whether the downloaded file was opened from the browser",
+        "GUID" => "Download, saved address or saved card GUID",
+        "Initiating Extension ID" | "Initiating Extension Name" => {
+            "Extension that started the download"
+        }
+        "Search Term" => "Search term as typed",
+        "Normalized Term" => "Lower-cased, normalized search term",
+        "Keyword ID" => "Search engine (keyword) ID",
+        "URL ID" => "ID of the search results URL",
+        "Host" => "Cookie domain",
+        "Name" => "Cookie or extension name",
+        "Path" => "Cookie path",
+        "Value" => "Stored value (cookie, form field or setting)",
+        "Secure" | "HttpOnly" | "Persistent" => "Cookie flags",
+        "SameSite" => "Cookie SameSite policy",
+        "Visited Host" => "A visited page's host is the cookie's host or a subdomain of it",
+        "First Party" => {
+            "The cookie's registrable domain (eTLD+1) is that of a visited page; false for third-party cookies"
+        }
+        "Field Name" => "Form field name",
+        "Times Used" => "Usage count",
+        "Sync Transaction Version" => "Chrome sync version of the bookmark",
+        "Folder Path" => "Bookmark folder, e.g. Bookmarks Bar > Work",
+        "Status" => "Recently Removed for Bookmarks.bak entries no longer live (--dedup)",
+        "Origin URL" | "Action URL" => "Login page URL / form submit URL",
+        "Username" => "Saved username (passwords are never extracted)",
+        "Signon Realm" => "Chrome signon_realm or Firefox httpRealm",
+        "Federation URL" => "Identity provider for federated (SSO) logins",
+        "Blacklisted By User" => "The user declined to save a password for the site",
+        "Extension ID" => "Extension ID",
+        "Version" => "Extension version",
+        "Description" => "Extension description",
+        "Enabled" => "Whether the extension is enabled",
+        "Has Stored State" => {
+            "Profile holds runtime data (storage, IndexedDB, cookies) for the extension"
+        }
+        "Update URL" => "Extension update URL (non-store URLs indicate sideloading)",
+        "Permissions" => "Requested permissions",
+        "Expire Time" => "When Firefox would refetch the icon; set when it was last fetched",
+        "Page URL" => "Page the icon was shown for",
+        "Icon URL" => "Where the icon was fetched from",
+        "Width" => "Icon width in pixels (0 for SVG or unknown)",
+        "Typed Text" => "Text typed into the omnibox",
+        "Contents" => "Suggestion shown for the typed text and picked by the user",
+        "Hits" => "Times the suggestion was picked for the typed text",
+        "Rank" => "Top Sites: position among the new-tab tiles (0 = first)",
+        "Misses" => "Times the text was typed and a different page was opened",
+        "Consecutive Misses" => "Misses since the prediction last led to the URL",
+        "Full Name" => "Names saved on the address, ;-separated",
+        "Company" => "Company saved on the address",
+        "Street Address" => "Street lines of the saved address",
+        "City" | "Region" | "Zip Code" | "Country" => "Part of the saved address",
+        "Emails" | "Phones" => "Email addresses / phone numbers saved on the address",
+        "Name on Card" => "Cardholder name (card numbers and CVVs are never extracted)",
+        "Network" => "Card network of a synced card (visa, mastercard, ...)",
+        "Last Four" => "Last four digits, only for synced cards Chrome keeps them for",
+        "Expiration Month" | "Expiration Year" => "Card expiry",
+        "Nickname" => "Name the user gave the card",
+        "Storage" => "Local (saved on the device) or Server (masked, synced from Google Pay)",
+        "Setting" => "Which setting the row describes",
+        "Details" => "Extra context for the setting",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn visit(i: u32) -> HistoryEntry {
+        HistoryEntry {
+            url: format!("https://example.com/{i}"),
+            visit_time: Utc.with_ymd_and_hms(2024, 3, 1, 0, i, 0).unwrap(),
+            visit_count: 1,
+            web_browser: "Chrome".to_string(),
+            record_id: i as i64,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_split_rows() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let entries: Vec<_> = (0..25).map(visit).collect();
+        let path = dir.path().join("history.csv");
+
+        let (rows, parts) = write_split(&entries, &path, 10, |e, p| write_csv(e, p, "%Y", None)).unwrap();
+        assert_eq!((rows, parts), (25, 3));
+        assert!(!path.exists());
+
+        let data_rows: Vec<usize> = (1..=3)
+            .map(|n| {
+                let part = dir.path().join(format!("history_{n:04}.csv"));
+                let mut rdr = csv::Reader::from_path(part).unwrap();
+                assert_eq!(rdr.headers().unwrap().get(0), Some("Visit Time"));
+                rdr.records().count()
+            })
+            .collect();
+        assert_eq!(data_rows, vec![10, 10, 5]);
+        assert!(!dir.path().join("history_0004.csv").exists());
+    }
+
+    #[test]
+    fn test_split_rows_disabled() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let entries: Vec<_> = (0..25).map(visit).collect();
+        let path = dir.path().join("history.csv");
+        let (rows, parts) = write_split(&entries, &path, 0, |e, p| write_csv(e, p, "%Y", None)).unwrap();
+        assert_eq!((rows, parts), (25, 1));
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_columns_projection() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("history.csv");
+        let names: Vec<String> = ["web_browser", "URL", "Visit Time"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let projection = Projection::new(ArtifactType::History, &names).unwrap();
+        assert_eq!(projection.headers(), ["Web Browser", "URL", "Visit Time"]);
+
+        write_csv(&[visit(1), visit(2)], &path, "%H:%M", Some(&projection)).unwrap();
+        let mut rdr = csv::Reader::from_path(&path).unwrap();
+        assert_eq!(
+            rdr.headers().unwrap().iter().collect::<Vec<_>>(),
+            ["Web Browser", "URL", "Visit Time"]
+        );
+        let rows: Vec<csv::StringRecord> = rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[1].iter().collect::<Vec<_>>(),
+            ["Chrome", "https://example.com/2", "00:02 UTC"]
+        );
+
+        let typo = vec!["URL".to_string(), "Visit Tyme".to_string()];
+        let err = Projection::new(ArtifactType::History, &typo).unwrap_err();
+        assert!(err.to_string().contains("Visit Tyme"));
+        assert!(Projection::matching(ArtifactType::Cookies, &typo).is_none());
+        assert!(check_columns([ArtifactType::Cookies, ArtifactType::History], &names).is_ok());
+        assert!(check_columns([ArtifactType::Cookies], &names[1..]).is_err());
+    }
+
+    #[test]
+    fn test_json_and_jsonl_records_are_keyed_by_headers() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let entries = [visit(1), visit(2)];
+
+        let jsonl = dir.path().join("history.jsonl");
+        write_csv(&entries, &jsonl, "%H:%M", None).unwrap();
+        let text = std::fs::read_to_string(&jsonl).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let record: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert!(lines[0].starts_with(r#"{"Visit Time":"#));
+        assert_eq!(record.as_object().unwrap().len(), HISTORY_HEADERS.len());
+        assert_eq!(record["URL"], "https://example.com/1");
+        assert_eq!(
+            record["Visit Time"],
+            fmt_dt(&entries[0].visit_time, "%H:%M")
+        );
+        assert_eq!(record["NaturalLanguage"], linearize_entry(&entries[0]));
+
+        let json = dir.path().join("history.json");
+        let names = vec![
+            "Record ID".to_string(),
+            "URL".to_string(),
+            "NaturalLanguage".to_string(),
+        ];
+        let projection = Projection::new(ArtifactType::History, &names).unwrap();
+        write_csv(&entries, &json, "%H:%M", Some(&projection)).unwrap();
+        let text = std::fs::read_to_string(&json).unwrap();
+        // Keys in the selected order
+        assert!(
+            text.contains(r#"{"Record ID":"1","URL":"https://example.com/1","NaturalLanguage":"#)
+        );
+        let array: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(array.as_array().unwrap().len(), 2);
+        assert_eq!(array[1]["Record ID"], "2");
+
+        assert_eq!(
+            RecordFormat::for_path(Path::new("a.JSONL")),
+            RecordFormat::Jsonl
+        );
+        assert_eq!(
+            RecordFormat::for_path(Path::new("a.csv")),
+            RecordFormat::Csv
+        );
+    }
+
+    #[test]
+    fn test_arrow_ipc_round_trip() {
+        use arrow::array::{Array, StringArray, UInt32Array};
+        use arrow::ipc::reader::FileReader;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("arrow/history.arrow");
+        let entries = vec![visit(1), visit(2), visit(3)];
+        assert_eq!(write_arrow_ipc(&entries, &path).unwrap(), 3);
+
+        let reader = FileReader::try_new(File::open(&path).unwrap(), None).unwrap();
+        assert_eq!(*reader.schema(), history_parquet_schema());
+        let batches: Vec<RecordBatch> = reader.map(|b| b.unwrap()).collect();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 3);
+
+        let urls = batch.column(1).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(urls.value(2), "https://example.com/3");
+        let counts = batch.column(3).as_any().downcast_ref::<UInt32Array>().unwrap();
+        assert_eq!(counts.value(0), 1);
+
+        // Like Parquet, no file is written for an empty artifact
+        let empty = dir.path().join("arrow/empty.arrow");
+        assert_eq!(write_arrow_ipc::<HistoryEntry>(&[], &empty).unwrap(), 0);
+        assert!(!empty.exists());
+    }
+
+    #[test]
+    fn test_timestamps_carry_their_zone() {
+        // 2024-03-10 12:00 UTC is 08:00 EDT, the day US daylight time starts
+        let noon = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+        let fmt = "%m/%d/%Y %I:%M:%S %p";
+        assert_eq!(format_in_zone(&noon, fmt, None), "03/10/2024 12:00:00 PM UTC");
+        assert_eq!(
+            format_in_zone(&noon, fmt, Some(chrono_tz::America::New_York)),
+            "03/10/2024 08:00:00 AM EDT"
+        );
+        let winter = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        assert_eq!(
+            format_in_zone(&winter, "%Y-%m-%d %H:%M", Some(chrono_tz::America::New_York)),
+            "2024-01-15 07:00 EST"
+        );
+    }
+
+    #[test]
+    fn test_activity_heatmap_buckets_in_zone() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("heatmap.csv");
+        // 2024-03-01 was a Friday; 00:05 UTC is Thursday 19:05 in New York (EST)
+        let entries = vec![visit(5), visit(6), visit(7)];
+
+        let counted = write_activity_heatmap(&entries, &path, &Utc).unwrap();
+        assert_eq!(counted, 3);
+        let mut rdr = csv::Reader::from_path(&path).unwrap();
+        assert_eq!(rdr.headers().unwrap().len(), 25);
+        let rows: Vec<csv::StringRecord> = rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(rows.len(), 7);
+        assert_eq!(&rows[4][0], "Friday");
+        assert_eq!(&rows[4][1], "3");
+
+        write_activity_heatmap(&entries, &path, &chrono_tz::America::New_York).unwrap();
+        let rows: Vec<csv::StringRecord> = csv::Reader::from_path(&path)
+            .unwrap()
+            .records()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(&rows[3][0], "Thursday");
+        assert_eq!(&rows[3][20], "3");
+        assert_eq!(&rows[4][1], "0");
+    }
+
+    #[test]
+    fn test_domain_summary_groups_by_domain_and_day() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("summary.csv");
+        let mut mail = visit(7);
+        mail.url = "https://mail.example.com/inbox".to_string();
+        let mut repeat = visit(8);
+        repeat.url = "https://example.com/5".to_string();
+        let mut next_day = visit(9);
+        next_day.url = "https://news.bbc.co.uk/".to_string();
+        next_day.visit_time += chrono::Duration::days(1);
+        let mut blank = visit(10);
+        blank.url = "about:blank".to_string();
+        let entries = vec![visit(6), visit(5), mail, repeat, next_day, blank];
+
+        let rows = write_domain_summary_csv(&entries, &path, "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(rows, 2);
+        let mut rdr = csv::Reader::from_path(&path).unwrap();
+        assert_eq!(rdr.headers().unwrap(), DOMAIN_SUMMARY_HEADERS);
+        let rows: Vec<csv::StringRecord> = rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(&rows[0][0], "bbc.co.uk");
+        assert_eq!(&rows[0][1], "2024-03-02");
+        assert_eq!(&rows[1][0], "example.com");
+        assert_eq!(&rows[1][1], "2024-03-01");
+        assert_eq!(&rows[1][2], "4");
+        assert_eq!(&rows[1][3], "2024-03-01 00:05:00 UTC");
+        assert_eq!(&rows[1][4], "2024-03-01 00:08:00 UTC");
+        assert_eq!(&rows[1][5], "3");
+    }
+
+    #[test]
+    fn test_timeline_csv_merges_artifacts_in_time_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("timeline.csv");
+        let mut hsts = visit(9);
+        hsts.visit_type = "HSTS Record".to_string();
+        let mut alice = visit(2);
+        alice.user_profile = "alice".to_string();
+        let set = ArtifactSet {
+            history: vec![visit(7), hsts, alice],
+            extensions: vec![ExtensionEntry {
+                extension_id: "abc".to_string(),
+                name: "Helper".to_string(),
+                version: "1.0".to_string(),
+                enabled: true,
+                web_browser: "Chrome".to_string(),
+                user_profile: "alice".to_string(),
+                ..Default::default()
+            }],
+            favicons: vec![FaviconEntry {
+                page_url: "https://example.com/7".to_string(),
+                icon_url: "https://example.com/favicon.ico".to_string(),
+                width: 16,
+                expire_time: Some(Utc.with_ymd_and_hms(2024, 3, 1, 0, 5, 0).unwrap()),
+                web_browser: "Firefox".to_string(),
+                user_profile: "bob".to_string(),
+                ..Default::default()
+            }],
+            ..ArtifactSet::default()
+        };
+
+        assert_eq!(write_timeline_csv(&set, &path, "%H:%M").unwrap(), 5);
+        let mut rdr = csv::Reader::from_path(&path).unwrap();
+        assert_eq!(rdr.headers().unwrap(), TIMELINE_HEADERS);
+        let rows: Vec<csv::StringRecord> = rdr.records().map(|r| r.unwrap()).collect();
+        let summary: Vec<(&str, &str, &str, &str)> = rows
+            .iter()
+            .map(|r| (&r[0], &r[1], &r[2], &r[3]))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("00:02 UTC", "History", "Chrome", "alice"),
+                ("00:05 UTC", "Favicons", "Firefox", "bob"),
+                ("00:07 UTC", "History", "Chrome", ""),
+                ("00:09 UTC", "HSTS Records", "Chrome", ""),
+                ("", "Extensions", "Chrome", "alice"),
+            ]
+        );
+        assert_eq!(&rows[2][4], linearize_entry(&visit(7)));
+    }
+
+    #[test]
+    fn test_xlsx_cell_typing() {
+        assert_eq!(xlsx_column("Visit Time"), XlsxColumn::DateTime);
+        assert_eq!(xlsx_column("Day"), XlsxColumn::Date);
+        assert_eq!(xlsx_column("Total Bytes"), XlsxColumn::Number);
+        assert_eq!(xlsx_column("Value"), XlsxColumn::Text);
+
+        let visit = "2024-03-01 00:05:00.250000 EST".to_string();
+        let visit = xlsx_cell(XlsxColumn::DateTime, visit);
+        let expected = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_milli_opt(0, 5, 0, 250)
+            .unwrap();
+        assert_eq!(visit, Cell::DateTime(expected));
+        assert_eq!(
+            xlsx_cell(XlsxColumn::Number, "42".into()),
+            Cell::Number(42.0)
+        );
+        // Hashed record IDs are longer than Excel can hold exactly
+        assert_eq!(
+            xlsx_cell(XlsxColumn::Number, "-4611686018427387904".into()),
+            Cell::Text("-4611686018427387904".into())
+        );
+        assert_eq!(
+            xlsx_cell(XlsxColumn::Text, "007".into()),
+            Cell::Text("007".into())
+        );
+        assert_eq!(xlsx_cell(XlsxColumn::Number, String::new()), Cell::Empty);
+    }
+
+    #[test]
+    fn test_write_xlsx_one_sheet_per_artifact() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("report.xlsx");
+        assert_eq!(write_xlsx(&ArtifactSet::default(), &path).unwrap(), 0);
+        assert!(!path.exists());
+
+        let set = ArtifactSet {
+            history: vec![visit(1), visit(2)],
+            ..ArtifactSet::default()
+        };
+        assert_eq!(write_xlsx(&set, &path).unwrap(), 2);
+        assert!(std::fs::read(&path).unwrap().starts_with(b"PK\x03\x04"));
+    }
+
+    #[test]
+    fn test_html_report_timeline_and_tables() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("report.html");
+        let mut later = visit(1);
+        later.visit_time = Utc.with_ymd_and_hms(2024, 3, 2, 9, 30, 0).unwrap();
+        later.title = "<script>alert(1)</script>".to_string();
+        let set = ArtifactSet {
+            history: vec![later, visit(5)],
+            favicons: vec![FaviconEntry {
+                page_url: "https://example.com/5".to_string(),
+                icon_url: "https://example.com/favicon.ico".to_string(),
+                width: 16,
+                expire_time: Some(Utc.with_ymd_and_hms(2024, 3, 1, 0, 6, 0).unwrap()),
+                web_browser: "Firefox".to_string(),
+                ..Default::default()
+            }],
+            ..ArtifactSet::default()
+        };
+
+        assert_eq!(write_html_report(&set, &path).unwrap(), 3);
+        let html = std::fs::read_to_string(&path).unwrap();
+        // No external resources, and cell text is escaped
+        assert!(!html.contains("src=") && !html.contains("<link"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!html.contains("<script>alert"));
+
+        // One collapsible section per day, in order, with the merged events
+        let first = html.find("<summary>2024-03-01 (Fri) UTC &mdash; 2 event(s)").unwrap();
+        let second = html.find("<summary>2024-03-02 (Sat) UTC &mdash; 1 event(s)").unwrap();
+        assert!(first < second);
+        let visit_row = html.find("<td>00:05:00 UTC</td><td>History</td>").unwrap();
+        let icon_row = html.find("<td>00:06:00 UTC</td><td>Favicons</td>").unwrap();
+        assert!(visit_row < icon_row && icon_row < second);
+
+        // Per-artifact tables with the CSV columns, less NaturalLanguage
+        assert!(html.contains("<h2>History (2 row(s))</h2>"));
+        assert!(html.contains("<h2>Favicons (1 row(s))</h2>"));
+        assert!(!html.contains("<h2>Cookies"));
+        assert!(html.contains("<th>Visit Time</th>"));
+        assert!(!html.contains("<th>NaturalLanguage</th>"));
+        assert!(html.contains("<td>2024-03-02 09:30:00 UTC</td>"));
+    }
+
+    #[test]
+    fn test_artifact_schema_matches_writers() {
+        for artifact in ArtifactType::ALL {
+            let schema = artifact_schema(artifact);
+            let (headers, parquet) = artifact_columns(artifact);
+            assert_eq!(schema.csv.len(), headers.len());
+            assert_eq!(schema.parquet.len(), parquet.fields().len());
+            for col in &schema.csv {
+                assert!(
+                    !col.description.is_empty(),
+                    "{} has no description",
+                    col.name
+                );
+            }
+        }
+
+        let history = artifact_schema(ArtifactType::History);
+        assert_eq!(history.csv[0].name, "Visit Time");
+        assert_eq!(history.csv[3].name, "Visit Count");
+        assert_eq!(history.csv[3].data_type, "UInt32");
+        assert_eq!(history.parquet[13].name, "RecordID");
+        assert_eq!(history.parquet[13].data_type, "Int64");
+
+        let json = serde_json::to_value(&history).unwrap();
+        assert_eq!(json["csv"][3]["type"], "UInt32");
+        assert_eq!(json["artifact"], "history");
+    }
+
+    #[test]
+    fn test_history_columns_appended_in_lockstep() {
+        let (headers, parquet) = artifact_columns(ArtifactType::History);
+        assert_eq!(headers[14], "NaturalLanguage");
+        let fields: Vec<String> = parquet
+            .fields()
+            .iter()
+            .map(|f| squash_name(f.name()))
+            .collect();
+        let csv: Vec<String> = headers.iter().map(|h| squash_name(h)).collect();
+        assert_eq!(fields, csv);
+    }
+
+    #[test]
+    fn test_parquet_columns_in_csv_order() {
+        for artifact in ArtifactType::ALL {
+            let (headers, parquet) = artifact_columns(artifact);
+            let fields: Vec<String> = parquet
+                .fields()
+                .iter()
+                .map(|f| squash_name(f.name()))
+                .collect();
+            // Parquet leaves some columns out but keeps the rest in CSV order
+            let csv: Vec<String> = headers
+                .iter()
+                .map(|h| squash_name(h))
+                .filter(|h| fields.contains(h))
+                .collect();
+            assert_eq!(fields, csv, "{artifact:?}");
+        }
+    }
+
+    #[test]
+    fn test_bookmark_columns_appended_in_lockstep() {
+        let (headers, parquet) = artifact_columns(ArtifactType::Bookmarks);
+        // Columns added since the first release come after the original ones
+        assert_eq!(&headers[..3], ["Date Added", "Date Last Used", "URL"]);
+        assert_eq!(headers[10], "NaturalLanguage");
+        let fields: Vec<String> = parquet
+            .fields()
+            .iter()
+            .map(|f| squash_name(f.name()))
+            .collect();
+        assert_eq!(fields[8], "naturallanguage");
+        // Parquet has the same columns in the same order, less Browser Profile and Source File
+        let csv: Vec<String> = headers
+            .iter()
+            .filter(|h| !matches!(**h, "Browser Profile" | "Source File"))
+            .map(|h| squash_name(h))
+            .collect();
+        assert_eq!(fields, csv);
+    }
+}
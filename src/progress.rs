@@ -0,0 +1,105 @@
+//! Progress reporting for long scans (`--progress`).
+//!
+//! The scanner and the extraction loop report through [`ScanProgress`]; library callers
+//! can plug in their own reporter, and the CLI draws a single status line on stderr.
+
+use std::io::{IsTerminal, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Receives progress while a scan runs. Every method defaults to doing nothing, and
+/// may be called from several extraction threads at once.
+pub trait ScanProgress: Sync {
+    /// The directory walk has looked at `files` files so far.
+    fn files_walked(&self, _files: usize) {}
+
+    /// `done` of `total` artifacts have been extracted.
+    fn artifacts_extracted(&self, _done: usize, _total: usize) {}
+
+    /// The scan is over.
+    fn finish(&self) {}
+}
+
+/// Reports nothing.
+pub struct NoProgress;
+
+impl ScanProgress for NoProgress {}
+
+/// Minimum time between redraws, so walking many small files doesn't flood the terminal.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A status line on stderr, redrawn in place ("Walking: N files", then
+/// "Extracting: N/M artifacts"). Draws nothing unless stdout is a terminal, so piped
+/// or redirected output stays clean.
+pub struct TerminalProgress {
+    enabled: bool,
+    /// When the line was last drawn; `None` before the first draw
+    last_draw: Mutex<Option<Instant>>,
+}
+
+impl TerminalProgress {
+    /// Report when `requested` and stdout is a terminal.
+    pub fn new(requested: bool) -> Self {
+        Self {
+            enabled: requested && std::io::stdout().is_terminal(),
+            last_draw: Mutex::new(None),
+        }
+    }
+
+    /// Redraw the line with `status`, unless it was drawn very recently. `force`
+    /// draws regardless, for the last update of a phase.
+    fn draw(&self, status: &str, force: bool) {
+        if !self.enabled {
+            return;
+        }
+        let mut last_draw = self.last_draw.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        if !force && last_draw.is_some_and(|t| now.duration_since(t) < REDRAW_INTERVAL) {
+            return;
+        }
+        *last_draw = Some(now);
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(stderr, "\r\x1b[2K{}", status);
+        let _ = stderr.flush();
+    }
+}
+
+impl ScanProgress for TerminalProgress {
+    fn files_walked(&self, files: usize) {
+        self.draw(&walk_status(files), false);
+    }
+
+    fn artifacts_extracted(&self, done: usize, total: usize) {
+        self.draw(&extract_status(done, total), done == total);
+    }
+
+    fn finish(&self) {
+        if self.enabled {
+            let _ = writeln!(std::io::stderr());
+        }
+    }
+}
+
+fn walk_status(files: usize) -> String {
+    format!("Walking: {} file(s)", files)
+}
+
+fn extract_status(done: usize, total: usize) -> String {
+    format!("Extracting: {}/{} artifacts", done, total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_lines() {
+        assert_eq!(walk_status(12), "Walking: 12 file(s)");
+        assert_eq!(extract_status(3, 40), "Extracting: 3/40 artifacts");
+    }
+
+    #[test]
+    fn test_not_requested_is_disabled() {
+        assert!(!TerminalProgress::new(false).enabled);
+    }
+}
@@ -0,0 +1,275 @@
+//! Pattern-based URL redaction for shareable exports (`--redact-pattern`).
+//!
+//! Unlike filtering, redaction keeps the row: a visit to a banking or healthcare portal
+//! still shows up with its timestamps and counts, but the URL (or cookie host) is
+//! replaced with [`REDACTED`].
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::browsers::{
+    BookmarkEntry, BrowserSettingsEntry, CookieEntry, DailyVisitEntry, DownloadEntry, FaviconEntry,
+    HistoryEntry, KeywordSearchEntry, LoginEntry, PredictorEntry, ShortcutEntry, TopSiteEntry,
+};
+
+/// Replacement written in place of a matching value.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Compiled `--redact-pattern` regexes shared by every artifact in a run.
+pub struct Redactor {
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    /// Compile the patterns; an invalid regex is an error naming it.
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|p| {
+                Regex::new(p.as_ref())
+                    .with_context(|| format!("Invalid --redact-pattern '{}'", p.as_ref()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Replace `value` with [`REDACTED`] if any pattern matches it. Returns whether it did.
+    fn redact_in_place(&self, value: &mut String) -> bool {
+        if value.is_empty() || !self.patterns.iter().any(|p| p.is_match(value)) {
+            return false;
+        }
+        *value = REDACTED.to_string();
+        true
+    }
+
+    /// Redact every entry in `entries`. Returns the number of rows changed.
+    pub fn apply<T: Redact>(&self, entries: &mut [T]) -> usize {
+        let mut redacted = 0;
+        for e in entries {
+            if e.redact(self) {
+                redacted += 1;
+            }
+        }
+        redacted
+    }
+}
+
+/// An entry with URL-like fields subject to `--redact-pattern`.
+pub trait Redact {
+    /// Redact matching fields; true if any was replaced.
+    fn redact(&mut self, r: &Redactor) -> bool;
+}
+
+/// Redact each of `fields` (all of them, not just up to the first match).
+fn redact_fields(r: &Redactor, fields: &mut [&mut String]) -> bool {
+    let mut any = false;
+    for f in fields.iter_mut() {
+        any |= r.redact_in_place(f);
+    }
+    any
+}
+
+impl Redact for HistoryEntry {
+    fn redact(&mut self, r: &Redactor) -> bool {
+        let url_hit = r.redact_in_place(&mut self.url);
+        if url_hit {
+            // The page title usually names the site as plainly as the URL does
+            self.title = REDACTED.to_string();
+        }
+        redact_fields(
+            r,
+            &mut [
+                &mut self.visited_from,
+                &mut self.journey,
+                &mut self.canonical_url,
+            ],
+        ) | url_hit
+    }
+}
+
+impl Redact for DownloadEntry {
+    fn redact(&mut self, r: &Redactor) -> bool {
+        let hit = redact_fields(
+            r,
+            &mut [
+                &mut self.url,
+                &mut self.url_chain,
+                &mut self.referrer,
+                &mut self.tab_url,
+                &mut self.inferred_referrer,
+            ],
+        );
+        if hit && !self.page_title.is_empty() {
+            // Title of the page the download came from
+            self.page_title = REDACTED.to_string();
+        }
+        hit
+    }
+}
+
+impl Redact for CookieEntry {
+    fn redact(&mut self, r: &Redactor) -> bool {
+        r.redact_in_place(&mut self.host)
+    }
+}
+
+impl Redact for BookmarkEntry {
+    fn redact(&mut self, r: &Redactor) -> bool {
+        let hit = r.redact_in_place(&mut self.url);
+        if hit {
+            self.title = REDACTED.to_string();
+        }
+        hit
+    }
+}
+
+impl Redact for ShortcutEntry {
+    fn redact(&mut self, r: &Redactor) -> bool {
+        let hit = r.redact_in_place(&mut self.url);
+        if hit {
+            // The suggestion text is usually the URL or page title, and what was typed
+            // often spells out the site
+            self.contents = REDACTED.to_string();
+            self.text = REDACTED.to_string();
+        }
+        hit
+    }
+}
+
+impl Redact for FaviconEntry {
+    fn redact(&mut self, r: &Redactor) -> bool {
+        redact_fields(r, &mut [&mut self.page_url, &mut self.icon_url])
+    }
+}
+
+impl Redact for TopSiteEntry {
+    fn redact(&mut self, r: &Redactor) -> bool {
+        let url_hit = r.redact_in_place(&mut self.url);
+        if url_hit {
+            self.title = REDACTED.to_string();
+        }
+        url_hit
+    }
+}
+
+impl Redact for PredictorEntry {
+    fn redact(&mut self, r: &Redactor) -> bool {
+        let hit = r.redact_in_place(&mut self.url);
+        if hit {
+            // What was typed often spells out the site
+            self.user_text = REDACTED.to_string();
+        }
+        hit
+    }
+}
+
+impl Redact for KeywordSearchEntry {
+    fn redact(&mut self, r: &Redactor) -> bool {
+        let hit = r.redact_in_place(&mut self.url);
+        if hit {
+            // The results page title and the term itself name what was looked up
+            self.title = REDACTED.to_string();
+            self.search_term = REDACTED.to_string();
+            self.normalized_term = REDACTED.to_string();
+        }
+        hit
+    }
+}
+
+impl Redact for DailyVisitEntry {
+    fn redact(&mut self, r: &Redactor) -> bool {
+        let hit = r.redact_in_place(&mut self.url);
+        if hit {
+            self.title = REDACTED.to_string();
+        }
+        hit
+    }
+}
+
+impl Redact for LoginEntry {
+    fn redact(&mut self, r: &Redactor) -> bool {
+        redact_fields(
+            r,
+            &mut [
+                &mut self.origin_url,
+                &mut self.action_url,
+                &mut self.signon_realm,
+            ],
+        )
+    }
+}
+
+impl Redact for BrowserSettingsEntry {
+    fn redact(&mut self, r: &Redactor) -> bool {
+        // Startup pages, the homepage and search engine URLs
+        redact_fields(r, &mut [&mut self.value, &mut self.details])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn visit(url: &str, title: &str) -> HistoryEntry {
+        HistoryEntry {
+            url: url.to_string(),
+            title: title.to_string(),
+            visit_time: Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap(),
+            visit_count: 4,
+            visit_type: "Typed".to_string(),
+            web_browser: "Chrome".to_string(),
+            user_profile: "alice".to_string(),
+            url_length: url.len(),
+            typed_count: 2,
+            record_id: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_redact_banking_domain() {
+        let r = Redactor::new(&[r"(?i)\b(chase|wellsfargo)\.com\b"]).unwrap();
+        let mut history = vec![
+            visit(
+                "https://secure.chase.com/web/auth/dashboard",
+                "Chase Online",
+            ),
+            visit("https://news.example/chase-bank-earnings", "Earnings"),
+        ];
+        history[1].visited_from = "https://WellsFargo.com/".to_string();
+        assert_eq!(r.apply(&mut history), 2);
+
+        // The row stays, with its time and counts; only the sensitive values go
+        assert_eq!(history[0].url, REDACTED);
+        assert_eq!(history[0].title, REDACTED);
+        assert_eq!(history[0].visit_count, 4);
+        assert_eq!(history[0].typed_count, 2);
+        assert_eq!(
+            history[0].visit_time,
+            Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap()
+        );
+        assert_eq!(history[1].url, "https://news.example/chase-bank-earnings");
+        assert_eq!(history[1].visited_from, REDACTED);
+
+        let mut cookies = vec![CookieEntry {
+            host: ".chase.com".to_string(),
+            name: "session".to_string(),
+            path: "/".to_string(),
+            value: "x".to_string(),
+            creation_time: Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap(),
+            is_secure: true,
+            is_httponly: true,
+            is_persistent: true,
+            web_browser: "Chrome".to_string(),
+            user_profile: "alice".to_string(),
+            record_id: 1,
+            ..Default::default()
+        }];
+        assert_eq!(r.apply(&mut cookies), 1);
+        assert_eq!(cookies[0].host, REDACTED);
+        assert_eq!(cookies[0].name, "session");
+
+        assert!(Redactor::new(&["("]).is_err());
+    }
+}
@@ -0,0 +1,529 @@
+//! The extractor registry: one [`ArtifactExtractor`] per browser/artifact pair.
+//!
+//! [`crate::extract::extract_artifact`] looks a scanned artifact up here instead of
+//! matching on artifact type and browser, and single files (`webx extract`,
+//! [`crate::scanner::extract_history`]) and the history read for download referrers and
+//! cookie hosts go through it too, so supporting a new browser means
+//! implementing the trait for each of its artifacts and listing the extractors in
+//! [`registry`].
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::browsers::{
+    self, ArtifactType, AutofillEntry, AutofillProfileEntry, BookmarkEntry, BrowserArtifact,
+    BrowserSettingsEntry, BrowserType, CookieEntry, CreditCardEntry, DailyVisitEntry,
+    DownloadEntry, ExtensionEntry, FaviconEntry, HistoryEntry, KeywordSearchEntry, LoginEntry,
+    PredictorEntry, ShortcutEntry, TopSiteEntry,
+};
+use crate::extract::{ExtractOptions, Extracted};
+
+/// Reads one artifact type for the browsers it supports.
+pub trait ArtifactExtractor {
+    /// Entry type the extractor produces
+    type Item;
+
+    /// Artifact type this extractor reads
+    const ARTIFACT: ArtifactType;
+
+    /// Whether this extractor reads `browser`'s files
+    fn supports(browser: BrowserType) -> bool;
+
+    /// Whether this extractor reads the scanned `artifact`: its type and browser match.
+    /// Extractors for one particular file (Opera's `typed_history.xml`, Firefox's
+    /// session restore) narrow this further.
+    fn handles(artifact: &BrowserArtifact) -> bool {
+        artifact.artifact_type == Self::ARTIFACT && Self::supports(artifact.browser)
+    }
+
+    /// Extract every entry from the file at `path`, attributed to `username`.
+    fn extract(&self, path: &Path, username: &str, browser: BrowserType)
+        -> Result<Vec<Self::Item>>;
+}
+
+/// An [`ArtifactExtractor`] with its entries wrapped in [`Extracted`], so extractors of
+/// different entry types can share one registry.
+pub trait DynExtractor: Send + Sync {
+    /// Extractor type name, e.g. `ChromeHistory`
+    fn name(&self) -> &'static str;
+    fn artifact_type(&self) -> ArtifactType;
+    fn handles(&self, artifact: &BrowserArtifact) -> bool;
+    fn extract_entries(
+        &self,
+        path: &Path,
+        username: &str,
+        browser: BrowserType,
+    ) -> Result<Extracted>;
+}
+
+impl<E> DynExtractor for E
+where
+    E: ArtifactExtractor + Send + Sync,
+    Vec<E::Item>: Into<Extracted>,
+{
+    fn name(&self) -> &'static str {
+        let path = std::any::type_name::<E>();
+        path.rsplit("::").next().unwrap_or(path)
+    }
+
+    fn artifact_type(&self) -> ArtifactType {
+        E::ARTIFACT
+    }
+
+    fn handles(&self, artifact: &BrowserArtifact) -> bool {
+        E::handles(artifact)
+    }
+
+    fn extract_entries(
+        &self,
+        path: &Path,
+        username: &str,
+        browser: BrowserType,
+    ) -> Result<Extracted> {
+        ArtifactExtractor::extract(self, path, username, browser).map(Into::into)
+    }
+}
+
+/// Every extractor, with `opts` applied. Where several handle the same artifact the
+/// first one listed wins, so file-specific extractors come before the general ones.
+pub fn registry(opts: &ExtractOptions) -> Vec<Box<dyn DynExtractor>> {
+    vec![
+        Box::new(OperaTypedHistory),
+        Box::new(OperaTypedSearches),
+        Box::new(OperaNotes),
+        Box::new(FirefoxSessionHistory),
+        Box::new(ChromeHistory),
+        Box::new(FirefoxHistory),
+        Box::new(SafariHistory {
+            include_orphans: opts.include_orphans,
+        }),
+        Box::new(WebCacheHistory),
+        Box::new(ChromeDownloads),
+        Box::new(FirefoxDownloads),
+        Box::new(SafariDownloads),
+        Box::new(ChromeKeywordSearches),
+        Box::new(ChromeCookies),
+        Box::new(FirefoxCookies),
+        Box::new(SafariCookies),
+        Box::new(ChromeAutofill),
+        Box::new(FirefoxAutofill),
+        Box::new(ChromeBookmarks),
+        Box::new(FirefoxBookmarks),
+        Box::new(ChromeLogins),
+        Box::new(FirefoxLogins),
+        Box::new(ChromeExtensions),
+        Box::new(FirefoxExtensions),
+        Box::new(ChromeSettings),
+        Box::new(ChromeHsts),
+        Box::new(ChromeShortcuts),
+        Box::new(ChromeTopSites),
+        Box::new(ChromePredictor),
+        Box::new(ChromeAutofillProfiles),
+        Box::new(ChromeCreditCards),
+        Box::new(FirefoxFavicons),
+        Box::new(SafariDailyVisits),
+    ]
+}
+
+/// The extractor in `extractors` for a scanned artifact, if any.
+pub fn find<'a>(
+    extractors: &'a [Box<dyn DynExtractor>],
+    artifact: &BrowserArtifact,
+) -> Option<&'a dyn DynExtractor> {
+    extractors
+        .iter()
+        .find(|e| e.handles(artifact))
+        .map(|e| e.as_ref())
+}
+
+fn chromium(browser: BrowserType) -> bool {
+    browser.is_chromium()
+}
+
+fn firefox(browser: BrowserType) -> bool {
+    browser.is_firefox()
+}
+
+fn safari(browser: BrowserType) -> bool {
+    browser == BrowserType::Safari
+}
+
+fn webcache(browser: BrowserType) -> bool {
+    browser == BrowserType::InternetExplorer
+}
+
+/// Declares a unit extractor that calls a module's `extract` function. Chromium
+/// extractors take the browser so rows are labeled with the right fork.
+macro_rules! extractor {
+    (@impl $(#[$doc:meta])* $name:ident: $artifact:ident => $item:ty, $supports:ident,
+        |$path:ident, $username:ident, $browser:ident| $call:expr) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct $name;
+
+        impl ArtifactExtractor for $name {
+            type Item = $item;
+            const ARTIFACT: ArtifactType = ArtifactType::$artifact;
+
+            fn supports(browser: BrowserType) -> bool {
+                $supports(browser)
+            }
+
+            fn extract(
+                &self,
+                $path: &Path,
+                $username: &str,
+                $browser: BrowserType,
+            ) -> Result<Vec<$item>> {
+                $call
+            }
+        }
+    };
+    ($(#[$doc:meta])* $name:ident: $artifact:ident => $item:ty, $supports:ident, $f:path) => {
+        extractor!(@impl $(#[$doc])* $name: $artifact => $item, $supports,
+            |path, username, _browser| $f(path, username));
+    };
+    ($(#[$doc:meta])* $name:ident: $artifact:ident => $item:ty, $supports:ident, $f:path, browser) => {
+        extractor!(@impl $(#[$doc])* $name: $artifact => $item, $supports,
+            |path, username, browser| $f(path, username, Some(browser)));
+    };
+}
+
+extractor!(
+    /// Chromium `History` / `Archived History` visits
+    ChromeHistory: History => HistoryEntry, chromium, browsers::chrome::extract, browser
+);
+extractor!(
+    /// Firefox `places.sqlite` visits
+    FirefoxHistory: History => HistoryEntry, firefox, browsers::firefox::extract
+);
+extractor!(
+    /// IE / Edge Legacy `WebCacheV01.dat` history
+    WebCacheHistory: History => HistoryEntry, webcache, browsers::webcache::extract
+);
+extractor!(
+    /// Chromium `History` downloads
+    ChromeDownloads: Downloads => DownloadEntry, chromium,
+    browsers::chrome_downloads::extract, browser
+);
+extractor!(
+    /// Firefox `places.sqlite` downloads
+    FirefoxDownloads: Downloads => DownloadEntry, firefox, browsers::firefox_downloads::extract
+);
+extractor!(
+    /// Safari `Downloads.plist`
+    SafariDownloads: Downloads => DownloadEntry, safari, browsers::safari_downloads::extract
+);
+extractor!(
+    /// Chromium `keyword_search_terms`
+    ChromeKeywordSearches: KeywordSearches => KeywordSearchEntry, chromium,
+    browsers::chrome_keywords::extract, browser
+);
+extractor!(
+    /// Chromium `Cookies`
+    ChromeCookies: Cookies => CookieEntry, chromium, browsers::chrome_cookies::extract, browser
+);
+extractor!(
+    /// Firefox `cookies.sqlite`
+    FirefoxCookies: Cookies => CookieEntry, firefox, browsers::firefox_cookies::extract
+);
+extractor!(
+    /// Safari `Cookies.binarycookies`
+    SafariCookies: Cookies => CookieEntry, safari, browsers::safari_cookies::extract
+);
+extractor!(
+    /// Chromium `Web Data` form history
+    ChromeAutofill: Autofill => AutofillEntry, chromium, browsers::chrome_autofill::extract, browser
+);
+extractor!(
+    /// Firefox `formhistory.sqlite`
+    FirefoxAutofill: Autofill => AutofillEntry, firefox, browsers::firefox_autofill::extract
+);
+extractor!(
+    /// Chromium `Bookmarks` / `Bookmarks.bak`
+    ChromeBookmarks: Bookmarks => BookmarkEntry, chromium,
+    browsers::chrome_bookmarks::extract, browser
+);
+extractor!(
+    /// Firefox `places.sqlite` bookmarks
+    FirefoxBookmarks: Bookmarks => BookmarkEntry, firefox, browsers::firefox_bookmarks::extract
+);
+extractor!(
+    /// Chromium `Login Data` metadata
+    ChromeLogins: LoginData => LoginEntry, chromium, browsers::chrome_logins::extract, browser
+);
+extractor!(
+    /// Firefox `logins.json` metadata
+    FirefoxLogins: LoginData => LoginEntry, firefox, browsers::firefox_logins::extract
+);
+extractor!(
+    /// Chromium `Preferences` extensions
+    ChromeExtensions: Extensions => ExtensionEntry, chromium,
+    browsers::chrome_extensions::extract, browser
+);
+extractor!(
+    /// Firefox `extensions.json`
+    FirefoxExtensions: Extensions => ExtensionEntry, firefox,
+    browsers::firefox_extensions::extract
+);
+extractor!(
+    /// Chromium `Preferences` startup, homepage, search and account settings
+    ChromeSettings: BrowserSettings => BrowserSettingsEntry, chromium,
+    browsers::chrome_prefs::extract, browser
+);
+extractor!(
+    /// Chromium `TransportSecurity`, as history rows
+    ChromeHsts: HstsRecords => HistoryEntry, chromium, browsers::chrome_hsts::extract, browser
+);
+extractor!(
+    /// Chromium `Shortcuts`
+    ChromeShortcuts: Shortcuts => ShortcutEntry, chromium,
+    browsers::chrome_shortcuts::extract, browser
+);
+extractor!(
+    /// Chromium `Top Sites`
+    ChromeTopSites: TopSites => TopSiteEntry, chromium, browsers::chrome_topsites::extract, browser
+);
+extractor!(
+    /// Chromium `Network Action Predictor`
+    ChromePredictor: Predictor => PredictorEntry, chromium,
+    browsers::chrome_predictor::extract, browser
+);
+extractor!(
+    /// Chromium `Web Data` saved addresses
+    ChromeAutofillProfiles: AutofillProfiles => AutofillProfileEntry, chromium,
+    browsers::chrome_autofill_profiles::extract, browser
+);
+extractor!(
+    /// Chromium `Web Data` payment card metadata
+    ChromeCreditCards: CreditCards => CreditCardEntry, chromium,
+    browsers::chrome_autofill_profiles::extract_credit_cards, browser
+);
+extractor!(
+    /// Firefox `favicons.sqlite`
+    FirefoxFavicons: Favicons => FaviconEntry, firefox, browsers::firefox_favicons::extract
+);
+extractor!(
+    /// Safari `History.db` per-day visit counts
+    SafariDailyVisits: DailyVisits => DailyVisitEntry, safari,
+    browsers::safari::extract_daily_visits
+);
+
+/// Opera's `typed_history.xml`: addresses typed into the address bar.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OperaTypedHistory;
+
+impl ArtifactExtractor for OperaTypedHistory {
+    type Item = HistoryEntry;
+    const ARTIFACT: ArtifactType = ArtifactType::History;
+
+    fn supports(browser: BrowserType) -> bool {
+        browser.is_chromium()
+    }
+
+    fn handles(artifact: &BrowserArtifact) -> bool {
+        artifact.artifact_type == Self::ARTIFACT && artifact.is_typed_history()
+    }
+
+    fn extract(
+        &self,
+        path: &Path,
+        username: &str,
+        _browser: BrowserType,
+    ) -> Result<Vec<HistoryEntry>> {
+        browsers::opera_typed::extract(path, username)
+    }
+}
+
+/// Opera's `typed_history.xml`: search terms typed into the address bar.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OperaTypedSearches;
+
+impl ArtifactExtractor for OperaTypedSearches {
+    type Item = KeywordSearchEntry;
+    const ARTIFACT: ArtifactType = ArtifactType::KeywordSearches;
+
+    fn supports(browser: BrowserType) -> bool {
+        browser.is_chromium()
+    }
+
+    fn handles(artifact: &BrowserArtifact) -> bool {
+        artifact.artifact_type == Self::ARTIFACT && artifact.is_typed_history()
+    }
+
+    fn extract(
+        &self,
+        path: &Path,
+        username: &str,
+        _browser: BrowserType,
+    ) -> Result<Vec<KeywordSearchEntry>> {
+        browsers::opera_typed::extract_searches(path, username)
+    }
+}
+
+/// Opera's `notes.adr`: notes saved in the browser.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OperaNotes;
+
+impl ArtifactExtractor for OperaNotes {
+    type Item = BookmarkEntry;
+    const ARTIFACT: ArtifactType = ArtifactType::Bookmarks;
+
+    fn supports(browser: BrowserType) -> bool {
+        browser.is_chromium()
+    }
+
+    fn handles(artifact: &BrowserArtifact) -> bool {
+        artifact.artifact_type == Self::ARTIFACT && artifact.is_opera_notes()
+    }
+
+    fn extract(
+        &self,
+        path: &Path,
+        username: &str,
+        _browser: BrowserType,
+    ) -> Result<Vec<BookmarkEntry>> {
+        browsers::opera_notes::extract(path, username)
+    }
+}
+
+/// Firefox session restore (`recovery.jsonlz4` and friends): open tabs and their
+/// back/forward history.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirefoxSessionHistory;
+
+impl ArtifactExtractor for FirefoxSessionHistory {
+    type Item = HistoryEntry;
+    const ARTIFACT: ArtifactType = ArtifactType::History;
+
+    fn supports(browser: BrowserType) -> bool {
+        browser.is_firefox()
+    }
+
+    fn handles(artifact: &BrowserArtifact) -> bool {
+        artifact.artifact_type == Self::ARTIFACT && artifact.is_session_restore()
+    }
+
+    fn extract(
+        &self,
+        path: &Path,
+        username: &str,
+        _browser: BrowserType,
+    ) -> Result<Vec<HistoryEntry>> {
+        browsers::firefox_sessions::extract(path, username)
+    }
+}
+
+/// Safari `History.db` visits, optionally with visits whose history item was deleted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SafariHistory {
+    pub include_orphans: bool,
+}
+
+impl ArtifactExtractor for SafariHistory {
+    type Item = HistoryEntry;
+    const ARTIFACT: ArtifactType = ArtifactType::History;
+
+    fn supports(browser: BrowserType) -> bool {
+        browser == BrowserType::Safari
+    }
+
+    fn extract(
+        &self,
+        path: &Path,
+        username: &str,
+        _browser: BrowserType,
+    ) -> Result<Vec<HistoryEntry>> {
+        if self.include_orphans {
+            browsers::safari::extract_with_orphans(path, username)
+        } else {
+            browsers::safari::extract(path, username)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact(artifact_type: ArtifactType, browser: BrowserType, file: &str) -> BrowserArtifact {
+        BrowserArtifact {
+            browser,
+            artifact_type,
+            username: "alice".to_string(),
+            profile_name: "Default".to_string(),
+            db_path: format!("/triage/alice/{file}"),
+        }
+    }
+
+    #[test]
+    fn test_file_specific_extractors_take_precedence() {
+        let extractors = registry(&ExtractOptions::default());
+        let name = |t, b, file| find(&extractors, &artifact(t, b, file)).map(|e| e.name());
+
+        let history = ArtifactType::History;
+        let opera = BrowserType::Opera;
+        assert_eq!(
+            name(history, opera, "typed_history.xml"),
+            Some("OperaTypedHistory")
+        );
+        assert_eq!(name(history, opera, "History"), Some("ChromeHistory"));
+        assert_eq!(
+            name(ArtifactType::KeywordSearches, opera, "typed_history.xml"),
+            Some("OperaTypedSearches")
+        );
+        assert_eq!(
+            name(ArtifactType::Bookmarks, opera, "notes.adr"),
+            Some("OperaNotes")
+        );
+        assert_eq!(
+            name(ArtifactType::Bookmarks, opera, "Bookmarks"),
+            Some("ChromeBookmarks")
+        );
+        let firefox = BrowserType::Firefox;
+        assert_eq!(
+            name(history, firefox, "recovery.jsonlz4"),
+            Some("FirefoxSessionHistory")
+        );
+        assert_eq!(
+            name(history, firefox, "places.sqlite"),
+            Some("FirefoxHistory")
+        );
+    }
+
+    #[test]
+    fn test_every_pair_has_at_most_one_general_extractor() {
+        let extractors = registry(&ExtractOptions::default());
+        let browsers = [
+            BrowserType::Chrome,
+            BrowserType::Opera,
+            BrowserType::Firefox,
+            BrowserType::TorBrowser,
+            BrowserType::Safari,
+            BrowserType::InternetExplorer,
+        ];
+        for artifact_type in ArtifactType::ALL {
+            for browser in browsers {
+                let a = artifact(artifact_type, browser, "file");
+                let count = extractors.iter().filter(|e| e.handles(&a)).count();
+                assert!(
+                    count <= 1,
+                    "{artifact_type:?} / {browser:?}: {count} extractors"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_supported_pairs() {
+        let extractors = registry(&ExtractOptions::default());
+        let has = |t, b| find(&extractors, &artifact(t, b, "file")).is_some();
+        assert!(has(ArtifactType::History, BrowserType::InternetExplorer));
+        assert!(has(ArtifactType::CreditCards, BrowserType::EdgeChromium));
+        assert!(has(ArtifactType::Favicons, BrowserType::TorBrowser));
+        assert!(has(ArtifactType::DailyVisits, BrowserType::Safari));
+        assert!(!has(ArtifactType::KeywordSearches, BrowserType::Safari));
+        assert!(!has(ArtifactType::Downloads, BrowserType::InternetExplorer));
+        assert!(!has(ArtifactType::Favicons, BrowserType::Chrome));
+    }
+}
@@ -1,7 +1,59 @@
-use std::path::Path;
+use anyhow::{bail, Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use log::debug;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-use crate::browsers::{ArtifactType, BrowserArtifact, BrowserType};
+use crate::browsers::{
+    self, strip_extended_length, to_extended_length, ArtifactType, BrowserArtifact, BrowserType,
+    HistoryEntry,
+};
+use crate::extract::{self, ExtractOptions, Extracted};
+use crate::progress::{NoProgress, ScanProgress};
+
+/// Options controlling the triage directory walk.
+#[derive(Debug, Default, Clone)]
+pub struct ScanOptions {
+    /// Paths matching any of these globs (and everything below them) are skipped.
+    pub exclude: Option<GlobSet>,
+}
+
+impl ScanOptions {
+    /// Build scan options from `--exclude-path` glob patterns (matched case-insensitively).
+    pub fn with_excludes(patterns: &[String]) -> Result<Self> {
+        if patterns.is_empty() {
+            return Ok(Self::default());
+        }
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = GlobBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .with_context(|| format!("Invalid --exclude-path glob: {pattern}"))?;
+            builder.add(glob);
+        }
+        Ok(Self {
+            exclude: Some(builder.build().context("Failed to build exclude globs")?),
+        })
+    }
+
+    /// Check a path against the exclude globs, both as-is and relative to the scan root.
+    /// Directories are also tested with a trailing separator so `dir/**` prunes `dir` itself.
+    fn is_excluded(&self, path: &Path, root: &Path, is_dir: bool) -> bool {
+        let globs = match &self.exclude {
+            Some(g) => g,
+            None => return false,
+        };
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        [path, relative].iter().any(|p| {
+            if globs.is_match(p) {
+                return true;
+            }
+            is_dir && globs.is_match(format!("{}/", p.to_string_lossy()))
+        })
+    }
+}
 
 /// Extract username from a file path by finding the segment after the LAST "Users/".
 /// Uses rfind to handle cases where triage data is stored under a local user's home dir
@@ -27,27 +79,228 @@ fn extract_profile_name(path: &Path) -> String {
         .to_string()
 }
 
+/// Read Chromium profile display names from `Local State` (`profile.info_cache`) in a
+/// user data directory, keyed by profile directory name. Missing or unreadable files
+/// give an empty map.
+pub fn chromium_profile_names(user_data_dir: &Path) -> HashMap<String, String> {
+    let path = user_data_dir.join("Local State");
+    let data = match std::fs::read_to_string(browsers::long_path(&path)) {
+        Ok(d) => d,
+        Err(_) => return HashMap::new(),
+    };
+    let root: serde_json::Value = match serde_json::from_str(&data) {
+        Ok(v) => v,
+        Err(e) => {
+            debug!("Unparseable Local State {}: {}", path.display(), e);
+            return HashMap::new();
+        }
+    };
+    root.pointer("/profile/info_cache")
+        .and_then(|v| v.as_object())
+        .map(|cache| {
+            cache
+                .iter()
+                .filter_map(|(dir, info)| {
+                    Some((dir.clone(), info.get("name")?.as_str()?.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Case-insensitive `--profile` match against a profile directory name or display name.
+fn profile_selected(profiles: &[String], dir_name: &str, display_name: Option<&String>) -> bool {
+    let matches = |name: &str| {
+        let name = name.to_lowercase();
+        profiles.iter().any(|p| p.to_lowercase() == name)
+    };
+    matches(dir_name) || display_name.is_some_and(|n| matches(n))
+}
+
+/// Whether the profile holding `db_path` is one of `profiles`, by directory name
+/// (`Profile 2`) or by the display name recorded in `Local State`.
+pub fn profile_matches(db_path: &Path, profiles: &[String]) -> bool {
+    let dir_name = extract_profile_name(db_path);
+    let names = db_path
+        .parent()
+        .and_then(Path::parent)
+        .map(chromium_profile_names)
+        .unwrap_or_default();
+    profile_selected(profiles, &dir_name, names.get(&dir_name))
+}
+
+/// Keep only artifacts from the profiles named in `profiles` (see [`profile_matches`]).
+/// Artifacts without a profile (e.g. IE WebCache) are dropped. Returns the number removed.
+pub fn retain_profiles(artifacts: &mut Vec<BrowserArtifact>, profiles: &[String]) -> usize {
+    // Local State is shared by every profile in a user data dir, so read it once per dir
+    let mut names_by_dir: HashMap<PathBuf, HashMap<String, String>> = HashMap::new();
+    let before = artifacts.len();
+    artifacts.retain(|a| {
+        if a.profile_name.is_empty() {
+            return false;
+        }
+        let display_name = Path::new(&a.db_path)
+            .parent()
+            .and_then(Path::parent)
+            .and_then(|user_data| {
+                names_by_dir
+                    .entry(user_data.to_path_buf())
+                    .or_insert_with(|| chromium_profile_names(user_data))
+                    .get(&a.profile_name)
+                    .cloned()
+            });
+        profile_selected(profiles, &a.profile_name, display_name.as_ref())
+    });
+    before - artifacts.len()
+}
+
+/// File names [`scan`] recognizes as artifacts; anything else is skipped without a
+/// closer look. Firefox session restore files are matched by
+/// [`browsers::firefox_sessions::is_session_file`].
+pub const ARTIFACT_FILES: &[&str] = &[
+    "History",
+    "Archived History",
+    "typed_history.xml",
+    "notes.adr",
+    "places.sqlite",
+    "History.db",
+    "Downloads.plist",
+    "WebCacheV01.dat",
+    "TransportSecurity",
+    "favicons.sqlite",
+    "Shortcuts",
+    "Network Action Predictor",
+    "Top Sites",
+    "Cookies",
+    "cookies.sqlite",
+    "Cookies.binarycookies",
+    "Web Data",
+    "formhistory.sqlite",
+    "Login Data",
+    "logins.json",
+    "Bookmarks",
+    "Bookmarks.bak",
+    "Preferences",
+    "extensions.json",
+];
+
+/// Files read alongside an artifact rather than scanned: Chromium profile names from
+/// `Local State`, and `Visited Links` next to `History`.
+pub const SIDECAR_FILES: &[&str] = &["Local State", "Visited Links"];
+
+/// Whether [`scan`] recognizes `file_name` as an artifact (see [`ARTIFACT_FILES`]).
+pub fn is_artifact_file(file_name: &str) -> bool {
+    ARTIFACT_FILES.contains(&file_name) || browsers::firefox_sessions::is_session_file(file_name)
+}
+
+/// The `artifact_type` artifact in the single file `path`, for `extract` and
+/// [`extract_history`]. The browser is `browser` when given, otherwise the one [`scan`]
+/// would attribute the file name to (`History` to the Chromium fork in the path,
+/// `places.sqlite` and session files to Firefox or Tor Browser, `History.db` to Safari,
+/// ...); `None` when neither says. The username and profile come from the path.
+pub fn file_artifact(
+    path: &Path,
+    artifact_type: ArtifactType,
+    browser: Option<BrowserType>,
+) -> Option<BrowserArtifact> {
+    let file_name = path.file_name()?.to_str()?;
+    let path_str = strip_extended_length(&path.to_string_lossy());
+    let path_lower = path_str.to_lowercase();
+    let browser = match browser {
+        Some(b) => b,
+        None => match file_name {
+            "typed_history.xml" | "notes.adr" => BrowserType::Opera,
+            "History" | "Archived History" | "Web Data" => detect_chromium_browser(&path_lower),
+            "places.sqlite" => detect_gecko_browser(&path_lower),
+            name if browsers::firefox_sessions::is_session_file(name) => {
+                detect_gecko_browser(&path_lower)
+            }
+            "History.db" => BrowserType::Safari,
+            "WebCacheV01.dat" => BrowserType::InternetExplorer,
+            _ => return None,
+        },
+    };
+    Some(BrowserArtifact {
+        browser,
+        artifact_type,
+        db_path: path_str,
+        profile_name: extract_profile_name(path),
+        username: extract_username(path),
+    })
+}
+
+/// Extract history from a single database file with the [`crate::registry`] extractor
+/// for its name (`History`, `Archived History`, `places.sqlite`, `History.db`,
+/// `WebCacheV01.dat`, Opera's `typed_history.xml` or a Firefox session file such as
+/// `recovery.jsonlz4`; see [`file_artifact`]). The username is taken from the path as
+/// during a scan. A SQLite file without the expected schema is an error rather than
+/// empty output.
+pub fn extract_history(path: &Path) -> Result<Vec<HistoryEntry>> {
+    if let Some(Err(reason)) = browsers::signature::verify(path) {
+        bail!("{}: {}", path.display(), reason);
+    }
+    let artifact = file_artifact(path, ArtifactType::History, None)
+        .with_context(|| format!("Not a recognized history database: {}", path.display()))?;
+    let opts = ExtractOptions::default();
+    match extract::extract_artifact(&artifact, path, &artifact.username, &opts) {
+        Some(Ok(Extracted::History(entries))) => Ok(entries),
+        Some(Err(e)) => Err(e),
+        _ => bail!("Not a recognized history database: {}", path.display()),
+    }
+}
+
 /// Scan a triage directory for all browser artifacts.
 pub fn scan(triage_path: &Path) -> Vec<BrowserArtifact> {
+    scan_with_options(triage_path, &ScanOptions::default())
+}
+
+/// Scan a triage directory for all browser artifacts, honoring [`ScanOptions`].
+pub fn scan_with_options(triage_path: &Path, options: &ScanOptions) -> Vec<BrowserArtifact> {
+    scan_with_progress(triage_path, options, &NoProgress)
+}
+
+/// [`scan_with_options`], reporting each file walked to `progress`.
+pub fn scan_with_progress(
+    triage_path: &Path,
+    options: &ScanOptions,
+    progress: &dyn ScanProgress,
+) -> Vec<BrowserArtifact> {
     let mut artifacts = Vec::new();
+    let mut files = 0usize;
 
-    for entry in WalkDir::new(triage_path)
+    // On Windows, walk from an extended-length root so profiles nested past MAX_PATH are
+    // still visited. The prefix is stripped again from the paths we report.
+    let root = if cfg!(windows) {
+        std::path::absolute(triage_path)
+            .map(|p| PathBuf::from(to_extended_length(&p.to_string_lossy())))
+            .unwrap_or_else(|_| triage_path.to_path_buf())
+    } else {
+        triage_path.to_path_buf()
+    };
+
+    for entry in WalkDir::new(&root)
         .follow_links(true)
         .max_depth(15)
         .into_iter()
+        .filter_entry(|e| !options.is_excluded(e.path(), &root, e.file_type().is_dir()))
         .filter_map(|e| e.ok())
     {
         if !entry.file_type().is_file() {
             continue;
         }
+        files += 1;
+        progress.files_walked(files);
 
         let path = entry.path();
         let file_name = match path.file_name().and_then(|n| n.to_str()) {
             Some(n) => n,
             None => continue,
         };
+        if !is_artifact_file(file_name) {
+            continue;
+        }
 
-        let path_str = path.to_string_lossy().to_string();
+        let path_str = strip_extended_length(&path.to_string_lossy());
         let path_lower = path_str.to_lowercase();
 
         match file_name {
@@ -63,11 +316,45 @@ pub fn scan(triage_path: &Path) -> Vec<BrowserArtifact> {
                 });
             }
 
+            // Legacy long-term history split out by older Chrome (same schema)
+            "Archived History" if is_chromium_profile(&path_lower) => {
+                let browser = detect_chromium_browser(&path_lower);
+                artifacts.push(BrowserArtifact {
+                    browser,
+                    artifact_type: ArtifactType::History,
+                    db_path: path_str,
+                    profile_name: extract_profile_name(path),
+                    username: extract_username(path),
+                });
+            }
+
+            // Opera (Presto) address-bar input
+            "typed_history.xml" if path_lower.contains("opera") => {
+                artifacts.push(BrowserArtifact {
+                    browser: BrowserType::Opera,
+                    artifact_type: ArtifactType::History,
+                    db_path: path_str,
+                    profile_name: extract_profile_name(path),
+                    username: extract_username(path),
+                });
+            }
+
+            // Opera (Presto) notes, read as bookmarks
+            "notes.adr" if path_lower.contains("opera") => {
+                artifacts.push(BrowserArtifact {
+                    browser: BrowserType::Opera,
+                    artifact_type: ArtifactType::Bookmarks,
+                    db_path: path_str,
+                    profile_name: extract_profile_name(path),
+                    username: extract_username(path),
+                });
+            }
+
             "places.sqlite"
-                if path_lower.contains("firefox") || path_lower.contains("mozilla") =>
+                if is_gecko_profile(&path_lower) =>
             {
                 artifacts.push(BrowserArtifact {
-                    browser: BrowserType::Firefox,
+                    browser: detect_gecko_browser(&path_lower),
                     artifact_type: ArtifactType::History,
                     db_path: path_str,
                     profile_name: extract_profile_name(path),
@@ -75,6 +362,27 @@ pub fn scan(triage_path: &Path) -> Vec<BrowserArtifact> {
                 });
             }
 
+            // Tabs open when Firefox last saved its session
+            name if browsers::firefox_sessions::is_session_file(name)
+                && is_gecko_profile(&path_lower) =>
+            {
+                // All but sessionstore.jsonlz4 sit in the profile's sessionstore-backups/
+                let profile_name = match path
+                    .parent()
+                    .filter(|p| p.ends_with("sessionstore-backups"))
+                {
+                    Some(backups) => extract_profile_name(backups),
+                    None => extract_profile_name(path),
+                };
+                artifacts.push(BrowserArtifact {
+                    browser: detect_gecko_browser(&path_lower),
+                    artifact_type: ArtifactType::History,
+                    db_path: path_str,
+                    profile_name,
+                    username: extract_username(path),
+                });
+            }
+
             "History.db" if path_lower.contains("safari") => {
                 artifacts.push(BrowserArtifact {
                     browser: BrowserType::Safari,
@@ -85,6 +393,17 @@ pub fn scan(triage_path: &Path) -> Vec<BrowserArtifact> {
                 });
             }
 
+            // ---- Downloads (Safari keeps them in a plist, not History.db) ----
+            "Downloads.plist" if path_lower.contains("safari") => {
+                artifacts.push(BrowserArtifact {
+                    browser: BrowserType::Safari,
+                    artifact_type: ArtifactType::Downloads,
+                    db_path: path_str,
+                    profile_name: String::new(),
+                    username: extract_username(path),
+                });
+            }
+
             "WebCacheV01.dat" => {
                 artifacts.push(BrowserArtifact {
                     browser: BrowserType::InternetExplorer,
@@ -95,23 +414,92 @@ pub fn scan(triage_path: &Path) -> Vec<BrowserArtifact> {
                 });
             }
 
-            // ---- Cookies ----
+            // ---- HSTS (Default/TransportSecurity, or Default/Network/ in newer Chrome) ----
+            "TransportSecurity" if is_chromium_profile(&path_lower) => {
+                let browser = detect_chromium_browser(&path_lower);
+                let profile_dir = match path.parent() {
+                    Some(p) if p.file_name().is_some_and(|n| n == "Network") => p,
+                    _ => path,
+                };
+                artifacts.push(BrowserArtifact {
+                    browser,
+                    artifact_type: ArtifactType::HstsRecords,
+                    db_path: path_str,
+                    profile_name: extract_profile_name(profile_dir),
+                    username: extract_username(path),
+                });
+            }
+
+            // ---- Favicons ----
+            "favicons.sqlite"
+                if is_gecko_profile(&path_lower) =>
+            {
+                artifacts.push(BrowserArtifact {
+                    browser: detect_gecko_browser(&path_lower),
+                    artifact_type: ArtifactType::Favicons,
+                    db_path: path_str,
+                    profile_name: extract_profile_name(path),
+                    username: extract_username(path),
+                });
+            }
+
+            // ---- Omnibox shortcuts ----
+            "Shortcuts" if is_chromium_profile(&path_lower) => {
+                let browser = detect_chromium_browser(&path_lower);
+                artifacts.push(BrowserArtifact {
+                    browser,
+                    artifact_type: ArtifactType::Shortcuts,
+                    db_path: path_str,
+                    profile_name: extract_profile_name(path),
+                    username: extract_username(path),
+                });
+            }
+
+            // ---- Network Action Predictor (typed text -> predicted URL) ----
+            "Network Action Predictor" if is_chromium_profile(&path_lower) => {
+                let browser = detect_chromium_browser(&path_lower);
+                artifacts.push(BrowserArtifact {
+                    browser,
+                    artifact_type: ArtifactType::Predictor,
+                    db_path: path_str,
+                    profile_name: extract_profile_name(path),
+                    username: extract_username(path),
+                });
+            }
+
+            // ---- Top Sites (new-tab most visited tiles) ----
+            "Top Sites" if is_chromium_profile(&path_lower) => {
+                let browser = detect_chromium_browser(&path_lower);
+                artifacts.push(BrowserArtifact {
+                    browser,
+                    artifact_type: ArtifactType::TopSites,
+                    db_path: path_str,
+                    profile_name: extract_profile_name(path),
+                    username: extract_username(path),
+                });
+            }
+
+            // ---- Cookies (Default/Cookies, or Default/Network/ in Chrome 96+) ----
             "Cookies" if is_chromium_profile(&path_lower) => {
                 let browser = detect_chromium_browser(&path_lower);
+                let profile_dir = match path.parent() {
+                    Some(p) if p.file_name().is_some_and(|n| n == "Network") => p,
+                    _ => path,
+                };
                 artifacts.push(BrowserArtifact {
                     browser,
                     artifact_type: ArtifactType::Cookies,
                     db_path: path_str,
-                    profile_name: extract_profile_name(path),
+                    profile_name: extract_profile_name(profile_dir),
                     username: extract_username(path),
                 });
             }
 
             "cookies.sqlite"
-                if path_lower.contains("firefox") || path_lower.contains("mozilla") =>
+                if is_gecko_profile(&path_lower) =>
             {
                 artifacts.push(BrowserArtifact {
-                    browser: BrowserType::Firefox,
+                    browser: detect_gecko_browser(&path_lower),
                     artifact_type: ArtifactType::Cookies,
                     db_path: path_str,
                     profile_name: extract_profile_name(path),
@@ -119,6 +507,19 @@ pub fn scan(triage_path: &Path) -> Vec<BrowserArtifact> {
                 });
             }
 
+            // Safari: ~/Library/Cookies, or the sandbox container on macOS 10.15+
+            "Cookies.binarycookies"
+                if path_lower.contains("safari") || is_library_cookies(&path_lower) =>
+            {
+                artifacts.push(BrowserArtifact {
+                    browser: BrowserType::Safari,
+                    artifact_type: ArtifactType::Cookies,
+                    db_path: path_str,
+                    profile_name: String::new(),
+                    username: extract_username(path),
+                });
+            }
+
             // ---- Autofill ----
             "Web Data" if is_chromium_profile(&path_lower) => {
                 let browser = detect_chromium_browser(&path_lower);
@@ -132,10 +533,10 @@ pub fn scan(triage_path: &Path) -> Vec<BrowserArtifact> {
             }
 
             "formhistory.sqlite"
-                if path_lower.contains("firefox") || path_lower.contains("mozilla") =>
+                if is_gecko_profile(&path_lower) =>
             {
                 artifacts.push(BrowserArtifact {
-                    browser: BrowserType::Firefox,
+                    browser: detect_gecko_browser(&path_lower),
                     artifact_type: ArtifactType::Autofill,
                     db_path: path_str,
                     profile_name: extract_profile_name(path),
@@ -156,10 +557,10 @@ pub fn scan(triage_path: &Path) -> Vec<BrowserArtifact> {
             }
 
             "logins.json"
-                if path_lower.contains("firefox") || path_lower.contains("mozilla") =>
+                if is_gecko_profile(&path_lower) =>
             {
                 artifacts.push(BrowserArtifact {
-                    browser: BrowserType::Firefox,
+                    browser: detect_gecko_browser(&path_lower),
                     artifact_type: ArtifactType::LoginData,
                     db_path: path_str,
                     profile_name: extract_profile_name(path),
@@ -167,8 +568,8 @@ pub fn scan(triage_path: &Path) -> Vec<BrowserArtifact> {
                 });
             }
 
-            // ---- Bookmarks (Chrome JSON) ----
-            "Bookmarks" if is_chromium_profile(&path_lower) => {
+            // ---- Bookmarks (Chrome JSON, plus the previous state in Bookmarks.bak) ----
+            "Bookmarks" | "Bookmarks.bak" if is_chromium_profile(&path_lower) => {
                 let browser = detect_chromium_browser(&path_lower);
                 artifacts.push(BrowserArtifact {
                     browser,
@@ -192,10 +593,10 @@ pub fn scan(triage_path: &Path) -> Vec<BrowserArtifact> {
             }
 
             "extensions.json"
-                if path_lower.contains("firefox") || path_lower.contains("mozilla") =>
+                if is_gecko_profile(&path_lower) =>
             {
                 artifacts.push(BrowserArtifact {
-                    browser: BrowserType::Firefox,
+                    browser: detect_gecko_browser(&path_lower),
                     artifact_type: ArtifactType::Extensions,
                     db_path: path_str,
                     profile_name: extract_profile_name(path),
@@ -203,10 +604,30 @@ pub fn scan(triage_path: &Path) -> Vec<BrowserArtifact> {
                 });
             }
 
+            // Portable or relocated Chromium installs have no browser name in the path,
+            // so fall back to the database schema
+            "History" | "Cookies" | "Web Data" | "Login Data" => {
+                if let Some(artifact_type) = sniff_chromium_db(path, file_name) {
+                    debug!(
+                        "Chromium schema found outside a known profile: {}",
+                        path_str
+                    );
+                    artifacts.push(BrowserArtifact {
+                        browser: BrowserType::Chromium,
+                        artifact_type,
+                        db_path: path_str,
+                        profile_name: extract_profile_name(path),
+                        username: extract_username(path),
+                    });
+                }
+            }
+
             _ => {}
         }
     }
 
+    label_unattributed_profiles(&mut artifacts);
+
     // Synthesize additional artifacts from multi-artifact database files
     let synthesized = synthesize_additional_artifacts(&artifacts);
     artifacts.extend(synthesized);
@@ -214,14 +635,38 @@ pub fn scan(triage_path: &Path) -> Vec<BrowserArtifact> {
     artifacts
 }
 
+/// Chromium profile directories that belong to no signed-in user, with their label.
+const UNATTRIBUTED_PROFILES: &[(&str, &str)] =
+    &[("Guest Profile", "Guest"), ("System Profile", "System")];
+
+/// Relabel Chromium `Guest Profile` / `System Profile` artifacts as "Guest" / "System"
+/// and clear their username, so guest browsing (or the profile picker's own pages) isn't
+/// attributed to the OS user whose directory holds it.
+fn label_unattributed_profiles(artifacts: &mut [BrowserArtifact]) {
+    for a in artifacts.iter_mut().filter(|a| a.browser.is_chromium()) {
+        if let Some((_, label)) = UNATTRIBUTED_PROFILES
+            .iter()
+            .find(|(dir, _)| a.profile_name == *dir)
+        {
+            a.profile_name = label.to_string();
+            a.username.clear();
+        }
+    }
+}
+
 /// When we find a History DB, it also contains downloads and keyword searches.
 /// When we find Firefox places.sqlite, it also has bookmarks and downloads.
+/// When we find Chrome Preferences, it also has browser settings.
+/// When we find Safari History.db, it also has per-day visit counts.
+/// When we find Opera typed_history.xml, its search items are keyword searches.
 fn synthesize_additional_artifacts(artifacts: &[BrowserArtifact]) -> Vec<BrowserArtifact> {
     let mut additional = Vec::new();
     for a in artifacts {
         match (&a.browser, &a.artifact_type) {
-            // Chrome History DB also has downloads + keyword searches
-            (b, ArtifactType::History) if b.is_chromium() => {
+            // Chrome History DB also has downloads + keyword searches (Archived History doesn't)
+            (b, ArtifactType::History)
+                if b.is_chromium() && !a.is_archived_history() && !a.is_typed_history() =>
+            {
                 additional.push(BrowserArtifact {
                     artifact_type: ArtifactType::Downloads,
                     ..a.clone()
@@ -231,8 +676,40 @@ fn synthesize_additional_artifacts(artifacts: &[BrowserArtifact]) -> Vec<Browser
                     ..a.clone()
                 });
             }
+            // Opera's typed searches are kept apart from the typed addresses
+            (BrowserType::Opera, ArtifactType::History) if a.is_typed_history() => {
+                additional.push(BrowserArtifact {
+                    artifact_type: ArtifactType::KeywordSearches,
+                    ..a.clone()
+                });
+            }
+            // Chrome Web Data also has saved addresses and payment cards
+            (b, ArtifactType::Autofill) if b.is_chromium() => {
+                additional.push(BrowserArtifact {
+                    artifact_type: ArtifactType::AutofillProfiles,
+                    ..a.clone()
+                });
+                additional.push(BrowserArtifact {
+                    artifact_type: ArtifactType::CreditCards,
+                    ..a.clone()
+                });
+            }
+            // Chrome Preferences also has startup pages, homepage and search engine
+            (b, ArtifactType::Extensions) if b.is_chromium() => {
+                additional.push(BrowserArtifact {
+                    artifact_type: ArtifactType::BrowserSettings,
+                    ..a.clone()
+                });
+            }
+            // Safari History.db also has per-day visit counts
+            (BrowserType::Safari, ArtifactType::History) => {
+                additional.push(BrowserArtifact {
+                    artifact_type: ArtifactType::DailyVisits,
+                    ..a.clone()
+                });
+            }
             // Firefox places.sqlite also has downloads + bookmarks
-            (BrowserType::Firefox, ArtifactType::History) => {
+            (b, ArtifactType::History) if b.is_firefox() && !a.is_session_restore() => {
                 additional.push(BrowserArtifact {
                     artifact_type: ArtifactType::Downloads,
                     ..a.clone()
@@ -248,6 +725,25 @@ fn synthesize_additional_artifacts(artifacts: &[BrowserArtifact]) -> Vec<Browser
     additional
 }
 
+/// Accept a Chromium database found outside a recognizable browser path if it has the
+/// schema for its file name (see [`browsers::signature`]).
+fn sniff_chromium_db(path: &Path, file_name: &str) -> Option<ArtifactType> {
+    let artifact_type = match file_name {
+        "History" => ArtifactType::History,
+        "Cookies" => ArtifactType::Cookies,
+        "Web Data" => ArtifactType::Autofill,
+        "Login Data" => ArtifactType::LoginData,
+        _ => return None,
+    };
+    matches!(browsers::signature::verify(path), Some(Ok(()))).then_some(artifact_type)
+}
+
+/// Check if a path is in a macOS user's `Library/Cookies`, where Safari kept cookies
+/// before it was sandboxed.
+fn is_library_cookies(path_lower: &str) -> bool {
+    path_lower.replace('\\', "/").contains("/library/cookies/")
+}
+
 /// Check if a path is inside a Chromium browser profile directory.
 fn is_chromium_profile(path_lower: &str) -> bool {
     path_lower.contains("chrome")
@@ -256,6 +752,7 @@ fn is_chromium_profile(path_lower: &str) -> bool {
         || path_lower.contains("brave")
         || path_lower.contains("opera")
         || path_lower.contains("vivaldi")
+        || path_lower.contains("yandex")
         || path_lower.contains("/arc/")
         || path_lower.contains("user data")
 }
@@ -264,10 +761,14 @@ fn is_chromium_profile(path_lower: &str) -> bool {
 fn detect_chromium_browser(path_lower: &str) -> BrowserType {
     if path_lower.contains("brave") {
         BrowserType::Brave
+    } else if path_lower.contains("opera gx") || path_lower.contains("opera_gx") {
+        BrowserType::OperaGX
     } else if path_lower.contains("opera") {
         BrowserType::Opera
     } else if path_lower.contains("vivaldi") {
         BrowserType::Vivaldi
+    } else if path_lower.contains("yandex") {
+        BrowserType::Yandex
     } else if path_lower.contains("edge") || path_lower.contains("msedge") {
         BrowserType::EdgeChromium
     } else if path_lower.contains("/arc/") {
@@ -279,6 +780,27 @@ fn detect_chromium_browser(path_lower: &str) -> BrowserType {
     }
 }
 
+/// Check if a path is inside a Firefox-format profile. Tor Browser keeps its profile
+/// under `TorBrowser/Data/Browser`, with neither "firefox" nor "mozilla" in the path.
+fn is_gecko_profile(path_lower: &str) -> bool {
+    path_lower.contains("firefox") || path_lower.contains("mozilla") || is_tor_browser(path_lower)
+}
+
+fn is_tor_browser(path_lower: &str) -> bool {
+    path_lower.contains("tor browser")
+        || path_lower.contains("torbrowser")
+        || path_lower.contains("tor-browser")
+}
+
+/// Detect which Firefox-format browser from the path.
+fn detect_gecko_browser(path_lower: &str) -> BrowserType {
+    if is_tor_browser(path_lower) {
+        BrowserType::TorBrowser
+    } else {
+        BrowserType::Firefox
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -296,6 +818,464 @@ mod tests {
         assert_eq!(extract_username(path), "john.doe");
     }
 
+    #[test]
+    fn test_exclude_path_prunes_subtree() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let live = dir
+            .path()
+            .join("Users/alice/AppData/Local/Google/Chrome/User Data/Default");
+        let shadow = dir.path().join(
+            "System Volume Information/Users/alice/AppData/Local/Google/Chrome/User Data/Default",
+        );
+        for d in [&live, &shadow] {
+            std::fs::create_dir_all(d).unwrap();
+            std::fs::write(d.join("History"), b"").unwrap();
+        }
+
+        let all = scan(dir.path());
+        assert_eq!(
+            all.iter()
+                .filter(|a| a.artifact_type == ArtifactType::History)
+                .count(),
+            2
+        );
+
+        let options =
+            ScanOptions::with_excludes(&["**/system volume information/**".to_string()]).unwrap();
+        let filtered = scan_with_options(dir.path(), &options);
+        assert!(!filtered.is_empty());
+        assert!(filtered
+            .iter()
+            .all(|a| !a.db_path.contains("System Volume Information")));
+    }
+
+    #[test]
+    fn test_guest_profile_not_attributed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let user_data = dir
+            .path()
+            .join("Users/alice/AppData/Local/Google/Chrome/User Data");
+        for profile in ["Default", "Guest Profile", "System Profile"] {
+            std::fs::create_dir_all(user_data.join(profile)).unwrap();
+            std::fs::write(user_data.join(profile).join("History"), b"").unwrap();
+        }
+
+        let artifacts = scan(dir.path());
+        let history = |profile: &str| {
+            artifacts
+                .iter()
+                .find(|a| a.artifact_type == ArtifactType::History && a.profile_name == profile)
+                .unwrap()
+        };
+        assert_eq!(history("Default").username, "alice");
+        assert!(!history("Default").is_unattributed_profile());
+        for label in ["Guest", "System"] {
+            assert_eq!(history(label).username, "");
+            assert!(history(label).is_unattributed_profile());
+        }
+        // Downloads synthesized from the guest History carry the label too
+        assert!(artifacts
+            .iter()
+            .filter(|a| a.db_path.contains("Guest Profile"))
+            .all(|a| a.profile_name == "Guest" && a.username.is_empty()));
+    }
+
+    #[test]
+    fn test_archived_history_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir
+            .path()
+            .join("Users/alice/AppData/Local/Google/Chrome/User Data/Default");
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(profile.join("History"), b"").unwrap();
+        std::fs::write(profile.join("Archived History"), b"").unwrap();
+
+        let artifacts = scan(dir.path());
+        let history: Vec<_> = artifacts
+            .iter()
+            .filter(|a| a.artifact_type == ArtifactType::History)
+            .collect();
+        assert_eq!(history.len(), 2);
+        let archived = history.iter().find(|a| a.is_archived_history()).unwrap();
+        assert_eq!(archived.browser, BrowserType::Chrome);
+        assert_eq!(archived.profile_name, "Default");
+
+        // Downloads / keyword searches only come from the live History file
+        for t in [ArtifactType::Downloads, ArtifactType::KeywordSearches] {
+            let of_type: Vec<_> = artifacts.iter().filter(|a| a.artifact_type == t).collect();
+            assert_eq!(of_type.len(), 1);
+            assert!(!of_type[0].is_archived_history());
+        }
+    }
+
+    #[test]
+    fn test_safari_downloads_plist_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let safari = dir.path().join("Users/alice/Library/Safari");
+        std::fs::create_dir_all(&safari).unwrap();
+        std::fs::write(safari.join("Downloads.plist"), b"").unwrap();
+        std::fs::write(dir.path().join("Users/alice/Downloads.plist"), b"").unwrap();
+
+        let artifacts = scan(dir.path());
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].browser, BrowserType::Safari);
+        assert_eq!(artifacts[0].artifact_type, ArtifactType::Downloads);
+        assert_eq!(artifacts[0].username, "alice");
+    }
+
+    #[test]
+    fn test_firefox_favicons_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir
+            .path()
+            .join("Users/alice/AppData/Roaming/Mozilla/Firefox/Profiles/abcd1234.default");
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(profile.join("favicons.sqlite"), b"").unwrap();
+        std::fs::write(dir.path().join("Users/alice/favicons.sqlite"), b"").unwrap();
+
+        let artifacts = scan(dir.path());
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].browser, BrowserType::Firefox);
+        assert_eq!(artifacts[0].artifact_type, ArtifactType::Favicons);
+        assert_eq!(artifacts[0].username, "alice");
+    }
+
+    #[test]
+    fn test_firefox_session_files_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir
+            .path()
+            .join("Users/alice/AppData/Roaming/Mozilla/Firefox/Profiles/abcd1234.default");
+        std::fs::create_dir_all(profile.join("sessionstore-backups")).unwrap();
+        std::fs::write(profile.join("sessionstore-backups/recovery.jsonlz4"), b"").unwrap();
+        std::fs::write(profile.join("sessionstore.js"), b"").unwrap();
+        std::fs::write(dir.path().join("Users/alice/recovery.jsonlz4"), b"").unwrap();
+
+        let artifacts = scan(dir.path());
+        // Session files have no downloads or bookmarks behind them
+        assert_eq!(artifacts.len(), 2);
+        assert!(artifacts
+            .iter()
+            .all(|a| a.artifact_type == ArtifactType::History
+                && a.browser == BrowserType::Firefox
+                && a.is_session_restore()
+                && a.profile_name == "abcd1234.default"
+                && a.username == "alice"));
+    }
+
+    #[test]
+    fn test_tor_browser_profile_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir
+            .path()
+            .join("Users/alice/Desktop/Tor Browser/Browser/TorBrowser/Data/Browser")
+            .join("profile.default");
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(profile.join("places.sqlite"), b"").unwrap();
+        std::fs::write(profile.join("cookies.sqlite"), b"").unwrap();
+
+        let artifacts = scan(dir.path());
+        assert!(!artifacts.is_empty());
+        assert!(artifacts
+            .iter()
+            .all(|a| a.browser == BrowserType::TorBrowser && a.username == "alice"));
+        let mut types: Vec<_> = artifacts.iter().map(|a| a.artifact_type).collect();
+        types.sort_by_key(|t| t.display_name());
+        assert_eq!(
+            types,
+            vec![
+                ArtifactType::Bookmarks,
+                ArtifactType::Cookies,
+                ArtifactType::Downloads,
+                ArtifactType::History
+            ]
+        );
+        assert_eq!(artifacts[0].profile_name, "profile.default");
+
+        // Linux tarball layout
+        assert_eq!(
+            detect_gecko_browser("/home/bob/tor-browser/browser/torbrowser/data/browser/profile.default/places.sqlite"),
+            BrowserType::TorBrowser
+        );
+        assert_eq!(
+            detect_gecko_browser("/home/bob/.mozilla/firefox/x.default/places.sqlite"),
+            BrowserType::Firefox
+        );
+    }
+
+    #[test]
+    fn test_chromium_shortcuts_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir
+            .path()
+            .join("Users/alice/AppData/Local/Google/Chrome/User Data/Default");
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(profile.join("Shortcuts"), b"").unwrap();
+        std::fs::write(dir.path().join("Users/alice/Shortcuts"), b"").unwrap();
+
+        let artifacts = scan(dir.path());
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].browser, BrowserType::Chrome);
+        assert_eq!(artifacts[0].artifact_type, ArtifactType::Shortcuts);
+        assert_eq!(artifacts[0].profile_name, "Default");
+        assert_eq!(artifacts[0].username, "alice");
+    }
+
+    #[test]
+    fn test_chromium_web_data_also_gives_addresses_and_cards() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir
+            .path()
+            .join("Users/alice/AppData/Local/Google/Chrome/User Data/Default");
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(profile.join("Web Data"), b"").unwrap();
+
+        let mut types: Vec<_> = scan(dir.path())
+            .iter()
+            .map(|a| a.artifact_type.file_suffix())
+            .collect();
+        types.sort();
+        assert_eq!(types, ["autofill", "autofill_profiles", "credit_cards"]);
+    }
+
+    #[test]
+    fn test_chromium_predictor_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir
+            .path()
+            .join("Users/alice/AppData/Local/Google/Chrome/User Data/Default");
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(profile.join("Network Action Predictor"), b"").unwrap();
+        std::fs::write(dir.path().join("Users/alice/Network Action Predictor"), b"").unwrap();
+
+        let artifacts = scan(dir.path());
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].browser, BrowserType::Chrome);
+        assert_eq!(artifacts[0].artifact_type, ArtifactType::Predictor);
+        assert_eq!(artifacts[0].username, "alice");
+    }
+
+    #[test]
+    fn test_scan_reports_files_walked() {
+        struct Counter(std::sync::atomic::AtomicUsize);
+        impl ScanProgress for Counter {
+            fn files_walked(&self, files: usize) {
+                self.0.store(files, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir
+            .path()
+            .join("Users/alice/AppData/Local/Google/Chrome/User Data/Default");
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(profile.join("History"), b"").unwrap();
+        std::fs::write(profile.join("notes.txt"), b"").unwrap();
+        std::fs::write(dir.path().join("readme.txt"), b"").unwrap();
+
+        let counter = Counter(Default::default());
+        let artifacts = scan_with_progress(dir.path(), &ScanOptions::default(), &counter);
+        assert!(!artifacts.is_empty());
+        assert!(artifacts.iter().all(|a| a.db_path.ends_with("History")));
+        assert_eq!(counter.0.into_inner(), 3);
+    }
+
+    #[test]
+    fn test_chromium_top_sites_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir
+            .path()
+            .join("Users/alice/AppData/Local/Microsoft/Edge/User Data/Profile 1");
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(profile.join("Top Sites"), b"").unwrap();
+        std::fs::write(dir.path().join("Users/alice/Top Sites"), b"").unwrap();
+
+        let artifacts = scan(dir.path());
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].browser, BrowserType::EdgeChromium);
+        assert_eq!(artifacts[0].artifact_type, ArtifactType::TopSites);
+        assert_eq!(artifacts[0].profile_name, "Profile 1");
+        assert_eq!(artifacts[0].username, "alice");
+    }
+
+    #[test]
+    fn test_safari_binarycookies_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let legacy = dir.path().join("Users/alice/Library/Cookies");
+        let container = dir
+            .path()
+            .join("Users/bob/Library/Containers/com.apple.Safari/Data/Library/Cookies");
+        let elsewhere = dir.path().join("Users/carol/Desktop");
+        for d in [&legacy, &container, &elsewhere] {
+            std::fs::create_dir_all(d).unwrap();
+            std::fs::write(d.join("Cookies.binarycookies"), b"").unwrap();
+        }
+
+        let mut users: Vec<_> = scan(dir.path())
+            .into_iter()
+            .inspect(|a| {
+                assert_eq!(a.browser, BrowserType::Safari);
+                assert_eq!(a.artifact_type, ArtifactType::Cookies);
+            })
+            .map(|a| a.username)
+            .collect();
+        users.sort();
+        assert_eq!(users, ["alice", "bob"]);
+    }
+
+    #[test]
+    fn test_profile_beyond_max_path_is_scanned() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut profile = dir.path().join("Users/alice");
+        for _ in 0..6 {
+            profile.push("a-rather-long-directory-name-from-a-sync-tool");
+        }
+        profile.push("AppData/Local/Google/Chrome/User Data/Default");
+        assert!(profile.as_os_str().len() > 260);
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(profile.join("History"), b"").unwrap();
+
+        let artifacts = scan(dir.path());
+        let history = artifacts
+            .iter()
+            .find(|a| a.artifact_type == ArtifactType::History)
+            .unwrap();
+        assert_eq!(history.username, "alice");
+        assert!(!history.db_path.starts_with(r"\\?\"));
+
+        // The copy step must also cope with the deep path
+        let (_tmp, copied) =
+            crate::browsers::chrome::copy_db_to_temp(Path::new(&history.db_path), "History")
+                .unwrap();
+        assert!(copied.exists());
+    }
+
+    #[test]
+    fn test_profile_filter() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let user_data = dir
+            .path()
+            .join("Users/alice/AppData/Local/Google/Chrome/User Data");
+        for profile in ["Default", "Profile 2"] {
+            std::fs::create_dir_all(user_data.join(profile)).unwrap();
+            std::fs::write(user_data.join(profile).join("History"), b"").unwrap();
+        }
+        std::fs::write(
+            user_data.join("Local State"),
+            r#"{"profile": {"info_cache": {
+                "Default": {"name": "Personal"},
+                "Profile 2": {"name": "Work"}
+            }}}"#,
+        )
+        .unwrap();
+
+        let mut artifacts = scan(dir.path());
+        let removed = retain_profiles(&mut artifacts, &["profile 2".to_string()]);
+        assert!(removed > 0);
+        assert!(!artifacts.is_empty());
+        assert!(artifacts.iter().all(|a| a.profile_name == "Profile 2"));
+
+        // Display names from Local State select the same profile
+        let mut by_name = scan(dir.path());
+        retain_profiles(&mut by_name, &["WORK".to_string()]);
+        assert_eq!(by_name.len(), artifacts.len());
+        assert!(by_name.iter().all(|a| a.profile_name == "Profile 2"));
+
+        assert!(profile_matches(
+            &user_data.join("Default/History"),
+            &["personal".to_string()]
+        ));
+        assert!(!profile_matches(
+            &user_data.join("Default/History"),
+            &["Profile 2".to_string()]
+        ));
+    }
+
+    #[test]
+    fn test_opera_typed_history_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir.path().join("Users/bob/AppData/Roaming/Opera/Opera");
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(profile.join("typed_history.xml"), b"").unwrap();
+        std::fs::write(profile.join("notes.adr"), b"").unwrap();
+
+        let artifacts = scan(dir.path());
+        let types: Vec<_> = artifacts.iter().map(|a| a.artifact_type).collect();
+        assert_eq!(artifacts.len(), 3, "{types:?}");
+        assert!(artifacts.iter().all(|a| a.browser == BrowserType::Opera));
+        let typed: Vec<_> = artifacts.iter().filter(|a| a.is_typed_history()).collect();
+        assert_eq!(typed.len(), 2);
+        assert!(typed
+            .iter()
+            .any(|a| a.artifact_type == ArtifactType::History));
+        assert!(typed
+            .iter()
+            .any(|a| a.artifact_type == ArtifactType::KeywordSearches));
+        let notes = artifacts.iter().find(|a| a.is_opera_notes()).unwrap();
+        assert_eq!(notes.artifact_type, ArtifactType::Bookmarks);
+    }
+
+    #[test]
+    fn test_extract_history_by_file_name() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = dir.path().join("Users/bob/AppData/Roaming/Opera/Opera");
+        std::fs::create_dir_all(&profile).unwrap();
+        let path = profile.join("typed_history.xml");
+        std::fs::write(
+            &path,
+            r#"<typed_history><typed_history_item content="example.com" type="text" last_typed="2013-03-22T16:36:58Z"/></typed_history>"#,
+        )
+        .unwrap();
+
+        let entries = extract_history(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].user_profile, "bob");
+        assert!(extract_history(&profile.join("notes.txt")).is_err());
+    }
+
+    #[test]
+    fn test_portable_chromium_detected_by_schema() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let portable = dir.path().join("D/tools/webkit/profile");
+        std::fs::create_dir_all(&portable).unwrap();
+        let conn = rusqlite::Connection::open(portable.join("History")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                                visit_count INTEGER, typed_count INTEGER, last_visit_time INTEGER);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                                  from_visit INTEGER, transition INTEGER);",
+        )
+        .unwrap();
+        drop(conn);
+        // Same file names without a Chromium schema are left alone
+        let other = dir.path().join("D/tools/notes");
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(other.join("History"), b"not a database").unwrap();
+        let conn = rusqlite::Connection::open(other.join("Cookies")).unwrap();
+        conn.execute_batch("CREATE TABLE cookies (domain TEXT, value TEXT);")
+            .unwrap();
+        drop(conn);
+
+        let artifacts = scan(dir.path());
+        assert_eq!(artifacts.len(), 3);
+        assert!(artifacts.iter().all(|a| a.browser == BrowserType::Chromium
+            && a.db_path.ends_with("History")
+            && a.profile_name == "profile"));
+        let history = artifacts
+            .iter()
+            .find(|a| a.artifact_type == ArtifactType::History)
+            .unwrap();
+        assert!(Path::new(&history.db_path).starts_with(&portable));
+        assert!(artifacts
+            .iter()
+            .any(|a| a.artifact_type == ArtifactType::Downloads));
+    }
+
+    #[test]
+    fn test_invalid_exclude_glob() {
+        assert!(ScanOptions::with_excludes(&["a/[".to_string()]).is_err());
+    }
+
     #[test]
     fn test_detect_chromium_browser() {
         assert_eq!(
@@ -308,5 +1288,17 @@ mod tests {
             ),
             BrowserType::Brave
         );
+        assert_eq!(
+            detect_chromium_browser("/appdata/roaming/opera software/opera gx stable/history"),
+            BrowserType::OperaGX
+        );
+        assert_eq!(
+            detect_chromium_browser("/appdata/roaming/opera software/opera stable/history"),
+            BrowserType::Opera
+        );
+        // No "User Data" on macOS, so the vendor folder alone marks the profile
+        let yandex = "/users/bob/library/application support/yandex/yandexbrowser/default/history";
+        assert!(is_chromium_profile(yandex));
+        assert_eq!(detect_chromium_browser(yandex), BrowserType::Yandex);
     }
 }
@@ -0,0 +1,194 @@
+//! Capping of oversized free-text fields (`--max-value-len`).
+//!
+//! Malformed or hostile data can carry megabyte-long titles or cookie values that bloat
+//! the output and break spreadsheet/SIEM importers. Long fields are cut at a UTF-8 char
+//! boundary and marked so the reader knows data was dropped.
+
+use crate::browsers::{
+    AutofillEntry, AutofillProfileEntry, BookmarkEntry, BrowserSettingsEntry, CookieEntry,
+    DailyVisitEntry, DownloadEntry, ExtensionEntry, HistoryEntry, KeywordSearchEntry,
+    PredictorEntry, ShortcutEntry, TopSiteEntry,
+};
+
+/// Default `--max-value-len` in bytes.
+pub const DEFAULT_MAX_VALUE_LEN: usize = 8192;
+
+/// Truncate `value` to at most `max` bytes (plus the marker), never splitting a UTF-8
+/// character, and append `…[truncated N bytes]`. Returns true if the value was cut.
+pub fn cap_value(value: &mut String, max: usize) -> bool {
+    if value.len() <= max {
+        return false;
+    }
+    let end = value.floor_char_boundary(max);
+    let dropped = value.len() - end;
+    value.truncate(end);
+    value.push_str(&format!("…[truncated {dropped} bytes]"));
+    true
+}
+
+/// An entry with free-text fields subject to `--max-value-len`.
+pub trait CapValues {
+    /// Cap each long field; returns how many were truncated.
+    fn cap_values(&mut self, max: usize) -> usize;
+}
+
+fn cap_fields(fields: &mut [&mut String], max: usize) -> usize {
+    fields
+        .iter_mut()
+        .map(|f| cap_value(f, max))
+        .filter(|&cut| cut)
+        .count()
+}
+
+/// Cap every entry in `entries`. `max == 0` disables the cap.
+/// Returns the number of fields truncated.
+pub fn cap_all<T: CapValues>(entries: &mut [T], max: usize) -> usize {
+    if max == 0 {
+        return 0;
+    }
+    entries.iter_mut().map(|e| e.cap_values(max)).sum()
+}
+
+impl CapValues for HistoryEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(&mut [&mut self.title], max)
+    }
+}
+
+impl CapValues for DownloadEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(&mut [&mut self.page_title], max)
+    }
+}
+
+impl CapValues for KeywordSearchEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(
+            &mut [
+                &mut self.search_term,
+                &mut self.normalized_term,
+                &mut self.title,
+            ],
+            max,
+        )
+    }
+}
+
+impl CapValues for CookieEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(&mut [&mut self.value], max)
+    }
+}
+
+impl CapValues for AutofillEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(&mut [&mut self.value], max)
+    }
+}
+
+impl CapValues for AutofillProfileEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(&mut [&mut self.street_address], max)
+    }
+}
+
+impl CapValues for BookmarkEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(&mut [&mut self.title], max)
+    }
+}
+
+impl CapValues for ExtensionEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(&mut [&mut self.description, &mut self.permissions], max)
+    }
+}
+
+impl CapValues for BrowserSettingsEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(&mut [&mut self.value, &mut self.details], max)
+    }
+}
+
+impl CapValues for DailyVisitEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(&mut [&mut self.title], max)
+    }
+}
+
+impl CapValues for ShortcutEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(&mut [&mut self.text, &mut self.contents], max)
+    }
+}
+
+impl CapValues for TopSiteEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(&mut [&mut self.title], max)
+    }
+}
+
+impl CapValues for PredictorEntry {
+    fn cap_values(&mut self, max: usize) -> usize {
+        cap_fields(&mut [&mut self.user_text], max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_value_marks_truncation() {
+        let mut value = "a".repeat(10_000);
+        assert!(cap_value(&mut value, 8192));
+        assert!(value.starts_with(&"a".repeat(8192)));
+        assert!(value.ends_with("…[truncated 1808 bytes]"));
+
+        let mut short = "short".to_string();
+        assert!(!cap_value(&mut short, 8192));
+        assert_eq!(short, "short");
+    }
+
+    #[test]
+    fn test_cap_value_respects_char_boundary() {
+        // "é" is two bytes; a cap of 5 would land inside the third one
+        let mut value = "éééé".to_string();
+        assert!(cap_value(&mut value, 5));
+        assert_eq!(value, "éé…[truncated 4 bytes]");
+    }
+
+    #[test]
+    fn test_cap_all_history_titles() {
+        use chrono::{TimeZone, Utc};
+        let mut entries = vec![HistoryEntry {
+            url: "https://example.com/".to_string(),
+            title: "x".repeat(1_000_000),
+            visit_time: Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap(),
+            visit_count: 1,
+            web_browser: "Chrome".to_string(),
+            url_length: 20,
+            record_id: 1,
+            ..Default::default()
+        }];
+        assert_eq!(cap_all(&mut entries, 0), 0);
+        assert_eq!(entries[0].title.len(), 1_000_000);
+
+        assert_eq!(cap_all(&mut entries, DEFAULT_MAX_VALUE_LEN), 1);
+        assert!(entries[0].title.len() < DEFAULT_MAX_VALUE_LEN + 64);
+        assert!(entries[0].title.ends_with("…[truncated 991808 bytes]"));
+        assert_eq!(entries[0].url, "https://example.com/");
+    }
+
+    #[test]
+    fn test_cap_all_download_page_titles() {
+        let mut entries = vec![DownloadEntry {
+            url: "https://example.com/setup.exe".to_string(),
+            page_title: "x".repeat(100_000),
+            ..Default::default()
+        }];
+        assert_eq!(cap_all(&mut entries, DEFAULT_MAX_VALUE_LEN), 1);
+        assert!(entries[0].page_title.ends_with("…[truncated 91808 bytes]"));
+        assert_eq!(entries[0].url, "https://example.com/setup.exe");
+    }
+}
@@ -0,0 +1,188 @@
+//! Following a live history database (`webx watch`).
+//!
+//! Each poll extracts the file again from a fresh temp copy (the same WAL-aware copy the
+//! extractors always read), and only visits newer than the high-water mark are handed
+//! back. The mark is a visit time rather than a row id, so a database that is replaced
+//! between polls (profile reset, restored backup, rotated file) is followed without
+//! repeating what was already reported.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::info;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::browsers::{long_path, HistoryEntry};
+use crate::scanner::extract_history;
+
+/// Polls one history database for visits that appeared since the previous poll.
+pub struct Watcher {
+    path: PathBuf,
+    /// Newest visit time reported so far; `None` before the first poll
+    high_water: Option<DateTime<Utc>>,
+    /// Visits at exactly `high_water` already reported, so ties aren't repeated
+    at_high_water: HashSet<(i64, String)>,
+    /// Visits from the first poll to report, newest last
+    tail: usize,
+    identity: Option<u64>,
+}
+
+impl Watcher {
+    /// Follow `path`. The first poll reports the newest `tail` existing visits (none
+    /// for 0) and every later poll reports only new ones.
+    pub fn new(path: &Path, tail: usize) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            high_water: None,
+            at_high_water: HashSet::new(),
+            tail,
+            identity: None,
+        }
+    }
+
+    /// Extract the database again and return the visits not reported before, oldest
+    /// first. A failed poll leaves the mark unchanged, so the caller can retry.
+    pub fn poll(&mut self) -> Result<Vec<HistoryEntry>> {
+        let meta = std::fs::metadata(long_path(&self.path))
+            .with_context(|| format!("Cannot read {}", self.path.display()))?;
+        let identity = file_identity(&meta);
+        if self.identity.is_some() && identity != self.identity {
+            info!(
+                "{} was replaced; reporting visits after {}",
+                self.path.display(),
+                self.high_water
+                    .map(|t| t.to_rfc3339())
+                    .unwrap_or_else(|| "the start".to_string())
+            );
+        }
+
+        let mut entries = extract_history(&self.path)?;
+        entries.sort_by_key(|e| e.visit_time);
+        self.identity = identity;
+
+        let first_poll = self.high_water.is_none();
+        let mut fresh: Vec<HistoryEntry> = match self.high_water {
+            None => entries,
+            Some(mark) => entries
+                .into_iter()
+                .filter(|e| {
+                    e.visit_time > mark
+                        || (e.visit_time == mark
+                            && !self.at_high_water.contains(&(e.record_id, e.url.clone())))
+                })
+                .collect(),
+        };
+
+        if let Some(newest) = fresh.last().map(|e| e.visit_time) {
+            if self.high_water != Some(newest) {
+                self.at_high_water.clear();
+            }
+            self.high_water = Some(newest);
+            for e in fresh.iter().filter(|e| e.visit_time == newest) {
+                self.at_high_water.insert((e.record_id, e.url.clone()));
+            }
+        } else if first_poll {
+            // An empty database: everything from now on is new
+            self.high_water = Some(DateTime::<Utc>::MIN_UTC);
+        }
+
+        if first_poll {
+            fresh.drain(..fresh.len().saturating_sub(self.tail));
+        }
+        Ok(fresh)
+    }
+}
+
+/// Identity of the file behind a path, to notice it being replaced between polls.
+fn file_identity(meta: &std::fs::Metadata) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(meta.ino())
+    }
+    #[cfg(not(unix))]
+    {
+        meta.created()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_history(path: &Path, visits: &[(i64, &str, i64)]) {
+        let conn = rusqlite::Connection::open(path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS urls (id INTEGER PRIMARY KEY, url LONGVARCHAR,
+                                              title LONGVARCHAR, visit_count INTEGER,
+                                              typed_count INTEGER, last_visit_time INTEGER);
+             CREATE TABLE IF NOT EXISTS visits (id INTEGER PRIMARY KEY, url INTEGER,
+                                                visit_time INTEGER, from_visit INTEGER,
+                                                transition INTEGER);",
+        )
+        .unwrap();
+        for (id, url, time) in visits {
+            conn.execute(
+                "INSERT OR IGNORE INTO urls VALUES (?1, ?2, '', 1, 0, ?3)",
+                rusqlite::params![id, url, time],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO visits (url, visit_time, from_visit, transition)
+                 VALUES (?1, ?2, 0, 1)",
+                rusqlite::params![id, time],
+            )
+            .unwrap();
+        }
+    }
+
+    fn urls(entries: &[HistoryEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.url.as_str()).collect()
+    }
+
+    #[test]
+    fn test_reports_only_new_visits_across_replacement() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let history = dir.path().join("History");
+        write_history(
+            &history,
+            &[
+                (1, "https://a.example/", 13245010621000000),
+                (2, "https://b.example/", 13245010622000000),
+            ],
+        );
+
+        let mut watcher = Watcher::new(&history, 1);
+        assert_eq!(urls(&watcher.poll().unwrap()), ["https://b.example/"]);
+        assert!(watcher.poll().unwrap().is_empty());
+
+        // A second visit at the same instant as the mark is still new
+        write_history(
+            &history,
+            &[
+                (3, "https://c.example/", 13245010622000000),
+                (4, "https://d.example/", 13245010623000000),
+            ],
+        );
+        assert_eq!(
+            urls(&watcher.poll().unwrap()),
+            ["https://c.example/", "https://d.example/"]
+        );
+
+        // The file is swapped for one holding older history plus one newer visit
+        let replacement = dir.path().join("History.new");
+        write_history(
+            &replacement,
+            &[
+                (1, "https://a.example/", 13245010621000000),
+                (9, "https://e.example/", 13245010624000000),
+            ],
+        );
+        std::fs::remove_file(&history).unwrap();
+        std::fs::rename(&replacement, &history).unwrap();
+        assert_eq!(urls(&watcher.poll().unwrap()), ["https://e.example/"]);
+    }
+}
@@ -0,0 +1,508 @@
+//! Minimal Excel workbook (`.xlsx`) writer, for `--xlsx`.
+//!
+//! An `.xlsx` file is a zip archive of SpreadsheetML parts. Only what Excel and
+//! LibreOffice need for typed cells is written: text as inline strings, numbers, and
+//! date-times as serial day numbers with a date format, plus a bold header row that stays
+//! frozen while scrolling.
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveDateTime};
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Most characters Excel keeps in one cell.
+const MAX_CELL_CHARS: usize = 32_767;
+
+/// Most characters in a worksheet name.
+const MAX_SHEET_NAME_CHARS: usize = 31;
+
+/// Style indexes into the `cellXfs` of [`STYLES_XML`].
+const STYLE_HEADER: u8 = 1;
+const STYLE_DATETIME: u8 = 2;
+const STYLE_DATE: u8 = 3;
+
+/// One typed cell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Empty,
+    Text(String),
+    Number(f64),
+    /// Wall-clock time, shown as `yyyy-mm-dd hh:mm:ss.000`
+    DateTime(NaiveDateTime),
+    /// Shown as `yyyy-mm-dd`
+    Date(NaiveDate),
+}
+
+/// A workbook built in memory, one worksheet at a time, then saved with [`Workbook::save`].
+#[derive(Default)]
+pub struct Workbook {
+    /// Sheet name and worksheet XML
+    sheets: Vec<(String, String)>,
+}
+
+impl Workbook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a worksheet named `name` with a bold, frozen `headers` row followed by `rows`.
+    /// The name is cut to Excel's 31 characters, with characters Excel forbids in sheet
+    /// names replaced. Returns the number of data rows.
+    pub fn add_sheet(
+        &mut self,
+        name: &str,
+        headers: &[&str],
+        rows: impl IntoIterator<Item = Vec<Cell>>,
+    ) -> usize {
+        let mut xml = String::from(XML_DECLARATION);
+        xml.push_str(
+            "<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">\
+             <sheetViews><sheetView workbookViewId=\"0\">\
+             <pane ySplit=\"1\" topLeftCell=\"A2\" activePane=\"bottomLeft\" state=\"frozen\"/>\
+             </sheetView></sheetViews><sheetData>",
+        );
+
+        let header_cells = headers.iter().map(|h| Cell::Text(h.to_string()));
+        push_row(&mut xml, 1, header_cells, Some(STYLE_HEADER));
+        let mut count = 0;
+        for row in rows {
+            count += 1;
+            push_row(&mut xml, count + 1, row, None);
+        }
+        xml.push_str("</sheetData></worksheet>");
+
+        self.sheets.push((sheet_name(name), xml));
+        count
+    }
+
+    /// Whether no sheet has been added.
+    pub fn is_empty(&self) -> bool {
+        self.sheets.is_empty()
+    }
+
+    /// Write the workbook to `path`.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        anyhow::ensure!(!self.is_empty(), "A workbook needs at least one sheet");
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create workbook: {}", path.display()))?;
+        let mut zip = ZipWriter::new(BufWriter::new(file));
+
+        let mut content_types = String::from(XML_DECLARATION);
+        content_types.push_str(
+            "<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+             <Default Extension=\"rels\" \
+             ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+             <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+             <Override PartName=\"/xl/workbook.xml\" ContentType=\"application/\
+             vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+             <Override PartName=\"/xl/styles.xml\" ContentType=\"application/\
+             vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>",
+        );
+        let mut workbook = String::from(XML_DECLARATION);
+        workbook.push_str(
+            "<workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+             xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+             <sheets>",
+        );
+        let mut workbook_rels = String::from(XML_DECLARATION);
+        workbook_rels.push_str(
+            "<Relationships \
+             xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+        );
+        for (i, (name, _)) in self.sheets.iter().enumerate() {
+            let n = i + 1;
+            content_types.push_str(&format!(
+                "<Override PartName=\"/xl/worksheets/sheet{n}.xml\" ContentType=\"application/\
+                 vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>"
+            ));
+            workbook.push_str(&format!(
+                "<sheet name=\"{}\" sheetId=\"{n}\" r:id=\"rId{n}\"/>",
+                xml_escape(name)
+            ));
+            workbook_rels.push_str(&format!(
+                "<Relationship Id=\"rId{n}\" Type=\"http://schemas.openxmlformats.org/\
+                 officeDocument/2006/relationships/worksheet\" \
+                 Target=\"worksheets/sheet{n}.xml\"/>"
+            ));
+        }
+        content_types.push_str("</Types>");
+        workbook.push_str("</sheets></workbook>");
+        workbook_rels.push_str(&format!(
+            "<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/\
+             officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/>\
+             </Relationships>",
+            self.sheets.len() + 1
+        ));
+
+        zip.add("[Content_Types].xml", content_types.as_bytes())?;
+        zip.add("_rels/.rels", ROOT_RELS_XML.as_bytes())?;
+        zip.add("xl/workbook.xml", workbook.as_bytes())?;
+        zip.add("xl/_rels/workbook.xml.rels", workbook_rels.as_bytes())?;
+        zip.add("xl/styles.xml", STYLES_XML.as_bytes())?;
+        for (i, (_, xml)) in self.sheets.iter().enumerate() {
+            zip.add(&format!("xl/worksheets/sheet{}.xml", i + 1), xml.as_bytes())?;
+        }
+        zip.finish()
+            .with_context(|| format!("Failed to write workbook: {}", path.display()))
+    }
+}
+
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n";
+
+const ROOT_RELS_XML: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+    <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+    <Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/\
+    relationships/officeDocument\" Target=\"xl/workbook.xml\"/></Relationships>";
+
+/// Cell formats: 0 plain, 1 bold header, 2 date-time, 3 date.
+const STYLES_XML: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+    <styleSheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">\
+    <numFmts count=\"2\">\
+    <numFmt numFmtId=\"164\" formatCode=\"yyyy\\-mm\\-dd\\ hh:mm:ss.000\"/>\
+    <numFmt numFmtId=\"165\" formatCode=\"yyyy\\-mm\\-dd\"/></numFmts>\
+    <fonts count=\"2\"><font><sz val=\"11\"/><name val=\"Calibri\"/></font>\
+    <font><b/><sz val=\"11\"/><name val=\"Calibri\"/></font></fonts>\
+    <fills count=\"2\"><fill><patternFill patternType=\"none\"/></fill>\
+    <fill><patternFill patternType=\"gray125\"/></fill></fills>\
+    <borders count=\"1\"><border><left/><right/><top/><bottom/><diagonal/></border></borders>\
+    <cellStyleXfs count=\"1\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\"/>\
+    </cellStyleXfs>\
+    <cellXfs count=\"4\">\
+    <xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\"/>\
+    <xf numFmtId=\"0\" fontId=\"1\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyFont=\"1\"/>\
+    <xf numFmtId=\"164\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\" \
+    applyNumberFormat=\"1\"/>\
+    <xf numFmtId=\"165\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\" \
+    applyNumberFormat=\"1\"/></cellXfs>\
+    <cellStyles count=\"1\"><cellStyle name=\"Normal\" xfId=\"0\" builtinId=\"0\"/></cellStyles>\
+    </styleSheet>";
+
+/// Append row `number` (1-based). `style` overrides the style of every cell.
+fn push_row(
+    xml: &mut String,
+    number: usize,
+    cells: impl IntoIterator<Item = Cell>,
+    style: Option<u8>,
+) {
+    xml.push_str(&format!("<row r=\"{}\">", number));
+    for (col, cell) in cells.into_iter().enumerate() {
+        let reference = format!("{}{}", column_name(col), number);
+        let style_attr = |s: u8| match style.unwrap_or(s) {
+            0 => String::new(),
+            s => format!(" s=\"{}\"", s),
+        };
+        match cell {
+            Cell::Empty => {}
+            Cell::Text(text) if text.is_empty() => {}
+            Cell::Text(text) => {
+                xml.push_str(&format!(
+                    "<c r=\"{}\" t=\"inlineStr\"{}><is><t xml:space=\"preserve\">{}</t></is></c>",
+                    reference,
+                    style_attr(0),
+                    xml_escape(&cell_text(&text))
+                ));
+            }
+            Cell::Number(n) if n.is_finite() => {
+                xml.push_str(&format!(
+                    "<c r=\"{}\"{}><v>{}</v></c>",
+                    reference,
+                    style_attr(0),
+                    n
+                ));
+            }
+            Cell::Number(_) => {}
+            Cell::DateTime(dt) => match serial_date(dt) {
+                Some(serial) => xml.push_str(&format!(
+                    "<c r=\"{}\"{}><v>{}</v></c>",
+                    reference,
+                    style_attr(STYLE_DATETIME),
+                    serial
+                )),
+                None => xml.push_str(&format!(
+                    "<c r=\"{}\" t=\"inlineStr\"{}><is><t>{}</t></is></c>",
+                    reference,
+                    style_attr(0),
+                    dt.format("%Y-%m-%d %H:%M:%S%.3f")
+                )),
+            },
+            Cell::Date(d) => match d.and_hms_opt(0, 0, 0).and_then(serial_date) {
+                Some(serial) => xml.push_str(&format!(
+                    "<c r=\"{}\"{}><v>{}</v></c>",
+                    reference,
+                    style_attr(STYLE_DATE),
+                    serial
+                )),
+                None => xml.push_str(&format!(
+                    "<c r=\"{}\" t=\"inlineStr\"{}><is><t>{}</t></is></c>",
+                    reference,
+                    style_attr(0),
+                    d.format("%Y-%m-%d")
+                )),
+            },
+        }
+    }
+    xml.push_str("</row>");
+}
+
+/// Excel's serial date: days since 1899-12-30, the fraction being the time of day.
+/// `None` before 1900-03-01, where Excel's phantom 1900-02-29 makes serials ambiguous.
+fn serial_date(dt: NaiveDateTime) -> Option<f64> {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?.and_hms_opt(0, 0, 0)?;
+    let first = NaiveDate::from_ymd_opt(1900, 3, 1)?.and_hms_opt(0, 0, 0)?;
+    if dt < first {
+        return None;
+    }
+    let micros = (dt - epoch).num_microseconds()?;
+    Some(micros as f64 / 86_400_000_000.0)
+}
+
+/// Column letters for 0-based `index`: A..Z, AA..
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// `text` without the control characters XML 1.0 can't carry, cut to [`MAX_CELL_CHARS`].
+fn cell_text(text: &str) -> String {
+    let forbidden = |c: char| {
+        matches!(c, '\0'..='\x08' | '\x0b' | '\x0c' | '\x0e'..='\x1f')
+            || c == '\u{fffe}'
+            || c == '\u{ffff}'
+    };
+    text.chars()
+        .filter(|&c| !forbidden(c))
+        .take(MAX_CELL_CHARS)
+        .collect()
+}
+
+fn sheet_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '[' | ']' | ':' | '*' | '?' | '/' | '\\' => ' ',
+            c => c,
+        })
+        .take(MAX_SHEET_NAME_CHARS)
+        .collect();
+    match name.trim().trim_matches('\'') {
+        "" => "Sheet".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    quick_xml::escape::escape(s).into_owned()
+}
+
+/// Writes a zip archive of deflated entries. Sizes are 32-bit (no Zip64), which bounds
+/// each part and the whole archive at 4 GiB.
+struct ZipWriter<W: Write> {
+    out: W,
+    offset: u64,
+    /// Central directory records, written by [`ZipWriter::finish`]
+    central: Vec<u8>,
+    entries: u16,
+}
+
+/// MS-DOS date of 1980-01-01 (the earliest it can hold), so output is reproducible.
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+impl<W: Write> ZipWriter<W> {
+    fn new(out: W) -> Self {
+        Self {
+            out,
+            offset: 0,
+            central: Vec::new(),
+            entries: 0,
+        }
+    }
+
+    fn add(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        let mut crc = Crc::new();
+        crc.update(data);
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+
+        let size = u32::try_from(data.len()).context("Workbook part over 4 GiB")?;
+        let compressed_size =
+            u32::try_from(compressed.len()).context("Workbook part over 4 GiB")?;
+        let offset = u32::try_from(self.offset).context("Workbook over 4 GiB")?;
+
+        // Fields shared by the local header and the central directory record, from
+        // "version needed" through the extra field length
+        let mut common = Vec::with_capacity(26);
+        common.extend(20u16.to_le_bytes()); // version needed: 2.0, deflate
+        common.extend(0x0800u16.to_le_bytes()); // names are UTF-8
+        common.extend(8u16.to_le_bytes()); // deflate
+        common.extend(0u16.to_le_bytes()); // time 00:00:00
+        common.extend(DOS_DATE.to_le_bytes());
+        common.extend(crc.sum().to_le_bytes());
+        common.extend(compressed_size.to_le_bytes());
+        common.extend(size.to_le_bytes());
+        common.extend((name.len() as u16).to_le_bytes());
+        common.extend(0u16.to_le_bytes()); // extra field length
+
+        let mut local = Vec::with_capacity(30 + name.len());
+        local.extend(0x04034b50u32.to_le_bytes());
+        local.extend(&common);
+        local.extend(name.as_bytes());
+        self.out.write_all(&local)?;
+        self.out.write_all(&compressed)?;
+        self.offset += (local.len() + compressed.len()) as u64;
+
+        self.central.extend(0x02014b50u32.to_le_bytes());
+        self.central.extend(20u16.to_le_bytes()); // version made by
+        self.central.extend(&common);
+        self.central.extend(0u16.to_le_bytes()); // comment length
+        self.central.extend(0u16.to_le_bytes()); // disk number
+        self.central.extend(0u16.to_le_bytes()); // internal attributes
+        self.central.extend(0u32.to_le_bytes()); // external attributes
+        self.central.extend(offset.to_le_bytes());
+        self.central.extend(name.as_bytes());
+        self.entries += 1;
+        Ok(())
+    }
+
+    /// Write the central directory and end-of-archive record.
+    fn finish(mut self) -> Result<()> {
+        let central_offset = u32::try_from(self.offset).context("Workbook over 4 GiB")?;
+        self.out.write_all(&self.central)?;
+
+        let mut end = Vec::with_capacity(22);
+        end.extend(0x06054b50u32.to_le_bytes());
+        end.extend(0u16.to_le_bytes()); // this disk
+        end.extend(0u16.to_le_bytes()); // disk with the central directory
+        end.extend(self.entries.to_le_bytes());
+        end.extend(self.entries.to_le_bytes());
+        end.extend((self.central.len() as u32).to_le_bytes());
+        end.extend(central_offset.to_le_bytes());
+        end.extend(0u16.to_le_bytes()); // comment length
+        self.out.write_all(&end)?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::DeflateDecoder;
+    use std::io::Read;
+
+    /// Contents of every entry of a zip written by [`ZipWriter`], by name.
+    fn unzip(data: &[u8]) -> Vec<(String, String)> {
+        let mut parts = Vec::new();
+        let mut at = 0;
+        while data[at..].starts_with(&0x04034b50u32.to_le_bytes()) {
+            let le16 = |i: usize| u16::from_le_bytes([data[at + i], data[at + i + 1]]) as usize;
+            let le32 = |i: usize| {
+                u32::from_le_bytes([
+                    data[at + i],
+                    data[at + i + 1],
+                    data[at + i + 2],
+                    data[at + i + 3],
+                ]) as usize
+            };
+            let (compressed, name_len) = (le32(18), le16(26));
+            let name = String::from_utf8(data[at + 30..at + 30 + name_len].to_vec()).unwrap();
+            let start = at + 30 + name_len;
+            let mut text = String::new();
+            DeflateDecoder::new(&data[start..start + compressed])
+                .read_to_string(&mut text)
+                .unwrap();
+            parts.push((name, text));
+            at = start + compressed;
+        }
+        assert!(data[at..].starts_with(&0x02014b50u32.to_le_bytes()));
+        parts
+    }
+
+    #[test]
+    fn test_column_names_and_serial_dates() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(27), "AB");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(column_name(702), "AAA");
+
+        let noon = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        assert_eq!(serial_date(noon), Some(45292.5));
+        let early = NaiveDate::from_ymd_opt(1900, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        assert_eq!(serial_date(early), None);
+    }
+
+    #[test]
+    fn test_workbook_parts_and_typed_cells() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("report.xlsx");
+        let visit = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(6, 0, 0)
+            .unwrap();
+
+        let mut workbook = Workbook::new();
+        let rows = vec![vec![
+            Cell::DateTime(visit),
+            Cell::Text("007 <b>&".to_string()),
+            Cell::Number(42.0),
+            Cell::Empty,
+            Cell::Text("bell\x07".to_string()),
+        ]];
+        let headers = ["Visit Time", "Title", "Visit Count", "Account", "Notes"];
+        assert_eq!(workbook.add_sheet("History: [x]", &headers, rows), 1);
+        workbook.save(&path).unwrap();
+
+        let parts = unzip(&std::fs::read(&path).unwrap());
+        let names: Vec<&str> = parts.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "[Content_Types].xml",
+                "_rels/.rels",
+                "xl/workbook.xml",
+                "xl/_rels/workbook.xml.rels",
+                "xl/styles.xml",
+                "xl/worksheets/sheet1.xml",
+            ]
+        );
+        assert!(parts[2]
+            .1
+            .contains("<sheet name=\"History   x\" sheetId=\"1\""));
+
+        let sheet = &parts[5].1;
+        assert!(sheet.contains("state=\"frozen\""));
+        assert!(sheet.contains("<c r=\"A1\" t=\"inlineStr\" s=\"1\">"));
+        assert!(sheet.contains("<c r=\"A2\" s=\"2\"><v>45292.25</v></c>"));
+        assert!(sheet.contains("<t xml:space=\"preserve\">007 &lt;b&gt;&amp;</t>"));
+        assert!(sheet.contains("<c r=\"C2\"><v>42</v></c>"));
+        assert!(!sheet.contains("r=\"D2\""));
+        assert!(sheet.contains(">bell</t>"));
+    }
+
+    #[test]
+    fn test_empty_workbook_is_not_saved() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(Workbook::new()
+            .save(&dir.path().join("empty.xlsx"))
+            .is_err());
+    }
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::write_history;
+use std::process::Command;
+
+#[test]
+fn test_scan_tar_gz_bundle() {
+    let work = tempfile::TempDir::new().unwrap();
+    let tree = work.path().join("triage");
+    write_history(&tree.join("C"), "alice");
+
+    let bundle = work.path().join("host01.tar.gz");
+    let gz = flate2::write::GzEncoder::new(
+        std::fs::File::create(&bundle).unwrap(),
+        flate2::Compression::default(),
+    );
+    let mut tar = tar::Builder::new(gz);
+    tar.append_dir_all(".", &tree).unwrap();
+    tar.into_inner().unwrap().finish().unwrap();
+    std::fs::remove_dir_all(&tree).unwrap();
+
+    let out_dir = work.path().join("out");
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["scan", "--artifacts", "history", "-d"])
+        .arg(&bundle)
+        .arg("-o")
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    // The username still comes from the path inside the archive
+    let csv = out_dir.join("Chrome_history_alice_Default.csv");
+    let text = std::fs::read_to_string(&csv).unwrap();
+    assert!(text.contains("https://example.com/"), "{text}");
+
+    // History File names the member inside the bundle, not the temp unpack dir
+    let member = format!(
+        "{}!C/Users/alice/AppData/Local/Google/Chrome/User Data/Default/History",
+        bundle.display()
+    );
+    assert!(text.contains(&member), "{text}");
+}
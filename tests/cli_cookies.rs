@@ -0,0 +1,72 @@
+mod common;
+
+use common::{chrome_profile, write_history_db};
+use std::path::Path;
+use std::process::Command;
+
+fn write_profile(root: &Path) {
+    let profile = chrome_profile(root, "alice");
+    std::fs::create_dir_all(profile.join("Network")).unwrap();
+    write_history_db(
+        &profile.join("History"),
+        "INSERT INTO urls VALUES (1, 'https://www.shop.example/cart', 'Cart', 1, 0, 13245010621000000);
+         INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 1);",
+    );
+
+    // One cookie from the visited shop, one from an ad network it embeds
+    let conn = rusqlite::Connection::open(profile.join("Network/Cookies")).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE cookies (creation_utc INTEGER, host_key TEXT, name TEXT, value TEXT,
+                               path TEXT, expires_utc INTEGER, is_secure INTEGER,
+                               is_httponly INTEGER, last_access_utc INTEGER,
+                               is_persistent INTEGER, samesite INTEGER);
+         INSERT INTO cookies VALUES (13245010622000000, '.shop.example', 'session', 'abc',
+                                     '/', 0, 1, 1, 13245010622000000, 1, 1);
+         INSERT INTO cookies VALUES (13245010623000000, '.ads.example', 'uid', 'xyz',
+                                     '/', 0, 1, 0, 13245010623000000, 1, -1);",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_scan_marks_first_and_third_party_cookies() {
+    let work = tempfile::TempDir::new().unwrap();
+    write_profile(work.path());
+
+    let out_dir = work.path().join("out");
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["scan", "--artifacts", "cookies", "-d"])
+        .arg(work.path().join("Users"))
+        .arg("-o")
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let csv = out_dir.join("Chrome_cookies_alice_Default.csv");
+    let mut reader = csv::Reader::from_path(&csv).unwrap();
+    let headers = reader.headers().unwrap().clone();
+    let col = |name: &str| headers.iter().position(|h| h == name).unwrap();
+    let flags: Vec<_> = reader
+        .records()
+        .map(|r| {
+            let r = r.unwrap();
+            (
+                r[col("Host")].to_string(),
+                r[col("Visited Host")].to_string(),
+                r[col("First Party")].to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        flags,
+        vec![
+            (".shop.example".into(), "true".into(), "true".into()),
+            (".ads.example".into(), "false".into(), "false".into()),
+        ]
+    );
+}
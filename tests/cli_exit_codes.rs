@@ -0,0 +1,74 @@
+mod common;
+
+use common::{write_corrupt_history, write_history};
+use std::path::Path;
+use std::process::Command;
+
+fn scan_code(root: &Path) -> Option<i32> {
+    let out_dir = tempfile::TempDir::new().unwrap();
+    Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["scan", "--artifacts", "history", "-d"])
+        .arg(root)
+        .arg("-o")
+        .arg(out_dir.path())
+        .current_dir(root)
+        .output()
+        .unwrap()
+        .status
+        .code()
+}
+
+#[test]
+fn test_empty_directory_vs_good_scan() {
+    let empty = tempfile::TempDir::new().unwrap();
+    assert_eq!(scan_code(empty.path()), Some(2));
+
+    let good = tempfile::TempDir::new().unwrap();
+    write_history(good.path(), "alice");
+    assert_eq!(scan_code(good.path()), Some(0));
+}
+
+#[test]
+fn test_partial_failure_without_strict() {
+    // One readable and one corrupt History: the good one is still written
+    let triage = tempfile::TempDir::new().unwrap();
+    write_history(triage.path(), "alice");
+    write_corrupt_history(triage.path(), "bob");
+    assert_eq!(scan_code(triage.path()), Some(3));
+}
+
+#[test]
+fn test_no_rows_written() {
+    // Every visit is before --after, so nothing is written
+    let triage = tempfile::TempDir::new().unwrap();
+    write_history(triage.path(), "alice");
+    let out_dir = tempfile::TempDir::new().unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args([
+            "scan",
+            "--artifacts",
+            "history",
+            "--after",
+            "2024-01-01",
+            "-d",
+        ])
+        .arg(triage.path())
+        .arg("-o")
+        .arg(out_dir.path())
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn test_all_failed_and_usage_codes() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_corrupt_history(triage.path(), "bob");
+    assert_eq!(scan_code(triage.path()), Some(4));
+
+    let usage = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["scan", "--no-such-flag"])
+        .output()
+        .unwrap();
+    assert_eq!(usage.status.code(), Some(1));
+}
@@ -0,0 +1,252 @@
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+use common::write_history;
+
+#[test]
+fn test_scan_writes_each_requested_format() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_history(triage.path(), "alice");
+    let out_dir = tempfile::TempDir::new().unwrap();
+
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args([
+            "scan",
+            "--artifacts",
+            "history",
+            "--format",
+            "csv,jsonl",
+            "-d",
+        ])
+        .arg(triage.path())
+        .arg("-o")
+        .arg(out_dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    assert!(out_dir
+        .path()
+        .join("Chrome_history_alice_Default.csv")
+        .exists());
+    let jsonl = out_dir.path().join("Chrome_history_alice_Default.jsonl");
+    let text = std::fs::read_to_string(&jsonl).unwrap();
+    let record: serde_json::Value = serde_json::from_str(text.trim()).unwrap();
+    // Keyed by the CSV headers, with the CSV cells as values
+    assert_eq!(record["URL"], "https://example.com/");
+    assert_eq!(record["User Profile"], "alice");
+    assert_eq!(record["Visit Count"], "1");
+    assert!(record["NaturalLanguage"]
+        .as_str()
+        .unwrap()
+        .contains("example.com"));
+}
+
+#[test]
+fn test_tz_writes_local_times_with_abbreviation() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_history(triage.path(), "alice");
+    let out_dir = tempfile::TempDir::new().unwrap();
+
+    // 2020-09-19 17:37:01 UTC is 13:37:01 EDT
+    for (tz, expected) in [
+        (None, "2020-09-19 17:37:01 UTC"),
+        (Some("America/New_York"), "2020-09-19 13:37:01 EDT"),
+    ] {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"));
+        cmd.args(["scan", "--artifacts", "history", "--date-format", "iso"]);
+        if let Some(tz) = tz {
+            cmd.args(["--tz", tz]);
+        }
+        let out = cmd
+            .arg("-d")
+            .arg(triage.path())
+            .arg("-o")
+            .arg(out_dir.path())
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+
+        let csv = out_dir.path().join("Chrome_history_alice_Default.csv");
+        let mut reader = csv::Reader::from_path(&csv).unwrap();
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!(&record[0], expected);
+    }
+}
+
+#[test]
+fn test_cli_flags_override_config_file() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_history(triage.path(), "alice");
+    let config = triage.path().join("webx.toml");
+    std::fs::write(
+        &config,
+        "date_format = \"iso\"\nformat = [\"jsonl\"]\ntz = \"America/New_York\"\nmax_value_len = 3\n",
+    )
+    .unwrap();
+    let run = |flags: &[&str], out_dir: &Path| {
+        let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+            .args(["scan", "--artifacts", "history", "--config"])
+            .arg(&config)
+            .args(flags)
+            .arg("-d")
+            .arg(triage.path())
+            .arg("-o")
+            .arg(out_dir)
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+    };
+
+    // The config file's format, zone and value cap apply when no flag is given
+    let from_config = tempfile::TempDir::new().unwrap();
+    run(&[], from_config.path());
+    let stem = from_config.path().join("Chrome_history_alice_Default");
+    assert!(!stem.with_extension("csv").exists());
+    let text = std::fs::read_to_string(stem.with_extension("jsonl")).unwrap();
+    let record: serde_json::Value = serde_json::from_str(text.trim()).unwrap();
+    assert_eq!(record["Title"], "Exa…[truncated 4 bytes]");
+    assert_eq!(record["Visit Time"], "2020-09-19 13:37:01 EDT");
+
+    // Explicit flags win over every one of them
+    let from_flags = tempfile::TempDir::new().unwrap();
+    run(
+        &["--format", "csv", "--tz", "UTC", "--max-value-len", "0"],
+        from_flags.path(),
+    );
+    let stem = from_flags.path().join("Chrome_history_alice_Default");
+    assert!(!stem.with_extension("jsonl").exists());
+    let mut reader = csv::Reader::from_path(stem.with_extension("csv")).unwrap();
+    let headers = reader.headers().unwrap().clone();
+    let record = reader.records().next().unwrap().unwrap();
+    assert_eq!(&record[0], "2020-09-19 17:37:01 UTC");
+    let title = headers.iter().position(|h| h == "Title").unwrap();
+    assert_eq!(&record[title], "Example");
+
+    // Without --tz the config zone is used
+    let zoned = tempfile::TempDir::new().unwrap();
+    run(&["--format", "csv"], zoned.path());
+    let csv = zoned.path().join("Chrome_history_alice_Default.csv");
+    let mut reader = csv::Reader::from_path(&csv).unwrap();
+    let record = reader.records().next().unwrap().unwrap();
+    assert_eq!(&record[0], "2020-09-19 13:37:01 EDT");
+}
+
+#[test]
+fn test_scan_html_report() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_history(triage.path(), "alice");
+    let out_dir = tempfile::TempDir::new().unwrap();
+    let report = out_dir.path().join("report.html");
+
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["scan", "--artifacts", "history", "-d"])
+        .arg(triage.path())
+        .arg("-o")
+        .arg(out_dir.path())
+        .arg("--html")
+        .arg(&report)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let html = std::fs::read_to_string(&report).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<summary>2020-09-19 (Sat) UTC &mdash; 1 event(s)</summary>"));
+    assert!(html.contains("<h2>History (1 row(s))</h2>"));
+    assert!(html.contains("<td>https://example.com/</td>"));
+    // The CSV is still written alongside
+    assert!(out_dir
+        .path()
+        .join("Chrome_history_alice_Default.csv")
+        .exists());
+}
+
+#[test]
+fn test_scan_timeline_csv() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_history(triage.path(), "alice");
+    let out_dir = tempfile::TempDir::new().unwrap();
+    let timeline = out_dir.path().join("timeline.csv");
+
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["scan", "--date-format", "iso", "-d"])
+        .arg(triage.path())
+        .arg("-o")
+        .arg(out_dir.path())
+        .arg("--timeline")
+        .arg(&timeline)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let mut rdr = csv::Reader::from_path(&timeline).unwrap();
+    assert_eq!(
+        rdr.headers().unwrap(),
+        vec![
+            "Timestamp",
+            "Artifact",
+            "Web Browser",
+            "User Profile",
+            "Description"
+        ]
+    );
+    let rows: Vec<csv::StringRecord> = rdr.records().map(|r| r.unwrap()).collect();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(&rows[0][0], "2020-09-19 17:37:01 UTC");
+    assert_eq!(&rows[0][1], "History");
+    assert_eq!(&rows[0][3], "alice");
+    assert!(rows[0][4].contains("https://example.com/"));
+}
+
+#[test]
+fn test_scan_xlsx_alongside_csv() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_history(triage.path(), "alice");
+    let out_dir = tempfile::TempDir::new().unwrap();
+    let workbook = out_dir.path().join("report.xlsx");
+
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["scan", "--artifacts", "history", "-d"])
+        .arg(triage.path())
+        .arg("-o")
+        .arg(out_dir.path())
+        .arg("--xlsx")
+        .arg(&workbook)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    // CSV output is unchanged; the workbook comes on top of it
+    assert!(out_dir
+        .path()
+        .join("Chrome_history_alice_Default.csv")
+        .exists());
+    assert!(std::fs::read(&workbook).unwrap().starts_with(b"PK\x03\x04"));
+}
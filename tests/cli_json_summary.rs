@@ -0,0 +1,157 @@
+mod common;
+
+use common::{chrome_profile, write_history_db, CHROME_PROFILE};
+use std::path::Path;
+use std::process::Command;
+
+fn write_history(root: &Path, user: &str) {
+    write_history_db(
+        &chrome_profile(root, user).join("History"),
+        "INSERT INTO urls VALUES (1, 'https://example.com/', 'Example', 2, 1, 13245010622000000);
+         INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 1);
+         INSERT INTO visits VALUES (2, 1, 13245010622000000, 0, 1);",
+    );
+}
+
+#[test]
+fn test_scan_json_summary_on_stdout() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_history(triage.path(), "alice");
+    let out_dir = tempfile::TempDir::new().unwrap();
+
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["scan", "--artifacts", "history", "--json-summary", "-d"])
+        .arg(triage.path())
+        .arg("-o")
+        .arg(out_dir.path())
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(0));
+
+    // Logs go to stderr, so stdout is exactly one JSON line
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    let summary: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(summary["command"], "scan");
+    // The History file also backs downloads and keywords, found but filtered out
+    assert!(summary["artifacts_found"].as_u64().unwrap() >= 1);
+    assert_eq!(summary["artifacts_extracted"], 1);
+    assert_eq!(summary["artifacts_failed"], 0);
+    assert_eq!(summary["entries_written"], 2);
+    assert_eq!(summary["entries_by_type"]["History"], 2);
+    assert_eq!(summary["exit_code"], 0);
+    assert!(summary["duration_secs"].as_f64().unwrap() >= 0.0);
+}
+
+#[test]
+fn test_extract_json_summary_requires_output() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_history(triage.path(), "alice");
+    let db = triage
+        .path()
+        .join("Users/alice")
+        .join(CHROME_PROFILE)
+        .join("History");
+    let csv = triage.path().join("history.csv");
+
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["extract", "--json-summary", "-i"])
+        .arg(&db)
+        .arg("-o")
+        .arg(&csv)
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(0));
+    let summary: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(summary["command"], "extract");
+    assert_eq!(summary["entries_written"], 2);
+
+    let no_output = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["extract", "--json-summary", "-i"])
+        .arg(&db)
+        .output()
+        .unwrap();
+    assert_eq!(no_output.status.code(), Some(1));
+}
+
+#[test]
+fn test_scan_reports_encrypted_database() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_history(triage.path(), "alice");
+    // Whole pages without the SQLite header, as SQLCipher writes them
+    let cookies = triage
+        .path()
+        .join("Users/alice")
+        .join(CHROME_PROFILE)
+        .join("Cookies");
+    let noise: Vec<u8> = (0..8192u32).map(|i| (i * 7919 % 251) as u8).collect();
+    std::fs::write(&cookies, noise).unwrap();
+    let out_dir = tempfile::TempDir::new().unwrap();
+
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args([
+            "scan",
+            "--artifacts",
+            "history,cookies",
+            "--json-summary",
+            "-d",
+        ])
+        .arg(triage.path())
+        .arg("-o")
+        .arg(out_dir.path())
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(3));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("encrypted or non-SQLite database, cannot extract"),
+        "{stderr}"
+    );
+
+    let summary: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(summary["artifacts_extracted"], 1);
+    assert_eq!(summary["artifacts_failed"], 1);
+    let encrypted = summary["encrypted_databases"].as_array().unwrap();
+    assert_eq!(encrypted.len(), 1);
+    assert!(encrypted[0].as_str().unwrap().ends_with("Cookies"));
+}
+
+#[test]
+fn test_parallel_scan_counts_match_serial() {
+    let triage = tempfile::TempDir::new().unwrap();
+    for user in ["alice", "bob", "carol"] {
+        write_history(triage.path(), user);
+    }
+    // A broken History counts as one failure whatever the thread count
+    let broken = triage.path().join("Users/dave").join(CHROME_PROFILE);
+    std::fs::create_dir_all(&broken).unwrap();
+    std::fs::write(broken.join("History"), b"not a database").unwrap();
+
+    let summary = |threads: &str| {
+        let out_dir = tempfile::TempDir::new().unwrap();
+        let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+            .args(["scan", "--artifacts", "history", "--json-summary"])
+            .args(["--threads", threads, "-d"])
+            .arg(triage.path())
+            .arg("-o")
+            .arg(out_dir.path())
+            .output()
+            .unwrap();
+        let mut summary: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        summary.as_object_mut().unwrap().remove("duration_secs");
+        let mut files: Vec<_> = std::fs::read_dir(out_dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        files.sort();
+        (summary, files)
+    };
+
+    let (serial, serial_files) = summary("1");
+    assert_eq!(serial["artifacts_extracted"], 3);
+    assert_eq!(serial["artifacts_failed"], 1);
+    assert_eq!(serial["entries_written"], 6);
+    let (parallel, parallel_files) = summary("4");
+    assert_eq!(serial, parallel);
+    assert_eq!(serial_files, parallel_files);
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::{chrome_profile, write_history_db};
+use std::path::Path;
+use std::process::Command;
+
+/// Chrome timestamps (microseconds since 1601) for 2020-09-19 17:37:01, +1 min, +2 min.
+const VISITS: [i64; 3] = [13245010621000000, 13245010681000000, 13245010741000000];
+
+fn write_triage(root: &Path) {
+    let history = chrome_profile(root, "alice").join("History");
+    write_history_db(&history, "");
+    let conn = rusqlite::Connection::open(history).unwrap();
+    for (i, t) in VISITS.iter().enumerate() {
+        let id = i as i64 + 1;
+        conn.execute(
+            "INSERT INTO urls VALUES (?1, ?2, 'Page', 1, 0, ?3)",
+            rusqlite::params![id, format!("https://example.com/{id}"), t],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO visits VALUES (?1, ?1, ?2, 0, 1)",
+            rusqlite::params![id, t],
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_resume_from_emits_only_newer_entries() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_triage(triage.path());
+    let out_dir = tempfile::TempDir::new().unwrap();
+
+    // The second visit's exact time: it was the last row of the previous export
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["scan", "--resume-from", "2020-09-19 17:38:01", "-d"])
+        .arg(triage.path())
+        .arg("-o")
+        .arg(out_dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let csv = std::fs::read_dir(out_dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .find(|p| p.extension().is_some_and(|x| x == "csv"))
+        .unwrap();
+    let mut rdr = csv::Reader::from_path(&csv).unwrap();
+    let url_idx = rdr
+        .headers()
+        .unwrap()
+        .iter()
+        .position(|h| h == "URL")
+        .unwrap();
+    let urls: Vec<String> = rdr
+        .records()
+        .map(|r| r.unwrap()[url_idx].to_string())
+        .collect();
+    assert_eq!(urls, ["https://example.com/3"]);
+}
+
+#[test]
+fn test_extract_keeps_only_the_after_before_window() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_triage(triage.path());
+    let history = triage
+        .path()
+        .join("Users/alice/AppData/Local/Google/Chrome/User Data/Default/History");
+
+    // --after is inclusive and --before exclusive, so only the second visit is kept
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["extract", "--after", "2020-09-19T17:38:01Z"])
+        .args(["--before", "2020-09-19 17:39:01", "-i"])
+        .arg(&history)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("https://example.com/2"), "{stdout}");
+    assert!(!stdout.contains("https://example.com/1"), "{stdout}");
+    assert!(!stdout.contains("https://example.com/3"), "{stdout}");
+
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args([
+            "extract",
+            "--after",
+            "2021-01-01",
+            "--before",
+            "2020-01-01",
+            "-i",
+        ])
+        .arg(&history)
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::{chrome_profile, write_history};
+use std::path::Path;
+use std::process::Command;
+
+/// A triage folder with a real Chrome `History` for alice and, in bob's profile, an
+/// unrelated SQLite cache that happens to be named `History`.
+fn write_triage(root: &Path) {
+    write_history(root, "alice");
+
+    let wrong = chrome_profile(root, "bob");
+    let conn = rusqlite::Connection::open(wrong.join("History")).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE entries (key TEXT PRIMARY KEY, data BLOB);
+         INSERT INTO entries VALUES ('a', x'00');",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_wrong_sqlite_is_skipped_cleanly() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_triage(triage.path());
+    let out_dir = tempfile::TempDir::new().unwrap();
+
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["scan", "--strict", "-d"])
+        .arg(triage.path())
+        .arg("-o")
+        .arg(out_dir.path())
+        .current_dir(triage.path())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    // A skip is not an extraction failure, even under --strict
+    assert!(out.status.success(), "{stderr}");
+
+    let csvs: Vec<String> = std::fs::read_dir(out_dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(csvs.len(), 1, "{csvs:?}");
+    assert!(csvs[0].contains("alice"));
+
+    // Warned once for the database, not once per artifact read from it
+    let warnings: Vec<&str> = stderr.lines().filter(|l| l.contains("Skipping")).collect();
+    assert_eq!(warnings.len(), 1, "{stderr}");
+    assert!(warnings[0].contains("bob"));
+    assert!(warnings[0].contains("without the browser History schema"));
+    assert!(warnings[0].contains("tables: entries"));
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::write_history_db;
+use std::path::Path;
+use std::process::Command;
+
+/// Build a minimal Chrome `History` database with two visits.
+fn write_chrome_history(path: &Path) {
+    write_history_db(
+        path,
+        "INSERT INTO urls VALUES (1, 'https://example.com/', 'Example', 1, 1, 13245010621000000);
+         INSERT INTO urls VALUES (2, 'https://example.org/a', 'A', 1, 0, 13245010622000000);
+         INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 1);
+         INSERT INTO visits VALUES (2, 2, 13245010622000000, 1, 0);",
+    );
+}
+
+#[test]
+fn test_extract_stdout_jsonl() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let db = dir.path().join("History");
+    write_chrome_history(&db);
+
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["extract", "-i"])
+        .arg(&db)
+        .args(["--stdout", "jsonl"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+
+    // Logs go to stderr; stdout holds only the records, one JSON object per line
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let records: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["url"], "https://example.com/");
+    assert_eq!(records[0]["visit_type"], "Typed");
+    assert_eq!(records[1]["title"], "A");
+    assert!(String::from_utf8_lossy(&out.stderr).contains("Extracted 2 history entries"));
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use common::{write_corrupt_history, write_history};
+use std::path::Path;
+use std::process::Command;
+
+/// A triage folder with one readable and one corrupt Chrome `History`.
+fn write_triage(root: &Path) {
+    write_history(root, "alice");
+    write_corrupt_history(root, "bob");
+}
+
+fn scan(root: &Path, out: &Path, strict: bool) -> std::process::Output {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"));
+    cmd.args(["scan", "--artifacts", "history", "-d"])
+        .arg(root)
+        .arg("-o")
+        .arg(out)
+        .current_dir(root);
+    if strict {
+        cmd.arg("--strict");
+    }
+    cmd.output().unwrap()
+}
+
+fn csv_count(dir: &Path) -> usize {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .filter(|e| e.as_ref().unwrap().path().extension() == Some("csv".as_ref()))
+        .count()
+}
+
+#[test]
+fn test_strict_fails_on_corrupt_artifact() {
+    let triage = tempfile::TempDir::new().unwrap();
+    write_triage(triage.path());
+
+    let lenient_out = tempfile::TempDir::new().unwrap();
+    let lenient = scan(triage.path(), lenient_out.path(), false);
+    assert_eq!(lenient.status.code(), Some(3));
+    assert_eq!(csv_count(lenient_out.path()), 1);
+
+    // Same output is written, but the failure is reported as an error
+    let strict_out = tempfile::TempDir::new().unwrap();
+    let strict = scan(triage.path(), strict_out.path(), true);
+    assert_eq!(strict.status.code(), Some(1));
+    assert_eq!(csv_count(strict_out.path()), 1);
+    assert!(String::from_utf8_lossy(&strict.stderr).contains("1 artifact(s) failed"));
+}
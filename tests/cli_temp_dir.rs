@@ -0,0 +1,73 @@
+mod common;
+
+use common::{write_history_db, ONE_VISIT};
+use std::path::Path;
+use std::process::Command;
+
+/// Run `extract` with copies made under `temp_dir` and any extra `flags`; returns the
+/// copied files left behind.
+fn extract_with_temp_dir(history: &Path, temp_dir: &Path, flags: &[&str]) -> Vec<String> {
+    let csv = history.with_file_name("history.csv");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"));
+    cmd.args(["extract", "-i"])
+        .arg(history)
+        .arg("-o")
+        .arg(&csv)
+        .arg("--temp-dir")
+        .arg(temp_dir)
+        .args(flags);
+    let out = cmd.output().unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    walkdir::WalkDir::new(temp_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect()
+}
+
+#[test]
+fn test_preserve_temp_keeps_copy() {
+    let evidence = tempfile::TempDir::new().unwrap();
+    let history = evidence.path().join("History");
+    write_history_db(&history, ONE_VISIT);
+
+    // Copies land in --temp-dir (created if missing) and are removed afterwards...
+    let scratch = tempfile::TempDir::new().unwrap();
+    let temp_dir = scratch.path().join("webx-tmp");
+    assert!(extract_with_temp_dir(&history, &temp_dir, &[]).is_empty());
+    assert!(temp_dir.is_dir());
+
+    // ...unless --preserve-temp is set
+    let kept = extract_with_temp_dir(&history, &temp_dir, &["--preserve-temp"]);
+    assert_eq!(kept, vec!["History".to_string()]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_no_copy_reads_in_place() {
+    let evidence = tempfile::TempDir::new().unwrap();
+    let history = evidence.path().join("History");
+    write_history_db(&history, ONE_VISIT);
+
+    let scratch = tempfile::TempDir::new().unwrap();
+    let flags = ["--no-copy", "--preserve-temp"];
+    assert!(extract_with_temp_dir(&history, scratch.path(), &flags).is_empty());
+    let csv = std::fs::read_to_string(evidence.path().join("history.csv")).unwrap();
+    assert!(csv.contains("https://example.com/"));
+    // Nothing was written next to the evidence either
+    assert!(!evidence.path().join("History-journal").exists());
+    assert!(!evidence.path().join("History-wal").exists());
+
+    // An empty -wal is ignored, but a -journal may hold changes an in-place read
+    // would miss, so the database is copied
+    std::fs::write(evidence.path().join("History-wal"), b"").unwrap();
+    std::fs::write(evidence.path().join("History-journal"), b"hot").unwrap();
+    let kept = extract_with_temp_dir(&history, scratch.path(), &flags);
+    assert!(kept.contains(&"History".to_string()));
+}
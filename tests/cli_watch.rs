@@ -0,0 +1,49 @@
+mod common;
+
+use common::write_history_db;
+use std::process::Command;
+
+#[test]
+fn test_watch_tail_prints_jsonl() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let history = dir.path().join("History");
+    write_history_db(
+        &history,
+        "INSERT INTO urls VALUES (1, 'https://old.example/', 'Old', 1, 0, 13245010621000000);
+         INSERT INTO urls VALUES (2, 'https://new.example/', 'New', 1, 0, 13245010622000000);
+         INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 1);
+         INSERT INTO visits VALUES (2, 2, 13245010622000000, 0, 1);",
+    );
+
+    let out = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args([
+            "watch",
+            "--tail",
+            "1",
+            "--max-polls",
+            "2",
+            "--interval",
+            "1s",
+            "-i",
+        ])
+        .arg(&history)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    // Only the newest existing visit; the second poll finds nothing new
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    let entry: serde_json::Value = serde_json::from_str(stdout.trim()).unwrap();
+    assert_eq!(entry["url"], "https://new.example/");
+
+    let zero = Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["watch", "--interval", "0s", "-i"])
+        .arg(&history)
+        .output()
+        .unwrap();
+    assert_eq!(zero.status.code(), Some(1));
+}
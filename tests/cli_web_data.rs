@@ -0,0 +1,69 @@
+use std::path::Path;
+use std::process::Command;
+
+fn write_web_data(path: &Path) {
+    let conn = rusqlite::Connection::open(path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE autofill (name VARCHAR, value VARCHAR, value_lower VARCHAR,
+                                date_created INTEGER DEFAULT 0, date_last_used INTEGER DEFAULT 0,
+                                count INTEGER DEFAULT 1, PRIMARY KEY (name, value));
+         INSERT INTO autofill VALUES ('email', 'jdoe@example.com', 'jdoe@example.com',
+                                      1705305600, 1705392000, 3);
+         INSERT INTO autofill VALUES ('q', 'how to wipe a laptop', 'how to wipe a laptop',
+                                      1705309200, 1705309200, 1);",
+    )
+    .unwrap();
+}
+
+fn extract(args: &[&str], input: &Path) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_forensic-webhistory"))
+        .args(["extract", "-i"])
+        .arg(input)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_extract_web_data_autofill() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let web_data = dir.path().join("Web Data");
+    write_web_data(&web_data);
+    let csv = dir.path().join("autofill.csv");
+    let csv_arg = csv.to_str().unwrap();
+
+    // Auto-detected from the file name, and with --artifacts autofill
+    for args in [
+        vec!["-o", csv_arg],
+        vec!["--artifacts", "autofill", "-o", csv_arg],
+    ] {
+        let out = extract(&args, &web_data);
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        let text = std::fs::read_to_string(&csv).unwrap();
+        let mut lines = text.lines();
+        assert!(lines.next().unwrap().contains("Field Name"), "{text}");
+        assert_eq!(lines.count(), 2);
+        assert!(text.contains("how to wipe a laptop"));
+    }
+
+    // A copy under another name is read with --browser webdata
+    let renamed = dir.path().join("evidence_0042.db");
+    std::fs::copy(&web_data, &renamed).unwrap();
+    let out = extract(&["--browser", "webdata", "--stdout", "jsonl"], &renamed);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 2);
+    assert!(stdout.contains("jdoe@example.com"));
+
+    // Other artifacts are read by scan, not extract
+    let out = extract(&["--artifacts", "downloads", "-o", csv_arg], &web_data);
+    assert_eq!(out.status.code(), Some(1));
+}
@@ -0,0 +1,49 @@
+//! Fixtures shared by the CLI tests.
+
+// Each test binary compiles this module and uses only part of it
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+/// A Chrome profile directory under a user's home.
+pub const CHROME_PROFILE: &str = "AppData/Local/Google/Chrome/User Data/Default";
+
+/// Chrome timestamp (microseconds since 1601) for 2020-09-19 17:37:01 UTC.
+pub const VISIT_TIME: i64 = 13245010621000000;
+
+/// One typed visit to `https://example.com/` at [`VISIT_TIME`].
+pub const ONE_VISIT: &str =
+    "INSERT INTO urls VALUES (1, 'https://example.com/', 'Example', 1, 1, 13245010621000000);
+     INSERT INTO visits VALUES (1, 1, 13245010621000000, 0, 1);";
+
+/// Create a Chrome `History` database at `path` with the `urls` and `visits` tables,
+/// then run `inserts` against it.
+pub fn write_history_db(path: &Path, inserts: &str) {
+    let conn = rusqlite::Connection::open(path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                            visit_count INTEGER, typed_count INTEGER, last_visit_time INTEGER);
+         CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                              from_visit INTEGER, transition INTEGER);",
+    )
+    .unwrap();
+    conn.execute_batch(inserts).unwrap();
+}
+
+/// `user`'s Chrome Default profile under `root`, created if missing.
+pub fn chrome_profile(root: &Path, user: &str) -> PathBuf {
+    let profile = root.join("Users").join(user).join(CHROME_PROFILE);
+    std::fs::create_dir_all(&profile).unwrap();
+    profile
+}
+
+/// A triage folder with `user`'s Chrome `History` holding [`ONE_VISIT`].
+pub fn write_history(root: &Path, user: &str) {
+    write_history_db(&chrome_profile(root, user).join("History"), ONE_VISIT);
+}
+
+/// A `History` in `user`'s Chrome profile that is not a SQLite database.
+pub fn write_corrupt_history(root: &Path, user: &str) {
+    let profile = chrome_profile(root, user);
+    std::fs::write(profile.join("History"), b"not a sqlite database at all").unwrap();
+}